@@ -0,0 +1,99 @@
+//! Parsing of `OrderBook` resources of increasing depth, shaped like the API returns them.
+
+use criterion::{
+    black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput,
+};
+use laminar_sdk::types::order::OrderBook;
+use serde_json::{json, Value};
+
+const OWNER: &str = "0x2d1d2c4cd5e8d5a30a1c8c4c8d0b6e0a9c0ff8ab0ee38c7d5f4a9b7e7e3a1f10";
+const NONE: u64 = u64::MAX;
+
+fn order_json(creation_num: u64, side: u8, price: u64) -> Value {
+    json!({
+        "id": { "creation_num": creation_num.to_string(), "addr": OWNER },
+        "side": side,
+        "price": price.to_string(),
+        "size": "1000",
+        "post_only": false,
+        "remaining_size": "750",
+    })
+}
+
+fn side_json(side: u8, levels: u64, orders_per_level: u64) -> Value {
+    let nodes = (0..levels)
+        .map(|level| {
+            let price = 10_000 + level;
+            let queue = (0..orders_per_level)
+                .map(|i| {
+                    let next = if i + 1 < orders_per_level {
+                        i + 1
+                    } else {
+                        NONE
+                    };
+                    let order = order_json(level * orders_per_level + i, side, price);
+                    json!({
+                        "next": { "value": next.to_string() },
+                        "value": { "vec": [order] },
+                    })
+                })
+                .collect::<Vec<_>>();
+            json!({
+                "key": price.to_string(),
+                "left": { "value": NONE.to_string() },
+                "right": { "value": NONE.to_string() },
+                "value": { "head": { "value": "0" }, "nodes": queue },
+            })
+        })
+        .collect::<Vec<_>>();
+
+    json!({
+        "max": { "value": NONE.to_string() },
+        "min": { "value": NONE.to_string() },
+        "nodes": nodes,
+        "removed_nodes": [],
+        "root": { "value": NONE.to_string() },
+        "single_splay": true,
+    })
+}
+
+fn book_json(levels: u64, orders_per_level: u64) -> Value {
+    json!({
+        "id": { "creation_num": "3", "addr": OWNER },
+        "instrument": {
+            "owner": OWNER,
+            "price_decimals": 2,
+            "size_decimals": 3,
+            "min_size_amount": "1",
+            "base_decimals": 8,
+            "quote_decimals": 6,
+        },
+        "asks": side_json(1, levels, orders_per_level),
+        "signer_addr": OWNER,
+    })
+}
+
+fn parse_orderbook(c: &mut Criterion) {
+    let mut group = c.benchmark_group("orderbook");
+    for (levels, orders_per_level) in [(10, 5), (100, 10), (500, 10), (1_000, 20)] {
+        let value = book_json(levels, orders_per_level);
+        let text = value.to_string();
+        let size = format!("{}x{}", levels, orders_per_level);
+        group.throughput(Throughput::Elements(levels * orders_per_level));
+
+        group.bench_with_input(BenchmarkId::new("from_value", &size), &value, |b, value| {
+            b.iter_batched(
+                || value.clone(),
+                |value| serde_json::from_value::<OrderBook>(value).unwrap(),
+                BatchSize::LargeInput,
+            )
+        });
+        group.bench_with_input(BenchmarkId::new("from_str", &size), &text, |b, text| {
+            b.iter(|| serde_json::from_str::<OrderBook>(black_box(text)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, parse_orderbook);
+criterion_main!(benches);
@@ -0,0 +1,81 @@
+/* C interface of laminar-sdk, built with the `ffi` feature. See src/ffi.rs for details. */
+
+#ifndef LAMINAR_H
+#define LAMINAR_H
+
+#include <stdbool.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define LAMINAR_OK 0
+#define LAMINAR_INVALID_ARGUMENT -1
+#define LAMINAR_NOT_FOUND -2
+#define LAMINAR_REJECTED -3
+#define LAMINAR_ERROR -4
+
+#define LAMINAR_SIDE_BID 0
+#define LAMINAR_SIDE_ASK 1
+
+#define LAMINAR_GOOD_TILL_CANCELED 0
+#define LAMINAR_IMMEDIATE_OR_CANCEL 1
+#define LAMINAR_FILL_OR_KILL 2
+
+typedef struct LaminarHandle LaminarHandle;
+typedef struct LaminarSubscription LaminarSubscription;
+
+typedef void (*LaminarEventCallback)(const char *event_json, void *user_data);
+
+const char *laminar_last_error(void);
+
+void laminar_string_free(char *s);
+
+int laminar_client_connect(const char *node_url,
+                           const char *laminar_address,
+                           const char *account_address,
+                           const char *account_private_key,
+                           LaminarHandle **out);
+
+void laminar_client_free(LaminarHandle *handle);
+
+int laminar_place_limit_order(const LaminarHandle *handle,
+                              const char *book_id,
+                              uint8_t side,
+                              uint64_t price,
+                              uint64_t size,
+                              uint8_t time_in_force,
+                              bool post_only,
+                              char **out);
+
+int laminar_place_market_order(const LaminarHandle *handle,
+                               const char *book_id,
+                               uint8_t side,
+                               uint64_t size,
+                               char **out);
+
+int laminar_cancel_order(const LaminarHandle *handle,
+                         const char *book_id,
+                         const char *order_id,
+                         uint8_t side);
+
+int laminar_fetch_orderbook(const LaminarHandle *handle,
+                            const char *book_id,
+                            uint32_t depth,
+                            char **out);
+
+int laminar_subscribe(const LaminarHandle *handle,
+                      const char *book_id,
+                      uint64_t interval_ms,
+                      LaminarEventCallback callback,
+                      void *user_data,
+                      LaminarSubscription **out);
+
+void laminar_unsubscribe(LaminarSubscription *subscription);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* LAMINAR_H */
@@ -0,0 +1,86 @@
+-- Laminar events and the orders reconstructed from them. Ids are stored as `0xaddr:num`,
+-- sides, time in force and states as their discriminants.
+
+CREATE TABLE order_books (
+    book_id TEXT PRIMARY KEY,
+    creator TEXT NOT NULL,
+    base TEXT NOT NULL,
+    quote TEXT NOT NULL,
+    price_decimals SMALLINT NOT NULL,
+    size_decimals SMALLINT NOT NULL,
+    min_size_amount BIGINT NOT NULL,
+    base_decimals SMALLINT NOT NULL,
+    quote_decimals SMALLINT NOT NULL,
+    time BIGINT NOT NULL
+);
+
+CREATE TABLE place_order_events (
+    order_id TEXT PRIMARY KEY,
+    book_id TEXT NOT NULL,
+    side SMALLINT NOT NULL,
+    price BIGINT NOT NULL,
+    size BIGINT NOT NULL,
+    time_in_force SMALLINT NOT NULL,
+    post_only BOOLEAN NOT NULL,
+    time BIGINT NOT NULL
+);
+
+CREATE INDEX place_order_events_book_time ON place_order_events (book_id, time);
+
+CREATE TABLE amend_order_events (
+    amend_id TEXT PRIMARY KEY,
+    book_id TEXT NOT NULL,
+    order_id TEXT NOT NULL,
+    side SMALLINT NOT NULL,
+    price BIGINT NOT NULL,
+    size BIGINT NOT NULL,
+    time BIGINT NOT NULL
+);
+
+CREATE INDEX amend_order_events_order ON amend_order_events (order_id);
+CREATE INDEX amend_order_events_book_time ON amend_order_events (book_id, time);
+
+CREATE TABLE cancel_order_events (
+    cancel_id TEXT PRIMARY KEY,
+    book_id TEXT NOT NULL,
+    order_id TEXT NOT NULL,
+    side SMALLINT NOT NULL,
+    reason SMALLINT NOT NULL,
+    time BIGINT NOT NULL
+);
+
+CREATE INDEX cancel_order_events_order ON cancel_order_events (order_id);
+CREATE INDEX cancel_order_events_book_time ON cancel_order_events (book_id, time);
+
+-- fills have no id, an order is filled at most once per transaction and remaining size
+CREATE TABLE fill_events (
+    book_id TEXT NOT NULL,
+    order_id TEXT NOT NULL,
+    side SMALLINT NOT NULL,
+    price BIGINT NOT NULL,
+    fill_size BIGINT NOT NULL,
+    fee BIGINT NOT NULL,
+    fee_rate BIGINT NOT NULL,
+    time BIGINT NOT NULL,
+    remaining_size BIGINT NOT NULL,
+    is_maker BOOLEAN NOT NULL,
+    PRIMARY KEY (order_id, time, remaining_size)
+);
+
+CREATE INDEX fill_events_book_time ON fill_events (book_id, time);
+
+CREATE TABLE orders (
+    order_id TEXT PRIMARY KEY,
+    book_id TEXT NOT NULL,
+    side SMALLINT NOT NULL,
+    price BIGINT NOT NULL,
+    size BIGINT NOT NULL,
+    remaining_size BIGINT NOT NULL,
+    post_only BOOLEAN NOT NULL,
+    time_in_force SMALLINT NOT NULL,
+    state SMALLINT NOT NULL,
+    created_at BIGINT NOT NULL,
+    updated_at BIGINT NOT NULL
+);
+
+CREATE INDEX orders_book_state ON orders (book_id, state);
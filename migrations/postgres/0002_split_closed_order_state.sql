@@ -0,0 +1,7 @@
+-- The closed state (2) is now the filled state, cancelled orders get their own states:
+-- 3 for GTC orders, which only their owner cancels, 4 for IOC and FOK orders, whose unfilled
+-- size the book cancelled.
+
+UPDATE orders SET state = CASE WHEN time_in_force = 0 THEN 3 ELSE 4 END
+    WHERE state = 2
+        AND EXISTS (SELECT 1 FROM cancel_order_events c WHERE c.order_id = orders.order_id);
@@ -0,0 +1,206 @@
+use crate::types::events::FillEvent;
+use crate::types::order::{Id, Side};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use futures::channel::mpsc::UnboundedSender;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap, VecDeque};
+
+/// Unusual market activity detected on the fill stream.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Alert {
+    /// Volume in the current window exceeds the baseline average by the configured ratio.
+    VolumeSpike {
+        book_id: Id,
+        window_start: u64,
+        volume: u64,
+        baseline: u64,
+    },
+    /// Consecutive fill prices differ by more than the configured threshold.
+    PriceGap {
+        book_id: Id,
+        time: u64,
+        previous_price: u64,
+        price: u64,
+        gap_bps: u64,
+    },
+    /// A single aggressor swept several price levels on one side at the same time.
+    OneSidedSweep {
+        book_id: Id,
+        time: u64,
+        side: Side,
+        levels: usize,
+        size: u64,
+    },
+}
+
+/// Destination for `Alert`s.
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    async fn notify(&self, alert: &Alert) -> Result<()>;
+}
+
+#[async_trait]
+impl Notifier for UnboundedSender<Alert> {
+    async fn notify(&self, alert: &Alert) -> Result<()> {
+        self.unbounded_send(alert.clone())
+            .map_err(|_| anyhow!("alert receiver dropped"))
+    }
+}
+
+/// Thresholds used by the `AnomalyDetector`. Times are in the same unit as `FillEvent::time`.
+#[derive(Clone, Debug)]
+pub struct AnomalyConfig {
+    pub window: u64,
+    pub baseline_windows: usize,
+    pub volume_spike_ratio: u64,
+    pub price_gap_bps: u64,
+    pub sweep_min_levels: usize,
+}
+
+impl Default for AnomalyConfig {
+    fn default() -> Self {
+        Self {
+            window: 60_000_000,
+            baseline_windows: 30,
+            volume_spike_ratio: 5,
+            price_gap_bps: 200,
+            sweep_min_levels: 3,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Sweep {
+    time: u64,
+    side: Option<Side>,
+    prices: BTreeSet<u64>,
+    size: u64,
+    alerted: bool,
+}
+
+#[derive(Debug, Default)]
+struct BookState {
+    windows: VecDeque<(u64, u64)>,
+    spike_alerted: Option<u64>,
+    last_price: Option<u64>,
+    sweep: Sweep,
+}
+
+/// Detects volume spikes, price gaps and one-sided sweeps on a stream of `FillEvent`s.
+/// Fills are expected in time order per book.
+#[derive(Debug, Default)]
+pub struct AnomalyDetector {
+    config: AnomalyConfig,
+    books: HashMap<Id, BookState>,
+}
+
+impl AnomalyDetector {
+    pub fn new(config: AnomalyConfig) -> Self {
+        Self {
+            config,
+            books: HashMap::new(),
+        }
+    }
+
+    pub fn config(&self) -> &AnomalyConfig {
+        &self.config
+    }
+
+    /// Feed a fill into the detector, returning any alerts it triggers.
+    pub fn observe(&mut self, fill: &FillEvent) -> Vec<Alert> {
+        let config = &self.config;
+        let state = self.books.entry(fill.book_id.clone()).or_default();
+        let mut alerts = vec![];
+
+        // maker and taker fills describe the same trade, count volume once from the taker side
+        if !fill.is_maker {
+            let window_start = fill.time - fill.time % config.window.max(1);
+            match state.windows.back_mut() {
+                Some((start, volume)) if *start == window_start => *volume += fill.fill_size,
+                _ => {
+                    state.windows.push_back((window_start, fill.fill_size));
+                    while state.windows.len() > config.baseline_windows + 1 {
+                        state.windows.pop_front();
+                    }
+                }
+            }
+
+            let (current_start, current_volume) = *state.windows.back().unwrap_or(&(0, 0));
+            let previous = state.windows.len() - 1;
+            if previous > 0 && state.spike_alerted != Some(current_start) {
+                let total = state
+                    .windows
+                    .iter()
+                    .take(previous)
+                    .map(|(_, v)| *v as u128)
+                    .sum::<u128>();
+                let baseline = (total / previous as u128) as u64;
+                if baseline > 0 && current_volume / baseline >= config.volume_spike_ratio {
+                    state.spike_alerted = Some(current_start);
+                    alerts.push(Alert::VolumeSpike {
+                        book_id: fill.book_id.clone(),
+                        window_start: current_start,
+                        volume: current_volume,
+                        baseline,
+                    });
+                }
+            }
+
+            let sweep = &mut state.sweep;
+            if sweep.time != fill.time || sweep.side != Some(fill.side) {
+                *sweep = Sweep {
+                    time: fill.time,
+                    side: Some(fill.side),
+                    ..Sweep::default()
+                };
+            }
+            sweep.prices.insert(fill.price);
+            sweep.size += fill.fill_size;
+            if !sweep.alerted && sweep.prices.len() >= config.sweep_min_levels {
+                sweep.alerted = true;
+                alerts.push(Alert::OneSidedSweep {
+                    book_id: fill.book_id.clone(),
+                    time: fill.time,
+                    side: fill.side,
+                    levels: sweep.prices.len(),
+                    size: sweep.size,
+                });
+            }
+        }
+
+        if let Some(previous_price) = state.last_price {
+            if previous_price > 0 {
+                let gap = previous_price.abs_diff(fill.price) as u128 * 10_000;
+                let gap_bps = (gap / previous_price as u128) as u64;
+                if gap_bps > config.price_gap_bps {
+                    alerts.push(Alert::PriceGap {
+                        book_id: fill.book_id.clone(),
+                        time: fill.time,
+                        previous_price,
+                        price: fill.price,
+                        gap_bps,
+                    });
+                }
+            }
+        }
+        state.last_price = Some(fill.price);
+
+        alerts
+    }
+
+    /// Feed a fill into the detector and send triggered alerts to `notifier`.
+    pub async fn observe_and_notify(
+        &mut self,
+        fill: &FillEvent,
+        notifier: &dyn Notifier,
+    ) -> Result<Vec<Alert>> {
+        let alerts = self.observe(fill);
+        for a in &alerts {
+            notifier.notify(a).await?;
+        }
+
+        Ok(alerts)
+    }
+}
@@ -0,0 +1,334 @@
+//! Arrow and Parquet export of events and candles, to load Laminar history into dataframe
+//! libraries and query engines without a custom schema mapping.
+//!
+//! Each record type has its own schema. Ids are written as `{address}:{creation_number}`
+//! strings, sides and times in force by name, times as UTC microsecond timestamps and prices
+//! and sizes in the units of the book.
+
+use crate::candles::Candle;
+use crate::error::Result;
+use crate::types::events::{
+    AmendOrderEvent, CancelOrderEvent, CreateOrderBookEvent, FillEvent, LaminarEventEnvelope,
+    PlaceOrderEvent,
+};
+use anyhow::Context;
+use arrow::array::{
+    ArrayRef, BooleanArray, StringArray, TimestampMicrosecondArray, UInt64Array, UInt8Array,
+};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use arrow::record_batch::RecordBatch;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use std::fs::File;
+use std::io::Write;
+use std::marker::PhantomData;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Record with a fixed Arrow schema.
+pub trait ArrowRecord: Sized {
+    /// Fields of the schema, in the order of the arrays returned by `columns`.
+    fn fields() -> Vec<Field>;
+
+    fn columns(records: &[&Self]) -> Vec<ArrayRef>;
+}
+
+pub fn schema<T: ArrowRecord>() -> SchemaRef {
+    Arc::new(Schema::new(T::fields()))
+}
+
+/// Convert records into a single `RecordBatch`.
+pub fn to_record_batch<T: ArrowRecord>(records: &[T]) -> Result<RecordBatch> {
+    let records = records.iter().collect::<Vec<_>>();
+    let batch = RecordBatch::try_new(schema::<T>(), T::columns(&records))
+        .context("failed building record batch")?;
+    Ok(batch)
+}
+
+fn timestamp_field(name: &str) -> Field {
+    Field::new(
+        name,
+        DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".to_string())),
+        false,
+    )
+}
+
+fn strings<T>(records: &[&T], f: impl Fn(&T) -> String) -> ArrayRef {
+    Arc::new(StringArray::from(
+        records.iter().map(|r| f(r)).collect::<Vec<_>>(),
+    ))
+}
+
+fn u64s<T>(records: &[&T], f: impl Fn(&T) -> u64) -> ArrayRef {
+    Arc::new(UInt64Array::from(
+        records.iter().map(|r| f(r)).collect::<Vec<_>>(),
+    ))
+}
+
+fn u8s<T>(records: &[&T], f: impl Fn(&T) -> u8) -> ArrayRef {
+    Arc::new(UInt8Array::from(
+        records.iter().map(|r| f(r)).collect::<Vec<_>>(),
+    ))
+}
+
+fn bools<T>(records: &[&T], f: impl Fn(&T) -> bool) -> ArrayRef {
+    Arc::new(BooleanArray::from(
+        records.iter().map(|r| f(r)).collect::<Vec<_>>(),
+    ))
+}
+
+fn timestamps<T>(records: &[&T], f: impl Fn(&T) -> u64) -> ArrayRef {
+    let micros = records.iter().map(|r| f(r) as i64).collect::<Vec<_>>();
+    Arc::new(TimestampMicrosecondArray::from(micros).with_timezone("UTC".to_string()))
+}
+
+impl ArrowRecord for CreateOrderBookEvent {
+    fn fields() -> Vec<Field> {
+        vec![
+            Field::new("book_id", DataType::Utf8, false),
+            Field::new("creator", DataType::Utf8, false),
+            Field::new("base", DataType::Utf8, false),
+            Field::new("quote", DataType::Utf8, false),
+            Field::new("price_decimals", DataType::UInt8, false),
+            Field::new("size_decimals", DataType::UInt8, false),
+            Field::new("min_size_amount", DataType::UInt64, false),
+            Field::new("base_decimals", DataType::UInt8, false),
+            Field::new("quote_decimals", DataType::UInt8, false),
+            timestamp_field("time"),
+        ]
+    }
+
+    fn columns(records: &[&Self]) -> Vec<ArrayRef> {
+        vec![
+            strings(records, |e| e.book_id.to_string()),
+            strings(records, |e| e.creator.to_hex_literal()),
+            strings(records, |e| e.base.to_string()),
+            strings(records, |e| e.quote.to_string()),
+            u8s(records, |e| e.price_decimals),
+            u8s(records, |e| e.size_decimals),
+            u64s(records, |e| e.min_size_amount),
+            u8s(records, |e| e.base_decimals),
+            u8s(records, |e| e.quote_decimals),
+            timestamps(records, |e| e.time),
+        ]
+    }
+}
+
+impl ArrowRecord for PlaceOrderEvent {
+    fn fields() -> Vec<Field> {
+        vec![
+            Field::new("book_id", DataType::Utf8, false),
+            Field::new("order_id", DataType::Utf8, false),
+            Field::new("side", DataType::Utf8, false),
+            Field::new("price", DataType::UInt64, false),
+            Field::new("size", DataType::UInt64, false),
+            Field::new("time_in_force", DataType::Utf8, false),
+            Field::new("post_only", DataType::Boolean, false),
+            timestamp_field("time"),
+        ]
+    }
+
+    fn columns(records: &[&Self]) -> Vec<ArrayRef> {
+        vec![
+            strings(records, |e| e.book_id.to_string()),
+            strings(records, |e| e.order_id.to_string()),
+            strings(records, |e| format!("{:?}", e.side)),
+            u64s(records, |e| e.price),
+            u64s(records, |e| e.size),
+            strings(records, |e| format!("{:?}", e.time_in_force)),
+            bools(records, |e| e.post_only),
+            timestamps(records, |e| e.time),
+        ]
+    }
+}
+
+impl ArrowRecord for AmendOrderEvent {
+    fn fields() -> Vec<Field> {
+        vec![
+            Field::new("book_id", DataType::Utf8, false),
+            Field::new("order_id", DataType::Utf8, false),
+            Field::new("amend_id", DataType::Utf8, false),
+            Field::new("side", DataType::Utf8, false),
+            Field::new("price", DataType::UInt64, false),
+            Field::new("size", DataType::UInt64, false),
+            timestamp_field("time"),
+        ]
+    }
+
+    fn columns(records: &[&Self]) -> Vec<ArrayRef> {
+        vec![
+            strings(records, |e| e.book_id.to_string()),
+            strings(records, |e| e.order_id.to_string()),
+            strings(records, |e| e.amend_id.to_string()),
+            strings(records, |e| format!("{:?}", e.side)),
+            u64s(records, |e| e.price),
+            u64s(records, |e| e.size),
+            timestamps(records, |e| e.time),
+        ]
+    }
+}
+
+impl ArrowRecord for CancelOrderEvent {
+    fn fields() -> Vec<Field> {
+        vec![
+            Field::new("book_id", DataType::Utf8, false),
+            Field::new("order_id", DataType::Utf8, false),
+            Field::new("cancel_id", DataType::Utf8, false),
+            Field::new("side", DataType::Utf8, false),
+            Field::new("reason", DataType::UInt8, false),
+            timestamp_field("time"),
+        ]
+    }
+
+    fn columns(records: &[&Self]) -> Vec<ArrayRef> {
+        vec![
+            strings(records, |e| e.book_id.to_string()),
+            strings(records, |e| e.order_id.to_string()),
+            strings(records, |e| e.cancel_id.to_string()),
+            strings(records, |e| format!("{:?}", e.side)),
+            u8s(records, |e| e.reason),
+            timestamps(records, |e| e.time),
+        ]
+    }
+}
+
+impl ArrowRecord for FillEvent {
+    fn fields() -> Vec<Field> {
+        vec![
+            Field::new("book_id", DataType::Utf8, false),
+            Field::new("order_id", DataType::Utf8, false),
+            Field::new("side", DataType::Utf8, false),
+            Field::new("price", DataType::UInt64, false),
+            Field::new("fill_size", DataType::UInt64, false),
+            Field::new("fee", DataType::UInt64, false),
+            Field::new("fee_rate", DataType::UInt64, false),
+            timestamp_field("time"),
+            Field::new("remaining_size", DataType::UInt64, false),
+            Field::new("is_maker", DataType::Boolean, false),
+        ]
+    }
+
+    fn columns(records: &[&Self]) -> Vec<ArrayRef> {
+        vec![
+            strings(records, |e| e.book_id.to_string()),
+            strings(records, |e| e.order_id.to_string()),
+            strings(records, |e| format!("{:?}", e.side)),
+            u64s(records, |e| e.price),
+            u64s(records, |e| e.fill_size),
+            u64s(records, |e| e.fee),
+            u64s(records, |e| e.fee_rate),
+            timestamps(records, |e| e.time),
+            u64s(records, |e| e.remaining_size),
+            bools(records, |e| e.is_maker),
+        ]
+    }
+}
+
+impl ArrowRecord for Candle {
+    fn fields() -> Vec<Field> {
+        vec![
+            Field::new("book_id", DataType::Utf8, false),
+            timestamp_field("start"),
+            Field::new("open", DataType::UInt64, false),
+            Field::new("high", DataType::UInt64, false),
+            Field::new("low", DataType::UInt64, false),
+            Field::new("close", DataType::UInt64, false),
+            Field::new("volume", DataType::UInt64, false),
+            Field::new("trades", DataType::UInt64, false),
+        ]
+    }
+
+    fn columns(records: &[&Self]) -> Vec<ArrayRef> {
+        vec![
+            strings(records, |c| c.book_id.to_string()),
+            timestamps(records, |c| c.start),
+            u64s(records, |c| c.open),
+            u64s(records, |c| c.high),
+            u64s(records, |c| c.low),
+            u64s(records, |c| c.close),
+            u64s(records, |c| c.volume),
+            u64s(records, |c| c.trades),
+        ]
+    }
+}
+
+/// Events with their ledger version and sequence number as the first two columns.
+impl<E: ArrowRecord> ArrowRecord for LaminarEventEnvelope<E> {
+    fn fields() -> Vec<Field> {
+        let mut fields = vec![
+            Field::new("version", DataType::UInt64, false),
+            Field::new("sequence_number", DataType::UInt64, false),
+        ];
+        fields.extend(E::fields());
+        fields
+    }
+
+    fn columns(records: &[&Self]) -> Vec<ArrayRef> {
+        let events = records.iter().map(|r| &r.event).collect::<Vec<_>>();
+        let mut columns = vec![
+            u64s(records, |r| r.version),
+            u64s(records, |r| r.sequence_number),
+        ];
+        columns.extend(E::columns(&events));
+        columns
+    }
+}
+
+/// Writes records of one type to a Parquet file, one row group per call to `write`.
+pub struct ParquetWriter<W: Write, T> {
+    writer: ArrowWriter<W>,
+    _record: PhantomData<T>,
+}
+
+impl<T: ArrowRecord> ParquetWriter<File, T> {
+    /// Create or truncate the file at `path`.
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let file =
+            File::create(path).with_context(|| format!("failed creating {}", path.display()))?;
+        Self::new(file)
+    }
+}
+
+impl<W: Write, T: ArrowRecord> ParquetWriter<W, T> {
+    /// Write Snappy compressed Parquet to `writer`.
+    pub fn new(writer: W) -> Result<Self> {
+        let props = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build();
+        let writer = ArrowWriter::try_new(writer, schema::<T>(), Some(props))
+            .context("failed creating parquet writer")?;
+
+        Ok(Self {
+            writer,
+            _record: PhantomData,
+        })
+    }
+
+    pub fn write(&mut self, records: &[T]) -> Result<()> {
+        if records.is_empty() {
+            return Ok(());
+        }
+        let batch = to_record_batch(records)?;
+        self.writer
+            .write(&batch)
+            .context("failed writing parquet row group")?;
+        Ok(())
+    }
+
+    /// Write the file footer. The file is unreadable until it is closed.
+    pub fn close(self) -> Result<()> {
+        self.writer
+            .close()
+            .context("failed closing parquet writer")?;
+        Ok(())
+    }
+}
+
+/// Write `records` to a new Parquet file at `path`.
+pub fn write_parquet<T: ArrowRecord, P: AsRef<Path>>(path: P, records: &[T]) -> Result<()> {
+    let mut writer = ParquetWriter::create(path)?;
+    writer.write(records)?;
+    writer.close()
+}
@@ -0,0 +1,129 @@
+use crate::types::events::{FillEvent, LaminarEvent};
+use crate::types::order::Id;
+use crate::LaminarTransaction;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+
+/// Record of a single submitted transaction and the operator that submitted it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub operator: String,
+    pub hash: String,
+    pub sequence_number: u64,
+    pub timestamp: u64,
+    pub success: bool,
+    pub order_ids: Vec<Id>,
+}
+
+impl AuditRecord {
+    pub fn from_transaction(operator: &str, tx: &LaminarTransaction) -> Self {
+        let order_ids = tx
+            .events
+            .iter()
+            .filter_map(|e| match e {
+                LaminarEvent::PlaceOrder(p) => Some(p.order_id.clone()),
+                _ => None,
+            })
+            .collect();
+
+        Self {
+            operator: operator.to_string(),
+            hash: tx.info.hash.to_string(),
+            sequence_number: tx.request.sequence_number.0,
+            timestamp: tx.timestamp.0,
+            success: tx.info.success,
+            order_ids,
+        }
+    }
+}
+
+/// Append-only log of submissions, optionally persisted as JSON lines on disk.
+#[derive(Debug, Default)]
+pub struct AuditLog {
+    records: Vec<AuditRecord>,
+    file: Option<File>,
+}
+
+impl AuditLog {
+    /// Create an in-memory audit log.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Open an audit log file, loading existing records and appending new ones to it.
+    ///
+    /// # Arguments:
+    ///
+    /// * `path` - Path of the JSON lines audit file, created if missing.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let mut records = vec![];
+        if path.exists() {
+            let reader = BufReader::new(File::open(path).context("failed opening audit log")?);
+            for line in reader.lines() {
+                let line = line.context("failed reading audit log")?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                records.push(serde_json::from_str(&line).context("invalid audit record")?);
+            }
+        }
+
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .context("failed opening audit log")?;
+
+        Ok(Self {
+            records,
+            file: Some(file),
+        })
+    }
+
+    pub fn record(&mut self, record: AuditRecord) -> Result<()> {
+        if let Some(file) = self.file.as_mut() {
+            serde_json::to_writer(&mut *file, &record).context("failed writing audit record")?;
+            file.write_all(b"\n")?;
+        }
+        self.records.push(record);
+        Ok(())
+    }
+
+    pub fn records(&self) -> &[AuditRecord] {
+        &self.records
+    }
+
+    /// Operator that placed the given order, if the placement was recorded.
+    pub fn operator_for(&self, order_id: &Id) -> Option<&str> {
+        self.records
+            .iter()
+            .find(|r| r.order_ids.contains(order_id))
+            .map(|r| r.operator.as_str())
+    }
+
+    /// Group fills by the operator that placed the filled order.
+    /// Fills of orders without an audit record are grouped under `None`.
+    pub fn attribute_fills<'a>(
+        &self,
+        fills: &'a [FillEvent],
+    ) -> HashMap<Option<String>, Vec<&'a FillEvent>> {
+        let operators = self
+            .records
+            .iter()
+            .flat_map(|r| r.order_ids.iter().map(move |id| (id, r.operator.as_str())))
+            .collect::<HashMap<&Id, &str>>();
+
+        let mut res: HashMap<Option<String>, Vec<&FillEvent>> = HashMap::new();
+        for f in fills {
+            let operator = operators.get(&f.order_id).map(|o| o.to_string());
+            res.entry(operator).or_default().push(f);
+        }
+
+        res
+    }
+}
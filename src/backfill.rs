@@ -0,0 +1,232 @@
+//! Market-wide event history read from the transactions of a version range.
+//!
+//! Event handles are stored per account, so the events of all accounts trading a book can
+//! only be collected by scanning the transactions that emitted them.
+
+use crate::error::{LaminarError, Result};
+use crate::rate_limit::Endpoint;
+use crate::types::events::{EventKey, LaminarEvent, LaminarEventEnvelope};
+use crate::types::order::Id;
+use crate::LaminarClient;
+use aptos_api_types::{HashValue, MoveType, Transaction};
+use aptos_sdk::types::account_address::AccountAddress;
+use serde::Serialize;
+
+/// Largest page of transactions the node returns.
+pub const MAX_PAGE_SIZE: u16 = 100;
+
+/// Laminar event found in a scanned transaction.
+#[derive(Clone, Debug, Serialize)]
+pub struct VersionedEvent {
+    pub version: u64,
+    /// Index of the event among all events of its transaction.
+    pub event_index: usize,
+    pub hash: HashValue,
+    pub sender: AccountAddress,
+    /// Block timestamp of the transaction in microseconds.
+    pub timestamp: u64,
+    /// Event stream the event was emitted on, all zeros for module events.
+    pub key: EventKey,
+    pub sequence_number: u64,
+    pub event: LaminarEvent,
+}
+
+impl VersionedEvent {
+    pub fn into_envelope(self) -> LaminarEventEnvelope<LaminarEvent> {
+        LaminarEventEnvelope {
+            key: self.key,
+            sequence_number: self.sequence_number,
+            version: self.version,
+            event: self.event,
+        }
+    }
+}
+
+impl LaminarClient {
+    /// Scan committed transactions for Laminar events of any account. Events are returned in
+    /// version and event order, failed and non-user transactions emit none.
+    ///
+    /// Returns the events and the version to continue from, which is `start_version` if no
+    /// transactions were committed after it yet. Events that fail to decode are returned as
+    /// `LaminarEvent::Unknown` rather than failing the page.
+    ///
+    /// # Arguments:
+    ///
+    /// * `start_version` - Version of the first transaction to scan.
+    /// * `limit` - Number of transactions to scan, at most `MAX_PAGE_SIZE`.
+    pub async fn scan_transactions(
+        &self,
+        start_version: u64,
+        limit: u16,
+    ) -> Result<(Vec<VersionedEvent>, u64)> {
+        self.rate_limit(Endpoint::Transactions).await;
+        let txs = match self
+            .aptos_client
+            .get_transactions(Some(start_version), Some(limit.min(MAX_PAGE_SIZE)))
+            .await
+        {
+            Ok(txs) => txs.into_inner(),
+            Err(e) => {
+                // the node rejects a start past the latest committed version instead of
+                // returning an empty page
+                self.rate_limit(Endpoint::Index).await;
+                let index = self
+                    .aptos_client
+                    .get_index()
+                    .await
+                    .map_err(|e| LaminarError::rest(e, "failed getting ledger info"))?
+                    .into_inner();
+                if start_version > index.ledger_version.0 {
+                    return Ok((vec![], start_version));
+                }
+                return Err(LaminarError::rest(
+                    e,
+                    format!(
+                        "failed getting transactions from version: {}",
+                        start_version
+                    ),
+                ));
+            }
+        };
+
+        let next_version = txs
+            .iter()
+            .filter_map(|tx| tx.version())
+            .max()
+            .map_or(start_version, |v| v + 1);
+        let mut res = vec![];
+        for tx in txs {
+            let Transaction::UserTransaction(ut) = tx else {
+                continue;
+            };
+            for (event_index, e) in ut.events.iter().enumerate() {
+                if !matches!(&e.typ, MoveType::Struct(s) if s.address.inner() == self.laminar()) {
+                    continue;
+                }
+                let typ = e.typ.to_string();
+                let event = match LaminarEvent::from_move_event(&typ, e.data.clone()) {
+                    Ok(event) => event,
+                    Err(_err) => {
+                        trace_event!(
+                            warn,
+                            version = ut.info.version.0,
+                            event_index,
+                            error = %_err,
+                            "failed decoding laminar event"
+                        );
+                        LaminarEvent::Unknown {
+                            type_name: typ,
+                            data: e.data.clone(),
+                        }
+                    }
+                };
+                res.push(VersionedEvent {
+                    version: ut.info.version.0,
+                    event_index,
+                    hash: ut.info.hash,
+                    sender: ut.request.sender.into(),
+                    timestamp: ut.timestamp.0,
+                    key: EventKey {
+                        account_address: e.guid.account_address.into(),
+                        creation_number: e.guid.creation_number.0,
+                    },
+                    sequence_number: e.sequence_number.0,
+                    event,
+                });
+            }
+        }
+        trace_event!(
+            debug,
+            start_version,
+            next_version,
+            events = res.len(),
+            "scanned transactions"
+        );
+
+        Ok((res, next_version))
+    }
+}
+
+/// Pages through a version range with `LaminarClient::scan_transactions`.
+///
+/// Nodes prune old transactions, a backfill from before the pruning window of the node fails
+/// with a `Rest` error and needs an archive node.
+#[derive(Clone, Debug)]
+pub struct Backfill {
+    next_version: u64,
+    end_version: Option<u64>,
+    page_size: u16,
+    book_id: Option<Id>,
+}
+
+impl Backfill {
+    /// Scan from `start_version` up to the latest committed transaction.
+    pub fn new(start_version: u64) -> Self {
+        Self {
+            next_version: start_version,
+            end_version: None,
+            page_size: MAX_PAGE_SIZE,
+            book_id: None,
+        }
+    }
+
+    /// Stop before `end_version`.
+    pub fn with_end_version(mut self, end_version: u64) -> Self {
+        self.end_version = Some(end_version);
+        self
+    }
+
+    pub fn with_page_size(mut self, page_size: u16) -> Self {
+        self.page_size = page_size.clamp(1, MAX_PAGE_SIZE);
+        self
+    }
+
+    /// Only yield the events of one book.
+    pub fn with_book(mut self, book_id: Id) -> Self {
+        self.book_id = Some(book_id);
+        self
+    }
+
+    /// Version the next page starts at, store it to resume the backfill later.
+    pub fn next_version(&self) -> u64 {
+        self.next_version
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.end_version
+            .map_or(false, |end| self.next_version >= end)
+    }
+
+    /// Scan the next page of transactions. Returns `None` once the end version or the latest
+    /// committed transaction is reached, a later call continues with new transactions.
+    pub async fn next_page(
+        &mut self,
+        client: &LaminarClient,
+    ) -> Result<Option<Vec<VersionedEvent>>> {
+        let limit = match self.end_version {
+            Some(end) if self.next_version >= end => return Ok(None),
+            Some(end) => (end - self.next_version).min(self.page_size as u64) as u16,
+            None => self.page_size,
+        };
+        let (mut events, next_version) = client.scan_transactions(self.next_version, limit).await?;
+        if next_version == self.next_version {
+            return Ok(None);
+        }
+        self.next_version = next_version;
+        if let Some(book_id) = &self.book_id {
+            events.retain(|e| e.event.book_id() == Some(book_id));
+        }
+
+        Ok(Some(events))
+    }
+
+    /// Scan all remaining pages, returning the events in order.
+    pub async fn collect(&mut self, client: &LaminarClient) -> Result<Vec<VersionedEvent>> {
+        let mut res = vec![];
+        while let Some(events) = self.next_page(client).await? {
+            res.extend(events);
+        }
+
+        Ok(res)
+    }
+}
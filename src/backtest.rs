@@ -0,0 +1,309 @@
+use crate::error::Result;
+use crate::fixtures::read_json_lines;
+use crate::position::Position;
+use crate::sim::{EventReplay, SimBook, SimError};
+use crate::strategy::{OrderRequest, Strategy, StrategyContext};
+use crate::types::events::{FillEvent, LaminarEvent};
+use crate::types::order::{Id, Instrument};
+use aptos_sdk::types::account_address::AccountAddress;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::io::BufRead;
+
+#[derive(Clone, Debug)]
+pub struct BacktestConfig {
+    /// Account the strategy trades as. It should not appear in the recorded events.
+    pub account: AccountAddress,
+    pub instrument: Instrument,
+    /// Number of levels per side passed to `Strategy::on_book_update`.
+    pub depth: usize,
+    /// Delay between a strategy request and its arrival at the book, in the unit of
+    /// `LaminarEvent::time`.
+    pub latency: u64,
+    /// Interval of `Strategy::on_timer` calls, no timer if unset.
+    pub timer_interval: Option<u64>,
+}
+
+impl BacktestConfig {
+    pub fn new(account: AccountAddress, instrument: Instrument) -> Self {
+        Self {
+            account,
+            instrument,
+            depth: 10,
+            latency: 0,
+            timer_interval: None,
+        }
+    }
+
+    pub fn with_depth(mut self, depth: usize) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    pub fn with_latency(mut self, latency: u64) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    pub fn with_timer_interval(mut self, interval: u64) -> Self {
+        self.timer_interval = Some(interval).filter(|i| *i > 0);
+        self
+    }
+}
+
+/// Distribution of latency samples.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct LatencyStats {
+    pub count: usize,
+    pub mean: f64,
+    pub p50: u64,
+    pub p99: u64,
+    pub max: u64,
+}
+
+impl LatencyStats {
+    fn from_samples(mut samples: Vec<u64>) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        samples.sort_unstable();
+        let percentile = |p: usize| samples[(samples.len() - 1) * p / 100];
+
+        Some(Self {
+            count: samples.len(),
+            mean: samples.iter().sum::<u64>() as f64 / samples.len() as f64,
+            p50: percentile(50),
+            p99: percentile(99),
+            max: samples[samples.len() - 1],
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct BacktestReport {
+    pub position: Position,
+    /// Fills of the strategy's orders.
+    pub fills: Vec<FillEvent>,
+    pub orders_placed: usize,
+    /// Strategy requests rejected by the book.
+    pub rejections: Vec<(OrderRequest, SimError)>,
+    /// Recorded events that could not be replayed, e.g. amends of orders the strategy
+    /// filled before they were amended in the recording.
+    pub replay_errors: usize,
+    pub events_replayed: usize,
+    /// Price of the last fill on the book, used to mark the position.
+    pub last_price: Option<u64>,
+    /// Realized and unrealized PnL net of fees, in quote coin.
+    pub total_pnl: f64,
+    /// Time from a place request to the first fill of the order.
+    pub fill_latency: Option<LatencyStats>,
+}
+
+#[derive(Debug)]
+struct Pending {
+    arrival: u64,
+    decision: u64,
+    request: OrderRequest,
+}
+
+/// Replays recorded `LaminarEvent`s of a book through a `SimBook` while a `Strategy` trades
+/// against it.
+///
+/// Recorded events are re-executed on the simulator with an `EventReplay`, so the strategy's
+/// orders take liquidity from and provide liquidity to the recorded flow. Strategy requests
+/// reach the book after the configured latency.
+pub struct Backtester {
+    config: BacktestConfig,
+    book: SimBook,
+    replay: EventReplay,
+    pending: VecDeque<Pending>,
+    next_timer: Option<u64>,
+    decisions: HashMap<Id, u64>,
+    latencies: Vec<u64>,
+    report: BacktestReport,
+}
+
+impl Backtester {
+    /// # Arguments:
+    ///
+    /// * `config` - Account, instrument and replay settings.
+    /// * `book` - Book to start from, e.g. `SimBook::from_order_book` of a snapshot taken at
+    /// the time of the first recorded event.
+    pub fn new(config: BacktestConfig, book: SimBook) -> Self {
+        let report = BacktestReport {
+            position: Position::new(book.book_id().clone(), config.instrument.clone()),
+            fills: vec![],
+            orders_placed: 0,
+            rejections: vec![],
+            replay_errors: 0,
+            events_replayed: 0,
+            last_price: None,
+            total_pnl: 0.0,
+            fill_latency: None,
+        };
+        Self {
+            config,
+            book,
+            replay: EventReplay::new(),
+            pending: VecDeque::new(),
+            next_timer: None,
+            decisions: HashMap::new(),
+            latencies: vec![],
+            report,
+        }
+    }
+
+    /// Run `strategy` over `events`, which are replayed in time order.
+    pub fn run<S, I>(mut self, strategy: &mut S, events: I) -> BacktestReport
+    where
+        S: Strategy,
+        I: IntoIterator<Item = LaminarEvent>,
+    {
+        let mut events = events.into_iter().collect::<Vec<_>>();
+        events.sort_by_key(|e| e.time());
+        let mut ctx = StrategyContext::new(self.book.book_id().clone(), 0);
+
+        for event in events {
+            let time = event.time();
+            if self.next_timer.is_none() {
+                self.next_timer = self.config.timer_interval.map(|i| time + i);
+            }
+            self.advance(strategy, &mut ctx, time);
+
+            match self.replay.apply(&mut self.book, &event) {
+                Ok(sim_events) => {
+                    self.report.events_replayed += 1;
+                    self.dispatch(strategy, &mut ctx, time, &sim_events);
+                }
+                Err(_) => self.report.replay_errors += 1,
+            }
+        }
+        // requests in flight at the end of the recording still arrive, later ones are dropped
+        let in_flight = self.pending.drain(..).collect::<Vec<_>>();
+        for pending in in_flight {
+            self.execute(strategy, &mut ctx, pending);
+        }
+        self.pending.clear();
+
+        self.finish()
+    }
+
+    /// Run `strategy` over events exported as JSON lines, see `fixtures::write_json_lines`.
+    pub fn run_json_lines<S, R>(self, strategy: &mut S, reader: R) -> Result<BacktestReport>
+    where
+        S: Strategy,
+        R: BufRead,
+    {
+        let events = read_json_lines(reader)?;
+        Ok(self.run(strategy, events))
+    }
+
+    // fire timers and execute requests arriving up to `until`, in time order
+    fn advance<S: Strategy>(&mut self, strategy: &mut S, ctx: &mut StrategyContext, until: u64) {
+        loop {
+            let arrival = self.pending.front().map(|p| p.arrival);
+            match (arrival, self.next_timer) {
+                (Some(a), timer) if a <= until && timer.map_or(true, |t| a <= t) => {
+                    let pending = self.pending.pop_front().expect("pending request exists");
+                    self.execute(strategy, ctx, pending);
+                }
+                (_, Some(t)) if t <= until => {
+                    ctx.set_time(t);
+                    strategy.on_timer(ctx);
+                    self.enqueue(ctx, t);
+                    self.next_timer = self.config.timer_interval.map(|i| t + i);
+                }
+                _ => return,
+            }
+        }
+    }
+
+    fn execute<S: Strategy>(&mut self, strategy: &mut S, ctx: &mut StrategyContext, p: Pending) {
+        match self
+            .book
+            .execute(self.config.account, &p.request, p.arrival)
+        {
+            Ok(sim_events) => {
+                if let Some(LaminarEvent::PlaceOrder(place)) = sim_events.first() {
+                    self.report.orders_placed += 1;
+                    self.decisions.insert(place.order_id.clone(), p.decision);
+                }
+                self.dispatch(strategy, ctx, p.arrival, &sim_events);
+            }
+            Err(e) => {
+                ctx.set_time(p.arrival);
+                strategy.on_reject(ctx, &p.request, &e.to_string());
+                self.enqueue(ctx, p.arrival);
+                self.report.rejections.push((p.request, e));
+            }
+        }
+    }
+
+    fn enqueue(&mut self, ctx: &mut StrategyContext, decision: u64) {
+        for request in ctx.take_requests() {
+            self.pending.push_back(Pending {
+                arrival: decision.saturating_add(self.config.latency),
+                decision,
+                request,
+            });
+        }
+    }
+
+    // pass the events of the strategy's orders and the new book to the strategy
+    fn dispatch<S: Strategy>(
+        &mut self,
+        strategy: &mut S,
+        ctx: &mut StrategyContext,
+        time: u64,
+        sim_events: &[LaminarEvent],
+    ) {
+        if sim_events.is_empty() {
+            return;
+        }
+        ctx.set_time(time);
+        let account = self.config.account;
+        let own = |id: &Id| id.addr.inner() == &account;
+
+        for event in sim_events {
+            match event {
+                LaminarEvent::FillEvent(fill) => {
+                    self.report.last_price = Some(fill.price);
+                    if !own(&fill.order_id) {
+                        continue;
+                    }
+                    if let Some(decision) = self.decisions.remove(&fill.order_id) {
+                        self.latencies.push(fill.time.saturating_sub(decision));
+                    }
+                    self.report.position.apply(fill);
+                    self.report.fills.push(fill.clone());
+                    strategy.on_fill(ctx, fill);
+                }
+                LaminarEvent::PlaceOrder(e) if own(&e.order_id) => {
+                    strategy.on_order_update(ctx, event)
+                }
+                LaminarEvent::AmendOrder(e) if own(&e.order_id) => {
+                    strategy.on_order_update(ctx, event)
+                }
+                LaminarEvent::CancelOrder(e) if own(&e.order_id) => {
+                    self.decisions.remove(&e.order_id);
+                    strategy.on_order_update(ctx, event)
+                }
+                _ => {}
+            }
+        }
+        strategy.on_book_update(ctx, &self.book.to_l2(self.config.depth));
+        self.enqueue(ctx, time);
+    }
+
+    fn finish(mut self) -> BacktestReport {
+        let position = &self.report.position;
+        let mark = self
+            .report
+            .last_price
+            .map(|p| self.config.instrument.price_from_units(p))
+            .unwrap_or(position.avg_entry_price);
+        self.report.total_pnl = position.total_pnl(mark);
+        self.report.fill_latency = LatencyStats::from_samples(self.latencies);
+        self.report
+    }
+}
@@ -0,0 +1,27 @@
+use crate::types::order::{Id, Side, TimeInForce};
+
+/// A single order operation on an `OrderBook`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LaminarAction {
+    PlaceLimit {
+        side: Side,
+        price: u64,
+        size: u64,
+        time_in_force: TimeInForce,
+        post_only: bool,
+    },
+    PlaceMarket {
+        side: Side,
+        size: u64,
+    },
+    Amend {
+        order_id: Id,
+        side: Side,
+        price: u64,
+        size: u64,
+    },
+    Cancel {
+        order_id: Id,
+        side: Side,
+    },
+}
@@ -0,0 +1,610 @@
+//! `laminar` command line client, for manual testing and as a reference of the SDK's API.
+//!
+//! The client is configured from the environment, see `LaminarClient::from_env`. Prices and
+//! sizes are given in decimals and must land on the book's ticks. `laminar repl` keeps the
+//! client connected and evaluates the same commands line by line.
+
+use anyhow::{anyhow, Result};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use laminar_sdk::market_data::MarketDataFeed;
+use laminar_sdk::query::{EventIds, EventQuery};
+use laminar_sdk::types::decimal::format_units;
+use laminar_sdk::types::events::{
+    AmendOrderEvent, CancelOrderEvent, EventStoreField, FillEvent, LaminarEvent,
+    LaminarEventEnvelope, PlaceOrderEvent,
+};
+use laminar_sdk::types::l2::L2Level;
+use laminar_sdk::types::market::Market;
+use laminar_sdk::types::order::{Id, OrderBook, Side, TickPolicy, TimeInForce};
+use laminar_sdk::LaminarClient;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::json;
+use std::io::{BufRead, Write};
+use std::time::Duration;
+
+#[derive(Parser)]
+#[command(
+    name = "laminar",
+    version,
+    about = "Laminar Markets command line client"
+)]
+struct Cli {
+    /// Print results as json instead of tables.
+    #[arg(long, global = true)]
+    json: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Inspect order books.
+    Book {
+        #[command(subcommand)]
+        command: BookCommand,
+    },
+    /// Place, simulate, cancel and amend orders.
+    Order {
+        #[command(subcommand)]
+        command: OrderCommand,
+    },
+    /// Inspect the account's orders.
+    Orders {
+        #[command(subcommand)]
+        command: OrdersCommand,
+    },
+    /// Inspect and follow the events of a book.
+    Events {
+        #[command(subcommand)]
+        command: EventsCommand,
+    },
+    /// Register the account to trade on Laminar.
+    Register,
+    /// Evaluate commands interactively with a connected client, `exit` to leave.
+    Repl,
+}
+
+#[derive(Subcommand)]
+enum BookCommand {
+    /// Show the aggregated price levels of a book.
+    Show {
+        /// Id of the book, `address:number`.
+        book_id: Id,
+        /// Number of levels shown per side.
+        #[arg(long, default_value_t = 10)]
+        depth: usize,
+    },
+    /// Watch a book live in the terminal, highlighting the account's orders.
+    #[cfg(feature = "tui")]
+    Watch {
+        book_id: Id,
+        #[arg(long, default_value_t = 20)]
+        depth: usize,
+        /// Delay between polls in milliseconds.
+        #[arg(long, default_value_t = 1000)]
+        interval_ms: u64,
+    },
+}
+
+#[derive(Args)]
+struct OrderArgs {
+    book_id: Id,
+    #[arg(long, value_enum)]
+    side: SideArg,
+    #[arg(long)]
+    size: f64,
+    /// Limit price, a market order is placed without it.
+    #[arg(long)]
+    price: Option<f64>,
+    #[arg(long, value_enum, default_value_t = TimeInForceArg::Gtc)]
+    time_in_force: TimeInForceArg,
+    #[arg(long)]
+    post_only: bool,
+}
+
+#[derive(Subcommand)]
+enum OrderCommand {
+    /// Place a limit order, or a market order without a price.
+    Place(OrderArgs),
+    /// Simulate placing an order, showing the events it would emit and its gas.
+    Simulate(OrderArgs),
+    /// Cancel an order.
+    Cancel {
+        book_id: Id,
+        order_id: Id,
+        #[arg(long, value_enum)]
+        side: SideArg,
+    },
+    /// Amend the price or size of an order, keeping the current value of the one not given.
+    Amend {
+        book_id: Id,
+        order_id: Id,
+        #[arg(long, value_enum)]
+        side: SideArg,
+        #[arg(long)]
+        price: Option<f64>,
+        #[arg(long)]
+        size: Option<f64>,
+    },
+}
+
+#[derive(Subcommand)]
+enum OrdersCommand {
+    /// List the account's open orders on a book.
+    List { book_id: Id },
+}
+
+#[derive(Subcommand)]
+enum EventsCommand {
+    /// List the events of a kind emitted on a book.
+    List {
+        book_id: Id,
+        #[arg(long, value_enum, default_value_t = EventKind::Fill)]
+        kind: EventKind,
+        /// Sequence number of the first event to read.
+        #[arg(long, default_value_t = 0)]
+        start: u64,
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+    },
+    /// Print the events of a book as json lines until interrupted.
+    Tail {
+        book_id: Id,
+        /// Delay between polls in milliseconds.
+        #[arg(long, default_value_t = 1000)]
+        interval_ms: u64,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum SideArg {
+    Bid,
+    Ask,
+}
+
+impl From<SideArg> for Side {
+    fn from(side: SideArg) -> Self {
+        match side {
+            SideArg::Bid => Side::Bid,
+            SideArg::Ask => Side::Ask,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum TimeInForceArg {
+    Gtc,
+    Ioc,
+    Fok,
+}
+
+impl From<TimeInForceArg> for TimeInForce {
+    fn from(time_in_force: TimeInForceArg) -> Self {
+        match time_in_force {
+            TimeInForceArg::Gtc => TimeInForce::GoodTillCanceled,
+            TimeInForceArg::Ioc => TimeInForce::ImmediateOrCancel,
+            TimeInForceArg::Fok => TimeInForce::FillOrKill,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum EventKind {
+    Place,
+    Amend,
+    Cancel,
+    Fill,
+}
+
+fn side_name(side: Side) -> &'static str {
+    match side {
+        Side::Bid => "bid",
+        Side::Ask => "ask",
+    }
+}
+
+fn print_json<T: Serialize>(value: &T) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+fn print_levels(book: &OrderBook, levels: &[L2Level]) {
+    for level in levels {
+        println!(
+            "{:>20} {:>20} {:>6}",
+            format_units(level.price, book.instrument.price_decimals),
+            format_units(level.total_size, book.instrument.size_decimals),
+            level.order_count
+        );
+    }
+}
+
+fn print_events(events: &[LaminarEventEnvelope<LaminarEvent>]) {
+    println!(
+        "{:>12} {:>8} {:<16} {:<72} {:>20}",
+        "version", "seq", "type", "order", "time"
+    );
+    for envelope in events {
+        let kind = match &envelope.event {
+            LaminarEvent::CreateOrderBook(_) => "create_orderbook",
+            LaminarEvent::PlaceOrder(_) => "place",
+            LaminarEvent::AmendOrder(_) => "amend",
+            LaminarEvent::CancelOrder(_) => "cancel",
+            LaminarEvent::FillEvent(_) => "fill",
+            LaminarEvent::Unknown { .. } => "unknown",
+        };
+        println!(
+            "{:>12} {:>8} {:<16} {:<72} {:>20}",
+            envelope.version,
+            envelope.sequence_number,
+            kind,
+            envelope
+                .event
+                .order_id()
+                .map(|id| id.to_string())
+                .unwrap_or_default(),
+            envelope.event.time()
+        );
+    }
+}
+
+async fn fetch_market(client: &LaminarClient, book_id: &Id) -> Result<(Market, OrderBook)> {
+    let market = client.fetch_market_by_id(book_id).await?;
+    let book = client
+        .fetch_orderbook(&market.base, &market.quote, &market.book_owner)
+        .await?;
+    Ok((market, book))
+}
+
+async fn fetch_events<'a, E>(
+    client: &LaminarClient,
+    book_id: &Id,
+    start: u64,
+    limit: usize,
+    event: fn(E) -> LaminarEvent,
+) -> Result<Vec<LaminarEventEnvelope<LaminarEvent>>>
+where
+    E: EventStoreField<'a> + EventIds + DeserializeOwned,
+{
+    let query = EventQuery::<E>::new()
+        .for_book(book_id)
+        .in_range(start, u64::MAX)
+        .limit(limit);
+    let events = client.query_event_envelopes(&query).await?;
+    Ok(events.into_iter().map(|e| e.map(event)).collect())
+}
+
+/// Market of the order and its price and size in units.
+async fn order_units(
+    client: &LaminarClient,
+    args: &OrderArgs,
+) -> Result<(Market, Option<u64>, u64)> {
+    let (market, book) = fetch_market(client, &args.book_id).await?;
+    let side = Side::from(args.side);
+    let price = args
+        .price
+        .map(|price| {
+            book.instrument
+                .checked_price_units(price, side, TickPolicy::Reject)
+        })
+        .transpose()?;
+    let size = book
+        .instrument
+        .checked_size_units(args.size, TickPolicy::Reject)?;
+    Ok((market, price, size))
+}
+
+async fn run(client: &LaminarClient, command: Command, json: bool) -> Result<()> {
+    match command {
+        Command::Book {
+            command: BookCommand::Show { book_id, depth },
+        } => {
+            let book = client.fetch_orderbook_by_id(&book_id).await?;
+            let l2 = book.to_l2(depth);
+            if json {
+                return print_json(&l2);
+            }
+            println!("{:>20} {:>20} {:>6}", "price", "size", "orders");
+            print_levels(&book, &l2.asks.iter().rev().copied().collect::<Vec<_>>());
+            println!("{:-<48}", "");
+            print_levels(&book, &l2.bids);
+        }
+        #[cfg(feature = "tui")]
+        Command::Book {
+            command:
+                BookCommand::Watch {
+                    book_id,
+                    depth,
+                    interval_ms,
+                },
+        } => {
+            let market = client.fetch_market_by_id(&book_id).await?;
+            laminar_sdk::tui::run(client, &market, depth, Duration::from_millis(interval_ms))
+                .await?;
+        }
+        Command::Order { command } => match command {
+            OrderCommand::Place(args) => {
+                let (market, price, size) = order_units(client, &args).await?;
+                let side = Side::from(args.side);
+                let placed = match price {
+                    Some(price) => {
+                        client
+                            .place_limit_order(
+                                &market.base,
+                                &market.quote,
+                                &market.book_owner,
+                                side,
+                                price,
+                                size,
+                                args.time_in_force.into(),
+                                args.post_only,
+                            )
+                            .await?
+                    }
+                    None => {
+                        client
+                            .place_market_order(
+                                &market.base,
+                                &market.quote,
+                                &market.book_owner,
+                                side,
+                                size,
+                            )
+                            .await?
+                    }
+                };
+                print_json(&placed)?;
+            }
+            OrderCommand::Simulate(args) => {
+                let (market, price, size) = order_units(client, &args).await?;
+                let side = Side::from(args.side);
+                let payload = match price {
+                    Some(price) => client.place_limit_order_payload(
+                        &market.base,
+                        &market.quote,
+                        &market.book_owner,
+                        side,
+                        price,
+                        size,
+                        args.time_in_force.into(),
+                        args.post_only,
+                    )?,
+                    None => client.place_market_order_payload(
+                        &market.base,
+                        &market.quote,
+                        &market.book_owner,
+                        side,
+                        size,
+                    )?,
+                };
+                let simulation = client.simulate_tx(payload).await?;
+                if json {
+                    return print_json(&simulation);
+                }
+                println!("success      {}", simulation.success);
+                println!("vm status    {}", simulation.vm_status);
+                println!("gas used     {}", simulation.gas_used);
+                println!("fee (octas)  {}", simulation.estimated_fee());
+                for event in &simulation.events {
+                    println!("{}", serde_json::to_string(event)?);
+                }
+            }
+            OrderCommand::Cancel {
+                book_id,
+                order_id,
+                side,
+            } => {
+                let market = client.fetch_market_by_id(&book_id).await?;
+                let cancel = client
+                    .cancel_order(
+                        &market.base,
+                        &market.quote,
+                        &market.book_owner,
+                        &order_id,
+                        side.into(),
+                    )
+                    .await?;
+                print_json(&cancel)?;
+            }
+            OrderCommand::Amend {
+                book_id,
+                order_id,
+                side,
+                price,
+                size,
+            } => {
+                if price.is_none() && size.is_none() {
+                    return Err(anyhow!("amend requires --price or --size"));
+                }
+                let (market, book) = fetch_market(client, &book_id).await?;
+                let side = Side::from(side);
+                let order = client.get_order(&order_id).await?;
+                let price = match price {
+                    Some(price) => {
+                        book.instrument
+                            .checked_price_units(price, side, TickPolicy::Reject)?
+                    }
+                    None => order.price,
+                };
+                let size = match size {
+                    Some(size) => book
+                        .instrument
+                        .checked_size_units(size, TickPolicy::Reject)?,
+                    None => order.size,
+                };
+                let amend = client
+                    .amend_order(
+                        &market.base,
+                        &market.quote,
+                        &market.book_owner,
+                        &order_id,
+                        side,
+                        price,
+                        size,
+                    )
+                    .await?;
+                print_json(&amend)?;
+            }
+        },
+        Command::Orders {
+            command: OrdersCommand::List { book_id },
+        } => {
+            let market = client.fetch_market_by_id(&book_id).await?;
+            let orders = client
+                .fetch_my_open_orders(&market.base, &market.quote, &market.book_owner)
+                .await?;
+            if json {
+                let orders = orders
+                    .iter()
+                    .map(|open| {
+                        json!({
+                            "id": open.order.id.to_string(),
+                            "side": side_name(open.order.side),
+                            "price": open.price,
+                            "size": open.size,
+                            "remaining_size": open.remaining_size,
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                return print_json(&orders);
+            }
+            println!(
+                "{:<72} {:<4} {:>20} {:>20} {:>20}",
+                "id", "side", "price", "size", "remaining"
+            );
+            for open in orders {
+                println!(
+                    "{:<72} {:<4} {:>20} {:>20} {:>20}",
+                    open.order.id.to_string(),
+                    side_name(open.order.side),
+                    open.price,
+                    open.size,
+                    open.remaining_size
+                );
+            }
+        }
+        Command::Events {
+            command:
+                EventsCommand::List {
+                    book_id,
+                    kind,
+                    start,
+                    limit,
+                },
+        } => {
+            let events = match kind {
+                EventKind::Place => {
+                    fetch_events::<PlaceOrderEvent>(
+                        client,
+                        &book_id,
+                        start,
+                        limit,
+                        LaminarEvent::PlaceOrder,
+                    )
+                    .await?
+                }
+                EventKind::Amend => {
+                    fetch_events::<AmendOrderEvent>(
+                        client,
+                        &book_id,
+                        start,
+                        limit,
+                        LaminarEvent::AmendOrder,
+                    )
+                    .await?
+                }
+                EventKind::Cancel => {
+                    fetch_events::<CancelOrderEvent>(
+                        client,
+                        &book_id,
+                        start,
+                        limit,
+                        LaminarEvent::CancelOrder,
+                    )
+                    .await?
+                }
+                EventKind::Fill => {
+                    fetch_events::<FillEvent>(
+                        client,
+                        &book_id,
+                        start,
+                        limit,
+                        LaminarEvent::FillEvent,
+                    )
+                    .await?
+                }
+            };
+            if json {
+                return print_json(&events);
+            }
+            print_events(&events);
+        }
+        Command::Events {
+            command:
+                EventsCommand::Tail {
+                    book_id,
+                    interval_ms,
+                },
+        } => {
+            let market = client.fetch_market_by_id(&book_id).await?;
+            let mut feed = MarketDataFeed::new(market.base, market.quote, market.book_owner);
+            loop {
+                for envelope in feed.poll_envelopes(client).await? {
+                    println!("{}", serde_json::to_string(&envelope)?);
+                }
+                tokio::time::sleep(Duration::from_millis(interval_ms)).await;
+            }
+        }
+        Command::Register => {
+            if client.is_user_registered().await? {
+                println!("{} is already registered", client.address());
+            } else {
+                let tx = client.register_user().await?;
+                println!("registered {} in tx {}", client.address(), tx.hash());
+            }
+        }
+        Command::Repl => return Err(anyhow!("already in the repl")),
+    }
+    Ok(())
+}
+
+/// Read commands from stdin until `exit` or the end of input. `--json` given when starting
+/// the repl applies to every command.
+async fn repl(client: &LaminarClient, json: bool) -> Result<()> {
+    let stdin = std::io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        print!("laminar> ");
+        std::io::stdout().flush()?;
+        let Some(line) = lines.next().transpose()? else {
+            return Ok(());
+        };
+        let words = line.split_whitespace().collect::<Vec<_>>();
+        match words.as_slice() {
+            [] => continue,
+            ["exit"] | ["quit"] => return Ok(()),
+            _ => {}
+        }
+
+        match Cli::try_parse_from(std::iter::once("laminar").chain(words)) {
+            Ok(cli) => {
+                if let Err(e) = run(client, cli.command, json || cli.json).await {
+                    eprintln!("error: {:#}", e);
+                }
+            }
+            // also prints `--help`
+            Err(e) => e.print()?,
+        }
+    }
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let client = LaminarClient::from_env().await?;
+    match cli.command {
+        Command::Repl => repl(&client, cli.json).await,
+        command => run(&client, command, cli.json).await,
+    }
+}
@@ -0,0 +1,284 @@
+//! Synchronous wrapper of `LaminarClient`, for scripts and GUIs that don't run an async
+//! runtime.
+
+use crate::error::Result;
+use crate::query::{EventIds, EventQuery};
+use crate::types::events::{
+    AmendOrderEvent, CancelOrderEvent, CreateOrderBookEvent, EventStoreField,
+};
+use crate::types::market::Market;
+use crate::types::order::{Id, OpenOrder, Order, OrderBook, Side, TimeInForce};
+use crate::{LaminarClient, LaminarTransaction, PlacedOrder};
+use anyhow::anyhow;
+use aptos_api_types::U64;
+use aptos_sdk::move_types::language_storage::TypeTag;
+use aptos_sdk::types::account_address::AccountAddress;
+use aptos_sdk::types::transaction::EntryFunction;
+use aptos_sdk::types::LocalAccount;
+use reqwest::Url;
+use serde::de::DeserializeOwned;
+use std::future::Future;
+use tokio::runtime::Runtime;
+
+/// `LaminarClient` whose methods block the calling thread until they complete.
+///
+/// The client owns a single threaded tokio runtime driving the requests. Like other blocking
+/// clients it must not be used from within an async runtime, where blocking panics. Methods
+/// without a blocking counterpart can be called with `block_on` on `inner`.
+pub struct LaminarBlockingClient {
+    inner: LaminarClient,
+    runtime: Runtime,
+}
+
+fn runtime() -> Result<Runtime> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| anyhow!("failed starting runtime: {}", e))?;
+    Ok(runtime)
+}
+
+impl LaminarBlockingClient {
+    /// See `LaminarClient::connect`.
+    pub fn connect(node_url: Url, laminar: AccountAddress, account: LocalAccount) -> Result<Self> {
+        let runtime = runtime()?;
+        let inner = runtime.block_on(LaminarClient::connect(node_url, laminar, account))?;
+        Ok(Self { inner, runtime })
+    }
+
+    /// See `LaminarClient::connect_with_strings`.
+    pub fn connect_with_strings(
+        node_url: &str,
+        laminar_address: &str,
+        account_address: &str,
+        account_private_key: &str,
+    ) -> Result<Self> {
+        let runtime = runtime()?;
+        let inner = runtime.block_on(LaminarClient::connect_with_strings(
+            node_url,
+            laminar_address,
+            account_address,
+            account_private_key,
+        ))?;
+        Ok(Self { inner, runtime })
+    }
+
+    /// See `LaminarClient::connect_with_config`.
+    pub fn connect_with_config(
+        node_url: &str,
+        laminar_address: &str,
+        config_path: &str,
+        config_profile_name: &str,
+    ) -> Result<Self> {
+        let runtime = runtime()?;
+        let inner = runtime.block_on(LaminarClient::connect_with_config(
+            node_url,
+            laminar_address,
+            config_path,
+            config_profile_name,
+        ))?;
+        Ok(Self { inner, runtime })
+    }
+
+    /// Wrap a connected client, e.g. one configured with the builder methods of
+    /// `LaminarClient`.
+    pub fn from_client(client: LaminarClient) -> Result<Self> {
+        Ok(Self {
+            inner: client,
+            runtime: runtime()?,
+        })
+    }
+
+    pub fn inner(&self) -> &LaminarClient {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> LaminarClient {
+        self.inner
+    }
+
+    /// Run a future on the client's runtime, e.g. a method of `inner` without a blocking
+    /// counterpart.
+    pub fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future)
+    }
+
+    pub fn address(&self) -> AccountAddress {
+        self.inner.address()
+    }
+
+    pub fn get_sequence_number(&self) -> Result<u64> {
+        self.block_on(self.inner.get_sequence_number())
+    }
+
+    pub fn get_coin_balance(&self, coin: &TypeTag) -> Result<U64> {
+        self.block_on(self.inner.get_coin_balance(coin))
+    }
+
+    pub fn is_user_registered(&self) -> Result<bool> {
+        self.block_on(self.inner.is_user_registered())
+    }
+
+    pub fn register_user(&self) -> Result<LaminarTransaction> {
+        self.block_on(self.inner.register_user())
+    }
+
+    /// See `LaminarClient::create_orderbook`.
+    pub fn create_orderbook(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        price_decimals: u8,
+        size_decimals: u8,
+        min_size_amount: u64,
+    ) -> Result<CreateOrderBookEvent> {
+        self.block_on(self.inner.create_orderbook(
+            base,
+            quote,
+            price_decimals,
+            size_decimals,
+            min_size_amount,
+        ))
+    }
+
+    pub fn fetch_order_books(&self) -> Result<Vec<CreateOrderBookEvent>> {
+        self.block_on(self.inner.fetch_order_books())
+    }
+
+    pub fn fetch_market_by_id(&self, book_id: &Id) -> Result<Market> {
+        self.block_on(self.inner.fetch_market_by_id(book_id))
+    }
+
+    pub fn fetch_orderbook(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        book_owner: &AccountAddress,
+    ) -> Result<OrderBook> {
+        self.block_on(self.inner.fetch_orderbook(base, quote, book_owner))
+    }
+
+    pub fn fetch_orderbook_by_id(&self, book_id: &Id) -> Result<OrderBook> {
+        self.block_on(self.inner.fetch_orderbook_by_id(book_id))
+    }
+
+    /// See `LaminarClient::fetch_orderbook_side`.
+    pub fn fetch_orderbook_side(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        book_owner: &AccountAddress,
+        side: Side,
+        depth: Option<usize>,
+    ) -> Result<OrderBook> {
+        self.block_on(
+            self.inner
+                .fetch_orderbook_side(base, quote, book_owner, side, depth),
+        )
+    }
+
+    pub fn fetch_my_open_orders(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        book_owner: &AccountAddress,
+    ) -> Result<Vec<OpenOrder>> {
+        self.block_on(self.inner.fetch_my_open_orders(base, quote, book_owner))
+    }
+
+    /// See `LaminarClient::place_limit_order`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn place_limit_order(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        book_owner: &AccountAddress,
+        side: Side,
+        price: u64,
+        size: u64,
+        time_in_force: TimeInForce,
+        post_only: bool,
+    ) -> Result<PlacedOrder> {
+        self.block_on(self.inner.place_limit_order(
+            base,
+            quote,
+            book_owner,
+            side,
+            price,
+            size,
+            time_in_force,
+            post_only,
+        ))
+    }
+
+    /// See `LaminarClient::place_market_order`.
+    pub fn place_market_order(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        book_owner: &AccountAddress,
+        side: Side,
+        size: u64,
+    ) -> Result<PlacedOrder> {
+        self.block_on(
+            self.inner
+                .place_market_order(base, quote, book_owner, side, size),
+        )
+    }
+
+    /// See `LaminarClient::amend_order`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn amend_order(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        book_owner: &AccountAddress,
+        order_id: &Id,
+        side: Side,
+        price: u64,
+        size: u64,
+    ) -> Result<AmendOrderEvent> {
+        self.block_on(
+            self.inner
+                .amend_order(base, quote, book_owner, order_id, side, price, size),
+        )
+    }
+
+    /// See `LaminarClient::cancel_order`.
+    pub fn cancel_order(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        book_owner: &AccountAddress,
+        order_id: &Id,
+        side: Side,
+    ) -> Result<CancelOrderEvent> {
+        self.block_on(
+            self.inner
+                .cancel_order(base, quote, book_owner, order_id, side),
+        )
+    }
+
+    pub fn cancel_all_orders(&self, books: &[Market]) -> Result<Vec<CancelOrderEvent>> {
+        self.block_on(self.inner.cancel_all_orders(books))
+    }
+
+    pub fn build_and_submit_tx(&self, payload: EntryFunction) -> Result<LaminarTransaction> {
+        self.block_on(self.inner.build_and_submit_tx(payload))
+    }
+
+    pub fn get_order(&self, order_id: &Id) -> Result<Order> {
+        self.block_on(self.inner.get_order(order_id))
+    }
+
+    pub fn get_orders(&self, order_ids: &[Id]) -> Result<Vec<Order>> {
+        self.block_on(self.inner.get_orders(order_ids))
+    }
+
+    pub fn query_events<'a, E>(&self, query: &EventQuery<E>) -> Result<Vec<E>>
+    where
+        E: EventStoreField<'a> + EventIds + DeserializeOwned,
+    {
+        self.block_on(self.inner.query_events(query))
+    }
+}
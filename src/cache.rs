@@ -0,0 +1,146 @@
+//! Cache of chain reads that rarely change, e.g. coin metadata, book instruments and
+//! registration checks, so hot paths don't hit the node for them on every call.
+
+use crate::time::Instant;
+use crate::types::coin::CoinInfo;
+use crate::types::events::CreateOrderBookEvent;
+use crate::types::market::Market;
+use crate::types::order::Instrument;
+use aptos_sdk::move_types::language_storage::TypeTag;
+use aptos_sdk::types::account_address::AccountAddress;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Settings of the read cache of a `LaminarClient`.
+///
+/// Registration and coin existence checks only cache positive results, which can't be undone
+/// on chain, so a check after registering is never answered from the cache.
+#[derive(Clone, Copy, Debug)]
+pub struct CacheConfig {
+    /// Time an entry is served from the cache before it is read again.
+    pub ttl: Duration,
+    /// Maximum number of entries of each kind. A capacity of 0 disables the cache.
+    pub capacity: usize,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            ttl: Duration::from_secs(300),
+            capacity: 1024,
+        }
+    }
+}
+
+impl CacheConfig {
+    /// Config that doesn't cache anything.
+    pub fn disabled() -> Self {
+        Self {
+            ttl: Duration::ZERO,
+            capacity: 0,
+        }
+    }
+
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+}
+
+/// Map whose entries expire `ttl` after they were inserted. When full, expired entries are
+/// dropped first, then the oldest one.
+#[derive(Debug)]
+pub(crate) struct TtlCache<K, V> {
+    config: CacheConfig,
+    entries: Mutex<HashMap<K, (V, Instant)>>,
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> TtlCache<K, V> {
+    pub(crate) fn new(config: CacheConfig) -> Self {
+        Self {
+            config,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub(crate) fn get(&self, key: &K) -> Option<V> {
+        let mut entries = self.entries.lock().expect("cache poisoned");
+        match entries.get(key) {
+            Some((value, inserted)) if inserted.elapsed() < self.config.ttl => Some(value.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub(crate) fn insert(&self, key: K, value: V) {
+        if self.config.capacity == 0 {
+            return;
+        }
+        let mut entries = self.entries.lock().expect("cache poisoned");
+        if entries.len() >= self.config.capacity && !entries.contains_key(&key) {
+            let ttl = self.config.ttl;
+            entries.retain(|_, (_, inserted)| inserted.elapsed() < ttl);
+            if entries.len() >= self.config.capacity {
+                let oldest = entries
+                    .iter()
+                    .min_by_key(|(_, (_, inserted))| *inserted)
+                    .map(|(k, _)| k.clone());
+                if let Some(oldest) = oldest {
+                    entries.remove(&oldest);
+                }
+            }
+        }
+        entries.insert(key, (value, Instant::now()));
+    }
+
+    pub(crate) fn clear(&self) {
+        self.entries.lock().expect("cache poisoned").clear();
+    }
+}
+
+/// Cached reads of a `LaminarClient`.
+#[derive(Debug)]
+pub(crate) struct ReadCache {
+    pub(crate) coin_infos: TtlCache<TypeTag, CoinInfo>,
+    pub(crate) coins: TtlCache<TypeTag, bool>,
+    pub(crate) coin_registrations: TtlCache<TypeTag, bool>,
+    pub(crate) user_registered: TtlCache<(), bool>,
+    pub(crate) instruments: TtlCache<Market, Instrument>,
+    pub(crate) order_books: TtlCache<AccountAddress, Vec<CreateOrderBookEvent>>,
+}
+
+impl ReadCache {
+    pub(crate) fn new(config: CacheConfig) -> Self {
+        Self {
+            coin_infos: TtlCache::new(config),
+            coins: TtlCache::new(config),
+            coin_registrations: TtlCache::new(config),
+            user_registered: TtlCache::new(config),
+            instruments: TtlCache::new(config),
+            order_books: TtlCache::new(config),
+        }
+    }
+
+    pub(crate) fn config(&self) -> CacheConfig {
+        self.coin_infos.config
+    }
+
+    pub(crate) fn clear(&self) {
+        self.coin_infos.clear();
+        self.coins.clear();
+        self.coin_registrations.clear();
+        self.user_registered.clear();
+        self.instruments.clear();
+        self.order_books.clear();
+    }
+}
@@ -0,0 +1,230 @@
+use crate::tape::{Trade, TradeTape};
+use crate::types::events::FillEvent;
+use crate::types::order::Id;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Width of a candle. Times are in the same unit as `FillEvent::time`, microseconds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Interval {
+    OneSecond,
+    OneMinute,
+    FiveMinutes,
+    OneHour,
+    Custom(u64),
+}
+
+impl Interval {
+    pub fn micros(&self) -> u64 {
+        match self {
+            Interval::OneSecond => 1_000_000,
+            Interval::OneMinute => 60_000_000,
+            Interval::FiveMinutes => 300_000_000,
+            Interval::OneHour => 3_600_000_000,
+            Interval::Custom(micros) => (*micros).max(1),
+        }
+    }
+
+    /// Start of the candle containing `time`.
+    pub fn bucket(&self, time: u64) -> u64 {
+        time - time % self.micros()
+    }
+}
+
+/// OHLCV bar in price and size units of the book.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Candle {
+    pub book_id: Id,
+    pub start: u64,
+    pub open: u64,
+    pub high: u64,
+    pub low: u64,
+    pub close: u64,
+    pub volume: u64,
+    pub trades: u64,
+}
+
+impl Candle {
+    fn new(start: u64, trade: &Trade) -> Self {
+        Self {
+            book_id: trade.book_id.clone(),
+            start,
+            open: trade.price,
+            high: trade.price,
+            low: trade.price,
+            close: trade.price,
+            volume: trade.size,
+            trades: 1,
+        }
+    }
+
+    fn update(&mut self, trade: &Trade) {
+        self.high = self.high.max(trade.price);
+        self.low = self.low.min(trade.price);
+        self.close = trade.price;
+        self.volume += trade.size;
+        self.trades += 1;
+    }
+}
+
+/// Aggregates `FillEvent`s into candles per book.
+///
+/// Fills are reconciled into trades with a `TradeTape`, so each match contributes once.
+/// Fills are expected in time order per book.
+#[derive(Debug)]
+pub struct CandleBuilder {
+    interval: Interval,
+    tape: TradeTape,
+    current: HashMap<Id, Candle>,
+}
+
+impl CandleBuilder {
+    pub fn new(interval: Interval) -> Self {
+        Self {
+            interval,
+            tape: TradeTape::default(),
+            current: HashMap::new(),
+        }
+    }
+
+    pub fn interval(&self) -> Interval {
+        self.interval
+    }
+
+    /// Add a fill. Returns the candle of the fill's book that was closed by it, if any.
+    pub fn observe(&mut self, fill: &FillEvent) -> Option<Candle> {
+        let trade = self.tape.push(fill)?;
+        self.observe_trade(&trade)
+    }
+
+    /// Add a trade. Returns the candle of the trade's book that was closed by it, if any.
+    pub fn observe_trade(&mut self, trade: &Trade) -> Option<Candle> {
+        let start = self.interval.bucket(trade.time);
+        match self.current.get_mut(&trade.book_id) {
+            Some(c) if c.start == start => {
+                c.update(trade);
+                None
+            }
+            Some(c) if c.start > start => None,
+            _ => self
+                .current
+                .insert(trade.book_id.clone(), Candle::new(start, trade)),
+        }
+    }
+
+    /// Candle currently being built for a book.
+    pub fn current(&self, book_id: &Id) -> Option<&Candle> {
+        self.current.get(book_id)
+    }
+
+    /// Close and return all candles currently being built.
+    pub fn flush(&mut self) -> Vec<Candle> {
+        let mut candles = self.current.drain().map(|(_, c)| c).collect::<Vec<_>>();
+        candles.sort_by_key(|c| c.start);
+        candles
+    }
+
+    /// Build candles from historical fills, sorted by start time.
+    pub fn from_fills<'a, I>(interval: Interval, fills: I) -> Vec<Candle>
+    where
+        I: IntoIterator<Item = &'a FillEvent>,
+    {
+        let mut builder = Self::new(interval);
+        let mut candles = TradeTape::from_fills(fills)
+            .iter()
+            .filter_map(|t| builder.observe_trade(t))
+            .collect::<Vec<_>>();
+        candles.extend(builder.flush());
+        candles.sort_by_key(|c| c.start);
+        candles
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::order::Side;
+    use aptos_api_types::{Address, U64};
+    use aptos_sdk::types::account_address::AccountAddress;
+
+    fn id(creation_num: u64) -> Id {
+        Id {
+            creation_num: U64(creation_num),
+            addr: Address::from(AccountAddress::ONE),
+        }
+    }
+
+    // maker and taker fill of one match on book 0
+    fn trade(price: u64, size: u64, time: u64) -> [FillEvent; 2] {
+        let fill = |order, side, is_maker| FillEvent {
+            book_id: id(0),
+            order_id: id(order),
+            side,
+            price,
+            fill_size: size,
+            fee: 0,
+            fee_rate: 0,
+            time,
+            remaining_size: 0,
+            is_maker,
+        };
+        [fill(1, Side::Ask, true), fill(2, Side::Bid, false)]
+    }
+
+    #[test]
+    fn test_bucket() {
+        assert_eq!(Interval::OneMinute.bucket(61_000_000), 60_000_000);
+        assert_eq!(Interval::OneSecond.bucket(999_999), 0);
+        assert_eq!(Interval::Custom(0).micros(), 1);
+    }
+
+    #[test]
+    fn test_candle_closed_by_next_interval() {
+        let mut builder = CandleBuilder::new(Interval::OneSecond);
+        for (price, size, time) in [(100, 1, 0), (105, 2, 500_000), (95, 3, 700_000)] {
+            for fill in &trade(price, size, time) {
+                assert!(builder.observe(fill).is_none());
+            }
+        }
+        let current = builder.current(&id(0)).unwrap();
+        assert_eq!(current.trades, 3);
+        assert_eq!(current.volume, 6);
+
+        let closed = builder.observe(&trade(101, 1, 1_000_000)[0]).unwrap();
+        assert_eq!(closed.start, 0);
+        assert_eq!(
+            (closed.open, closed.high, closed.low, closed.close),
+            (100, 105, 95, 95)
+        );
+        assert_eq!(builder.current(&id(0)).unwrap().start, 1_000_000);
+    }
+
+    #[test]
+    fn test_late_trade_is_ignored() {
+        let mut builder = CandleBuilder::new(Interval::OneSecond);
+        builder.observe(&trade(100, 1, 2_000_000)[0]);
+        assert!(builder.observe(&trade(90, 1, 1_000_000)[0]).is_none());
+
+        let current = builder.current(&id(0)).unwrap();
+        assert_eq!(current.low, 100);
+        assert_eq!(current.trades, 1);
+    }
+
+    #[test]
+    fn test_from_fills() {
+        let fills = [
+            trade(110, 1, 61_000_000),
+            trade(100, 2, 1_000_000),
+            trade(102, 1, 59_000_000),
+        ]
+        .concat();
+        let candles = CandleBuilder::from_fills(Interval::OneMinute, &fills);
+
+        assert_eq!(candles.len(), 2);
+        assert_eq!(candles[0].start, 0);
+        assert_eq!((candles[0].open, candles[0].close), (100, 102));
+        assert_eq!((candles[0].volume, candles[0].trades), (3, 2));
+        assert_eq!(candles[1].start, 60_000_000);
+        assert_eq!(candles[1].open, 110);
+    }
+}
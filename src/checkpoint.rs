@@ -0,0 +1,119 @@
+use crate::error::Result;
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Position of a market-wide event consumer in the ledger.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventCursor {
+    /// Version of the next transaction to scan.
+    pub version: u64,
+}
+
+/// Store of `EventCursor`s, so an event consumer resumes where it left off after a restart.
+///
+/// Implement it to keep cursors in the same database as the processed events, then saving the
+/// cursor in the transaction that stores the events processes every event exactly once.
+pub trait Checkpoint: Send + Sync {
+    /// Cursor saved under `key`, `None` if nothing was saved yet.
+    fn load(&self, key: &str) -> Result<Option<EventCursor>>;
+
+    fn save(&self, key: &str, cursor: &EventCursor) -> Result<()>;
+}
+
+/// Checkpoint kept in memory, e.g. for tests.
+#[derive(Debug, Default)]
+pub struct MemoryCheckpoint {
+    cursors: Mutex<HashMap<String, EventCursor>>,
+}
+
+impl MemoryCheckpoint {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Checkpoint for MemoryCheckpoint {
+    fn load(&self, key: &str) -> Result<Option<EventCursor>> {
+        let cursors = self.cursors.lock().expect("checkpoint lock poisoned");
+        Ok(cursors.get(key).copied())
+    }
+
+    fn save(&self, key: &str, cursor: &EventCursor) -> Result<()> {
+        let mut cursors = self.cursors.lock().expect("checkpoint lock poisoned");
+        cursors.insert(key.to_string(), *cursor);
+        Ok(())
+    }
+}
+
+/// Checkpoint persisted as a JSON object of cursors by key.
+///
+/// Every save writes and syncs a temporary file, then renames it over the previous one, so a
+/// crash or power loss leaves either the previous or the new cursors.
+#[derive(Debug)]
+pub struct FileCheckpoint {
+    path: PathBuf,
+    cursors: Mutex<HashMap<String, EventCursor>>,
+}
+
+impl FileCheckpoint {
+    /// Open a checkpoint file, loading the cursors saved in it.
+    ///
+    /// # Arguments:
+    ///
+    /// * `path` - Path of the checkpoint file, created on the first save if missing.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let cursors = if path.exists() {
+            let data = fs::read(&path).context("failed reading checkpoint file")?;
+            serde_json::from_slice(&data).context("invalid checkpoint file")?
+        } else {
+            HashMap::new()
+        };
+
+        Ok(Self {
+            path,
+            cursors: Mutex::new(cursors),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Checkpoint for FileCheckpoint {
+    fn load(&self, key: &str) -> Result<Option<EventCursor>> {
+        let cursors = self.cursors.lock().expect("checkpoint lock poisoned");
+        Ok(cursors.get(key).copied())
+    }
+
+    fn save(&self, key: &str, cursor: &EventCursor) -> Result<()> {
+        let mut cursors = self.cursors.lock().expect("checkpoint lock poisoned");
+        cursors.insert(key.to_string(), *cursor);
+
+        let tmp = self.path.with_extension("tmp");
+        let data = serde_json::to_vec_pretty(&*cursors).context("failed encoding checkpoint")?;
+        let mut file = fs::File::create(&tmp).context("failed creating checkpoint file")?;
+        file.write_all(&data)
+            .and_then(|_| file.sync_all())
+            .context("failed writing checkpoint file")?;
+        fs::rename(&tmp, &self.path).context("failed replacing checkpoint file")?;
+        // the rename itself only survives a crash once the directory is synced
+        #[cfg(unix)]
+        {
+            let dir = match self.path.parent() {
+                Some(dir) if !dir.as_os_str().is_empty() => dir,
+                _ => Path::new("."),
+            };
+            fs::File::open(dir)
+                .and_then(|dir| dir.sync_all())
+                .context("failed syncing checkpoint directory")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,206 @@
+use crate::error::{LaminarError, Result};
+use crate::network::Network;
+use crate::LaminarClient;
+use aptos_sdk::crypto::ed25519::{Ed25519PrivateKey, Ed25519PublicKey};
+use aptos_sdk::crypto::ValidCryptoMaterialStringExt;
+use aptos_sdk::types::account_address::AccountAddress;
+use aptos_sdk::types::transaction::authenticator::AuthenticationKey;
+use aptos_sdk::types::{AccountKey, LocalAccount};
+use reqwest::Url;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::str::FromStr;
+
+/// Preset `Network`, e.g. `testnet`, supplying defaults for the node url and laminar address.
+pub const NETWORK_VAR: &str = "LAMINAR_NETWORK";
+pub const NODE_URL_VAR: &str = "LAMINAR_NODE_URL";
+pub const LAMINAR_ADDRESS_VAR: &str = "LAMINAR_ADDRESS";
+/// Hex encoded Ed25519 private key of the account.
+pub const PRIVATE_KEY_VAR: &str = "APTOS_PRIVATE_KEY";
+/// Address of the account, derived from the private key if unset.
+pub const ACCOUNT_ADDRESS_VAR: &str = "APTOS_ACCOUNT_ADDRESS";
+/// Profile of the aptos cli config to take the account from when no private key is set.
+pub const PROFILE_VAR: &str = "APTOS_PROFILE";
+/// Path of the aptos cli config, `.aptos/config.yaml` if unset.
+pub const CONFIG_PATH_VAR: &str = "APTOS_CONFIG_PATH";
+pub const DEFAULT_CONFIG_PATH: &str = ".aptos/config.yaml";
+
+/// Profile of an aptos cli config. Fields are optional as profiles may be created for
+/// hardware wallets or without a rest url.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct AptosProfile {
+    pub private_key: Option<String>,
+    pub public_key: Option<String>,
+    pub account: Option<String>,
+    pub rest_url: Option<String>,
+    pub faucet_url: Option<String>,
+}
+
+/// Config file in the default format created by the aptos cli, e.g. `.aptos/config.yaml`.
+#[derive(Deserialize, Debug, Clone)]
+pub struct AptosConfig {
+    #[serde(skip)]
+    path: String,
+    #[serde(default)]
+    profiles: BTreeMap<String, AptosProfile>,
+}
+
+impl AptosConfig {
+    pub fn load(path: &str) -> Result<Self> {
+        let file = File::open(path).map_err(|source| LaminarError::ConfigFile {
+            path: path.to_string(),
+            source,
+        })?;
+        let mut config = serde_yaml::from_reader::<File, Self>(file).map_err(|e| {
+            LaminarError::InvalidConfig {
+                path: path.to_string(),
+                reason: e.to_string(),
+            }
+        })?;
+        config.path = path.to_string();
+        Ok(config)
+    }
+
+    /// Parse a config from its contents, e.g. in the browser where there are no files. The
+    /// config's path is left empty.
+    pub fn from_yaml(yaml: &str) -> Result<Self> {
+        serde_yaml::from_str::<Self>(yaml).map_err(|e| LaminarError::InvalidConfig {
+            path: String::new(),
+            reason: e.to_string(),
+        })
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Names of the profiles in the config, sorted.
+    pub fn profile_names(&self) -> Vec<&str> {
+        self.profiles.keys().map(|p| p.as_str()).collect()
+    }
+
+    pub fn profiles(&self) -> &BTreeMap<String, AptosProfile> {
+        &self.profiles
+    }
+
+    pub fn profile(&self, name: &str) -> Result<&AptosProfile> {
+        self.profiles
+            .get(name)
+            .ok_or_else(|| LaminarError::ProfileNotFound {
+                path: self.path.clone(),
+                profile: name.to_string(),
+            })
+    }
+
+    /// Account address and private key of a profile.
+    pub(crate) fn credentials(&self, name: &str) -> Result<(&str, &str)> {
+        let profile = self.profile(name)?;
+        let missing = |field: &str| LaminarError::InvalidConfig {
+            path: self.path.clone(),
+            reason: format!("profile {} has no {}", name, field),
+        };
+        let account = profile
+            .account
+            .as_deref()
+            .ok_or_else(|| missing("account"))?;
+        let private_key = profile
+            .private_key
+            .as_deref()
+            .ok_or_else(|| missing("private_key"))?;
+        Ok((account, private_key))
+    }
+
+    /// Load the account of a profile.
+    pub fn account(&self, name: &str) -> Result<LocalAccount> {
+        let (account, private_key) = self.credentials(name)?;
+        let address =
+            AccountAddress::from_hex_literal(account).map_err(|e| LaminarError::InvalidConfig {
+                path: self.path.clone(),
+                reason: format!("invalid account of profile {}: {}", name, e),
+            })?;
+        let private_key = Ed25519PrivateKey::from_encoded_string(private_key)
+            .map_err(|e| LaminarError::InvalidKey(e.to_string()))?;
+        Ok(LocalAccount::new(address, AccountKey::from(private_key), 0))
+    }
+}
+
+/// Names of the profiles in an aptos cli config file.
+pub fn list_profiles(path: &str) -> Result<Vec<String>> {
+    let config = AptosConfig::load(path)?;
+    Ok(config
+        .profile_names()
+        .into_iter()
+        .map(String::from)
+        .collect())
+}
+
+// unset and empty variables are treated the same, as containers often set empty defaults
+fn var(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|v| !v.trim().is_empty())
+}
+
+fn missing(name: &str) -> LaminarError {
+    LaminarError::InvalidInput(format!("environment variable {} is not set", name))
+}
+
+fn parse_address(name: &str, value: &str) -> Result<AccountAddress> {
+    AccountAddress::from_hex_literal(value)
+        .map_err(|e| LaminarError::InvalidInput(format!("{}: {}", name, e)))
+}
+
+fn account_from_env() -> Result<LocalAccount> {
+    let (private_key, address) = match (var(PRIVATE_KEY_VAR), var(PROFILE_VAR)) {
+        (Some(key), _) => (key, var(ACCOUNT_ADDRESS_VAR)),
+        (None, Some(profile)) => {
+            let path = var(CONFIG_PATH_VAR).unwrap_or_else(|| DEFAULT_CONFIG_PATH.to_string());
+            let config = AptosConfig::load(&path)?;
+            let (account, private_key) = config.credentials(&profile)?;
+            (private_key.to_string(), Some(account.to_string()))
+        }
+        (None, None) => return Err(missing(PRIVATE_KEY_VAR)),
+    };
+
+    let private_key = Ed25519PrivateKey::from_encoded_string(private_key.trim())
+        .map_err(|e| LaminarError::InvalidKey(e.to_string()))?;
+    let address = match address {
+        Some(address) => parse_address(ACCOUNT_ADDRESS_VAR, &address)?,
+        None => AuthenticationKey::ed25519(&Ed25519PublicKey::from(&private_key)).derived_address(),
+    };
+    Ok(LocalAccount::new(address, AccountKey::from(private_key), 0))
+}
+
+impl LaminarClient {
+    /// Connect using environment variables only, for deployments without config files.
+    ///
+    /// * `LAMINAR_NETWORK` - Optional preset `Network` providing the defaults below.
+    /// * `LAMINAR_NODE_URL` - Url of the aptos node, required without a network.
+    /// * `LAMINAR_ADDRESS` - Laminar address, required without a known deployment.
+    /// * `APTOS_PRIVATE_KEY` - Hex encoded private key of the account.
+    /// * `APTOS_ACCOUNT_ADDRESS` - Address of the account, derived from the key if unset.
+    /// * `APTOS_PROFILE` - Profile of the aptos cli config used if no private key is set,
+    /// read from `APTOS_CONFIG_PATH` or `.aptos/config.yaml`.
+    pub async fn from_env() -> Result<Self> {
+        let network = var(NETWORK_VAR)
+            .map(|n| Network::from_str(&n))
+            .transpose()?;
+        let node_url = match var(NODE_URL_VAR) {
+            Some(url) => Url::parse(&url)
+                .map_err(|e| LaminarError::InvalidInput(format!("{}: {}", NODE_URL_VAR, e)))?,
+            None => network
+                .map(|n| n.node_url())
+                .ok_or_else(|| missing(NODE_URL_VAR))?,
+        };
+        let laminar = match var(LAMINAR_ADDRESS_VAR) {
+            Some(address) => parse_address(LAMINAR_ADDRESS_VAR, &address)?,
+            None => network
+                .and_then(|n| n.laminar_address())
+                .ok_or_else(|| missing(LAMINAR_ADDRESS_VAR))?,
+        };
+        let account = account_from_env()?;
+
+        let mut client = Self::connect(node_url, laminar, account).await?;
+        client.set_network(network);
+        Ok(client)
+    }
+}
@@ -0,0 +1,176 @@
+//! Polars `DataFrame` conversions of SDK results, for research notebooks.
+//!
+//! Columns follow the fields of the converted types. Ids are written as
+//! `{address}:{creation_number}` strings, sides and times in force by name, times as
+//! microsecond datetimes and prices and sizes in the units of the book.
+
+use crate::candles::Candle;
+use crate::error::Result;
+use crate::types::events::{FillEvent, PlaceOrderEvent};
+use crate::types::l2::L2Book;
+use crate::types::order::Side;
+use anyhow::Context;
+use polars::prelude::{DataFrame, DataType, NamedFrom, Series, TimeUnit};
+
+/// Conversion into a Polars `DataFrame`, one row per record.
+pub trait ToDataFrame {
+    fn to_dataframe(&self) -> Result<DataFrame>;
+}
+
+fn frame(columns: Vec<Series>) -> Result<DataFrame> {
+    let df = DataFrame::new(columns).context("failed building dataframe")?;
+    Ok(df)
+}
+
+fn datetimes(name: &str, micros: Vec<u64>) -> Result<Series> {
+    let micros = micros.into_iter().map(|t| t as i64).collect::<Vec<_>>();
+    let series = Series::new(name, micros)
+        .cast(&DataType::Datetime(TimeUnit::Microseconds, None))
+        .context("failed casting timestamps")?;
+    Ok(series)
+}
+
+impl ToDataFrame for [FillEvent] {
+    fn to_dataframe(&self) -> Result<DataFrame> {
+        frame(vec![
+            Series::new(
+                "book_id",
+                self.iter()
+                    .map(|f| f.book_id.to_string())
+                    .collect::<Vec<_>>(),
+            ),
+            Series::new(
+                "order_id",
+                self.iter()
+                    .map(|f| f.order_id.to_string())
+                    .collect::<Vec<_>>(),
+            ),
+            Series::new(
+                "side",
+                self.iter()
+                    .map(|f| format!("{:?}", f.side))
+                    .collect::<Vec<_>>(),
+            ),
+            Series::new("price", self.iter().map(|f| f.price).collect::<Vec<_>>()),
+            Series::new(
+                "fill_size",
+                self.iter().map(|f| f.fill_size).collect::<Vec<_>>(),
+            ),
+            Series::new("fee", self.iter().map(|f| f.fee).collect::<Vec<_>>()),
+            Series::new(
+                "fee_rate",
+                self.iter().map(|f| f.fee_rate).collect::<Vec<_>>(),
+            ),
+            datetimes("time", self.iter().map(|f| f.time).collect())?,
+            Series::new(
+                "remaining_size",
+                self.iter().map(|f| f.remaining_size).collect::<Vec<_>>(),
+            ),
+            Series::new(
+                "is_maker",
+                self.iter().map(|f| f.is_maker).collect::<Vec<_>>(),
+            ),
+        ])
+    }
+}
+
+impl ToDataFrame for [PlaceOrderEvent] {
+    fn to_dataframe(&self) -> Result<DataFrame> {
+        frame(vec![
+            Series::new(
+                "book_id",
+                self.iter()
+                    .map(|p| p.book_id.to_string())
+                    .collect::<Vec<_>>(),
+            ),
+            Series::new(
+                "order_id",
+                self.iter()
+                    .map(|p| p.order_id.to_string())
+                    .collect::<Vec<_>>(),
+            ),
+            Series::new(
+                "side",
+                self.iter()
+                    .map(|p| format!("{:?}", p.side))
+                    .collect::<Vec<_>>(),
+            ),
+            Series::new("price", self.iter().map(|p| p.price).collect::<Vec<_>>()),
+            Series::new("size", self.iter().map(|p| p.size).collect::<Vec<_>>()),
+            Series::new(
+                "time_in_force",
+                self.iter()
+                    .map(|p| format!("{:?}", p.time_in_force))
+                    .collect::<Vec<_>>(),
+            ),
+            Series::new(
+                "post_only",
+                self.iter().map(|p| p.post_only).collect::<Vec<_>>(),
+            ),
+            datetimes("time", self.iter().map(|p| p.time).collect())?,
+        ])
+    }
+}
+
+impl ToDataFrame for [Candle] {
+    fn to_dataframe(&self) -> Result<DataFrame> {
+        frame(vec![
+            Series::new(
+                "book_id",
+                self.iter()
+                    .map(|c| c.book_id.to_string())
+                    .collect::<Vec<_>>(),
+            ),
+            datetimes("start", self.iter().map(|c| c.start).collect())?,
+            Series::new("open", self.iter().map(|c| c.open).collect::<Vec<_>>()),
+            Series::new("high", self.iter().map(|c| c.high).collect::<Vec<_>>()),
+            Series::new("low", self.iter().map(|c| c.low).collect::<Vec<_>>()),
+            Series::new("close", self.iter().map(|c| c.close).collect::<Vec<_>>()),
+            Series::new("volume", self.iter().map(|c| c.volume).collect::<Vec<_>>()),
+            Series::new("trades", self.iter().map(|c| c.trades).collect::<Vec<_>>()),
+        ])
+    }
+}
+
+/// Levels of a series of snapshots, one row per level. `level` is the position of the level
+/// on its side, 0 being the best price.
+impl ToDataFrame for [L2Book] {
+    fn to_dataframe(&self) -> Result<DataFrame> {
+        let mut book_id = vec![];
+        let mut ledger_version = vec![];
+        let mut side = vec![];
+        let mut level = vec![];
+        let mut price = vec![];
+        let mut total_size = vec![];
+        let mut order_count = vec![];
+        for book in self {
+            for s in [Side::Bid, Side::Ask] {
+                for (i, l) in book.levels(s).iter().enumerate() {
+                    book_id.push(book.book_id.to_string());
+                    ledger_version.push(book.ledger_version);
+                    side.push(format!("{:?}", s));
+                    level.push(i as u32);
+                    price.push(l.price);
+                    total_size.push(l.total_size);
+                    order_count.push(l.order_count);
+                }
+            }
+        }
+
+        frame(vec![
+            Series::new("book_id", book_id),
+            Series::new("ledger_version", ledger_version),
+            Series::new("side", side),
+            Series::new("level", level),
+            Series::new("price", price),
+            Series::new("total_size", total_size),
+            Series::new("order_count", order_count),
+        ])
+    }
+}
+
+impl ToDataFrame for L2Book {
+    fn to_dataframe(&self) -> Result<DataFrame> {
+        std::slice::from_ref(self).to_dataframe()
+    }
+}
@@ -0,0 +1,212 @@
+use anyhow::{anyhow, Context, Result};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::io::Write;
+
+/// Encodes records for exports and sinks.
+///
+/// Records are converted to a `serde_json::Value` before encoding so encoders stay object
+/// safe and can be shared as `Box<dyn RecordEncoder>` between sinks.
+pub trait RecordEncoder: Send + Sync {
+    /// MIME type of the encoded records, e.g. used as a message header by sinks.
+    fn content_type(&self) -> &'static str;
+
+    fn encode_value(&self, record: &Value) -> Result<Vec<u8>>;
+
+    /// Bytes written once at the start of a file, e.g. a CSV header row.
+    fn header(&self) -> Option<Vec<u8>> {
+        None
+    }
+
+    /// Bytes written after every record when records are concatenated into a file.
+    fn delimiter(&self) -> &'static [u8] {
+        b""
+    }
+}
+
+/// Encode any serializable record with the given encoder.
+pub fn encode<T: Serialize + ?Sized>(encoder: &dyn RecordEncoder, record: &T) -> Result<Vec<u8>> {
+    let value = serde_json::to_value(record).context("failed serializing record")?;
+    encoder.encode_value(&value)
+}
+
+/// Write records to `writer`, including the encoder header and delimiters.
+pub fn write_records<W: Write, T: Serialize>(
+    mut writer: W,
+    encoder: &dyn RecordEncoder,
+    records: &[T],
+) -> Result<()> {
+    if let Some(header) = encoder.header() {
+        writer.write_all(&header)?;
+    }
+    for r in records {
+        writer.write_all(&encode(encoder, r)?)?;
+        writer.write_all(encoder.delimiter())?;
+    }
+
+    Ok(())
+}
+
+/// Newline delimited JSON.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct JsonEncoder;
+
+impl RecordEncoder for JsonEncoder {
+    fn content_type(&self) -> &'static str {
+        "application/json"
+    }
+
+    fn encode_value(&self, record: &Value) -> Result<Vec<u8>> {
+        serde_json::to_vec(record).context("failed encoding json")
+    }
+
+    fn delimiter(&self) -> &'static [u8] {
+        b"\n"
+    }
+}
+
+/// CSV rows with a fixed set of columns. Nested objects are flattened with `.` separated
+/// column names, e.g. `book_id.creation_num`.
+#[derive(Clone, Debug)]
+pub struct CsvEncoder {
+    columns: Vec<String>,
+}
+
+impl CsvEncoder {
+    pub fn new(columns: Vec<String>) -> Self {
+        Self { columns }
+    }
+
+    /// Derive the columns from the flattened fields of a sample record.
+    pub fn for_record<T: Serialize>(sample: &T) -> Result<Self> {
+        let value = serde_json::to_value(sample).context("failed serializing record")?;
+        let mut flat = Map::new();
+        flatten("", &value, &mut flat);
+        Ok(Self::new(flat.keys().cloned().collect()))
+    }
+
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+
+    fn row<'a>(fields: impl Iterator<Item = &'a str>) -> Vec<u8> {
+        let escaped = fields
+            .map(|f| {
+                if f.contains([',', '"', '\n', '\r']) {
+                    format!("\"{}\"", f.replace('"', "\"\""))
+                } else {
+                    f.to_string()
+                }
+            })
+            .collect::<Vec<_>>();
+        format!("{}\n", escaped.join(",")).into_bytes()
+    }
+}
+
+fn flatten(prefix: &str, value: &Value, out: &mut Map<String, Value>) {
+    match value {
+        Value::Object(map) => {
+            for (k, v) in map {
+                let key = if prefix.is_empty() {
+                    k.clone()
+                } else {
+                    format!("{}.{}", prefix, k)
+                };
+                flatten(&key, v, out);
+            }
+        }
+        v => {
+            out.insert(prefix.to_string(), v.clone());
+        }
+    }
+}
+
+impl RecordEncoder for CsvEncoder {
+    fn content_type(&self) -> &'static str {
+        "text/csv"
+    }
+
+    fn encode_value(&self, record: &Value) -> Result<Vec<u8>> {
+        let mut flat = Map::new();
+        flatten("", record, &mut flat);
+        let fields = self
+            .columns
+            .iter()
+            .map(|c| match flat.get(c) {
+                None | Some(Value::Null) => String::new(),
+                Some(Value::String(s)) => s.clone(),
+                Some(v) => v.to_string(),
+            })
+            .collect::<Vec<_>>();
+        Ok(Self::row(fields.iter().map(|f| f.as_str())))
+    }
+
+    fn header(&self) -> Option<Vec<u8>> {
+        Some(Self::row(self.columns.iter().map(|c| c.as_str())))
+    }
+}
+
+/// MessagePack, records are written as maps keyed by field name.
+#[cfg(feature = "msgpack")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MessagePackEncoder;
+
+#[cfg(feature = "msgpack")]
+impl RecordEncoder for MessagePackEncoder {
+    fn content_type(&self) -> &'static str {
+        "application/msgpack"
+    }
+
+    fn encode_value(&self, record: &Value) -> Result<Vec<u8>> {
+        rmp_serde::to_vec_named(record).context("failed encoding msgpack")
+    }
+}
+
+/// Avro datum encoding against a user provided schema.
+#[cfg(feature = "avro")]
+#[derive(Clone, Debug)]
+pub struct AvroEncoder {
+    schema: apache_avro::Schema,
+}
+
+#[cfg(feature = "avro")]
+impl AvroEncoder {
+    pub fn new(schema: apache_avro::Schema) -> Self {
+        Self { schema }
+    }
+
+    pub fn parse_schema(schema: &str) -> Result<Self> {
+        let schema = apache_avro::Schema::parse_str(schema).context("invalid avro schema")?;
+        Ok(Self::new(schema))
+    }
+
+    pub fn schema(&self) -> &apache_avro::Schema {
+        &self.schema
+    }
+}
+
+#[cfg(feature = "avro")]
+impl RecordEncoder for AvroEncoder {
+    fn content_type(&self) -> &'static str {
+        "avro/binary"
+    }
+
+    fn encode_value(&self, record: &Value) -> Result<Vec<u8>> {
+        let value = apache_avro::to_value(record)
+            .and_then(|v| v.resolve(&self.schema))
+            .map_err(|e| anyhow!("record does not match avro schema: {}", e))?;
+        apache_avro::to_avro_datum(&self.schema, value)
+            .map_err(|e| anyhow!("failed encoding avro: {}", e))
+    }
+}
+
+/// Look up a built-in encoder by name: `json`, `csv`, `msgpack` or `avro`.
+/// Encoders that need configuration (`csv`, `avro`) can't be created this way.
+pub fn encoder_by_name(name: &str) -> Result<Box<dyn RecordEncoder>> {
+    match name {
+        "json" => Ok(Box::new(JsonEncoder)),
+        #[cfg(feature = "msgpack")]
+        "msgpack" => Ok(Box::new(MessagePackEncoder)),
+        _ => Err(anyhow!("no default encoder named: {}", name)),
+    }
+}
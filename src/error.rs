@@ -0,0 +1,193 @@
+use crate::submission::Submission;
+use crate::types::events::CancelOrderEvent;
+use aptos_api_types::{AptosError, HashValue};
+use aptos_sdk::bcs;
+use aptos_sdk::rest_client::error::RestError;
+use aptos_sdk::types::account_address::AccountAddress;
+use thiserror::Error;
+
+pub type Result<T> = std::result::Result<T, LaminarError>;
+
+// `std::error` category of abort codes for a missing resource or entry, e.g. an order
+const ERROR_NOT_FOUND: u64 = 0x6;
+
+/// Errors returned by `LaminarClient`.
+#[derive(Debug, Error)]
+pub enum LaminarError {
+    /// A request to the Aptos node failed.
+    #[error("{context}: {source}")]
+    Rest {
+        context: String,
+        #[source]
+        source: RestError,
+    },
+    /// The transaction was rejected because the account sequence number is out of date.
+    #[error("sequence number conflict: {}", .0.message)]
+    SequenceNumber(AptosError),
+    /// The transaction was rejected by the node before execution.
+    #[error("transaction rejected: {}", .0.message)]
+    Rejected(AptosError),
+    /// The transaction was executed and aborted in a Move module.
+    #[error("transaction {hash} aborted in {location} with code {code}")]
+    MoveAbort {
+        hash: HashValue,
+        location: String,
+        code: u64,
+        vm_status: String,
+    },
+    /// The transaction was executed and failed for a reason other than a Move abort.
+    #[error("transaction {hash} failed: {vm_status}")]
+    ExecutionFailure { hash: HashValue, vm_status: String },
+    /// A transaction could not be confirmed before its deadline.
+    /// `hash` is set if the transaction was accepted by the node and may still be committed.
+    #[error("deadline exceeded, pending transaction: {hash:?}")]
+    DeadlineExceeded { hash: Option<HashValue> },
+    /// The outcome of a transaction could not be established. It may still be committed until
+    /// it expires, see `LaminarClient::submission_status`.
+    #[error("transaction {} unconfirmed", .0.hash)]
+    Unconfirmed(Box<Submission>),
+    #[error("failed deserializing {what}: {source}")]
+    Deserialization {
+        what: String,
+        #[source]
+        source: serde_json::Error,
+    },
+    #[error("book not found: {0}")]
+    BookNotFound(String),
+    #[error("not found: {0}")]
+    NotFound(String),
+    #[error("invalid input: {0}")]
+    InvalidInput(String),
+    /// A config file could not be read.
+    #[error("failed reading config file {path}: {source}")]
+    ConfigFile {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+    /// A config file could be read but not parsed.
+    #[error("invalid config file {path}: {reason}")]
+    InvalidConfig { path: String, reason: String },
+    /// The requested profile is not in the config file.
+    #[error("profile {profile} missing in config file {path}")]
+    ProfileNotFound { path: String, profile: String },
+    #[error("invalid private key: {0}")]
+    InvalidKey(String),
+    /// The payload was blocked by the client's `RiskManager`.
+    #[error("risk limit exceeded: {0}")]
+    RiskLimit(String),
+    /// `replace_order` cancelled the order but failed placing the new one, so the account has
+    /// no order in its place.
+    #[error("order {} cancelled but its replacement failed: {source}", .cancelled.order_id)]
+    ReplaceFailed {
+        cancelled: Box<CancelOrderEvent>,
+        #[source]
+        source: Box<LaminarError>,
+    },
+    #[error("failed serializing payload: {0}")]
+    Bcs(#[from] bcs::Error),
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+impl From<RestError> for LaminarError {
+    fn from(source: RestError) -> Self {
+        Self::rest(source, "request failed")
+    }
+}
+
+impl LaminarError {
+    pub(crate) fn rest(source: RestError, context: impl Into<String>) -> Self {
+        Self::Rest {
+            context: context.into(),
+            source,
+        }
+    }
+
+    pub(crate) fn deserialization(source: serde_json::Error, what: impl Into<String>) -> Self {
+        Self::Deserialization {
+            what: what.into(),
+            source,
+        }
+    }
+
+    /// Build the error for a committed transaction that did not succeed.
+    pub(crate) fn from_vm_status(hash: HashValue, vm_status: &str) -> Self {
+        match parse_move_abort(vm_status) {
+            Some((location, code)) => Self::MoveAbort {
+                hash,
+                location,
+                code,
+                vm_status: vm_status.to_string(),
+            },
+            None => Self::ExecutionFailure {
+                hash,
+                vm_status: vm_status.to_string(),
+            },
+        }
+    }
+
+    /// Move abort code, if the transaction aborted.
+    pub fn abort_code(&self) -> Option<u64> {
+        match self {
+            Self::MoveAbort { code, .. } => Some(*code),
+            _ => None,
+        }
+    }
+
+    /// Whether the transaction aborted in a module published at `address`, e.g. in the Laminar
+    /// modules rather than in the framework.
+    pub fn is_abort_in(&self, address: &AccountAddress) -> bool {
+        matches!(self, Self::MoveAbort { location, .. }
+            if location
+                .split_once("::")
+                .and_then(|(addr, _)| AccountAddress::from_hex_literal(addr).ok())
+                .as_ref()
+                == Some(address))
+    }
+
+    /// Whether the abort code is in the `std::error` not found category, as raised for an
+    /// order that is no longer in the book.
+    pub fn is_not_found_abort(&self) -> bool {
+        matches!(self.abort_code(), Some(code) if code >> 16 == ERROR_NOT_FOUND)
+    }
+
+    /// Whether the transaction may have been committed although the call failed, so its
+    /// effect must not be taken as absent.
+    pub fn is_outcome_unknown(&self) -> bool {
+        match self {
+            Self::Unconfirmed(_) | Self::DeadlineExceeded { hash: Some(_) } => true,
+            Self::ReplaceFailed { source, .. } => source.is_outcome_unknown(),
+            _ => false,
+        }
+    }
+
+    /// Whether resubmitting the same payload may succeed.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            Self::Rest { .. } | Self::SequenceNumber(_) | Self::Rejected(_)
+        )
+    }
+}
+
+/// Parse location and abort code out of a vm status such as
+/// `Move abort in 0x1::coin: EINSUFFICIENT_BALANCE(0x10006): Not enough coins`.
+fn parse_move_abort(vm_status: &str) -> Option<(String, u64)> {
+    let rest = vm_status
+        .strip_prefix("Move abort in ")
+        .or_else(|| vm_status.strip_prefix("Move abort by "))?;
+    let (location, rest) = rest.split_once(": ")?;
+    let code = match rest.find("0x") {
+        Some(i) => {
+            let hex = rest[i + 2..]
+                .chars()
+                .take_while(|c| c.is_ascii_hexdigit())
+                .collect::<String>();
+            u64::from_str_radix(&hex, 16).ok()?
+        }
+        None => rest.split_whitespace().next()?.parse().ok()?,
+    };
+
+    Some((location.to_string(), code))
+}
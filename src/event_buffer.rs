@@ -0,0 +1,257 @@
+//! Event polling that reuses its buffers between pages.
+//!
+//! Pages fetched through the aptos rest client are parsed into a `serde_json::Value` first,
+//! allocating every stringified integer and address of every event before it is parsed again.
+//! Here the response body is read into an `EventBuffer` and the events are deserialized from
+//! it directly, borrowing their strings from the body.
+
+use crate::error::{LaminarError, Result};
+use crate::query::{EventIds, EventQuery};
+use crate::rate_limit::Endpoint;
+use crate::types::deserialize_from_str;
+use crate::types::events::{EventKey, EventStoreField, LaminarEventEnvelope};
+use crate::{LaminarClient, EVENT_PAGE_SIZE};
+use aptos_sdk::rest_client::error::RestError;
+use aptos_sdk::types::account_address::AccountAddress;
+use serde::de::{DeserializeOwned, DeserializeSeed, Error, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+use std::fmt::Formatter;
+
+/// Storage of the response bodies of event pages, reused by every fetch it is passed to.
+#[derive(Debug, Default)]
+pub struct EventBuffer {
+    body: Vec<u8>,
+}
+
+impl EventBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Buffer starting with room for `bytes` of response body.
+    pub fn with_capacity(bytes: usize) -> Self {
+        Self {
+            body: Vec::with_capacity(bytes),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.body.capacity()
+    }
+}
+
+#[derive(Deserialize)]
+struct RawGuid<'a> {
+    #[serde(deserialize_with = "deserialize_from_str")]
+    creation_number: u64,
+    account_address: &'a str,
+}
+
+// event as returned by the events API
+#[derive(Deserialize)]
+#[serde(bound = "E: DeserializeOwned")]
+struct RawEvent<'a, E> {
+    #[serde(deserialize_with = "deserialize_from_str")]
+    version: u64,
+    #[serde(borrow)]
+    guid: RawGuid<'a>,
+    #[serde(deserialize_with = "deserialize_from_str")]
+    sequence_number: u64,
+    data: E,
+}
+
+// appends the events of a page to a vec instead of collecting them in a new one
+struct PageSeed<'v, E> {
+    events: &'v mut Vec<LaminarEventEnvelope<E>>,
+}
+
+impl<'de, 'v, E: DeserializeOwned> DeserializeSeed<'de> for PageSeed<'v, E> {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> std::result::Result<(), D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, 'v, E: DeserializeOwned> Visitor<'de> for PageSeed<'v, E> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        formatter.write_str("a list of events")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> std::result::Result<(), A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        while let Some(e) = seq.next_element::<RawEvent<E>>()? {
+            let account_address = AccountAddress::from_hex_literal(e.guid.account_address)
+                .map_err(|_| Error::custom("failed parsing address"))?;
+            self.events.push(LaminarEventEnvelope {
+                key: EventKey {
+                    account_address,
+                    creation_number: e.guid.creation_number,
+                },
+                sequence_number: e.sequence_number,
+                version: e.version,
+                event: e.data,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Parse a page of events returned by the events API, appending them to `events`.
+pub(crate) fn parse_event_page<E: DeserializeOwned>(
+    body: &[u8],
+    events: &mut Vec<LaminarEventEnvelope<E>>,
+) -> serde_json::Result<()> {
+    let mut deserializer = serde_json::Deserializer::from_slice(body);
+    PageSeed { events }.deserialize(&mut deserializer)?;
+    deserializer.end()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+async fn read_body(
+    mut response: reqwest::Response,
+    body: &mut Vec<u8>,
+) -> std::result::Result<(), reqwest::Error> {
+    while let Some(chunk) = response.chunk().await? {
+        body.extend_from_slice(&chunk);
+    }
+    Ok(())
+}
+
+// responses can't be read in chunks in the browser
+#[cfg(target_arch = "wasm32")]
+async fn read_body(
+    response: reqwest::Response,
+    body: &mut Vec<u8>,
+) -> std::result::Result<(), reqwest::Error> {
+    body.extend_from_slice(&response.bytes().await?);
+    Ok(())
+}
+
+impl LaminarClient {
+    /// Same as `fetch_account_event_envelopes_page`, reading the page into `buffer` and
+    /// appending its events to `events`. Reusing both between polls avoids allocating per
+    /// event. Returns the cursor of the next page, `None` once the handle is exhausted.
+    ///
+    /// Requests are sent with the client's `reqwest::Client`, see `http_client`.
+    ///
+    /// # Arguments:
+    ///
+    /// * `account` - Address of the account whose events to fetch.
+    /// * `cursor` - Sequence number of the first event to fetch.
+    /// * `limit` - Maximum number of events in the page.
+    /// * `buffer` - `EventBuffer` the response is read into.
+    /// * `events` - Vec the events are appended to.
+    pub async fn fetch_account_event_envelopes_buffered<'a, T>(
+        &self,
+        account: AccountAddress,
+        cursor: u64,
+        limit: u16,
+        buffer: &mut EventBuffer,
+        events: &mut Vec<LaminarEventEnvelope<T>>,
+    ) -> Result<Option<u64>>
+    where
+        T: EventStoreField<'a> + DeserializeOwned,
+    {
+        let context = || {
+            format!(
+                "failed getting event type: {} for account: {}",
+                T::event_store_field(),
+                account
+            )
+        };
+        let path = format!(
+            "accounts/{}/events/{}::book::OrderBookStore/{}",
+            account.to_hex_literal(),
+            self.laminar.to_hex_literal(),
+            T::event_store_field()
+        );
+        let url = self
+            .aptos_client
+            .build_path(&path)
+            .map_err(|e| LaminarError::rest(e, context()))?;
+
+        self.rate_limit(Endpoint::Events).await;
+        let response = self
+            .http_client
+            .get(url)
+            .query(&[("start", cursor), ("limit", limit as u64)])
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| LaminarError::rest(RestError::from(e), context()))?;
+        buffer.body.clear();
+        read_body(response, &mut buffer.body)
+            .await
+            .map_err(|e| LaminarError::rest(RestError::from(e), context()))?;
+
+        let start = events.len();
+        parse_event_page(&buffer.body, events)
+            .map_err(|e| LaminarError::deserialization(e, T::event_store_field()))?;
+        let fetched = events.len() - start;
+        let next = match events.last() {
+            Some(last) if fetched > 0 && fetched >= limit as usize => Some(last.next_cursor()),
+            _ => None,
+        };
+        trace_event!(debug, events = fetched, next = ?next, "fetched buffered events page");
+        Ok(next)
+    }
+
+    /// Same as `query_event_envelopes`, reading the pages of this client's account events
+    /// into `buffer`.
+    ///
+    /// # Arguments:
+    ///
+    /// * `query` - `EventQuery` selecting the events to return.
+    /// * `buffer` - `EventBuffer` the pages are read into.
+    pub async fn query_event_envelopes_buffered<'a, E>(
+        &self,
+        query: &EventQuery<E>,
+        buffer: &mut EventBuffer,
+    ) -> Result<Vec<LaminarEventEnvelope<E>>>
+    where
+        E: EventStoreField<'a> + EventIds + DeserializeOwned,
+    {
+        let max_results = query.max_results().unwrap_or(usize::MAX);
+        let max_version = query.max_version.unwrap_or(u64::MAX);
+        let mut res = vec![];
+        let mut page = vec![];
+        let mut cursor = Some(query.start);
+        while let Some(c) = cursor.filter(|c| *c < query.end) {
+            let limit = (query.end - c).min(EVENT_PAGE_SIZE as u64) as u16;
+            page.clear();
+            let next = self
+                .fetch_account_event_envelopes_buffered::<E>(
+                    self.signer.address(),
+                    c,
+                    limit,
+                    buffer,
+                    &mut page,
+                )
+                .await?;
+            // events on a handle are emitted in version order
+            let past_version = page.iter().any(|e| e.version > max_version);
+            res.extend(
+                page.drain(..)
+                    .filter(|e| e.version <= max_version && query.matches(&e.event)),
+            );
+            if res.len() >= max_results {
+                res.truncate(max_results);
+                break;
+            }
+            if past_version {
+                break;
+            }
+            cursor = next;
+        }
+
+        Ok(res)
+    }
+}
@@ -0,0 +1,317 @@
+//! Unified exchange interface in the shape of CCXT, to port strategies written against
+//! centralized exchange abstractions.
+
+use crate::error::{LaminarError, Result};
+use crate::query::EventQuery;
+use crate::registry::{MarketEntry, MarketRegistry};
+use crate::types::events::FillEvent;
+use crate::types::l2::L2Book;
+use crate::types::order::{Id, Side, State, TimeInForce};
+use crate::{LaminarClient, PlacedOrder};
+use aptos_sdk::move_types::language_storage::TypeTag;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OrderType {
+    Limit,
+    Market,
+}
+
+/// Order as returned by `Exchange::create_order` and `Exchange::cancel_order`.
+#[derive(Clone, Debug)]
+pub struct ExchangeOrder {
+    pub id: String,
+    pub symbol: String,
+    pub order_type: OrderType,
+    pub side: Side,
+    /// Limit price, `None` for market orders.
+    pub price: Option<u64>,
+    pub amount: u64,
+    pub filled: u64,
+    pub remaining: u64,
+    pub status: State,
+    pub trades: Vec<FillEvent>,
+}
+
+/// Fill of an order of the account, as returned by `Exchange::fetch_my_trades`.
+#[derive(Clone, Debug)]
+pub struct MyTrade {
+    pub order_id: String,
+    pub symbol: String,
+    pub side: Side,
+    pub price: u64,
+    pub amount: u64,
+    pub fee: u64,
+    pub is_maker: bool,
+    pub timestamp: u64,
+}
+
+/// Coin balance of the account, as returned by `Exchange::fetch_balance`.
+#[derive(Clone, Debug)]
+pub struct Balance {
+    pub coin: TypeTag,
+    /// Balance in the smallest unit of the coin.
+    pub total: u64,
+    pub decimals: u8,
+}
+
+/// Exchange operations with the method names and arguments of CCXT. Markets are addressed by
+/// pair symbols such as `APT/USDC`, orders by their id as a string. Prices and amounts are in
+/// the units of the book.
+#[async_trait]
+pub trait Exchange: Send + Sync {
+    /// Aggregated book of `symbol`, `limit` levels per side, all levels if `None`.
+    async fn fetch_order_book(&self, symbol: &str, limit: Option<usize>) -> Result<L2Book>;
+
+    /// Place an order, good till canceled for limit orders.
+    ///
+    /// # Arguments:
+    ///
+    /// * `symbol` - Pair symbol of the book.
+    /// * `order_type` - Limit or market order.
+    /// * `side` - Side.
+    /// * `amount` - Size in units.
+    /// * `price` - Limit price in units, required for limit orders and ignored otherwise.
+    async fn create_order(
+        &self,
+        symbol: &str,
+        order_type: OrderType,
+        side: Side,
+        amount: u64,
+        price: Option<u64>,
+    ) -> Result<ExchangeOrder>;
+
+    async fn cancel_order(&self, id: &str, symbol: &str) -> Result<ExchangeOrder>;
+
+    /// Fills of the account on `symbol` from time `since` on, oldest first, at most `limit`.
+    async fn fetch_my_trades(
+        &self,
+        symbol: &str,
+        since: Option<u64>,
+        limit: Option<usize>,
+    ) -> Result<Vec<MyTrade>>;
+
+    /// Balances of the coins of all known markets by coin symbol.
+    async fn fetch_balance(&self) -> Result<HashMap<String, Balance>>;
+}
+
+/// `Exchange` over a `LaminarClient`, trading the books of a `MarketRegistry`.
+pub struct LaminarExchange {
+    client: Arc<LaminarClient>,
+    registry: MarketRegistry,
+}
+
+impl LaminarExchange {
+    pub fn new(client: Arc<LaminarClient>, registry: MarketRegistry) -> Self {
+        Self { client, registry }
+    }
+
+    pub fn client(&self) -> &LaminarClient {
+        &self.client
+    }
+
+    pub fn registry(&self) -> &MarketRegistry {
+        &self.registry
+    }
+
+    fn market(&self, symbol: &str) -> Result<&MarketEntry> {
+        self.registry
+            .get(symbol)
+            .ok_or_else(|| LaminarError::BookNotFound(symbol.to_string()))
+    }
+}
+
+fn parse_id(id: &str) -> Result<Id> {
+    Id::from_str(id).map_err(|e| LaminarError::InvalidInput(format!("order id {}: {}", id, e)))
+}
+
+fn from_placed(
+    symbol: &str,
+    order_type: OrderType,
+    price: Option<u64>,
+    placed: PlacedOrder,
+) -> ExchangeOrder {
+    let filled = placed.fills.iter().map(|f| f.fill_size).sum::<u64>();
+    let remaining = placed.place.size.saturating_sub(filled);
+    let status = match &placed.cancel {
+        Some(cancel) => cancel.order_state(placed.place.time_in_force),
+        None if remaining == 0 => State::Filled,
+        None if filled > 0 => State::PartiallyFilled,
+        None => State::Open,
+    };
+
+    ExchangeOrder {
+        id: placed.place.order_id.to_string(),
+        symbol: symbol.to_string(),
+        order_type,
+        side: placed.place.side,
+        price,
+        amount: placed.place.size,
+        filled,
+        remaining: if placed.cancel.is_some() {
+            0
+        } else {
+            remaining
+        },
+        status,
+        trades: placed.fills,
+    }
+}
+
+#[async_trait]
+impl Exchange for LaminarExchange {
+    async fn fetch_order_book(&self, symbol: &str, limit: Option<usize>) -> Result<L2Book> {
+        let market = &self.market(symbol)?.market;
+        let book = self
+            .client
+            .fetch_orderbook(&market.base, &market.quote, &market.book_owner)
+            .await?;
+        Ok(book.to_l2(limit.unwrap_or(usize::MAX)))
+    }
+
+    async fn create_order(
+        &self,
+        symbol: &str,
+        order_type: OrderType,
+        side: Side,
+        amount: u64,
+        price: Option<u64>,
+    ) -> Result<ExchangeOrder> {
+        let market = &self.market(symbol)?.market;
+        let placed = match order_type {
+            OrderType::Limit => {
+                let price = price.ok_or_else(|| {
+                    LaminarError::InvalidInput("limit order without price".to_string())
+                })?;
+                self.client
+                    .place_limit_order(
+                        &market.base,
+                        &market.quote,
+                        &market.book_owner,
+                        side,
+                        price,
+                        amount,
+                        TimeInForce::GoodTillCanceled,
+                        false,
+                    )
+                    .await?
+            }
+            OrderType::Market => {
+                self.client
+                    .place_market_order(
+                        &market.base,
+                        &market.quote,
+                        &market.book_owner,
+                        side,
+                        amount,
+                    )
+                    .await?
+            }
+        };
+        let price = match order_type {
+            OrderType::Limit => price,
+            OrderType::Market => None,
+        };
+
+        Ok(from_placed(symbol, order_type, price, placed))
+    }
+
+    async fn cancel_order(&self, id: &str, symbol: &str) -> Result<ExchangeOrder> {
+        let market = &self.market(symbol)?.market;
+        let order_id = parse_id(id)?;
+        let order = self.client.get_order(&order_id).await?;
+        self.client
+            .cancel_order(
+                &market.base,
+                &market.quote,
+                &market.book_owner,
+                &order_id,
+                order.side,
+            )
+            .await?;
+
+        let filled = order.size - order.remaining_size;
+        Ok(ExchangeOrder {
+            id: id.to_string(),
+            symbol: symbol.to_string(),
+            order_type: OrderType::Limit,
+            side: order.side,
+            price: Some(order.price),
+            amount: order.size,
+            filled,
+            remaining: 0,
+            status: State::Cancelled,
+            trades: order.fills,
+        })
+    }
+
+    async fn fetch_my_trades(
+        &self,
+        symbol: &str,
+        since: Option<u64>,
+        limit: Option<usize>,
+    ) -> Result<Vec<MyTrade>> {
+        let book_id = &self.market(symbol)?.book.book_id;
+        let account = self.client.address();
+        let since = since.unwrap_or(0);
+        let mut query = EventQuery::<FillEvent>::new()
+            .for_book(book_id)
+            .filter(move |f| f.order_id.addr.inner() == &account && f.time >= since);
+        if let Some(limit) = limit {
+            query = query.limit(limit);
+        }
+        let fills = self.client.query_events(&query).await?;
+
+        Ok(fills
+            .into_iter()
+            .map(|f| MyTrade {
+                order_id: f.order_id.to_string(),
+                symbol: symbol.to_string(),
+                side: f.side,
+                price: f.price,
+                amount: f.fill_size,
+                fee: f.fee,
+                is_maker: f.is_maker,
+                timestamp: f.time,
+            })
+            .collect())
+    }
+
+    async fn fetch_balance(&self) -> Result<HashMap<String, Balance>> {
+        let mut coins = vec![];
+        for entry in self.registry.entries() {
+            for (coin, symbol) in [
+                (&entry.market.base, &entry.base_symbol),
+                (&entry.market.quote, &entry.quote_symbol),
+            ] {
+                if !coins.iter().any(|(c, _)| c == coin) {
+                    coins.push((coin.clone(), symbol.clone()));
+                }
+            }
+        }
+
+        let mut res = HashMap::new();
+        for (coin, symbol) in coins {
+            // coins the account never registered for have no coin store
+            let total = match self.client.get_coin_balance(&coin).await {
+                Ok(balance) => balance.0,
+                Err(LaminarError::NotFound(_)) => 0,
+                Err(e) => return Err(e),
+            };
+            let decimals = self.client.get_coin_info(&coin).await?.decimals;
+            res.insert(
+                symbol,
+                Balance {
+                    coin,
+                    total,
+                    decimals,
+                },
+            );
+        }
+
+        Ok(res)
+    }
+}
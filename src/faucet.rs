@@ -0,0 +1,108 @@
+use crate::error::{LaminarError, Result};
+use crate::time::{sleep, Instant};
+use crate::LaminarClient;
+use anyhow::{anyhow, Context};
+use aptos_api_types::AptosErrorCode;
+use aptos_sdk::move_types::ident_str;
+use aptos_sdk::move_types::language_storage::{StructTag, TypeTag};
+use aptos_sdk::rest_client::error::RestError;
+use aptos_sdk::types::account_address::AccountAddress;
+use reqwest::Url;
+use std::time::Duration;
+
+/// Maximum time `fund_from_faucet` waits for the funds to show up in the balance.
+pub const FAUCET_TIMEOUT: Duration = Duration::from_secs(30);
+const FAUCET_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// `TypeTag` of `0x1::aptos_coin::AptosCoin`.
+pub fn aptos_coin() -> TypeTag {
+    TypeTag::Struct(Box::new(StructTag {
+        address: AccountAddress::ONE,
+        module: ident_str!("aptos_coin").to_owned(),
+        name: ident_str!("AptosCoin").to_owned(),
+        type_params: vec![],
+    }))
+}
+
+/// Ask the faucet at `faucet_url` to mint `amount` octas of APT to `address`, creating the
+/// account if it does not exist yet. Returns once the faucet accepted the request, the funds
+/// may not be committed yet.
+///
+/// # Arguments:
+///
+/// * `faucet_url` - Url of the faucet, e.g. `Network::faucet_url`.
+/// * `address` - Address of the account to fund.
+/// * `amount` - Amount of APT in octas.
+pub async fn request_faucet(faucet_url: &Url, address: AccountAddress, amount: u64) -> Result<()> {
+    let mut url = faucet_url.join("mint").context("invalid faucet url")?;
+    url.query_pairs_mut()
+        .append_pair("amount", &amount.to_string())
+        .append_pair("address", &address.to_hex_literal());
+    let res = reqwest::Client::new()
+        .post(url)
+        .send()
+        .await
+        .context("faucet request failed")?;
+    if !res.status().is_success() {
+        let status = res.status();
+        let body = res.text().await.unwrap_or_default();
+        return Err(anyhow!("faucet returned {}: {}", status, body).into());
+    }
+
+    Ok(())
+}
+
+impl LaminarClient {
+    /// Fund this client's account with APT from the faucet of the network it was connected to
+    /// with `connect_to`, creating the account if it does not exist yet. Waits until the
+    /// balance reflects the funds.
+    ///
+    /// # Arguments:
+    ///
+    /// * `amount` - Amount of APT in octas.
+    pub async fn fund_from_faucet(&self, amount: u64) -> Result<()> {
+        let faucet_url = self.network().and_then(|n| n.faucet_url()).ok_or_else(|| {
+            LaminarError::InvalidInput(
+                "client is not connected to a network with a faucet".to_string(),
+            )
+        })?;
+        self.fund_from_faucet_at(faucet_url, amount).await
+    }
+
+    /// Same as `fund_from_faucet`, using the faucet at `faucet_url`.
+    ///
+    /// # Arguments:
+    ///
+    /// * `faucet_url` - Url of the faucet, e.g. `Network::faucet_url`.
+    /// * `amount` - Amount of APT in octas.
+    pub async fn fund_from_faucet_at(&self, faucet_url: Url, amount: u64) -> Result<()> {
+        let coin = aptos_coin();
+        let before = self.apt_balance_or_zero(&coin).await?;
+
+        request_faucet(&faucet_url, self.address(), amount).await?;
+
+        let deadline = Instant::now() + FAUCET_TIMEOUT;
+        loop {
+            if self.apt_balance_or_zero(&coin).await? >= before.saturating_add(amount) {
+                return Ok(());
+            }
+            if Instant::now() >= deadline {
+                return Err(LaminarError::DeadlineExceeded { hash: None });
+            }
+            sleep(FAUCET_POLL_INTERVAL).await;
+        }
+    }
+
+    // the account and its coin store do not exist before the first funding
+    async fn apt_balance_or_zero(&self, coin: &TypeTag) -> Result<u64> {
+        match self.get_coin_balance(coin).await {
+            Ok(balance) => Ok(balance.0),
+            Err(LaminarError::NotFound(_)) => Ok(0),
+            Err(LaminarError::Rest {
+                source: RestError::Api(a),
+                ..
+            }) if a.error.error_code == AptosErrorCode::AccountNotFound => Ok(0),
+            Err(e) => Err(e),
+        }
+    }
+}
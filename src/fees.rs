@@ -0,0 +1,199 @@
+use crate::error::Result;
+use crate::query::EventQuery;
+use crate::types::decimal;
+use crate::types::events::FillEvent;
+use crate::types::order::{Id, Instrument, Side};
+use crate::LaminarClient;
+use serde::Serialize;
+use std::ops::Range;
+
+/// Fees of one side and maker/taker role of a book.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct FeeBreakdown {
+    pub side: Side,
+    pub is_maker: bool,
+    pub fills: u64,
+    /// Total fee in raw quote coin units.
+    pub fee_units: u64,
+    /// Total fee in quote coin, adjusted by the quote decimals.
+    pub fee: f64,
+}
+
+/// Fees paid on a book over a time range.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct FeeSummary {
+    pub book_id: Id,
+    pub start: u64,
+    pub end: u64,
+    pub breakdown: Vec<FeeBreakdown>,
+    pub total_units: u64,
+    pub total: f64,
+}
+
+impl FeeSummary {
+    /// Aggregate fees of `fills` on `book_id` with `time` in `time_range`.
+    ///
+    /// # Arguments:
+    ///
+    /// * `book_id` - `OrderBook` Id, fills of other books are ignored.
+    /// * `fills` - Fill events of the account.
+    /// * `time_range` - Range of `FillEvent::time` to include.
+    /// * `quote_decimals` - Decimals of the quote coin fees are charged in.
+    pub fn from_fills<'a, I>(
+        book_id: &Id,
+        fills: I,
+        time_range: Range<u64>,
+        quote_decimals: u8,
+    ) -> Self
+    where
+        I: IntoIterator<Item = &'a FillEvent>,
+    {
+        let mut breakdown = [Side::Bid, Side::Ask]
+            .into_iter()
+            .flat_map(|side| {
+                [true, false].into_iter().map(move |is_maker| FeeBreakdown {
+                    side,
+                    is_maker,
+                    fills: 0,
+                    fee_units: 0,
+                    fee: 0.0,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for fill in fills
+            .into_iter()
+            .filter(|f| &f.book_id == book_id && time_range.contains(&f.time))
+        {
+            if let Some(b) = breakdown
+                .iter_mut()
+                .find(|b| b.side == fill.side && b.is_maker == fill.is_maker)
+            {
+                b.fills += 1;
+                b.fee_units += fill.fee;
+            }
+        }
+        for b in &mut breakdown {
+            b.fee = decimal::from_units(b.fee_units, quote_decimals);
+        }
+
+        let total_units = breakdown.iter().map(|b| b.fee_units).sum();
+        Self {
+            book_id: book_id.clone(),
+            start: time_range.start,
+            end: time_range.end,
+            breakdown,
+            total_units,
+            total: decimal::from_units(total_units, quote_decimals),
+        }
+    }
+
+    pub fn maker_total(&self) -> f64 {
+        self.role_total(true)
+    }
+
+    pub fn taker_total(&self) -> f64 {
+        self.role_total(false)
+    }
+
+    fn role_total(&self, is_maker: bool) -> f64 {
+        self.breakdown
+            .iter()
+            .filter(|b| b.is_maker == is_maker)
+            .map(|b| b.fee)
+            .sum()
+    }
+}
+
+impl LaminarClient {
+    /// Summarize the fees this client's account paid on a book.
+    ///
+    /// # Arguments:
+    ///
+    /// * `book_id` - `OrderBook` Id.
+    /// * `instrument` - `Instrument` of the book, used for the quote decimals.
+    /// * `time_range` - Range of fill times to include, in microseconds.
+    pub async fn fee_summary(
+        &self,
+        book_id: &Id,
+        instrument: &Instrument,
+        time_range: Range<u64>,
+    ) -> Result<FeeSummary> {
+        let start = time_range.start;
+        let end = time_range.end;
+        let query = EventQuery::new()
+            .for_book(book_id)
+            .filter(move |f: &FillEvent| f.time >= start && f.time < end);
+        let fills = self.query_events(&query).await?;
+        Ok(FeeSummary::from_fills(
+            book_id,
+            &fills,
+            time_range,
+            instrument.quote_decimals,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_api_types::{Address, U64};
+    use aptos_sdk::types::account_address::AccountAddress;
+
+    fn id(creation_num: u64) -> Id {
+        Id {
+            creation_num: U64(creation_num),
+            addr: Address::from(AccountAddress::ONE),
+        }
+    }
+
+    fn fill(book: u64, side: Side, is_maker: bool, fee: u64, time: u64) -> FillEvent {
+        FillEvent {
+            book_id: id(book),
+            order_id: id(1),
+            side,
+            price: 100,
+            fill_size: 1,
+            fee,
+            fee_rate: 0,
+            time,
+            remaining_size: 0,
+            is_maker,
+        }
+    }
+
+    #[test]
+    fn test_breakdown() {
+        let fills = vec![
+            fill(0, Side::Bid, true, 100, 0),
+            fill(0, Side::Bid, true, 50, 1),
+            fill(0, Side::Bid, false, 200, 5),
+            fill(0, Side::Ask, true, 300, 9),
+            // the end of the range and other books are left out
+            fill(0, Side::Ask, false, 400, 10),
+            fill(1, Side::Ask, false, 500, 5),
+        ];
+        let summary = FeeSummary::from_fills(&id(0), &fills, 0..10, 2);
+
+        let fees = summary
+            .breakdown
+            .iter()
+            .map(|b| (b.side, b.is_maker, b.fills, b.fee_units))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            fees,
+            vec![
+                (Side::Bid, true, 2, 150),
+                (Side::Bid, false, 1, 200),
+                (Side::Ask, true, 1, 300),
+                (Side::Ask, false, 0, 0),
+            ]
+        );
+        assert_eq!(summary.breakdown[0].fee, 1.5);
+        assert_eq!(summary.maker_total(), 4.5);
+        assert_eq!(summary.taker_total(), 2.0);
+        assert_eq!(summary.total_units, 650);
+        assert_eq!(summary.total, 6.5);
+        assert_eq!((summary.start, summary.end), (0, 10));
+    }
+}
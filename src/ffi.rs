@@ -0,0 +1,488 @@
+//! C ABI over `LaminarClient`, to embed the SDK in C, C++ and C# trading systems.
+//!
+//! Build a shared library with `cargo rustc --release --features ffi --crate-type cdylib` and
+//! include `include/laminar.h`. The client is an opaque `LaminarHandle` owning its own tokio
+//! runtime, so calls block the calling thread until the request completes.
+//!
+//! Functions return `LAMINAR_OK` or a negative status, the message of the last error of the
+//! calling thread is returned by `laminar_last_error`. Strings are UTF-8 and nul terminated.
+//! Strings returned by the library are owned by the caller and freed with
+//! `laminar_string_free`. Ids are written as `{address}:{creation_number}`, results are
+//! returned as JSON in the format of the SDK's serde types.
+
+use crate::error::{LaminarError, Result};
+use crate::market_data::MarketDataFeed;
+use crate::types::market::Market;
+use crate::types::order::{Id, Side, TimeInForce};
+use crate::LaminarClient;
+use anyhow::anyhow;
+use serde::Serialize;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::{c_char, c_int, c_void, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::runtime::Runtime;
+use tokio::task::JoinHandle;
+
+pub const LAMINAR_OK: c_int = 0;
+/// A pointer was null, a string was not UTF-8 or an argument was out of range.
+pub const LAMINAR_INVALID_ARGUMENT: c_int = -1;
+/// The book or order does not exist.
+pub const LAMINAR_NOT_FOUND: c_int = -2;
+/// The transaction was rejected or aborted on chain.
+pub const LAMINAR_REJECTED: c_int = -3;
+/// Any other failure, e.g. of the connection to the node.
+pub const LAMINAR_ERROR: c_int = -4;
+
+/// Called with the JSON of each `LaminarEventEnvelope` of a subscription and the
+/// `user_data` passed to `laminar_subscribe`. The string is only valid during the call.
+pub type LaminarEventCallback = extern "C" fn(event_json: *const c_char, user_data: *mut c_void);
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = RefCell::new(None);
+}
+
+/// Client handle created by `laminar_client_connect`.
+pub struct LaminarHandle {
+    runtime: Runtime,
+    client: Arc<LaminarClient>,
+    markets: Mutex<HashMap<Id, Market>>,
+}
+
+impl LaminarHandle {
+    // markets don't change once created, so they are resolved once per book
+    fn market(&self, book_id: &Id) -> Result<Market> {
+        let cached = self
+            .markets
+            .lock()
+            .expect("lock poisoned")
+            .get(book_id)
+            .cloned();
+        if let Some(market) = cached {
+            return Ok(market);
+        }
+        let market = self
+            .runtime
+            .block_on(self.client.fetch_market_by_id(book_id))?;
+        self.markets
+            .lock()
+            .expect("lock poisoned")
+            .insert(book_id.clone(), market.clone());
+        Ok(market)
+    }
+}
+
+/// Event subscription created by `laminar_subscribe`.
+pub struct LaminarSubscription {
+    task: JoinHandle<()>,
+    runtime: tokio::runtime::Handle,
+}
+
+struct UserData(*mut c_void);
+
+// the caller of `laminar_subscribe` guarantees `user_data` can be used from the runtime's
+// worker threads
+unsafe impl Send for UserData {}
+
+fn status(e: &LaminarError) -> c_int {
+    match e {
+        LaminarError::InvalidInput(_) | LaminarError::InvalidKey(_) => LAMINAR_INVALID_ARGUMENT,
+        LaminarError::NotFound(_) | LaminarError::BookNotFound(_) => LAMINAR_NOT_FOUND,
+        LaminarError::MoveAbort { .. }
+        | LaminarError::ExecutionFailure { .. }
+        | LaminarError::Rejected(_) => LAMINAR_REJECTED,
+        _ => LAMINAR_ERROR,
+    }
+}
+
+fn set_last_error(message: String) {
+    // messages can't contain nul bytes in C, cut them off there
+    let message = message.split('\0').next().unwrap_or_default().to_string();
+    LAST_ERROR.with(|e| *e.borrow_mut() = CString::new(message).ok());
+}
+
+/// Run `f`, recording its error and turning panics into errors so they don't unwind into
+/// the caller.
+fn call(f: impl FnOnce() -> Result<()>) -> c_int {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => LAMINAR_OK,
+        Ok(Err(e)) => {
+            set_last_error(e.to_string());
+            status(&e)
+        }
+        Err(_) => {
+            set_last_error("panic in laminar sdk".to_string());
+            LAMINAR_ERROR
+        }
+    }
+}
+
+unsafe fn str_arg<'a>(s: *const c_char, name: &str) -> Result<&'a str> {
+    if s.is_null() {
+        return Err(LaminarError::InvalidInput(format!("{} is null", name)));
+    }
+    CStr::from_ptr(s)
+        .to_str()
+        .map_err(|_| LaminarError::InvalidInput(format!("{} is not utf-8", name)))
+}
+
+unsafe fn id_arg(s: *const c_char, name: &str) -> Result<Id> {
+    let s = str_arg(s, name)?;
+    Id::from_str(s).map_err(|e| LaminarError::InvalidInput(format!("{} {}: {}", name, s, e)))
+}
+
+unsafe fn handle_arg<'a>(handle: *const LaminarHandle) -> Result<&'a LaminarHandle> {
+    handle
+        .as_ref()
+        .ok_or_else(|| LaminarError::InvalidInput("handle is null".to_string()))
+}
+
+fn side_arg(side: u8) -> Result<Side> {
+    match side {
+        0 => Ok(Side::Bid),
+        1 => Ok(Side::Ask),
+        _ => Err(LaminarError::InvalidInput(format!("side {}", side))),
+    }
+}
+
+fn time_in_force_arg(time_in_force: u8) -> Result<TimeInForce> {
+    match time_in_force {
+        0 => Ok(TimeInForce::GoodTillCanceled),
+        1 => Ok(TimeInForce::ImmediateOrCancel),
+        2 => Ok(TimeInForce::FillOrKill),
+        _ => Err(LaminarError::InvalidInput(format!(
+            "time in force {}",
+            time_in_force
+        ))),
+    }
+}
+
+fn to_json<T: Serialize>(value: &T, what: &str) -> Result<CString> {
+    let json = serde_json::to_string(value).map_err(|e| LaminarError::deserialization(e, what))?;
+    // serde_json escapes nul bytes, so the json never contains one
+    Ok(CString::new(json).map_err(|e| anyhow!("{}: {}", what, e))?)
+}
+
+unsafe fn out_arg<'a, T>(out: *mut T) -> Result<&'a mut T> {
+    out.as_mut()
+        .ok_or_else(|| LaminarError::InvalidInput("out pointer is null".to_string()))
+}
+
+/// Message of the last error of the calling thread, or null if no call failed yet. The
+/// string is owned by the library and valid until the next failing call on the thread.
+#[no_mangle]
+pub extern "C" fn laminar_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ref().map_or(ptr::null(), |s| s.as_ptr()))
+}
+
+/// Free a string returned by the library.
+///
+/// # Safety
+///
+/// `s` must be null or a string returned by the library that was not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn laminar_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// Connect a client and write its handle to `out`.
+///
+/// # Arguments:
+///
+/// * `node_url` - Url of the Aptos node to connect to.
+/// * `laminar_address` - Hex encoded address of the account holding the Laminar modules.
+/// * `account_address` - Hex encoded address of the account trading with the client.
+/// * `account_private_key` - Hex encoded private key of the account.
+/// * `out` - Receives the handle, free it with `laminar_client_free`.
+///
+/// # Safety
+///
+/// The strings must be valid nul terminated strings and `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn laminar_client_connect(
+    node_url: *const c_char,
+    laminar_address: *const c_char,
+    account_address: *const c_char,
+    account_private_key: *const c_char,
+    out: *mut *mut LaminarHandle,
+) -> c_int {
+    call(|| {
+        let out = out_arg(out)?;
+        let node_url = str_arg(node_url, "node_url")?;
+        let laminar_address = str_arg(laminar_address, "laminar_address")?;
+        let account_address = str_arg(account_address, "account_address")?;
+        let account_private_key = str_arg(account_private_key, "account_private_key")?;
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| anyhow!("failed starting runtime: {}", e))?;
+        let client = runtime.block_on(LaminarClient::connect_with_strings(
+            node_url,
+            laminar_address,
+            account_address,
+            account_private_key,
+        ))?;
+        let handle = LaminarHandle {
+            runtime,
+            client: Arc::new(client),
+            markets: Mutex::new(HashMap::new()),
+        };
+        *out = Box::into_raw(Box::new(handle));
+        Ok(())
+    })
+}
+
+/// Free a client handle. Subscriptions of the client must be freed first.
+///
+/// # Safety
+///
+/// `handle` must be null or a handle returned by `laminar_client_connect` that was not freed
+/// yet.
+#[no_mangle]
+pub unsafe extern "C" fn laminar_client_free(handle: *mut LaminarHandle) {
+    if !handle.is_null() {
+        drop(Box::from_raw(handle));
+    }
+}
+
+/// Place a limit order and write the JSON of the `PlacedOrder` to `out`.
+///
+/// # Arguments:
+///
+/// * `handle` - Client handle.
+/// * `book_id` - Id of the book.
+/// * `side` - 0 for bid, 1 for ask.
+/// * `price` - Price in units.
+/// * `size` - Size in units.
+/// * `time_in_force` - 0 for good till canceled, 1 for immediate or cancel, 2 for fill or kill.
+/// * `post_only` - Reject the order if it would take liquidity.
+/// * `out` - Receives the JSON, free it with `laminar_string_free`.
+///
+/// # Safety
+///
+/// `handle` must be a valid handle, `book_id` a valid string and `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn laminar_place_limit_order(
+    handle: *const LaminarHandle,
+    book_id: *const c_char,
+    side: u8,
+    price: u64,
+    size: u64,
+    time_in_force: u8,
+    post_only: bool,
+    out: *mut *mut c_char,
+) -> c_int {
+    call(|| {
+        let out = out_arg(out)?;
+        let handle = handle_arg(handle)?;
+        let market = handle.market(&id_arg(book_id, "book_id")?)?;
+        let placed = handle.runtime.block_on(handle.client.place_limit_order(
+            &market.base,
+            &market.quote,
+            &market.book_owner,
+            side_arg(side)?,
+            price,
+            size,
+            time_in_force_arg(time_in_force)?,
+            post_only,
+        ))?;
+        *out = to_json(&placed, "placed order")?.into_raw();
+        Ok(())
+    })
+}
+
+/// Place a market order and write the JSON of the `PlacedOrder` to `out`.
+///
+/// # Arguments:
+///
+/// * `handle` - Client handle.
+/// * `book_id` - Id of the book.
+/// * `side` - 0 for bid, 1 for ask.
+/// * `size` - Size in units.
+/// * `out` - Receives the JSON, free it with `laminar_string_free`.
+///
+/// # Safety
+///
+/// `handle` must be a valid handle, `book_id` a valid string and `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn laminar_place_market_order(
+    handle: *const LaminarHandle,
+    book_id: *const c_char,
+    side: u8,
+    size: u64,
+    out: *mut *mut c_char,
+) -> c_int {
+    call(|| {
+        let out = out_arg(out)?;
+        let handle = handle_arg(handle)?;
+        let market = handle.market(&id_arg(book_id, "book_id")?)?;
+        let placed = handle.runtime.block_on(handle.client.place_market_order(
+            &market.base,
+            &market.quote,
+            &market.book_owner,
+            side_arg(side)?,
+            size,
+        ))?;
+        *out = to_json(&placed, "placed order")?.into_raw();
+        Ok(())
+    })
+}
+
+/// Cancel an order.
+///
+/// # Arguments:
+///
+/// * `handle` - Client handle.
+/// * `book_id` - Id of the book.
+/// * `order_id` - Id of the order.
+/// * `side` - Side of the order, 0 for bid, 1 for ask.
+///
+/// # Safety
+///
+/// `handle` must be a valid handle and the ids valid strings.
+#[no_mangle]
+pub unsafe extern "C" fn laminar_cancel_order(
+    handle: *const LaminarHandle,
+    book_id: *const c_char,
+    order_id: *const c_char,
+    side: u8,
+) -> c_int {
+    call(|| {
+        let handle = handle_arg(handle)?;
+        let market = handle.market(&id_arg(book_id, "book_id")?)?;
+        let order_id = id_arg(order_id, "order_id")?;
+        handle.runtime.block_on(handle.client.cancel_order(
+            &market.base,
+            &market.quote,
+            &market.book_owner,
+            &order_id,
+            side_arg(side)?,
+        ))?;
+        Ok(())
+    })
+}
+
+/// Fetch a book and write the JSON of its `L2Book` to `out`.
+///
+/// # Arguments:
+///
+/// * `handle` - Client handle.
+/// * `book_id` - Id of the book.
+/// * `depth` - Number of levels per side, 0 for all levels.
+/// * `out` - Receives the JSON, free it with `laminar_string_free`.
+///
+/// # Safety
+///
+/// `handle` must be a valid handle, `book_id` a valid string and `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn laminar_fetch_orderbook(
+    handle: *const LaminarHandle,
+    book_id: *const c_char,
+    depth: u32,
+    out: *mut *mut c_char,
+) -> c_int {
+    call(|| {
+        let out = out_arg(out)?;
+        let handle = handle_arg(handle)?;
+        let market = handle.market(&id_arg(book_id, "book_id")?)?;
+        let book = handle.runtime.block_on(handle.client.fetch_orderbook(
+            &market.base,
+            &market.quote,
+            &market.book_owner,
+        ))?;
+        let depth = if depth == 0 {
+            usize::MAX
+        } else {
+            depth as usize
+        };
+        *out = to_json(&book.to_l2(depth), "l2 book")?.into_raw();
+        Ok(())
+    })
+}
+
+/// Poll the events of a book every `interval_ms` and call `callback` with each of them, from
+/// a runtime thread, until the subscription is freed. Failed polls are retried on the next
+/// tick.
+///
+/// # Arguments:
+///
+/// * `handle` - Client handle.
+/// * `book_id` - Id of the book.
+/// * `interval_ms` - Time between polls in milliseconds.
+/// * `callback` - Called with each event.
+/// * `user_data` - Passed to `callback` as is.
+/// * `out` - Receives the subscription, free it with `laminar_unsubscribe`.
+///
+/// # Safety
+///
+/// `handle` must be a valid handle, `book_id` a valid string and `out` a valid pointer.
+/// `user_data` must be usable from another thread until the subscription is freed.
+#[no_mangle]
+pub unsafe extern "C" fn laminar_subscribe(
+    handle: *const LaminarHandle,
+    book_id: *const c_char,
+    interval_ms: u64,
+    callback: LaminarEventCallback,
+    user_data: *mut c_void,
+    out: *mut *mut LaminarSubscription,
+) -> c_int {
+    call(|| {
+        let out = out_arg(out)?;
+        let handle = handle_arg(handle)?;
+        let market = handle.market(&id_arg(book_id, "book_id")?)?;
+        let client = handle.client.clone();
+        let user_data = UserData(user_data);
+        let interval = Duration::from_millis(interval_ms.max(1));
+        let task = handle.runtime.spawn(async move {
+            let mut feed = MarketDataFeed::new(market.base, market.quote, market.book_owner);
+            loop {
+                match feed.poll_envelopes(&client).await {
+                    Ok(envelopes) => {
+                        for envelope in &envelopes {
+                            if let Ok(json) = to_json(envelope, "event") {
+                                callback(json.as_ptr(), user_data.0);
+                            }
+                        }
+                    }
+                    Err(_e) => {
+                        trace_event!(warn, error = %_e, "ffi subscription poll failed");
+                    }
+                }
+                tokio::time::sleep(interval).await;
+            }
+        });
+        *out = Box::into_raw(Box::new(LaminarSubscription {
+            task,
+            runtime: handle.runtime.handle().clone(),
+        }));
+        Ok(())
+    })
+}
+
+/// Stop and free a subscription. Returns once the subscription's task has stopped, so the
+/// callback is not running anymore and won't be called again.
+///
+/// # Safety
+///
+/// `subscription` must be null or a subscription returned by `laminar_subscribe` that was
+/// not freed yet. It must not be called from a callback, where the task can't be waited for
+/// and the callback may be called again once this returns.
+#[no_mangle]
+pub unsafe extern "C" fn laminar_unsubscribe(subscription: *mut LaminarSubscription) {
+    if subscription.is_null() {
+        return;
+    }
+    let LaminarSubscription { task, runtime } = *Box::from_raw(subscription);
+    task.abort();
+    // blocking on a runtime thread would panic, the task is only aborted there
+    if tokio::runtime::Handle::try_current().is_err() {
+        // the task ends cancelled, once the callback it may be running returned
+        let _ = runtime.block_on(task);
+    }
+}
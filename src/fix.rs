@@ -0,0 +1,1029 @@
+//! Mapping of FIX 4.4 order messages to Laminar order operations and events.
+//!
+//! `FixGateway` executes `NewOrderSingle` (35=D) and `OrderCancelRequest` (35=F) messages of
+//! an OMS with a `LaminarClient` and answers with `ExecutionReport`s (35=8), or
+//! `OrderCancelReject`s (35=9) for cancels that could not be executed. Prices and
+//! quantities are integers in the units of the book, symbols are the pair symbols of a
+//! `MarketRegistry`, e.g. `APT/USDC`. The session layer (logon, heartbeats, resends) is left
+//! to a FIX engine, messages are exchanged with it as `FixMessage`s.
+
+use crate::error::{LaminarError, Result};
+use crate::registry::{MarketEntry, MarketRegistry};
+use crate::time::unix_now_micros;
+use crate::types::civil_from_days;
+use crate::types::events::{CancelOrderEvent, FillEvent, LaminarEvent, PlaceOrderEvent};
+use crate::types::order::{Id, Side, TimeInForce};
+use crate::LaminarClient;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+const SOH: u8 = 0x01;
+const BEGIN_STRING: &str = "FIX.4.4";
+
+/// Tags used by the mapped messages.
+pub mod tags {
+    pub const AVG_PX: u32 = 6;
+    pub const BEGIN_STRING: u32 = 8;
+    pub const BODY_LENGTH: u32 = 9;
+    pub const CHECK_SUM: u32 = 10;
+    pub const CL_ORD_ID: u32 = 11;
+    pub const CUM_QTY: u32 = 14;
+    pub const EXEC_ID: u32 = 17;
+    pub const EXEC_INST: u32 = 18;
+    pub const LAST_PX: u32 = 31;
+    pub const LAST_QTY: u32 = 32;
+    pub const MSG_SEQ_NUM: u32 = 34;
+    pub const MSG_TYPE: u32 = 35;
+    pub const ORDER_ID: u32 = 37;
+    pub const ORDER_QTY: u32 = 38;
+    pub const ORD_STATUS: u32 = 39;
+    pub const ORD_TYPE: u32 = 40;
+    pub const ORIG_CL_ORD_ID: u32 = 41;
+    pub const PRICE: u32 = 44;
+    pub const REF_SEQ_NUM: u32 = 45;
+    pub const SENDER_COMP_ID: u32 = 49;
+    pub const SENDING_TIME: u32 = 52;
+    pub const SIDE: u32 = 54;
+    pub const SYMBOL: u32 = 55;
+    pub const TARGET_COMP_ID: u32 = 56;
+    pub const TEXT: u32 = 58;
+    pub const CXL_REJ_REASON: u32 = 102;
+    pub const TIME_IN_FORCE: u32 = 59;
+    pub const TRANSACT_TIME: u32 = 60;
+    pub const ORD_REJ_REASON: u32 = 103;
+    pub const EXEC_TYPE: u32 = 150;
+    pub const LEAVES_QTY: u32 = 151;
+    pub const REF_MSG_TYPE: u32 = 372;
+    pub const BUSINESS_REJECT_REASON: u32 = 380;
+    pub const CXL_REJ_RESPONSE_TO: u32 = 434;
+}
+
+/// FIX message as its type and body fields in order. `BeginString`, `BodyLength`, `MsgType`
+/// and `CheckSum` are added by `encode` and removed by `decode`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FixMessage {
+    pub msg_type: String,
+    pub fields: Vec<(u32, String)>,
+}
+
+impl FixMessage {
+    pub fn new(msg_type: &str) -> Self {
+        Self {
+            msg_type: msg_type.to_string(),
+            fields: vec![],
+        }
+    }
+
+    /// Append a field.
+    pub fn with(mut self, tag: u32, value: impl ToString) -> Self {
+        self.fields.push((tag, value.to_string()));
+        self
+    }
+
+    /// Value of the first field with `tag`.
+    pub fn get(&self, tag: u32) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(t, _)| *t == tag)
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn required(&self, tag: u32) -> Result<&str> {
+        self.get(tag).ok_or_else(|| {
+            LaminarError::InvalidInput(format!("{} without required tag {}", self.msg_type, tag))
+        })
+    }
+
+    fn required_u64(&self, tag: u32) -> Result<u64> {
+        let value = self.required(tag)?;
+        value.parse().map_err(|_| {
+            LaminarError::InvalidInput(format!("tag {}: invalid value {}", tag, value))
+        })
+    }
+
+    /// Encode as tag=value fields separated by SOH.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut body = vec![];
+        push_field(&mut body, tags::MSG_TYPE, &self.msg_type);
+        for (tag, value) in &self.fields {
+            push_field(&mut body, *tag, value);
+        }
+
+        let mut res = vec![];
+        push_field(&mut res, tags::BEGIN_STRING, BEGIN_STRING);
+        push_field(&mut res, tags::BODY_LENGTH, &body.len().to_string());
+        res.extend(body);
+        let checksum = res.iter().map(|b| *b as u32).sum::<u32>() % 256;
+        push_field(&mut res, tags::CHECK_SUM, &format!("{:03}", checksum));
+        res
+    }
+
+    /// Decode a message, validating its body length and checksum.
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        let invalid = |reason: &str| LaminarError::InvalidInput(format!("fix message: {}", reason));
+        let checksum_at = bytes
+            .windows(4)
+            .rposition(|w| w == b"\x0110=")
+            .map(|i| i + 1)
+            .ok_or_else(|| invalid("missing checksum"))?;
+        let expected = bytes[..checksum_at].iter().map(|b| *b as u32).sum::<u32>() % 256;
+
+        let mut fields = vec![];
+        for field in bytes.split(|b| *b == SOH).filter(|f| !f.is_empty()) {
+            let field = std::str::from_utf8(field).map_err(|_| invalid("not utf-8"))?;
+            let (tag, value) = field
+                .split_once('=')
+                .ok_or_else(|| invalid("field without ="))?;
+            let tag = tag.parse::<u32>().map_err(|_| invalid("invalid tag"))?;
+            fields.push((tag, value.to_string()));
+        }
+
+        let mut fields = fields.into_iter();
+        match fields.next() {
+            Some((tags::BEGIN_STRING, v)) if v == BEGIN_STRING => {}
+            _ => return Err(invalid("expected BeginString FIX.4.4")),
+        }
+        let body_length = match fields.next() {
+            Some((tags::BODY_LENGTH, v)) => v
+                .parse::<usize>()
+                .map_err(|_| invalid("invalid BodyLength"))?,
+            _ => return Err(invalid("expected BodyLength")),
+        };
+        let body_start = bytes
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| **b == SOH)
+            .nth(1)
+            .map(|(i, _)| i + 1)
+            .ok_or_else(|| invalid("truncated header"))?;
+        if checksum_at - body_start != body_length {
+            return Err(invalid("BodyLength mismatch"));
+        }
+        let msg_type = match fields.next() {
+            Some((tags::MSG_TYPE, v)) => v,
+            _ => return Err(invalid("expected MsgType")),
+        };
+
+        let mut fields = fields.collect::<Vec<_>>();
+        match fields.pop() {
+            Some((tags::CHECK_SUM, v)) if v.parse::<u32>().ok() == Some(expected) => {}
+            _ => return Err(invalid("CheckSum mismatch")),
+        }
+
+        Ok(Self { msg_type, fields })
+    }
+}
+
+fn push_field(buf: &mut Vec<u8>, tag: u32, value: &str) {
+    buf.extend_from_slice(tag.to_string().as_bytes());
+    buf.push(b'=');
+    buf.extend_from_slice(value.as_bytes());
+    buf.push(SOH);
+}
+
+/// `NewOrderSingle` (35=D).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NewOrderSingle {
+    pub cl_ord_id: String,
+    pub symbol: String,
+    pub side: Side,
+    pub order_qty: u64,
+    /// Limit price, `None` for market orders (OrdType 1).
+    pub price: Option<u64>,
+    pub time_in_force: TimeInForce,
+    /// ExecInst 6, participate don't initiate.
+    pub post_only: bool,
+}
+
+impl TryFrom<&FixMessage> for NewOrderSingle {
+    type Error = LaminarError;
+
+    fn try_from(msg: &FixMessage) -> Result<Self> {
+        let price = match msg.required(tags::ORD_TYPE)? {
+            "1" => None,
+            "2" => Some(msg.required_u64(tags::PRICE)?),
+            t => {
+                return Err(LaminarError::InvalidInput(format!(
+                    "unsupported OrdType {}",
+                    t
+                )))
+            }
+        };
+        // Day orders rest until canceled, books have no trading session
+        let time_in_force = match msg.get(tags::TIME_IN_FORCE).unwrap_or("0") {
+            "0" | "1" => TimeInForce::GoodTillCanceled,
+            "3" => TimeInForce::ImmediateOrCancel,
+            "4" => TimeInForce::FillOrKill,
+            t => {
+                return Err(LaminarError::InvalidInput(format!(
+                    "unsupported TimeInForce {}",
+                    t
+                )))
+            }
+        };
+        let post_only = msg
+            .get(tags::EXEC_INST)
+            .map_or(false, |v| v.split(' ').any(|i| i == "6"));
+
+        Ok(Self {
+            cl_ord_id: msg.required(tags::CL_ORD_ID)?.to_string(),
+            symbol: msg.required(tags::SYMBOL)?.to_string(),
+            side: parse_side(msg.required(tags::SIDE)?)?,
+            order_qty: msg.required_u64(tags::ORDER_QTY)?,
+            price,
+            time_in_force,
+            post_only,
+        })
+    }
+}
+
+/// `OrderCancelRequest` (35=F).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OrderCancelRequest {
+    pub cl_ord_id: String,
+    pub orig_cl_ord_id: String,
+    pub symbol: String,
+    pub side: Side,
+}
+
+impl TryFrom<&FixMessage> for OrderCancelRequest {
+    type Error = LaminarError;
+
+    fn try_from(msg: &FixMessage) -> Result<Self> {
+        Ok(Self {
+            cl_ord_id: msg.required(tags::CL_ORD_ID)?.to_string(),
+            orig_cl_ord_id: msg.required(tags::ORIG_CL_ORD_ID)?.to_string(),
+            symbol: msg.required(tags::SYMBOL)?.to_string(),
+            side: parse_side(msg.required(tags::SIDE)?)?,
+        })
+    }
+}
+
+fn parse_side(side: &str) -> Result<Side> {
+    match side {
+        "1" => Ok(Side::Bid),
+        "2" => Ok(Side::Ask),
+        s => Err(LaminarError::InvalidInput(format!(
+            "unsupported Side {}",
+            s
+        ))),
+    }
+}
+
+fn side_code(side: Side) -> &'static str {
+    match side {
+        Side::Bid => "1",
+        Side::Ask => "2",
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExecType {
+    New,
+    Trade,
+    Canceled,
+    PendingCancel,
+    Rejected,
+    PendingNew,
+}
+
+impl ExecType {
+    fn code(&self) -> &'static str {
+        match self {
+            ExecType::New => "0",
+            ExecType::Canceled => "4",
+            ExecType::PendingCancel => "6",
+            ExecType::Rejected => "8",
+            ExecType::PendingNew => "A",
+            ExecType::Trade => "F",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OrdStatus {
+    New,
+    PartiallyFilled,
+    Filled,
+    Canceled,
+    PendingCancel,
+    Rejected,
+    PendingNew,
+}
+
+impl OrdStatus {
+    fn code(&self) -> &'static str {
+        match self {
+            OrdStatus::New => "0",
+            OrdStatus::PartiallyFilled => "1",
+            OrdStatus::Filled => "2",
+            OrdStatus::Canceled => "4",
+            OrdStatus::PendingCancel => "6",
+            OrdStatus::Rejected => "8",
+            OrdStatus::PendingNew => "A",
+        }
+    }
+}
+
+/// `ExecutionReport` (35=8).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExecutionReport {
+    /// Laminar order id, `NONE` for rejected orders.
+    pub order_id: String,
+    pub cl_ord_id: String,
+    pub orig_cl_ord_id: Option<String>,
+    pub exec_id: String,
+    pub exec_type: ExecType,
+    pub ord_status: OrdStatus,
+    pub symbol: String,
+    pub side: Side,
+    pub order_qty: u64,
+    pub price: Option<u64>,
+    pub last_qty: u64,
+    pub last_px: u64,
+    pub leaves_qty: u64,
+    pub cum_qty: u64,
+    /// Average fill price.
+    pub avg_px: u64,
+    pub transact_time: u64,
+    pub text: Option<String>,
+}
+
+impl ExecutionReport {
+    pub fn to_fix(&self) -> FixMessage {
+        let mut msg = FixMessage::new("8")
+            .with(tags::ORDER_ID, &self.order_id)
+            .with(tags::CL_ORD_ID, &self.cl_ord_id);
+        if let Some(orig) = &self.orig_cl_ord_id {
+            msg = msg.with(tags::ORIG_CL_ORD_ID, orig);
+        }
+        msg = msg
+            .with(tags::EXEC_ID, &self.exec_id)
+            .with(tags::EXEC_TYPE, self.exec_type.code())
+            .with(tags::ORD_STATUS, self.ord_status.code())
+            .with(tags::SYMBOL, &self.symbol)
+            .with(tags::SIDE, side_code(self.side))
+            .with(tags::ORDER_QTY, self.order_qty);
+        if let Some(price) = self.price {
+            msg = msg.with(tags::PRICE, price);
+        }
+        if self.exec_type == ExecType::Trade {
+            msg = msg
+                .with(tags::LAST_QTY, self.last_qty)
+                .with(tags::LAST_PX, self.last_px);
+        }
+        msg = msg
+            .with(tags::LEAVES_QTY, self.leaves_qty)
+            .with(tags::CUM_QTY, self.cum_qty)
+            .with(tags::AVG_PX, self.avg_px)
+            .with(tags::TRANSACT_TIME, utc_timestamp(self.transact_time));
+        if self.exec_type == ExecType::Rejected {
+            // 0 = broker / exchange option
+            msg = msg.with(tags::ORD_REJ_REASON, 0);
+        }
+        if let Some(text) = &self.text {
+            msg = msg.with(tags::TEXT, text);
+        }
+        msg
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CxlRejReason {
+    TooLateToCancel,
+    UnknownOrder,
+    BrokerOption,
+}
+
+impl CxlRejReason {
+    fn code(&self) -> &'static str {
+        match self {
+            CxlRejReason::TooLateToCancel => "0",
+            CxlRejReason::UnknownOrder => "1",
+            CxlRejReason::BrokerOption => "2",
+        }
+    }
+}
+
+/// `OrderCancelReject` (35=9), answering an `OrderCancelRequest` that was not executed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OrderCancelReject {
+    /// Laminar order id, `NONE` for unknown orders.
+    pub order_id: String,
+    pub cl_ord_id: String,
+    pub orig_cl_ord_id: String,
+    /// Status of the order the cancel was for, `Rejected` for unknown orders.
+    pub ord_status: OrdStatus,
+    pub cxl_rej_reason: CxlRejReason,
+    pub transact_time: u64,
+    pub text: Option<String>,
+}
+
+impl OrderCancelReject {
+    pub fn to_fix(&self) -> FixMessage {
+        let mut msg = FixMessage::new("9")
+            .with(tags::ORDER_ID, &self.order_id)
+            .with(tags::CL_ORD_ID, &self.cl_ord_id)
+            .with(tags::ORIG_CL_ORD_ID, &self.orig_cl_ord_id)
+            .with(tags::ORD_STATUS, self.ord_status.code())
+            .with(tags::TRANSACT_TIME, utc_timestamp(self.transact_time))
+            .with(tags::CXL_REJ_REASON, self.cxl_rej_reason.code())
+            // 1 = order cancel request
+            .with(tags::CXL_REJ_RESPONSE_TO, 1);
+        if let Some(text) = &self.text {
+            msg = msg.with(tags::TEXT, text);
+        }
+        msg
+    }
+}
+
+/// FIX `UTCTimestamp` with milliseconds, e.g. `20221101-12:00:00.000`.
+fn utc_timestamp(micros: u64) -> String {
+    let secs = micros / 1_000_000;
+    let (days, secs_of_day) = ((secs / 86_400) as i64, secs % 86_400);
+    let (year, month, day) = civil_from_days(days);
+    format!(
+        "{:04}{:02}{:02}-{:02}:{:02}:{:02}.{:03}",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60,
+        secs_of_day % 60,
+        micros % 1_000_000 / 1000
+    )
+}
+
+// order placed through the gateway
+struct FixOrder {
+    cl_ord_id: String,
+    order_id: Id,
+    symbol: String,
+    side: Side,
+    order_qty: u64,
+    price: Option<u64>,
+    cum_qty: u64,
+    // sum of price * size of the fills, for the average price
+    notional: u128,
+    leaves_qty: u64,
+    done: bool,
+    canceled: bool,
+}
+
+impl FixOrder {
+    fn ord_status(&self) -> OrdStatus {
+        match () {
+            _ if self.canceled => OrdStatus::Canceled,
+            _ if self.leaves_qty == 0 && self.cum_qty > 0 => OrdStatus::Filled,
+            _ if self.cum_qty > 0 => OrdStatus::PartiallyFilled,
+            _ => OrdStatus::New,
+        }
+    }
+
+    fn report(&self, exec_type: ExecType, exec_id: String, time: u64) -> ExecutionReport {
+        let ord_status = match exec_type {
+            ExecType::Canceled => OrdStatus::Canceled,
+            ExecType::PendingCancel => OrdStatus::PendingCancel,
+            ExecType::Rejected => OrdStatus::Rejected,
+            _ => self.ord_status(),
+        };
+        ExecutionReport {
+            order_id: self.order_id.to_string(),
+            cl_ord_id: self.cl_ord_id.clone(),
+            orig_cl_ord_id: None,
+            exec_id,
+            exec_type,
+            ord_status,
+            symbol: self.symbol.clone(),
+            side: self.side,
+            order_qty: self.order_qty,
+            price: self.price,
+            last_qty: 0,
+            last_px: 0,
+            leaves_qty: self.leaves_qty,
+            cum_qty: self.cum_qty,
+            avg_px: self
+                .notional
+                .checked_div(self.cum_qty as u128)
+                .unwrap_or_default() as u64,
+            transact_time: time,
+            text: None,
+        }
+    }
+
+    fn on_place(&mut self, e: &PlaceOrderEvent) -> ExecutionReport {
+        self.report(ExecType::New, format!("{}:new", e.order_id), e.time)
+    }
+
+    // None if the fill was reported already
+    fn on_fill(&mut self, e: &FillEvent) -> Option<ExecutionReport> {
+        if self.done || e.remaining_size >= self.leaves_qty {
+            return None;
+        }
+        self.cum_qty += e.fill_size;
+        self.notional += e.price as u128 * e.fill_size as u128;
+        self.leaves_qty = e.remaining_size;
+        self.done = e.remaining_size == 0;
+        let exec_id = format!("{}:fill:{}", e.order_id, self.cum_qty);
+        let mut report = self.report(ExecType::Trade, exec_id, e.time);
+        report.last_qty = e.fill_size;
+        report.last_px = e.price;
+        Some(report)
+    }
+
+    fn on_cancel(&mut self, e: &CancelOrderEvent) -> Option<ExecutionReport> {
+        if self.done {
+            return None;
+        }
+        self.done = true;
+        self.canceled = true;
+        self.leaves_qty = 0;
+        let exec_id = format!("{}:cancel", e.order_id);
+        Some(self.report(ExecType::Canceled, exec_id, e.time))
+    }
+}
+
+/// Executes FIX order messages on Laminar and reports order updates as `ExecutionReport`s.
+///
+/// Fills and cancels of resting orders happen after `handle` returns, pass the events of the
+/// traded books, e.g. from a `MarketDataFeed`, to `on_events` to report them. Orders are
+/// tracked by ClOrdID in memory, so cancels only find orders placed since the gateway was
+/// created. An order whose placement can't be confirmed is reported `PendingNew` without an
+/// order id, its ClOrdID is not accepted again and the OMS has to resolve it.
+pub struct FixGateway {
+    client: Arc<LaminarClient>,
+    registry: MarketRegistry,
+    sender_comp_id: String,
+    target_comp_id: String,
+    next_seq_num: u64,
+    orders: Vec<FixOrder>,
+    by_cl_ord_id: HashMap<String, usize>,
+    by_order_id: HashMap<Id, usize>,
+    // ClOrdIDs of orders whose placement may or may not have committed
+    unconfirmed: HashSet<String>,
+}
+
+impl FixGateway {
+    /// # Arguments:
+    ///
+    /// * `client` - Client submitting the orders.
+    /// * `registry` - Books the OMS can trade, by pair symbol.
+    /// * `sender_comp_id` - SenderCompID of the gateway's messages.
+    /// * `target_comp_id` - TargetCompID of the gateway's messages, the OMS.
+    pub fn new(
+        client: Arc<LaminarClient>,
+        registry: MarketRegistry,
+        sender_comp_id: &str,
+        target_comp_id: &str,
+    ) -> Self {
+        Self {
+            client,
+            registry,
+            sender_comp_id: sender_comp_id.to_string(),
+            target_comp_id: target_comp_id.to_string(),
+            next_seq_num: 1,
+            orders: vec![],
+            by_cl_ord_id: HashMap::new(),
+            by_order_id: HashMap::new(),
+            unconfirmed: HashSet::new(),
+        }
+    }
+
+    /// Continue the outgoing sequence numbers at `seq_num`, e.g. after a restart.
+    pub fn with_next_seq_num(mut self, seq_num: u64) -> Self {
+        self.next_seq_num = seq_num;
+        self
+    }
+
+    /// Execute a message of the OMS, returning the messages to send back with their standard
+    /// header set. Unsupported message types get a `BusinessMessageReject` (35=j).
+    pub async fn handle(&mut self, msg: &FixMessage) -> Vec<FixMessage> {
+        let msgs = match msg.msg_type.as_str() {
+            "D" => match NewOrderSingle::try_from(msg) {
+                Ok(order) => self.new_order(order).await,
+                Err(e) => vec![reject(msg, &e)],
+            }
+            .iter()
+            .map(ExecutionReport::to_fix)
+            .collect::<Vec<_>>(),
+            "F" => match OrderCancelRequest::try_from(msg) {
+                Ok(cancel) => self.cancel_order(cancel).await,
+                Err(e) => {
+                    let reject = cancel_rejected(
+                        msg.get(tags::CL_ORD_ID).unwrap_or_default(),
+                        msg.get(tags::ORIG_CL_ORD_ID).unwrap_or_default(),
+                        &e,
+                    );
+                    vec![reject.to_fix()]
+                }
+            },
+            msg_type => {
+                let mut reject = FixMessage::new("j")
+                    .with(tags::REF_MSG_TYPE, msg_type)
+                    // 3 = unsupported message type
+                    .with(tags::BUSINESS_REJECT_REASON, 3);
+                if let Some(seq) = msg.get(tags::MSG_SEQ_NUM) {
+                    reject = reject.with(tags::REF_SEQ_NUM, seq);
+                }
+                return vec![self.finish(reject)];
+            }
+        };
+
+        msgs.into_iter().map(|m| self.finish(m)).collect()
+    }
+
+    /// Report fills and cancels of the gateway's orders among `events`. Events already
+    /// reported are skipped.
+    pub fn on_events(&mut self, events: &[LaminarEvent]) -> Vec<FixMessage> {
+        let mut reports = vec![];
+        for event in events {
+            let Some(idx) = event.order_id().and_then(|id| self.by_order_id.get(id)) else {
+                continue;
+            };
+            let order = &mut self.orders[*idx];
+            let report = match event {
+                LaminarEvent::FillEvent(e) => order.on_fill(e),
+                LaminarEvent::CancelOrder(e) => order.on_cancel(e),
+                _ => None,
+            };
+            reports.extend(report);
+        }
+
+        reports
+            .into_iter()
+            .map(|r| self.finish(r.to_fix()))
+            .collect()
+    }
+
+    fn market(&self, symbol: &str) -> Result<&MarketEntry> {
+        self.registry
+            .get(symbol)
+            .ok_or_else(|| LaminarError::BookNotFound(symbol.to_string()))
+    }
+
+    async fn new_order(&mut self, req: NewOrderSingle) -> Vec<ExecutionReport> {
+        let report_rejected = |e: &LaminarError| ExecutionReport {
+            order_qty: req.order_qty,
+            price: req.price,
+            ..rejected(&req.cl_ord_id, &req.symbol, req.side, e)
+        };
+        if self.by_cl_ord_id.contains_key(&req.cl_ord_id)
+            || self.unconfirmed.contains(&req.cl_ord_id)
+        {
+            let e = LaminarError::InvalidInput(format!("duplicate ClOrdID {}", req.cl_ord_id));
+            return vec![report_rejected(&e)];
+        }
+        let market = match self.market(&req.symbol) {
+            Ok(entry) => entry.market.clone(),
+            Err(e) => return vec![report_rejected(&e)],
+        };
+
+        let placed = match req.price {
+            Some(price) => {
+                self.client
+                    .place_limit_order(
+                        &market.base,
+                        &market.quote,
+                        &market.book_owner,
+                        req.side,
+                        price,
+                        req.order_qty,
+                        req.time_in_force,
+                        req.post_only,
+                    )
+                    .await
+            }
+            None => {
+                self.client
+                    .place_market_order(
+                        &market.base,
+                        &market.quote,
+                        &market.book_owner,
+                        req.side,
+                        req.order_qty,
+                    )
+                    .await
+            }
+        };
+        let placed = match placed {
+            Ok(placed) => placed,
+            // the order may be live, reporting it rejected would have the OMS send it again
+            Err(e) if e.is_outcome_unknown() => {
+                self.unconfirmed.insert(req.cl_ord_id.clone());
+                return vec![ExecutionReport {
+                    exec_id: format!("{}:pending", req.cl_ord_id),
+                    exec_type: ExecType::PendingNew,
+                    ord_status: OrdStatus::PendingNew,
+                    leaves_qty: req.order_qty,
+                    ..report_rejected(&e)
+                }];
+            }
+            Err(e) => return vec![report_rejected(&e)],
+        };
+
+        let mut order = FixOrder {
+            cl_ord_id: req.cl_ord_id.clone(),
+            order_id: placed.place.order_id.clone(),
+            symbol: req.symbol.clone(),
+            side: req.side,
+            order_qty: req.order_qty,
+            price: req.price,
+            cum_qty: 0,
+            notional: 0,
+            leaves_qty: req.order_qty,
+            done: false,
+            canceled: false,
+        };
+        let mut reports = vec![order.on_place(&placed.place)];
+        reports.extend(placed.fills.iter().filter_map(|f| order.on_fill(f)));
+        reports.extend(placed.cancel.as_ref().and_then(|c| order.on_cancel(c)));
+
+        let idx = self.orders.len();
+        self.by_cl_ord_id.insert(order.cl_ord_id.clone(), idx);
+        self.by_order_id.insert(order.order_id.clone(), idx);
+        self.orders.push(order);
+        reports
+    }
+
+    async fn cancel_order(&mut self, req: OrderCancelRequest) -> Vec<FixMessage> {
+        let cancel_reject =
+            |e: &LaminarError| cancel_rejected(&req.cl_ord_id, &req.orig_cl_ord_id, e);
+        let Some(&idx) = self.by_cl_ord_id.get(&req.orig_cl_ord_id) else {
+            let e = LaminarError::NotFound(format!("order {}", req.orig_cl_ord_id));
+            let ord_status = match self.unconfirmed.contains(&req.orig_cl_ord_id) {
+                true => OrdStatus::PendingNew,
+                false => OrdStatus::Rejected,
+            };
+            let reject = OrderCancelReject {
+                ord_status,
+                cxl_rej_reason: CxlRejReason::UnknownOrder,
+                ..cancel_reject(&e)
+            };
+            return vec![reject.to_fix()];
+        };
+        let order = &self.orders[idx];
+        let order_reject = |e: &LaminarError, reason: CxlRejReason| OrderCancelReject {
+            order_id: order.order_id.to_string(),
+            ord_status: order.ord_status(),
+            cxl_rej_reason: reason,
+            ..cancel_reject(e)
+        };
+        if order.done {
+            let e = LaminarError::InvalidInput(format!("order {} is done", req.orig_cl_ord_id));
+            return vec![order_reject(&e, CxlRejReason::TooLateToCancel).to_fix()];
+        }
+        let market = match self.market(&order.symbol) {
+            Ok(entry) => entry.market.clone(),
+            Err(e) => return vec![order_reject(&e, CxlRejReason::BrokerOption).to_fix()],
+        };
+
+        let order_id = order.order_id.clone();
+        let cancel = self
+            .client
+            .cancel_order(
+                &market.base,
+                &market.quote,
+                &market.book_owner,
+                &order_id,
+                req.side,
+            )
+            .await;
+        let order = &mut self.orders[idx];
+        let report = match cancel {
+            Ok(cancel) => order.on_cancel(&cancel),
+            // the cancel may still commit, its event is reported by `on_events`
+            Err(e) if e.is_outcome_unknown() => {
+                let exec_id = format!("{}:pending_cancel:{}", order_id, req.cl_ord_id);
+                Some(ExecutionReport {
+                    text: Some(e.to_string()),
+                    ..order.report(ExecType::PendingCancel, exec_id, unix_now_micros())
+                })
+            }
+            Err(e) => {
+                let reject = OrderCancelReject {
+                    order_id: order_id.to_string(),
+                    ord_status: order.ord_status(),
+                    ..cancel_reject(&e)
+                };
+                return vec![reject.to_fix()];
+            }
+        };
+
+        report
+            .map(|r| ExecutionReport {
+                cl_ord_id: req.cl_ord_id.clone(),
+                orig_cl_ord_id: Some(req.orig_cl_ord_id.clone()),
+                ..r
+            })
+            .iter()
+            .map(ExecutionReport::to_fix)
+            .collect()
+    }
+
+    // set the standard header of an outgoing message
+    fn finish(&mut self, msg: FixMessage) -> FixMessage {
+        let mut fields = vec![
+            (tags::SENDER_COMP_ID, self.sender_comp_id.clone()),
+            (tags::TARGET_COMP_ID, self.target_comp_id.clone()),
+            (tags::MSG_SEQ_NUM, self.next_seq_num.to_string()),
+            (tags::SENDING_TIME, utc_timestamp(unix_now_micros())),
+        ];
+        self.next_seq_num += 1;
+        fields.extend(msg.fields);
+        FixMessage {
+            msg_type: msg.msg_type,
+            fields,
+        }
+    }
+}
+
+fn rejected(cl_ord_id: &str, symbol: &str, side: Side, e: &LaminarError) -> ExecutionReport {
+    ExecutionReport {
+        order_id: "NONE".to_string(),
+        cl_ord_id: cl_ord_id.to_string(),
+        orig_cl_ord_id: None,
+        exec_id: format!("{}:rejected", cl_ord_id),
+        exec_type: ExecType::Rejected,
+        ord_status: OrdStatus::Rejected,
+        symbol: symbol.to_string(),
+        side,
+        order_qty: 0,
+        price: None,
+        last_qty: 0,
+        last_px: 0,
+        leaves_qty: 0,
+        cum_qty: 0,
+        avg_px: 0,
+        transact_time: unix_now_micros(),
+        text: Some(e.to_string()),
+    }
+}
+
+fn cancel_rejected(cl_ord_id: &str, orig_cl_ord_id: &str, e: &LaminarError) -> OrderCancelReject {
+    OrderCancelReject {
+        order_id: "NONE".to_string(),
+        cl_ord_id: cl_ord_id.to_string(),
+        orig_cl_ord_id: orig_cl_ord_id.to_string(),
+        ord_status: OrdStatus::Rejected,
+        cxl_rej_reason: CxlRejReason::BrokerOption,
+        transact_time: unix_now_micros(),
+        text: Some(e.to_string()),
+    }
+}
+
+// ExecutionReport rejecting a message that could not be parsed
+fn reject(msg: &FixMessage, e: &LaminarError) -> ExecutionReport {
+    let side = msg
+        .get(tags::SIDE)
+        .and_then(|s| parse_side(s).ok())
+        .unwrap_or(Side::Bid);
+    ExecutionReport {
+        orig_cl_ord_id: msg.get(tags::ORIG_CL_ORD_ID).map(str::to_string),
+        ..rejected(
+            msg.get(tags::CL_ORD_ID).unwrap_or_default(),
+            msg.get(tags::SYMBOL).unwrap_or_default(),
+            side,
+            e,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_order() -> FixMessage {
+        FixMessage::new("D")
+            .with(tags::CL_ORD_ID, "a")
+            .with(tags::SYMBOL, "APT/USDC")
+            .with(tags::SIDE, 1)
+            .with(tags::ORDER_QTY, 10)
+            .with(tags::ORD_TYPE, 2)
+            .with(tags::PRICE, 500)
+    }
+
+    fn is_invalid<T>(res: Result<T>, reason: &str) -> bool {
+        matches!(res, Err(LaminarError::InvalidInput(m)) if m.contains(reason))
+    }
+
+    #[test]
+    fn test_encode_decode() {
+        let msg = new_order();
+        let bytes = msg.encode();
+        assert!(bytes.starts_with(b"8=FIX.4.4\x019="));
+        assert_eq!(FixMessage::decode(&bytes).unwrap(), msg);
+    }
+
+    #[test]
+    fn test_decode_rejects_corrupted_messages() {
+        let mut bytes = new_order().encode();
+        let i = bytes.iter().position(|b| *b == b'a').unwrap();
+        bytes[i] = b'b';
+        assert!(is_invalid(FixMessage::decode(&bytes), "CheckSum"));
+
+        let bytes = b"8=FIX.4.4\x019=99\x0135=0\x0110=000\x01";
+        assert!(is_invalid(FixMessage::decode(bytes), "BodyLength"));
+        assert!(is_invalid(FixMessage::decode(b"8=FIX.4.4\x01"), "checksum"));
+    }
+
+    #[test]
+    fn test_new_order_single() {
+        let order = NewOrderSingle::try_from(&new_order()).unwrap();
+        assert_eq!(
+            order,
+            NewOrderSingle {
+                cl_ord_id: "a".to_string(),
+                symbol: "APT/USDC".to_string(),
+                side: Side::Bid,
+                order_qty: 10,
+                price: Some(500),
+                time_in_force: TimeInForce::GoodTillCanceled,
+                post_only: false,
+            }
+        );
+
+        let msg = FixMessage::new("D")
+            .with(tags::CL_ORD_ID, "b")
+            .with(tags::SYMBOL, "APT/USDC")
+            .with(tags::SIDE, 2)
+            .with(tags::ORDER_QTY, 10)
+            .with(tags::ORD_TYPE, 1)
+            .with(tags::TIME_IN_FORCE, 3)
+            .with(tags::EXEC_INST, "G 6");
+        let order = NewOrderSingle::try_from(&msg).unwrap();
+        assert_eq!(order.side, Side::Ask);
+        assert_eq!(order.price, None);
+        assert_eq!(order.time_in_force, TimeInForce::ImmediateOrCancel);
+        assert!(order.post_only);
+
+        let msg = new_order().with(tags::TIME_IN_FORCE, 6);
+        assert!(is_invalid(NewOrderSingle::try_from(&msg), "TimeInForce"));
+        let msg = FixMessage::new("D").with(tags::ORD_TYPE, 2);
+        assert!(is_invalid(NewOrderSingle::try_from(&msg), "tag 44"));
+    }
+
+    #[test]
+    fn test_order_cancel_request() {
+        let msg = FixMessage::new("F")
+            .with(tags::CL_ORD_ID, "b")
+            .with(tags::ORIG_CL_ORD_ID, "a")
+            .with(tags::SYMBOL, "APT/USDC")
+            .with(tags::SIDE, 2);
+        let cancel = OrderCancelRequest::try_from(&msg).unwrap();
+        assert_eq!(cancel.orig_cl_ord_id, "a");
+        assert_eq!(cancel.side, Side::Ask);
+
+        let msg = FixMessage::new("F")
+            .with(tags::CL_ORD_ID, "b")
+            .with(tags::ORIG_CL_ORD_ID, "a")
+            .with(tags::SYMBOL, "APT/USDC")
+            .with(tags::SIDE, 5);
+        assert!(is_invalid(OrderCancelRequest::try_from(&msg), "Side"));
+    }
+
+    #[test]
+    fn test_utc_timestamp() {
+        assert_eq!(utc_timestamp(0), "19700101-00:00:00.000");
+        assert_eq!(
+            utc_timestamp(1_667_304_000_123_456),
+            "20221101-12:00:00.123"
+        );
+    }
+
+    #[test]
+    fn test_execution_report_fields() {
+        let mut report = ExecutionReport {
+            order_id: "1".to_string(),
+            cl_ord_id: "a".to_string(),
+            orig_cl_ord_id: None,
+            exec_id: "1-0".to_string(),
+            exec_type: ExecType::New,
+            ord_status: OrdStatus::New,
+            symbol: "APT/USDC".to_string(),
+            side: Side::Bid,
+            order_qty: 10,
+            price: Some(500),
+            last_qty: 0,
+            last_px: 0,
+            leaves_qty: 10,
+            cum_qty: 0,
+            avg_px: 0,
+            transact_time: 0,
+            text: None,
+        };
+        let msg = report.to_fix();
+        assert_eq!(msg.msg_type, "8");
+        assert_eq!(msg.get(tags::EXEC_TYPE), Some("0"));
+        assert_eq!(msg.get(tags::LAST_QTY), None);
+
+        report.exec_type = ExecType::Trade;
+        report.ord_status = OrdStatus::PartiallyFilled;
+        report.last_qty = 4;
+        report.last_px = 500;
+        let msg = report.to_fix();
+        assert_eq!(msg.get(tags::EXEC_TYPE), Some("F"));
+        assert_eq!(msg.get(tags::ORD_STATUS), Some("1"));
+        assert_eq!(msg.get(tags::LAST_QTY), Some("4"));
+        assert_eq!(msg.get(tags::LAST_PX), Some("500"));
+    }
+}
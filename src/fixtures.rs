@@ -0,0 +1,118 @@
+use crate::encoding::{write_records, JsonEncoder};
+use crate::types::events::LaminarEvent;
+use crate::types::order::Id;
+use anyhow::{Context, Result};
+use aptos_api_types::Address;
+use aptos_sdk::types::account_address::AccountAddress;
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+
+/// Transforms recorded production events into backtest fixtures.
+///
+/// Account addresses are replaced by stable pseudonyms (the same address always maps to
+/// the same pseudonym within one builder) and timestamps are re-based onto `time_origin`.
+/// Coin type addresses are left untouched since they identify public coin modules.
+#[derive(Debug, Default)]
+pub struct FixtureBuilder {
+    time_origin: u64,
+    pseudonyms: HashMap<AccountAddress, AccountAddress>,
+}
+
+impl FixtureBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the timestamp the earliest event of the fixture is moved to.
+    pub fn with_time_origin(mut self, time_origin: u64) -> Self {
+        self.time_origin = time_origin;
+        self
+    }
+
+    /// Addresses seen so far, mapped to their pseudonyms.
+    pub fn pseudonyms(&self) -> &HashMap<AccountAddress, AccountAddress> {
+        &self.pseudonyms
+    }
+
+    fn anonymize_address(&mut self, addr: &AccountAddress) -> AccountAddress {
+        let next = self.pseudonyms.len() as u64 + 1;
+        *self.pseudonyms.entry(*addr).or_insert_with(|| {
+            let mut bytes = [0u8; AccountAddress::LENGTH];
+            bytes[AccountAddress::LENGTH - 8..].copy_from_slice(&next.to_be_bytes());
+            AccountAddress::new(bytes)
+        })
+    }
+
+    fn anonymize_id(&mut self, id: &mut Id) {
+        let addr = self.anonymize_address(id.addr.inner());
+        id.addr = Address::from(addr);
+    }
+
+    fn anonymize(&mut self, event: &mut LaminarEvent) {
+        match event {
+            LaminarEvent::CreateOrderBook(e) => {
+                self.anonymize_id(&mut e.book_id);
+                e.creator = self.anonymize_address(&e.creator);
+            }
+            LaminarEvent::PlaceOrder(e) => {
+                self.anonymize_id(&mut e.book_id);
+                self.anonymize_id(&mut e.order_id);
+            }
+            LaminarEvent::AmendOrder(e) => {
+                self.anonymize_id(&mut e.book_id);
+                self.anonymize_id(&mut e.order_id);
+                self.anonymize_id(&mut e.amend_id);
+            }
+            LaminarEvent::CancelOrder(e) => {
+                self.anonymize_id(&mut e.book_id);
+                self.anonymize_id(&mut e.order_id);
+                self.anonymize_id(&mut e.cancel_id);
+            }
+            LaminarEvent::FillEvent(e) => {
+                self.anonymize_id(&mut e.book_id);
+                self.anonymize_id(&mut e.order_id);
+            }
+            LaminarEvent::Unknown { .. } => {}
+        }
+    }
+
+    /// Anonymize and re-base a recorded event stream.
+    /// Events are sorted by time, the relative order of events with equal time is kept.
+    /// Unknown events are dropped since the addresses they carry cannot be anonymized.
+    pub fn build(&mut self, mut events: Vec<LaminarEvent>) -> Vec<LaminarEvent> {
+        events.retain(|e| !matches!(e, LaminarEvent::Unknown { .. }));
+        events.sort_by_key(|e| e.time());
+        let start = events.first().map_or(0, |e| e.time());
+        for e in events.iter_mut() {
+            self.anonymize(e);
+            let time = e.time() - start + self.time_origin;
+            e.set_time(time);
+        }
+
+        events
+    }
+
+    /// Read an archive of JSON encoded events, one per line, and build a fixture from it.
+    pub fn build_from_json_lines<R: BufRead>(&mut self, reader: R) -> Result<Vec<LaminarEvent>> {
+        let events = read_json_lines(reader)?;
+        Ok(self.build(events))
+    }
+}
+
+/// Read JSON encoded events, one per line. Empty lines are skipped.
+pub fn read_json_lines<R: BufRead>(reader: R) -> Result<Vec<LaminarEvent>> {
+    reader
+        .lines()
+        .enumerate()
+        .filter(|(_, l)| !matches!(l, Ok(l) if l.trim().is_empty()))
+        .map(|(i, l)| {
+            let line = l.context("failed reading event archive")?;
+            serde_json::from_str(&line).with_context(|| format!("invalid event on line {}", i + 1))
+        })
+        .collect()
+}
+
+/// Write events as JSON, one per line.
+pub fn write_json_lines<W: Write>(writer: W, events: &[LaminarEvent]) -> Result<()> {
+    write_records(writer, &JsonEncoder, events)
+}
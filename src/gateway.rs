@@ -0,0 +1,320 @@
+//! REST API over a shared `LaminarClient`, to run the SDK as an exchange gateway service.
+//!
+//! # Endpoints
+//!
+//! * `GET /books` - All order books, as `CreateOrderBookEvent`s.
+//! * `GET /books/{book_id}/l2?depth=N` - Aggregated depth of a book, 50 levels by default.
+//! * `GET /orders/{order_id}` - Order of the gateway's account, with state and fills.
+//! * `POST /orders` - Place an order, see `PlaceOrderRequest`. Returns the `PlacedOrder`.
+//! * `DELETE /orders/{order_id}?book_id=..&side=..` - Cancel an order.
+//!
+//! Ids are written as `{address}:{creation_number}`. Errors are returned as
+//! `{"error": "..", "abort_code": N}` with a 4xx or 5xx status.
+
+use crate::error::LaminarError;
+use crate::types::events::{CancelOrderEvent, CreateOrderBookEvent, FillEvent};
+use crate::types::l2::L2Book;
+use crate::types::market::Market;
+use crate::types::order::{Id, Order, Side, State, TimeInForce};
+use crate::{LaminarClient, PlacedOrder};
+use anyhow::anyhow;
+use axum::extract::{Path, Query, State as AxumState};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+
+type Result<T> = std::result::Result<T, GatewayError>;
+
+/// Body of `POST /orders`.
+#[derive(Clone, Debug, Deserialize)]
+pub struct PlaceOrderRequest {
+    pub book_id: String,
+    pub side: Side,
+    /// Limit price in units, omit it for a market order.
+    pub price: Option<u64>,
+    pub size: u64,
+    /// Time in force of limit orders, good till canceled by default.
+    pub time_in_force: Option<TimeInForce>,
+    #[serde(default)]
+    pub post_only: bool,
+}
+
+/// `Order` as returned by `GET /orders/{order_id}`.
+#[derive(Clone, Debug, Serialize)]
+pub struct OrderView {
+    pub id: Id,
+    pub side: Side,
+    pub price: u64,
+    pub size: u64,
+    pub post_only: bool,
+    pub remaining_size: u64,
+    pub state: State,
+    pub fills: Vec<FillEvent>,
+}
+
+impl From<Order> for OrderView {
+    fn from(order: Order) -> Self {
+        Self {
+            id: order.id,
+            side: order.side,
+            price: order.price,
+            size: order.size,
+            post_only: order.post_only,
+            remaining_size: order.remaining_size,
+            state: order.state,
+            fills: order.fills,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct DepthQuery {
+    depth: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CancelQuery {
+    book_id: String,
+    side: Side,
+}
+
+#[derive(Serialize)]
+struct ErrorBody {
+    error: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    abort_code: Option<u64>,
+}
+
+/// Error of a gateway request, returned as an HTTP response.
+#[derive(Debug)]
+pub enum GatewayError {
+    Laminar(LaminarError),
+    /// The request is missing the auth token.
+    Unauthorized,
+}
+
+impl From<LaminarError> for GatewayError {
+    fn from(e: LaminarError) -> Self {
+        Self::Laminar(e)
+    }
+}
+
+impl IntoResponse for GatewayError {
+    fn into_response(self) -> Response {
+        let e = match self {
+            GatewayError::Laminar(e) => e,
+            GatewayError::Unauthorized => {
+                let body = ErrorBody {
+                    error: "unauthorized".to_string(),
+                    abort_code: None,
+                };
+                return (StatusCode::UNAUTHORIZED, Json(body)).into_response();
+            }
+        };
+        let status = match &e {
+            LaminarError::NotFound(_) | LaminarError::BookNotFound(_) => StatusCode::NOT_FOUND,
+            LaminarError::InvalidInput(_) | LaminarError::Rejected(_) => StatusCode::BAD_REQUEST,
+            LaminarError::MoveAbort { .. } | LaminarError::ExecutionFailure { .. } => {
+                StatusCode::UNPROCESSABLE_ENTITY
+            }
+            LaminarError::DeadlineExceeded { .. } | LaminarError::Unconfirmed(_) => {
+                StatusCode::GATEWAY_TIMEOUT
+            }
+            _ => StatusCode::BAD_GATEWAY,
+        };
+        let body = ErrorBody {
+            error: e.to_string(),
+            abort_code: e.abort_code(),
+        };
+        (status, Json(body)).into_response()
+    }
+}
+
+#[derive(Clone)]
+struct GatewayState {
+    client: Arc<LaminarClient>,
+    auth_token: Option<String>,
+    markets: Arc<RwLock<HashMap<Id, Market>>>,
+}
+
+impl GatewayState {
+    // markets don't change once created, so they are resolved once per book
+    async fn market(&self, book_id: &Id) -> Result<Market> {
+        let cached = self
+            .markets
+            .read()
+            .expect("lock poisoned")
+            .get(book_id)
+            .cloned();
+        if let Some(market) = cached {
+            return Ok(market);
+        }
+        let market = self.client.fetch_market_by_id(book_id).await?;
+        self.markets
+            .write()
+            .expect("lock poisoned")
+            .insert(book_id.clone(), market.clone());
+        Ok(market)
+    }
+
+    fn authorize(&self, headers: &HeaderMap) -> Result<()> {
+        let Some(token) = &self.auth_token else {
+            return Ok(());
+        };
+        let expected = format!("Bearer {}", token);
+        match headers.get("authorization").and_then(|v| v.to_str().ok()) {
+            Some(value) if value == expected => Ok(()),
+            _ => Err(GatewayError::Unauthorized),
+        }
+    }
+}
+
+/// Builds the gateway's axum `Router`.
+pub struct Gateway {
+    state: GatewayState,
+}
+
+impl Gateway {
+    /// # Arguments:
+    ///
+    /// * `client` - Client serving the requests, orders are placed from its account.
+    pub fn new(client: Arc<LaminarClient>) -> Self {
+        Self {
+            state: GatewayState {
+                client,
+                auth_token: None,
+                markets: Arc::new(RwLock::new(HashMap::new())),
+            },
+        }
+    }
+
+    /// Require `Authorization: Bearer {token}` on `POST` and `DELETE` requests.
+    pub fn with_auth_token(mut self, token: impl Into<String>) -> Self {
+        self.state.auth_token = Some(token.into());
+        self
+    }
+
+    pub fn router(&self) -> Router {
+        Router::new()
+            .route("/books", get(books))
+            .route("/books/:book_id/l2", get(l2))
+            .route("/orders", post(place_order))
+            .route("/orders/:order_id", get(order).delete(cancel_order))
+            .with_state(self.state.clone())
+    }
+
+    /// Serve the gateway on `addr` until the server fails.
+    pub async fn serve(&self, addr: SocketAddr) -> crate::error::Result<()> {
+        axum::Server::try_bind(&addr)
+            .map_err(|e| LaminarError::InvalidInput(format!("bind {}: {}", addr, e)))?
+            .serve(self.router().into_make_service())
+            .await
+            .map_err(|e| anyhow!("gateway server failed: {}", e))?;
+        Ok(())
+    }
+}
+
+fn parse_id(s: &str) -> Result<Id> {
+    Id::from_str(s)
+        .map_err(|e| LaminarError::InvalidInput(format!("invalid id {}: {}", s, e)).into())
+}
+
+async fn books(
+    AxumState(state): AxumState<GatewayState>,
+) -> Result<Json<Vec<CreateOrderBookEvent>>> {
+    Ok(Json(state.client.fetch_order_books().await?))
+}
+
+async fn l2(
+    AxumState(state): AxumState<GatewayState>,
+    Path(book_id): Path<String>,
+    Query(query): Query<DepthQuery>,
+) -> Result<Json<L2Book>> {
+    let book_id = parse_id(&book_id)?;
+    let market = state.market(&book_id).await?;
+    let book = state
+        .client
+        .fetch_orderbook(&market.base, &market.quote, &market.book_owner)
+        .await?;
+    Ok(Json(book.to_l2(query.depth.unwrap_or(50))))
+}
+
+async fn order(
+    AxumState(state): AxumState<GatewayState>,
+    Path(order_id): Path<String>,
+) -> Result<Json<OrderView>> {
+    let order_id = parse_id(&order_id)?;
+    let order = state.client.get_order(&order_id).await?;
+    Ok(Json(order.into()))
+}
+
+async fn place_order(
+    AxumState(state): AxumState<GatewayState>,
+    headers: HeaderMap,
+    Json(req): Json<PlaceOrderRequest>,
+) -> Result<Json<PlacedOrder>> {
+    state.authorize(&headers)?;
+    let book_id = parse_id(&req.book_id)?;
+    let market = state.market(&book_id).await?;
+    let placed = match req.price {
+        Some(price) => {
+            state
+                .client
+                .place_limit_order(
+                    &market.base,
+                    &market.quote,
+                    &market.book_owner,
+                    req.side,
+                    price,
+                    req.size,
+                    req.time_in_force.unwrap_or(TimeInForce::GoodTillCanceled),
+                    req.post_only,
+                )
+                .await?
+        }
+        None => {
+            state
+                .client
+                .place_market_order(
+                    &market.base,
+                    &market.quote,
+                    &market.book_owner,
+                    req.side,
+                    req.size,
+                )
+                .await?
+        }
+    };
+
+    Ok(Json(placed))
+}
+
+async fn cancel_order(
+    AxumState(state): AxumState<GatewayState>,
+    headers: HeaderMap,
+    Path(order_id): Path<String>,
+    Query(query): Query<CancelQuery>,
+) -> Result<Json<CancelOrderEvent>> {
+    state.authorize(&headers)?;
+    let order_id = parse_id(&order_id)?;
+    let book_id = parse_id(&query.book_id)?;
+    let market = state.market(&book_id).await?;
+    let cancel = state
+        .client
+        .cancel_order(
+            &market.base,
+            &market.quote,
+            &market.book_owner,
+            &order_id,
+            query.side,
+        )
+        .await?;
+
+    Ok(Json(cancel))
+}
@@ -0,0 +1,242 @@
+//! Laminar events from the transaction stream of an Aptos indexer-grpc data service.
+
+use crate::backfill::VersionedEvent;
+use crate::error::{LaminarError, Result};
+use crate::market_data::MarketDataUpdate;
+use crate::types::events::{EventKey, LaminarEvent};
+use crate::types::order::Id;
+use anyhow::{anyhow, Context};
+use aptos_protos::indexer::v1::raw_data_client::RawDataClient;
+use aptos_protos::indexer::v1::{GetTransactionsRequest, TransactionsResponse};
+use aptos_protos::transaction::v1::transaction::TxnData;
+use aptos_protos::transaction::v1::Transaction;
+use aptos_sdk::crypto::HashValue;
+use aptos_sdk::types::account_address::AccountAddress;
+use tonic::metadata::MetadataValue;
+use tonic::transport::{Channel, ClientTlsConfig, Endpoint};
+use tonic::Streaming;
+
+/// Consumes the transaction stream of an indexer-grpc data service and yields the Laminar
+/// events of all accounts as transactions are committed, without polling a node.
+///
+/// The stream is opened on the first call to `next` and reopened from the version after the
+/// last returned batch after an error, so a consumer can retry by calling `next` again.
+pub struct TransactionStream {
+    client: RawDataClient<Channel>,
+    laminar: AccountAddress,
+    auth_token: Option<String>,
+    next_version: u64,
+    book_id: Option<Id>,
+    stream: Option<Streaming<TransactionsResponse>>,
+}
+
+impl TransactionStream {
+    /// # Arguments:
+    ///
+    /// * `endpoint` - Url of the data service, e.g. `https://grpc.mainnet.aptoslabs.com:443`.
+    /// * `laminar` - Address the Laminar modules are deployed at.
+    /// * `start_version` - Version of the first transaction to stream.
+    pub async fn connect(
+        endpoint: &str,
+        laminar: AccountAddress,
+        start_version: u64,
+    ) -> Result<Self> {
+        let mut endpoint = Endpoint::from_shared(endpoint.to_string())
+            .map_err(|e| LaminarError::InvalidInput(format!("grpc endpoint: {}", e)))?;
+        if endpoint.uri().scheme_str() == Some("https") {
+            endpoint = endpoint
+                .tls_config(ClientTlsConfig::new())
+                .context("failed configuring grpc tls")?;
+        }
+        let channel = endpoint
+            .connect()
+            .await
+            .context("failed connecting to grpc data service")?;
+
+        Ok(Self {
+            client: RawDataClient::new(channel),
+            laminar,
+            auth_token: None,
+            next_version: start_version,
+            book_id: None,
+            stream: None,
+        })
+    }
+
+    /// Send `authorization: Bearer {auth_token}` when opening the stream.
+    pub fn with_auth_token(mut self, auth_token: impl Into<String>) -> Self {
+        self.auth_token = Some(auth_token.into());
+        self
+    }
+
+    /// Only yield the events of one book.
+    pub fn with_book(mut self, book_id: Id) -> Self {
+        self.book_id = Some(book_id);
+        self
+    }
+
+    /// Version of the next transaction to receive, store it to resume the stream later.
+    pub fn next_version(&self) -> u64 {
+        self.next_version
+    }
+
+    /// Wait for the next batch of transactions, returning their Laminar events in version and
+    /// event order. Batches without Laminar events return an empty vec, events that fail to
+    /// decode are returned as `LaminarEvent::Unknown`. Returns `None` if the data service ended
+    /// the stream.
+    pub async fn next(&mut self) -> Result<Option<Vec<VersionedEvent>>> {
+        if self.stream.is_none() {
+            self.stream = Some(self.open().await?);
+        }
+        let stream = self.stream.as_mut().expect("stream is open");
+
+        let res = match stream.message().await {
+            Ok(Some(res)) => res,
+            Ok(None) => {
+                self.stream = None;
+                return Ok(None);
+            }
+            Err(e) => {
+                self.stream = None;
+                return Err(anyhow!("grpc transaction stream failed: {}", e).into());
+            }
+        };
+
+        // a batch is consumed whole or not at all, after an error the stream is reopened at
+        // its first transaction
+        let mut events = vec![];
+        let mut next_version = self.next_version;
+        for tx in &res.transactions {
+            match self.laminar_events(tx) {
+                Ok(tx_events) => events.extend(tx_events),
+                Err(e) => {
+                    self.stream = None;
+                    return Err(e);
+                }
+            }
+            next_version = next_version.max(tx.version + 1);
+        }
+        self.next_version = next_version;
+        if let Some(book_id) = &self.book_id {
+            events.retain(|e| e.event.book_id() == Some(book_id));
+        }
+        trace_event!(
+            debug,
+            transactions = res.transactions.len(),
+            events = events.len(),
+            next_version = self.next_version,
+            "received transactions"
+        );
+
+        Ok(Some(events))
+    }
+
+    /// Same as `next`, as the updates a `MarketDataFeed` delivers.
+    pub async fn next_updates(&mut self) -> Result<Option<Vec<MarketDataUpdate>>> {
+        let events = self.next().await?;
+        Ok(events.map(|events| {
+            events
+                .into_iter()
+                .map(|e| MarketDataUpdate::Event(e.event))
+                .collect()
+        }))
+    }
+
+    async fn open(&mut self) -> Result<Streaming<TransactionsResponse>> {
+        let mut request = tonic::Request::new(GetTransactionsRequest {
+            starting_version: Some(self.next_version),
+            ..Default::default()
+        });
+        if let Some(token) = &self.auth_token {
+            let value = MetadataValue::try_from(format!("Bearer {}", token))
+                .map_err(|e| LaminarError::InvalidInput(format!("grpc auth token: {}", e)))?;
+            request.metadata_mut().insert("authorization", value);
+        }
+
+        let stream = self
+            .client
+            .get_transactions(request)
+            .await
+            .map_err(|e| anyhow!("failed opening grpc transaction stream: {}", e))?
+            .into_inner();
+        Ok(stream)
+    }
+
+    fn laminar_events(&self, tx: &Transaction) -> Result<Vec<VersionedEvent>> {
+        let Some(TxnData::User(ut)) = &tx.txn_data else {
+            return Ok(vec![]);
+        };
+        let Some(info) = tx.info.as_ref().filter(|info| info.success) else {
+            return Ok(vec![]);
+        };
+
+        let mut res = vec![];
+        for (event_index, e) in ut.events.iter().enumerate() {
+            let is_laminar = e
+                .type_str
+                .split_once("::")
+                .and_then(|(addr, _)| AccountAddress::from_hex_literal(addr).ok())
+                .map_or(false, |addr| addr == self.laminar);
+            if !is_laminar {
+                continue;
+            }
+            let data = serde_json::from_str::<serde_json::Value>(&e.data)
+                .map_err(|err| LaminarError::deserialization(err, e.type_str.clone()))?;
+            let event = match LaminarEvent::from_move_event(&e.type_str, data.clone()) {
+                Ok(event) => event,
+                Err(_err) => {
+                    trace_event!(
+                        warn,
+                        version = tx.version,
+                        event_index,
+                        error = %_err,
+                        "failed decoding laminar event"
+                    );
+                    LaminarEvent::Unknown {
+                        type_name: e.type_str.clone(),
+                        data,
+                    }
+                }
+            };
+            let hash = HashValue::from_slice(&info.hash)
+                .map_err(|err| anyhow!("invalid hash of transaction {}: {}", tx.version, err))?;
+            let sender = ut
+                .request
+                .as_ref()
+                .map(|r| AccountAddress::from_hex_literal(&r.sender))
+                .transpose()
+                .map_err(|err| anyhow!("invalid sender of transaction {}: {}", tx.version, err))?
+                .unwrap_or(AccountAddress::ZERO);
+            let timestamp = tx
+                .timestamp
+                .as_ref()
+                .map_or(0, |t| t.seconds as u64 * 1_000_000 + t.nanos as u64 / 1_000);
+            let key = match &e.key {
+                Some(key) => EventKey {
+                    account_address: AccountAddress::from_hex_literal(&key.account_address)
+                        .map_err(|err| {
+                            anyhow!("invalid event key of transaction {}: {}", tx.version, err)
+                        })?,
+                    creation_number: key.creation_number,
+                },
+                None => EventKey {
+                    account_address: AccountAddress::ZERO,
+                    creation_number: 0,
+                },
+            };
+
+            res.push(VersionedEvent {
+                version: tx.version,
+                event_index,
+                hash: hash.into(),
+                sender,
+                timestamp,
+                key,
+                sequence_number: e.sequence_number,
+                event,
+            });
+        }
+
+        Ok(res)
+    }
+}
@@ -0,0 +1,164 @@
+use crate::error::{LaminarError, Result};
+use crate::signer::TransactionSigner;
+use crate::LaminarClient;
+use anyhow::Context;
+use aptos_sdk::rest_client::Client;
+use aptos_sdk::types::account_address::AccountAddress;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+#[cfg(not(all(target_arch = "wasm32", feature = "wasm")))]
+use reqwest::Proxy;
+use reqwest::Url;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Timeout of node requests, same as the default of the aptos rest client.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Settings of the http client used for node requests, e.g. to go through a proxy or to pass
+/// the API key of a managed fullnode provider.
+#[derive(Clone, Debug)]
+pub struct HttpConfig {
+    pub timeout: Duration,
+    pub connect_timeout: Option<Duration>,
+    /// Proxy for all requests. System proxies from `HTTP_PROXY` and `HTTPS_PROXY` are used
+    /// if unset.
+    pub proxy: Option<Url>,
+    /// Maximum idle connections kept per host, unlimited if unset.
+    pub pool_max_idle_per_host: Option<usize>,
+    /// Headers sent with every request.
+    pub headers: Vec<(String, String)>,
+    pub user_agent: Option<String>,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            timeout: DEFAULT_TIMEOUT,
+            connect_timeout: None,
+            proxy: None,
+            pool_max_idle_per_host: None,
+            headers: vec![],
+            user_agent: None,
+        }
+    }
+}
+
+impl HttpConfig {
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_proxy(mut self, proxy: Url) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    pub fn with_pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.pool_max_idle_per_host = Some(max);
+        self
+    }
+
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Send `Authorization: Bearer {api_key}` with every request.
+    pub fn with_bearer_token(self, api_key: &str) -> Self {
+        self.with_header("Authorization", format!("Bearer {}", api_key))
+    }
+
+    pub fn with_user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Build a reqwest client with these settings. In the browser only `headers` apply and
+    /// setting a `proxy` is an error.
+    pub fn build_http_client(&self) -> Result<reqwest::Client> {
+        let mut headers = HeaderMap::new();
+        for (name, value) in &self.headers {
+            let name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|e| LaminarError::InvalidInput(format!("header {}: {}", name, e)))?;
+            let mut value = HeaderValue::from_str(value)
+                .map_err(|e| LaminarError::InvalidInput(format!("header {}: {}", name, e)))?;
+            // keeps api keys out of debug output
+            value.set_sensitive(true);
+            headers.append(name, value);
+        }
+
+        self.build_with_headers(headers)
+    }
+
+    #[cfg(not(all(target_arch = "wasm32", feature = "wasm")))]
+    fn build_with_headers(&self, headers: HeaderMap) -> Result<reqwest::Client> {
+        let mut builder = reqwest::Client::builder()
+            .timeout(self.timeout)
+            .default_headers(headers);
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(proxy) = &self.proxy {
+            let proxy = Proxy::all(proxy.clone())
+                .map_err(|e| LaminarError::InvalidInput(format!("proxy {}: {}", proxy, e)))?;
+            builder = builder.proxy(proxy);
+        }
+        if let Some(max) = self.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max);
+        }
+        if let Some(user_agent) = &self.user_agent {
+            builder = builder.user_agent(user_agent);
+        }
+
+        Ok(builder.build().context("failed building http client")?)
+    }
+
+    // requests go through the browser's fetch, which owns timeouts, connections and the
+    // user agent. A proxy can't be set there, so one is an error rather than ignored.
+    #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+    fn build_with_headers(&self, headers: HeaderMap) -> Result<reqwest::Client> {
+        if let Some(proxy) = &self.proxy {
+            return Err(LaminarError::InvalidInput(format!(
+                "proxy {}: not supported in the browser",
+                proxy
+            )));
+        }
+
+        Ok(reqwest::Client::builder()
+            .default_headers(headers)
+            .build()
+            .context("failed building http client")?)
+    }
+
+    /// Build an aptos rest client for `node_url` with these settings.
+    pub fn build_client(&self, node_url: Url) -> Result<Client> {
+        Ok(Client::from((self.build_http_client()?, node_url)))
+    }
+}
+
+impl LaminarClient {
+    /// Connect with custom http settings, see `HttpConfig`.
+    ///
+    /// # Arguments:
+    ///
+    /// * `node_url` - Url of aptos node.
+    /// * `laminar_address` - Aptos `AccountAddress`.
+    /// * `signer` - `TransactionSigner` of the user account.
+    /// * `http_config` - Settings of the http client.
+    pub async fn connect_with_http_config(
+        node_url: Url,
+        laminar: AccountAddress,
+        signer: Arc<dyn TransactionSigner>,
+        http_config: &HttpConfig,
+    ) -> Result<Self> {
+        let http_client = http_config.build_http_client()?;
+        let aptos_client = Client::from((http_client.clone(), node_url));
+        Self::connect_with_client(aptos_client, http_client, laminar, signer).await
+    }
+}
@@ -0,0 +1,272 @@
+use crate::error::Result;
+use crate::types::events::{FillEvent, LaminarEvent};
+use crate::types::order::{Id, Side, TimeInForce};
+use crate::LaminarClient;
+use aptos_sdk::move_types::language_storage::TypeTag;
+use aptos_sdk::types::account_address::AccountAddress;
+
+/// Visible part of an iceberg order currently resting on the book.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Clip {
+    pub order_id: Id,
+    pub size: u64,
+    pub remaining_size: u64,
+}
+
+/// Emulates an iceberg order: only `display_size` of `total_size` rests on the book at a time,
+/// and a new clip is placed at the same price once the previous one is filled.
+///
+/// The executor is driven by the account's event stream through `on_event`. Fills are tracked
+/// by the remaining size they report, so a fill delivered both by `start` and by the event
+/// stream is only counted once. The executor stops if a clip is cancelled outside of it.
+#[derive(Clone, Debug)]
+pub struct IcebergExecutor {
+    base: TypeTag,
+    quote: TypeTag,
+    book_owner: AccountAddress,
+    side: Side,
+    price: u64,
+    total_size: u64,
+    display_size: u64,
+    post_only: bool,
+    completed: u64,
+    clip: Option<Clip>,
+    done: bool,
+}
+
+impl IcebergExecutor {
+    /// # Arguments:
+    ///
+    /// * `base` - Aptos `TypeTag` of the orderbook base coin.
+    /// * `quote` - Aptos `TypeTag` of the orderbook quote coin.
+    /// * `book_owner` - Address of the account that owns the `OrderBook`.
+    /// * `side` - `Side`: Bid or Ask.
+    /// * `price` - Price of every clip.
+    /// * `total_size` - Size to execute over all clips.
+    /// * `display_size` - Size of each clip.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        base: TypeTag,
+        quote: TypeTag,
+        book_owner: AccountAddress,
+        side: Side,
+        price: u64,
+        total_size: u64,
+        display_size: u64,
+    ) -> Self {
+        Self {
+            base,
+            quote,
+            book_owner,
+            side,
+            price,
+            total_size,
+            display_size: display_size.max(1),
+            post_only: false,
+            completed: 0,
+            clip: None,
+            done: false,
+        }
+    }
+
+    /// Place clips as post only orders.
+    pub fn with_post_only(mut self, post_only: bool) -> Self {
+        self.post_only = post_only;
+        self
+    }
+
+    pub fn clip(&self) -> Option<&Clip> {
+        self.clip.as_ref()
+    }
+
+    pub fn filled_size(&self) -> u64 {
+        self.completed + self.clip.as_ref().map_or(0, |c| c.size - c.remaining_size)
+    }
+
+    pub fn remaining_size(&self) -> u64 {
+        self.total_size - self.filled_size()
+    }
+
+    /// Whether the total size was filled or the executor was stopped.
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// Place the first clip.
+    pub async fn start(&mut self, client: &LaminarClient) -> Result<()> {
+        if self.clip.is_none() && !self.done {
+            self.place_next(client).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Apply a Laminar event, placing the next clip if it completes the current one.
+    /// Events of other orders are ignored.
+    pub async fn on_event(&mut self, client: &LaminarClient, event: &LaminarEvent) -> Result<()> {
+        let Some(clip) = &self.clip else {
+            return Ok(());
+        };
+
+        match event {
+            LaminarEvent::FillEvent(f) if f.order_id == clip.order_id => {
+                if self.apply_fill(f) && !self.done {
+                    self.place_next(client).await?;
+                }
+            }
+            LaminarEvent::CancelOrder(c) if c.order_id == clip.order_id => self.stop(),
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Cancel the resting clip and stop placing new ones.
+    pub async fn cancel(&mut self, client: &LaminarClient) -> Result<()> {
+        if let Some(clip) = &self.clip {
+            if !self.done && clip.remaining_size > 0 {
+                client
+                    .cancel_order(
+                        &self.base,
+                        &self.quote,
+                        &self.book_owner,
+                        &clip.order_id,
+                        self.side,
+                    )
+                    .await?;
+            }
+        }
+        self.stop();
+
+        Ok(())
+    }
+
+    fn stop(&mut self) {
+        self.done = true;
+    }
+
+    // returns whether the fill completed the current clip
+    fn apply_fill(&mut self, fill: &FillEvent) -> bool {
+        let Some(clip) = &mut self.clip else {
+            return false;
+        };
+        if fill.remaining_size >= clip.remaining_size {
+            return false;
+        }
+
+        clip.remaining_size = fill.remaining_size;
+        clip.remaining_size == 0
+    }
+
+    async fn place_next(&mut self, client: &LaminarClient) -> Result<()> {
+        loop {
+            if let Some(clip) = self.clip.take() {
+                self.completed += clip.size - clip.remaining_size;
+            }
+            let size = self.display_size.min(self.total_size - self.completed);
+            if size == 0 {
+                self.stop();
+                return Ok(());
+            }
+
+            let placed = client
+                .place_limit_order(
+                    &self.base,
+                    &self.quote,
+                    &self.book_owner,
+                    self.side,
+                    self.price,
+                    size,
+                    TimeInForce::GoodTillCanceled,
+                    self.post_only,
+                )
+                .await?;
+            self.clip = Some(Clip {
+                order_id: placed.order_id().clone(),
+                size,
+                remaining_size: size,
+            });
+
+            let mut completed = false;
+            for f in &placed.fills {
+                completed |= self.apply_fill(f);
+            }
+            if placed.cancel.is_some() {
+                self.stop();
+                return Ok(());
+            }
+            if !completed {
+                return Ok(());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_api_types::{Address, U64};
+
+    fn id(creation_num: u64) -> Id {
+        Id {
+            creation_num: U64(creation_num),
+            addr: Address::from(AccountAddress::ONE),
+        }
+    }
+
+    fn executor(total_size: u64, display_size: u64) -> IcebergExecutor {
+        IcebergExecutor::new(
+            TypeTag::U8,
+            TypeTag::U64,
+            AccountAddress::ONE,
+            Side::Bid,
+            100,
+            total_size,
+            display_size,
+        )
+    }
+
+    fn fill(remaining_size: u64) -> FillEvent {
+        FillEvent {
+            book_id: id(0),
+            order_id: id(1),
+            side: Side::Bid,
+            price: 100,
+            fill_size: 1,
+            fee: 0,
+            fee_rate: 0,
+            time: 0,
+            remaining_size,
+            is_maker: true,
+        }
+    }
+
+    #[test]
+    fn test_fill_counted_once() {
+        let mut iceberg = executor(30, 10);
+        assert!(!iceberg.apply_fill(&fill(5)));
+
+        iceberg.completed = 10;
+        iceberg.clip = Some(Clip {
+            order_id: id(1),
+            size: 10,
+            remaining_size: 10,
+        });
+        assert!(!iceberg.apply_fill(&fill(6)));
+        assert!(!iceberg.apply_fill(&fill(6)));
+        assert_eq!(iceberg.filled_size(), 14);
+        assert_eq!(iceberg.remaining_size(), 16);
+
+        assert!(iceberg.apply_fill(&fill(0)));
+        assert_eq!(iceberg.filled_size(), 20);
+        assert!(!iceberg.apply_fill(&fill(0)));
+    }
+
+    #[test]
+    fn test_display_size_is_at_least_one() {
+        let iceberg = executor(30, 0);
+        assert_eq!(iceberg.display_size, 1);
+        assert_eq!(iceberg.remaining_size(), 30);
+        assert!(!iceberg.is_done());
+    }
+}
@@ -0,0 +1,450 @@
+//! Client for the Aptos Indexer GraphQL API, for historical event queries across accounts.
+
+use crate::error::{LaminarError, Result};
+use crate::http::HttpConfig;
+use crate::network::Network;
+use crate::types::civil_from_days;
+use crate::types::events::{FillEvent, LaminarEvent};
+use crate::types::order::Id;
+use anyhow::{anyhow, Context};
+use aptos_sdk::types::account_address::AccountAddress;
+use reqwest::Url;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// Largest page the indexer returns.
+pub const MAX_PAGE_SIZE: u16 = 100;
+
+const EVENTS_QUERY: &str = "query LaminarEvents($where: events_bool_exp!, $limit: Int!) {
+  events(
+    where: $where
+    order_by: [{transaction_version: asc}, {event_index: asc}]
+    limit: $limit
+  ) {
+    transaction_version
+    event_index
+    account_address
+    creation_number
+    sequence_number
+    type
+    data
+  }
+}";
+
+const VERSION_AT_QUERY: &str = "query VersionAt($timestamp: timestamp!) {
+  block_metadata_transactions(
+    where: {timestamp: {_gte: $timestamp}}
+    order_by: {version: asc}
+    limit: 1
+  ) {
+    version
+  }
+}";
+
+/// Position of an event in the ledger, used to continue a query after the last page.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct IndexerCursor {
+    pub version: u64,
+    pub event_index: u64,
+}
+
+/// Laminar event returned by the indexer.
+#[derive(Clone, Debug, Serialize)]
+pub struct IndexedEvent {
+    pub version: u64,
+    pub event_index: u64,
+    /// Account whose event handle emitted the event.
+    pub account: AccountAddress,
+    pub creation_number: u64,
+    pub sequence_number: u64,
+    pub event: LaminarEvent,
+}
+
+impl IndexedEvent {
+    pub fn cursor(&self) -> IndexerCursor {
+        IndexerCursor {
+            version: self.version,
+            event_index: self.event_index,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct EventPage {
+    pub events: Vec<IndexedEvent>,
+    /// Cursor to fetch the next page with, `None` after the last page.
+    pub next: Option<IndexerCursor>,
+}
+
+/// Filter of an indexer event query. Unset fields match everything.
+#[derive(Clone, Debug)]
+pub struct IndexerQuery {
+    /// Names of the Laminar event structs to return, e.g. `FillEvent`.
+    pub event_types: Vec<String>,
+    /// Account whose event handles emitted the events.
+    pub account: Option<AccountAddress>,
+    pub book_id: Option<Id>,
+    /// Block time of the first event, in microseconds.
+    pub start_time: Option<u64>,
+    /// Block time the events end before, in microseconds.
+    pub end_time: Option<u64>,
+    pub page_size: u16,
+}
+
+impl Default for IndexerQuery {
+    fn default() -> Self {
+        Self {
+            event_types: vec![],
+            account: None,
+            book_id: None,
+            start_time: None,
+            end_time: None,
+            page_size: MAX_PAGE_SIZE,
+        }
+    }
+}
+
+impl IndexerQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_event_type(mut self, event_type: impl Into<String>) -> Self {
+        self.event_types.push(event_type.into());
+        self
+    }
+
+    pub fn with_account(mut self, account: AccountAddress) -> Self {
+        self.account = Some(account);
+        self
+    }
+
+    pub fn with_book(mut self, book_id: Id) -> Self {
+        self.book_id = Some(book_id);
+        self
+    }
+
+    pub fn with_start_time(mut self, time: u64) -> Self {
+        self.start_time = Some(time);
+        self
+    }
+
+    pub fn with_end_time(mut self, time: u64) -> Self {
+        self.end_time = Some(time);
+        self
+    }
+
+    pub fn with_page_size(mut self, page_size: u16) -> Self {
+        self.page_size = page_size.clamp(1, MAX_PAGE_SIZE);
+        self
+    }
+}
+
+#[derive(Deserialize)]
+struct GraphQlResponse<T> {
+    data: Option<T>,
+    #[serde(default)]
+    errors: Vec<GraphQlError>,
+}
+
+#[derive(Deserialize)]
+struct GraphQlError {
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct EventsData {
+    events: Vec<EventRow>,
+}
+
+#[derive(Deserialize)]
+struct EventRow {
+    transaction_version: u64,
+    event_index: u64,
+    account_address: String,
+    creation_number: u64,
+    sequence_number: u64,
+    #[serde(rename = "type")]
+    typ: String,
+    data: Value,
+}
+
+#[derive(Deserialize)]
+struct VersionAtData {
+    block_metadata_transactions: Vec<VersionRow>,
+}
+
+#[derive(Deserialize)]
+struct VersionRow {
+    version: u64,
+}
+
+/// Queries Laminar events of all accounts from an Aptos indexer, which is much faster than
+/// paging the event handles of every account from a fullnode.
+///
+/// Time filters are resolved to ledger versions through the block metadata of the indexer,
+/// so pages are filtered and ordered by the indexer.
+#[derive(Clone, Debug)]
+pub struct IndexerClient {
+    http: reqwest::Client,
+    url: Url,
+    laminar: AccountAddress,
+}
+
+impl IndexerClient {
+    /// # Arguments:
+    ///
+    /// * `url` - Url of the indexer GraphQL endpoint.
+    /// * `laminar` - Address the Laminar modules are deployed at.
+    pub fn new(url: Url, laminar: AccountAddress) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            url,
+            laminar,
+        }
+    }
+
+    /// Same as `new`, with custom http settings, e.g. the API key of an indexer provider.
+    pub fn with_http_config(
+        url: Url,
+        laminar: AccountAddress,
+        http_config: &HttpConfig,
+    ) -> Result<Self> {
+        Ok(Self {
+            http: http_config.build_http_client()?,
+            url,
+            laminar,
+        })
+    }
+
+    /// Client for the public indexer of `network`.
+    pub fn for_network(network: Network, laminar: AccountAddress) -> Result<Self> {
+        let url = network.indexer_url().ok_or_else(|| {
+            LaminarError::InvalidInput(format!("no public indexer on {}", network))
+        })?;
+        Ok(Self::new(url, laminar))
+    }
+
+    /// Fetch a page of events matching `query`.
+    ///
+    /// # Arguments:
+    ///
+    /// * `query` - Filter of the events.
+    /// * `after` - Cursor of the last event of the previous page, `None` for the first page.
+    pub async fn events(
+        &self,
+        query: &IndexerQuery,
+        after: Option<IndexerCursor>,
+    ) -> Result<EventPage> {
+        let mut filters = vec![self.type_filter(&query.event_types)];
+        if let Some(account) = &query.account {
+            // the indexer stores addresses without stripping leading zeros
+            let account = format!("0x{}", account.to_hex());
+            filters.push(json!({ "account_address": { "_eq": account } }));
+        }
+        if let Some(book_id) = &query.book_id {
+            filters.push(json!({ "data": { "_contains": { "book_id": book_id } } }));
+        }
+        if let Some(start_time) = query.start_time {
+            match self.version_at(start_time).await? {
+                Some(version) => {
+                    filters.push(json!({ "transaction_version": { "_gte": version } }))
+                }
+                // nothing committed at or after the start time yet
+                None => {
+                    return Ok(EventPage {
+                        events: vec![],
+                        next: None,
+                    })
+                }
+            }
+        }
+        if let Some(end_time) = query.end_time {
+            if let Some(version) = self.version_at(end_time).await? {
+                filters.push(json!({ "transaction_version": { "_lt": version } }));
+            }
+        }
+        if let Some(cursor) = after {
+            filters.push(json!({ "_or": [
+                { "transaction_version": { "_gt": cursor.version } },
+                {
+                    "transaction_version": { "_eq": cursor.version },
+                    "event_index": { "_gt": cursor.event_index },
+                },
+            ] }));
+        }
+
+        let variables = json!({
+            "where": { "_and": filters },
+            "limit": query.page_size,
+        });
+        let data: EventsData = self.query(EVENTS_QUERY, variables).await?;
+        let full = data.events.len() >= query.page_size as usize;
+        let events = data
+            .events
+            .into_iter()
+            .map(|row| self.indexed_event(row))
+            .collect::<Result<Vec<_>>>()?;
+        let next = match events.last() {
+            Some(last) if full => Some(last.cursor()),
+            _ => None,
+        };
+
+        Ok(EventPage { events, next })
+    }
+
+    /// Fetch all events matching `query`, page by page.
+    pub async fn all_events(&self, query: &IndexerQuery) -> Result<Vec<IndexedEvent>> {
+        let mut res = vec![];
+        let mut after = None;
+        loop {
+            let page = self.events(query, after).await?;
+            res.extend(page.events);
+            match page.next {
+                Some(next) => after = Some(next),
+                None => return Ok(res),
+            }
+        }
+    }
+
+    /// Fetch the fills of all accounts on a book.
+    ///
+    /// # Arguments:
+    ///
+    /// * `book_id` - Id of the book.
+    /// * `start_time` - Block time of the first fill in microseconds, `None` for no bound.
+    /// * `end_time` - Block time the fills end before in microseconds, `None` for no bound.
+    pub async fn fills(
+        &self,
+        book_id: &Id,
+        start_time: Option<u64>,
+        end_time: Option<u64>,
+    ) -> Result<Vec<FillEvent>> {
+        let query = IndexerQuery {
+            event_types: vec!["FillEvent".to_string()],
+            book_id: Some(book_id.clone()),
+            start_time,
+            end_time,
+            ..Default::default()
+        };
+        let fills = self
+            .all_events(&query)
+            .await?
+            .into_iter()
+            .filter_map(|e| match e.event {
+                LaminarEvent::FillEvent(fill) => Some(fill),
+                _ => None,
+            })
+            .collect();
+
+        Ok(fills)
+    }
+
+    /// Fetch all Laminar events of an account.
+    ///
+    /// # Arguments:
+    ///
+    /// * `account` - Address of the account.
+    /// * `start_time` - Block time of the first event in microseconds, `None` for no bound.
+    /// * `end_time` - Block time the events end before in microseconds, `None` for no bound.
+    pub async fn account_activity(
+        &self,
+        account: AccountAddress,
+        start_time: Option<u64>,
+        end_time: Option<u64>,
+    ) -> Result<Vec<IndexedEvent>> {
+        let query = IndexerQuery {
+            account: Some(account),
+            start_time,
+            end_time,
+            ..Default::default()
+        };
+        self.all_events(&query).await
+    }
+
+    /// Version of the first block at or after `time`, in microseconds. `None` if no block was
+    /// indexed after it yet.
+    pub async fn version_at(&self, time: u64) -> Result<Option<u64>> {
+        let variables = json!({ "timestamp": format_timestamp(time) });
+        let data: VersionAtData = self.query(VERSION_AT_QUERY, variables).await?;
+        Ok(data.block_metadata_transactions.first().map(|b| b.version))
+    }
+
+    fn type_filter(&self, event_types: &[String]) -> Value {
+        let module = format!("{}::book::", self.laminar.to_hex_literal());
+        if event_types.is_empty() {
+            json!({ "type": { "_like": format!("{}%", module) } })
+        } else {
+            let types = event_types
+                .iter()
+                .map(|t| format!("{}{}", module, t))
+                .collect::<Vec<_>>();
+            json!({ "type": { "_in": types } })
+        }
+    }
+
+    fn indexed_event(&self, row: EventRow) -> Result<IndexedEvent> {
+        let event = LaminarEvent::from_move_event(&row.typ, row.data)
+            .map_err(|err| LaminarError::deserialization(err, row.typ))?;
+        let account = AccountAddress::from_hex_literal(&row.account_address)
+            .map_err(|e| LaminarError::InvalidInput(format!("indexer account address: {}", e)))?;
+
+        Ok(IndexedEvent {
+            version: row.transaction_version,
+            event_index: row.event_index,
+            account,
+            creation_number: row.creation_number,
+            sequence_number: row.sequence_number,
+            event,
+        })
+    }
+
+    async fn query<T: DeserializeOwned>(&self, query: &str, variables: Value) -> Result<T> {
+        let body = json!({ "query": query, "variables": variables });
+        let res = self
+            .http
+            .post(self.url.clone())
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.to_string())
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .context("failed sending indexer query")?;
+        let bytes = res
+            .bytes()
+            .await
+            .context("failed reading indexer response")?;
+        let res: GraphQlResponse<T> = serde_json::from_slice(&bytes)
+            .map_err(|e| LaminarError::deserialization(e, "indexer response"))?;
+
+        if !res.errors.is_empty() {
+            let messages = res
+                .errors
+                .iter()
+                .map(|e| e.message.as_str())
+                .collect::<Vec<_>>();
+            return Err(anyhow!("indexer query failed: {}", messages.join("; ")).into());
+        }
+        res.data
+            .ok_or_else(|| anyhow!("indexer response without data").into())
+    }
+}
+
+// `timestamp` literal of the indexer, e.g. `2022-11-01T12:00:00.000000`
+fn format_timestamp(micros: u64) -> String {
+    let secs = micros / 1_000_000;
+    let (days, secs_of_day) = ((secs / 86_400) as i64, secs % 86_400);
+    let (year, month, day) = civil_from_days(days);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:06}",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60,
+        secs_of_day % 60,
+        micros % 1_000_000
+    )
+}
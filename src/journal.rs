@@ -0,0 +1,234 @@
+//! Local SQLite journal of received events, as an audit trail for bots without a database
+//! server.
+
+use crate::error::{LaminarError, Result};
+use crate::types::events::LaminarEvent;
+use crate::types::order::Id;
+use crate::LaminarTransaction;
+use anyhow::Context;
+use serde::Serialize;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqliteRow};
+use sqlx::Row;
+use std::path::Path;
+use std::str::FromStr;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS events (
+    seq INTEGER PRIMARY KEY AUTOINCREMENT,
+    type TEXT NOT NULL,
+    book_id TEXT,
+    order_id TEXT,
+    time INTEGER NOT NULL,
+    version INTEGER,
+    tx_hash TEXT,
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS events_order ON events (order_id);
+CREATE INDEX IF NOT EXISTS events_book_time ON events (book_id, time);
+CREATE INDEX IF NOT EXISTS events_time ON events (time);
+";
+
+/// Event read back from an `EventJournal`.
+#[derive(Clone, Debug, Serialize)]
+pub struct JournalEntry {
+    /// Position in the journal, in append order.
+    pub seq: i64,
+    /// Version of the transaction, if the event was appended with it.
+    pub version: Option<u64>,
+    pub tx_hash: Option<String>,
+    pub event: LaminarEvent,
+}
+
+/// Append-only journal of `LaminarEvent`s in a SQLite file.
+///
+/// Events are stored as JSON with their book id, order id and time extracted for the query
+/// helpers. Appending does not deduplicate, the journal records what was received.
+#[derive(Clone, Debug)]
+pub struct EventJournal {
+    pool: SqlitePool,
+}
+
+impl EventJournal {
+    /// Open a journal file, creating it if missing.
+    ///
+    /// # Arguments:
+    ///
+    /// * `path` - Path of the SQLite file.
+    pub async fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let options = SqliteConnectOptions::new()
+            .filename(path)
+            .create_if_missing(true);
+        Self::connect_with(options).await
+    }
+
+    /// Open a journal kept in memory, e.g. for tests.
+    pub async fn in_memory() -> Result<Self> {
+        let options =
+            SqliteConnectOptions::from_str("sqlite::memory:").context("invalid sqlite options")?;
+        Self::connect_with(options).await
+    }
+
+    async fn connect_with(options: SqliteConnectOptions) -> Result<Self> {
+        // one connection, in-memory databases are per connection
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(options)
+            .await
+            .context("failed opening journal")?;
+        sqlx::query(SCHEMA)
+            .execute(&pool)
+            .await
+            .context("failed creating journal tables")?;
+
+        Ok(Self { pool })
+    }
+
+    /// Append an event received without transaction, e.g. from a `MarketDataFeed`.
+    pub async fn append(&self, event: &LaminarEvent) -> Result<()> {
+        self.append_all(std::slice::from_ref(event), None).await
+    }
+
+    /// Append the events of a submitted transaction with its version and hash.
+    pub async fn append_transaction(&self, tx: &LaminarTransaction) -> Result<()> {
+        let hash = tx.hash().to_string();
+        self.append_all(&tx.events, Some((tx.version(), hash.as_str())))
+            .await
+    }
+
+    /// Append events in one transaction.
+    ///
+    /// # Arguments:
+    ///
+    /// * `events` - Events to append, in order.
+    /// * `tx` - Version and hash of the transaction that emitted the events, if known.
+    pub async fn append_all(&self, events: &[LaminarEvent], tx: Option<(u64, &str)>) -> Result<()> {
+        let version = tx
+            .map(|(version, _)| to_i64(version, "version"))
+            .transpose()?;
+        let mut db_tx = self
+            .pool
+            .begin()
+            .await
+            .context("failed starting transaction")?;
+        for event in events {
+            let data = serde_json::to_string(event)
+                .map_err(|e| LaminarError::deserialization(e, "journal event"))?;
+            sqlx::query(
+                "INSERT INTO events (type, book_id, order_id, time, version, tx_hash, data) \
+                 VALUES (?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(event_type(event))
+            .bind(event.book_id().map(|id| id.to_string()))
+            .bind(event.order_id().map(|id| id.to_string()))
+            .bind(to_i64(event.time(), "time")?)
+            .bind(version)
+            .bind(tx.map(|(_, hash)| hash))
+            .bind(data)
+            .execute(&mut db_tx)
+            .await
+            .context("failed appending to journal")?;
+        }
+        db_tx
+            .commit()
+            .await
+            .context("failed committing transaction")?;
+
+        Ok(())
+    }
+
+    /// Events of an order in append order.
+    pub async fn by_order(&self, order_id: &Id) -> Result<Vec<JournalEntry>> {
+        let rows = sqlx::query("SELECT * FROM events WHERE order_id = ? ORDER BY seq")
+            .bind(order_id.to_string())
+            .fetch_all(&self.pool)
+            .await
+            .context("failed querying journal")?;
+        rows.iter().map(entry_from_row).collect()
+    }
+
+    /// Events of a book in time order.
+    ///
+    /// # Arguments:
+    ///
+    /// * `book_id` - `OrderBook` Id.
+    /// * `start_time` - Time of the first event, `None` for no bound.
+    /// * `end_time` - Time the events end before, `None` for no bound.
+    pub async fn by_book(
+        &self,
+        book_id: &Id,
+        start_time: Option<u64>,
+        end_time: Option<u64>,
+    ) -> Result<Vec<JournalEntry>> {
+        let rows = sqlx::query(
+            "SELECT * FROM events WHERE book_id = ? AND time >= ? AND time < ? \
+             ORDER BY time, seq",
+        )
+        .bind(book_id.to_string())
+        .bind(to_i64(start_time.unwrap_or(0), "start_time")?)
+        .bind(end_time.map_or(Ok(i64::MAX), |t| to_i64(t, "end_time"))?)
+        .fetch_all(&self.pool)
+        .await
+        .context("failed querying journal")?;
+        rows.iter().map(entry_from_row).collect()
+    }
+
+    /// Events of all books in time order.
+    ///
+    /// # Arguments:
+    ///
+    /// * `start_time` - Time of the first event, `None` for no bound.
+    /// * `end_time` - Time the events end before, `None` for no bound.
+    pub async fn in_range(
+        &self,
+        start_time: Option<u64>,
+        end_time: Option<u64>,
+    ) -> Result<Vec<JournalEntry>> {
+        let rows =
+            sqlx::query("SELECT * FROM events WHERE time >= ? AND time < ? ORDER BY time, seq")
+                .bind(to_i64(start_time.unwrap_or(0), "start_time")?)
+                .bind(end_time.map_or(Ok(i64::MAX), |t| to_i64(t, "end_time"))?)
+                .fetch_all(&self.pool)
+                .await
+                .context("failed querying journal")?;
+        rows.iter().map(entry_from_row).collect()
+    }
+
+    /// Number of journaled events.
+    pub async fn count(&self) -> Result<u64> {
+        let count: i64 = sqlx::query("SELECT COUNT(*) FROM events")
+            .fetch_one(&self.pool)
+            .await
+            .context("failed querying journal")?
+            .try_get(0)
+            .context("failed reading count")?;
+        Ok(count as u64)
+    }
+}
+
+fn event_type(event: &LaminarEvent) -> &str {
+    match event {
+        LaminarEvent::CreateOrderBook(_) => "CreateOrderBookEvent",
+        LaminarEvent::PlaceOrder(_) => "PlaceOrderEvent",
+        LaminarEvent::AmendOrder(_) => "AmendOrderEvent",
+        LaminarEvent::CancelOrder(_) => "CancelOrderEvent",
+        LaminarEvent::FillEvent(_) => "FillEvent",
+        LaminarEvent::Unknown { type_name, .. } => type_name,
+    }
+}
+
+fn to_i64(value: u64, column: &str) -> Result<i64> {
+    i64::try_from(value)
+        .map_err(|_| LaminarError::InvalidInput(format!("{} out of range: {}", column, value)))
+}
+
+fn entry_from_row(row: &SqliteRow) -> Result<JournalEntry> {
+    let data: String = row.try_get("data").context("failed reading column")?;
+    let version: Option<i64> = row.try_get("version").context("failed reading column")?;
+    Ok(JournalEntry {
+        seq: row.try_get("seq").context("failed reading column")?,
+        version: version.map(|v| v as u64),
+        tx_hash: row.try_get("tx_hash").context("failed reading column")?,
+        event: serde_json::from_str(&data)
+            .map_err(|e| LaminarError::deserialization(e, "journal event"))?,
+    })
+}
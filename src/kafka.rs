@@ -0,0 +1,125 @@
+//! Publishing Laminar events to Kafka.
+
+use crate::encoding::{encode, RecordEncoder};
+use crate::error::{LaminarError, Result};
+use crate::market_data::MarketDataFeed;
+use crate::types::events::{LaminarEvent, LaminarEventEnvelope};
+use crate::LaminarClient;
+use anyhow::{anyhow, Context};
+use futures::future::try_join_all;
+use rdkafka::message::OwnedHeaders;
+use rdkafka::producer::{FutureProducer, FutureRecord};
+use rdkafka::ClientConfig;
+use std::time::Duration;
+
+/// Publishes envelope-wrapped events to a Kafka topic, keyed by book id so the events of a
+/// book land on one partition in order.
+///
+/// `forward` drives the sink from a `MarketDataFeed` and commits the feed's checkpoint only
+/// after every event of a poll was acknowledged, so events are delivered at least once:
+/// events published before a crash and not yet committed are published again on restart.
+pub struct KafkaSink {
+    producer: FutureProducer,
+    topic: String,
+    encoder: Box<dyn RecordEncoder>,
+    delivery_timeout: Duration,
+}
+
+impl KafkaSink {
+    /// Create a sink with an idempotent producer that waits for all in-sync replicas.
+    ///
+    /// # Arguments:
+    ///
+    /// * `brokers` - Comma separated `host:port` list of bootstrap brokers.
+    /// * `topic` - Topic to publish to.
+    /// * `encoder` - Encoding of the envelopes, e.g. `JsonEncoder` or `AvroEncoder`.
+    pub fn new(brokers: &str, topic: &str, encoder: Box<dyn RecordEncoder>) -> Result<Self> {
+        let mut config = ClientConfig::new();
+        config
+            .set("bootstrap.servers", brokers)
+            .set("enable.idempotence", "true")
+            .set("acks", "all");
+        Self::from_config(&config, topic, encoder)
+    }
+
+    /// Create a sink from a producer configuration, e.g. with authentication settings.
+    pub fn from_config(
+        config: &ClientConfig,
+        topic: &str,
+        encoder: Box<dyn RecordEncoder>,
+    ) -> Result<Self> {
+        let producer = config.create().context("failed creating kafka producer")?;
+        Ok(Self {
+            producer,
+            topic: topic.to_string(),
+            encoder,
+            delivery_timeout: Duration::from_secs(30),
+        })
+    }
+
+    /// How long a message may wait in the producer queue before publishing fails.
+    pub fn with_delivery_timeout(mut self, timeout: Duration) -> Self {
+        self.delivery_timeout = timeout;
+        self
+    }
+
+    pub fn topic(&self) -> &str {
+        &self.topic
+    }
+
+    /// Publish an envelope and wait for it to be acknowledged.
+    pub async fn publish(&self, envelope: &LaminarEventEnvelope<LaminarEvent>) -> Result<()> {
+        let payload = encode(self.encoder.as_ref(), envelope)?;
+        let key = envelope
+            .event
+            .book_id()
+            .map(|id| id.to_string())
+            .unwrap_or_default();
+        let headers = OwnedHeaders::new().add("content-type", self.encoder.content_type());
+        let record = FutureRecord::to(&self.topic)
+            .key(&key)
+            .payload(&payload)
+            .headers(headers);
+
+        self.producer
+            .send(record, self.delivery_timeout)
+            .await
+            .map_err(|(e, _)| {
+                LaminarError::Other(anyhow!("failed publishing to {}: {}", self.topic, e))
+            })?;
+        Ok(())
+    }
+
+    /// Publish envelopes and wait until all are acknowledged. Envelopes of one book keep
+    /// their order, as the producer is idempotent.
+    pub async fn publish_all(
+        &self,
+        envelopes: &[LaminarEventEnvelope<LaminarEvent>],
+    ) -> Result<()> {
+        try_join_all(envelopes.iter().map(|e| self.publish(e))).await?;
+        Ok(())
+    }
+
+    /// Poll `feed` once, publish the received events and commit the feed's checkpoint.
+    /// Returns the number of published events.
+    ///
+    /// # Arguments:
+    ///
+    /// * `client` - Client the feed reads events with.
+    /// * `feed` - Feed of the book to publish, with a checkpoint to resume from.
+    pub async fn forward(
+        &self,
+        client: &LaminarClient,
+        feed: &mut MarketDataFeed,
+    ) -> Result<usize> {
+        let envelopes = feed.poll_envelopes(client).await?;
+        if envelopes.is_empty() {
+            return Ok(0);
+        }
+        self.publish_all(&envelopes).await?;
+        feed.commit()?;
+        trace_event!(debug, topic = %self.topic, events = envelopes.len(), "published events");
+
+        Ok(envelopes.len())
+    }
+}
@@ -0,0 +1,207 @@
+use crate::error::{LaminarError, Result};
+use crate::LaminarClient;
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Nonce};
+use anyhow::{anyhow, Context};
+use aptos_sdk::crypto::ed25519::Ed25519PrivateKey;
+use aptos_sdk::types::account_address::AccountAddress;
+use aptos_sdk::types::{AccountKey, LocalAccount};
+use rand::RngCore;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+pub const KEYSTORE_VERSION: u32 = 1;
+const CIPHER: &str = "aes-256-gcm";
+const KDF: &str = "scrypt";
+const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+const SALT_LEN: usize = 32;
+
+/// Scrypt cost parameters.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScryptParams {
+    pub log_n: u8,
+    pub r: u32,
+    pub p: u32,
+}
+
+impl Default for ScryptParams {
+    /// Same cost as the web3 keystore defaults, n = 2^18.
+    fn default() -> Self {
+        Self {
+            log_n: 18,
+            r: 8,
+            p: 1,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KdfParams {
+    #[serde(flatten)]
+    pub scrypt: ScryptParams,
+    /// Hex encoded salt.
+    pub salt: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeystoreCrypto {
+    pub cipher: String,
+    /// Hex encoded encrypted private key, including the GCM tag.
+    pub ciphertext: String,
+    /// Hex encoded GCM nonce.
+    pub nonce: String,
+    pub kdf: String,
+    pub kdfparams: KdfParams,
+}
+
+/// Ed25519 private key encrypted with a passphrase, in a JSON file modelled on web3 keystores.
+/// The encryption key is derived from the passphrase with scrypt and the private key is
+/// encrypted with AES-256-GCM, so a wrong passphrase or a tampered file fails to decrypt.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Keystore {
+    pub version: u32,
+    pub address: AccountAddress,
+    pub crypto: KeystoreCrypto,
+}
+
+impl Keystore {
+    /// Encrypt a private key with the default scrypt cost.
+    ///
+    /// # Arguments:
+    ///
+    /// * `address` - Address of the account the key belongs to.
+    /// * `private_key` - Key to encrypt.
+    /// * `passphrase` - Passphrase to derive the encryption key from.
+    pub fn encrypt(
+        address: AccountAddress,
+        private_key: &Ed25519PrivateKey,
+        passphrase: &str,
+    ) -> Result<Self> {
+        Self::encrypt_with_params(address, private_key, passphrase, ScryptParams::default())
+    }
+
+    /// Same as `encrypt`, with the given scrypt cost.
+    pub fn encrypt_with_params(
+        address: AccountAddress,
+        private_key: &Ed25519PrivateKey,
+        passphrase: &str,
+        params: ScryptParams,
+    ) -> Result<Self> {
+        let mut salt = [0u8; SALT_LEN];
+        let mut nonce = [0u8; NONCE_LEN];
+        rand::rngs::OsRng.fill_bytes(&mut salt);
+        rand::rngs::OsRng.fill_bytes(&mut nonce);
+
+        let key = derive_key(passphrase, &salt, &params)?;
+        let cipher = Aes256Gcm::new_from_slice(&key).context("invalid key length")?;
+        let ciphertext = cipher
+            .encrypt(Nonce::from_slice(&nonce), private_key.to_bytes().as_ref())
+            .map_err(|_| anyhow!("failed encrypting private key"))?;
+
+        Ok(Self {
+            version: KEYSTORE_VERSION,
+            address,
+            crypto: KeystoreCrypto {
+                cipher: CIPHER.to_string(),
+                ciphertext: hex::encode(ciphertext),
+                nonce: hex::encode(nonce),
+                kdf: KDF.to_string(),
+                kdfparams: KdfParams {
+                    scrypt: params,
+                    salt: hex::encode(salt),
+                },
+            },
+        })
+    }
+
+    /// Decrypt the private key.
+    pub fn decrypt(&self, passphrase: &str) -> Result<Ed25519PrivateKey> {
+        if self.version != KEYSTORE_VERSION {
+            return Err(LaminarError::InvalidInput(format!(
+                "unsupported keystore version: {}",
+                self.version
+            )));
+        }
+        let crypto = &self.crypto;
+        if crypto.cipher != CIPHER || crypto.kdf != KDF {
+            return Err(LaminarError::InvalidInput(format!(
+                "unsupported keystore cipher {} or kdf {}",
+                crypto.cipher, crypto.kdf
+            )));
+        }
+
+        let salt = hex::decode(&crypto.kdfparams.salt).context("invalid keystore salt")?;
+        let nonce = hex::decode(&crypto.nonce).context("invalid keystore nonce")?;
+        let ciphertext = hex::decode(&crypto.ciphertext).context("invalid keystore ciphertext")?;
+        if nonce.len() != NONCE_LEN {
+            return Err(LaminarError::InvalidInput(
+                "invalid keystore nonce length".to_string(),
+            ));
+        }
+
+        let key = derive_key(passphrase, &salt, &crypto.kdfparams.scrypt)?;
+        let cipher = Aes256Gcm::new_from_slice(&key).context("invalid key length")?;
+        let plaintext = cipher
+            .decrypt(Nonce::from_slice(&nonce), ciphertext.as_ref())
+            .map_err(|_| {
+                LaminarError::InvalidInput("wrong passphrase or corrupted keystore".to_string())
+            })?;
+
+        Ed25519PrivateKey::try_from(plaintext.as_slice())
+            .map_err(|e| LaminarError::InvalidInput(format!("invalid private key: {}", e)))
+    }
+
+    /// Decrypt the private key into a `LocalAccount`, with sequence number 0.
+    pub fn decrypt_account(&self, passphrase: &str) -> Result<LocalAccount> {
+        let private_key = self.decrypt(passphrase)?;
+        Ok(LocalAccount::new(
+            self.address,
+            AccountKey::from(private_key),
+            0,
+        ))
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let data = fs::read(path).context("failed reading keystore")?;
+        serde_json::from_slice(&data).map_err(|e| LaminarError::deserialization(e, "keystore"))
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let data = serde_json::to_vec_pretty(self).context("failed serializing keystore")?;
+        fs::write(path, data).context("failed writing keystore")?;
+        Ok(())
+    }
+}
+
+fn derive_key(passphrase: &str, salt: &[u8], params: &ScryptParams) -> Result<[u8; KEY_LEN]> {
+    let params = scrypt::Params::new(params.log_n, params.r, params.p)
+        .map_err(|e| LaminarError::InvalidInput(format!("invalid scrypt params: {}", e)))?;
+    let mut key = [0u8; KEY_LEN];
+    scrypt::scrypt(passphrase.as_bytes(), salt, &params, &mut key)
+        .map_err(|e| anyhow!("failed deriving keystore key: {}", e))?;
+    Ok(key)
+}
+
+impl LaminarClient {
+    /// Connect to an Aptos node and initialize the Laminar Markets client using an encrypted
+    /// `Keystore` file.
+    ///
+    /// # Arguments:
+    ///
+    /// * `node_url` - Url of aptos node.
+    /// * `laminar` - Address of account that holds the laminar modules.
+    /// * `keystore_path` - Path to the keystore file.
+    /// * `passphrase` - Passphrase the keystore was encrypted with.
+    pub async fn connect_with_keystore<P: AsRef<Path>>(
+        node_url: Url,
+        laminar: AccountAddress,
+        keystore_path: P,
+        passphrase: &str,
+    ) -> Result<Self> {
+        let account = Keystore::load(keystore_path)?.decrypt_account(passphrase)?;
+        Self::connect(node_url, laminar, account).await
+    }
+}
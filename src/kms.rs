@@ -0,0 +1,121 @@
+use crate::error::{LaminarError, Result};
+use crate::signer::{ed25519_simulation_transaction, TransactionSigner};
+use anyhow::{anyhow, Context};
+use aptos_sdk::crypto::ed25519::{Ed25519PublicKey, Ed25519Signature};
+use aptos_sdk::crypto::traits::signing_message;
+use aptos_sdk::crypto::Signature;
+use aptos_sdk::types::account_address::AccountAddress;
+use aptos_sdk::types::transaction::authenticator::AuthenticationKey;
+use aptos_sdk::types::transaction::{RawTransaction, SignedTransaction};
+use async_trait::async_trait;
+use aws_sdk_kms::primitives::Blob;
+use aws_sdk_kms::types::{MessageType, SigningAlgorithmSpec};
+use aws_sdk_kms::Client;
+
+// DER SubjectPublicKeyInfo header of an Ed25519 key, followed by the 32 key bytes
+const ED25519_SPKI_PREFIX: [u8; 12] = [
+    0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x03, 0x21, 0x00,
+];
+
+/// Ed25519 signer backed by an AWS KMS key with the `ECC_NIST_EDWARDS25519` key spec.
+/// The private key never leaves KMS, transactions are signed with the `Sign` API.
+#[derive(Clone, Debug)]
+pub struct KmsSigner {
+    client: Client,
+    key_id: String,
+    address: AccountAddress,
+    public_key: Ed25519PublicKey,
+}
+
+impl KmsSigner {
+    /// Fetch the public key of a KMS key and build a signer for it.
+    ///
+    /// # Arguments:
+    ///
+    /// * `client` - AWS KMS client.
+    /// * `key_id` - Id, ARN or alias of the KMS key.
+    /// * `address` - Address of the account, if its authentication key was rotated to the
+    /// KMS key. Derived from the public key if `None`.
+    pub async fn new(
+        client: Client,
+        key_id: impl Into<String>,
+        address: Option<AccountAddress>,
+    ) -> Result<Self> {
+        let key_id = key_id.into();
+        let res = client
+            .get_public_key()
+            .key_id(&key_id)
+            .send()
+            .await
+            .context("failed getting kms public key")?;
+        let der = res
+            .public_key()
+            .ok_or_else(|| LaminarError::NotFound(format!("public key of kms key {}", key_id)))?
+            .as_ref();
+        let public_key = parse_spki(der)?;
+        let address =
+            address.unwrap_or_else(|| AuthenticationKey::ed25519(&public_key).derived_address());
+
+        Ok(Self {
+            client,
+            key_id,
+            address,
+            public_key,
+        })
+    }
+
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    pub fn public_key(&self) -> &Ed25519PublicKey {
+        &self.public_key
+    }
+}
+
+fn parse_spki(der: &[u8]) -> Result<Ed25519PublicKey> {
+    let key = der
+        .strip_prefix(&ED25519_SPKI_PREFIX[..])
+        .ok_or_else(|| LaminarError::InvalidInput("kms key is not an ed25519 key".to_string()))?;
+    Ed25519PublicKey::try_from(key)
+        .map_err(|e| LaminarError::InvalidInput(format!("invalid kms public key: {}", e)))
+}
+
+#[async_trait]
+impl TransactionSigner for KmsSigner {
+    fn address(&self) -> AccountAddress {
+        self.address
+    }
+
+    async fn sign_transaction(&self, tx: RawTransaction) -> Result<SignedTransaction> {
+        let message = signing_message(&tx);
+        let res = self
+            .client
+            .sign()
+            .key_id(&self.key_id)
+            .message(Blob::new(message.clone()))
+            .message_type(MessageType::Raw)
+            .signing_algorithm(SigningAlgorithmSpec::Ed25519Sha512)
+            .send()
+            .await
+            .context("kms sign request failed")?;
+        let signature = res
+            .signature()
+            .ok_or_else(|| anyhow!("kms returned no signature"))?;
+        let signature = Ed25519Signature::try_from(signature.as_ref())
+            .map_err(|e| anyhow!("invalid kms signature: {}", e))?;
+        signature
+            .verify_arbitrary_msg(&message, &self.public_key)
+            .map_err(|e| anyhow!("kms signature does not verify: {}", e))?;
+
+        Ok(SignedTransaction::new(
+            tx,
+            self.public_key.clone(),
+            signature,
+        ))
+    }
+
+    fn simulation_transaction(&self, tx: RawTransaction) -> Result<SignedTransaction> {
+        ed25519_simulation_transaction(tx, &self.public_key)
+    }
+}
@@ -0,0 +1,217 @@
+use crate::error::{LaminarError, Result};
+use crate::signer::{ed25519_simulation_transaction, TransactionSigner};
+use anyhow::{anyhow, Context};
+use aptos_sdk::crypto::ed25519::{Ed25519PublicKey, Ed25519Signature};
+use aptos_sdk::crypto::traits::signing_message;
+use aptos_sdk::crypto::Signature;
+use aptos_sdk::types::account_address::AccountAddress;
+use aptos_sdk::types::transaction::authenticator::AuthenticationKey;
+use aptos_sdk::types::transaction::{RawTransaction, SignedTransaction};
+use async_trait::async_trait;
+use ledger_apdu::APDUCommand;
+use ledger_transport_hid::hidapi::HidApi;
+use ledger_transport_hid::TransportNativeHID;
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+
+// APDU protocol of the Aptos Ledger app
+const CLA: u8 = 0x5b;
+const INS_GET_PUBLIC_KEY: u8 = 0x05;
+const INS_SIGN_TX: u8 = 0x06;
+const P1_NON_CONFIRM: u8 = 0x00;
+const P1_CONFIRM: u8 = 0x01;
+const P1_START: u8 = 0x00;
+const P2_MORE: u8 = 0x80;
+const P2_LAST: u8 = 0x00;
+const MAX_CHUNK_SIZE: usize = 255;
+const SW_OK: u16 = 0x9000;
+const HARDENED: u32 = 0x8000_0000;
+
+/// Signer using a Ledger device running the Aptos app, for accounts that should not have hot
+/// keys, e.g. book owners and treasury accounts.
+///
+/// Every transaction has to be approved on the device, which displays the sender, the entry
+/// function and its arguments. Order parameters are shown as the raw arguments of the
+/// entry function, e.g. price and size in units. Signing blocks until the transaction is
+/// approved or rejected on the device.
+#[derive(Clone)]
+pub struct LedgerSigner {
+    transport: Arc<TransportNativeHID>,
+    path: Vec<u32>,
+    address: AccountAddress,
+    public_key: Ed25519PublicKey,
+}
+
+impl LedgerSigner {
+    /// Connect to the first Ledger device and read the public key of the account at `index`,
+    /// derived at `m/44'/637'/{index}'/0'/0'`.
+    ///
+    /// # Arguments:
+    ///
+    /// * `index` - Index of the account on the device, 0 for the first account.
+    /// * `address` - Address of the account, if its authentication key was rotated to the
+    /// device key. Derived from the public key if `None`.
+    pub async fn connect(index: u32, address: Option<AccountAddress>) -> Result<Self> {
+        let path = vec![44, 637, index, 0, 0]
+            .into_iter()
+            .map(|i| i | HARDENED)
+            .collect::<Vec<_>>();
+        let transport = tokio::task::spawn_blocking(|| {
+            let api = HidApi::new().context("failed opening hid api")?;
+            TransportNativeHID::new(&api).context("failed connecting to ledger device")
+        })
+        .await
+        .context("ledger task failed")??;
+        let transport = Arc::new(transport);
+
+        let response = exchange(
+            transport.clone(),
+            INS_GET_PUBLIC_KEY,
+            P1_NON_CONFIRM,
+            P2_LAST,
+            serialize_path(&path),
+        )
+        .await?;
+        let public_key = parse_public_key(&response)?;
+        let address =
+            address.unwrap_or_else(|| AuthenticationKey::ed25519(&public_key).derived_address());
+
+        Ok(Self {
+            transport,
+            path,
+            address,
+            public_key,
+        })
+    }
+
+    pub fn public_key(&self) -> &Ed25519PublicKey {
+        &self.public_key
+    }
+
+    /// Show the address on the device so the user can check it, e.g. before funding it.
+    pub async fn display_address(&self) -> Result<()> {
+        exchange(
+            self.transport.clone(),
+            INS_GET_PUBLIC_KEY,
+            P1_CONFIRM,
+            P2_LAST,
+            serialize_path(&self.path),
+        )
+        .await
+        .map(|_| ())
+    }
+}
+
+impl Debug for LedgerSigner {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LedgerSigner")
+            .field("path", &self.path)
+            .field("address", &self.address)
+            .field("public_key", &self.public_key)
+            .finish_non_exhaustive()
+    }
+}
+
+fn serialize_path(path: &[u32]) -> Vec<u8> {
+    let mut data = vec![path.len() as u8];
+    for i in path {
+        data.extend_from_slice(&i.to_be_bytes());
+    }
+    data
+}
+
+// length prefixed public key, followed by the chain code
+fn parse_public_key(response: &[u8]) -> Result<Ed25519PublicKey> {
+    let len = *response
+        .first()
+        .ok_or_else(|| anyhow!("empty ledger response"))? as usize;
+    let key = response
+        .get(1..1 + len)
+        .ok_or_else(|| anyhow!("truncated ledger public key"))?;
+    Ed25519PublicKey::try_from(key)
+        .map_err(|e| LaminarError::InvalidInput(format!("invalid ledger public key: {}", e)))
+}
+
+async fn exchange(
+    transport: Arc<TransportNativeHID>,
+    ins: u8,
+    p1: u8,
+    p2: u8,
+    data: Vec<u8>,
+) -> Result<Vec<u8>> {
+    let command = APDUCommand {
+        cla: CLA,
+        ins,
+        p1,
+        p2,
+        data,
+    };
+    let answer = tokio::task::spawn_blocking(move || transport.exchange(&command))
+        .await
+        .context("ledger task failed")?
+        .context("ledger exchange failed")?;
+    if answer.retcode() != SW_OK {
+        return Err(anyhow!("ledger returned status {:#06x}", answer.retcode()).into());
+    }
+
+    Ok(answer.data().to_vec())
+}
+
+#[async_trait]
+impl TransactionSigner for LedgerSigner {
+    fn address(&self) -> AccountAddress {
+        self.address
+    }
+
+    async fn sign_transaction(&self, tx: RawTransaction) -> Result<SignedTransaction> {
+        let message = signing_message(&tx);
+        // the derivation path goes first, then the message in chunks
+        let chunks = message.chunks(MAX_CHUNK_SIZE).collect::<Vec<_>>();
+        exchange(
+            self.transport.clone(),
+            INS_SIGN_TX,
+            P1_START,
+            P2_MORE,
+            serialize_path(&self.path),
+        )
+        .await?;
+        let mut response = vec![];
+        for (i, chunk) in chunks.iter().enumerate() {
+            let p2 = if i + 1 == chunks.len() {
+                P2_LAST
+            } else {
+                P2_MORE
+            };
+            response = exchange(
+                self.transport.clone(),
+                INS_SIGN_TX,
+                (i + 1) as u8,
+                p2,
+                chunk.to_vec(),
+            )
+            .await?;
+        }
+
+        let len = *response
+            .first()
+            .ok_or_else(|| anyhow!("empty ledger signature"))? as usize;
+        let signature = response
+            .get(1..1 + len)
+            .ok_or_else(|| anyhow!("truncated ledger signature"))?;
+        let signature = Ed25519Signature::try_from(signature)
+            .map_err(|e| anyhow!("invalid ledger signature: {}", e))?;
+        signature
+            .verify_arbitrary_msg(&message, &self.public_key)
+            .map_err(|e| anyhow!("ledger signature does not verify: {}", e))?;
+
+        Ok(SignedTransaction::new(
+            tx,
+            self.public_key.clone(),
+            signature,
+        ))
+    }
+
+    fn simulation_transaction(&self, tx: RawTransaction) -> Result<SignedTransaction> {
+        ed25519_simulation_transaction(tx, &self.public_key)
+    }
+}
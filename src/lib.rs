@@ -1,16 +1,134 @@
+#[macro_use]
+mod trace;
+mod sequence;
+mod time;
+
+pub mod alerts;
+#[cfg(feature = "analytics")]
+pub mod analytics;
+pub mod audit;
+pub mod backfill;
+pub mod backtest;
+pub mod batch;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod cache;
+pub mod candles;
+pub mod checkpoint;
+pub mod config;
+#[cfg(feature = "polars")]
+pub mod dataframe;
+#[cfg(feature = "db")]
+pub mod db;
+pub mod encoding;
+pub mod error;
+pub mod event_buffer;
+pub mod exchange;
+pub mod faucet;
+pub mod fees;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod fix;
+pub mod fixtures;
+#[cfg(feature = "gateway")]
+pub mod gateway;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod http;
+pub mod iceberg;
+#[cfg(feature = "indexer")]
+pub mod indexer;
+#[cfg(feature = "journal")]
+pub mod journal;
+#[cfg(feature = "kafka")]
+pub mod kafka;
+#[cfg(feature = "keystore")]
+pub mod keystore;
+#[cfg(feature = "kms")]
+pub mod kms;
+#[cfg(feature = "ledger")]
+pub mod ledger;
+pub mod market_data;
+pub mod mirror;
+pub mod mnemonic;
+pub mod monitor;
+pub mod multisig;
+#[cfg(feature = "nats")]
+pub mod nats;
+pub mod network;
+pub mod notional;
+pub mod oco;
+pub mod offline;
+pub mod oms;
+pub mod onboarding;
+pub mod paper;
+pub mod payload;
+pub mod position;
+pub mod query;
+pub mod queue;
+pub mod quoting;
+pub mod rate_limit;
+#[cfg(feature = "redis")]
+pub mod redis;
+pub mod registry;
+pub mod risk;
+#[cfg(feature = "server")]
+pub mod server;
+pub mod signer;
+pub mod sim;
+pub mod strategy;
+pub mod submission;
+pub mod table;
+pub mod tape;
+#[cfg(feature = "otel")]
+pub mod telemetry;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod ticker;
+pub mod tracker;
+pub mod trading;
+pub mod trigger;
+#[cfg(feature = "tui")]
+pub mod tui;
 pub mod types;
-
+pub mod udf;
+pub mod warmup;
+pub mod watchdog;
+#[cfg(feature = "webhook")]
+pub mod webhook;
+
+use crate::audit::{AuditLog, AuditRecord};
+use crate::batch::LaminarAction;
+use crate::cache::{CacheConfig, ReadCache};
+use crate::checkpoint::EventCursor;
+use crate::config::AptosConfig;
+use crate::error::{LaminarError, Result};
+use crate::http::HttpConfig;
+use crate::monitor::{StaleQuote, StaleQuoteMonitor};
+use crate::network::Network;
+use crate::payload::PayloadBuilder;
+use crate::query::{EventIds, EventQuery};
+use crate::queue::{QueuedAction, SubmissionQueue};
+use crate::rate_limit::{Endpoint, RateLimiter};
+use crate::risk::RiskManager;
+use crate::sequence::SequenceNumbers;
+use crate::signer::TransactionSigner;
+use crate::submission::{Submission, SubmissionStatus};
+use crate::time::{sleep, timeout, Instant};
+use crate::types::coin::CoinInfo;
 use crate::types::events::{
-    AmendOrderEvent, CancelOrderEvent, CreateOrderBookEvent, EventStoreField, FillEvent,
-    LaminarEvent, PlaceOrderEvent,
+    AmendOrderEvent, CancelOrderEvent, CreateOrderBookEvent, EventKey, EventStoreField, FillEvent,
+    LaminarEvent, LaminarEventEnvelope, PlaceOrderEvent,
+};
+use crate::types::market::Market;
+use crate::types::order::{
+    Id, Instrument, OpenOrder, Order, OrderBook, Side, State, TickPolicy, TimeInForce,
 };
-use crate::types::order::{Id, Order, OrderBook, Side, State, TimeInForce};
-use anyhow::{anyhow, Context, Result};
+use anyhow::anyhow;
 use aptos_api_types::{
-    AptosErrorCode, MoveModuleId, MoveType, Transaction, TransactionInfo, UserTransactionRequest,
-    U64,
+    AptosErrorCode, Event, HashValue, MoveModuleId, MoveType, PendingTransaction, Transaction,
+    TransactionInfo, TransactionPayload, UserTransactionRequest, U64,
 };
-use aptos_sdk::bcs;
 use aptos_sdk::crypto::ed25519::Ed25519PrivateKey;
 use aptos_sdk::crypto::ValidCryptoMaterialStringExt;
 use aptos_sdk::move_types::ident_str;
@@ -18,58 +136,221 @@ use aptos_sdk::move_types::language_storage::{ModuleId, TypeTag};
 use aptos_sdk::rest_client::aptos::Balance;
 use aptos_sdk::rest_client::error::RestError;
 use aptos_sdk::rest_client::{Client, Resource};
-use aptos_sdk::transaction_builder::TransactionFactory;
 use aptos_sdk::types::account_address::AccountAddress;
-use aptos_sdk::types::chain_id::ChainId;
-use aptos_sdk::types::transaction::EntryFunction;
+use aptos_sdk::types::transaction::{self, EntryFunction, RawTransaction, SignedTransaction};
 use aptos_sdk::types::{AccountKey, LocalAccount};
+use futures::future::{join_all, try_join_all};
 use futures::try_join;
 use reqwest::Url;
 use serde::de::DeserializeOwned;
-use serde::Deserialize;
-use std::cmp::max;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt::Debug;
-use std::fs::File;
 use std::str::FromStr;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
 
 pub const SUBMIT_ATTEMPTS: u8 = 10;
+const TX_POLL_INTERVAL: Duration = Duration::from_millis(500);
+const TX_WAIT_TIMEOUT: Duration = Duration::from_secs(60);
+/// Number of events requested per page when walking an event handle.
+pub const EVENT_PAGE_SIZE: u16 = 100;
 
-#[derive(Deserialize, Debug, Clone)]
-struct AptosConfig {
-    private_key: String,
-    account: String,
+#[derive(Clone, Debug, Serialize)]
+pub struct LaminarTransaction {
+    pub info: TransactionInfo,
+    pub request: UserTransactionRequest,
+    pub events: Vec<LaminarEvent>,
+    pub timestamp: U64,
 }
 
-type AptosConfigYaml = HashMap<String, HashMap<String, AptosConfig>>;
-
-impl AptosConfig {
-    pub fn from_config(path: &str, profile_name: &str) -> Self {
-        let file = File::open(path).expect("invalid config path provided");
-        let config =
-            serde_yaml::from_reader::<File, AptosConfigYaml>(file).expect("config file is invalid");
-        let profiles = config
-            .get("profiles")
-            .expect("profiles section missing in config file");
-        profiles
-            .get(profile_name)
-            .expect("given profile name is missing in config file")
-            .clone()
+impl LaminarTransaction {
+    pub fn hash(&self) -> HashValue {
+        self.info.hash
+    }
+
+    pub fn version(&self) -> u64 {
+        self.info.version.0
+    }
+
+    pub fn success(&self) -> bool {
+        self.info.success
+    }
+
+    pub fn gas_used(&self) -> u64 {
+        self.info.gas_used.0
+    }
+
+    pub fn fills(&self) -> impl Iterator<Item = &FillEvent> {
+        self.events.iter().filter_map(|e| match e {
+            LaminarEvent::FillEvent(f) => Some(f),
+            _ => None,
+        })
+    }
+
+    pub fn place_events(&self) -> impl Iterator<Item = &PlaceOrderEvent> {
+        self.events.iter().filter_map(|e| match e {
+            LaminarEvent::PlaceOrder(p) => Some(p),
+            _ => None,
+        })
+    }
+
+    pub fn amend_events(&self) -> impl Iterator<Item = &AmendOrderEvent> {
+        self.events.iter().filter_map(|e| match e {
+            LaminarEvent::AmendOrder(a) => Some(a),
+            _ => None,
+        })
+    }
+
+    pub fn cancel_events(&self) -> impl Iterator<Item = &CancelOrderEvent> {
+        self.events.iter().filter_map(|e| match e {
+            LaminarEvent::CancelOrder(c) => Some(c),
+            _ => None,
+        })
+    }
+
+    /// Id of the order the transaction placed, amended or cancelled.
+    pub fn order_id(&self) -> Option<&Id> {
+        self.place_events()
+            .map(|p| &p.order_id)
+            .chain(self.amend_events().map(|a| &a.order_id))
+            .chain(self.cancel_events().map(|c| &c.order_id))
+            .next()
+    }
+
+    /// Base and quote `TypeTag`s of the entry function call, if it was a call on an `OrderBook`.
+    pub fn type_args(&self) -> Option<[TypeTag; 2]> {
+        let TransactionPayload::EntryFunctionPayload(p) = &self.request.payload else {
+            return None;
+        };
+        match p.type_arguments.as_slice() {
+            [base, quote] => Some([
+                TypeTag::try_from(base).ok()?,
+                TypeTag::try_from(quote).ok()?,
+            ]),
+            _ => None,
+        }
     }
 }
 
-pub struct LaminarTransaction {
-    pub info: TransactionInfo,
-    pub request: UserTransactionRequest,
+/// Outcome of placing an order: the placement itself, the fills it took as a taker and
+/// the cancellation of any unfilled remainder (IOC, FOK and market orders).
+#[derive(Clone, Debug, Serialize)]
+pub struct PlacedOrder {
+    pub place: PlaceOrderEvent,
+    pub fills: Vec<FillEvent>,
+    pub cancel: Option<CancelOrderEvent>,
+}
+
+impl PlacedOrder {
+    fn from_transaction(tx: &LaminarTransaction) -> Option<Self> {
+        let place = tx.place_events().next()?.clone();
+        let fills = tx
+            .fills()
+            .filter(|f| f.order_id == place.order_id)
+            .cloned()
+            .collect();
+        let cancel = tx
+            .cancel_events()
+            .find(|c| c.order_id == place.order_id)
+            .cloned();
+
+        Some(Self {
+            place,
+            fills,
+            cancel,
+        })
+    }
+
+    pub fn order_id(&self) -> &Id {
+        &self.place.order_id
+    }
+
+    pub fn filled_size(&self) -> u64 {
+        self.fills.iter().map(|f| f.fill_size).sum()
+    }
+
+    /// Whether the order is still resting on the book after the transaction.
+    pub fn is_resting(&self) -> bool {
+        self.cancel.is_none() && self.filled_size() < self.place.size
+    }
+}
+
+/// Result of simulating a transaction with `LaminarClient::simulate_tx`.
+#[derive(Clone, Debug, Serialize)]
+pub struct Simulation {
+    pub hash: HashValue,
+    pub success: bool,
+    pub vm_status: String,
+    pub gas_used: u64,
+    pub gas_unit_price: u64,
     pub events: Vec<LaminarEvent>,
-    pub timestamp: U64,
 }
 
+impl Simulation {
+    /// Estimated fee in octas.
+    pub fn estimated_fee(&self) -> u64 {
+        self.gas_used.saturating_mul(self.gas_unit_price)
+    }
+
+    /// Error the transaction would fail with if it was submitted, e.g. a `MoveAbort`
+    /// carrying the book's tick size or balance abort code.
+    pub fn error(&self) -> Option<LaminarError> {
+        (!self.success).then(|| LaminarError::from_vm_status(self.hash, &self.vm_status))
+    }
+}
+
+/// Gas, expiration and retry settings used when submitting transactions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TxOptions {
+    pub max_gas_amount: u64,
+    /// Price per gas unit in octas.
+    pub gas_unit_price: u64,
+    /// Seconds from build time after which the tx expires.
+    pub expiration_secs: u64,
+    /// Number of times a retryable failure is retried before giving up.
+    pub max_retries: u8,
+}
+
+impl Default for TxOptions {
+    fn default() -> Self {
+        Self {
+            max_gas_amount: 1_000_000,
+            gas_unit_price: 100,
+            expiration_secs: 30,
+            max_retries: SUBMIT_ATTEMPTS - 1,
+        }
+    }
+}
+
+/// Parameters of an `OrderBook` to be created with `LaminarClient::create_markets`.
+#[derive(Clone, Debug)]
+pub struct MarketSpec {
+    pub base: TypeTag,
+    pub quote: TypeTag,
+    pub price_decimals: u8,
+    pub size_decimals: u8,
+    pub min_size_amount: u64,
+}
+
+/// Client for Laminar Markets.
+/// Submission methods take `&self`, so a client can be shared between tasks behind an `Arc`.
 pub struct LaminarClient {
     laminar: AccountAddress,
     aptos_client: Client,
-    chain_id: ChainId,
-    account: LocalAccount,
+    chain_id: AtomicU8,
+    signer: Arc<dyn TransactionSigner>,
+    sequence_numbers: SequenceNumbers,
+    operator: Option<String>,
+    audit_log: Mutex<Option<AuditLog>>,
+    pending: Mutex<Vec<Submission>>,
+    tx_options: TxOptions,
+    risk_manager: Option<RiskManager>,
+    cache: ReadCache,
+    network: Option<Network>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    http_client: reqwest::Client,
 }
 
 impl LaminarClient {
@@ -83,24 +364,65 @@ impl LaminarClient {
     pub async fn connect(
         node_url: Url,
         laminar: AccountAddress,
-        mut account: LocalAccount,
+        account: LocalAccount,
+    ) -> Result<Self> {
+        Self::connect_with_signer(node_url, laminar, Arc::new(account)).await
+    }
+
+    /// Connect to an Aptos node and initialize the Laminar Markets client, signing
+    /// transactions with a `TransactionSigner`.
+    ///
+    /// # Arguments:
+    ///
+    /// * `node_url` - Url of aptos node.
+    /// * `laminar_address` - Aptos `AccountAddress`.
+    /// * `signer` - `TransactionSigner` of the user account.
+    pub async fn connect_with_signer(
+        node_url: Url,
+        laminar: AccountAddress,
+        signer: Arc<dyn TransactionSigner>,
+    ) -> Result<Self> {
+        Self::connect_with_http_config(node_url, laminar, signer, &HttpConfig::default()).await
+    }
+
+    /// Initialize the Laminar Markets client with an existing aptos rest client, e.g. one
+    /// built with `HttpConfig` or shared with other code.
+    ///
+    /// # Arguments:
+    ///
+    /// * `aptos_client` - Rest client of the aptos node.
+    /// * `http_client` - Http client of the requests made without `aptos_client`, e.g. the
+    ///   one `aptos_client` was built from, so both share their settings.
+    /// * `laminar_address` - Aptos `AccountAddress`.
+    /// * `signer` - `TransactionSigner` of the user account.
+    pub async fn connect_with_client(
+        aptos_client: Client,
+        http_client: reqwest::Client,
+        laminar: AccountAddress,
+        signer: Arc<dyn TransactionSigner>,
     ) -> Result<Self> {
-        let aptos_client = Client::new(node_url);
         let index = aptos_client.get_index().await?.into_inner();
-        let chain_id = ChainId::new(index.chain_id);
         let account_info = aptos_client
-            .get_account(account.address())
+            .get_account(signer.address())
             .await?
             .into_inner();
         let seq_num = account_info.sequence_number;
-        let acc_seq_num = account.sequence_number_mut();
-        *acc_seq_num = seq_num;
 
         Ok(Self {
             laminar,
             aptos_client,
-            chain_id,
-            account,
+            chain_id: AtomicU8::new(index.chain_id),
+            signer,
+            sequence_numbers: SequenceNumbers::new(seq_num),
+            operator: None,
+            audit_log: Mutex::new(None),
+            pending: Mutex::new(vec![]),
+            tx_options: TxOptions::default(),
+            risk_manager: None,
+            cache: ReadCache::new(CacheConfig::default()),
+            network: None,
+            rate_limiter: None,
+            http_client,
         })
     }
 
@@ -113,22 +435,20 @@ impl LaminarClient {
     /// * `laminar_address` - hex encoded address string of account that holds the laminar modules.
     /// * `account_address` - hex encoded address string of user using this client.
     /// * `account_private_key` - hex encoded private key string of user using this client.
-    ///
-    /// # Panics:
-    ///
-    /// * If provided url is not valid.
-    /// * If provided private key is invalid.
     pub async fn connect_with_strings(
         node_url: &str,
         laminar_address: &str,
         account_address: &str,
         account_private_key: &str,
     ) -> Result<Self> {
-        let node_url = Url::parse(node_url).expect("node url is not valid");
-        let laminar = AccountAddress::from_hex_literal(laminar_address)?;
-        let account_address = AccountAddress::from_hex_literal(account_address)?;
+        let node_url = Url::parse(node_url)
+            .map_err(|e| LaminarError::InvalidInput(format!("node url: {}", e)))?;
+        let laminar = AccountAddress::from_hex_literal(laminar_address)
+            .map_err(|e| LaminarError::InvalidInput(format!("laminar address: {}", e)))?;
+        let account_address = AccountAddress::from_hex_literal(account_address)
+            .map_err(|e| LaminarError::InvalidInput(format!("account address: {}", e)))?;
         let private_key = Ed25519PrivateKey::from_encoded_string(account_private_key)
-            .expect("private key provided is not valid");
+            .map_err(|e| LaminarError::InvalidKey(e.to_string()))?;
         let account_key = AccountKey::from(private_key);
         let account = LocalAccount::new(account_address, account_key, 0);
         Self::connect(node_url, laminar, account).await
@@ -149,47 +469,161 @@ impl LaminarClient {
         config_path: &str,
         config_profile_name: &str,
     ) -> Result<Self> {
-        let config = AptosConfig::from_config(config_path, config_profile_name);
-        Self::connect_with_strings(
-            node_url,
-            laminar_address,
-            &config.account,
-            &config.private_key,
-        )
-        .await
+        let config = AptosConfig::load(config_path)?;
+        let (account, private_key) = config.credentials(config_profile_name)?;
+        Self::connect_with_strings(node_url, laminar_address, account, private_key).await
     }
 
     pub fn laminar(&self) -> &AccountAddress {
         &self.laminar
     }
 
+    /// `PayloadBuilder` for the Laminar modules of this client.
+    pub fn payload_builder(&self) -> PayloadBuilder {
+        PayloadBuilder::new(self.laminar)
+    }
+
     pub fn aptos_client(&self) -> &Client {
         &self.aptos_client
     }
 
-    pub fn account(&self) -> &LocalAccount {
-        &self.account
+    /// `Network` the client was connected to with `connect_to`, if any.
+    pub fn network(&self) -> Option<Network> {
+        self.network
+    }
+
+    pub fn set_network(&mut self, network: Option<Network>) {
+        self.network = network;
+    }
+
+    /// Chain id signed into transactions, see `update_chain_id`.
+    pub fn chain_id(&self) -> u8 {
+        self.chain_id.load(Ordering::SeqCst)
+    }
+
+    /// Signer of this client's transactions.
+    pub fn signer(&self) -> &dyn TransactionSigner {
+        self.signer.as_ref()
+    }
+
+    /// Address of the account used by this client.
+    pub fn address(&self) -> AccountAddress {
+        self.signer.address()
+    }
+
+    /// Sequence number the next submitted tx will use.
+    pub fn sequence_number(&self) -> u64 {
+        self.sequence_numbers.peek()
+    }
+
+    /// Overwrite the locally tracked sequence number, e.g. after submitting from another client.
+    /// Txs of this client still in flight are forgotten, so only call this while there are none.
+    pub fn set_sequence_number(&self, sequence_number: u64) {
+        self.sequence_numbers.reset(sequence_number);
+    }
+
+    /// Label submissions made by this client with an operator name.
+    /// Useful when several operators trade from one shared account.
+    pub fn set_operator(&mut self, operator: Option<String>) {
+        self.operator = operator;
+    }
+
+    pub fn operator(&self) -> Option<&str> {
+        self.operator.as_deref()
+    }
+
+    /// Record every successful submission into the given `AuditLog`.
+    pub fn set_audit_log(&mut self, audit_log: Option<AuditLog>) {
+        self.audit_log = Mutex::new(audit_log);
+    }
+
+    pub fn audit_log(&self) -> MutexGuard<'_, Option<AuditLog>> {
+        self.audit_log.lock().expect("audit log lock poisoned")
+    }
+
+    /// Default `TxOptions` used by `build_and_submit_tx` and the methods built on it.
+    pub fn set_tx_options(&mut self, options: TxOptions) {
+        self.tx_options = options;
+    }
+
+    pub fn tx_options(&self) -> &TxOptions {
+        &self.tx_options
+    }
+
+    /// Check every Laminar entry function payload against a `RiskManager` before submitting it.
+    /// Other payloads can't be checked and are refused while one is set.
+    pub fn set_risk_manager(&mut self, risk_manager: Option<RiskManager>) {
+        self.risk_manager = risk_manager;
+    }
+
+    pub fn risk_manager(&self) -> Option<&RiskManager> {
+        self.risk_manager.as_ref()
+    }
+
+    /// Throttle the requests this client makes to the node with a `RateLimiter`.
+    /// Requests made through `aptos_client` directly are not throttled.
+    pub fn set_rate_limiter(&mut self, rate_limiter: Option<Arc<RateLimiter>>) {
+        self.rate_limiter = rate_limiter;
+    }
+
+    pub fn rate_limiter(&self) -> Option<&Arc<RateLimiter>> {
+        self.rate_limiter.as_ref()
+    }
+
+    /// Set the TTL and capacity of the cache of rarely changing reads, e.g. `get_coin_info`,
+    /// `get_instrument` and registration checks. Cached entries are dropped.
+    pub fn set_cache_config(&mut self, config: CacheConfig) {
+        self.cache = ReadCache::new(config);
+    }
+
+    pub fn cache_config(&self) -> CacheConfig {
+        self.cache.config()
+    }
+
+    /// Drop all cached reads, e.g. after a redeployment.
+    pub fn clear_cache(&self) {
+        self.cache.clear();
+    }
+
+    /// Http client of the requests the client makes without `aptos_client`, e.g. buffered
+    /// event fetches. Set when connecting, see `connect_with_client`.
+    pub fn set_http_client(&mut self, http_client: reqwest::Client) {
+        self.http_client = http_client;
+    }
+
+    pub fn http_client(&self) -> &reqwest::Client {
+        &self.http_client
+    }
+
+    async fn rate_limit(&self, endpoint: Endpoint) {
+        if let Some(rate_limiter) = &self.rate_limiter {
+            rate_limiter.acquire(endpoint).await;
+        }
     }
 
     /// Update the laminar clients aptos chain id.
     /// If the aptos team pushes out a new node deployment, the chain id may change.
     /// In case of a change the internal chain id needs to be updated
-    pub async fn update_chain_id(&mut self) -> Result<()> {
+    pub async fn update_chain_id(&self) -> Result<()> {
+        self.rate_limit(Endpoint::Index).await;
         let index = self.aptos_client.get_index().await?.into_inner();
-        let chain_id = ChainId::new(index.chain_id);
-        self.chain_id = chain_id;
+        self.chain_id.store(index.chain_id, Ordering::SeqCst);
         Ok(())
     }
 
     // TODO doc strings for these functions
     pub async fn get_sequence_number(&self) -> Result<u64> {
+        self.rate_limit(Endpoint::Index).await;
         self.aptos_client
-            .get_account(self.account.address())
+            .get_account(self.signer.address())
             .await
-            .with_context(|| {
-                format!(
-                    "failed getting account: {}",
-                    self.account.address().to_hex_literal()
+            .map_err(|e| {
+                LaminarError::rest(
+                    e,
+                    format!(
+                        "failed getting account: {}",
+                        self.signer.address().to_hex_literal()
+                    ),
                 )
             })
             .map(|a| a.inner().sequence_number)
@@ -200,40 +634,192 @@ impl LaminarClient {
         address: AccountAddress,
         resource: &str,
     ) -> Result<Option<Resource>> {
-        self.aptos_client
-            .get_account_resource(address, resource)
+        self.fetch_resource_at(address, resource, None)
+            .await
+            .map(|(r, _)| r)
+    }
+
+    /// Fetch a resource from the BCS endpoint and decode it with bcs, `None` if the account
+    /// has no such resource. `T` must declare the fields in the order of the Move struct.
+    ///
+    /// # Arguments:
+    ///
+    /// * `address` - Account holding the resource.
+    /// * `resource` - Move type of the resource, e.g. `0x1::book::OrderBookStore`.
+    pub async fn fetch_resource_bcs<T: DeserializeOwned>(
+        &self,
+        address: AccountAddress,
+        resource: &str,
+    ) -> Result<Option<T>> {
+        self.fetch_resource_bcs_at(address, resource, None)
             .await
-            .with_context(|| {
+            .map(|(r, _)| r)
+    }
+
+    /// Same as `fetch_resource_at`, through the BCS endpoint.
+    async fn fetch_resource_bcs_at<T: DeserializeOwned>(
+        &self,
+        address: AccountAddress,
+        resource: &str,
+        version: Option<u64>,
+    ) -> Result<(Option<T>, u64)> {
+        self.rate_limit(Endpoint::Resources).await;
+        let res = match version {
+            Some(v) => {
+                self.aptos_client
+                    .get_account_resource_at_version_bcs::<T>(address, resource, v)
+                    .await
+            }
+            None => {
+                self.aptos_client
+                    .get_account_resource_bcs::<T>(address, resource)
+                    .await
+            }
+        };
+        match res {
+            Ok(r) => {
+                let read_at = version.unwrap_or(r.state().version);
+                Ok((Some(r.into_inner()), read_at))
+            }
+            Err(RestError::Api(a)) if a.error.error_code == AptosErrorCode::ResourceNotFound => {
+                let read_at = match version {
+                    Some(v) => v,
+                    None => a.state.map_or(0, |s| s.version),
+                };
+                Ok((None, read_at))
+            }
+            Err(e) => Err(LaminarError::rest(
+                e,
                 format!(
                     "failed getting resource: {} for account: {}",
                     resource,
                     address.to_hex_literal()
-                )
-            })
-            .map(|a| a.into_inner())
+                ),
+            )),
+        }
+    }
+
+    /// Fetch a resource at `version`, or at the latest version if `None`.
+    /// Also returns the ledger version the resource was read at.
+    async fn fetch_resource_at(
+        &self,
+        address: AccountAddress,
+        resource: &str,
+        version: Option<u64>,
+    ) -> Result<(Option<Resource>, u64)> {
+        self.rate_limit(Endpoint::Resources).await;
+        let res = match version {
+            Some(v) => {
+                self.aptos_client
+                    .get_account_resource_at_version(address, resource, v)
+                    .await
+            }
+            None => {
+                self.aptos_client
+                    .get_account_resource(address, resource)
+                    .await
+            }
+        };
+        res.map_err(|e| {
+            LaminarError::rest(
+                e,
+                format!(
+                    "failed getting resource: {} for account: {}",
+                    resource,
+                    address.to_hex_literal()
+                ),
+            )
+        })
+        .map(|a| {
+            let read_at = version.unwrap_or(a.state().version);
+            (a.into_inner(), read_at)
+        })
     }
 
+    /// Whether a coin is initialized. Existing coins are cached.
     pub async fn does_coin_exist(&self, coin: &TypeTag) -> Result<bool> {
+        if self.cache.coins.get(coin).is_some() {
+            return Ok(true);
+        }
         let coin_info = format!("0x1::coin::CoinInfo<{}>", coin);
         let TypeTag::Struct(tag) = coin else {
-            return Err(anyhow!("failed extracting coin typetag"))
+            return Err(LaminarError::InvalidInput(format!(
+                "not a coin type: {}",
+                coin
+            )));
         };
 
-        self.fetch_resource(tag.address, &coin_info)
-            .await
-            .map(|r| r.is_some())
+        let exists = self
+            .fetch_resource(tag.address, &coin_info)
+            .await?
+            .is_some();
+        if exists {
+            self.cache.coins.insert(coin.clone(), true);
+        }
+        Ok(exists)
+    }
+
+    /// Fetch the `CoinInfo` of a coin, cached after the first read.
+    /// The cached `supply` is only updated once the entry expires, use `fetch_coin_info` for
+    /// the current supply.
+    ///
+    /// # Arguments:
+    ///
+    /// * `coin` - Aptos `TypeTag` of the coin.
+    pub async fn get_coin_info(&self, coin: &TypeTag) -> Result<CoinInfo> {
+        if let Some(info) = self.cache.coin_infos.get(coin) {
+            return Ok(info);
+        }
+
+        self.fetch_coin_info(coin).await
+    }
+
+    /// Fetch the `CoinInfo` of a coin from the node, bypassing and refreshing the cache.
+    ///
+    /// # Arguments:
+    ///
+    /// * `coin` - Aptos `TypeTag` of the coin.
+    pub async fn fetch_coin_info(&self, coin: &TypeTag) -> Result<CoinInfo> {
+        let coin_info = format!("0x1::coin::CoinInfo<{}>", coin);
+        let TypeTag::Struct(tag) = coin else {
+            return Err(LaminarError::InvalidInput(format!(
+                "not a coin type: {}",
+                coin
+            )));
+        };
+
+        let resource = self
+            .fetch_resource(tag.address, &coin_info)
+            .await?
+            .ok_or_else(|| LaminarError::NotFound(format!("coin info: {}", coin)))?;
+        let info = serde_json::from_value::<CoinInfo>(resource.data)
+            .map_err(|e| LaminarError::deserialization(e, "coin info"))?;
+        self.cache.coin_infos.insert(coin.clone(), info.clone());
+
+        Ok(info)
     }
 
+    /// Whether this client's account is registered for a coin. Registrations are cached.
     pub async fn is_registered_for_coin(&self, coin: &TypeTag) -> Result<bool> {
+        if self.cache.coin_registrations.get(coin).is_some() {
+            return Ok(true);
+        }
         let coin_store = format!("0x1::coin::CoinStore<{}>", coin);
-        self.fetch_resource(self.account.address(), &coin_store)
-            .await
-            .map(|r| r.is_some())
+        let registered = self
+            .fetch_resource(self.signer.address(), &coin_store)
+            .await?
+            .is_some();
+        if registered {
+            self.cache.coin_registrations.insert(coin.clone(), true);
+        }
+        Ok(registered)
     }
 
     pub fn register_for_coin(coin: &TypeTag) -> Result<EntryFunction> {
         let entry = EntryFunction::new(
-            ModuleId::from(MoveModuleId::from_str("0x1::managed_coin")?),
+            ModuleId::from(
+                MoveModuleId::from_str("0x1::managed_coin").map_err(LaminarError::Other)?,
+            ),
             ident_str!("register").to_owned(),
             vec![coin.clone()],
             vec![],
@@ -243,24 +829,32 @@ impl LaminarClient {
     }
 
     pub async fn get_coin_balance(&self, coin: &TypeTag) -> Result<U64> {
+        self.fetch_coin_balance(coin, None).await
+    }
+
+    /// Fetch this client's coin balance as of ledger `version`.
+    pub async fn get_coin_balance_at_version(&self, coin: &TypeTag, version: u64) -> Result<U64> {
+        self.fetch_coin_balance(coin, Some(version)).await
+    }
+
+    async fn fetch_coin_balance(&self, coin: &TypeTag, version: Option<u64>) -> Result<U64> {
         let coin_store = format!("0x1::coin::CoinStore<{}>", coin);
-        self.fetch_resource(self.account.address(), &coin_store)
+        self.fetch_resource_at(self.signer.address(), &coin_store, version)
             .await?
-            .with_context(|| format!("user is not registered for coin: {}", &coin_store))
+            .0
+            .ok_or_else(|| {
+                LaminarError::NotFound(format!("user is not registered for coin: {}", &coin_store))
+            })
             .and_then(|r| {
-                serde_json::from_value::<Balance>(r.data).context("failed deserializing balance")
+                serde_json::from_value::<Balance>(r.data)
+                    .map_err(|e| LaminarError::deserialization(e, "balance"))
             })
             .map(|b| b.coin.value)
     }
 
     /// Create payload for this client's account to be registered to trade on Laminar
     pub fn register_user_payload(&self) -> EntryFunction {
-        EntryFunction::new(
-            ModuleId::new(self.laminar, ident_str!("book").to_owned()),
-            ident_str!("register_user").to_owned(),
-            vec![],
-            vec![],
-        )
+        self.payload_builder().register_user()
     }
 
     /// Create payload for creating an `OrderBook`.
@@ -282,18 +876,50 @@ impl LaminarClient {
         size_decimals: u8,
         min_size_amount: u64,
     ) -> Result<EntryFunction> {
-        let entry = EntryFunction::new(
-            ModuleId::new(self.laminar, ident_str!("book").to_owned()),
-            ident_str!("create_orderbook").to_owned(),
-            vec![base.clone(), quote.clone()],
-            vec![
-                bcs::to_bytes(&price_decimals)?,
-                bcs::to_bytes(&size_decimals)?,
-                bcs::to_bytes(&min_size_amount)?,
-            ],
-        );
+        self.payload_builder().create_orderbook(
+            base,
+            quote,
+            price_decimals,
+            size_decimals,
+            min_size_amount,
+        )
+    }
 
-        Ok(entry)
+    /// Create several `OrderBook`s in one call.
+    /// All base and quote coins are checked to exist before anything is submitted.
+    /// Returns the ids of the created books, in the order of `specs`.
+    ///
+    /// # Arguments:
+    ///
+    /// * `specs` - `MarketSpec`s of the books to create.
+    pub async fn create_markets(&self, specs: &[MarketSpec]) -> Result<Vec<Id>> {
+        let coins = specs
+            .iter()
+            .flat_map(|s| [&s.base, &s.quote])
+            .collect::<Vec<_>>();
+        let exists = try_join_all(coins.iter().map(|c| self.does_coin_exist(c))).await?;
+        if let Some((coin, _)) = coins.iter().zip(exists).find(|(_, e)| !e) {
+            return Err(LaminarError::NotFound(format!(
+                "coin does not exist: {}",
+                coin
+            )));
+        }
+
+        let mut ids = vec![];
+        for s in specs {
+            let event = self
+                .create_orderbook(
+                    &s.base,
+                    &s.quote,
+                    s.price_decimals,
+                    s.size_decimals,
+                    s.min_size_amount,
+                )
+                .await?;
+            ids.push(event.book_id);
+        }
+
+        Ok(ids)
     }
 
     fn get_book_bids_type(&self, base: &TypeTag, quote: &TypeTag) -> String {
@@ -315,6 +941,7 @@ impl LaminarClient {
     }
 
     /// Fetch `OrderBook` information from Aptos node.
+    /// Both sides are read at the same ledger version, recorded in `OrderBook::ledger_version`.
     ///
     /// # Arguments:
     ///
@@ -327,300 +954,1618 @@ impl LaminarClient {
         quote: &TypeTag,
         book_owner: &AccountAddress,
     ) -> Result<OrderBook> {
-        let bids = self.fetch_orderbook_side(self.get_book_bids_type(base, quote), book_owner);
-        let asks = self.fetch_orderbook_side(self.get_book_asks_type(base, quote), book_owner);
-        try_join!(bids, asks).map(|(mut b, a)| {
-            b.asks = a.asks;
-            b
-        })
-    }
-
-    async fn fetch_orderbook_side(
-        &self,
-        book_type: String,
-        book_owner: &AccountAddress,
-    ) -> Result<OrderBook> {
-        self.fetch_resource(*book_owner, &book_type)
-            .await?
-            .context("book not found")
-            .and_then(
-                |Resource {
-                     data,
-                     resource_type,
-                 }| {
-                    let mut book = serde_json::from_value::<OrderBook>(data)?;
-                    let types = resource_type.type_params;
-                    book.type_tags.extend(types);
-                    Ok(book)
-                },
+        let mut book = self
+            .fetch_orderbook_side_at(base, quote, book_owner, Side::Bid, None, None)
+            .await?;
+        let asks = self
+            .fetch_orderbook_side_at(
+                base,
+                quote,
+                book_owner,
+                Side::Ask,
+                book.ledger_version,
+                None,
             )
+            .await?;
+        book.asks = asks.asks;
+        Ok(book)
     }
 
-    /// Checks if account using this client is eligible to trade on Laminar
-    pub async fn is_user_registered(&self) -> Result<bool> {
-        let event_store_type = format!("{}::book::OrderBookStore", self.laminar.to_hex_literal(),);
-        self.fetch_resource(self.account.address(), &event_store_type)
-            .await
-            .map(|r| r.is_some())
-    }
-
-    /// Create payload for placing a limit order.
+    /// Fetch `OrderBook` as of ledger `version`, for reproducible reads.
     ///
     /// # Arguments:
     ///
     /// * `base` - Aptos `TypeTag` of the orderbook base coin.
     /// * `quote` - Aptos `TypeTag` of the orderbook quote coin.
     /// * `book_owner` - Address of the account that owns the `OrderBook`.
-    /// * `side` - `OrderSide`: Bid or Ask.
-    /// * `price` - Price in `U64` of limit order.
-    /// * `size` - `U64` size of limit order.
-    /// * `time_in_force` - `TimeInForce` for limit order, can be GTC, IOC, or FOK.
-    /// * `post_only` - Flag to specify whether or not the limit order is `post_only`.
-    #[allow(clippy::too_many_arguments)]
-    pub fn place_limit_order_payload(
+    /// * `version` - Ledger version to read the book at.
+    pub async fn fetch_orderbook_at_version(
         &self,
         base: &TypeTag,
         quote: &TypeTag,
         book_owner: &AccountAddress,
-        side: Side,
-        price: u64,
-        size: u64,
-        time_in_force: TimeInForce,
-        post_only: bool,
-    ) -> Result<EntryFunction> {
-        let entry = EntryFunction::new(
-            ModuleId::new(self.laminar, ident_str!("book").to_owned()),
-            ident_str!("place_limit_order").to_owned(),
-            vec![base.clone(), quote.clone()],
-            vec![
-                bcs::to_bytes(book_owner)?,
-                bcs::to_bytes(&side)?,
-                bcs::to_bytes(&price)?,
-                bcs::to_bytes(&size)?,
-                bcs::to_bytes(&time_in_force)?,
-                bcs::to_bytes(&post_only)?,
-            ],
-        );
-
-        Ok(entry)
-    }
+        version: u64,
+    ) -> Result<OrderBook> {
+        let bids =
+            self.fetch_orderbook_side_at(base, quote, book_owner, Side::Bid, Some(version), None);
+        let asks =
+            self.fetch_orderbook_side_at(base, quote, book_owner, Side::Ask, Some(version), None);
+        try_join!(bids, asks).map(|(mut b, a)| {
+            b.asks = a.asks;
+            b
+        })
+    }
+
+    /// Fetch `OrderBook` by its Id. The owner is taken from the Id and the coin types from
+    /// the book's `CreateOrderBookEvent`. Use `MarketRegistry::get_by_book_id` and
+    /// `fetch_orderbook` instead to avoid reading the creation events on every call.
+    ///
+    /// # Arguments:
+    ///
+    /// * `book_id` - `OrderBook` Id.
+    pub async fn fetch_orderbook_by_id(&self, book_id: &Id) -> Result<OrderBook> {
+        let market = self.fetch_market_by_id(book_id).await?;
+        self.fetch_orderbook(&market.base, &market.quote, &market.book_owner)
+            .await
+    }
+
+    /// Resolve the `Market` of a book from its `CreateOrderBookEvent`. The books of an owner
+    /// are cached, see `fetch_order_books_of`.
+    ///
+    /// # Arguments:
+    ///
+    /// * `book_id` - `OrderBook` Id.
+    pub async fn fetch_market_by_id(&self, book_id: &Id) -> Result<Market> {
+        let book_owner = *book_id.addr.inner();
+        let find =
+            |books: Vec<CreateOrderBookEvent>| books.into_iter().find(|b| &b.book_id == book_id);
+        let mut book = self.cache.order_books.get(&book_owner).and_then(find);
+        // the cached books of the owner can predate the book
+        if book.is_none() {
+            book = find(self.fetch_order_books_of(book_owner).await?);
+        }
+        let book =
+            book.ok_or_else(|| LaminarError::NotFound(format!("order book: {}", book_id)))?;
+
+        Ok(Market::new(
+            TypeTag::try_from(book.base)?,
+            TypeTag::try_from(book.quote)?,
+            book_owner,
+        ))
+    }
+
+    /// Fetch the `Instrument` of a book, cached after the first read. Only the prices of the
+    /// book's asks are read, see `fetch_orderbook_side`.
+    ///
+    /// # Arguments:
+    ///
+    /// * `market` - `Market` of the book.
+    pub async fn get_instrument(&self, market: &Market) -> Result<Instrument> {
+        if let Some(instrument) = self.cache.instruments.get(market) {
+            return Ok(instrument);
+        }
+        let book = self
+            .fetch_orderbook_side(
+                &market.base,
+                &market.quote,
+                &market.book_owner,
+                Side::Ask,
+                Some(0),
+            )
+            .await?;
+        self.cache
+            .instruments
+            .insert(market.clone(), book.instrument.clone());
+
+        Ok(book.instrument)
+    }
+
+    /// Fetch a single side of an `OrderBook`. The other side of the returned book is empty.
+    ///
+    /// With a `depth` only the best `depth` levels with resting orders are kept, and only
+    /// their orders are deserialized, which is much cheaper than reading a deep book.
+    ///
+    /// # Arguments:
+    ///
+    /// * `base` - Aptos `TypeTag` of the orderbook base coin.
+    /// * `quote` - Aptos `TypeTag` of the orderbook quote coin.
+    /// * `book_owner` - Address of the account that owns the `OrderBook`.
+    /// * `side` - Side to fetch.
+    /// * `depth` - Number of levels to keep, `None` for all.
+    pub async fn fetch_orderbook_side(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        book_owner: &AccountAddress,
+        side: Side,
+        depth: Option<usize>,
+    ) -> Result<OrderBook> {
+        self.fetch_orderbook_side_at(base, quote, book_owner, side, None, depth)
+            .await
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, base, quote), err)
+    )]
+    async fn fetch_orderbook_side_at(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        book_owner: &AccountAddress,
+        side: Side,
+        version: Option<u64>,
+        depth: Option<usize>,
+    ) -> Result<OrderBook> {
+        let book_type = match side {
+            Side::Bid => self.get_book_bids_type(base, quote),
+            Side::Ask => self.get_book_asks_type(base, quote),
+        };
+        let (resource, read_at) = self
+            .fetch_resource_at(*book_owner, &book_type, version)
+            .await?;
+        let Resource {
+            data,
+            resource_type,
+        } = resource.ok_or_else(|| LaminarError::BookNotFound(book_type))?;
+        let mut book = match depth {
+            Some(depth) => OrderBook::from_side_resource(&data, side, depth),
+            None => serde_json::from_value::<OrderBook>(data),
+        }
+        .map_err(|e| LaminarError::deserialization(e, "order book"))?;
+        let types = resource_type.type_params;
+        book.type_tags.extend(types);
+        book.ledger_version = Some(read_at);
+        trace_event!(
+            debug,
+            bid_levels = book.bids.len(),
+            ask_levels = book.asks.len(),
+            version = read_at,
+            "fetched book side"
+        );
+        Ok(book)
+    }
+
+    /// Fetch the orders this client's account has resting on an `OrderBook`.
+    ///
+    /// # Arguments:
+    ///
+    /// * `base` - Aptos `TypeTag` of the orderbook base coin.
+    /// * `quote` - Aptos `TypeTag` of the orderbook quote coin.
+    /// * `book_owner` - Address of the account that owns the `OrderBook`.
+    pub async fn fetch_my_open_orders(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        book_owner: &AccountAddress,
+    ) -> Result<Vec<OpenOrder>> {
+        let book = self.fetch_orderbook(base, quote, book_owner).await?;
+        Ok(book.open_orders_of(&self.signer.address()))
+    }
+
+    /// Run a `StaleQuoteMonitor` against the current `OrderBook` and cancel the flagged
+    /// orders if the monitor has `auto_cancel` enabled. Every flagged order is attempted, a
+    /// single failed cancel is returned as is and several are combined into one error.
+    ///
+    /// # Arguments:
+    ///
+    /// * `base` - Aptos `TypeTag` of the orderbook base coin.
+    /// * `quote` - Aptos `TypeTag` of the orderbook quote coin.
+    /// * `book_owner` - Address of the account that owns the `OrderBook`.
+    /// * `monitor` - `StaleQuoteMonitor` configuration.
+    /// * `reference_price` - Fair value in price units, `None` to only check for crossed orders.
+    pub async fn check_stale_quotes(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        book_owner: &AccountAddress,
+        monitor: &StaleQuoteMonitor,
+        reference_price: Option<u64>,
+    ) -> Result<Vec<StaleQuote>> {
+        let book = self.fetch_orderbook(base, quote, book_owner).await?;
+        let stale = monitor.check(&book, reference_price);
+        if !monitor.auto_cancel {
+            return Ok(stale);
+        }
+
+        let mut failed = vec![];
+        for s in &stale {
+            if let Err(e) = self
+                .cancel_order(base, quote, book_owner, &s.order.id, s.side)
+                .await
+            {
+                failed.push((s.order.id.clone(), e));
+            }
+        }
+        match failed.len() {
+            0 => Ok(stale),
+            1 => Err(failed.remove(0).1),
+            n => Err(LaminarError::Other(anyhow!(
+                "failed cancelling {} of {} stale quotes: {}",
+                n,
+                stale.len(),
+                failed
+                    .iter()
+                    .map(|(id, e)| format!("{}: {}", id, e))
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            ))),
+        }
+    }
+
+    /// Checks if account using this client is eligible to trade on Laminar.
+    /// A registered account is cached.
+    pub async fn is_user_registered(&self) -> Result<bool> {
+        if self.cache.user_registered.get(&()).is_some() {
+            return Ok(true);
+        }
+        let event_store_type = format!("{}::book::OrderBookStore", self.laminar.to_hex_literal(),);
+        let registered = self
+            .fetch_resource(self.signer.address(), &event_store_type)
+            .await?
+            .is_some();
+        if registered {
+            self.cache.user_registered.insert((), true);
+        }
+        Ok(registered)
+    }
+
+    /// Cursor past the latest committed transaction, to read only the events emitted from now
+    /// on.
+    pub async fn fetch_event_cursor(&self) -> Result<EventCursor> {
+        self.rate_limit(Endpoint::Index).await;
+        let index = self
+            .aptos_client
+            .get_index()
+            .await
+            .map_err(|e| LaminarError::rest(e, "failed getting ledger info"))?
+            .into_inner();
+
+        Ok(EventCursor {
+            version: index.ledger_version.0 + 1,
+        })
+    }
+
+    /// Create payload for placing a limit order.
+    ///
+    /// # Arguments:
+    ///
+    /// * `base` - Aptos `TypeTag` of the orderbook base coin.
+    /// * `quote` - Aptos `TypeTag` of the orderbook quote coin.
+    /// * `book_owner` - Address of the account that owns the `OrderBook`.
+    /// * `side` - `OrderSide`: Bid or Ask.
+    /// * `price` - Price in `U64` of limit order.
+    /// * `size` - `U64` size of limit order.
+    /// * `time_in_force` - `TimeInForce` for limit order, can be GTC, IOC, or FOK.
+    /// * `post_only` - Flag to specify whether or not the limit order is `post_only`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn place_limit_order_payload(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        book_owner: &AccountAddress,
+        side: Side,
+        price: u64,
+        size: u64,
+        time_in_force: TimeInForce,
+        post_only: bool,
+    ) -> Result<EntryFunction> {
+        self.payload_builder().place_limit_order(
+            base,
+            quote,
+            book_owner,
+            side,
+            price,
+            size,
+            time_in_force,
+            post_only,
+        )
+    }
+
+    /// Create payload for placing a market order.
+    ///
+    /// # Arguments:
+    ///
+    /// * `base` - Aptos `TypeTag` of the orderbook base coin.
+    /// * `quote` - Aptos `TypeTag` of the orderbook quote coin.
+    /// * `book_owner` - Address of the account that owns the `OrderBook`.
+    /// * `side` - `Side`: Bid or Ask.
+    /// * `size` - U64 size of market order.
+    pub fn place_market_order_payload(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        book_owner: &AccountAddress,
+        side: Side,
+        size: u64,
+    ) -> Result<EntryFunction> {
+        self.payload_builder()
+            .place_market_order(base, quote, book_owner, side, size)
+    }
+
+    /// Create payload for amending an order.
+    ///
+    /// # Arguments:
+    ///
+    /// * `base` - Aptos `TypeTag` of the orderbook base coin.
+    /// * `quote` - Aptos `TypeTag` of the orderbook quote coin.
+    /// * `book_owner` - Address of the account that owns the `OrderBook`.
+    /// * `order_id` - ID of order to amend.
+    /// * `side` - `OrderSide`: Bid or Ask.
+    /// * `price` - Price to update, provide current price if no amendment needed.
+    /// * `size` - Size to update, provide current size if no amendment needed.
+    #[allow(clippy::too_many_arguments)]
+    pub fn amend_order_payload(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        book_owner: &AccountAddress,
+        order_id: &Id,
+        side: Side,
+        price: u64,
+        size: u64,
+    ) -> Result<EntryFunction> {
+        self.payload_builder()
+            .amend_order(base, quote, book_owner, order_id, side, price, size)
+    }
+
+    /// Create payload for canceling an order.
+    ///
+    /// # Arguments:
+    ///
+    /// * `base` - Aptos `TypeTag` of the orderbook base coin.
+    /// * `quote` - Aptos `TypeTag` of the orderbook quote coin.
+    /// * `book_owner` - Address of the account that owns the `OrderBook`.
+    /// * `order_id` - ID of order to cancel.
+    /// * `side` - `OrderSide`: Bid or Ask.
+    pub fn cancel_order_payload(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        book_owner: &AccountAddress,
+        order_id: &Id,
+        side: Side,
+    ) -> Result<EntryFunction> {
+        self.payload_builder()
+            .cancel_order(base, quote, book_owner, order_id, side)
+    }
+
+    /// Create the entry function payload for a single `LaminarAction`.
+    /// The book module has no batch entry function, pipeline several actions with
+    /// `build_and_submit_txs` instead.
+    ///
+    /// # Arguments:
+    ///
+    /// * `base` - Aptos `TypeTag` of the orderbook base coin.
+    /// * `quote` - Aptos `TypeTag` of the orderbook quote coin.
+    /// * `book_owner` - Address of the account that owns the `OrderBook`.
+    /// * `action` - Action to create the payload for.
+    pub fn action_payload(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        book_owner: &AccountAddress,
+        action: &LaminarAction,
+    ) -> Result<EntryFunction> {
+        self.payload_builder()
+            .action(base, quote, book_owner, action)
+    }
+
+    /// Register this client's account to trade on Laminar.
+    pub async fn register_user(&self) -> Result<LaminarTransaction> {
+        let payload = self.register_user_payload();
+        self.build_and_submit_tx(payload).await
+    }
+
+    /// Create an `OrderBook` owned by this client's account.
+    /// See `create_orderbook_payload` for the arguments.
+    pub async fn create_orderbook(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        price_decimals: u8,
+        size_decimals: u8,
+        min_size_amount: u64,
+    ) -> Result<CreateOrderBookEvent> {
+        let payload = self.create_orderbook_payload(
+            base,
+            quote,
+            price_decimals,
+            size_decimals,
+            min_size_amount,
+        )?;
+        let tx = self.build_and_submit_tx(payload).await?;
+        expect_event(&tx, "create orderbook", |e| match e {
+            LaminarEvent::CreateOrderBook(c) => Some(c.clone()),
+            _ => None,
+        })
+    }
+
+    /// Place a limit order. See `place_limit_order_payload` for the arguments.
+    #[allow(clippy::too_many_arguments)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "place_limit_order",
+            skip_all,
+            fields(
+                laminar.book_owner = %book_owner,
+                laminar.side = ?side,
+                laminar.price = price,
+                laminar.size = size,
+                laminar.book_id = tracing::field::Empty,
+                laminar.order_id = tracing::field::Empty,
+                tx.hash = tracing::field::Empty
+            ),
+            err
+        )
+    )]
+    pub async fn place_limit_order(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        book_owner: &AccountAddress,
+        side: Side,
+        price: u64,
+        size: u64,
+        time_in_force: TimeInForce,
+        post_only: bool,
+    ) -> Result<PlacedOrder> {
+        let payload = self.place_limit_order_payload(
+            base,
+            quote,
+            book_owner,
+            side,
+            price,
+            size,
+            time_in_force,
+            post_only,
+        )?;
+        let tx = self.build_and_submit_tx(payload).await?;
+        let placed = PlacedOrder::from_transaction(&tx)
+            .ok_or_else(|| LaminarError::NotFound("place order event".to_string()))?;
+        trace_record!("laminar.book_id", &placed.place.book_id);
+        trace_record!("laminar.order_id", &placed.place.order_id);
+        trace_event!(debug, fills = placed.fills.len(), "order placed");
+        Ok(placed)
+    }
+
+    /// Place a limit order given a decimal price and size, validating them against the book's
+    /// `Instrument` before anything is submitted. Off-tick values are rejected with a
+    /// descriptive error, or rounded if `tick_policy` is `TickPolicy::Round`.
+    ///
+    /// # Arguments:
+    ///
+    /// * `instrument` - `Instrument` of the book, e.g. from `fetch_orderbook`.
+    /// * `base` - Aptos `TypeTag` of the orderbook base coin.
+    /// * `quote` - Aptos `TypeTag` of the orderbook quote coin.
+    /// * `book_owner` - Address of the account that owns the `OrderBook`.
+    /// * `side` - `Side`: Bid or Ask.
+    /// * `price` - Decimal price of the order.
+    /// * `size` - Decimal size of the order.
+    /// * `time_in_force` - `TimeInForce` for limit order, can be GTC, IOC, or FOK.
+    /// * `post_only` - Flag to specify whether or not the limit order is `post_only`.
+    /// * `tick_policy` - Whether off-tick values are rejected or rounded.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn place_limit_order_checked(
+        &self,
+        instrument: &Instrument,
+        base: &TypeTag,
+        quote: &TypeTag,
+        book_owner: &AccountAddress,
+        side: Side,
+        price: f64,
+        size: f64,
+        time_in_force: TimeInForce,
+        post_only: bool,
+        tick_policy: TickPolicy,
+    ) -> Result<PlacedOrder> {
+        let invalid = |e: anyhow::Error| LaminarError::InvalidInput(e.to_string());
+        let price = instrument
+            .checked_price_units(price, side, tick_policy)
+            .map_err(invalid)?;
+        let size = instrument
+            .checked_size_units(size, tick_policy)
+            .map_err(invalid)?;
+
+        self.place_limit_order(
+            base,
+            quote,
+            book_owner,
+            side,
+            price,
+            size,
+            time_in_force,
+            post_only,
+        )
+        .await
+    }
+
+    /// Place a market order. See `place_market_order_payload` for the arguments.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "place_market_order",
+            skip_all,
+            fields(
+                laminar.book_owner = %book_owner,
+                laminar.side = ?side,
+                laminar.size = size,
+                laminar.book_id = tracing::field::Empty,
+                laminar.order_id = tracing::field::Empty,
+                tx.hash = tracing::field::Empty
+            ),
+            err
+        )
+    )]
+    pub async fn place_market_order(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        book_owner: &AccountAddress,
+        side: Side,
+        size: u64,
+    ) -> Result<PlacedOrder> {
+        let payload = self.place_market_order_payload(base, quote, book_owner, side, size)?;
+        let tx = self.build_and_submit_tx(payload).await?;
+        let placed = PlacedOrder::from_transaction(&tx)
+            .ok_or_else(|| LaminarError::NotFound("place order event".to_string()))?;
+        trace_record!("laminar.book_id", &placed.place.book_id);
+        trace_record!("laminar.order_id", &placed.place.order_id);
+        trace_event!(debug, fills = placed.fills.len(), "order placed");
+        Ok(placed)
+    }
+
+    /// Place a market order protected against slippage.
+    ///
+    /// The current book is fetched and the order is submitted as an IOC limit order at the
+    /// worst price within `max_slippage_bps` of the best opposite price, see
+    /// `OrderBook::slippage_limit_price`. Size that cannot be filled within the limit is
+    /// cancelled instead of sweeping a thin book.
+    ///
+    /// # Arguments:
+    ///
+    /// * `base` - Aptos `TypeTag` of the orderbook base coin.
+    /// * `quote` - Aptos `TypeTag` of the orderbook quote coin.
+    /// * `book_owner` - Address of the account that owns the `OrderBook`.
+    /// * `side` - `Side`: Bid or Ask.
+    /// * `size` - U64 size of the order.
+    /// * `max_slippage_bps` - Maximum distance from the best opposite price in basis points.
+    pub async fn place_market_order_with_limit(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        book_owner: &AccountAddress,
+        side: Side,
+        size: u64,
+        max_slippage_bps: u64,
+    ) -> Result<PlacedOrder> {
+        let book = self.fetch_orderbook(base, quote, book_owner).await?;
+        let price = book
+            .slippage_limit_price(side, max_slippage_bps)
+            .ok_or_else(|| {
+                LaminarError::InvalidInput("no liquidity on the opposite side".to_string())
+            })?;
+
+        self.place_limit_order(
+            base,
+            quote,
+            book_owner,
+            side,
+            price,
+            size,
+            TimeInForce::ImmediateOrCancel,
+            false,
+        )
+        .await
+    }
+
+    /// Amend an order. See `amend_order_payload` for the arguments.
+    #[allow(clippy::too_many_arguments)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "amend_order",
+            skip_all,
+            fields(
+                laminar.book_owner = %book_owner,
+                laminar.side = ?side,
+                laminar.price = price,
+                laminar.size = size,
+                laminar.book_id = tracing::field::Empty,
+                laminar.order_id = tracing::field::Empty,
+                tx.hash = tracing::field::Empty
+            ),
+            err
+        )
+    )]
+    pub async fn amend_order(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        book_owner: &AccountAddress,
+        order_id: &Id,
+        side: Side,
+        price: u64,
+        size: u64,
+    ) -> Result<AmendOrderEvent> {
+        trace_record!("laminar.order_id", order_id);
+        let payload =
+            self.amend_order_payload(base, quote, book_owner, order_id, side, price, size)?;
+        let tx = self.build_and_submit_tx(payload).await?;
+        let amend = expect_event(&tx, "amend order", |e| match e {
+            LaminarEvent::AmendOrder(a) if &a.order_id == order_id => Some(a.clone()),
+            _ => None,
+        })?;
+        trace_record!("laminar.book_id", &amend.book_id);
+        Ok(amend)
+    }
+
+    /// Cancel an order. See `cancel_order_payload` for the arguments.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "cancel_order",
+            skip_all,
+            fields(
+                laminar.book_owner = %book_owner,
+                laminar.side = ?side,
+                laminar.book_id = tracing::field::Empty,
+                laminar.order_id = tracing::field::Empty,
+                tx.hash = tracing::field::Empty
+            ),
+            err
+        )
+    )]
+    pub async fn cancel_order(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        book_owner: &AccountAddress,
+        order_id: &Id,
+        side: Side,
+    ) -> Result<CancelOrderEvent> {
+        trace_record!("laminar.order_id", order_id);
+        let payload = self.cancel_order_payload(base, quote, book_owner, order_id, side)?;
+        let tx = self.build_and_submit_tx(payload).await?;
+        let cancel = expect_event(&tx, "cancel order", |e| match e {
+            LaminarEvent::CancelOrder(c) if &c.order_id == order_id => Some(c.clone()),
+            _ => None,
+        })?;
+        trace_record!("laminar.book_id", &cancel.book_id);
+        Ok(cancel)
+    }
+
+    /// Move an order to a new price and size, returning the id of the resulting order.
+    ///
+    /// The order is amended in place when possible. If the amend is aborted by the book because
+    /// the new price would cross and `post_only` is false, the order is cancelled and a new GTC
+    /// order is placed instead, in which case the returned id differs from `order_id`. If the
+    /// new order then fails, `LaminarError::ReplaceFailed` is returned with the cancelled
+    /// order. Other errors are returned with the order left unchanged.
+    ///
+    /// # Arguments:
+    ///
+    /// * `base` - Aptos `TypeTag` of the orderbook base coin.
+    /// * `quote` - Aptos `TypeTag` of the orderbook quote coin.
+    /// * `book_owner` - Address of the account that owns the `OrderBook`.
+    /// * `order_id` - ID of order to replace.
+    /// * `side` - `Side`: Bid or Ask.
+    /// * `price` - New price of the order.
+    /// * `size` - New size of the order.
+    /// * `post_only` - Whether the order must not take liquidity, which rules out the fallback.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn replace_order(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        book_owner: &AccountAddress,
+        order_id: &Id,
+        side: Side,
+        price: u64,
+        size: u64,
+        post_only: bool,
+    ) -> Result<Id> {
+        let e = match self
+            .amend_order(base, quote, book_owner, order_id, side, price, size)
+            .await
+        {
+            Ok(amend) => return Ok(amend.order_id),
+            Err(e) => e,
+        };
+        // only an amend the book aborted for crossing is moved by cancel and place. Any other
+        // abort, or a post only replacement, would fail the new order the same way once the
+        // old one is cancelled
+        if post_only || !e.is_abort_in(self.laminar()) {
+            return Err(e);
+        }
+        let book = self
+            .fetch_orderbook(base, quote, book_owner)
+            .await?
+            .to_l2(1);
+        let crosses = match side {
+            Side::Bid => book.best_ask().map_or(false, |ask| price >= ask.price),
+            Side::Ask => book.best_bid().map_or(false, |bid| price <= bid.price),
+        };
+        if !crosses {
+            return Err(e);
+        }
+
+        let cancelled = self
+            .cancel_order(base, quote, book_owner, order_id, side)
+            .await?;
+        match self
+            .place_limit_order(
+                base,
+                quote,
+                book_owner,
+                side,
+                price,
+                size,
+                TimeInForce::GoodTillCanceled,
+                post_only,
+            )
+            .await
+        {
+            Ok(placed) => Ok(placed.place.order_id),
+            Err(e) => Err(LaminarError::ReplaceFailed {
+                cancelled: Box::new(cancelled),
+                source: Box::new(e),
+            }),
+        }
+    }
+
+    /// Engage the kill switch of the client's `RiskManager`, blocking new placements, and cancel
+    /// every resting order of this client's account on every book the account ever placed an
+    /// order on, including orders placed by other clients, see `fetch_my_books`. If the books
+    /// can't be fetched, orders are still cancelled on the books known to the `RiskManager`
+    /// and the error is returned.
+    pub async fn kill_switch(&self) -> Result<Vec<CancelOrderEvent>> {
+        let risk = self
+            .risk_manager
+            .as_ref()
+            .ok_or_else(|| LaminarError::InvalidInput("no risk manager set".to_string()))?;
+        risk.engage_kill_switch();
+
+        let mut books = risk.books();
+        let discovered = self.fetch_my_books().await;
+        for book in discovered.iter().flatten() {
+            if !books.contains(book) {
+                books.push(book.clone());
+            }
+        }
+        let cancelled = self.cancel_all_orders(&books).await?;
+        discovered?;
+        Ok(cancelled)
+    }
+
+    /// Fetch the books this client's account placed orders on, from its place order events.
+    pub async fn fetch_my_books(&self) -> Result<Vec<Market>> {
+        let mut book_ids: Vec<Id> = vec![];
+        for e in self.get_dex_events::<PlaceOrderEvent>().await? {
+            if !book_ids.contains(&e.book_id) {
+                book_ids.push(e.book_id);
+            }
+        }
+
+        try_join_all(book_ids.iter().map(|id| self.fetch_market_by_id(id))).await
+    }
+
+    /// Cancel every resting order of this client's account on the given books.
+    /// Every order is attempted, the last error is returned if any cancel failed.
+    ///
+    /// # Arguments:
+    ///
+    /// * `books` - Books to cancel orders on.
+    pub async fn cancel_all_orders(&self, books: &[Market]) -> Result<Vec<CancelOrderEvent>> {
+        let mut cancelled = vec![];
+        let mut error = None;
+        for book in books {
+            let open_orders = match self
+                .fetch_my_open_orders(&book.base, &book.quote, &book.book_owner)
+                .await
+            {
+                Ok(orders) => orders,
+                Err(e) => {
+                    error = Some(e);
+                    continue;
+                }
+            };
+            for o in open_orders {
+                match self
+                    .cancel_order(
+                        &book.base,
+                        &book.quote,
+                        &book.book_owner,
+                        &o.order.id,
+                        o.order.side,
+                    )
+                    .await
+                {
+                    Ok(c) => cancelled.push(c),
+                    Err(e) => error = Some(e),
+                }
+            }
+        }
+
+        match error {
+            Some(e) => Err(e),
+            None => Ok(cancelled),
+        }
+    }
+
+    fn raw_tx(
+        &self,
+        payload: transaction::TransactionPayload,
+        sequence_number: u64,
+        options: TxOptions,
+    ) -> RawTransaction {
+        self.payload_builder().raw_transaction(
+            payload,
+            self.signer.address(),
+            sequence_number,
+            self.chain_id.load(Ordering::SeqCst),
+            options,
+        )
+    }
+
+    /// Simulate a tx without submitting it, returning its estimated gas, the Laminar events
+    /// it would emit and its VM status.
+    /// Failed executions are returned as a `Simulation` with `success` unset rather than as an error.
+    ///
+    /// # Arguments:
+    ///
+    /// * `payload` - Entry function payload to be used in the tx.
+    pub async fn simulate_tx(&self, payload: EntryFunction) -> Result<Simulation> {
+        let tx = self.raw_tx(
+            transaction::TransactionPayload::EntryFunction(payload),
+            self.sequence_number(),
+            self.tx_options,
+        );
+        // the node refuses to simulate transactions carrying a valid signature
+        let signed_tx = self.signer.simulation_transaction(tx)?;
+
+        self.rate_limit(Endpoint::Simulate).await;
+        let ut = self
+            .aptos_client
+            .simulate(&signed_tx)
+            .await
+            .map_err(|e| LaminarError::rest(e, "failed simulating tx"))?
+            .into_inner()
+            .into_iter()
+            .next()
+            .ok_or_else(|| LaminarError::NotFound("simulated transaction".to_string()))?;
+        let events = if ut.info.success {
+            self.parse_events(&ut.events)?
+        } else {
+            vec![]
+        };
+
+        Ok(Simulation {
+            hash: ut.info.hash,
+            success: ut.info.success,
+            vm_status: ut.info.vm_status,
+            gas_used: ut.info.gas_used.0,
+            gas_unit_price: ut.request.gas_unit_price.0,
+            events,
+        })
+    }
+
+    async fn sign_and_submit(
+        &self,
+        payload: EntryFunction,
+        options: TxOptions,
+    ) -> Result<PendingTransaction> {
+        self.sign_and_submit_payload(
+            transaction::TransactionPayload::EntryFunction(payload),
+            options,
+        )
+        .await
+    }
+
+    async fn sign_and_submit_payload(
+        &self,
+        payload: transaction::TransactionPayload,
+        options: TxOptions,
+    ) -> Result<PendingTransaction> {
+        let signed_tx = self.sign_payload(payload, options).await?;
+        self.broadcast(&signed_tx).await
+    }
+
+    // sign a tx with the next local sequence number, handing it out again if signing fails
+    async fn sign_payload(
+        &self,
+        payload: transaction::TransactionPayload,
+        options: TxOptions,
+    ) -> Result<SignedTransaction> {
+        self.check_risk(&payload)?;
+        let seq_num = self.sequence_numbers.take();
+        trace_event!(
+            debug,
+            sequence_number = seq_num,
+            payload = %payload_summary(&payload),
+            max_gas_amount = options.max_gas_amount,
+            gas_unit_price = options.gas_unit_price,
+            "signing tx"
+        );
+        let tx = self.raw_tx(payload, seq_num, options);
+        match self.signer.sign_transaction(tx).await {
+            Ok(signed_tx) => {
+                let hash = signed_tx.clone().committed_hash().into();
+                self.sequence_numbers.bind(seq_num, hash);
+                Ok(signed_tx)
+            }
+            Err(e) => {
+                self.sequence_numbers.release(seq_num, None);
+                Err(e)
+            }
+        }
+    }
+
+    // run the `RiskManager` checks on Laminar payloads, anything else is opaque to the checks
+    fn check_risk(&self, payload: &transaction::TransactionPayload) -> Result<()> {
+        let Some(risk) = &self.risk_manager else {
+            return Ok(());
+        };
+        match payload {
+            transaction::TransactionPayload::EntryFunction(entry) => {
+                if entry.module().address() == &self.laminar {
+                    risk.check(entry)?;
+                }
+                Ok(())
+            }
+            _ => Err(LaminarError::RiskLimit(
+                "payload can't be checked against the risk limits".to_string(),
+            )),
+        }
+    }
+
+    // first submission of a tx signed by `sign_payload`. A tx failing validation was not
+    // accepted, any earlier error may still leave it in the mempool and its number taken.
+    async fn broadcast(&self, signed_tx: &SignedTransaction) -> Result<PendingTransaction> {
+        let res = self.submit_signed_tx(signed_tx).await;
+        if let Err(LaminarError::Rejected(_)) = &res {
+            let hash = signed_tx.clone().committed_hash().into();
+            self.sequence_numbers
+                .release(signed_tx.sequence_number(), Some(hash));
+        }
+        res
+    }
+
+    // submit a signed tx, also to resubmit one that may already be in the mempool
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(sender = %signed_tx.sender(), sequence_number = signed_tx.sequence_number())
+        )
+    )]
+    async fn submit_signed_tx(&self, signed_tx: &SignedTransaction) -> Result<PendingTransaction> {
+        self.rate_limit(Endpoint::Submit).await;
+        match self.aptos_client.submit(signed_tx).await {
+            Ok(res) => {
+                let pending = res.into_inner();
+                trace_event!(debug, hash = %pending.hash, "submitted tx");
+                Ok(pending)
+            }
+            Err(RestError::Api(a)) => match a.error.error_code {
+                AptosErrorCode::InvalidTransactionUpdate | AptosErrorCode::SequenceNumberTooOld => {
+                    let chain_seq_num = self.get_sequence_number().await?;
+                    trace_event!(
+                        warn,
+                        chain_sequence_number = chain_seq_num,
+                        error = %a.error.message,
+                        "sequence number conflict"
+                    );
+                    self.sequence_numbers.sync(chain_seq_num);
+                    Err(LaminarError::SequenceNumber(a.error))
+                }
+                AptosErrorCode::VmError => Err(LaminarError::Rejected(a.error)),
+                _ => Err(LaminarError::rest(
+                    RestError::Api(a),
+                    "failed submitting tx",
+                )),
+            },
+            Err(e) => Err(LaminarError::rest(e, "failed submitting tx")),
+        }
+    }
+
+    fn to_laminar_transaction(&self, tx: Transaction) -> Result<LaminarTransaction> {
+        let Transaction::UserTransaction(ut) = tx else {
+            return Err(LaminarError::Other(anyhow!("not a user transaction")));
+        };
+        if !ut.info.success {
+            return Err(LaminarError::from_vm_status(
+                ut.info.hash,
+                &ut.info.vm_status,
+            ));
+        }
+
+        let events = self.parse_events(&ut.events)?;
+
+        let lt = LaminarTransaction {
+            info: ut.info.clone(),
+            request: ut.request.clone(),
+            events,
+            timestamp: ut.timestamp,
+        };
+        if let Some(risk) = &self.risk_manager {
+            risk.apply_transaction(&self.signer.address(), &lt);
+        }
+        Ok(lt)
+    }
+
+    fn parse_events(&self, events: &[Event]) -> Result<Vec<LaminarEvent>> {
+        events
+            .iter()
+            .filter(
+                |e| matches!(&e.typ, MoveType::Struct(s) if s.address.inner() == self.laminar()),
+            )
+            .map(|e| {
+                let typ = e.typ.to_string();
+                LaminarEvent::from_move_event(&typ, e.data.clone())
+                    .map_err(|err| LaminarError::deserialization(err, typ))
+            })
+            .collect()
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(tx.hash = %pending.hash), err)
+    )]
+    async fn wait_for_laminar_tx(
+        &self,
+        pending: &PendingTransaction,
+    ) -> Result<LaminarTransaction> {
+        let res = self
+            .wait_for_tx(pending.hash, pending.request.expiration_timestamp_secs.0)
+            .await
+            .and_then(|tx| self.to_laminar_transaction(tx));
+        if let Ok(_) | Err(LaminarError::MoveAbort { .. } | LaminarError::ExecutionFailure { .. }) =
+            &res
+        {
+            // the tx was executed, its sequence number and every one below it are used
+            if pending.request.sender.inner() == &self.address() {
+                self.sequence_numbers
+                    .sync(pending.request.sequence_number.0 + 1);
+            }
+        }
+        let lt = res?;
+        trace_event!(
+            debug,
+            version = lt.info.version.0,
+            gas_used = lt.info.gas_used.0,
+            events = lt.events.len(),
+            "tx committed"
+        );
+        Ok(lt)
+    }
+
+    // poll for a submitted tx until it is committed or expired, every poll takes a permit from
+    // the rate limiter
+    async fn wait_for_tx(
+        &self,
+        hash: HashValue,
+        expiration_timestamp_secs: u64,
+    ) -> Result<Transaction> {
+        let failed = |e| LaminarError::rest(e, format!("failed waiting for tx: {}", hash));
+        let start = Instant::now();
+        loop {
+            self.rate_limit(Endpoint::Transactions).await;
+            let ledger_timestamp_usecs =
+                match self.aptos_client.get_transaction_by_hash(hash.into()).await {
+                    Ok(res) => {
+                        let timestamp_usecs = res.state().timestamp_usecs;
+                        match res.into_inner() {
+                            Transaction::PendingTransaction(_) => timestamp_usecs,
+                            tx => return Ok(tx),
+                        }
+                    }
+                    Err(RestError::Api(a))
+                        if a.error.error_code == AptosErrorCode::TransactionNotFound =>
+                    {
+                        a.state.map_or(0, |s| s.timestamp_usecs)
+                    }
+                    Err(e) => return Err(failed(e)),
+                };
+            if ledger_timestamp_usecs >= expiration_timestamp_secs * 1_000_000 {
+                return Err(failed(RestError::Unknown(anyhow!("transaction expired"))));
+            }
+            if start.elapsed() >= TX_WAIT_TIMEOUT {
+                return Err(failed(RestError::Unknown(anyhow!(
+                    "transaction not committed in time"
+                ))));
+            }
+            sleep(TX_POLL_INTERVAL).await;
+        }
+    }
 
-    /// Create payload for placing a market order.
+    /// Utility method for building and submitting a tx with the client's default `TxOptions`.
     ///
     /// # Arguments:
     ///
-    /// * `base` - Aptos `TypeTag` of the orderbook base coin.
-    /// * `quote` - Aptos `TypeTag` of the orderbook quote coin.
-    /// * `book_owner` - Address of the account that owns the `OrderBook`.
-    /// * `side` - `Side`: Bid or Ask.
-    /// * `size` - U64 size of market order.
-    pub fn place_market_order_payload(
+    /// * `payload` - Entry function payload to be used in the tx.
+    pub async fn build_and_submit_tx(&self, payload: EntryFunction) -> Result<LaminarTransaction> {
+        self.build_and_submit_tx_with_options(payload, self.tx_options)
+            .await
+    }
+
+    /// Build and submit a tx with the given `TxOptions`.
+    ///
+    /// The payload is signed once and resubmitted until its outcome is known, see `submit_once`.
+    /// It is only signed again, up to `max_retries` times, once the previous tx is provably
+    /// absent, so retrying can't commit it twice.
+    ///
+    /// # Arguments:
+    ///
+    /// * `payload` - Entry function payload to be used in the tx.
+    /// * `options` - Gas, expiration and retry settings for this tx.
+    pub async fn build_and_submit_tx_with_options(
         &self,
-        base: &TypeTag,
-        quote: &TypeTag,
-        book_owner: &AccountAddress,
-        side: Side,
-        size: u64,
-    ) -> Result<EntryFunction> {
-        let entry = EntryFunction::new(
-            ModuleId::new(self.laminar, ident_str!("book").to_owned()),
-            ident_str!("place_market_order").to_owned(),
-            vec![base.clone(), quote.clone()],
-            vec![
-                bcs::to_bytes(book_owner)?,
-                bcs::to_bytes(&side)?,
-                bcs::to_bytes(&size)?,
-            ],
-        );
+        payload: EntryFunction,
+        options: TxOptions,
+    ) -> Result<LaminarTransaction> {
+        let attempts = options.max_retries as u32 + 1;
+        for _i in 0..attempts {
+            let submission = self.sign_submission(payload.clone(), options).await?;
+            if let SubmissionStatus::Committed(lt) = self.submit_once(&submission).await? {
+                trace_record!("tx.hash", lt.info.hash);
+                return Ok(lt);
+            }
+            trace_event!(
+                warn,
+                attempt = _i + 1,
+                attempts,
+                hash = %submission.hash,
+                "tx absent, signing again"
+            );
+        }
 
-        Ok(entry)
+        Err(LaminarError::Other(anyhow!("failed submitting tx")))
     }
 
-    /// Create payload for amending an order.
+    // the tx is committed whatever happens here, so a failure to record it is logged rather
+    // than returned, where it would be taken for a failed submission and retried
+    fn record_audit(&self, lt: &LaminarTransaction) {
+        if let Some(log) = self.audit_log().as_mut() {
+            let operator = self.operator.as_deref().unwrap_or_default();
+            if let Err(_e) = log.record(AuditRecord::from_transaction(operator, lt)) {
+                trace_event!(error, hash = %lt.info.hash, error = %_e, "failed recording audit");
+            }
+        }
+    }
+
+    /// Sign and submit a tx without waiting for it to be committed.
+    /// Every call takes the next local sequence number, so several txs can be in flight at
+    /// once. Confirm them with `wait_for_pipelined`.
     ///
     /// # Arguments:
     ///
-    /// * `base` - Aptos `TypeTag` of the orderbook base coin.
-    /// * `quote` - Aptos `TypeTag` of the orderbook quote coin.
-    /// * `book_owner` - Address of the account that owns the `OrderBook`.
-    /// * `order_id` - ID of order to amend.
-    /// * `side` - `OrderSide`: Bid or Ask.
-    /// * `price` - Price to update, provide current price if no amendment needed.
-    /// * `size` - Size to update, provide current size if no amendment needed.
-    #[allow(clippy::too_many_arguments)]
-    pub fn amend_order_payload(
+    /// * `payload` - Entry function payload to be used in the tx.
+    pub async fn submit_pipelined(&self, payload: EntryFunction) -> Result<PendingTransaction> {
+        self.sign_and_submit(payload, self.tx_options).await
+    }
+
+    /// Wait for txs submitted with `submit_pipelined`, returning one result per tx in order.
+    ///
+    /// A tx that was dropped by the mempool instead of being committed leaves a gap that blocks
+    /// every later sequence number. Once such a tx is provably expired its sequence number is
+    /// handed out to the next tx, txs still in flight keep theirs.
+    ///
+    /// # Arguments:
+    ///
+    /// * `pending` - Txs returned by `submit_pipelined`.
+    pub async fn wait_for_pipelined(
         &self,
-        base: &TypeTag,
-        quote: &TypeTag,
-        book_owner: &AccountAddress,
-        order_id: &Id,
-        side: Side,
-        price: u64,
-        size: u64,
-    ) -> Result<EntryFunction> {
-        let entry = EntryFunction::new(
-            ModuleId::new(self.laminar, ident_str!("book").to_owned()),
-            ident_str!("amend_order").to_owned(),
-            vec![base.clone(), quote.clone()],
-            vec![
-                bcs::to_bytes(book_owner)?,
-                bcs::to_bytes(&order_id.creation_num.0)?,
-                bcs::to_bytes(&side)?,
-                bcs::to_bytes(&price)?,
-                bcs::to_bytes(&size)?,
-            ],
-        );
+        pending: &[PendingTransaction],
+    ) -> Result<Vec<Result<LaminarTransaction>>> {
+        let results = join_all(pending.iter().map(|p| self.wait_for_laminar_tx(p))).await;
+
+        for (pending, res) in pending.iter().zip(&results) {
+            match res {
+                Ok(lt) => self.record_audit(lt),
+                // executed txs consume their sequence number even if they failed
+                Err(LaminarError::MoveAbort { .. } | LaminarError::ExecutionFailure { .. }) => {}
+                Err(_) => {
+                    let _status = self
+                        .tx_status(
+                            pending.hash,
+                            pending.request.sequence_number.0,
+                            pending.request.expiration_timestamp_secs.0,
+                        )
+                        .await;
+                    trace_event!(warn, hash = %pending.hash, status = ?_status, "tx dropped");
+                }
+            }
+        }
 
-        Ok(entry)
+        Ok(results)
     }
 
-    /// Create payload for canceling an order.
+    /// Submit several txs back-to-back with consecutive sequence numbers and wait for all of
+    /// them, instead of waiting for each tx to be committed before submitting the next one.
+    /// Payloads that fail to submit are reported in place and their sequence numbers are reused.
     ///
     /// # Arguments:
     ///
-    /// * `base` - Aptos `TypeTag` of the orderbook base coin.
-    /// * `quote` - Aptos `TypeTag` of the orderbook quote coin.
-    /// * `book_owner` - Address of the account that owns the `OrderBook`.
-    /// * `order_id` - ID of order to cancel.
-    /// * `side` - `OrderSide`: Bid or Ask.
-    pub fn cancel_order_payload(
+    /// * `payloads` - Entry function payloads, submitted in order.
+    pub async fn build_and_submit_txs(
         &self,
-        base: &TypeTag,
-        quote: &TypeTag,
-        book_owner: &AccountAddress,
-        order_id: &Id,
-        side: Side,
-    ) -> Result<EntryFunction> {
-        let entry = EntryFunction::new(
-            ModuleId::new(self.laminar, ident_str!("book").to_owned()),
-            ident_str!("cancel_order").to_owned(),
-            vec![base.clone(), quote.clone()],
-            vec![
-                bcs::to_bytes(book_owner)?,
-                bcs::to_bytes(&order_id.creation_num.0)?,
-                bcs::to_bytes(&side)?,
-            ],
-        );
+        payloads: Vec<EntryFunction>,
+    ) -> Result<Vec<Result<LaminarTransaction>>> {
+        let mut failed = vec![];
+        let mut pending = vec![];
+        for payload in payloads {
+            match self.submit_pipelined(payload).await {
+                Ok(p) => {
+                    failed.push(None);
+                    pending.push(p);
+                }
+                Err(e) => failed.push(Some(e)),
+            }
+        }
 
-        Ok(entry)
+        let mut confirmed = self.wait_for_pipelined(&pending).await?.into_iter();
+        Ok(failed
+            .into_iter()
+            .map(|f| match f {
+                Some(e) => Err(e),
+                None => confirmed
+                    .next()
+                    .unwrap_or_else(|| Err(LaminarError::NotFound("pipelined tx".to_string()))),
+            })
+            .collect())
     }
 
-    async fn submit_tx(&mut self, payload: EntryFunction) -> Result<LaminarTransaction> {
-        let addr = self.account.address();
-        let tx = TransactionFactory::new(self.chain_id)
-            .entry_function(payload)
-            .sender(addr)
-            .sequence_number(self.account.sequence_number())
-            .max_gas_amount(1_000_000)
-            .build();
-
-        let signed_tx = self.account.sign_transaction(tx);
-        let pending = match self.aptos_client.submit(&signed_tx).await {
-            Ok(res) => res.into_inner(),
-            Err(RestError::Api(a)) => {
-                return match a.error.error_code {
-                    AptosErrorCode::InvalidTransactionUpdate
-                    | AptosErrorCode::SequenceNumberTooOld
-                    | AptosErrorCode::VmError => {
-                        let seq_num = self.get_sequence_number().await?;
-                        let acc_seq_num = self.account.sequence_number_mut();
-                        *acc_seq_num = max(seq_num, *acc_seq_num + 1);
-                        Err(anyhow!(a))
-                    }
-                    _ => Err(anyhow!(a)),
+    /// Build and submit a tx, giving up once `budget` has elapsed.
+    ///
+    /// The payload is signed once and the same tx is resubmitted until the deadline, see
+    /// `submit_once`, and only signed again if it is provably absent. A tx that was not
+    /// confirmed in time is kept in `pending_transactions` so it can be reconciled later
+    /// with `reconcile_pending`, and `LaminarError::DeadlineExceeded` carrying its hash is returned.
+    ///
+    /// # Arguments:
+    ///
+    /// * `payload` - Entry function payload to be used in the tx.
+    /// * `budget` - Maximum time to spend submitting and confirming the tx.
+    pub async fn build_and_submit_tx_with_deadline(
+        &self,
+        payload: EntryFunction,
+        budget: Duration,
+    ) -> Result<LaminarTransaction> {
+        let deadline = Instant::now() + budget;
+        loop {
+            let submission = self
+                .sign_submission(payload.clone(), self.tx_options)
+                .await?;
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match timeout(remaining, self.submit_once(&submission)).await {
+                Ok(Ok(SubmissionStatus::Committed(lt))) => return Ok(lt),
+                Ok(Ok(_)) if Instant::now() < deadline => {
+                    trace_event!(warn, hash = %submission.hash, "tx absent, signing again");
+                }
+                Ok(Ok(_)) => return Err(LaminarError::DeadlineExceeded { hash: None }),
+                Ok(Err(e @ LaminarError::Unconfirmed(_))) => {
+                    self.pending_lock().push(submission);
+                    return Err(e);
+                }
+                Ok(Err(e)) => return Err(e),
+                Err(_) => {
+                    let hash = submission.hash;
+                    self.pending_lock().push(submission);
+                    return Err(LaminarError::DeadlineExceeded { hash: Some(hash) });
                 }
             }
-            Err(e) => return Err(anyhow!(e)),
-        };
+        }
+    }
 
-        let Transaction::UserTransaction(ut) = self.aptos_client.wait_for_transaction(&pending).await?.into_inner() else {
-            return Err(anyhow!("not a user transaction"))
-        };
+    fn pending_lock(&self) -> MutexGuard<'_, Vec<Submission>> {
+        self.pending.lock().expect("pending lock poisoned")
+    }
 
-        let events = ut
-            .events
-            .iter()
-            .filter(
-                |e| matches!(&e.typ, MoveType::Struct(s) if s.address.inner() == self.laminar()),
-            )
-            .map(|e| serde_json::from_value(e.data.clone()).context("failed deserializing event"))
-            .collect::<Result<Vec<LaminarEvent>>>()?;
+    /// Transactions that were submitted but not confirmed before their deadline.
+    pub fn pending_transactions(&self) -> Vec<Submission> {
+        self.pending_lock().clone()
+    }
 
-        Ok(LaminarTransaction {
-            info: ut.info.clone(),
-            request: ut.request.clone(),
-            events,
-            timestamp: ut.timestamp,
-        })
+    /// Check the outcome of transactions left pending by `build_and_submit_tx_with_deadline`.
+    /// Committed and provably absent transactions are removed from the pending list,
+    /// successful ones are returned.
+    ///
+    /// # Arguments:
+    ///
+    /// * `cancel_placed` - Cancel orders placed by transactions that landed after their deadline.
+    pub async fn reconcile_pending(&self, cancel_placed: bool) -> Result<Vec<LaminarTransaction>> {
+        let mut committed = vec![];
+        let mut still_pending = vec![];
+        let pending = std::mem::take(&mut *self.pending_lock());
+        for submission in pending {
+            match self.submission_status(&submission).await {
+                Ok(SubmissionStatus::Committed(lt)) => committed.push(lt),
+                Ok(SubmissionStatus::Pending) | Err(_) => still_pending.push(submission),
+                Ok(_) => {}
+            }
+        }
+        self.pending_lock().extend(still_pending);
+
+        if cancel_placed {
+            for tx in &committed {
+                for e in &tx.events {
+                    let LaminarEvent::PlaceOrder(p) = e else {
+                        continue;
+                    };
+                    let Some([base, quote]) = tx.type_args() else {
+                        continue;
+                    };
+                    let book_owner = *p.book_id.addr.inner();
+                    let payload =
+                        self.cancel_order_payload(&base, &quote, &book_owner, &p.order_id, p.side)?;
+                    // the order may have been filled or cancelled in the meantime
+                    let _ = self.build_and_submit_tx(payload).await;
+                }
+            }
+        }
+
+        Ok(committed)
     }
 
-    /// Utility method for building and submitting a tx
+    /// Submit up to `limit` actions from a `SubmissionQueue` in priority order, one at a time.
+    /// Each action is only popped once the rate limiter allows a submission, so actions wait
+    /// in the queue where they can still be cancelled, and cancels enqueued in the meantime,
+    /// e.g. by a kill switch, are submitted first.
     ///
     /// # Arguments:
     ///
-    /// * `payload` - Entry function payload to be used in the tx.
-    pub async fn build_and_submit_tx(
-        &mut self,
-        payload: EntryFunction,
-    ) -> Result<LaminarTransaction> {
-        for i in 0..SUBMIT_ATTEMPTS {
-            match self.submit_tx(payload.clone()).await {
-                Ok(lt) => return Ok(lt),
-                Err(e) if i == SUBMIT_ATTEMPTS - 1 => return Err(e),
-                _ => continue,
+    /// * `queue` - `SubmissionQueue` to drain, shared with the tasks enqueueing actions.
+    /// * `limit` - Maximum number of actions to submit in this call.
+    pub async fn submit_queued(
+        &self,
+        queue: &Mutex<SubmissionQueue>,
+        limit: usize,
+    ) -> Vec<(QueuedAction, Result<LaminarTransaction>)> {
+        let mut results = vec![];
+        while results.len() < limit {
+            if let Some(rate_limiter) = &self.rate_limiter {
+                rate_limiter.ready(Endpoint::Submit).await;
             }
+            let action = queue.lock().expect("submission queue lock poisoned").pop();
+            let Some(action) = action else {
+                break;
+            };
+            let res = self.build_and_submit_tx(action.payload.clone()).await;
+            results.push((action, res));
         }
 
-        Err(anyhow!("failed submitting tx"))
+        results
     }
 
-    async fn get_dex_events<'a, T>(&self) -> Result<Vec<T>>
+    /// Fetch one page of this client's account events, starting at sequence number `cursor`.
+    /// Returns the events and the cursor of the next page, `None` once the handle is exhausted.
+    ///
+    /// # Arguments:
+    ///
+    /// * `cursor` - Sequence number of the first event to fetch.
+    /// * `limit` - Maximum number of events in the page.
+    pub async fn fetch_events_page<'a, T>(
+        &self,
+        cursor: u64,
+        limit: u16,
+    ) -> Result<(Vec<T>, Option<u64>)>
+    where
+        T: EventStoreField<'a> + DeserializeOwned,
+    {
+        self.fetch_event_envelopes_page(cursor, limit)
+            .await
+            .map(|(events, next)| (events.into_iter().map(|e| e.event).collect(), next))
+    }
+
+    /// Same as `fetch_events_page`, with each event wrapped in a `LaminarEventEnvelope`
+    /// carrying its event key, sequence number and ledger version.
+    ///
+    /// # Arguments:
+    ///
+    /// * `cursor` - Sequence number of the first event to fetch.
+    /// * `limit` - Maximum number of events in the page.
+    pub async fn fetch_event_envelopes_page<'a, T>(
+        &self,
+        cursor: u64,
+        limit: u16,
+    ) -> Result<(Vec<LaminarEventEnvelope<T>>, Option<u64>)>
+    where
+        T: EventStoreField<'a> + DeserializeOwned,
+    {
+        self.fetch_account_event_envelopes_page(self.signer.address(), cursor, limit)
+            .await
+    }
+
+    /// Same as `fetch_event_envelopes_page`, for the events of any account's `OrderBookStore`.
+    ///
+    /// # Arguments:
+    ///
+    /// * `account` - Address of the account whose events to fetch.
+    /// * `cursor` - Sequence number of the first event to fetch.
+    /// * `limit` - Maximum number of events in the page.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip(self),
+            fields(field = T::event_store_field()),
+            err
+        )
+    )]
+    pub async fn fetch_account_event_envelopes_page<'a, T>(
+        &self,
+        account: AccountAddress,
+        cursor: u64,
+        limit: u16,
+    ) -> Result<(Vec<LaminarEventEnvelope<T>>, Option<u64>)>
     where
         T: EventStoreField<'a> + DeserializeOwned,
     {
         let event_store = format!("{}::book::OrderBookStore", self.laminar.to_hex_literal(),);
-        self.aptos_client
+        self.rate_limit(Endpoint::Events).await;
+        let events = self
+            .aptos_client
             .get_account_events(
-                self.account.address(),
+                account,
                 &event_store,
                 T::event_store_field(),
-                None,
-                None,
+                Some(cursor),
+                Some(limit),
             )
             .await
-            .with_context(|| {
-                format!(
-                    "failed getting event type: {} for account: {}",
-                    T::event_store_field(),
-                    self.account.address()
+            .map_err(|e| {
+                LaminarError::rest(
+                    e,
+                    format!(
+                        "failed getting event type: {} for account: {}",
+                        T::event_store_field(),
+                        account
+                    ),
                 )
             })?
-            .into_inner()
+            .into_inner();
+
+        let next = match events.last() {
+            Some(last) if events.len() >= limit as usize => Some(last.sequence_number.0 + 1),
+            _ => None,
+        };
+        let events = events
             .into_iter()
-            .map(|e| serde_json::from_value(e.data).context("failed deserializing event"))
-            .collect()
+            .map(|e| {
+                serde_json::from_value(e.data)
+                    .map(|event| LaminarEventEnvelope {
+                        key: EventKey {
+                            account_address: e.guid.account_address.into(),
+                            creation_number: e.guid.creation_number.0,
+                        },
+                        sequence_number: e.sequence_number.0,
+                        version: e.version.0,
+                        event,
+                    })
+                    .map_err(|err| LaminarError::deserialization(err, T::event_store_field()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        trace_event!(debug, events = events.len(), next = ?next, "fetched events page");
+        Ok((events, next))
+    }
+
+    /// Fetch this client's account events with sequence numbers in `[start, end)`.
+    ///
+    /// # Arguments:
+    ///
+    /// * `start` - Sequence number of the first event, inclusive.
+    /// * `end` - Sequence number to stop at, exclusive.
+    pub async fn fetch_events_in_range<'a, T>(&self, start: u64, end: u64) -> Result<Vec<T>>
+    where
+        T: EventStoreField<'a> + DeserializeOwned,
+    {
+        let mut res = vec![];
+        let mut cursor = Some(start);
+        while let Some(c) = cursor.filter(|c| *c < end) {
+            let limit = (end - c).min(EVENT_PAGE_SIZE as u64) as u16;
+            let (events, next) = self.fetch_events_page::<T>(c, limit).await?;
+            res.extend(events);
+            cursor = next;
+        }
+
+        Ok(res)
+    }
+
+    /// Fetch this client's account events matching an `EventQuery`.
+    /// Pages are fetched within the query's range until it is exhausted or the query's
+    /// limit is reached.
+    ///
+    /// # Arguments:
+    ///
+    /// * `query` - `EventQuery` selecting the events to return.
+    pub async fn query_events<'a, E>(&self, query: &EventQuery<E>) -> Result<Vec<E>>
+    where
+        E: EventStoreField<'a> + EventIds + DeserializeOwned,
+    {
+        self.query_event_envelopes(query)
+            .await
+            .map(|events| events.into_iter().map(|e| e.event).collect())
     }
 
-    async fn get_filtered_dex_events<'a, E, P>(&self, predicate: P) -> Result<Vec<E>>
+    /// Same as `query_events`, with each event wrapped in a `LaminarEventEnvelope`.
+    ///
+    /// # Arguments:
+    ///
+    /// * `query` - `EventQuery` selecting the events to return.
+    pub async fn query_event_envelopes<'a, E>(
+        &self,
+        query: &EventQuery<E>,
+    ) -> Result<Vec<LaminarEventEnvelope<E>>>
     where
-        E: EventStoreField<'a> + DeserializeOwned + Clone + Send,
-        P: Send + Fn(&E) -> bool,
+        E: EventStoreField<'a> + EventIds + DeserializeOwned,
     {
-        let res = self.get_dex_events::<E>().await?;
-        let mut result: Vec<E> = vec![];
-        for e in &res {
-            if predicate(e) {
-                result.push(e.to_owned());
+        let max_results = query.max_results().unwrap_or(usize::MAX);
+        let mut res = vec![];
+        let mut cursor = Some(query.start);
+        while let Some(c) = cursor.filter(|c| *c < query.end) {
+            let limit = (query.end - c).min(EVENT_PAGE_SIZE as u64) as u16;
+            let (events, next) = self.fetch_event_envelopes_page::<E>(c, limit).await?;
+            let max_version = query.max_version.unwrap_or(u64::MAX);
+            // events on a handle are emitted in version order
+            let past_version = events.iter().any(|e| e.version > max_version);
+            res.extend(
+                events
+                    .into_iter()
+                    .filter(|e| e.version <= max_version && query.matches(&e.event)),
+            );
+            if res.len() >= max_results {
+                res.truncate(max_results);
+                break;
             }
+            if past_version {
+                break;
+            }
+            cursor = next;
         }
 
         Ok(res)
     }
 
+    async fn get_dex_events<'a, T>(&self) -> Result<Vec<T>>
+    where
+        T: EventStoreField<'a> + DeserializeOwned,
+    {
+        self.fetch_events_in_range(0, u64::MAX).await
+    }
+
     /// Fetch all order books.
     pub async fn fetch_order_books(&self) -> Result<Vec<CreateOrderBookEvent>> {
-        let filter = |_e: &CreateOrderBookEvent| true;
-        self.get_filtered_dex_events(filter).await
+        self.get_dex_events().await
+    }
+
+    /// Fetch all order books created by `book_owner`, refreshing the cached books of the owner.
+    ///
+    /// # Arguments:
+    ///
+    /// * `book_owner` - Address of the account that created the books.
+    pub async fn fetch_order_books_of(
+        &self,
+        book_owner: AccountAddress,
+    ) -> Result<Vec<CreateOrderBookEvent>> {
+        let mut res = vec![];
+        let mut cursor = Some(0);
+        while let Some(c) = cursor {
+            let (events, next) = self
+                .fetch_account_event_envelopes_page::<CreateOrderBookEvent>(
+                    book_owner,
+                    c,
+                    EVENT_PAGE_SIZE,
+                )
+                .await?;
+            res.extend(events.into_iter().map(|e| e.event));
+            cursor = next;
+        }
+        self.cache.order_books.insert(book_owner, res.clone());
+
+        Ok(res)
     }
 
     /// Fetch all place order events for this client's account for a given book.
@@ -629,8 +2574,8 @@ impl LaminarClient {
     ///
     /// * `book_id` - `OrderBook` Id.
     pub async fn fetch_all_place_events(&self, book_id: &Id) -> Result<Vec<PlaceOrderEvent>> {
-        let filter = |e: &PlaceOrderEvent| &e.book_id == book_id;
-        self.get_filtered_dex_events(filter).await
+        self.query_events(&EventQuery::new().for_book(book_id))
+            .await
     }
 
     /// Fetch place order event for a given order ID.
@@ -639,12 +2584,10 @@ impl LaminarClient {
     ///
     /// * `order_id` - ID of order to fetch place event for.
     pub async fn get_place_event(&self, order_id: &Id) -> Result<PlaceOrderEvent> {
-        self.get_dex_events::<PlaceOrderEvent>()
+        self.query_events(&EventQuery::new().for_order(order_id).limit(1))
             .await?
-            .iter()
-            .find(|e| order_id == &e.order_id)
-            .cloned()
-            .context("order not found")
+            .pop()
+            .ok_or_else(|| LaminarError::NotFound(format!("order: {}", order_id)))
     }
 
     /// Fetch all amend order events for this client's account for a given book.
@@ -653,13 +2596,13 @@ impl LaminarClient {
     ///
     /// * `book_id` - `OrderBook` Id.
     pub async fn fetch_all_amend_events(&self, book_id: &Id) -> Result<Vec<AmendOrderEvent>> {
-        let filter = |e: &AmendOrderEvent| &e.book_id == book_id;
-        self.get_filtered_dex_events(filter).await
+        self.query_events(&EventQuery::new().for_book(book_id))
+            .await
     }
 
     async fn get_amends_internal(&self, order_id: &Id) -> Result<Vec<AmendOrderEvent>> {
-        let filter = |e: &AmendOrderEvent| order_id == &e.order_id;
-        self.get_filtered_dex_events(filter).await
+        self.query_events(&EventQuery::new().for_order(order_id))
+            .await
     }
 
     /// Fetch amend order events for a given order ID.
@@ -680,8 +2623,8 @@ impl LaminarClient {
     ///
     /// * `book_id` - `OrderBook` Id.
     pub async fn fetch_all_cancel_events(&self, book_id: &Id) -> Result<Vec<CancelOrderEvent>> {
-        let filter = |e: &CancelOrderEvent| &e.book_id == book_id;
-        self.get_filtered_dex_events(filter).await
+        self.query_events(&EventQuery::new().for_book(book_id))
+            .await
     }
 
     /// Fetch cancel order event for a given order ID.
@@ -690,13 +2633,9 @@ impl LaminarClient {
     ///
     /// * `order_id` - ID of order to fetch cancel event for.
     pub async fn get_cancel_event(&self, order_id: &Id) -> Result<Option<CancelOrderEvent>> {
-        let res = self
-            .get_dex_events::<CancelOrderEvent>()
-            .await?
-            .iter()
-            .find(|e| order_id == &e.order_id)
-            .cloned();
-        Ok(res)
+        self.query_events(&EventQuery::new().for_order(order_id).limit(1))
+            .await
+            .map(|mut res| res.pop())
     }
 
     /// Fetch all fill events for this client's account for all orders
@@ -705,13 +2644,13 @@ impl LaminarClient {
     ///
     /// * `book_id` - `OrderBook` Id.
     pub async fn fetch_all_fill_events(&self, book_id: &Id) -> Result<Vec<FillEvent>> {
-        let filter = |e: &FillEvent| &e.book_id == book_id;
-        self.get_filtered_dex_events(filter).await
+        self.query_events(&EventQuery::new().for_book(book_id))
+            .await
     }
 
     async fn get_fills_internal(&self, order_id: &Id) -> Result<Vec<FillEvent>> {
-        let filter = |e: &FillEvent| order_id == &e.order_id;
-        self.get_filtered_dex_events(filter).await
+        self.query_events(&EventQuery::new().for_order(order_id))
+            .await
     }
 
     /// Fetch fill events for a given order ID.
@@ -732,28 +2671,93 @@ impl LaminarClient {
     ///
     /// * `order_id` - ID of order to fetch fill events for.
     pub async fn get_order(&self, order_id: &Id) -> Result<Order> {
-        let place_event = self.get_place_event(order_id).await?;
-        let amend_events = self.get_amends_internal(order_id).await?;
-        let cancel_event = self.get_cancel_event(order_id).await?;
-        let fills = self.get_fills_internal(order_id).await?;
+        self.get_orders(std::slice::from_ref(order_id))
+            .await?
+            .pop()
+            .ok_or_else(|| LaminarError::NotFound(format!("order: {}", order_id)))
+    }
+
+    /// Fetch several orders of this client's account at once.
+    /// Each event stream is fetched a single time and grouped by order id, instead of
+    /// four round trips per order. Orders are returned in the order of `order_ids`.
+    ///
+    /// # Arguments:
+    ///
+    /// * `order_ids` - IDs of orders to fetch.
+    pub async fn get_orders(&self, order_ids: &[Id]) -> Result<Vec<Order>> {
+        let (places, amends, cancels, fills) = try_join!(
+            self.get_dex_events::<PlaceOrderEvent>(),
+            self.get_dex_events::<AmendOrderEvent>(),
+            self.get_dex_events::<CancelOrderEvent>(),
+            self.get_dex_events::<FillEvent>(),
+        )?;
+
+        let mut histories = order_ids
+            .iter()
+            .map(|id| (id.clone(), OrderHistory::default()))
+            .collect::<HashMap<_, _>>();
+        for e in places {
+            if let Some(h) = histories.get_mut(&e.order_id) {
+                h.place = Some(e);
+            }
+        }
+        for e in amends {
+            if let Some(h) = histories.get_mut(&e.order_id) {
+                h.amends.push(e);
+            }
+        }
+        for e in cancels {
+            if let Some(h) = histories.get_mut(&e.order_id) {
+                h.cancel.get_or_insert(e);
+            }
+        }
+        for e in fills {
+            if let Some(h) = histories.get_mut(&e.order_id) {
+                h.fills.push(e);
+            }
+        }
+
+        order_ids
+            .iter()
+            .map(|id| {
+                let history = histories.remove(id).unwrap_or_default();
+                history
+                    .into_order(id)
+                    .ok_or_else(|| LaminarError::NotFound(format!("order: {}", id)))
+            })
+            .collect()
+    }
+}
+
+/// Events of a single order, collected from the account's event streams.
+#[derive(Default)]
+struct OrderHistory {
+    place: Option<PlaceOrderEvent>,
+    amends: Vec<AmendOrderEvent>,
+    cancel: Option<CancelOrderEvent>,
+    fills: Vec<FillEvent>,
+}
 
-        let (price, size) = match amend_events.last() {
+impl OrderHistory {
+    fn into_order(self, order_id: &Id) -> Option<Order> {
+        let place_event = self.place?;
+        let (price, size) = match self.amends.last() {
             Some(a) => (a.price, a.size),
             None => (place_event.price, place_event.size),
         };
 
-        let state = if !matches!(place_event.time_in_force, TimeInForce::GoodTillCanceled)
-            || cancel_event.is_some()
-        {
-            State::Closed
-        } else if !fills.is_empty() {
-            State::PartiallyFilled
-        } else {
-            State::Open
+        let remaining_size = self.fills.last().map_or(size, |f| f.remaining_size);
+        let state = match &self.cancel {
+            Some(cancel) => cancel.order_state(place_event.time_in_force),
+            None if remaining_size == 0 => State::Filled,
+            // IOC and FOK orders never rest on the book
+            None if !matches!(place_event.time_in_force, TimeInForce::GoodTillCanceled) => {
+                State::Expired
+            }
+            None if !self.fills.is_empty() => State::PartiallyFilled,
+            None => State::Open,
         };
-
-        let remaining_size = fills.last().map_or(0, |f| f.remaining_size);
-        let o = Order {
+        Some(Order {
             id: order_id.clone(),
             side: place_event.side,
             price,
@@ -761,10 +2765,31 @@ impl LaminarClient {
             post_only: place_event.post_only,
             remaining_size,
             state,
-            fills,
-        };
+            fills: self.fills,
+        })
+    }
+}
+
+fn expect_event<T>(
+    tx: &LaminarTransaction,
+    what: &str,
+    f: impl Fn(&LaminarEvent) -> Option<T>,
+) -> Result<T> {
+    tx.events
+        .iter()
+        .find_map(f)
+        .ok_or_else(|| LaminarError::NotFound(format!("{} event in tx: {}", what, tx.info.hash)))
+}
 
-        Ok(o)
+// called function of a payload for logs, e.g. `0x1::book::place_limit_order`
+#[cfg(feature = "tracing")]
+fn payload_summary(payload: &transaction::TransactionPayload) -> String {
+    match payload {
+        transaction::TransactionPayload::EntryFunction(f) => {
+            format!("{}::{}", f.module(), f.function())
+        }
+        transaction::TransactionPayload::Script(_) => "script".to_string(),
+        _ => "other".to_string(),
     }
 }
 
@@ -0,0 +1,311 @@
+use crate::backfill::{Backfill, VersionedEvent};
+use crate::checkpoint::{Checkpoint, EventCursor};
+use crate::error::Result;
+#[cfg(feature = "journal")]
+use crate::journal::EventJournal;
+use crate::types::delta::LevelChange;
+use crate::types::events::{LaminarEvent, LaminarEventEnvelope};
+use crate::types::order::{Id, Order, OrderBook, Side};
+use crate::LaminarClient;
+use aptos_sdk::move_types::language_storage::TypeTag;
+use aptos_sdk::types::account_address::AccountAddress;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Change of the total resting size at a price level. A `size` of 0 means the level was removed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct LevelUpdate {
+    pub side: Side,
+    pub price: u64,
+    pub size: u64,
+}
+
+impl From<&LevelChange> for LevelUpdate {
+    fn from(change: &LevelChange) -> Self {
+        Self {
+            side: change.side,
+            price: change.price,
+            size: change.new_size,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum MarketDataUpdate {
+    Event(LaminarEvent),
+    Levels(Vec<LevelUpdate>),
+    /// Event queries failed and the feed switched to snapshot polling.
+    Degraded {
+        reason: String,
+    },
+    /// Event queries succeeded again and the feed switched back to events.
+    Recovered,
+}
+
+/// Pages of transactions scanned by one poll, so a feed far behind the ledger catches up over
+/// several polls instead of blocking one.
+const MAX_PAGES_PER_POLL: usize = 10;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FeedMode {
+    Events,
+    Snapshots,
+}
+
+/// Market data feed for a single `OrderBook`.
+///
+/// The feed delivers the Laminar events of every account trading the book, read from the
+/// committed transactions like a `Backfill`, while the transactions API works. Once a scan
+/// fails it degrades to polling `fetch_orderbook` and synthesizing `LevelUpdate`s from
+/// consecutive snapshots, periodically retrying the scan. The caller drives the feed by
+/// calling `poll` at its preferred cadence.
+///
+/// The feed starts at the latest committed transaction unless a cursor is set. With a
+/// `Checkpoint` it resumes from the saved `EventCursor` after a restart. The cursor is saved
+/// by `commit`, call it once the updates of a poll are processed.
+pub struct MarketDataFeed {
+    base: TypeTag,
+    quote: TypeTag,
+    book_owner: AccountAddress,
+    book_id: Option<Id>,
+    mode: FeedMode,
+    retry_events_every: u32,
+    polls_since_degraded: u32,
+    cursor: Option<EventCursor>,
+    checkpoint: Option<(Arc<dyn Checkpoint>, String)>,
+    #[cfg(feature = "journal")]
+    journal: Option<Arc<EventJournal>>,
+    levels: BTreeMap<(u8, u64), u64>,
+}
+
+impl MarketDataFeed {
+    pub fn new(base: TypeTag, quote: TypeTag, book_owner: AccountAddress) -> Self {
+        Self {
+            base,
+            quote,
+            book_owner,
+            book_id: None,
+            mode: FeedMode::Events,
+            retry_events_every: 10,
+            polls_since_degraded: 0,
+            cursor: None,
+            checkpoint: None,
+            #[cfg(feature = "journal")]
+            journal: None,
+            levels: BTreeMap::new(),
+        }
+    }
+
+    /// Set after how many snapshot polls the event APIs are retried while degraded.
+    pub fn with_retry_events_every(mut self, polls: u32) -> Self {
+        self.retry_events_every = polls.max(1);
+        self
+    }
+
+    /// Start reading events at `cursor` instead of the latest committed transaction.
+    pub fn with_cursor(mut self, cursor: EventCursor) -> Self {
+        self.cursor = Some(cursor);
+        self
+    }
+
+    /// Resume from the cursor saved under `key` in `checkpoint` and save it there on `commit`.
+    pub fn with_checkpoint(
+        mut self,
+        checkpoint: Arc<dyn Checkpoint>,
+        key: impl Into<String>,
+    ) -> Result<Self> {
+        let key = key.into();
+        if let Some(cursor) = checkpoint.load(&key)? {
+            self.cursor = Some(cursor);
+        }
+        self.checkpoint = Some((checkpoint, key));
+        Ok(self)
+    }
+
+    /// Append every received event to `journal`.
+    #[cfg(feature = "journal")]
+    pub fn with_journal(mut self, journal: Arc<EventJournal>) -> Self {
+        self.journal = Some(journal);
+        self
+    }
+
+    pub fn mode(&self) -> FeedMode {
+        self.mode
+    }
+
+    /// Id of the book, known after the first poll.
+    pub fn book_id(&self) -> Option<&Id> {
+        self.book_id.as_ref()
+    }
+
+    /// Cursor after the events delivered so far, `None` before the first successful scan
+    /// unless a cursor was set.
+    pub fn cursor(&self) -> Option<EventCursor> {
+        self.cursor
+    }
+
+    /// Save the cursor to the checkpoint, marking the events delivered so far as processed.
+    pub fn commit(&self) -> Result<()> {
+        if let (Some((checkpoint, key)), Some(cursor)) = (&self.checkpoint, &self.cursor) {
+            checkpoint.save(key, cursor)?;
+        }
+        Ok(())
+    }
+
+    /// Fetch the next batch of updates.
+    pub async fn poll(&mut self, client: &LaminarClient) -> Result<Vec<MarketDataUpdate>> {
+        let book_id = self.resolve_book_id(client).await?;
+
+        let mut res = vec![];
+        if self.mode == FeedMode::Snapshots {
+            self.polls_since_degraded += 1;
+            if self.polls_since_degraded % self.retry_events_every != 0 {
+                res.extend(self.poll_snapshot(client).await?);
+                return Ok(res);
+            }
+        }
+
+        match self.poll_events(client, &book_id).await {
+            Ok(events) => {
+                if self.mode == FeedMode::Snapshots {
+                    self.mode = FeedMode::Events;
+                    res.push(MarketDataUpdate::Recovered);
+                }
+                res.extend(
+                    events
+                        .into_iter()
+                        .map(|e| MarketDataUpdate::Event(e.into_event())),
+                );
+            }
+            Err(e) => {
+                if self.mode == FeedMode::Events {
+                    self.mode = FeedMode::Snapshots;
+                    self.polls_since_degraded = 0;
+                    res.push(MarketDataUpdate::Degraded {
+                        reason: e.to_string(),
+                    });
+                }
+                res.extend(self.poll_snapshot(client).await?);
+            }
+        }
+
+        Ok(res)
+    }
+
+    /// Fetch the next batch of events with their position on the event handles. Unlike
+    /// `poll`, scan failures are returned instead of degrading to snapshots.
+    pub async fn poll_envelopes(
+        &mut self,
+        client: &LaminarClient,
+    ) -> Result<Vec<LaminarEventEnvelope<LaminarEvent>>> {
+        let book_id = self.resolve_book_id(client).await?;
+        self.poll_events(client, &book_id).await
+    }
+
+    async fn resolve_book_id(&mut self, client: &LaminarClient) -> Result<Id> {
+        if let Some(id) = &self.book_id {
+            return Ok(id.clone());
+        }
+        let book = self.fetch_book(client).await?;
+        self.book_id = Some(book.id.clone());
+        self.levels = Self::aggregate(&book);
+        Ok(book.id)
+    }
+
+    async fn fetch_book(&self, client: &LaminarClient) -> Result<OrderBook> {
+        client
+            .fetch_orderbook(&self.base, &self.quote, &self.book_owner)
+            .await
+    }
+
+    async fn poll_events(
+        &mut self,
+        client: &LaminarClient,
+        book_id: &Id,
+    ) -> Result<Vec<LaminarEventEnvelope<LaminarEvent>>> {
+        let cursor = match self.cursor {
+            Some(cursor) => cursor,
+            None => client.fetch_event_cursor().await?,
+        };
+        let mut backfill = Backfill::new(cursor.version).with_book(book_id.clone());
+        let mut events = vec![];
+        for _ in 0..MAX_PAGES_PER_POLL {
+            match backfill.next_page(client).await? {
+                Some(page) => events.extend(page.into_iter().map(VersionedEvent::into_envelope)),
+                None => break,
+            }
+        }
+        #[cfg(feature = "journal")]
+        if let Some(journal) = &self.journal {
+            let journaled = events.iter().map(|e| e.event.clone()).collect::<Vec<_>>();
+            journal.append_all(&journaled, None).await?;
+        }
+        // only advance once all pages were read and journaled, so a failed poll is repeated
+        // in full
+        self.cursor = Some(EventCursor {
+            version: backfill.next_version(),
+        });
+
+        for e in &events {
+            if let LaminarEvent::FillEvent(_fill) = &e.event {
+                trace_event!(
+                    info,
+                    laminar.book_id = %_fill.book_id,
+                    laminar.order_id = %_fill.order_id,
+                    laminar.price = _fill.price,
+                    laminar.size = _fill.fill_size,
+                    "fill observed"
+                );
+            }
+        }
+
+        Ok(events)
+    }
+
+    async fn poll_snapshot(&mut self, client: &LaminarClient) -> Result<Vec<MarketDataUpdate>> {
+        let book = self.fetch_book(client).await?;
+        let levels = Self::aggregate(&book);
+
+        let mut updates = vec![];
+        for (&(side, price), &size) in &levels {
+            if self.levels.get(&(side, price)) != Some(&size) {
+                updates.push(Self::level_update(side, price, size));
+            }
+        }
+        for &(side, price) in self.levels.keys() {
+            if !levels.contains_key(&(side, price)) {
+                updates.push(Self::level_update(side, price, 0));
+            }
+        }
+
+        self.levels = levels;
+        if updates.is_empty() {
+            return Ok(vec![]);
+        }
+
+        Ok(vec![MarketDataUpdate::Levels(updates)])
+    }
+
+    fn level_update(side: u8, price: u64, size: u64) -> LevelUpdate {
+        let side = if side == Side::Bid as u8 {
+            Side::Bid
+        } else {
+            Side::Ask
+        };
+        LevelUpdate { side, price, size }
+    }
+
+    fn aggregate(book: &OrderBook) -> BTreeMap<(u8, u64), u64> {
+        let total = |orders: &Vec<Order>| orders.iter().map(|o| o.remaining_size).sum::<u64>();
+        let bids = book
+            .bids
+            .iter()
+            .map(|(p, o)| ((Side::Bid as u8, *p), total(o)));
+        let asks = book
+            .asks
+            .iter()
+            .map(|(p, o)| ((Side::Ask as u8, *p), total(o)));
+        bids.chain(asks).collect()
+    }
+}
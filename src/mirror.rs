@@ -0,0 +1,280 @@
+use crate::error::Result;
+use crate::market_data::{LevelUpdate, MarketDataUpdate};
+use crate::types::order::{Order, OrderBook, Side};
+use crate::LaminarClient;
+use aptos_sdk::move_types::language_storage::TypeTag;
+use aptos_sdk::types::account_address::AccountAddress;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Default)]
+struct MirrorSide {
+    levels: BTreeMap<u64, u64>,
+    truncated: bool,
+}
+
+/// Local copy of aggregated `OrderBook` levels, kept up to date from `LevelUpdate`s.
+///
+/// With a configured depth only the best `depth` levels per side are kept. When a truncated
+/// side drops below `depth` levels the mirror cannot know what lies behind it, so it flags
+/// itself for a refresh which re-expands it from a new snapshot.
+#[derive(Debug, Default)]
+pub struct OrderBookMirror {
+    depth: Option<usize>,
+    bids: MirrorSide,
+    asks: MirrorSide,
+    needs_refresh: bool,
+}
+
+impl OrderBookMirror {
+    /// Create a mirror that keeps every level.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a mirror that keeps only the best `depth` levels per side.
+    pub fn with_depth(depth: usize) -> Self {
+        Self {
+            depth: Some(depth.max(1)),
+            ..Self::default()
+        }
+    }
+
+    pub fn depth(&self) -> Option<usize> {
+        self.depth
+    }
+
+    /// Whether a truncated side ran out of levels and a new snapshot should be applied.
+    pub fn needs_refresh(&self) -> bool {
+        self.needs_refresh
+    }
+
+    fn side(&self, side: Side) -> &MirrorSide {
+        match side {
+            Side::Bid => &self.bids,
+            Side::Ask => &self.asks,
+        }
+    }
+
+    fn side_mut(&mut self, side: Side) -> &mut MirrorSide {
+        match side {
+            Side::Bid => &mut self.bids,
+            Side::Ask => &mut self.asks,
+        }
+    }
+
+    /// Levels of a side ordered from best to worst, as `(price, total remaining size)`.
+    pub fn levels(&self, side: Side) -> Vec<(u64, u64)> {
+        let levels = self.side(side).levels.iter().map(|(p, s)| (*p, *s));
+        match side {
+            Side::Bid => levels.rev().collect(),
+            Side::Ask => levels.collect(),
+        }
+    }
+
+    pub fn best_bid(&self) -> Option<(u64, u64)> {
+        self.bids.levels.iter().next_back().map(|(p, s)| (*p, *s))
+    }
+
+    pub fn best_ask(&self) -> Option<(u64, u64)> {
+        self.asks.levels.iter().next().map(|(p, s)| (*p, *s))
+    }
+
+    fn is_better(side: Side, price: u64, than: u64) -> bool {
+        match side {
+            Side::Bid => price > than,
+            Side::Ask => price < than,
+        }
+    }
+
+    fn worst_price(&self, side: Side) -> Option<u64> {
+        let levels = &self.side(side).levels;
+        match side {
+            Side::Bid => levels.keys().next().copied(),
+            Side::Ask => levels.keys().next_back().copied(),
+        }
+    }
+
+    fn truncate(&mut self, side: Side) {
+        let Some(depth) = self.depth else {
+            return;
+        };
+        let s = self.side_mut(side);
+        while s.levels.len() > depth {
+            match side {
+                Side::Bid => s.levels.pop_first(),
+                Side::Ask => s.levels.pop_last(),
+            };
+            s.truncated = true;
+        }
+    }
+
+    /// Replace the mirror content with an `OrderBook` snapshot.
+    pub fn apply_snapshot(&mut self, book: &OrderBook) {
+        let total = |orders: &Vec<Order>| orders.iter().map(|o| o.remaining_size).sum::<u64>();
+        for (side, levels) in [(Side::Bid, &book.bids), (Side::Ask, &book.asks)] {
+            let s = self.side_mut(side);
+            s.truncated = false;
+            s.levels = levels.iter().map(|(p, o)| (*p, total(o))).collect();
+            self.truncate(side);
+        }
+
+        self.needs_refresh = false;
+    }
+
+    /// Apply a single level change.
+    pub fn apply_level(&mut self, update: &LevelUpdate) {
+        let LevelUpdate { side, price, size } = *update;
+        let outside_window = self.side(side).truncated
+            && self.worst_price(side).map_or(false, |worst| {
+                !Self::is_better(side, price, worst) && price != worst
+            });
+        if outside_window {
+            return;
+        }
+
+        let s = self.side_mut(side);
+        if size == 0 {
+            s.levels.remove(&price);
+        } else {
+            s.levels.insert(price, size);
+        }
+        self.truncate(side);
+
+        let s = self.side(side);
+        if let Some(depth) = self.depth {
+            if s.truncated && s.levels.len() < depth {
+                self.needs_refresh = true;
+            }
+        }
+    }
+
+    /// Apply an update coming from a `MarketDataFeed`.
+    pub fn apply_update(&mut self, update: &MarketDataUpdate) {
+        if let MarketDataUpdate::Levels(levels) = update {
+            for l in levels {
+                self.apply_level(l);
+            }
+        }
+    }
+
+    /// Fetch a new snapshot and apply it.
+    ///
+    /// # Arguments:
+    ///
+    /// * `client` - `LaminarClient` used to fetch the `OrderBook`.
+    /// * `base` - Aptos `TypeTag` of the orderbook base coin.
+    /// * `quote` - Aptos `TypeTag` of the orderbook quote coin.
+    /// * `book_owner` - Address of the account that owns the `OrderBook`.
+    pub async fn refresh(
+        &mut self,
+        client: &LaminarClient,
+        base: &TypeTag,
+        quote: &TypeTag,
+        book_owner: &AccountAddress,
+    ) -> Result<()> {
+        let book = client.fetch_orderbook(base, quote, book_owner).await?;
+        self.apply_snapshot(&book);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::order::{Id, Instrument, State};
+    use aptos_api_types::{Address, U64};
+
+    fn level(side: Side, price: u64, size: u64) -> LevelUpdate {
+        LevelUpdate { side, price, size }
+    }
+
+    fn order(side: Side, price: u64, size: u64) -> Order {
+        Order {
+            id: Id {
+                creation_num: U64(price),
+                addr: Address::from(AccountAddress::ONE),
+            },
+            side,
+            price,
+            size,
+            post_only: false,
+            remaining_size: size,
+            state: State::default(),
+            fills: vec![],
+        }
+    }
+
+    fn book(bids: &[u64], asks: &[u64]) -> OrderBook {
+        // two orders of size 2 and 3 per level
+        let levels = |side, prices: &[u64]| -> BTreeMap<u64, Vec<Order>> {
+            prices
+                .iter()
+                .map(|p| (*p, vec![order(side, *p, 2), order(side, *p, 3)]))
+                .collect()
+        };
+        OrderBook {
+            id: Id {
+                creation_num: U64(0),
+                addr: Address::from(AccountAddress::ONE),
+            },
+            instrument: Instrument {
+                owner: AccountAddress::ONE,
+                price_decimals: 2,
+                size_decimals: 2,
+                min_size_amount: 1,
+                base_decimals: 6,
+                quote_decimals: 6,
+                rounding: Default::default(),
+            },
+            bids: levels(Side::Bid, bids),
+            asks: levels(Side::Ask, asks),
+            type_tags: vec![],
+            ledger_version: None,
+        }
+    }
+
+    #[test]
+    fn test_levels_best_first() {
+        let mut mirror = OrderBookMirror::new();
+        mirror.apply_update(&MarketDataUpdate::Levels(vec![
+            level(Side::Bid, 100, 5),
+            level(Side::Bid, 101, 3),
+            level(Side::Ask, 103, 2),
+            level(Side::Ask, 102, 4),
+        ]));
+        assert_eq!(mirror.levels(Side::Bid), vec![(101, 3), (100, 5)]);
+        assert_eq!(mirror.levels(Side::Ask), vec![(102, 4), (103, 2)]);
+        assert_eq!(mirror.best_ask(), Some((102, 4)));
+
+        mirror.apply_level(&level(Side::Bid, 101, 0));
+        assert_eq!(mirror.best_bid(), Some((100, 5)));
+        assert!(!mirror.needs_refresh());
+    }
+
+    #[test]
+    fn test_truncated_side_needs_refresh() {
+        let mut mirror = OrderBookMirror::with_depth(2);
+        mirror.apply_snapshot(&book(&[98, 99, 100], &[101]));
+        assert_eq!(mirror.levels(Side::Bid), vec![(100, 5), (99, 5)]);
+        assert_eq!(mirror.levels(Side::Ask), vec![(101, 5)]);
+
+        // the ask side holds every level, removing one leaves nothing unknown behind it
+        mirror.apply_level(&level(Side::Ask, 101, 0));
+        assert!(!mirror.needs_refresh());
+
+        // behind the worst kept bid, so not known to be the next level
+        mirror.apply_level(&level(Side::Bid, 97, 3));
+        assert_eq!(mirror.levels(Side::Bid), vec![(100, 5), (99, 5)]);
+
+        mirror.apply_level(&level(Side::Bid, 101, 1));
+        assert_eq!(mirror.levels(Side::Bid), vec![(101, 1), (100, 5)]);
+        assert!(!mirror.needs_refresh());
+
+        mirror.apply_level(&level(Side::Bid, 100, 0));
+        assert!(mirror.needs_refresh());
+
+        mirror.apply_snapshot(&book(&[98, 99, 100], &[101]));
+        assert!(!mirror.needs_refresh());
+        assert_eq!(mirror.best_bid(), Some((100, 5)));
+    }
+}
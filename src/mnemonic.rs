@@ -0,0 +1,56 @@
+use crate::error::{LaminarError, Result};
+use crate::LaminarClient;
+use aptos_sdk::types::account_address::AccountAddress;
+use aptos_sdk::types::LocalAccount;
+use reqwest::Url;
+
+/// Derivation path of the account at `index`, `m/44'/637'/{index}'/0'/0'`, as used by Petra
+/// and Martian.
+pub fn aptos_derivation_path(index: u32) -> String {
+    format!("m/44'/637'/{}'/0'/0'", index)
+}
+
+/// Derive the account at `index` from a BIP39 mnemonic with the standard Aptos derivation path.
+/// The address is derived from the key, so it is only correct for accounts that never rotated
+/// their authentication key.
+///
+/// # Arguments:
+///
+/// * `mnemonic` - BIP39 mnemonic phrase, words separated by spaces.
+/// * `index` - Index of the account in the wallet, 0 for the first account.
+pub fn account_from_mnemonic(mnemonic: &str, index: u32) -> Result<LocalAccount> {
+    account_from_derivation_path(mnemonic, &aptos_derivation_path(index))
+}
+
+/// Same as `account_from_mnemonic`, with an arbitrary hardened derivation path.
+///
+/// # Arguments:
+///
+/// * `mnemonic` - BIP39 mnemonic phrase, words separated by spaces.
+/// * `derivation_path` - Derivation path such as `m/44'/637'/0'/0'/0'`.
+pub fn account_from_derivation_path(mnemonic: &str, derivation_path: &str) -> Result<LocalAccount> {
+    LocalAccount::from_derive_path(derivation_path, mnemonic.trim(), 0).map_err(|e| {
+        LaminarError::InvalidInput(format!("invalid mnemonic or derivation path: {}", e))
+    })
+}
+
+impl LaminarClient {
+    /// Connect to an Aptos node and initialize the Laminar Markets client with the account at
+    /// `index` of a BIP39 mnemonic, see `account_from_mnemonic`.
+    ///
+    /// # Arguments:
+    ///
+    /// * `node_url` - Url of aptos node.
+    /// * `laminar` - Address of account that holds the laminar modules.
+    /// * `mnemonic` - BIP39 mnemonic phrase, words separated by spaces.
+    /// * `index` - Index of the account in the wallet, 0 for the first account.
+    pub async fn connect_with_mnemonic(
+        node_url: Url,
+        laminar: AccountAddress,
+        mnemonic: &str,
+        index: u32,
+    ) -> Result<Self> {
+        let account = account_from_mnemonic(mnemonic, index)?;
+        Self::connect(node_url, laminar, account).await
+    }
+}
@@ -0,0 +1,116 @@
+use crate::types::order::{Order, OrderBook, Side};
+use aptos_sdk::types::account_address::AccountAddress;
+
+/// Maximum allowed distance between a resting order and the reference price.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MaxDistance {
+    /// Absolute distance in price units.
+    Ticks(u64),
+    /// Relative distance in basis points of the reference price.
+    Bps(u64),
+}
+
+impl MaxDistance {
+    fn exceeded(&self, price: u64, reference_price: u64) -> bool {
+        let distance = price.abs_diff(reference_price);
+        match self {
+            MaxDistance::Ticks(t) => distance > *t,
+            MaxDistance::Bps(bps) => {
+                (distance as u128) * 10_000 > (reference_price as u128) * (*bps as u128)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StaleReason {
+    /// Order price drifted beyond the configured distance from the reference price.
+    Drifted { reference_price: u64 },
+    /// Order is crossed by a resting order on the opposite side, usually caused by a missed event.
+    Crossed { opposite_price: u64 },
+}
+
+#[derive(Clone, Debug)]
+pub struct StaleQuote {
+    pub side: Side,
+    pub order: Order,
+    pub reason: StaleReason,
+}
+
+/// Flags resting orders of an account that no longer reflect the intended quotes.
+#[derive(Clone, Debug)]
+pub struct StaleQuoteMonitor {
+    pub owner: AccountAddress,
+    pub max_distance: MaxDistance,
+    pub auto_cancel: bool,
+}
+
+impl StaleQuoteMonitor {
+    pub fn new(owner: AccountAddress, max_distance: MaxDistance) -> Self {
+        Self {
+            owner,
+            max_distance,
+            auto_cancel: false,
+        }
+    }
+
+    pub fn with_auto_cancel(mut self, auto_cancel: bool) -> Self {
+        self.auto_cancel = auto_cancel;
+        self
+    }
+
+    fn is_mine(&self, order: &Order) -> bool {
+        order.id.addr.inner() == &self.owner
+    }
+
+    fn best_foreign_price<'a>(
+        &self,
+        mut levels: impl Iterator<Item = (&'a u64, &'a Vec<Order>)>,
+    ) -> Option<u64> {
+        levels
+            .find(|(_, orders)| orders.iter().any(|o| !self.is_mine(o)))
+            .map(|(p, _)| *p)
+    }
+
+    /// Check the monitored account's resting orders in `book` against `reference_price`.
+    ///
+    /// # Arguments:
+    ///
+    /// * `book` - `OrderBook` snapshot containing the resting orders.
+    /// * `reference_price` - Fair value in price units, `None` to only check for crossed orders.
+    pub fn check(&self, book: &OrderBook, reference_price: Option<u64>) -> Vec<StaleQuote> {
+        let best_foreign_bid = self.best_foreign_price(book.bids.iter().rev());
+        let best_foreign_ask = self.best_foreign_price(book.asks.iter());
+
+        let mut res = vec![];
+        let sides = [(Side::Bid, &book.bids), (Side::Ask, &book.asks)];
+        for (side, levels) in sides {
+            for (price, orders) in levels {
+                for order in orders.iter().filter(|o| self.is_mine(o)) {
+                    let crossed_by = match side {
+                        Side::Bid => best_foreign_ask.filter(|ask| ask <= price),
+                        Side::Ask => best_foreign_bid.filter(|bid| bid >= price),
+                    };
+                    let reason = if let Some(opposite_price) = crossed_by {
+                        StaleReason::Crossed { opposite_price }
+                    } else {
+                        match reference_price {
+                            Some(r) if self.max_distance.exceeded(*price, r) => {
+                                StaleReason::Drifted { reference_price: r }
+                            }
+                            _ => continue,
+                        }
+                    };
+
+                    res.push(StaleQuote {
+                        side,
+                        order: order.clone(),
+                        reason,
+                    });
+                }
+            }
+        }
+
+        res
+    }
+}
@@ -0,0 +1,250 @@
+use crate::error::{LaminarError, Result};
+use crate::signer::TransactionSigner;
+use crate::{LaminarClient, LaminarTransaction, TxOptions};
+use anyhow::anyhow;
+use aptos_sdk::crypto::ed25519::{
+    Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature, ED25519_SIGNATURE_LENGTH,
+};
+use aptos_sdk::crypto::multi_ed25519::{MultiEd25519PublicKey, MultiEd25519Signature};
+use aptos_sdk::crypto::{Signature, SigningKey};
+use aptos_sdk::types::account_address::AccountAddress;
+use aptos_sdk::types::transaction::authenticator::AuthenticationKey;
+use aptos_sdk::types::transaction::{EntryFunction, RawTransaction, SignedTransaction};
+use async_trait::async_trait;
+use std::collections::BTreeMap;
+
+/// K-of-N MultiEd25519 account.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MultiEd25519Account {
+    address: AccountAddress,
+    public_key: MultiEd25519PublicKey,
+}
+
+impl MultiEd25519Account {
+    /// # Arguments:
+    ///
+    /// * `public_keys` - Public keys of the N owners, in the order of the account's key.
+    /// * `threshold` - Number of signatures K required.
+    pub fn new(public_keys: Vec<Ed25519PublicKey>, threshold: u8) -> Result<Self> {
+        let public_key = MultiEd25519PublicKey::new(public_keys, threshold)
+            .map_err(|e| LaminarError::InvalidInput(format!("invalid multisig key: {}", e)))?;
+        let address = AuthenticationKey::multi_ed25519(&public_key).derived_address();
+        Ok(Self {
+            address,
+            public_key,
+        })
+    }
+
+    /// Use `address` instead of the address derived from the key, for accounts whose
+    /// authentication key was rotated to the multisig key.
+    pub fn with_address(mut self, address: AccountAddress) -> Self {
+        self.address = address;
+        self
+    }
+
+    pub fn address(&self) -> AccountAddress {
+        self.address
+    }
+
+    pub fn public_key(&self) -> &MultiEd25519PublicKey {
+        &self.public_key
+    }
+
+    pub fn threshold(&self) -> u8 {
+        *self.public_key.threshold()
+    }
+
+    /// Start collecting signatures for a transaction sent from this account.
+    pub fn partial_signatures(&self, tx: RawTransaction) -> PartialSignatures {
+        PartialSignatures {
+            account: self.clone(),
+            tx,
+            signatures: BTreeMap::new(),
+        }
+    }
+}
+
+/// Connecting a client with the account itself allows reads and simulation. Transactions
+/// have to be signed out-of-band, see `LaminarClient::prepare_multisig`.
+#[async_trait]
+impl TransactionSigner for MultiEd25519Account {
+    fn address(&self) -> AccountAddress {
+        self.address
+    }
+
+    async fn sign_transaction(&self, _tx: RawTransaction) -> Result<SignedTransaction> {
+        Err(LaminarError::InvalidInput(
+            "multisig transactions are signed with PartialSignatures".to_string(),
+        ))
+    }
+
+    fn simulation_transaction(&self, tx: RawTransaction) -> Result<SignedTransaction> {
+        let zero = Ed25519Signature::try_from(&[0u8; ED25519_SIGNATURE_LENGTH][..])
+            .map_err(|e| anyhow!("failed building signature: {}", e))?;
+        let signatures = (0..self.threshold()).map(|i| (zero.clone(), i)).collect();
+        let signature = MultiEd25519Signature::new(signatures)
+            .map_err(|e| anyhow!("failed building signature: {}", e))?;
+        Ok(SignedTransaction::new_multisig(
+            tx,
+            self.public_key.clone(),
+            signature,
+        ))
+    }
+}
+
+/// Signatures collected for a MultiEd25519 transaction, e.g. passed between owners out-of-band.
+/// Each signature is checked against the owner's key when added.
+#[derive(Clone, Debug)]
+pub struct PartialSignatures {
+    account: MultiEd25519Account,
+    tx: RawTransaction,
+    signatures: BTreeMap<u8, Ed25519Signature>,
+}
+
+impl PartialSignatures {
+    pub fn raw_transaction(&self) -> &RawTransaction {
+        &self.tx
+    }
+
+    /// Indexes of the owners that signed.
+    pub fn signers(&self) -> impl Iterator<Item = u8> + '_ {
+        self.signatures.keys().copied()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.signatures.len() >= self.account.threshold() as usize
+    }
+
+    /// Add the signature of the owner at `index`.
+    ///
+    /// # Arguments:
+    ///
+    /// * `index` - Index of the owner's key in the account's key.
+    /// * `signature` - Owner's signature of the raw transaction.
+    pub fn add(&mut self, index: u8, signature: Ed25519Signature) -> Result<()> {
+        let public_key = self
+            .account
+            .public_key
+            .public_keys()
+            .get(index as usize)
+            .ok_or_else(|| LaminarError::InvalidInput(format!("no owner at index {}", index)))?;
+        signature.verify(&self.tx, public_key).map_err(|e| {
+            LaminarError::InvalidInput(format!("invalid signature of owner {}: {}", index, e))
+        })?;
+        self.signatures.insert(index, signature);
+        Ok(())
+    }
+
+    /// Sign with the private key of the owner at `index`.
+    pub fn sign(&mut self, index: u8, private_key: &Ed25519PrivateKey) -> Result<()> {
+        let signature = private_key.sign(&self.tx);
+        self.add(index, signature)
+    }
+
+    /// Assemble the signed transaction once the threshold is reached.
+    pub fn assemble(&self) -> Result<SignedTransaction> {
+        if !self.is_complete() {
+            return Err(LaminarError::InvalidInput(format!(
+                "{} of {} signatures collected",
+                self.signatures.len(),
+                self.account.threshold()
+            )));
+        }
+
+        let signatures = self
+            .signatures
+            .iter()
+            .take(self.account.threshold() as usize)
+            .map(|(i, s)| (s.clone(), *i))
+            .collect();
+        let signature = MultiEd25519Signature::new(signatures)
+            .map_err(|e| anyhow!("failed assembling signature: {}", e))?;
+        Ok(SignedTransaction::new_multisig(
+            self.tx.clone(),
+            self.account.public_key.clone(),
+            signature,
+        ))
+    }
+}
+
+/// Signer for a MultiEd25519 account whose K keys are all available in process.
+#[derive(Debug)]
+pub struct MultiEd25519Signer {
+    account: MultiEd25519Account,
+    keys: Vec<(u8, Ed25519PrivateKey)>,
+}
+
+impl MultiEd25519Signer {
+    /// # Arguments:
+    ///
+    /// * `account` - The multisig account.
+    /// * `keys` - Private keys with the index of their owner, at least `threshold` of them.
+    pub fn new(account: MultiEd25519Account, keys: Vec<(u8, Ed25519PrivateKey)>) -> Result<Self> {
+        if keys.len() < account.threshold() as usize {
+            return Err(LaminarError::InvalidInput(format!(
+                "{} keys given, {} required",
+                keys.len(),
+                account.threshold()
+            )));
+        }
+        Ok(Self { account, keys })
+    }
+}
+
+#[async_trait]
+impl TransactionSigner for MultiEd25519Signer {
+    fn address(&self) -> AccountAddress {
+        self.account.address
+    }
+
+    async fn sign_transaction(&self, tx: RawTransaction) -> Result<SignedTransaction> {
+        let mut partial = self.account.partial_signatures(tx);
+        for (index, key) in &self.keys {
+            partial.sign(*index, key)?;
+        }
+        partial.assemble()
+    }
+
+    fn simulation_transaction(&self, tx: RawTransaction) -> Result<SignedTransaction> {
+        self.account.simulation_transaction(tx)
+    }
+}
+
+impl LaminarClient {
+    /// Build a transaction from a MultiEd25519 account, to be signed out-of-band and then
+    /// submitted with `submit_multisig`. The client must be connected as the multisig account.
+    ///
+    /// A sequence number is reserved for the transaction. If it is abandoned, later
+    /// transactions stay pending until the sequence number is handed back with
+    /// `release_sequence_number`.
+    ///
+    /// # Arguments:
+    ///
+    /// * `account` - The multisig account.
+    /// * `payload` - Entry function payload to be used in the tx.
+    /// * `options` - Gas and expiration settings, leave time for collecting signatures.
+    pub fn prepare_multisig(
+        &self,
+        account: &MultiEd25519Account,
+        payload: EntryFunction,
+        options: TxOptions,
+    ) -> Result<PartialSignatures> {
+        if account.address() != self.address() {
+            return Err(LaminarError::InvalidInput(format!(
+                "client is not connected as multisig account {}",
+                account.address().to_hex_literal()
+            )));
+        }
+        let tx = self.build_raw_tx(payload, options)?;
+        Ok(account.partial_signatures(tx))
+    }
+
+    /// Submit a multisig transaction once enough signatures were collected and wait for it.
+    pub async fn submit_multisig(&self, partial: &PartialSignatures) -> Result<LaminarTransaction> {
+        let signed_tx = partial.assemble()?;
+        let pending = self.broadcast_reserved(&signed_tx).await?;
+        let lt = self.wait_for_laminar_tx(&pending).await?;
+        self.record_audit(&lt);
+        Ok(lt)
+    }
+}
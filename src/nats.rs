@@ -0,0 +1,277 @@
+//! Distributing Laminar events over NATS, optionally persisted in a JetStream stream.
+
+use crate::error::{LaminarError, Result};
+use crate::market_data::MarketDataFeed;
+use crate::types::events::{LaminarEvent, LaminarEventEnvelope};
+use crate::types::order::Id;
+use crate::LaminarClient;
+use anyhow::anyhow;
+use async_nats::jetstream::consumer::pull::Config as PullConfig;
+use async_nats::jetstream::consumer::{DeliverPolicy, PullConsumer};
+use async_nats::jetstream::{self, stream, Context as JetStream};
+use futures::StreamExt;
+use serde::Deserialize;
+
+/// Publishes Laminar events to NATS subjects `{prefix}.{book_id}.{kind}`, where kind is one of
+/// `books`, `places`, `amends`, `cancels`, `fills` or `other`.
+///
+/// With JetStream the events are stored in a stream covering `{prefix}.>`, each publish waits
+/// for the stream's ack and carries a `Nats-Msg-Id` built from the event's key and sequence
+/// number, so publishing again after a crash doesn't duplicate events within the stream's
+/// duplicate window. Consumers read the stream through `durable_consumer` or `replay`.
+pub struct NatsPublisher {
+    client: async_nats::Client,
+    jetstream: Option<(JetStream, String)>,
+    prefix: String,
+}
+
+/// Event read from a JetStream stream, acknowledge it with `ack` once processed.
+pub struct NatsEvent {
+    pub subject: String,
+    /// Position of the message in the stream.
+    pub stream_sequence: u64,
+    pub event: LaminarEvent,
+    message: jetstream::Message,
+}
+
+impl NatsEvent {
+    pub async fn ack(&self) -> Result<()> {
+        self.message
+            .ack()
+            .await
+            .map_err(|e| anyhow!("failed acking nats message: {}", e))?;
+        Ok(())
+    }
+}
+
+/// Pull consumer of a JetStream stream of Laminar events.
+pub struct NatsEventConsumer {
+    consumer: PullConsumer,
+}
+
+impl NatsEventConsumer {
+    /// Fetch up to `max` events, returning fewer if the stream has no more pending.
+    pub async fn fetch(&self, max: usize) -> Result<Vec<NatsEvent>> {
+        let mut messages = self
+            .consumer
+            .fetch()
+            .max_messages(max)
+            .messages()
+            .await
+            .map_err(|e| anyhow!("failed fetching nats messages: {}", e))?;
+
+        let mut res = vec![];
+        while let Some(message) = messages.next().await {
+            let message = message.map_err(|e| anyhow!("failed receiving nats message: {}", e))?;
+            let stream_sequence = message
+                .info()
+                .map_err(|e| anyhow!("invalid nats message info: {}", e))?
+                .stream_sequence;
+            let payload: Payload = serde_json::from_slice(&message.payload)
+                .map_err(|e| LaminarError::deserialization(e, "nats message"))?;
+            res.push(NatsEvent {
+                subject: message.subject.clone(),
+                stream_sequence,
+                event: payload.event,
+                message,
+            });
+        }
+
+        Ok(res)
+    }
+}
+
+/// Part of a published envelope consumers read back.
+#[derive(Deserialize)]
+struct Payload {
+    event: LaminarEvent,
+}
+
+impl NatsPublisher {
+    /// Connect to a NATS server, publishing with core NATS.
+    ///
+    /// # Arguments:
+    ///
+    /// * `url` - Server url, e.g. `nats://127.0.0.1:4222`.
+    pub async fn connect(url: &str) -> Result<Self> {
+        let client = async_nats::connect(url)
+            .await
+            .map_err(|e| anyhow!("failed connecting to nats: {}", e))?;
+
+        Ok(Self {
+            client,
+            jetstream: None,
+            prefix: "laminar".to_string(),
+        })
+    }
+
+    /// Prefix of the subjects, `laminar` by default. Set it before `with_jetstream`.
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Publish to the JetStream stream `stream`, creating it if missing.
+    pub async fn with_jetstream(mut self, stream: &str) -> Result<Self> {
+        let context = jetstream::new(self.client.clone());
+        context
+            .get_or_create_stream(stream::Config {
+                name: stream.to_string(),
+                subjects: vec![format!("{}.>", self.prefix)],
+                ..Default::default()
+            })
+            .await
+            .map_err(|e| anyhow!("failed creating jetstream stream {}: {}", stream, e))?;
+        self.jetstream = Some((context, stream.to_string()));
+        Ok(self)
+    }
+
+    /// Subject events of `kind` of a book are published to.
+    pub fn subject(&self, book_id: &Id, kind: &str) -> String {
+        format!("{}.{}.{}", self.prefix, book_id, kind)
+    }
+
+    /// Publish an event, waiting for the stream's ack when publishing to JetStream.
+    pub async fn publish(&self, envelope: &LaminarEventEnvelope<LaminarEvent>) -> Result<()> {
+        let subject = match envelope.event.book_id() {
+            Some(book_id) => self.subject(book_id, event_kind(&envelope.event)),
+            None => format!("{}.unknown.other", self.prefix),
+        };
+        let payload = serde_json::to_vec(envelope)
+            .map_err(|e| LaminarError::deserialization(e, "nats message"))?;
+
+        match &self.jetstream {
+            Some((context, _)) => {
+                let mut headers = async_nats::HeaderMap::new();
+                let msg_id = format!(
+                    "{}:{}:{}",
+                    envelope.key.account_address.to_hex_literal(),
+                    envelope.key.creation_number,
+                    envelope.sequence_number
+                );
+                headers.insert("Nats-Msg-Id", msg_id.as_str());
+                context
+                    .publish_with_headers(subject, headers, payload.into())
+                    .await
+                    .map_err(|e| anyhow!("failed publishing to jetstream: {}", e))?
+                    .await
+                    .map_err(|e| anyhow!("jetstream did not ack publish: {}", e))?;
+            }
+            None => {
+                self.client
+                    .publish(subject, payload.into())
+                    .await
+                    .map_err(|e| anyhow!("failed publishing to nats: {}", e))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Publish events in order, then flush the connection.
+    pub async fn publish_all(
+        &self,
+        envelopes: &[LaminarEventEnvelope<LaminarEvent>],
+    ) -> Result<()> {
+        for envelope in envelopes {
+            self.publish(envelope).await?;
+        }
+        self.client
+            .flush()
+            .await
+            .map_err(|e| anyhow!("failed flushing nats connection: {}", e))?;
+        Ok(())
+    }
+
+    /// Poll `feed` once, publish the received events and commit the feed's checkpoint.
+    /// Returns the number of published events.
+    pub async fn forward(
+        &self,
+        client: &LaminarClient,
+        feed: &mut MarketDataFeed,
+    ) -> Result<usize> {
+        let envelopes = feed.poll_envelopes(client).await?;
+        if envelopes.is_empty() {
+            return Ok(0);
+        }
+        self.publish_all(&envelopes).await?;
+        feed.commit()?;
+        trace_event!(debug, events = envelopes.len(), "published to nats");
+
+        Ok(envelopes.len())
+    }
+
+    /// Durable consumer of the stream, created if missing. The server tracks its acked
+    /// position, so it resumes where it left off after a restart.
+    ///
+    /// # Arguments:
+    ///
+    /// * `name` - Durable name of the consumer.
+    /// * `filter_subject` - Subjects to consume, e.g. `laminar.*.fills`, `None` for all.
+    pub async fn durable_consumer(
+        &self,
+        name: &str,
+        filter_subject: Option<&str>,
+    ) -> Result<NatsEventConsumer> {
+        self.consumer(
+            PullConfig {
+                durable_name: Some(name.to_string()),
+                filter_subject: filter_subject.unwrap_or_default().to_string(),
+                ..Default::default()
+            },
+            name,
+        )
+        .await
+    }
+
+    /// Ephemeral consumer replaying the stream from `start_sequence`.
+    ///
+    /// # Arguments:
+    ///
+    /// * `filter_subject` - Subjects to replay, e.g. `laminar.{book_id}.>`, `None` for all.
+    /// * `start_sequence` - Stream sequence of the first message, 1 for the whole stream.
+    pub async fn replay(
+        &self,
+        filter_subject: Option<&str>,
+        start_sequence: u64,
+    ) -> Result<NatsEventConsumer> {
+        self.consumer(
+            PullConfig {
+                filter_subject: filter_subject.unwrap_or_default().to_string(),
+                deliver_policy: DeliverPolicy::ByStartSequence { start_sequence },
+                ..Default::default()
+            },
+            "replay",
+        )
+        .await
+    }
+
+    async fn consumer(&self, config: PullConfig, name: &str) -> Result<NatsEventConsumer> {
+        let Some((context, stream)) = &self.jetstream else {
+            return Err(LaminarError::InvalidInput(
+                "consumers need a jetstream stream, see with_jetstream".to_string(),
+            ));
+        };
+        let stream = context
+            .get_stream(stream)
+            .await
+            .map_err(|e| anyhow!("failed getting jetstream stream {}: {}", stream, e))?;
+        let consumer = match &config.durable_name {
+            Some(durable) => stream.get_or_create_consumer(durable, config.clone()).await,
+            None => stream.create_consumer(config).await,
+        }
+        .map_err(|e| anyhow!("failed creating jetstream consumer {}: {}", name, e))?;
+
+        Ok(NatsEventConsumer { consumer })
+    }
+}
+
+fn event_kind(event: &LaminarEvent) -> &'static str {
+    match event {
+        LaminarEvent::CreateOrderBook(_) => "books",
+        LaminarEvent::PlaceOrder(_) => "places",
+        LaminarEvent::AmendOrder(_) => "amends",
+        LaminarEvent::CancelOrder(_) => "cancels",
+        LaminarEvent::FillEvent(_) => "fills",
+        LaminarEvent::Unknown { .. } => "other",
+    }
+}
@@ -0,0 +1,143 @@
+use crate::error::{LaminarError, Result};
+use crate::LaminarClient;
+use aptos_sdk::types::account_address::AccountAddress;
+use aptos_sdk::types::LocalAccount;
+use reqwest::Url;
+use std::fmt::Formatter;
+use std::str::FromStr;
+
+/// Environment variable holding the Laminar address on networks without a known deployment.
+pub const DEX_ACCOUNT_ADDRESS_VAR: &str = "DEX_ACCOUNT_ADDRESS";
+
+/// Aptos network with its public endpoints.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Network {
+    Mainnet,
+    Testnet,
+    Devnet,
+    /// Node started with `aptos node run-local-testnet`.
+    Local,
+}
+
+impl Network {
+    pub fn node_url(&self) -> Url {
+        let url = match self {
+            Network::Mainnet => "https://fullnode.mainnet.aptoslabs.com/v1",
+            Network::Testnet => "https://fullnode.testnet.aptoslabs.com/v1",
+            Network::Devnet => "https://fullnode.devnet.aptoslabs.com/v1",
+            Network::Local => "http://127.0.0.1:8080/v1",
+        };
+        Url::parse(url).expect("preset node url is valid")
+    }
+
+    /// Faucet of the network, `None` on mainnet.
+    pub fn faucet_url(&self) -> Option<Url> {
+        let url = match self {
+            Network::Mainnet => return None,
+            Network::Testnet => "https://faucet.testnet.aptoslabs.com",
+            Network::Devnet => "https://faucet.devnet.aptoslabs.com",
+            Network::Local => "http://127.0.0.1:8081",
+        };
+        Some(Url::parse(url).expect("preset faucet url is valid"))
+    }
+
+    /// Public Aptos Indexer GraphQL endpoint of the network, `None` for local nodes.
+    pub fn indexer_url(&self) -> Option<Url> {
+        let url = match self {
+            Network::Mainnet => "https://indexer.mainnet.aptoslabs.com/v1/graphql",
+            Network::Testnet => "https://indexer-testnet.staging.gcp.aptosdev.com/v1/graphql",
+            Network::Devnet => "https://indexer-devnet.staging.gcp.aptosdev.com/v1/graphql",
+            Network::Local => return None,
+        };
+        Some(Url::parse(url).expect("preset indexer url is valid"))
+    }
+
+    /// Chain id of the network, `None` for devnet whose chain id changes on every reset.
+    pub fn chain_id(&self) -> Option<u8> {
+        match self {
+            Network::Mainnet => Some(1),
+            Network::Testnet => Some(2),
+            Network::Devnet => None,
+            Network::Local => Some(4),
+        }
+    }
+
+    /// Address the Laminar modules are deployed at, `None` if there is no known deployment.
+    pub fn laminar_address(&self) -> Option<AccountAddress> {
+        // no deployment address has been published for any network yet
+        match self {
+            Network::Mainnet | Network::Testnet | Network::Devnet | Network::Local => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Network {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Network::Mainnet => "mainnet",
+            Network::Testnet => "testnet",
+            Network::Devnet => "devnet",
+            Network::Local => "local",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl FromStr for Network {
+    type Err = LaminarError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "mainnet" => Ok(Network::Mainnet),
+            "testnet" => Ok(Network::Testnet),
+            "devnet" => Ok(Network::Devnet),
+            "local" => Ok(Network::Local),
+            _ => Err(LaminarError::InvalidInput(format!(
+                "unknown network: {}",
+                s
+            ))),
+        }
+    }
+}
+
+impl LaminarClient {
+    /// Connect to the public fullnode of a network.
+    /// The Laminar address is the network's known deployment, or read from the
+    /// `DEX_ACCOUNT_ADDRESS` environment variable if there is none. Fails if the node reports
+    /// a chain id other than the network's.
+    ///
+    /// # Arguments:
+    ///
+    /// * `network` - `Network` to connect to.
+    /// * `account` - `LocalAccount` representing Aptos user account
+    pub async fn connect_to(network: Network, account: LocalAccount) -> Result<Self> {
+        let laminar = match network.laminar_address() {
+            Some(laminar) => laminar,
+            None => {
+                let address = std::env::var(DEX_ACCOUNT_ADDRESS_VAR).map_err(|_| {
+                    LaminarError::InvalidInput(format!(
+                        "no known laminar deployment on {}, set {}",
+                        network, DEX_ACCOUNT_ADDRESS_VAR
+                    ))
+                })?;
+                AccountAddress::from_hex_literal(&address).map_err(|e| {
+                    LaminarError::InvalidInput(format!("{}: {}", DEX_ACCOUNT_ADDRESS_VAR, e))
+                })?
+            }
+        };
+
+        let mut client = Self::connect(network.node_url(), laminar, account).await?;
+        client.set_network(Some(network));
+        match network.chain_id() {
+            Some(chain_id) if chain_id != client.chain_id() => {
+                Err(LaminarError::InvalidInput(format!(
+                    "node of {} reports chain id {}, expected {}",
+                    network,
+                    client.chain_id(),
+                    chain_id
+                )))
+            }
+            _ => Ok(client),
+        }
+    }
+}
@@ -0,0 +1,179 @@
+use crate::error::{LaminarError, Result};
+use crate::types::decimal::{self, RoundingMode};
+use crate::types::order::{OrderBook, Side, TimeInForce};
+use crate::{LaminarClient, PlacedOrder};
+use aptos_sdk::move_types::language_storage::TypeTag;
+use aptos_sdk::types::account_address::AccountAddress;
+
+/// Base size equivalent to a quote notional, computed against the resting orders of a book.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NotionalSize {
+    /// Size in size units, rounded down to a whole unit.
+    pub size: u64,
+    /// Price of the last level needed to fill `size`.
+    pub worst_price: u64,
+    /// Whether the book had enough liquidity for the whole notional.
+    pub complete: bool,
+}
+
+// quote notional scaled by price and size decimals, i.e. in `price * size` units
+fn notional_to_units(book: &OrderBook, notional: f64) -> Result<u128> {
+    let price_units = decimal::to_units(
+        notional,
+        book.instrument.price_decimals,
+        RoundingMode::Floor,
+    )?;
+    let scale = 10u128
+        .checked_pow(book.instrument.size_decimals as u32)
+        .ok_or_else(|| LaminarError::InvalidInput("size decimals out of range".to_string()))?;
+    Ok(price_units as u128 * scale)
+}
+
+impl OrderBook {
+    /// Walk the side opposite to `side` from the best price and return the size that can be
+    /// bought or sold for `notional` in quote currency, e.g. 500.0 for 500 USDC.
+    /// `None` if the opposite side is empty or the notional buys less than one size unit.
+    pub fn size_for_notional(&self, side: Side, notional: f64) -> Result<Option<NotionalSize>> {
+        let mut remaining = notional_to_units(self, notional)?;
+        let levels: Box<dyn Iterator<Item = _>> = match side {
+            Side::Bid => Box::new(self.asks.iter()),
+            Side::Ask => Box::new(self.bids.iter().rev()),
+        };
+
+        let mut size = 0u64;
+        let mut worst_price = None;
+        for (price, orders) in levels {
+            if *price == 0 {
+                continue;
+            }
+            let level_size = orders.iter().map(|o| o.remaining_size).sum::<u64>();
+            let level_cost = *price as u128 * level_size as u128;
+            let take = if level_cost <= remaining {
+                level_size
+            } else {
+                (remaining / *price as u128) as u64
+            };
+            if take == 0 {
+                break;
+            }
+
+            size += take;
+            remaining -= *price as u128 * take as u128;
+            worst_price = Some(*price);
+            if take < level_size {
+                break;
+            }
+        }
+
+        let complete = remaining < worst_price.unwrap_or(u64::MAX) as u128;
+        Ok(worst_price.map(|worst_price| NotionalSize {
+            size,
+            worst_price,
+            complete,
+        }))
+    }
+
+    /// Size that `notional` in quote currency buys at `price`, rounded down to a whole unit.
+    pub fn size_at_price(&self, price: u64, notional: f64) -> Result<u64> {
+        if price == 0 {
+            return Err(LaminarError::InvalidInput(
+                "price must be positive".to_string(),
+            ));
+        }
+        Ok((notional_to_units(self, notional)? / price as u128).min(u64::MAX as u128) as u64)
+    }
+}
+
+impl LaminarClient {
+    /// Spend or receive `notional` in quote currency, e.g. buy 500 USDC worth of the base coin.
+    ///
+    /// The current book is walked to compute the equivalent base size at the resting prices,
+    /// and the size is submitted as an IOC limit order at the worst of those prices, so the
+    /// notional is not exceeded if the book moves in the meantime.
+    ///
+    /// # Arguments:
+    ///
+    /// * `base` - Aptos `TypeTag` of the orderbook base coin.
+    /// * `quote` - Aptos `TypeTag` of the orderbook quote coin.
+    /// * `book_owner` - Address of the account that owns the `OrderBook`.
+    /// * `side` - `Side`: Bid or Ask.
+    /// * `notional` - Amount of quote currency as a decimal.
+    pub async fn place_market_order_by_notional(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        book_owner: &AccountAddress,
+        side: Side,
+        notional: f64,
+    ) -> Result<PlacedOrder> {
+        let book = self.fetch_orderbook(base, quote, book_owner).await?;
+        let fill = book.size_for_notional(side, notional)?.ok_or_else(|| {
+            LaminarError::InvalidInput(format!("no liquidity for notional {}", notional))
+        })?;
+        check_min_size(&book, fill.size)?;
+
+        self.place_limit_order(
+            base,
+            quote,
+            book_owner,
+            side,
+            fill.worst_price,
+            fill.size,
+            TimeInForce::ImmediateOrCancel,
+            false,
+        )
+        .await
+    }
+
+    /// Place a limit order for `notional` in quote currency at `price`.
+    ///
+    /// # Arguments:
+    ///
+    /// * `base` - Aptos `TypeTag` of the orderbook base coin.
+    /// * `quote` - Aptos `TypeTag` of the orderbook quote coin.
+    /// * `book_owner` - Address of the account that owns the `OrderBook`.
+    /// * `side` - `Side`: Bid or Ask.
+    /// * `price` - Price of the order in price units.
+    /// * `notional` - Amount of quote currency as a decimal.
+    /// * `time_in_force` - `TimeInForce` for limit order, can be GTC, IOC, or FOK.
+    /// * `post_only` - Flag to specify whether or not the limit order is `post_only`.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn place_limit_order_by_notional(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        book_owner: &AccountAddress,
+        side: Side,
+        price: u64,
+        notional: f64,
+        time_in_force: TimeInForce,
+        post_only: bool,
+    ) -> Result<PlacedOrder> {
+        let book = self.fetch_orderbook(base, quote, book_owner).await?;
+        let size = book.size_at_price(price, notional)?;
+        check_min_size(&book, size)?;
+
+        self.place_limit_order(
+            base,
+            quote,
+            book_owner,
+            side,
+            price,
+            size,
+            time_in_force,
+            post_only,
+        )
+        .await
+    }
+}
+
+fn check_min_size(book: &OrderBook, size: u64) -> Result<()> {
+    if size < book.instrument.min_size_amount {
+        return Err(LaminarError::InvalidInput(format!(
+            "size {} is below the minimum size {}",
+            size, book.instrument.min_size_amount
+        )));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,417 @@
+use crate::error::{LaminarError, Result};
+use crate::submission::{Submission, SubmissionStatus};
+use crate::trigger::StopOrder;
+use crate::types::events::LaminarEvent;
+use crate::types::market::Market;
+use crate::types::order::{Id, Side, State, TimeInForce};
+use crate::{LaminarClient, PlacedOrder};
+use anyhow::{anyhow, Context};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OcoState {
+    /// Take-profit is signed and being submitted.
+    Placing,
+    /// Take-profit is resting and the stop is armed.
+    Active,
+    /// Take-profit was filled, the stop was dropped.
+    TakeProfitFilled,
+    /// Stop fired, the take-profit is being cancelled.
+    Triggering,
+    /// Take-profit was cancelled, the stop for its unfilled size is yet to be placed.
+    StopPending,
+    /// Stop fired and was placed, the take-profit was cancelled.
+    StopTriggered,
+    /// Pair was cancelled, by `OcoManager::cancel` or because the take-profit was cancelled.
+    Cancelled,
+}
+
+/// Take-profit limit order linked to a client-side stop order.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OcoPair {
+    pub id: u64,
+    pub book: Market,
+    pub take_profit_price: u64,
+    /// `None` until the take-profit is placed.
+    pub take_profit_order: Option<Id>,
+    /// Stop for the unfilled size of the take-profit.
+    pub stop: StopOrder,
+    pub state: OcoState,
+    /// Signed tx of the current step, the take-profit while `Placing` and the stop while
+    /// `StopPending`. It is saved before it is submitted and submitted again after a restart,
+    /// so the step commits at most once.
+    pub submission: Option<Submission>,
+}
+
+impl OcoPair {
+    pub fn is_active(&self) -> bool {
+        self.state == OcoState::Active
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct OcoStore {
+    next_id: u64,
+    pairs: Vec<OcoPair>,
+}
+
+/// One-cancels-other order pairs: a take-profit limit order resting on the book and a stop
+/// order held by a `StopOrder` trigger. Filling the take-profit drops the stop, triggering the
+/// stop cancels the take-profit.
+///
+/// Pairs are driven by the account's event stream through `on_event` and by trade prices
+/// through `on_price`. With `open`, link state is written to disk on every change so pairs
+/// survive restarts.
+#[derive(Debug, Default)]
+pub struct OcoManager {
+    store: OcoStore,
+    path: Option<PathBuf>,
+}
+
+impl OcoManager {
+    /// Create an in-memory manager.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Open a manager persisted in a JSON file, loading existing pairs.
+    ///
+    /// # Arguments:
+    ///
+    /// * `path` - Path of the JSON state file, created on the first change if missing.
+    pub fn open<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let store = if path.exists() {
+            let data = fs::read(path).context("failed reading oco state")?;
+            serde_json::from_slice(&data).context("invalid oco state")?
+        } else {
+            OcoStore::default()
+        };
+
+        Ok(Self {
+            store,
+            path: Some(path.to_path_buf()),
+        })
+    }
+
+    pub fn pairs(&self) -> &[OcoPair] {
+        &self.store.pairs
+    }
+
+    pub fn pair(&self, id: u64) -> Option<&OcoPair> {
+        self.store.pairs.iter().find(|p| p.id == id)
+    }
+
+    fn save(&self) -> anyhow::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        // write to a temporary file first so a crash never leaves a truncated state file
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, serde_json::to_vec(&self.store)?).context("failed writing oco state")?;
+        fs::rename(&tmp, path).context("failed writing oco state")?;
+        Ok(())
+    }
+
+    /// Place a take-profit limit order and link a stop order for the same size to it.
+    /// Returns the id of the pair.
+    ///
+    /// The pair is saved with the signed take-profit before it is submitted. If the outcome
+    /// of the submission is unknown, the pair stays `Placing` and the next `on_price` call
+    /// for its book submits the same tx again.
+    ///
+    /// # Arguments:
+    ///
+    /// * `client` - Client used to submit orders.
+    /// * `book` - Book to trade on.
+    /// * `side` - `Side` of both orders, e.g. Ask to exit a long position.
+    /// * `size` - Size of both orders.
+    /// * `take_profit_price` - Price of the take-profit limit order.
+    /// * `stop_price` - Price at which the stop fires, see `StopOrder::stop`.
+    pub async fn place(
+        &mut self,
+        client: &LaminarClient,
+        book: Market,
+        side: Side,
+        size: u64,
+        take_profit_price: u64,
+        stop_price: u64,
+    ) -> Result<u64> {
+        let payload = client.place_limit_order_payload(
+            &book.base,
+            &book.quote,
+            &book.book_owner,
+            side,
+            take_profit_price,
+            size,
+            TimeInForce::GoodTillCanceled,
+            false,
+        )?;
+        let submission = client
+            .sign_submission(payload, *client.tx_options())
+            .await?;
+
+        let id = self.store.next_id;
+        self.store.next_id += 1;
+        self.store.pairs.push(OcoPair {
+            id,
+            take_profit_price,
+            take_profit_order: None,
+            stop: StopOrder::stop(book.clone(), side, size, stop_price),
+            book,
+            state: OcoState::Placing,
+            submission: Some(submission),
+        });
+        self.save()?;
+        self.submit_take_profit(client, id).await?;
+
+        Ok(id)
+    }
+
+    // submit the signed take-profit of a `Placing` pair. A pair whose take-profit failed or
+    // never committed is dropped
+    async fn submit_take_profit(&mut self, client: &LaminarClient, id: u64) -> Result<()> {
+        let Some(submission) = self.pair_mut(id)?.submission.clone() else {
+            return Err(LaminarError::NotFound(format!(
+                "take-profit of oco pair {}",
+                id
+            )));
+        };
+        let placed = match client.submit_once(&submission).await {
+            Ok(SubmissionStatus::Committed(tx)) => PlacedOrder::from_transaction(&tx)
+                .ok_or_else(|| LaminarError::NotFound("place order event".to_string())),
+            Ok(_) => Err(LaminarError::Other(anyhow!(
+                "take-profit of oco pair {} expired before it was committed",
+                id
+            ))),
+            Err(e) if e.is_outcome_unknown() => return Err(e),
+            Err(e) => Err(e),
+        };
+        let placed = match placed {
+            Ok(placed) => placed,
+            Err(e) => {
+                self.store.pairs.retain(|p| p.id != id);
+                self.save()?;
+                return Err(e);
+            }
+        };
+
+        let pair = self.pair_mut(id)?;
+        let remaining = pair.stop.size - placed.filled_size().min(pair.stop.size);
+        pair.state = match (remaining, placed.cancel.is_some()) {
+            (0, _) => OcoState::TakeProfitFilled,
+            (_, true) => OcoState::Cancelled,
+            _ => OcoState::Active,
+        };
+        pair.take_profit_order = Some(placed.order_id().clone());
+        pair.stop.size = remaining;
+        pair.submission = None;
+        self.save()?;
+
+        Ok(())
+    }
+
+    /// Apply a Laminar event to the pairs whose take-profit it concerns.
+    pub fn on_event(&mut self, event: &LaminarEvent) -> Result<()> {
+        let mut changed = false;
+        for pair in self.store.pairs.iter_mut().filter(|p| p.is_active()) {
+            match event {
+                LaminarEvent::FillEvent(f)
+                    if pair.take_profit_order.as_ref() == Some(&f.order_id) =>
+                {
+                    pair.stop.size = pair.stop.size.min(f.remaining_size);
+                    if pair.stop.size == 0 {
+                        pair.state = OcoState::TakeProfitFilled;
+                    }
+                    changed = true;
+                }
+                LaminarEvent::CancelOrder(c)
+                    if pair.take_profit_order.as_ref() == Some(&c.order_id) =>
+                {
+                    pair.state = OcoState::Cancelled;
+                    changed = true;
+                }
+                _ => {}
+            }
+        }
+        if changed {
+            self.save()?;
+        }
+
+        Ok(())
+    }
+
+    /// Fire the stops of active pairs on `book` triggered by `price`, and resume pairs on
+    /// `book` whose take-profit is still being placed or whose stop fired earlier but was not
+    /// placed.
+    ///
+    /// The take-profit is cancelled before the stop is submitted, and the stop is sized to the
+    /// size the take-profit left unfilled. If the take-profit was filled in the meantime, the
+    /// stop is not submitted. Every step is persisted before the next one is taken, and orders
+    /// are signed and saved before they are submitted, so a pair interrupted by a failure or a
+    /// restart is resumed by the next call instead of firing twice or leaving neither order.
+    /// The first failure is returned.
+    pub async fn on_price(
+        &mut self,
+        client: &LaminarClient,
+        book: &Market,
+        price: u64,
+    ) -> Result<Vec<PlacedOrder>> {
+        let placing = self
+            .store
+            .pairs
+            .iter()
+            .filter(|p| &p.book == book && p.state == OcoState::Placing)
+            .map(|p| p.id)
+            .collect::<Vec<_>>();
+        for id in placing {
+            self.submit_take_profit(client, id).await?;
+        }
+
+        let triggered = self
+            .store
+            .pairs
+            .iter()
+            .filter(|p| &p.book == book)
+            .filter(|p| match p.state {
+                OcoState::Active => p.stop.condition.is_met(price),
+                OcoState::Triggering | OcoState::StopPending => true,
+                _ => false,
+            })
+            .map(|p| p.id)
+            .collect::<Vec<_>>();
+
+        let mut placed = vec![];
+        for id in triggered {
+            if let Some(order) = self.fire(client, id).await? {
+                placed.push(order);
+            }
+        }
+
+        Ok(placed)
+    }
+
+    // cancel the take-profit of a triggered pair and place its stop, resuming at the last
+    // persisted step
+    async fn fire(&mut self, client: &LaminarClient, id: u64) -> Result<Option<PlacedOrder>> {
+        let pair = self.pair_mut(id)?;
+        if pair.state == OcoState::Active {
+            // record the transition first so a restart never fires the stop twice
+            pair.state = OcoState::Triggering;
+            self.save()?;
+        }
+
+        let pair = self.pair_mut(id)?;
+        if pair.state == OcoState::Triggering {
+            let (book, side) = (pair.book.clone(), pair.stop.side);
+            let order_id = pair
+                .take_profit_order
+                .clone()
+                .ok_or_else(|| LaminarError::NotFound(format!("take-profit of oco pair {}", id)))?;
+            // an earlier attempt may have cancelled it already
+            let mut order = client.get_order(&order_id).await?;
+            if matches!(order.state, State::Open | State::PartiallyFilled) {
+                let cancel = client
+                    .cancel_order(&book.base, &book.quote, &book.book_owner, &order_id, side)
+                    .await;
+                match cancel {
+                    Ok(_) => {}
+                    // closed in the meantime, its state tells whether it was filled
+                    Err(e) if e.is_not_found_abort() && e.is_abort_in(client.laminar()) => {}
+                    Err(e) => return Err(e),
+                }
+                order = client.get_order(&order_id).await?;
+            }
+
+            let pair = self.pair_mut(id)?;
+            match order.state {
+                State::Filled => pair.state = OcoState::TakeProfitFilled,
+                State::Cancelled | State::Expired if order.remaining_size == 0 => {
+                    pair.state = OcoState::TakeProfitFilled
+                }
+                State::Cancelled | State::Expired => {
+                    pair.stop.size = order.remaining_size;
+                    pair.state = OcoState::StopPending;
+                }
+                _ => {
+                    return Err(LaminarError::Other(anyhow!(
+                        "take-profit {} still open after cancelling it",
+                        order_id
+                    )))
+                }
+            }
+            self.save()?;
+        }
+
+        let pair = self.pair_mut(id)?;
+        if pair.state != OcoState::StopPending {
+            return Ok(None);
+        }
+        let submission = match pair.submission.clone() {
+            Some(submission) => submission,
+            None => {
+                let payload = pair.stop.payload(client)?;
+                let submission = client
+                    .sign_submission(payload, *client.tx_options())
+                    .await?;
+                self.pair_mut(id)?.submission = Some(submission.clone());
+                self.save()?;
+                submission
+            }
+        };
+
+        // stays pending if the stop fails or never commits, signed again by the next call
+        let res = client.submit_once(&submission).await;
+        let pair = self.pair_mut(id)?;
+        match res {
+            Ok(SubmissionStatus::Committed(tx)) => {
+                pair.state = OcoState::StopTriggered;
+                pair.submission = None;
+                self.save()?;
+                Ok(PlacedOrder::from_transaction(&tx))
+            }
+            Ok(_) => {
+                pair.submission = None;
+                self.save()?;
+                Ok(None)
+            }
+            Err(e) if e.is_outcome_unknown() => Err(e),
+            Err(e) => {
+                pair.submission = None;
+                self.save()?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Cancel the take-profit of a pair and disarm its stop.
+    pub async fn cancel(&mut self, client: &LaminarClient, id: u64) -> Result<()> {
+        let pair = self.pair_mut(id)?;
+        if !pair.is_active() {
+            return Ok(());
+        }
+
+        let (book, side) = (pair.book.clone(), pair.stop.side);
+        let order_id = pair
+            .take_profit_order
+            .clone()
+            .ok_or_else(|| LaminarError::NotFound(format!("take-profit of oco pair {}", id)))?;
+        client
+            .cancel_order(&book.base, &book.quote, &book.book_owner, &order_id, side)
+            .await?;
+        self.pair_mut(id)?.state = OcoState::Cancelled;
+        self.save()?;
+
+        Ok(())
+    }
+
+    fn pair_mut(&mut self, id: u64) -> Result<&mut OcoPair> {
+        self.store
+            .pairs
+            .iter_mut()
+            .find(|p| p.id == id)
+            .ok_or_else(|| LaminarError::NotFound(format!("oco pair {}", id)))
+    }
+}
@@ -0,0 +1,206 @@
+use crate::error::{LaminarError, Result};
+use crate::signer::TransactionSigner;
+use crate::{LaminarClient, LaminarTransaction, TxOptions};
+use anyhow::Context;
+use aptos_api_types::PendingTransaction;
+use aptos_sdk::bcs;
+use aptos_sdk::crypto::traits::signing_message;
+use aptos_sdk::types::account_address::AccountAddress;
+use aptos_sdk::types::transaction::{
+    EntryFunction, RawTransaction, SignedTransaction, TransactionPayload,
+};
+use serde::{Deserialize, Serialize};
+
+/// Human readable fields of a raw transaction, for review on the signing machine.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionSummary {
+    pub sender: AccountAddress,
+    pub sequence_number: u64,
+    pub chain_id: u8,
+    pub expiration_timestamp_secs: u64,
+    pub max_gas_amount: u64,
+    pub gas_unit_price: u64,
+    /// Called function with its type arguments, e.g. `0x1::book::create_orderbook<...>`.
+    pub function: String,
+}
+
+impl TransactionSummary {
+    fn new(tx: &RawTransaction) -> Self {
+        let function = match tx.payload() {
+            TransactionPayload::EntryFunction(f) => {
+                let ty_args = f
+                    .ty_args()
+                    .iter()
+                    .map(|t| t.to_string())
+                    .collect::<Vec<_>>();
+                let mut function = format!("{}::{}", f.module(), f.function());
+                if !ty_args.is_empty() {
+                    function.push_str(&format!("<{}>", ty_args.join(", ")));
+                }
+                function
+            }
+            TransactionPayload::Script(_) => "script".to_string(),
+            _ => "other".to_string(),
+        };
+
+        Self {
+            sender: tx.sender(),
+            sequence_number: tx.sequence_number(),
+            chain_id: tx.chain_id().id(),
+            expiration_timestamp_secs: tx.expiration_timestamp_secs(),
+            max_gas_amount: tx.max_gas_amount(),
+            gas_unit_price: tx.gas_unit_price(),
+            function,
+        }
+    }
+}
+
+/// Transaction built on an online machine, to be signed elsewhere, e.g. on an air-gapped
+/// machine for admin operations. Serialized as BCS, or as JSON carrying the BCS bytes and a
+/// `TransactionSummary` that is checked against them when loaded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnsignedTransaction {
+    raw: RawTransaction,
+}
+
+#[derive(Serialize, Deserialize)]
+struct UnsignedTransactionJson {
+    summary: TransactionSummary,
+    /// Hex encoded BCS bytes of the `RawTransaction`.
+    raw_transaction: String,
+}
+
+impl UnsignedTransaction {
+    pub fn new(raw: RawTransaction) -> Self {
+        Self { raw }
+    }
+
+    pub fn raw_transaction(&self) -> &RawTransaction {
+        &self.raw
+    }
+
+    pub fn summary(&self) -> TransactionSummary {
+        TransactionSummary::new(&self.raw)
+    }
+
+    /// Bytes to sign with the sender's key.
+    pub fn signing_message(&self) -> Vec<u8> {
+        signing_message(&self.raw)
+    }
+
+    pub fn to_bcs(&self) -> Result<Vec<u8>> {
+        Ok(bcs::to_bytes(&self.raw)?)
+    }
+
+    pub fn from_bcs(bytes: &[u8]) -> Result<Self> {
+        Ok(Self::new(bcs::from_bytes(bytes)?))
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        let json = UnsignedTransactionJson {
+            summary: self.summary(),
+            raw_transaction: hex::encode(self.to_bcs()?),
+        };
+        Ok(serde_json::to_string_pretty(&json).context("failed serializing transaction")?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        let json = serde_json::from_str::<UnsignedTransactionJson>(json)
+            .map_err(|e| LaminarError::deserialization(e, "unsigned transaction"))?;
+        let bytes = hex::decode(&json.raw_transaction).context("invalid transaction hex")?;
+        let tx = Self::from_bcs(&bytes)?;
+        // the summary is what gets reviewed, it must describe the bytes that get signed
+        if tx.summary() != json.summary {
+            return Err(LaminarError::InvalidInput(
+                "transaction summary does not match the transaction".to_string(),
+            ));
+        }
+
+        Ok(tx)
+    }
+
+    /// Sign the transaction, returning the BCS bytes of the `SignedTransaction` to pass to
+    /// `LaminarClient::submit_signed`.
+    pub async fn sign(&self, signer: &dyn TransactionSigner) -> Result<Vec<u8>> {
+        if signer.address() != self.raw.sender() {
+            return Err(LaminarError::InvalidInput(format!(
+                "transaction is sent from {}, signer is {}",
+                self.raw.sender().to_hex_literal(),
+                signer.address().to_hex_literal()
+            )));
+        }
+        let signed_tx = signer.sign_transaction(self.raw.clone()).await?;
+        Ok(bcs::to_bytes(&signed_tx)?)
+    }
+}
+
+impl From<RawTransaction> for UnsignedTransaction {
+    fn from(raw: RawTransaction) -> Self {
+        Self::new(raw)
+    }
+}
+
+impl LaminarClient {
+    /// Build a raw tx from this client's account without signing it, e.g. to sign it offline.
+    /// The payload goes through the client's `RiskManager` and the next sequence number is
+    /// reserved for the tx. If it is abandoned, later transactions stay pending until the
+    /// sequence number is handed back with `release_sequence_number`.
+    ///
+    /// # Arguments:
+    ///
+    /// * `payload` - Entry function payload to be used in the tx.
+    /// * `options` - Gas and expiration settings, leave time for signing.
+    pub fn build_raw_tx(
+        &self,
+        payload: EntryFunction,
+        options: TxOptions,
+    ) -> Result<RawTransaction> {
+        let payload = TransactionPayload::EntryFunction(payload);
+        self.check_risk(&payload)?;
+        let seq_num = self.sequence_numbers.take();
+        Ok(self.raw_tx(payload, seq_num, options))
+    }
+
+    /// Hand a sequence number reserved by `build_raw_tx` or `prepare_multisig` out again, once
+    /// the tx built with it is abandoned. Only do so if the tx was never submitted elsewhere.
+    /// Returns `false` if the number isn't reserved, e.g. because the tx was submitted through
+    /// this client.
+    ///
+    /// # Arguments:
+    ///
+    /// * `sequence_number` - Sequence number of the abandoned tx.
+    pub fn release_sequence_number(&self, sequence_number: u64) -> bool {
+        self.sequence_numbers.release(sequence_number, None)
+    }
+
+    // record the tx signed for a number reserved by `build_raw_tx`, so it can't be released
+    // anymore, and submit it
+    pub(crate) async fn broadcast_reserved(
+        &self,
+        signed_tx: &SignedTransaction,
+    ) -> Result<PendingTransaction> {
+        let hash = signed_tx.clone().committed_hash().into();
+        self.sequence_numbers
+            .bind(signed_tx.sequence_number(), hash);
+        self.broadcast(signed_tx).await
+    }
+
+    /// Submit a tx signed elsewhere and wait for it.
+    ///
+    /// # Arguments:
+    ///
+    /// * `signed_tx_bytes` - BCS bytes of the `SignedTransaction`, see `UnsignedTransaction::sign`.
+    pub async fn submit_signed(&self, signed_tx_bytes: &[u8]) -> Result<LaminarTransaction> {
+        let signed_tx = bcs::from_bytes::<SignedTransaction>(signed_tx_bytes)?;
+        if signed_tx.sender() != self.address() {
+            return Err(LaminarError::InvalidInput(format!(
+                "transaction is sent from {}, not from this client's account",
+                signed_tx.sender().to_hex_literal()
+            )));
+        }
+        let pending = self.broadcast_reserved(&signed_tx).await?;
+        let lt = self.wait_for_laminar_tx(&pending).await?;
+        self.record_audit(&lt);
+        Ok(lt)
+    }
+}
@@ -0,0 +1,594 @@
+//! Client side order management: orders get an id of our own before they are submitted,
+//! which is correlated to their on-chain `Id` once their place event is seen.
+
+use crate::error::{LaminarError, Result};
+use crate::submission::{Submission, SubmissionStatus};
+use crate::types::events::{
+    AmendOrderEvent, CancelOrderEvent, FillEvent, LaminarEvent, PlaceOrderEvent,
+};
+use crate::types::market::Market;
+use crate::types::order::{Id, Side, State, TimeInForce};
+use crate::{LaminarClient, PlacedOrder};
+use anyhow::anyhow;
+use aptos_sdk::types::account_address::AccountAddress;
+use aptos_sdk::types::transaction::EntryFunction;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Formatter};
+use std::sync::{Mutex, MutexGuard};
+
+/// Id assigned to an order by an `OrderManager` before it is submitted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+pub struct ClientOrderId(pub u64);
+
+impl Display for ClientOrderId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Order submitted through an `OrderManager`.
+#[derive(Clone, Debug, Serialize)]
+pub struct ManagedOrder {
+    pub client_order_id: ClientOrderId,
+    pub market: Market,
+    /// On-chain id, known once the place event of the order was seen.
+    pub order_id: Option<Id>,
+    pub book_id: Option<Id>,
+    pub side: Side,
+    /// Limit price, `None` for market orders.
+    pub price: Option<u64>,
+    pub size: u64,
+    pub time_in_force: TimeInForce,
+    pub post_only: bool,
+    pub remaining_size: u64,
+    pub state: State,
+    pub fills: Vec<FillEvent>,
+    /// Error the submission was rejected with.
+    pub error: Option<String>,
+    /// Last tx signed for the order, kept until it is correlated.
+    #[serde(skip)]
+    pub submission: Option<Submission>,
+}
+
+impl ManagedOrder {
+    // a market order matches the place event of any price
+    fn matches(&self, e: &PlaceOrderEvent) -> bool {
+        self.state == State::PendingSubmit
+            && self.side == e.side
+            && self.size == e.size
+            && self.book_id.as_ref().map_or(true, |b| b == &e.book_id)
+            && match self.price {
+                Some(price) => {
+                    price == e.price
+                        && self.time_in_force == e.time_in_force
+                        && self.post_only == e.post_only
+                }
+                None => true,
+            }
+    }
+
+    fn on_place(&mut self, e: &PlaceOrderEvent) {
+        self.order_id = Some(e.order_id.clone());
+        self.book_id = Some(e.book_id.clone());
+        self.submission = None;
+        self.price = Some(e.price);
+        // IOC and FOK orders never rest, they end filled or with their unfilled size expired
+        self.state = if matches!(e.time_in_force, TimeInForce::GoodTillCanceled) {
+            State::Open
+        } else {
+            State::Expired
+        };
+    }
+
+    fn on_amend(&mut self, e: &AmendOrderEvent) {
+        let filled = self.size.saturating_sub(self.remaining_size);
+        self.price = Some(e.price);
+        self.size = e.size;
+        self.remaining_size = e.size.saturating_sub(filled);
+    }
+
+    fn on_cancel(&mut self, e: &CancelOrderEvent) {
+        if self.state != State::Filled {
+            self.state = e.order_state(self.time_in_force);
+        }
+    }
+
+    fn on_fill(&mut self, e: &FillEvent) {
+        // a fill that doesn't reduce the remaining size was already applied, e.g. from the
+        // transaction before the event stream delivered it
+        if e.remaining_size >= self.remaining_size {
+            return;
+        }
+        self.remaining_size = e.remaining_size;
+        if e.remaining_size == 0 {
+            self.state = State::Filled;
+        } else if self.state == State::Open {
+            self.state = State::PartiallyFilled;
+        }
+        self.fills.push(e.clone());
+    }
+}
+
+/// Keeps the orders submitted through it by client order id, correlates them to their
+/// on-chain `Id` and follows their state from the Laminar event stream.
+///
+/// Place events are correlated to the oldest pending order of the same book, side, price,
+/// size and time in force placed by the manager's account, so events should be applied in the
+/// order they were emitted. Events of orders that were not submitted through the manager are
+/// ignored. Methods take `&self`, so events can be applied while an order is being submitted.
+#[derive(Debug)]
+pub struct OrderManager {
+    state: Mutex<OmsState>,
+}
+
+#[derive(Debug)]
+struct OmsState {
+    owner: AccountAddress,
+    next_id: u64,
+    orders: BTreeMap<ClientOrderId, ManagedOrder>,
+    by_order_id: HashMap<Id, ClientOrderId>,
+    book_ids: HashMap<Market, Id>,
+}
+
+impl OmsState {
+    fn set_book_id(&mut self, market: Market, book_id: Id) {
+        for o in self.orders.values_mut() {
+            if o.market == market && o.book_id.is_none() {
+                o.book_id = Some(book_id.clone());
+            }
+        }
+        self.book_ids.insert(market, book_id);
+    }
+
+    fn register(
+        &mut self,
+        market: Market,
+        side: Side,
+        price: Option<u64>,
+        size: u64,
+        time_in_force: TimeInForce,
+        post_only: bool,
+    ) -> ClientOrderId {
+        let id = ClientOrderId(self.next_id);
+        self.next_id += 1;
+        let order = ManagedOrder {
+            client_order_id: id,
+            book_id: self.book_ids.get(&market).cloned(),
+            market,
+            order_id: None,
+            side,
+            price,
+            size,
+            time_in_force,
+            post_only,
+            remaining_size: size,
+            state: State::PendingSubmit,
+            fills: vec![],
+            error: None,
+            submission: None,
+        };
+        self.orders.insert(id, order);
+        id
+    }
+
+    fn on_placed(&mut self, id: ClientOrderId, placed: &PlacedOrder) {
+        if self.is_match(id, &placed.place) {
+            self.correlate(id, &placed.place);
+        }
+        for fill in &placed.fills {
+            self.on_fill(fill);
+        }
+        if let Some(cancel) = &placed.cancel {
+            self.on_cancel(cancel);
+        }
+    }
+
+    fn on_rejected(&mut self, id: ClientOrderId, error: impl Display) {
+        if let Some(o) = self.orders.get_mut(&id) {
+            if o.state == State::PendingSubmit {
+                o.state = State::Rejected;
+                o.error = Some(error.to_string());
+            }
+        }
+    }
+
+    fn correlate(&mut self, id: ClientOrderId, e: &PlaceOrderEvent) {
+        let Some(o) = self.orders.get_mut(&id) else {
+            return;
+        };
+        o.on_place(e);
+        self.by_order_id.insert(e.order_id.clone(), id);
+        if !self.book_ids.contains_key(&o.market) {
+            let market = o.market.clone();
+            self.set_book_id(market, e.book_id.clone());
+        }
+    }
+
+    fn update<F: FnOnce(&mut ManagedOrder)>(&mut self, order_id: &Id, f: F) {
+        let Some(id) = self.by_order_id.get(order_id) else {
+            return;
+        };
+        if let Some(o) = self.orders.get_mut(id) {
+            f(o);
+        }
+    }
+
+    // a place event can only be of a pending order if it was placed by the manager's account
+    // and is not correlated yet
+    fn is_match(&self, id: ClientOrderId, e: &PlaceOrderEvent) -> bool {
+        e.order_id.addr.inner() == &self.owner
+            && !self.by_order_id.contains_key(&e.order_id)
+            && self.orders.get(&id).map_or(false, |o| o.matches(e))
+    }
+
+    fn on_place(&mut self, e: &PlaceOrderEvent) {
+        let pending = self
+            .orders
+            .values()
+            .find(|o| self.is_match(o.client_order_id, e))
+            .map(|o| o.client_order_id);
+        if let Some(id) = pending {
+            self.correlate(id, e);
+        }
+    }
+
+    fn on_cancel(&mut self, e: &CancelOrderEvent) {
+        self.update(&e.order_id, |o| o.on_cancel(e));
+    }
+
+    fn on_fill(&mut self, e: &FillEvent) {
+        self.update(&e.order_id, |o| o.on_fill(e));
+    }
+
+    // apply the outcome of a submission, returning whether the order is resolved or has to be
+    // signed again
+    fn on_status(&mut self, id: ClientOrderId, status: Result<SubmissionStatus>) -> Result<bool> {
+        let placed = match status {
+            Ok(SubmissionStatus::Committed(tx)) => PlacedOrder::from_transaction(&tx)
+                .ok_or_else(|| LaminarError::NotFound("place order event".to_string()))?,
+            // the tx that used the sequence number may have placed the same order, e.g. when
+            // it was submitted by another process with the same key
+            Ok(SubmissionStatus::Absent {
+                replaced_by: Some(tx),
+            }) => {
+                match PlacedOrder::from_transaction(&tx).filter(|p| self.is_match(id, &p.place)) {
+                    Some(placed) => placed,
+                    None => return Ok(false),
+                }
+            }
+            Ok(SubmissionStatus::Absent { replaced_by: None }) => return Ok(false),
+            Ok(SubmissionStatus::Pending) => {
+                let submission = self.orders.get(&id).and_then(|o| o.submission.clone());
+                return Err(match submission {
+                    Some(s) => LaminarError::Unconfirmed(Box::new(s)),
+                    None => LaminarError::NotFound(format!("submission of order {}", id)),
+                });
+            }
+            Ok(SubmissionStatus::Failed(e)) => {
+                self.on_rejected(id, &e);
+                return Err(e);
+            }
+            Err(e) => {
+                // on other errors the tx may still commit, the order keeps its submission
+                if matches!(
+                    e,
+                    LaminarError::MoveAbort { .. }
+                        | LaminarError::ExecutionFailure { .. }
+                        | LaminarError::Rejected(_)
+                ) {
+                    self.on_rejected(id, &e);
+                }
+                return Err(e);
+            }
+        };
+        self.on_placed(id, &placed);
+        Ok(true)
+    }
+}
+
+impl OrderManager {
+    /// # Arguments:
+    ///
+    /// * `owner` - Account placing the managed orders, place events of other accounts are
+    ///   never correlated to them.
+    pub fn new(owner: AccountAddress) -> Self {
+        Self {
+            state: Mutex::new(OmsState {
+                owner,
+                next_id: 0,
+                orders: BTreeMap::new(),
+                by_order_id: HashMap::new(),
+                book_ids: HashMap::new(),
+            }),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, OmsState> {
+        self.state.lock().expect("order manager lock poisoned")
+    }
+
+    /// Record the on-chain id of a book, so pending orders of the market are only
+    /// correlated to place events of that book. Learned from the first correlated order
+    /// otherwise.
+    pub fn set_book_id(&self, market: Market, book_id: Id) {
+        self.state().set_book_id(market, book_id);
+    }
+
+    /// Register a limit order about to be submitted, returning its client order id.
+    ///
+    /// # Arguments:
+    ///
+    /// * `market` - Market of the order.
+    /// * `side` - Side of the order.
+    /// * `price` - Limit price.
+    /// * `size` - Size of the order.
+    /// * `time_in_force` - Time in force of the order.
+    /// * `post_only` - Whether the order must not take liquidity.
+    pub fn submit_limit(
+        &self,
+        market: Market,
+        side: Side,
+        price: u64,
+        size: u64,
+        time_in_force: TimeInForce,
+        post_only: bool,
+    ) -> ClientOrderId {
+        self.state()
+            .register(market, side, Some(price), size, time_in_force, post_only)
+    }
+
+    /// Register a market order about to be submitted, returning its client order id.
+    pub fn submit_market(&self, market: Market, side: Side, size: u64) -> ClientOrderId {
+        self.state().register(
+            market,
+            side,
+            None,
+            size,
+            TimeInForce::ImmediateOrCancel,
+            false,
+        )
+    }
+
+    /// Correlate a submitted order to the result of its transaction, applying the fills and
+    /// cancellation it contains.
+    ///
+    /// If the event stream already matched the place event to another identical pending
+    /// order, that correlation is kept and this order gets the next matching place event.
+    pub fn on_placed(&self, id: ClientOrderId, placed: &PlacedOrder) {
+        self.state().on_placed(id, placed);
+    }
+
+    /// Mark a submitted order as rejected by the chain.
+    pub fn on_rejected(&self, id: ClientOrderId, error: impl Display) {
+        self.state().on_rejected(id, error);
+    }
+
+    pub fn on_place(&self, e: &PlaceOrderEvent) {
+        self.state().on_place(e);
+    }
+
+    pub fn on_amend(&self, e: &AmendOrderEvent) {
+        self.state().update(&e.order_id, |o| o.on_amend(e));
+    }
+
+    pub fn on_cancel(&self, e: &CancelOrderEvent) {
+        self.state().on_cancel(e);
+    }
+
+    pub fn on_fill(&self, e: &FillEvent) {
+        self.state().on_fill(e);
+    }
+
+    /// Apply any Laminar event.
+    pub fn apply(&self, event: &LaminarEvent) {
+        match event {
+            LaminarEvent::PlaceOrder(e) => self.on_place(e),
+            LaminarEvent::AmendOrder(e) => self.on_amend(e),
+            LaminarEvent::CancelOrder(e) => self.on_cancel(e),
+            LaminarEvent::FillEvent(e) => self.on_fill(e),
+            LaminarEvent::CreateOrderBook(_) | LaminarEvent::Unknown { .. } => {}
+        }
+    }
+
+    /// Submit a limit order through `client`, returning its client order id.
+    ///
+    /// The order is submitted with `LaminarClient::submit_once`, so it is never placed twice.
+    /// An order that failed or was aborted by the chain is kept as `State::Rejected`. If the
+    /// outcome of its tx can't be established, `LaminarError::Unconfirmed` is returned and the
+    /// order stays pending until its place event is applied or `resubmit` resolves it.
+    ///
+    /// # Arguments:
+    ///
+    /// * `client` - Client submitting the order.
+    /// * `market` - Market of the order.
+    /// * `side` - Side of the order.
+    /// * `price` - Limit price.
+    /// * `size` - Size of the order.
+    /// * `time_in_force` - Time in force of the order.
+    /// * `post_only` - Whether the order must not take liquidity.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn place_limit_order(
+        &self,
+        client: &LaminarClient,
+        market: &Market,
+        side: Side,
+        price: u64,
+        size: u64,
+        time_in_force: TimeInForce,
+        post_only: bool,
+    ) -> Result<ClientOrderId> {
+        let id = self.submit_limit(market.clone(), side, price, size, time_in_force, post_only);
+        self.submit(client, id).await
+    }
+
+    /// Submit a market order through `client`, returning its client order id. Errors are
+    /// handled like in `place_limit_order`.
+    ///
+    /// # Arguments:
+    ///
+    /// * `client` - Client submitting the order.
+    /// * `market` - Market of the order.
+    /// * `side` - Side of the order.
+    /// * `size` - Size of the order.
+    pub async fn place_market_order(
+        &self,
+        client: &LaminarClient,
+        market: &Market,
+        side: Side,
+        size: u64,
+    ) -> Result<ClientOrderId> {
+        let id = self.submit_market(market.clone(), side, size);
+        self.submit(client, id).await
+    }
+
+    /// Resolve an order left pending by an unconfirmed submission. If its tx was committed
+    /// the order is correlated to it, and it is only submitted again once the tx is provably
+    /// absent and no tx that replaced it placed a matching order.
+    ///
+    /// # Arguments:
+    ///
+    /// * `client` - Client submitting the order.
+    /// * `id` - Client order id of the order.
+    pub async fn resubmit(
+        &self,
+        client: &LaminarClient,
+        id: ClientOrderId,
+    ) -> Result<ClientOrderId> {
+        let o = self
+            .get(id)
+            .ok_or_else(|| LaminarError::NotFound(format!("order {}", id)))?;
+        if o.state != State::PendingSubmit {
+            return Ok(id);
+        }
+        let Some(submission) = o.submission else {
+            return Err(LaminarError::InvalidInput(format!(
+                "order {} was not submitted by the order manager",
+                id
+            )));
+        };
+
+        let status = client.submission_status(&submission).await;
+        if self.state().on_status(id, status)? {
+            return Ok(id);
+        }
+        self.submit(client, id).await
+    }
+
+    fn payload(&self, client: &LaminarClient, id: ClientOrderId) -> Result<EntryFunction> {
+        let o = self
+            .get(id)
+            .ok_or_else(|| LaminarError::NotFound(format!("order {}", id)))?;
+        let m = &o.market;
+        match o.price {
+            Some(price) => client.place_limit_order_payload(
+                &m.base,
+                &m.quote,
+                &m.book_owner,
+                o.side,
+                price,
+                o.size,
+                o.time_in_force,
+                o.post_only,
+            ),
+            None => {
+                client.place_market_order_payload(&m.base, &m.quote, &m.book_owner, o.side, o.size)
+            }
+        }
+    }
+
+    // sign and submit the placement until it is committed or its outcome is unknown, signing
+    // again only while the previous tx is provably absent. The state is only locked between
+    // awaits.
+    async fn submit(&self, client: &LaminarClient, id: ClientOrderId) -> Result<ClientOrderId> {
+        let payload = self.payload(client, id)?;
+        let options = *client.tx_options();
+        for _ in 0..=options.max_retries {
+            let submission = match client.sign_submission(payload.clone(), options).await {
+                Ok(submission) => submission,
+                // nothing was submitted
+                Err(e) => {
+                    self.on_rejected(id, &e);
+                    return Err(e);
+                }
+            };
+            if let Some(o) = self.state().orders.get_mut(&id) {
+                o.submission = Some(submission.clone());
+            }
+            let status = client.submit_once(&submission).await;
+            if self.state().on_status(id, status)? {
+                return Ok(id);
+            }
+        }
+
+        Err(LaminarError::Other(anyhow!(
+            "failed submitting order {}",
+            id
+        )))
+    }
+
+    pub fn get(&self, id: ClientOrderId) -> Option<ManagedOrder> {
+        self.state().orders.get(&id).cloned()
+    }
+
+    pub fn by_order_id(&self, order_id: &Id) -> Option<ManagedOrder> {
+        let state = self.state();
+        state.orders.get(state.by_order_id.get(order_id)?).cloned()
+    }
+
+    /// Client order id of an on-chain order.
+    pub fn client_order_id(&self, order_id: &Id) -> Option<ClientOrderId> {
+        self.state().by_order_id.get(order_id).copied()
+    }
+
+    /// Stop managing an order.
+    pub fn remove(&self, id: ClientOrderId) -> Option<ManagedOrder> {
+        let mut state = self.state();
+        let o = state.orders.remove(&id)?;
+        if let Some(order_id) = &o.order_id {
+            state.by_order_id.remove(order_id);
+        }
+        Some(o)
+    }
+
+    pub fn len(&self) -> usize {
+        self.state().orders.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.state().orders.is_empty()
+    }
+
+    fn filter<F: Fn(&ManagedOrder) -> bool>(&self, f: F) -> Vec<ManagedOrder> {
+        self.state()
+            .orders
+            .values()
+            .filter(|o| f(o))
+            .cloned()
+            .collect()
+    }
+
+    /// All orders, in submission order.
+    pub fn orders(&self) -> Vec<ManagedOrder> {
+        self.filter(|_| true)
+    }
+
+    /// Orders resting on their book, in submission order.
+    pub fn open_orders(&self) -> Vec<ManagedOrder> {
+        self.filter(|o| o.state.is_open())
+    }
+
+    /// Orders still waiting for their place event.
+    pub fn pending_orders(&self) -> Vec<ManagedOrder> {
+        self.orders_by_state(State::PendingSubmit)
+    }
+
+    /// All orders of a market, including closed ones.
+    pub fn orders_in_book(&self, market: &Market) -> Vec<ManagedOrder> {
+        self.filter(|o| &o.market == market)
+    }
+
+    pub fn orders_by_state(&self, state: State) -> Vec<ManagedOrder> {
+        self.filter(|o| o.state == state)
+    }
+}
@@ -0,0 +1,134 @@
+use crate::error::{LaminarError, Result};
+use crate::faucet::{request_faucet, FAUCET_TIMEOUT};
+use crate::network::Network;
+use crate::rate_limit::{Endpoint, RateLimiter};
+use crate::time::{sleep, Instant};
+use crate::LaminarClient;
+use aptos_sdk::move_types::language_storage::TypeTag;
+use aptos_sdk::rest_client::Client;
+use aptos_sdk::types::account_address::AccountAddress;
+use aptos_sdk::types::LocalAccount;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Steps run by `LaminarClient::onboard` after connecting.
+#[derive(Clone, Debug, Default)]
+pub struct OnboardingOptions {
+    /// Fund the account with this many octas from the network's faucet before connecting.
+    /// If `None` the account must already exist on chain.
+    pub faucet_amount: Option<u64>,
+    /// Coins to register a `CoinStore` for, e.g. the base and quote coins of the books to trade.
+    pub coins: Vec<TypeTag>,
+    /// Register the account to trade on Laminar.
+    pub register_user: bool,
+    /// Rate limiter for the node requests made while onboarding, also set on the client.
+    pub rate_limiter: Option<Arc<RateLimiter>>,
+}
+
+impl OnboardingOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_faucet_amount(mut self, amount: u64) -> Self {
+        self.faucet_amount = Some(amount);
+        self
+    }
+
+    pub fn with_coin(mut self, coin: TypeTag) -> Self {
+        self.coins.push(coin);
+        self
+    }
+
+    pub fn with_register_user(mut self, register_user: bool) -> Self {
+        self.register_user = register_user;
+        self
+    }
+
+    pub fn with_rate_limiter(mut self, rate_limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+}
+
+impl LaminarClient {
+    /// Generate a new `LocalAccount` with a random Ed25519 key. The account does not exist on
+    /// chain until it is funded.
+    pub fn generate_account() -> LocalAccount {
+        LocalAccount::generate(&mut rand::rngs::OsRng)
+    }
+
+    /// Generate a new account and onboard it, see `onboard`.
+    ///
+    /// # Arguments:
+    ///
+    /// * `network` - `Network` to create the account on.
+    /// * `options` - `OnboardingOptions` selecting the onboarding steps.
+    pub async fn create_account(network: Network, options: &OnboardingOptions) -> Result<Self> {
+        Self::onboard(network, Self::generate_account(), options).await
+    }
+
+    /// Get an account ready to trade: fund it from the faucet, connect to `network`, register
+    /// `CoinStore`s for the given coins and register the account on Laminar, as selected by
+    /// `options`. Coins the account is already registered for are skipped.
+    ///
+    /// # Arguments:
+    ///
+    /// * `network` - `Network` to connect to, see `connect_to`.
+    /// * `account` - `LocalAccount` to onboard.
+    /// * `options` - `OnboardingOptions` selecting the onboarding steps.
+    pub async fn onboard(
+        network: Network,
+        account: LocalAccount,
+        options: &OnboardingOptions,
+    ) -> Result<Self> {
+        if let Some(amount) = options.faucet_amount {
+            let faucet_url = network
+                .faucet_url()
+                .ok_or_else(|| LaminarError::InvalidInput(format!("{} has no faucet", network)))?;
+            request_faucet(&faucet_url, account.address(), amount).await?;
+            wait_for_account(
+                &Client::new(network.node_url()),
+                account.address(),
+                options.rate_limiter.as_deref(),
+            )
+            .await?;
+        }
+
+        let mut client = Self::connect_to(network, account).await?;
+        client.set_rate_limiter(options.rate_limiter.clone());
+        for coin in &options.coins {
+            if !client.is_registered_for_coin(coin).await? {
+                client
+                    .build_and_submit_tx(Self::register_for_coin(coin)?)
+                    .await?;
+            }
+        }
+        if options.register_user {
+            client.register_user().await?;
+        }
+
+        Ok(client)
+    }
+}
+
+// the faucet returns before its transaction is committed
+async fn wait_for_account(
+    aptos_client: &Client,
+    address: AccountAddress,
+    rate_limiter: Option<&RateLimiter>,
+) -> Result<()> {
+    let deadline = Instant::now() + FAUCET_TIMEOUT;
+    loop {
+        if let Some(rate_limiter) = rate_limiter {
+            rate_limiter.acquire(Endpoint::Index).await;
+        }
+        if aptos_client.get_account(address).await.is_ok() {
+            return Ok(());
+        }
+        if Instant::now() >= deadline {
+            return Err(LaminarError::DeadlineExceeded { hash: None });
+        }
+        sleep(Duration::from_millis(500)).await;
+    }
+}
@@ -0,0 +1,490 @@
+//! Paper trading against a local copy of a live book.
+
+use crate::checkpoint::EventCursor;
+use crate::error::{LaminarError, Result};
+use crate::market_data::{MarketDataFeed, MarketDataUpdate};
+use crate::position::Position;
+use crate::sim::{EventReplay, SimBook, SimError};
+use crate::time::unix_now_micros;
+use crate::trading::Trader;
+use crate::types::events::{AmendOrderEvent, CancelOrderEvent, FillEvent, LaminarEvent};
+use crate::types::l2::L2Book;
+use crate::types::market::Market;
+use crate::types::order::{Id, Order, Side, TimeInForce};
+use crate::{LaminarClient, PlacedOrder};
+use aptos_sdk::crypto::HashValue;
+use aptos_sdk::move_types::language_storage::TypeTag;
+use aptos_sdk::types::account_address::AccountAddress;
+use async_trait::async_trait;
+use std::sync::{Mutex, MutexGuard};
+
+struct PaperBook {
+    book: SimBook,
+    replay: EventReplay,
+    position: Position,
+    // events of paper orders placed, amended or cancelled since the last replay
+    unreported: Vec<LaminarEvent>,
+}
+
+/// Trades a single book on a `SimBook` instead of submitting transactions.
+///
+/// The simulator is seeded with the live book and kept in sync by replaying the events of
+/// other accounts from a `MarketDataFeed`, read from the version after the snapshot, so paper
+/// orders fill against the real order flow.
+/// It implements `Trader` like `LaminarClient`, so a `StrategyRunner` can run on it, and
+/// rejects orders with the simulator's abort codes, see `sim::EINVALID_ARGUMENT`. Call `sync`
+/// regularly to receive the fills of resting paper orders, unless a `StrategyRunner` drives it.
+///
+/// Live orders of the account are left out of the paper book, as their ids would collide with
+/// the paper ones. While the feed is degraded to snapshot polling the simulator lags behind, it
+/// catches up with the missed events once the feed recovers.
+pub struct PaperTrader {
+    market: Market,
+    laminar: AccountAddress,
+    account: AccountAddress,
+    feed: tokio::sync::Mutex<MarketDataFeed>,
+    state: Mutex<PaperBook>,
+}
+
+impl PaperTrader {
+    /// Seed a paper book from the live book of `market`.
+    ///
+    /// # Arguments:
+    ///
+    /// * `client` - Client used to read the live book, its account places the paper orders.
+    /// * `market` - Market to paper trade.
+    pub async fn connect(client: &LaminarClient, market: Market) -> Result<Self> {
+        let mut book = client
+            .fetch_orderbook(&market.base, &market.quote, &market.book_owner)
+            .await?;
+        let account = client.address();
+        for levels in [&mut book.bids, &mut book.asks] {
+            levels.values_mut().for_each(|orders| {
+                orders.retain(|o| o.id.addr.inner() != &account);
+            });
+            levels.retain(|_, orders| !orders.is_empty());
+        }
+        // replay exactly the events committed after the snapshot
+        let cursor = match book.ledger_version {
+            Some(version) => EventCursor {
+                version: version + 1,
+            },
+            None => client.fetch_event_cursor().await?,
+        };
+        let feed =
+            MarketDataFeed::new(market.base.clone(), market.quote.clone(), market.book_owner)
+                .with_cursor(cursor);
+        let state = PaperBook {
+            book: SimBook::from_order_book(&book),
+            replay: EventReplay::new(),
+            position: Position::new(book.id.clone(), book.instrument.clone()),
+            unreported: vec![],
+        };
+
+        Ok(Self {
+            market,
+            laminar: *client.laminar(),
+            account,
+            feed: tokio::sync::Mutex::new(feed),
+            state: Mutex::new(state),
+        })
+    }
+
+    pub fn market(&self) -> &Market {
+        &self.market
+    }
+
+    pub fn address(&self) -> AccountAddress {
+        self.account
+    }
+
+    /// Position built from the fills of paper orders.
+    pub fn position(&self) -> Position {
+        self.state().position.clone()
+    }
+
+    /// Aggregated depth of the paper book, `depth` levels per side.
+    pub fn book(&self, depth: usize) -> L2Book {
+        self.state().book.to_l2(depth)
+    }
+
+    /// Resting paper orders.
+    pub fn open_orders(&self) -> Vec<Order> {
+        let state = self.state();
+        [Side::Bid, Side::Ask]
+            .into_iter()
+            .flat_map(|side| state.book.orders(side))
+            .filter(|o| self.own(&o.id))
+            .cloned()
+            .collect()
+    }
+
+    /// Replay the live events received since the last call, returning the events of paper
+    /// orders since the last call: the ones of orders placed, amended or cancelled, and the
+    /// ones the live events caused, e.g. fills of resting orders.
+    pub async fn sync(&self, client: &LaminarClient) -> Result<Vec<LaminarEvent>> {
+        let updates = self.feed.lock().await.poll(client).await?;
+        let events = updates
+            .into_iter()
+            .filter_map(|update| match update {
+                MarketDataUpdate::Event(event) => Some(event),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        Ok(self.replay(&events))
+    }
+
+    // replay live events of other accounts, returning the events of paper orders since the
+    // last call: the ones of orders placed, amended or cancelled and the ones the replay caused
+    fn replay(&self, events: &[LaminarEvent]) -> Vec<LaminarEvent> {
+        let mut state = self.state();
+        let PaperBook {
+            book,
+            replay,
+            position,
+            unreported,
+        } = &mut *state;
+
+        let mut res = std::mem::take(unreported);
+        for event in events {
+            if self.own_event(event) {
+                continue;
+            }
+            // replay errors mean the paper orders changed the outcome of a live order, e.g.
+            // an amend of an order they filled, which the paper book cannot follow
+            let Ok(sim_events) = replay.apply(book, event) else {
+                trace_event!(debug, "live event not replayed");
+                continue;
+            };
+            for sim_event in sim_events {
+                if !self.own_event(&sim_event) {
+                    continue;
+                }
+                if let LaminarEvent::FillEvent(fill) = &sim_event {
+                    position.apply(fill);
+                }
+                res.push(sim_event);
+            }
+        }
+
+        res
+    }
+
+    /// Place a limit order on the paper book.
+    ///
+    /// # Arguments:
+    ///
+    /// * `base` - Base coin `TypeTag`.
+    /// * `quote` - Quote coin `TypeTag`.
+    /// * `book_owner` - Book owner `AccountAddress`.
+    /// * `side` - Side.
+    /// * `price` - Limit price in units.
+    /// * `size` - Size in units.
+    /// * `time_in_force` - Time in force.
+    /// * `post_only` - Reject the order if it would take liquidity.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn place_limit_order(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        book_owner: &AccountAddress,
+        side: Side,
+        price: u64,
+        size: u64,
+        time_in_force: TimeInForce,
+        post_only: bool,
+    ) -> Result<PlacedOrder> {
+        self.check_market(base, quote, book_owner)?;
+        let mut state = self.state();
+        let events = state
+            .book
+            .place_limit_order(
+                self.account,
+                side,
+                price,
+                size,
+                time_in_force,
+                post_only,
+                unix_now_micros(),
+            )
+            .map_err(|e| self.abort(e))?;
+        self.placed(&mut state, events)
+    }
+
+    /// Place a market order on the paper book.
+    ///
+    /// # Arguments:
+    ///
+    /// * `base` - Base coin `TypeTag`.
+    /// * `quote` - Quote coin `TypeTag`.
+    /// * `book_owner` - Book owner `AccountAddress`.
+    /// * `side` - Side.
+    /// * `size` - Size in units.
+    pub async fn place_market_order(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        book_owner: &AccountAddress,
+        side: Side,
+        size: u64,
+    ) -> Result<PlacedOrder> {
+        self.check_market(base, quote, book_owner)?;
+        let mut state = self.state();
+        let events = state
+            .book
+            .place_market_order(self.account, side, size, unix_now_micros())
+            .map_err(|e| self.abort(e))?;
+        self.placed(&mut state, events)
+    }
+
+    /// Amend a paper order.
+    ///
+    /// # Arguments:
+    ///
+    /// * `base` - Base coin `TypeTag`.
+    /// * `quote` - Quote coin `TypeTag`.
+    /// * `book_owner` - Book owner `AccountAddress`.
+    /// * `order_id` - Id of the order.
+    /// * `side` - Side of the order.
+    /// * `price` - New price in units.
+    /// * `size` - New size in units.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn amend_order(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        book_owner: &AccountAddress,
+        order_id: &Id,
+        side: Side,
+        price: u64,
+        size: u64,
+    ) -> Result<AmendOrderEvent> {
+        self.check_market(base, quote, book_owner)?;
+        let mut state = self.state();
+        let events = state
+            .book
+            .amend_order(self.account, order_id, side, price, size, unix_now_micros())
+            .map_err(|e| self.abort(e))?;
+        state.unreported.extend(events.iter().cloned());
+        events
+            .into_iter()
+            .find_map(|e| match e {
+                LaminarEvent::AmendOrder(e) => Some(e),
+                _ => None,
+            })
+            .ok_or_else(|| LaminarError::NotFound("amend order event".to_string()))
+    }
+
+    /// Cancel a paper order.
+    ///
+    /// # Arguments:
+    ///
+    /// * `base` - Base coin `TypeTag`.
+    /// * `quote` - Quote coin `TypeTag`.
+    /// * `book_owner` - Book owner `AccountAddress`.
+    /// * `order_id` - Id of the order.
+    /// * `side` - Side of the order.
+    pub async fn cancel_order(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        book_owner: &AccountAddress,
+        order_id: &Id,
+        side: Side,
+    ) -> Result<CancelOrderEvent> {
+        self.check_market(base, quote, book_owner)?;
+        let mut state = self.state();
+        let events = state
+            .book
+            .cancel_order(self.account, order_id, side, unix_now_micros())
+            .map_err(|e| self.abort(e))?;
+        state.unreported.extend(events.iter().cloned());
+        events
+            .into_iter()
+            .find_map(|e| match e {
+                LaminarEvent::CancelOrder(e) => Some(e),
+                _ => None,
+            })
+            .ok_or_else(|| LaminarError::NotFound("cancel order event".to_string()))
+    }
+
+    /// Cancel all resting paper orders.
+    pub async fn cancel_all_orders(&self) -> Result<Vec<CancelOrderEvent>> {
+        let orders = self.open_orders();
+        let mut res = vec![];
+        for order in orders {
+            let Market {
+                base,
+                quote,
+                book_owner,
+            } = &self.market;
+            res.push(
+                self.cancel_order(base, quote, book_owner, &order.id, order.side)
+                    .await?,
+            );
+        }
+
+        Ok(res)
+    }
+
+    fn placed(&self, state: &mut PaperBook, events: Vec<LaminarEvent>) -> Result<PlacedOrder> {
+        let mut place = None;
+        let mut fills: Vec<FillEvent> = vec![];
+        let mut cancel = None;
+        state
+            .unreported
+            .extend(events.iter().filter(|e| self.own_event(e)).cloned());
+        for event in events {
+            match event {
+                LaminarEvent::PlaceOrder(e) => place = Some(e),
+                LaminarEvent::FillEvent(f) if self.own(&f.order_id) => {
+                    state.position.apply(&f);
+                    fills.push(f);
+                }
+                LaminarEvent::CancelOrder(e) if self.own(&e.order_id) => cancel = Some(e),
+                _ => {}
+            }
+        }
+        let place = place.ok_or_else(|| LaminarError::NotFound("place order event".to_string()))?;
+        // fills of paper orders resting on the other side are not reported here
+        fills.retain(|f| f.order_id == place.order_id);
+        let cancel = cancel.filter(|c| c.order_id == place.order_id);
+
+        Ok(PlacedOrder {
+            place,
+            fills,
+            cancel,
+        })
+    }
+
+    fn check_market(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        book_owner: &AccountAddress,
+    ) -> Result<()> {
+        if base != &self.market.base
+            || quote != &self.market.quote
+            || book_owner != &self.market.book_owner
+        {
+            return Err(LaminarError::BookNotFound(format!(
+                "paper trading {}/{} of {}",
+                base, quote, book_owner
+            )));
+        }
+        Ok(())
+    }
+
+    // rejections look like the aborted transaction, with the simulator's abort codes
+    fn abort(&self, e: SimError) -> LaminarError {
+        let vm_status = format!(
+            "Move abort in {}::book: {:?}(0x{:x}): {}",
+            self.laminar.to_hex_literal(),
+            e,
+            e.abort_code(),
+            e
+        );
+        LaminarError::from_vm_status(HashValue::zero().into(), &vm_status)
+    }
+
+    fn own(&self, id: &Id) -> bool {
+        id.addr.inner() == &self.account
+    }
+
+    fn own_event(&self, event: &LaminarEvent) -> bool {
+        event.order_id().map_or(false, |id| self.own(id))
+    }
+
+    fn state(&self) -> MutexGuard<'_, PaperBook> {
+        self.state.lock().expect("paper book lock poisoned")
+    }
+}
+
+#[async_trait]
+impl Trader for PaperTrader {
+    fn address(&self) -> AccountAddress {
+        self.account
+    }
+
+    async fn fetch_depth(&self, market: &Market, depth: usize) -> Result<L2Book> {
+        self.check_market(&market.base, &market.quote, &market.book_owner)?;
+        Ok(self.book(depth))
+    }
+
+    async fn order_events(
+        &self,
+        market: &Market,
+        events: &[LaminarEvent],
+    ) -> Result<Vec<LaminarEvent>> {
+        self.check_market(&market.base, &market.quote, &market.book_owner)?;
+        Ok(self.replay(events))
+    }
+
+    async fn place_limit_order(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        book_owner: &AccountAddress,
+        side: Side,
+        price: u64,
+        size: u64,
+        time_in_force: TimeInForce,
+        post_only: bool,
+    ) -> Result<PlacedOrder> {
+        PaperTrader::place_limit_order(
+            self,
+            base,
+            quote,
+            book_owner,
+            side,
+            price,
+            size,
+            time_in_force,
+            post_only,
+        )
+        .await
+    }
+
+    async fn place_market_order(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        book_owner: &AccountAddress,
+        side: Side,
+        size: u64,
+    ) -> Result<PlacedOrder> {
+        PaperTrader::place_market_order(self, base, quote, book_owner, side, size).await
+    }
+
+    async fn amend_order(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        book_owner: &AccountAddress,
+        order_id: &Id,
+        side: Side,
+        price: u64,
+        size: u64,
+    ) -> Result<AmendOrderEvent> {
+        PaperTrader::amend_order(self, base, quote, book_owner, order_id, side, price, size).await
+    }
+
+    async fn cancel_order(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        book_owner: &AccountAddress,
+        order_id: &Id,
+        side: Side,
+    ) -> Result<CancelOrderEvent> {
+        PaperTrader::cancel_order(self, base, quote, book_owner, order_id, side).await
+    }
+
+    /// Cancel all paper orders if `books` contains the paper traded market.
+    async fn cancel_all_orders(&self, books: &[Market]) -> Result<Vec<CancelOrderEvent>> {
+        if !books.contains(&self.market) {
+            return Ok(vec![]);
+        }
+        PaperTrader::cancel_all_orders(self).await
+    }
+}
@@ -0,0 +1,288 @@
+use crate::batch::LaminarAction;
+use crate::error::Result;
+use crate::time::unix_now_secs;
+use crate::types::order::{Id, Side, TimeInForce};
+use crate::TxOptions;
+use aptos_sdk::bcs;
+use aptos_sdk::move_types::ident_str;
+use aptos_sdk::move_types::identifier::Identifier;
+use aptos_sdk::move_types::language_storage::{ModuleId, TypeTag};
+use aptos_sdk::transaction_builder::TransactionBuilder;
+use aptos_sdk::types::account_address::AccountAddress;
+use aptos_sdk::types::chain_id::ChainId;
+use aptos_sdk::types::transaction::{EntryFunction, RawTransaction, TransactionPayload};
+
+/// Builds Laminar payloads and raw transactions without a connection to a node, e.g. to
+/// hand them to a browser wallet for signing.
+///
+/// `LaminarClient`'s payload methods use a `PayloadBuilder` for the client's Laminar address.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PayloadBuilder {
+    laminar: AccountAddress,
+}
+
+impl PayloadBuilder {
+    /// # Arguments:
+    ///
+    /// * `laminar` - Address of the account that holds the laminar modules.
+    pub fn new(laminar: AccountAddress) -> Self {
+        Self { laminar }
+    }
+
+    pub fn laminar(&self) -> &AccountAddress {
+        &self.laminar
+    }
+
+    fn entry_function(
+        &self,
+        function: Identifier,
+        ty_args: Vec<TypeTag>,
+        args: Vec<Vec<u8>>,
+    ) -> EntryFunction {
+        EntryFunction::new(
+            ModuleId::new(self.laminar, ident_str!("book").to_owned()),
+            function,
+            ty_args,
+            args,
+        )
+    }
+
+    /// Payload registering the sender to trade on Laminar.
+    pub fn register_user(&self) -> EntryFunction {
+        self.entry_function(ident_str!("register_user").to_owned(), vec![], vec![])
+    }
+
+    /// Payload creating an `OrderBook`.
+    ///
+    /// # Arguments:
+    ///
+    /// * `base` - Aptos `TypeTag` of the `OrderBook` base coin.
+    /// * `quote` - Aptos `TypeTag` of the `OrderBook` quote coin.
+    /// * `price_decimals` - Number of decimals of order prices.
+    /// * `size_decimals` - Number of decimals of order sizes.
+    /// * `min_size_amount` - Minimum order size for orders in the `OrderBook`.
+    pub fn create_orderbook(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        price_decimals: u8,
+        size_decimals: u8,
+        min_size_amount: u64,
+    ) -> Result<EntryFunction> {
+        Ok(self.entry_function(
+            ident_str!("create_orderbook").to_owned(),
+            vec![base.clone(), quote.clone()],
+            vec![
+                bcs::to_bytes(&price_decimals)?,
+                bcs::to_bytes(&size_decimals)?,
+                bcs::to_bytes(&min_size_amount)?,
+            ],
+        ))
+    }
+
+    /// Payload placing a limit order.
+    ///
+    /// # Arguments:
+    ///
+    /// * `base` - Aptos `TypeTag` of the orderbook base coin.
+    /// * `quote` - Aptos `TypeTag` of the orderbook quote coin.
+    /// * `book_owner` - Address of the account that owns the `OrderBook`.
+    /// * `side` - Bid or Ask.
+    /// * `price` - Price in units.
+    /// * `size` - Size in units.
+    /// * `time_in_force` - `TimeInForce` of the order.
+    /// * `post_only` - Reject the order if it would take liquidity.
+    #[allow(clippy::too_many_arguments)]
+    pub fn place_limit_order(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        book_owner: &AccountAddress,
+        side: Side,
+        price: u64,
+        size: u64,
+        time_in_force: TimeInForce,
+        post_only: bool,
+    ) -> Result<EntryFunction> {
+        Ok(self.entry_function(
+            ident_str!("place_limit_order").to_owned(),
+            vec![base.clone(), quote.clone()],
+            vec![
+                bcs::to_bytes(book_owner)?,
+                bcs::to_bytes(&side)?,
+                bcs::to_bytes(&price)?,
+                bcs::to_bytes(&size)?,
+                bcs::to_bytes(&time_in_force)?,
+                bcs::to_bytes(&post_only)?,
+            ],
+        ))
+    }
+
+    /// Payload placing a market order.
+    ///
+    /// # Arguments:
+    ///
+    /// * `base` - Aptos `TypeTag` of the orderbook base coin.
+    /// * `quote` - Aptos `TypeTag` of the orderbook quote coin.
+    /// * `book_owner` - Address of the account that owns the `OrderBook`.
+    /// * `side` - Bid or Ask.
+    /// * `size` - Size in units.
+    pub fn place_market_order(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        book_owner: &AccountAddress,
+        side: Side,
+        size: u64,
+    ) -> Result<EntryFunction> {
+        Ok(self.entry_function(
+            ident_str!("place_market_order").to_owned(),
+            vec![base.clone(), quote.clone()],
+            vec![
+                bcs::to_bytes(book_owner)?,
+                bcs::to_bytes(&side)?,
+                bcs::to_bytes(&size)?,
+            ],
+        ))
+    }
+
+    /// Payload amending an order.
+    ///
+    /// # Arguments:
+    ///
+    /// * `base` - Aptos `TypeTag` of the orderbook base coin.
+    /// * `quote` - Aptos `TypeTag` of the orderbook quote coin.
+    /// * `book_owner` - Address of the account that owns the `OrderBook`.
+    /// * `order_id` - ID of order to amend.
+    /// * `side` - Side of the order.
+    /// * `price` - New price, the current price to keep it.
+    /// * `size` - New size, the current size to keep it.
+    #[allow(clippy::too_many_arguments)]
+    pub fn amend_order(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        book_owner: &AccountAddress,
+        order_id: &Id,
+        side: Side,
+        price: u64,
+        size: u64,
+    ) -> Result<EntryFunction> {
+        Ok(self.entry_function(
+            ident_str!("amend_order").to_owned(),
+            vec![base.clone(), quote.clone()],
+            vec![
+                bcs::to_bytes(book_owner)?,
+                bcs::to_bytes(&order_id.creation_num.0)?,
+                bcs::to_bytes(&side)?,
+                bcs::to_bytes(&price)?,
+                bcs::to_bytes(&size)?,
+            ],
+        ))
+    }
+
+    /// Payload canceling an order.
+    ///
+    /// # Arguments:
+    ///
+    /// * `base` - Aptos `TypeTag` of the orderbook base coin.
+    /// * `quote` - Aptos `TypeTag` of the orderbook quote coin.
+    /// * `book_owner` - Address of the account that owns the `OrderBook`.
+    /// * `order_id` - ID of order to cancel.
+    /// * `side` - Side of the order.
+    pub fn cancel_order(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        book_owner: &AccountAddress,
+        order_id: &Id,
+        side: Side,
+    ) -> Result<EntryFunction> {
+        Ok(self.entry_function(
+            ident_str!("cancel_order").to_owned(),
+            vec![base.clone(), quote.clone()],
+            vec![
+                bcs::to_bytes(book_owner)?,
+                bcs::to_bytes(&order_id.creation_num.0)?,
+                bcs::to_bytes(&side)?,
+            ],
+        ))
+    }
+
+    /// Payload of a single `LaminarAction`.
+    ///
+    /// # Arguments:
+    ///
+    /// * `base` - Aptos `TypeTag` of the orderbook base coin.
+    /// * `quote` - Aptos `TypeTag` of the orderbook quote coin.
+    /// * `book_owner` - Address of the account that owns the `OrderBook`.
+    /// * `action` - Action to create the payload for.
+    pub fn action(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        book_owner: &AccountAddress,
+        action: &LaminarAction,
+    ) -> Result<EntryFunction> {
+        match action {
+            LaminarAction::PlaceLimit {
+                side,
+                price,
+                size,
+                time_in_force,
+                post_only,
+            } => self.place_limit_order(
+                base,
+                quote,
+                book_owner,
+                *side,
+                *price,
+                *size,
+                *time_in_force,
+                *post_only,
+            ),
+            LaminarAction::PlaceMarket { side, size } => {
+                self.place_market_order(base, quote, book_owner, *side, *size)
+            }
+            LaminarAction::Amend {
+                order_id,
+                side,
+                price,
+                size,
+            } => self.amend_order(base, quote, book_owner, order_id, *side, *price, *size),
+            LaminarAction::Cancel { order_id, side } => {
+                self.cancel_order(base, quote, book_owner, order_id, *side)
+            }
+        }
+    }
+
+    /// Build a raw tx to sign, e.g. with `UnsignedTransaction::sign`. It expires
+    /// `options.expiration_secs` after now.
+    ///
+    /// # Arguments:
+    ///
+    /// * `payload` - Payload of the tx.
+    /// * `sender` - Address of the account sending the tx.
+    /// * `sequence_number` - Sequence number of the tx on the sender's account.
+    /// * `chain_id` - Id of the chain the tx is sent to.
+    /// * `options` - Gas and expiration settings.
+    pub fn raw_transaction(
+        &self,
+        payload: TransactionPayload,
+        sender: AccountAddress,
+        sequence_number: u64,
+        chain_id: u8,
+        options: TxOptions,
+    ) -> RawTransaction {
+        TransactionBuilder::new(
+            payload,
+            unix_now_secs() + options.expiration_secs,
+            ChainId::new(chain_id),
+        )
+        .sender(sender)
+        .sequence_number(sequence_number)
+        .max_gas_amount(options.max_gas_amount)
+        .gas_unit_price(options.gas_unit_price)
+        .build()
+    }
+}
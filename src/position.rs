@@ -0,0 +1,200 @@
+use crate::types::decimal;
+use crate::types::events::FillEvent;
+use crate::types::order::{Id, Instrument, Side};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Net position in one book, in decimal base and quote amounts.
+///
+/// `FillEvent::fee` is taken to be charged in quote coin units.
+#[derive(Clone, Debug, Serialize)]
+pub struct Position {
+    pub book_id: Id,
+    #[serde(skip)]
+    instrument: Instrument,
+    /// Net base position, negative when short.
+    pub size: f64,
+    /// Average entry price of the open position, 0 when flat.
+    pub avg_entry_price: f64,
+    /// Realized PnL before fees.
+    pub realized_pnl: f64,
+    pub fees: f64,
+}
+
+impl Position {
+    pub fn new(book_id: Id, instrument: Instrument) -> Self {
+        Self {
+            book_id,
+            instrument,
+            size: 0.0,
+            avg_entry_price: 0.0,
+            realized_pnl: 0.0,
+            fees: 0.0,
+        }
+    }
+
+    pub fn is_flat(&self) -> bool {
+        self.size == 0.0
+    }
+
+    /// Fold a fill of this account into the position. Fills of other books are ignored.
+    pub fn apply(&mut self, fill: &FillEvent) {
+        if fill.book_id != self.book_id {
+            return;
+        }
+
+        let price = self.instrument.price_from_units(fill.price);
+        let qty = self.instrument.size_from_units(fill.fill_size);
+        let signed_qty = match fill.side {
+            Side::Bid => qty,
+            Side::Ask => -qty,
+        };
+        self.fees += decimal::from_units(fill.fee, self.instrument.quote_decimals);
+
+        if self.size == 0.0 || self.size.signum() == signed_qty.signum() {
+            let total = self.size.abs() + qty;
+            self.avg_entry_price = (self.avg_entry_price * self.size.abs() + price * qty) / total;
+            self.size += signed_qty;
+            return;
+        }
+
+        let closed = qty.min(self.size.abs());
+        self.realized_pnl += closed * (price - self.avg_entry_price) * self.size.signum();
+        self.size += signed_qty;
+        if self.size.abs() < f64::EPSILON {
+            self.size = 0.0;
+            self.avg_entry_price = 0.0;
+        } else if self.size.signum() == signed_qty.signum() {
+            // the fill flipped the position, the remainder was opened at the fill price
+            self.avg_entry_price = price;
+        }
+    }
+
+    /// PnL of the open position marked at `mark_price`.
+    pub fn unrealized_pnl(&self, mark_price: f64) -> f64 {
+        self.size * (mark_price - self.avg_entry_price)
+    }
+
+    /// Realized plus unrealized PnL, net of fees.
+    pub fn total_pnl(&self, mark_price: f64) -> f64 {
+        self.realized_pnl + self.unrealized_pnl(mark_price) - self.fees
+    }
+}
+
+/// `Position`s across several books.
+#[derive(Clone, Debug, Default)]
+pub struct PositionTracker {
+    positions: HashMap<Id, Position>,
+}
+
+impl PositionTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start tracking a book. Fills of books that are not tracked are ignored.
+    pub fn track(&mut self, book_id: Id, instrument: Instrument) {
+        self.positions
+            .entry(book_id.clone())
+            .or_insert_with(|| Position::new(book_id, instrument));
+    }
+
+    pub fn apply(&mut self, fill: &FillEvent) {
+        if let Some(p) = self.positions.get_mut(&fill.book_id) {
+            p.apply(fill);
+        }
+    }
+
+    pub fn position(&self, book_id: &Id) -> Option<&Position> {
+        self.positions.get(book_id)
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = &Position> {
+        self.positions.values()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_api_types::{Address, U64};
+    use aptos_sdk::types::account_address::AccountAddress;
+
+    fn id(creation_num: u64) -> Id {
+        Id {
+            creation_num: U64(creation_num),
+            addr: Address::from(AccountAddress::ONE),
+        }
+    }
+
+    fn instrument() -> Instrument {
+        Instrument {
+            owner: AccountAddress::ONE,
+            price_decimals: 2,
+            size_decimals: 2,
+            min_size_amount: 1,
+            base_decimals: 8,
+            quote_decimals: 6,
+            rounding: Default::default(),
+        }
+    }
+
+    fn fill(book: u64, side: Side, price: u64, size: u64, fee: u64) -> FillEvent {
+        FillEvent {
+            book_id: id(book),
+            order_id: id(1),
+            side,
+            price,
+            fill_size: size,
+            fee,
+            fee_rate: 0,
+            time: 0,
+            remaining_size: 0,
+            is_maker: true,
+        }
+    }
+
+    #[test]
+    fn test_average_realize_and_flip() {
+        let mut position = Position::new(id(0), instrument());
+        position.apply(&fill(0, Side::Bid, 10_000, 200, 500_000));
+        position.apply(&fill(0, Side::Bid, 11_000, 200, 500_000));
+        assert_eq!(position.size, 4.0);
+        assert_eq!(position.avg_entry_price, 105.0);
+
+        position.apply(&fill(0, Side::Ask, 12_000, 300, 500_000));
+        assert_eq!(position.size, 1.0);
+        assert_eq!(position.avg_entry_price, 105.0);
+        assert_eq!(position.realized_pnl, 45.0);
+
+        // closes the long at a loss of 5 and opens a short at the fill price
+        position.apply(&fill(0, Side::Ask, 10_000, 200, 0));
+        assert_eq!(position.size, -1.0);
+        assert_eq!(position.avg_entry_price, 100.0);
+        assert_eq!(position.realized_pnl, 40.0);
+        assert_eq!(position.fees, 1.5);
+        assert_eq!(position.unrealized_pnl(90.0), 10.0);
+        assert_eq!(position.total_pnl(90.0), 48.5);
+
+        position.apply(&fill(0, Side::Bid, 9_000, 100, 0));
+        assert!(position.is_flat());
+        assert_eq!(position.avg_entry_price, 0.0);
+        assert_eq!(position.realized_pnl, 50.0);
+    }
+
+    #[test]
+    fn test_other_books_are_ignored() {
+        let mut tracker = PositionTracker::new();
+        tracker.track(id(0), instrument());
+        tracker.apply(&fill(0, Side::Bid, 10_000, 100, 0));
+        tracker.apply(&fill(1, Side::Bid, 10_000, 100, 0));
+
+        assert_eq!(tracker.position(&id(0)).unwrap().size, 1.0);
+        assert!(tracker.position(&id(1)).is_none());
+        assert_eq!(tracker.positions().count(), 1);
+
+        let mut position = Position::new(id(0), instrument());
+        position.apply(&fill(1, Side::Bid, 10_000, 100, 0));
+        assert!(position.is_flat());
+    }
+}
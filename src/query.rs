@@ -0,0 +1,154 @@
+use crate::types::events::{
+    AmendOrderEvent, CancelOrderEvent, CreateOrderBookEvent, FillEvent, PlaceOrderEvent,
+};
+use crate::types::order::Id;
+use std::sync::Arc;
+
+/// Ids carried by a Laminar event, used by `EventQuery` to filter by book and order.
+pub trait EventIds {
+    fn book_id(&self) -> &Id;
+
+    fn order_id(&self) -> Option<&Id>;
+}
+
+impl EventIds for CreateOrderBookEvent {
+    fn book_id(&self) -> &Id {
+        &self.book_id
+    }
+
+    fn order_id(&self) -> Option<&Id> {
+        None
+    }
+}
+
+impl EventIds for PlaceOrderEvent {
+    fn book_id(&self) -> &Id {
+        &self.book_id
+    }
+
+    fn order_id(&self) -> Option<&Id> {
+        Some(&self.order_id)
+    }
+}
+
+impl EventIds for AmendOrderEvent {
+    fn book_id(&self) -> &Id {
+        &self.book_id
+    }
+
+    fn order_id(&self) -> Option<&Id> {
+        Some(&self.order_id)
+    }
+}
+
+impl EventIds for CancelOrderEvent {
+    fn book_id(&self) -> &Id {
+        &self.book_id
+    }
+
+    fn order_id(&self) -> Option<&Id> {
+        Some(&self.order_id)
+    }
+}
+
+impl EventIds for FillEvent {
+    fn book_id(&self) -> &Id {
+        &self.book_id
+    }
+
+    fn order_id(&self) -> Option<&Id> {
+        Some(&self.order_id)
+    }
+}
+
+type Predicate<E> = Arc<dyn Fn(&E) -> bool + Send + Sync>;
+
+/// Query over one event stream of the account's `OrderBookStore`.
+///
+/// The node can only restrict the sequence-number range, so `start` and `end` bound what is
+/// downloaded while the remaining filters are applied to each page as it arrives.
+#[derive(Clone)]
+pub struct EventQuery<E> {
+    pub start: u64,
+    pub end: u64,
+    /// Ignore events emitted after this ledger version.
+    pub max_version: Option<u64>,
+    book_id: Option<Id>,
+    order_id: Option<Id>,
+    predicates: Vec<Predicate<E>>,
+    limit: Option<usize>,
+}
+
+impl<E> Default for EventQuery<E> {
+    fn default() -> Self {
+        Self {
+            start: 0,
+            end: u64::MAX,
+            max_version: None,
+            book_id: None,
+            order_id: None,
+            predicates: vec![],
+            limit: None,
+        }
+    }
+}
+
+impl<E: EventIds> EventQuery<E> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only fetch events with sequence numbers in `[start, end)`.
+    pub fn in_range(mut self, start: u64, end: u64) -> Self {
+        self.start = start;
+        self.end = end;
+        self
+    }
+
+    /// Only return events emitted at or before ledger `version`, e.g. to line events up
+    /// with a snapshot fetched with `fetch_orderbook_at_version`.
+    pub fn at_version(mut self, version: u64) -> Self {
+        self.max_version = Some(version);
+        self
+    }
+
+    pub fn for_book(mut self, book_id: &Id) -> Self {
+        self.book_id = Some(book_id.clone());
+        self
+    }
+
+    pub fn for_order(mut self, order_id: &Id) -> Self {
+        self.order_id = Some(order_id.clone());
+        self
+    }
+
+    /// Add a predicate, all predicates need to hold for an event to match.
+    pub fn filter<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&E) -> bool + Send + Sync + 'static,
+    {
+        self.predicates.push(Arc::new(predicate));
+        self
+    }
+
+    /// Stop fetching once `limit` events matched.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn max_results(&self) -> Option<usize> {
+        self.limit
+    }
+
+    pub fn matches(&self, event: &E) -> bool {
+        self.book_id
+            .as_ref()
+            .map_or(true, |id| event.book_id() == id)
+            && self
+                .order_id
+                .as_ref()
+                .map_or(true, |id| event.order_id() == Some(id))
+            && self.predicates.iter().all(|p| p(event))
+    }
+}
@@ -0,0 +1,187 @@
+use aptos_sdk::types::transaction::EntryFunction;
+use std::collections::{BTreeMap, HashMap};
+
+/// Kind of order operation held in a `SubmissionQueue`.
+/// Variants are ordered by priority, cancels are always submitted first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ActionKind {
+    Cancel = 0,
+    Amend = 1,
+    Place = 2,
+    Other = 3,
+}
+
+/// Handle returned when enqueueing, used to cancel the action before it is submitted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Ticket(u64);
+
+#[derive(Clone, Debug)]
+pub struct QueuedAction {
+    pub ticket: Ticket,
+    pub kind: ActionKind,
+    pub payload: EntryFunction,
+}
+
+/// Priority queue of pending order operations.
+/// Actions are popped by `ActionKind` priority and in FIFO order within the same kind.
+#[derive(Debug, Default)]
+pub struct SubmissionQueue {
+    next_ticket: u64,
+    actions: BTreeMap<(ActionKind, Ticket), EntryFunction>,
+    kinds: HashMap<Ticket, ActionKind>,
+}
+
+impl SubmissionQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a payload to the queue.
+    ///
+    /// # Arguments:
+    ///
+    /// * `kind` - `ActionKind` of the payload, determines its priority.
+    /// * `payload` - Entry function payload to be submitted.
+    pub fn enqueue(&mut self, kind: ActionKind, payload: EntryFunction) -> Ticket {
+        let ticket = Ticket(self.next_ticket);
+        self.next_ticket += 1;
+        self.actions.insert((kind, ticket), payload);
+        self.kinds.insert(ticket, kind);
+        ticket
+    }
+
+    pub fn enqueue_cancel(&mut self, payload: EntryFunction) -> Ticket {
+        self.enqueue(ActionKind::Cancel, payload)
+    }
+
+    pub fn enqueue_amend(&mut self, payload: EntryFunction) -> Ticket {
+        self.enqueue(ActionKind::Amend, payload)
+    }
+
+    pub fn enqueue_place(&mut self, payload: EntryFunction) -> Ticket {
+        self.enqueue(ActionKind::Place, payload)
+    }
+
+    /// Remove an action before it gets submitted.
+    /// Returns the removed payload, or `None` if it was already popped or cancelled.
+    pub fn cancel(&mut self, ticket: Ticket) -> Option<EntryFunction> {
+        let kind = self.kinds.remove(&ticket)?;
+        self.actions.remove(&(kind, ticket))
+    }
+
+    /// Remove every queued action of the given kind, e.g. all pending placements
+    /// when a kill switch is engaged.
+    pub fn cancel_kind(&mut self, kind: ActionKind) -> Vec<QueuedAction> {
+        let tickets = self
+            .actions
+            .keys()
+            .filter(|(k, _)| *k == kind)
+            .map(|(_, t)| *t)
+            .collect::<Vec<_>>();
+        tickets
+            .into_iter()
+            .filter_map(|ticket| {
+                self.cancel(ticket).map(|payload| QueuedAction {
+                    ticket,
+                    kind,
+                    payload,
+                })
+            })
+            .collect()
+    }
+
+    /// Pop the highest priority action.
+    pub fn pop(&mut self) -> Option<QueuedAction> {
+        let ((kind, ticket), payload) = self.actions.pop_first()?;
+        self.kinds.remove(&ticket);
+        Some(QueuedAction {
+            ticket,
+            kind,
+            payload,
+        })
+    }
+
+    pub fn peek_kind(&self) -> Option<ActionKind> {
+        self.actions.keys().next().map(|(k, _)| *k)
+    }
+
+    pub fn contains(&self, ticket: Ticket) -> bool {
+        self.kinds.contains_key(&ticket)
+    }
+
+    pub fn len(&self) -> usize {
+        self.actions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_sdk::move_types::identifier::Identifier;
+    use aptos_sdk::move_types::language_storage::ModuleId;
+    use aptos_sdk::types::account_address::AccountAddress;
+
+    fn payload(function: &str) -> EntryFunction {
+        EntryFunction::new(
+            ModuleId::new(AccountAddress::ONE, Identifier::new("book").unwrap()),
+            Identifier::new(function).unwrap(),
+            vec![],
+            vec![],
+        )
+    }
+
+    fn drain(queue: &mut SubmissionQueue) -> Vec<String> {
+        std::iter::from_fn(|| queue.pop())
+            .map(|a| a.payload.function().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_cancels_overtake_places() {
+        let mut queue = SubmissionQueue::new();
+        queue.enqueue_place(payload("place_a"));
+        queue.enqueue_amend(payload("amend_a"));
+        queue.enqueue_place(payload("place_b"));
+        queue.enqueue_cancel(payload("cancel_a"));
+
+        assert_eq!(queue.peek_kind(), Some(ActionKind::Cancel));
+        assert_eq!(
+            drain(&mut queue),
+            vec!["cancel_a", "amend_a", "place_a", "place_b"]
+        );
+    }
+
+    #[test]
+    fn test_fifo_within_kind() {
+        let mut queue = SubmissionQueue::new();
+        for i in 0..5 {
+            queue.enqueue_cancel(payload(&format!("cancel_{}", i)));
+            queue.enqueue_place(payload(&format!("place_{}", i)));
+        }
+
+        let order = drain(&mut queue);
+        let expected = (0..5)
+            .map(|i| format!("cancel_{}", i))
+            .chain((0..5).map(|i| format!("place_{}", i)))
+            .collect::<Vec<_>>();
+        assert_eq!(order, expected);
+    }
+
+    #[test]
+    fn test_cancelled_ticket_is_not_popped() {
+        let mut queue = SubmissionQueue::new();
+        let first = queue.enqueue_place(payload("place_a"));
+        let second = queue.enqueue_place(payload("place_b"));
+
+        assert!(queue.cancel(first).is_some());
+        assert!(queue.cancel(first).is_none());
+        assert!(!queue.contains(first));
+        assert!(queue.contains(second));
+        assert_eq!(drain(&mut queue), vec!["place_b"]);
+        assert!(queue.is_empty());
+    }
+}
@@ -0,0 +1,223 @@
+use crate::error::Result;
+use crate::types::order::{Id, Order, OrderBook, Side, TimeInForce};
+use crate::LaminarClient;
+use aptos_sdk::move_types::language_storage::TypeTag;
+use aptos_sdk::types::account_address::AccountAddress;
+
+/// Target resting order of a quote ladder, in price and size units.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QuoteLevel {
+    pub price: u64,
+    pub size: u64,
+}
+
+impl QuoteLevel {
+    pub fn new(price: u64, size: u64) -> Self {
+        Self { price, size }
+    }
+}
+
+/// Change needed to move one resting order or target level to the desired ladder.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum QuoteChange {
+    /// Resting order already matches the level at `index`.
+    Keep { index: usize, order_id: Id },
+    /// Resting order is moved to the level at `index`.
+    Amend {
+        index: usize,
+        order_id: Id,
+        level: QuoteLevel,
+    },
+    /// Resting order is not part of the ladder anymore.
+    Cancel { order_id: Id },
+    /// No resting order is left for the level at `index`.
+    Place { index: usize, level: QuoteLevel },
+}
+
+/// Changes turning the resting orders of one side into a target ladder.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QuotePlan {
+    pub side: Side,
+    pub changes: Vec<QuoteChange>,
+}
+
+impl QuotePlan {
+    /// Diff resting orders against target levels.
+    ///
+    /// Orders already at a target price and remaining size are kept. The other orders are
+    /// matched with the remaining levels from the top of the book down and amended, surplus
+    /// orders are cancelled and levels without an order are placed.
+    pub fn new(side: Side, resting: &[Order], levels: &[QuoteLevel]) -> Self {
+        let mut changes = vec![];
+        let mut unmatched_levels = vec![];
+        let mut unmatched_orders = resting.iter().collect::<Vec<_>>();
+        for (index, level) in levels.iter().enumerate() {
+            let exact = unmatched_orders
+                .iter()
+                .position(|o| o.price == level.price && o.remaining_size == level.size);
+            match exact {
+                Some(i) => {
+                    let order = unmatched_orders.remove(i);
+                    changes.push(QuoteChange::Keep {
+                        index,
+                        order_id: order.id.clone(),
+                    });
+                }
+                None => unmatched_levels.push((index, *level)),
+            }
+        }
+
+        let best_first = |a: u64, b: u64| match side {
+            Side::Bid => b.cmp(&a),
+            Side::Ask => a.cmp(&b),
+        };
+        unmatched_orders.sort_by(|a, b| best_first(a.price, b.price));
+        unmatched_levels.sort_by(|(_, a), (_, b)| best_first(a.price, b.price));
+
+        let mut orders = unmatched_orders.into_iter();
+        for (index, level) in unmatched_levels {
+            match orders.next() {
+                Some(order) => changes.push(QuoteChange::Amend {
+                    index,
+                    order_id: order.id.clone(),
+                    level,
+                }),
+                None => changes.push(QuoteChange::Place { index, level }),
+            }
+        }
+        changes.extend(orders.map(|o| QuoteChange::Cancel {
+            order_id: o.id.clone(),
+        }));
+
+        Self { side, changes }
+    }
+
+    pub fn is_noop(&self) -> bool {
+        self.changes
+            .iter()
+            .all(|c| matches!(c, QuoteChange::Keep { .. }))
+    }
+}
+
+/// Ids of the resting orders making up a quote ladder, in the order of the target levels.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Quotes {
+    pub bids: Vec<Id>,
+    pub asks: Vec<Id>,
+}
+
+impl LaminarClient {
+    /// Update this client's resting orders on a book to match a two-sided ladder.
+    ///
+    /// Both sides are diffed with `QuotePlan`. Surplus orders are cancelled first, then
+    /// orders are moved with `replace_order`, amending in place where the book allows it,
+    /// and missing levels are placed as post only GTC orders.
+    ///
+    /// # Arguments:
+    ///
+    /// * `base` - Aptos `TypeTag` of the orderbook base coin.
+    /// * `quote` - Aptos `TypeTag` of the orderbook quote coin.
+    /// * `book_owner` - Address of the account that owns the `OrderBook`.
+    /// * `bid_levels` - Target bids, best first.
+    /// * `ask_levels` - Target asks, best first.
+    pub async fn quote(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        book_owner: &AccountAddress,
+        bid_levels: &[QuoteLevel],
+        ask_levels: &[QuoteLevel],
+    ) -> Result<Quotes> {
+        let book = self.fetch_orderbook(base, quote, book_owner).await?;
+        let plans = self.quote_plans(&book, bid_levels, ask_levels);
+
+        for plan in &plans {
+            for change in &plan.changes {
+                if let QuoteChange::Cancel { order_id } = change {
+                    self.cancel_order(base, quote, book_owner, order_id, plan.side)
+                        .await?;
+                }
+            }
+        }
+
+        let mut quotes = Quotes {
+            bids: Vec::with_capacity(bid_levels.len()),
+            asks: Vec::with_capacity(ask_levels.len()),
+        };
+        for plan in &plans {
+            let mut ids = vec![];
+            for change in &plan.changes {
+                match change {
+                    QuoteChange::Keep { index, order_id } => ids.push((*index, order_id.clone())),
+                    QuoteChange::Amend {
+                        index,
+                        order_id,
+                        level,
+                    } => {
+                        let id = self
+                            .replace_order(
+                                base,
+                                quote,
+                                book_owner,
+                                order_id,
+                                plan.side,
+                                level.price,
+                                level.size,
+                                true,
+                            )
+                            .await?;
+                        ids.push((*index, id));
+                    }
+                    QuoteChange::Place { index, level } => {
+                        let placed = self
+                            .place_limit_order(
+                                base,
+                                quote,
+                                book_owner,
+                                plan.side,
+                                level.price,
+                                level.size,
+                                TimeInForce::GoodTillCanceled,
+                                true,
+                            )
+                            .await?;
+                        ids.push((*index, placed.order_id().clone()));
+                    }
+                    QuoteChange::Cancel { .. } => {}
+                }
+            }
+
+            ids.sort_by_key(|(index, _)| *index);
+            let ids = ids.into_iter().map(|(_, id)| id).collect();
+            match plan.side {
+                Side::Bid => quotes.bids = ids,
+                Side::Ask => quotes.asks = ids,
+            }
+        }
+
+        Ok(quotes)
+    }
+
+    fn quote_plans(
+        &self,
+        book: &OrderBook,
+        bid_levels: &[QuoteLevel],
+        ask_levels: &[QuoteLevel],
+    ) -> [QuotePlan; 2] {
+        let owner = self.address();
+        let mine = |orders: Vec<&Order>| {
+            orders
+                .into_iter()
+                .filter(|o| o.id.addr.inner() == &owner)
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+        let bids = mine(book.bids.values().flatten().collect());
+        let asks = mine(book.asks.values().flatten().collect());
+
+        [
+            QuotePlan::new(Side::Bid, &bids, bid_levels),
+            QuotePlan::new(Side::Ask, &asks, ask_levels),
+        ]
+    }
+}
@@ -0,0 +1,134 @@
+use crate::time::{sleep, Instant};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+// keeps waits finite for zero or negative rates
+const MIN_RATE: f64 = 0.001;
+
+/// Kind of node request, each with its own budget in a `RateLimiter`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Endpoint {
+    /// Ledger info and account lookups.
+    Index,
+    /// Account resources and table items.
+    Resources,
+    Events,
+    /// Transaction lookups, one per poll while waiting for a submitted transaction.
+    Transactions,
+    Simulate,
+    Submit,
+}
+
+/// Sustained rate and burst size of a token bucket.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RateLimit {
+    pub per_second: f64,
+    pub burst: u32,
+}
+
+impl RateLimit {
+    pub fn new(per_second: f64, burst: u32) -> Self {
+        Self { per_second, burst }
+    }
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    limit: RateLimit,
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    fn new(limit: RateLimit) -> Self {
+        Self {
+            limit,
+            tokens: limit.burst as f64,
+            updated: Instant::now(),
+        }
+    }
+
+    // take a token if `take` is set, or return how long to wait for the next one
+    fn try_take(&mut self, now: Instant, take: bool) -> Option<Duration> {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens =
+            (self.tokens + elapsed * self.limit.per_second).min(self.limit.burst.max(1) as f64);
+        self.updated = now;
+        if self.tokens >= 1.0 {
+            if take {
+                self.tokens -= 1.0;
+            }
+            None
+        } else {
+            let missing = 1.0 - self.tokens;
+            Some(Duration::from_secs_f64(
+                missing / self.limit.per_second.max(MIN_RATE),
+            ))
+        }
+    }
+}
+
+/// Token bucket rate limiter for the requests a `LaminarClient` makes to the node, so
+/// polling loops stay within the limits of managed fullnode providers.
+///
+/// Each `Endpoint` takes from its own bucket if a limit is set for it, and from the shared
+/// bucket if a total limit is set. Requests wait for a token instead of failing. Share one
+/// limiter between clients using the same API key with `Arc`.
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    total: Option<Mutex<TokenBucket>>,
+    endpoints: HashMap<Endpoint, Mutex<TokenBucket>>,
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limit all requests together.
+    pub fn with_total_limit(mut self, limit: RateLimit) -> Self {
+        self.total = Some(Mutex::new(TokenBucket::new(limit)));
+        self
+    }
+
+    /// Limit the requests to one endpoint.
+    pub fn with_limit(mut self, endpoint: Endpoint, limit: RateLimit) -> Self {
+        self.endpoints
+            .insert(endpoint, Mutex::new(TokenBucket::new(limit)));
+        self
+    }
+
+    /// Wait until a request to `endpoint` is allowed.
+    pub async fn acquire(&self, endpoint: Endpoint) {
+        self.wait(endpoint, true).await
+    }
+
+    /// Wait until a request to `endpoint` would be allowed, without taking its token. The
+    /// request made afterwards still has to `acquire`.
+    pub async fn ready(&self, endpoint: Endpoint) {
+        self.wait(endpoint, false).await
+    }
+
+    async fn wait(&self, endpoint: Endpoint, take: bool) {
+        if let Some(bucket) = self.endpoints.get(&endpoint) {
+            Self::take(bucket, take).await;
+        }
+        if let Some(bucket) = &self.total {
+            Self::take(bucket, take).await;
+        }
+    }
+
+    async fn take(bucket: &Mutex<TokenBucket>, take: bool) {
+        loop {
+            let wait = bucket
+                .lock()
+                .expect("rate limiter lock poisoned")
+                .try_take(Instant::now(), take);
+            match wait {
+                Some(wait) => sleep(wait).await,
+                None => return,
+            }
+        }
+    }
+}
@@ -0,0 +1,161 @@
+//! Publishing market data to Redis for consumers that don't talk to Aptos.
+
+use crate::error::{LaminarError, Result};
+use crate::market_data::{MarketDataFeed, MarketDataUpdate};
+use crate::tape::TradeTape;
+use crate::types::events::LaminarEvent;
+use crate::types::order::Id;
+use crate::LaminarClient;
+use anyhow::Context;
+use redis::aio::ConnectionManager;
+use serde::Serialize;
+
+/// Publishes the updates of a `MarketDataFeed` to Redis pub/sub channels and, optionally,
+/// capped streams.
+///
+/// Updates of a book go to these keys, each message a JSON document:
+///
+/// * `{prefix}:{book_id}:levels` - `LevelUpdate`s, while the feed polls snapshots.
+/// * `{prefix}:{book_id}:trades` - `Trade`s, deduplicated from the maker and taker fills.
+/// * `{prefix}:{book_id}:orders` - Place, amend, cancel and fill events.
+/// * `{prefix}:{book_id}:status` - Feed switches between events and snapshots.
+///
+/// Stream entries hold the message in their `data` field.
+pub struct RedisPublisher {
+    conn: ConnectionManager,
+    prefix: String,
+    channels: bool,
+    stream_max_len: Option<usize>,
+    tape: TradeTape,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+enum FeedStatus<'a> {
+    Degraded { reason: &'a str },
+    Recovered,
+}
+
+impl RedisPublisher {
+    /// Connect to Redis, reconnecting automatically when the connection drops.
+    ///
+    /// # Arguments:
+    ///
+    /// * `url` - Redis url, e.g. `redis://127.0.0.1:6379`.
+    pub async fn connect(url: &str) -> Result<Self> {
+        let client = redis::Client::open(url)
+            .map_err(|e| LaminarError::InvalidInput(format!("redis url: {}", e)))?;
+        let conn = ConnectionManager::new(client)
+            .await
+            .context("failed connecting to redis")?;
+
+        Ok(Self {
+            conn,
+            prefix: "laminar".to_string(),
+            channels: true,
+            stream_max_len: None,
+            tape: TradeTape::default(),
+        })
+    }
+
+    /// Prefix of the channel and stream keys, `laminar` by default.
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Also append messages to streams, trimmed to about `max_len` entries each.
+    pub fn with_streams(mut self, max_len: usize) -> Self {
+        self.stream_max_len = Some(max_len);
+        self
+    }
+
+    /// Whether to publish to pub/sub channels, e.g. disable it to only write streams.
+    pub fn with_channels(mut self, channels: bool) -> Self {
+        self.channels = channels;
+        self
+    }
+
+    /// Key of the channel and stream `kind` of a book.
+    pub fn key(&self, book_id: &Id, kind: &str) -> String {
+        format!("{}:{}:{}", self.prefix, book_id, kind)
+    }
+
+    /// Publish the updates of a book in one round trip, returning the number of messages sent.
+    ///
+    /// # Arguments:
+    ///
+    /// * `book_id` - `OrderBook` Id the updates belong to.
+    /// * `updates` - Updates as returned by `MarketDataFeed::poll`.
+    pub async fn publish(&mut self, book_id: &Id, updates: &[MarketDataUpdate]) -> Result<usize> {
+        let mut messages = vec![];
+        for update in updates {
+            match update {
+                MarketDataUpdate::Event(event) => {
+                    if let LaminarEvent::FillEvent(fill) = event {
+                        if let Some(trade) = self.tape.push(fill) {
+                            messages.push(("trades", to_json(&trade)?));
+                        }
+                    }
+                    messages.push(("orders", to_json(event)?));
+                }
+                MarketDataUpdate::Levels(levels) => messages.push(("levels", to_json(levels)?)),
+                MarketDataUpdate::Degraded { reason } => {
+                    messages.push(("status", to_json(&FeedStatus::Degraded { reason })?))
+                }
+                MarketDataUpdate::Recovered => {
+                    messages.push(("status", to_json(&FeedStatus::Recovered)?))
+                }
+            }
+        }
+        if messages.is_empty() {
+            return Ok(0);
+        }
+
+        let mut pipe = redis::pipe();
+        for (kind, payload) in &messages {
+            let key = self.key(book_id, kind);
+            if self.channels {
+                pipe.cmd("PUBLISH").arg(&key).arg(payload).ignore();
+            }
+            if let Some(max_len) = self.stream_max_len {
+                pipe.cmd("XADD")
+                    .arg(&key)
+                    .arg("MAXLEN")
+                    .arg("~")
+                    .arg(max_len)
+                    .arg("*")
+                    .arg("data")
+                    .arg(payload)
+                    .ignore();
+            }
+        }
+        pipe.query_async::<_, ()>(&mut self.conn)
+            .await
+            .context("failed publishing to redis")?;
+
+        Ok(messages.len())
+    }
+
+    /// Poll `feed` once, publish its updates and commit the feed's checkpoint. Returns the
+    /// number of messages sent.
+    pub async fn forward(
+        &mut self,
+        client: &LaminarClient,
+        feed: &mut MarketDataFeed,
+    ) -> Result<usize> {
+        let updates = feed.poll(client).await?;
+        let Some(book_id) = feed.book_id().cloned() else {
+            return Ok(0);
+        };
+        let sent = self.publish(&book_id, &updates).await?;
+        feed.commit()?;
+        trace_event!(debug, book_id = %book_id, messages = sent, "published to redis");
+
+        Ok(sent)
+    }
+}
+
+fn to_json<T: Serialize + ?Sized>(value: &T) -> Result<String> {
+    serde_json::to_string(value).map_err(|e| LaminarError::deserialization(e, "redis message"))
+}
@@ -0,0 +1,115 @@
+use crate::error::Result;
+use crate::types::events::CreateOrderBookEvent;
+use crate::types::market::Market;
+use crate::types::order::Id;
+use crate::LaminarClient;
+use aptos_sdk::move_types::language_storage::TypeTag;
+use aptos_sdk::types::account_address::AccountAddress;
+use std::collections::HashMap;
+
+/// Order book known to a `MarketRegistry`, with the symbols of its coins.
+#[derive(Clone, Debug)]
+pub struct MarketEntry {
+    /// Pair symbol, e.g. "APT/USDC".
+    pub symbol: String,
+    pub base_symbol: String,
+    pub quote_symbol: String,
+    pub market: Market,
+    pub book: CreateOrderBookEvent,
+}
+
+/// Order books indexed by their coin symbols and book ids.
+///
+/// Symbols come from the `0x1::coin::CoinInfo` of each coin and are not unique on chain, so
+/// several books can share a pair symbol, e.g. when two accounts create an APT/USDC book or
+/// two coins use the same symbol. `get` returns the first of them, `get_all` every one.
+#[derive(Clone, Debug, Default)]
+pub struct MarketRegistry {
+    entries: Vec<MarketEntry>,
+    by_symbol: HashMap<String, Vec<usize>>,
+    by_book_id: HashMap<Id, usize>,
+}
+
+impl MarketRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, entry: MarketEntry) {
+        let idx = self.entries.len();
+        self.by_symbol
+            .entry(normalize_symbol(&entry.symbol))
+            .or_default()
+            .push(idx);
+        self.by_book_id.insert(entry.book.book_id.clone(), idx);
+        self.entries.push(entry);
+    }
+
+    /// First book with the pair symbol, e.g. "APT/USDC". Case insensitive.
+    pub fn get(&self, symbol: &str) -> Option<&MarketEntry> {
+        self.get_all(symbol).next()
+    }
+
+    /// All books with the pair symbol, in the order they were inserted.
+    pub fn get_all<'a>(&'a self, symbol: &str) -> impl Iterator<Item = &'a MarketEntry> {
+        self.by_symbol
+            .get(&normalize_symbol(symbol))
+            .into_iter()
+            .flatten()
+            .map(|idx| &self.entries[*idx])
+    }
+
+    pub fn get_by_book_id(&self, book_id: &Id) -> Option<&MarketEntry> {
+        self.by_book_id.get(book_id).map(|idx| &self.entries[*idx])
+    }
+
+    pub fn entries(&self) -> &[MarketEntry] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+fn normalize_symbol(symbol: &str) -> String {
+    symbol.trim().to_uppercase()
+}
+
+impl LaminarClient {
+    /// Build a `MarketRegistry` from the order books created by `book_owners`.
+    /// Books are found through the creation events of each owner's `OrderBookStore`, as the
+    /// chain has no global index of books. Coin symbols are read through `get_coin_info`.
+    ///
+    /// # Arguments:
+    ///
+    /// * `book_owners` - Addresses of the accounts whose books to load.
+    pub async fn fetch_market_registry(
+        &self,
+        book_owners: &[AccountAddress],
+    ) -> Result<MarketRegistry> {
+        let mut registry = MarketRegistry::new();
+        for owner in book_owners {
+            for book in self.fetch_order_books_of(*owner).await? {
+                let base = TypeTag::try_from(book.base.clone())?;
+                let quote = TypeTag::try_from(book.quote.clone())?;
+                let base_symbol = self.get_coin_info(&base).await?.symbol;
+                let quote_symbol = self.get_coin_info(&quote).await?.symbol;
+                let book_owner = *book.book_id.addr.inner();
+                registry.insert(MarketEntry {
+                    symbol: format!("{}/{}", base_symbol, quote_symbol),
+                    base_symbol,
+                    quote_symbol,
+                    market: Market::new(base, quote, book_owner),
+                    book,
+                });
+            }
+        }
+
+        Ok(registry)
+    }
+}
@@ -0,0 +1,290 @@
+use crate::error::{LaminarError, Result};
+use crate::types::events::{FillEvent, LaminarEvent};
+use crate::types::market::Market;
+use crate::types::order::{Id, Side};
+use crate::LaminarTransaction;
+use aptos_sdk::bcs;
+use aptos_sdk::types::account_address::AccountAddress;
+use aptos_sdk::types::transaction::EntryFunction;
+use serde::de::DeserializeOwned;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+/// Limits enforced by a `RiskManager`. Prices and sizes are in units, `None` disables a limit.
+#[derive(Clone, Debug, Default)]
+pub struct RiskLimits {
+    /// Maximum size of a single order.
+    pub max_order_size: Option<u64>,
+    /// Maximum `price * size` of a single limit order.
+    pub max_notional: Option<u128>,
+    /// Maximum number of resting orders across all books.
+    pub max_open_orders: Option<usize>,
+    /// Maximum absolute net position per book, including the order being placed.
+    pub max_net_position: Option<u64>,
+}
+
+// closed orders are remembered for a while, fills of them may still arrive from the event stream
+const MAX_CLOSED_ORDERS: usize = 10_000;
+
+#[derive(Debug, Default)]
+struct RiskState {
+    open_orders: HashMap<Id, Market>,
+    closed_orders: VecDeque<(Id, Market)>,
+    // remaining sizes after the fills applied per order, a fill can arrive both with the
+    // client's transaction and through `apply_fill`
+    applied_fills: HashMap<Id, HashSet<u64>>,
+    net_positions: HashMap<Market, i128>,
+}
+
+impl RiskState {
+    fn book(&self, order_id: &Id) -> Option<Market> {
+        self.open_orders.get(order_id).cloned().or_else(|| {
+            self.closed_orders
+                .iter()
+                .find(|(id, _)| id == order_id)
+                .map(|(_, book)| book.clone())
+        })
+    }
+
+    fn close(&mut self, order_id: &Id) {
+        let Some(book) = self.open_orders.remove(order_id) else {
+            return;
+        };
+        self.closed_orders.push_back((order_id.clone(), book));
+        if self.closed_orders.len() > MAX_CLOSED_ORDERS {
+            if let Some((id, _)) = self.closed_orders.pop_front() {
+                self.applied_fills.remove(&id);
+            }
+        }
+    }
+}
+
+/// Pre-trade checks applied by `LaminarClient` to every Laminar payload before it is submitted.
+///
+/// Open orders and net positions are updated from the events of transactions submitted by the
+/// client. Fills of resting orders happen in other accounts' transactions, feed them with
+/// `apply_fill`, e.g. from the account's fill event stream. A fill seen both ways is applied
+/// once, fills are told apart by order id and remaining size. Checks run before submission, so
+/// concurrent submissions are each checked against the state before the others land.
+#[derive(Debug, Default)]
+pub struct RiskManager {
+    limits: RiskLimits,
+    killed: AtomicBool,
+    state: Mutex<RiskState>,
+}
+
+impl RiskManager {
+    pub fn new(limits: RiskLimits) -> Self {
+        Self {
+            limits,
+            ..Self::default()
+        }
+    }
+
+    pub fn limits(&self) -> &RiskLimits {
+        &self.limits
+    }
+
+    /// Block every new placement and amend until `reset_kill_switch` is called.
+    /// Use `LaminarClient::kill_switch` to also cancel resting orders.
+    pub fn engage_kill_switch(&self) {
+        self.killed.store(true, Ordering::SeqCst);
+    }
+
+    pub fn reset_kill_switch(&self) {
+        self.killed.store(false, Ordering::SeqCst);
+    }
+
+    pub fn is_killed(&self) -> bool {
+        self.killed.load(Ordering::SeqCst)
+    }
+
+    pub fn open_orders(&self) -> usize {
+        self.state().open_orders.len()
+    }
+
+    pub fn net_position(&self, book: &Market) -> i128 {
+        self.state()
+            .net_positions
+            .get(book)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Books with open orders placed through the client.
+    pub fn books(&self) -> Vec<Market> {
+        let mut books: Vec<Market> = vec![];
+        for book in self.state().open_orders.values() {
+            if !books.contains(book) {
+                books.push(book.clone());
+            }
+        }
+        books
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, RiskState> {
+        self.state.lock().expect("risk state lock poisoned")
+    }
+
+    /// Check a `book` module payload against the limits.
+    pub fn check(&self, payload: &EntryFunction) -> Result<()> {
+        let function = payload.function().as_str();
+        let args = payload.args();
+        let (side, price, size) = match function {
+            "place_limit_order" => (side_arg(args, 1)?, Some(arg(args, 2)?), arg(args, 3)?),
+            "place_market_order" => (side_arg(args, 1)?, None, arg(args, 2)?),
+            "amend_order" => (side_arg(args, 2)?, Some(arg(args, 3)?), arg(args, 4)?),
+            _ => return Ok(()),
+        };
+        if self.is_killed() {
+            return Err(LaminarError::RiskLimit("kill switch engaged".to_string()));
+        }
+
+        if let Some(max) = self.limits.max_order_size {
+            if size > max {
+                return Err(LaminarError::RiskLimit(format!(
+                    "order size {} exceeds {}",
+                    size, max
+                )));
+            }
+        }
+        if let (Some(max), Some(price)) = (self.limits.max_notional, price) {
+            let notional = price as u128 * size as u128;
+            if notional > max {
+                return Err(LaminarError::RiskLimit(format!(
+                    "order notional {} exceeds {}",
+                    notional, max
+                )));
+            }
+        }
+        // amends neither add orders nor change the side of an order
+        if function == "amend_order" {
+            return Ok(());
+        }
+
+        let state = self.state();
+        if let Some(max) = self.limits.max_open_orders {
+            if function == "place_limit_order" && state.open_orders.len() >= max {
+                return Err(LaminarError::RiskLimit(format!(
+                    "{} open orders, limit is {}",
+                    state.open_orders.len(),
+                    max
+                )));
+            }
+        }
+        if let Some(max) = self.limits.max_net_position {
+            let book = book_key(payload)?;
+            let net = state.net_positions.get(&book).copied().unwrap_or_default();
+            let after = match side {
+                Side::Bid => net + size as i128,
+                Side::Ask => net - size as i128,
+            };
+            if after.unsigned_abs() > max as u128 {
+                return Err(LaminarError::RiskLimit(format!(
+                    "net position would be {}, limit is {}",
+                    after, max
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Update open orders and positions from the events of a committed transaction.
+    ///
+    /// # Arguments:
+    ///
+    /// * `account` - Address of the account whose orders are tracked.
+    /// * `tx` - Committed transaction.
+    pub fn apply_transaction(&self, account: &AccountAddress, tx: &LaminarTransaction) {
+        let Some([base, quote]) = tx.type_args() else {
+            return;
+        };
+
+        let key = |book_id: &Id| Market {
+            book_owner: *book_id.addr.inner(),
+            base: base.clone(),
+            quote: quote.clone(),
+        };
+
+        let mut state = self.state();
+        // insert placed orders first so fills and cancels in the same tx close them
+        for e in &tx.events {
+            if let LaminarEvent::PlaceOrder(p) = e {
+                if p.order_id.addr.inner() == account {
+                    state
+                        .open_orders
+                        .insert(p.order_id.clone(), key(&p.book_id));
+                }
+            }
+        }
+        for e in &tx.events {
+            match e {
+                LaminarEvent::CancelOrder(c) => state.close(&c.order_id),
+                LaminarEvent::FillEvent(f) if f.order_id.addr.inner() == account => {
+                    Self::fill(&mut state, key(&f.book_id), f);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Apply a fill of a resting order that happened outside of this client's transactions.
+    /// Fills of orders not placed through the client are ignored.
+    pub fn apply_fill(&self, fill: &FillEvent) {
+        let mut state = self.state();
+        let Some(book) = state.book(&fill.order_id) else {
+            return;
+        };
+        Self::fill(&mut state, book, fill);
+    }
+
+    fn fill(state: &mut RiskState, book: Market, fill: &FillEvent) {
+        let applied = state
+            .applied_fills
+            .entry(fill.order_id.clone())
+            .or_default();
+        if !applied.insert(fill.remaining_size) {
+            return;
+        }
+        let net = state.net_positions.entry(book).or_default();
+        match fill.side {
+            Side::Bid => *net += fill.fill_size as i128,
+            Side::Ask => *net -= fill.fill_size as i128,
+        }
+        if fill.remaining_size == 0 {
+            state.close(&fill.order_id);
+        }
+    }
+}
+
+fn arg<T: DeserializeOwned>(args: &[Vec<u8>], i: usize) -> Result<T> {
+    let bytes = args
+        .get(i)
+        .ok_or_else(|| LaminarError::InvalidInput(format!("missing payload argument {}", i)))?;
+    Ok(bcs::from_bytes(bytes)?)
+}
+
+// `Side` only deserializes from self-describing formats, payloads encode it as a single byte
+fn side_arg(args: &[Vec<u8>], i: usize) -> Result<Side> {
+    match arg::<u8>(args, i)? {
+        0 => Ok(Side::Bid),
+        1 => Ok(Side::Ask),
+        s => Err(LaminarError::InvalidInput(format!("invalid side: {}", s))),
+    }
+}
+
+fn book_key(payload: &EntryFunction) -> Result<Market> {
+    let [base, quote] = payload.ty_args() else {
+        return Err(LaminarError::InvalidInput(
+            "expected base and quote type arguments".to_string(),
+        ));
+    };
+
+    Ok(Market {
+        book_owner: arg(payload.args(), 0)?,
+        base: base.clone(),
+        quote: quote.clone(),
+    })
+}
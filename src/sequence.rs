@@ -0,0 +1,141 @@
+// Sequence numbers of the client's account, shared by txs signed concurrently.
+//
+// The counter only moves forward, so a number is never handed out while another in-flight tx
+// holds it. A number whose tx provably can't commit is released and handed out before new ones,
+// filling the gap that would otherwise block every later tx. Releases name the tx that held the
+// number, so a stale release can't take it from a newer tx signed with it.
+
+use aptos_api_types::HashValue;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::{Mutex, MutexGuard};
+
+#[derive(Debug, Default)]
+pub(crate) struct SequenceNumbers {
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    next: u64,
+    // numbers handed out whose tx may still commit, with the hash of the tx once signed
+    held: BTreeMap<u64, Option<HashValue>>,
+    released: BTreeSet<u64>,
+}
+
+impl SequenceNumbers {
+    pub(crate) fn new(next: u64) -> Self {
+        Self {
+            state: Mutex::new(State {
+                next,
+                ..State::default()
+            }),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().expect("sequence number lock poisoned")
+    }
+
+    /// The number `take` hands out next.
+    pub(crate) fn peek(&self) -> u64 {
+        let state = self.state();
+        state.released.first().copied().unwrap_or(state.next)
+    }
+
+    /// Hand out a number, the lowest released one first.
+    pub(crate) fn take(&self) -> u64 {
+        let mut state = self.state();
+        let n = match state.released.pop_first() {
+            Some(n) => n,
+            None => {
+                state.next += 1;
+                state.next - 1
+            }
+        };
+        state.held.insert(n, None);
+        n
+    }
+
+    /// Record the tx signed with a number handed out by `take`, unless one was already.
+    pub(crate) fn bind(&self, n: u64, hash: HashValue) {
+        if let Some(held @ None) = self.state().held.get_mut(&n) {
+            *held = Some(hash);
+        }
+    }
+
+    /// Hand `n` out again, its tx can't commit. `hash` is the tx signed with it, `None` if
+    /// none was. Ignored if `n` is held by another tx by now.
+    pub(crate) fn release(&self, n: u64, hash: Option<HashValue>) -> bool {
+        let mut state = self.state();
+        if state.held.get(&n) != Some(&hash) {
+            return false;
+        }
+        state.held.remove(&n);
+        state.released.insert(n);
+        true
+    }
+
+    /// Catch up with the account's sequence number on chain, every number below it is used.
+    pub(crate) fn sync(&self, chain: u64) {
+        let mut state = self.state();
+        state.next = state.next.max(chain);
+        state.held.retain(|n, _| *n >= chain);
+        state.released.retain(|n| *n >= chain);
+    }
+
+    /// Overwrite the next number, forgetting every number handed out before.
+    pub(crate) fn reset(&self, next: u64) {
+        *self.state() = State {
+            next,
+            ..State::default()
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hash(bytes: &[u8]) -> HashValue {
+        aptos_sdk::crypto::HashValue::sha3_256_of(bytes).into()
+    }
+
+    #[test]
+    fn test_released_number_is_reused_first() {
+        let seq = SequenceNumbers::new(5);
+        let (a, b, c) = (seq.take(), seq.take(), seq.take());
+        assert_eq!((a, b, c), (5, 6, 7));
+
+        assert!(seq.release(b, None));
+        assert_eq!(seq.peek(), 6);
+        assert_eq!(seq.take(), 6);
+        // the txs holding 5 and 7 keep their numbers
+        assert_eq!(seq.take(), 8);
+    }
+
+    #[test]
+    fn test_stale_release_is_ignored() {
+        let seq = SequenceNumbers::new(0);
+        let n = seq.take();
+        seq.bind(n, hash(b"first"));
+        assert!(seq.release(n, Some(hash(b"first"))));
+
+        let m = seq.take();
+        assert_eq!(m, n);
+        seq.bind(m, hash(b"second"));
+        assert!(!seq.release(n, Some(hash(b"first"))));
+        assert_eq!(seq.take(), 1);
+    }
+
+    #[test]
+    fn test_sync_only_moves_forward() {
+        let seq = SequenceNumbers::new(10);
+        seq.take();
+        seq.sync(3);
+        assert_eq!(seq.peek(), 11);
+
+        seq.release(10, None);
+        seq.sync(12);
+        assert_eq!(seq.peek(), 12);
+    }
+}
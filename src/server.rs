@@ -0,0 +1,433 @@
+//! WebSocket server re-broadcasting the market data of a book, so web frontends don't poll
+//! fullnodes themselves.
+//!
+//! # Protocol
+//!
+//! Clients connect to `/ws` and exchange JSON text messages. On connect a client receives a
+//! `snapshot` and is subscribed to all channels.
+//!
+//! Client messages, `channels` being any of `levels`, `trades` and `orders`:
+//!
+//! * `{"op": "subscribe", "channels": [..]}` - Receive the messages of `channels`.
+//! * `{"op": "unsubscribe", "channels": [..]}` - Stop receiving the messages of `channels`.
+//!
+//! Server messages, tagged by `type`:
+//!
+//! * `snapshot` - `{"type": "snapshot", "book": L2Book}`, the aggregated book. Sent again if
+//!   the client falls behind and messages were dropped, and to all clients when the server
+//!   re-seeds its book.
+//! * `levels` - `{"type": "levels", "book_id": Id, "levels": [LevelUpdate]}`, new total sizes
+//!   of the changed price levels, `size` 0 for removed levels. Channel `levels`.
+//! * `trade` - `{"type": "trade", ..Trade}`, a public trade. Channel `trades`.
+//! * `order` - `{"type": "order", "event": LaminarEvent}`, an event of an order of the
+//!   server's account. Channel `orders`.
+//! * `error` - `{"type": "error", "message": ".."}`, an invalid client message.
+
+use crate::error::{LaminarError, Result};
+use crate::market_data::{LevelUpdate, MarketDataFeed};
+use crate::sim::{EventReplay, SimBook};
+use crate::tape::{Trade, TradeTape};
+use crate::time::{sleep, Instant};
+use crate::types::events::{LaminarEvent, LaminarEventEnvelope};
+use crate::types::l2::L2Book;
+use crate::types::market::Market;
+use crate::types::order::{Id, Side};
+use crate::LaminarClient;
+use anyhow::anyhow;
+use aptos_sdk::types::account_address::AccountAddress;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::State;
+use axum::response::Response;
+use axum::routing::get;
+use axum::Router;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+/// Message sent to WebSocket clients, see the module docs for the protocol.
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    Snapshot {
+        book: L2Book,
+    },
+    Levels {
+        book_id: Id,
+        levels: Vec<LevelUpdate>,
+    },
+    Trade(Trade),
+    Order {
+        event: LaminarEvent,
+    },
+    Error {
+        message: String,
+    },
+}
+
+impl ServerMessage {
+    fn channel(&self) -> Option<Channel> {
+        match self {
+            ServerMessage::Levels { .. } => Some(Channel::Levels),
+            ServerMessage::Trade(_) => Some(Channel::Trades),
+            ServerMessage::Order { .. } => Some(Channel::Orders),
+            ServerMessage::Snapshot { .. } | ServerMessage::Error { .. } => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Channel {
+    Levels,
+    Trades,
+    Orders,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum ClientMessage {
+    Subscribe { channels: Vec<Channel> },
+    Unsubscribe { channels: Vec<Channel> },
+}
+
+struct ServerBook {
+    book: SimBook,
+    replay: EventReplay,
+    tape: TradeTape,
+    // events up to this version are already in the seeded book
+    seeded_at: Option<u64>,
+}
+
+#[derive(Clone)]
+struct ServerState {
+    tx: broadcast::Sender<ServerMessage>,
+    book: Arc<Mutex<ServerBook>>,
+    depth: usize,
+}
+
+impl ServerState {
+    fn book(&self) -> MutexGuard<'_, ServerBook> {
+        self.book.lock().expect("server book lock poisoned")
+    }
+
+    fn snapshot(&self) -> ServerMessage {
+        ServerMessage::Snapshot {
+            book: self.book().book.to_l2(self.depth),
+        }
+    }
+}
+
+/// WebSocket server broadcasting the L2 deltas and trades of a book and the order updates of
+/// an account.
+///
+/// The server keeps a `SimBook` seeded from the live book and replays the book's events on
+/// it to derive level changes. Drive it with `run`, which polls a `MarketDataFeed`, and serve
+/// the clients with `serve` or by mounting `router` in an existing axum app.
+///
+/// Replayed events can diverge from the chain, e.g. when an amend of a live order is not
+/// replayed, so `run` re-seeds the book from a new snapshot every `resnapshot_every` and
+/// whenever the feed's events don't continue from the book's version.
+#[derive(Clone)]
+pub struct MarketDataServer {
+    state: ServerState,
+    market: Market,
+    account: AccountAddress,
+    resnapshot_every: Duration,
+}
+
+impl MarketDataServer {
+    /// Seed the server from the live book of `market`.
+    ///
+    /// # Arguments:
+    ///
+    /// * `client` - Client used to read the live book, order updates of its account are sent.
+    /// * `market` - Market to broadcast.
+    pub async fn connect(client: &LaminarClient, market: &Market) -> Result<Self> {
+        let book = client
+            .fetch_orderbook(&market.base, &market.quote, &market.book_owner)
+            .await?;
+        let (tx, _) = broadcast::channel(1024);
+        let state = ServerBook {
+            book: SimBook::from_order_book(&book),
+            replay: EventReplay::new(),
+            tape: TradeTape::default(),
+            seeded_at: book.ledger_version,
+        };
+
+        Ok(Self {
+            state: ServerState {
+                tx,
+                book: Arc::new(Mutex::new(state)),
+                depth: 50,
+            },
+            market: market.clone(),
+            account: client.address(),
+            resnapshot_every: Duration::from_secs(300),
+        })
+    }
+
+    /// Send the order updates of `account` instead of the ones of the client's account.
+    pub fn with_account(mut self, account: AccountAddress) -> Self {
+        self.account = account;
+        self
+    }
+
+    /// Re-seed the book from a new snapshot every `interval`, 5 minutes by default.
+    pub fn with_resnapshot_every(mut self, interval: Duration) -> Self {
+        self.resnapshot_every = interval;
+        self
+    }
+
+    /// Number of levels per side in snapshots, 50 by default.
+    pub fn with_depth(mut self, depth: usize) -> Self {
+        self.state.depth = depth;
+        self
+    }
+
+    /// Router serving the WebSocket endpoint at `/ws`.
+    pub fn router(&self) -> Router {
+        Router::new()
+            .route("/ws", get(upgrade))
+            .with_state(self.state.clone())
+    }
+
+    /// Serve clients on `addr` until the server fails.
+    pub async fn serve(&self, addr: SocketAddr) -> Result<()> {
+        axum::Server::try_bind(&addr)
+            .map_err(|e| LaminarError::InvalidInput(format!("bind {}: {}", addr, e)))?
+            .serve(self.router().into_make_service())
+            .await
+            .map_err(|e| anyhow!("websocket server failed: {}", e))?;
+        Ok(())
+    }
+
+    /// Number of connected clients.
+    pub fn clients(&self) -> usize {
+        self.state.tx.receiver_count()
+    }
+
+    /// Poll `feed` every `interval` and broadcast the updates, committing the feed's
+    /// checkpoint after each poll. Failed polls are retried on the next tick.
+    ///
+    /// The book is re-seeded before applying a poll whose events don't start right after the
+    /// book's version, e.g. the first poll of a feed without cursor, and periodically.
+    ///
+    /// # Arguments:
+    ///
+    /// * `client` - Client the feed reads events with.
+    /// * `feed` - Feed of the server's book.
+    /// * `interval` - Time between polls.
+    pub async fn run(
+        &self,
+        client: &LaminarClient,
+        feed: &mut MarketDataFeed,
+        interval: Duration,
+    ) -> Result<()> {
+        // version up to which the book reflects the chain
+        let mut synced = self.state.book().seeded_at;
+        let mut seeded = Instant::now();
+        loop {
+            let start = feed.cursor();
+            match feed.poll_envelopes(client).await {
+                Ok(envelopes) => {
+                    let gap = match (start, synced) {
+                        (Some(start), Some(synced)) => start.version > synced + 1,
+                        _ => true,
+                    };
+                    if gap || seeded.elapsed() >= self.resnapshot_every {
+                        match self.resnapshot(client).await {
+                            Ok(version) => {
+                                synced = version;
+                                seeded = Instant::now();
+                            }
+                            Err(_e) => {
+                                trace_event!(warn, error = %_e, "market data snapshot failed");
+                                // retry on the next poll, until then the book stays unsynced
+                                if gap {
+                                    synced = None;
+                                }
+                            }
+                        }
+                    }
+                    self.process(&envelopes);
+                    if let (Some(cursor), Some(version)) = (feed.cursor(), synced) {
+                        synced = Some(version.max(cursor.version.saturating_sub(1)));
+                    }
+                    feed.commit()?;
+                }
+                Err(_e) => {
+                    trace_event!(warn, error = %_e, "market data poll failed");
+                }
+            }
+            sleep(interval).await;
+        }
+    }
+
+    // replace the book with a new snapshot and send it to all clients, returning the version
+    // it was read at
+    async fn resnapshot(&self, client: &LaminarClient) -> Result<Option<u64>> {
+        let Market {
+            base,
+            quote,
+            book_owner,
+        } = &self.market;
+        let live = client.fetch_orderbook(base, quote, book_owner).await?;
+        {
+            let mut state = self.state.book();
+            state.book = SimBook::from_order_book(&live);
+            state.replay = EventReplay::new();
+            state.seeded_at = live.ledger_version;
+        }
+        let _ = self.state.tx.send(self.state.snapshot());
+
+        Ok(live.ledger_version)
+    }
+
+    /// Apply events of the book and broadcast the resulting messages.
+    pub fn process(&self, envelopes: &[LaminarEventEnvelope<LaminarEvent>]) {
+        let mut messages = vec![];
+        {
+            let mut state = self.state.book();
+            let ServerBook {
+                book,
+                replay,
+                tape,
+                seeded_at,
+            } = &mut *state;
+            let before = depth(book);
+            for envelope in envelopes {
+                let event = &envelope.event;
+                // the seeded book already reflects the event, its trade is still new to clients
+                let seeded = seeded_at.map_or(false, |v| envelope.version <= v);
+                if !seeded && replay.apply(book, event).is_err() {
+                    trace_event!(debug, "event not replayed");
+                }
+                if let LaminarEvent::FillEvent(fill) = event {
+                    if let Some(trade) = tape.push(fill) {
+                        messages.push(ServerMessage::Trade(trade));
+                    }
+                }
+                let is_own = event
+                    .order_id()
+                    .map_or(false, |id| id.addr.inner() == &self.account);
+                if is_own {
+                    messages.push(ServerMessage::Order {
+                        event: event.clone(),
+                    });
+                }
+            }
+
+            let after = depth(book);
+            let mut levels = vec![];
+            for (&(side, price), &size) in &after {
+                if before.get(&(side, price)) != Some(&size) {
+                    levels.push(level_update(side, price, size));
+                }
+            }
+            for &(side, price) in before.keys() {
+                if !after.contains_key(&(side, price)) {
+                    levels.push(level_update(side, price, 0));
+                }
+            }
+            if !levels.is_empty() {
+                messages.insert(
+                    0,
+                    ServerMessage::Levels {
+                        book_id: book.book_id().clone(),
+                        levels,
+                    },
+                );
+            }
+        }
+
+        for message in messages {
+            // no receivers is not an error, clients may connect later
+            let _ = self.state.tx.send(message);
+        }
+    }
+}
+
+fn depth(book: &SimBook) -> BTreeMap<(u8, u64), u64> {
+    [Side::Bid, Side::Ask]
+        .into_iter()
+        .flat_map(|side| {
+            book.depth(side)
+                .into_iter()
+                .map(move |(price, size)| ((side as u8, price), size))
+        })
+        .collect()
+}
+
+fn level_update(side: u8, price: u64, size: u64) -> LevelUpdate {
+    let side = if side == Side::Bid as u8 {
+        Side::Bid
+    } else {
+        Side::Ask
+    };
+    LevelUpdate { side, price, size }
+}
+
+async fn upgrade(ws: WebSocketUpgrade, State(state): State<ServerState>) -> Response {
+    ws.on_upgrade(|socket| handle_socket(socket, state))
+}
+
+async fn handle_socket(mut socket: WebSocket, state: ServerState) {
+    let mut rx = state.tx.subscribe();
+    let mut channels = HashSet::from([Channel::Levels, Channel::Trades, Channel::Orders]);
+    if send(&mut socket, &state.snapshot()).await.is_err() {
+        return;
+    }
+
+    loop {
+        tokio::select! {
+            message = rx.recv() => {
+                let message = match message {
+                    Ok(message) => message,
+                    // dropped messages can't be replayed, resync the client instead
+                    Err(RecvError::Lagged(_)) => state.snapshot(),
+                    Err(RecvError::Closed) => break,
+                };
+                let subscribed = message.channel().map_or(true, |c| channels.contains(&c));
+                if subscribed && send(&mut socket, &message).await.is_err() {
+                    break;
+                }
+            }
+            message = socket.recv() => {
+                let text = match message {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => continue,
+                };
+                let reply = match serde_json::from_str::<ClientMessage>(&text) {
+                    Ok(ClientMessage::Subscribe { channels: c }) => {
+                        channels.extend(c);
+                        continue;
+                    }
+                    Ok(ClientMessage::Unsubscribe { channels: c }) => {
+                        c.iter().for_each(|c| {
+                            channels.remove(c);
+                        });
+                        continue;
+                    }
+                    Err(e) => ServerMessage::Error { message: e.to_string() },
+                };
+                if send(&mut socket, &reply).await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+async fn send(socket: &mut WebSocket, message: &ServerMessage) -> Result<()> {
+    let text = serde_json::to_string(message)
+        .map_err(|e| LaminarError::deserialization(e, "server message"))?;
+    socket
+        .send(Message::Text(text))
+        .await
+        .map_err(|e| anyhow!("failed sending to websocket client: {}", e))?;
+    Ok(())
+}
@@ -0,0 +1,118 @@
+use crate::error::{LaminarError, Result};
+use anyhow::anyhow;
+use aptos_sdk::crypto::ed25519::{Ed25519PublicKey, Ed25519Signature, ED25519_SIGNATURE_LENGTH};
+use aptos_sdk::crypto::traits::signing_message;
+use aptos_sdk::crypto::Signature;
+use aptos_sdk::types::account_address::AccountAddress;
+use aptos_sdk::types::transaction::{RawTransaction, SignedTransaction};
+use aptos_sdk::types::LocalAccount;
+use async_trait::async_trait;
+use futures::future::BoxFuture;
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+
+/// Signs the transactions of a `LaminarClient`, so the private key does not need to live in
+/// the client's process.
+#[async_trait]
+pub trait TransactionSigner: Debug + Send + Sync {
+    /// Address of the account transactions are sent from.
+    fn address(&self) -> AccountAddress;
+
+    async fn sign_transaction(&self, tx: RawTransaction) -> Result<SignedTransaction>;
+
+    /// Wrap a transaction with the signer's public key and an invalid signature, as required
+    /// by the node to simulate it.
+    fn simulation_transaction(&self, tx: RawTransaction) -> Result<SignedTransaction>;
+}
+
+/// `SignedTransaction` for simulation, signed with an all zero Ed25519 signature.
+pub fn ed25519_simulation_transaction(
+    tx: RawTransaction,
+    public_key: &Ed25519PublicKey,
+) -> Result<SignedTransaction> {
+    let signature = Ed25519Signature::try_from(&[0u8; ED25519_SIGNATURE_LENGTH][..])
+        .map_err(|e| LaminarError::Other(anyhow!("failed building signature: {}", e)))?;
+    Ok(SignedTransaction::new(tx, public_key.clone(), signature))
+}
+
+#[async_trait]
+impl TransactionSigner for LocalAccount {
+    fn address(&self) -> AccountAddress {
+        LocalAccount::address(self)
+    }
+
+    async fn sign_transaction(&self, tx: RawTransaction) -> Result<SignedTransaction> {
+        Ok(LocalAccount::sign_transaction(self, tx))
+    }
+
+    fn simulation_transaction(&self, tx: RawTransaction) -> Result<SignedTransaction> {
+        ed25519_simulation_transaction(tx, self.public_key())
+    }
+}
+
+/// Signs the signing message of a transaction, e.g. by calling a remote signing service.
+pub type SignFn =
+    Arc<dyn Fn(Vec<u8>) -> BoxFuture<'static, Result<Ed25519Signature>> + Send + Sync>;
+
+/// Ed25519 signer delegating signatures to a callback, for keys held by a remote service, an
+/// HSM or a KMS. The callback receives the signing message of the transaction, i.e. the
+/// domain separated BCS bytes of the `RawTransaction`, and returns its signature.
+#[derive(Clone)]
+pub struct RemoteSigner {
+    address: AccountAddress,
+    public_key: Ed25519PublicKey,
+    sign: SignFn,
+}
+
+impl RemoteSigner {
+    /// # Arguments:
+    ///
+    /// * `address` - Address of the account transactions are sent from.
+    /// * `public_key` - Public key of the signing key.
+    /// * `sign` - Callback signing a message with the key.
+    pub fn new(address: AccountAddress, public_key: Ed25519PublicKey, sign: SignFn) -> Self {
+        Self {
+            address,
+            public_key,
+            sign,
+        }
+    }
+
+    pub fn public_key(&self) -> &Ed25519PublicKey {
+        &self.public_key
+    }
+}
+
+impl Debug for RemoteSigner {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RemoteSigner")
+            .field("address", &self.address)
+            .field("public_key", &self.public_key)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl TransactionSigner for RemoteSigner {
+    fn address(&self) -> AccountAddress {
+        self.address
+    }
+
+    async fn sign_transaction(&self, tx: RawTransaction) -> Result<SignedTransaction> {
+        let message = signing_message(&tx);
+        let signature = (self.sign)(message.clone()).await?;
+        // a remote signer holding another key would otherwise only fail on submission
+        signature
+            .verify_arbitrary_msg(&message, &self.public_key)
+            .map_err(|e| LaminarError::InvalidInput(format!("invalid remote signature: {}", e)))?;
+        Ok(SignedTransaction::new(
+            tx,
+            self.public_key.clone(),
+            signature,
+        ))
+    }
+
+    fn simulation_transaction(&self, tx: RawTransaction) -> Result<SignedTransaction> {
+        ed25519_simulation_transaction(tx, &self.public_key)
+    }
+}
@@ -0,0 +1,1008 @@
+//! Local price-time priority matching engine following the rules of the Laminar `book` module,
+//! for deterministic strategy tests and pre-trade fill estimation without a node.
+//!
+//! Prices and sizes are in units. Balances are not settled on fills and fees are not charged.
+
+use crate::strategy::OrderRequest;
+use crate::types::events::{
+    AmendOrderEvent, CancelOrderEvent, FillEvent, LaminarEvent, PlaceOrderEvent,
+};
+use crate::types::l2::{L2Book, L2Level};
+use crate::types::order::{Id, Order, OrderBook, Side, State, TimeInForce};
+use aptos_api_types::{Address, U64};
+use aptos_sdk::types::account_address::AccountAddress;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use thiserror::Error;
+
+/// Abort codes of the simulator, returned by `SimError::abort_code` and `MockNode`. They are
+/// not read from the deployed `book` module, whose codes may differ, so aborts of live
+/// transactions must not be matched against them.
+pub const EINVALID_ARGUMENT: u64 = 0x10000;
+pub const EPOST_ONLY: u64 = 0x10001;
+pub const EMIN_SIZE: u64 = 0x10002;
+pub const EFILL_OR_KILL: u64 = 0x10003;
+pub const EORDER_NOT_FOUND: u64 = 0x60004;
+
+/// `CancelOrderEvent::reason` codes emitted by the simulator, for cancels by the owner and of
+/// unfilled size. Like the abort codes they are the simulator's own.
+pub const CANCEL_REASON_USER: u8 = 0;
+pub const CANCEL_REASON_UNFILLED: u8 = 1;
+
+/// Order rejected by the book. The book is left unchanged, as an aborted transaction would.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Error)]
+pub enum SimError {
+    #[error("price must not be zero")]
+    InvalidPrice,
+    #[error("price is not a multiple of the price tick")]
+    PriceTick,
+    #[error("size is not a multiple of the size tick")]
+    SizeTick,
+    #[error("post only order would take liquidity")]
+    PostOnly,
+    /// Amended orders never take liquidity, like post only orders.
+    #[error("amended order would take liquidity")]
+    WouldCross,
+    #[error("size is below the minimum size")]
+    MinSize,
+    #[error("fill or kill order cannot be filled completely")]
+    FillOrKill,
+    #[error("order not found")]
+    OrderNotFound,
+}
+
+impl SimError {
+    /// Simulator abort code of the rejection, see `EINVALID_ARGUMENT`.
+    pub fn abort_code(&self) -> u64 {
+        match self {
+            Self::InvalidPrice | Self::PriceTick | Self::SizeTick => EINVALID_ARGUMENT,
+            Self::PostOnly | Self::WouldCross => EPOST_ONLY,
+            Self::MinSize => EMIN_SIZE,
+            Self::FillOrKill => EFILL_OR_KILL,
+            Self::OrderNotFound => EORDER_NOT_FOUND,
+        }
+    }
+}
+
+fn opposite(side: Side) -> Side {
+    match side {
+        Side::Bid => Side::Ask,
+        Side::Ask => Side::Bid,
+    }
+}
+
+fn crosses(side: Side, price: u64, opposite: u64) -> bool {
+    match side {
+        Side::Bid => price >= opposite,
+        Side::Ask => price <= opposite,
+    }
+}
+
+/// In-memory `OrderBook` matching orders with price-time priority.
+///
+/// Ids are handed out per account like the creation numbers of on-chain objects, so the
+/// emitted events have the same shape as the ones read from a node.
+#[derive(Clone, Debug)]
+pub struct SimBook {
+    book_id: Id,
+    min_size_amount: u64,
+    price_tick: u64,
+    size_tick: u64,
+    bids: BTreeMap<u64, VecDeque<Order>>,
+    asks: BTreeMap<u64, VecDeque<Order>>,
+    creation_nums: HashMap<AccountAddress, u64>,
+}
+
+impl SimBook {
+    /// # Arguments:
+    ///
+    /// * `book_id` - Id of the simulated book, its address is taken as the book owner.
+    /// * `min_size_amount` - Minimum order size in units.
+    pub fn new(book_id: Id, min_size_amount: u64) -> Self {
+        let mut creation_nums = HashMap::new();
+        creation_nums.insert(*book_id.addr.inner(), book_id.creation_num.0 + 1);
+        Self {
+            book_id,
+            min_size_amount,
+            price_tick: 1,
+            size_tick: 1,
+            bids: BTreeMap::new(),
+            asks: BTreeMap::new(),
+            creation_nums,
+        }
+    }
+
+    /// Start from a snapshot of an on-chain book, e.g. to estimate the fills of an order.
+    pub fn from_order_book(book: &OrderBook) -> Self {
+        let mut sim = Self::new(book.id.clone(), book.instrument.min_size_amount);
+        for (levels, sim_levels) in [(&book.bids, &mut sim.bids), (&book.asks, &mut sim.asks)] {
+            for (price, orders) in levels {
+                sim_levels.insert(*price, orders.iter().cloned().collect());
+            }
+        }
+        for order in book.bids.values().chain(book.asks.values()).flatten() {
+            let next = sim.creation_nums.entry(*order.id.addr.inner()).or_default();
+            *next = (*next).max(order.id.creation_num.0 + 1);
+        }
+
+        sim
+    }
+
+    /// Only accept prices and sizes that are multiples of `price_tick` and `size_tick` units.
+    /// Both default to 1, as on-chain prices and sizes are in ticks of their decimals already.
+    pub fn with_ticks(mut self, price_tick: u64, size_tick: u64) -> Self {
+        self.price_tick = price_tick.max(1);
+        self.size_tick = size_tick.max(1);
+        self
+    }
+
+    pub fn book_id(&self) -> &Id {
+        &self.book_id
+    }
+
+    pub fn min_size_amount(&self) -> u64 {
+        self.min_size_amount
+    }
+
+    pub fn best_bid(&self) -> Option<u64> {
+        self.bids.keys().next_back().copied()
+    }
+
+    pub fn best_ask(&self) -> Option<u64> {
+        self.asks.keys().next().copied()
+    }
+
+    /// Resting orders of `side`, best price first and in time priority within a level.
+    pub fn orders(&self, side: Side) -> Box<dyn Iterator<Item = &Order> + '_> {
+        match side {
+            Side::Bid => Box::new(self.bids.values().rev().flatten()),
+            Side::Ask => Box::new(self.asks.values().flatten()),
+        }
+    }
+
+    pub fn order(&self, order_id: &Id) -> Option<&Order> {
+        self.bids
+            .values()
+            .chain(self.asks.values())
+            .flatten()
+            .find(|o| &o.id == order_id)
+    }
+
+    /// Total remaining size per price level of `side`, best price first.
+    pub fn depth(&self, side: Side) -> Vec<(u64, u64)> {
+        let total = |orders: &VecDeque<Order>| orders.iter().map(|o| o.remaining_size).sum();
+        match side {
+            Side::Bid => self
+                .bids
+                .iter()
+                .rev()
+                .map(|(p, o)| (*p, total(o)))
+                .collect(),
+            Side::Ask => self.asks.iter().map(|(p, o)| (*p, total(o))).collect(),
+        }
+    }
+
+    /// Aggregate the book into at most `depth` levels per side.
+    pub fn to_l2(&self, depth: usize) -> L2Book {
+        let level = |(price, orders): (&u64, &VecDeque<Order>)| L2Level {
+            price: *price,
+            total_size: orders.iter().map(|o| o.remaining_size).sum(),
+            order_count: orders.len() as u32,
+        };
+        L2Book {
+            book_id: self.book_id.clone(),
+            bids: self.bids.iter().rev().take(depth).map(level).collect(),
+            asks: self.asks.iter().take(depth).map(level).collect(),
+            ledger_version: None,
+        }
+    }
+
+    /// Fills an order would get as `(price, size)` pairs, without changing the book.
+    ///
+    /// # Arguments:
+    ///
+    /// * `side` - `Side` of the order.
+    /// * `price` - Limit price, `None` for a market order.
+    /// * `size` - Size of the order.
+    pub fn estimate_fills(&self, side: Side, price: Option<u64>, size: u64) -> Vec<(u64, u64)> {
+        let mut remaining = size;
+        let mut fills = vec![];
+        for (level_price, level_size) in self.depth(opposite(side)) {
+            if remaining == 0 || price.map_or(false, |p| !crosses(side, p, level_price)) {
+                break;
+            }
+            let fill = remaining.min(level_size);
+            fills.push((level_price, fill));
+            remaining -= fill;
+        }
+
+        fills
+    }
+
+    /// Place a limit order, returning the emitted events: the `PlaceOrderEvent`, a maker and
+    /// a taker `FillEvent` per match, and a `CancelOrderEvent` for size left unfilled by IOC
+    /// orders.
+    ///
+    /// # Arguments:
+    ///
+    /// * `owner` - Account placing the order.
+    /// * `side` - `Side`: Bid or Ask.
+    /// * `price` - Price of the order in units.
+    /// * `size` - Size of the order in units.
+    /// * `time_in_force` - `TimeInForce` of the order.
+    /// * `post_only` - Reject the order if it would take liquidity.
+    /// * `time` - Timestamp of the emitted events.
+    #[allow(clippy::too_many_arguments)]
+    pub fn place_limit_order(
+        &mut self,
+        owner: AccountAddress,
+        side: Side,
+        price: u64,
+        size: u64,
+        time_in_force: TimeInForce,
+        post_only: bool,
+        time: u64,
+    ) -> Result<Vec<LaminarEvent>, SimError> {
+        self.check_price(price)?;
+        self.place(
+            owner,
+            side,
+            Some(price),
+            size,
+            time_in_force,
+            post_only,
+            time,
+        )
+    }
+
+    /// Place a market order, which takes liquidity at any price and cancels what it cannot
+    /// fill. See `place_limit_order` for the emitted events.
+    pub fn place_market_order(
+        &mut self,
+        owner: AccountAddress,
+        side: Side,
+        size: u64,
+        time: u64,
+    ) -> Result<Vec<LaminarEvent>, SimError> {
+        self.place(
+            owner,
+            side,
+            None,
+            size,
+            TimeInForce::ImmediateOrCancel,
+            false,
+            time,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn place(
+        &mut self,
+        owner: AccountAddress,
+        side: Side,
+        price: Option<u64>,
+        size: u64,
+        time_in_force: TimeInForce,
+        post_only: bool,
+        time: u64,
+    ) -> Result<Vec<LaminarEvent>, SimError> {
+        if size == 0 || size < self.min_size_amount {
+            return Err(SimError::MinSize);
+        }
+        if size % self.size_tick != 0 {
+            return Err(SimError::SizeTick);
+        }
+        let crosses_book = |book: &Self| match (book.best(opposite(side)), price) {
+            (Some(best), Some(price)) => crosses(side, price, best),
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+        if post_only && crosses_book(self) {
+            return Err(SimError::PostOnly);
+        }
+        if time_in_force == TimeInForce::FillOrKill
+            && self
+                .estimate_fills(side, price, size)
+                .iter()
+                .map(|(_, s)| s)
+                .sum::<u64>()
+                < size
+        {
+            return Err(SimError::FillOrKill);
+        }
+
+        let order_id = self.next_id(owner);
+        let mut events = vec![LaminarEvent::PlaceOrder(PlaceOrderEvent {
+            book_id: self.book_id.clone(),
+            order_id: order_id.clone(),
+            side,
+            price: price.unwrap_or_default(),
+            size,
+            time_in_force,
+            post_only,
+            time,
+        })];
+
+        let mut remaining = size;
+        while remaining > 0 && crosses_book(self) {
+            let level_price = self.best(opposite(side)).expect("crossing level exists");
+            let book_id = self.book_id.clone();
+            let levels = self.side_mut(opposite(side));
+            let queue = levels.get_mut(&level_price).expect("level exists");
+            let maker = queue.front_mut().expect("levels are not empty");
+            let fill = remaining.min(maker.remaining_size);
+            maker.remaining_size -= fill;
+            maker.state = if maker.remaining_size == 0 {
+                State::Filled
+            } else {
+                State::PartiallyFilled
+            };
+            remaining -= fill;
+            events.push(LaminarEvent::FillEvent(FillEvent {
+                book_id: book_id.clone(),
+                order_id: maker.id.clone(),
+                side: maker.side,
+                price: level_price,
+                fill_size: fill,
+                fee: 0,
+                fee_rate: 0,
+                time,
+                remaining_size: maker.remaining_size,
+                is_maker: true,
+            }));
+            events.push(LaminarEvent::FillEvent(FillEvent {
+                book_id,
+                order_id: order_id.clone(),
+                side,
+                price: level_price,
+                fill_size: fill,
+                fee: 0,
+                fee_rate: 0,
+                time,
+                remaining_size: remaining,
+                is_maker: false,
+            }));
+
+            if maker.remaining_size == 0 {
+                queue.pop_front();
+            }
+            if queue.is_empty() {
+                levels.remove(&level_price);
+            }
+        }
+
+        if remaining > 0 {
+            match (time_in_force, price) {
+                (TimeInForce::GoodTillCanceled, Some(price)) => {
+                    let state = if remaining < size {
+                        State::PartiallyFilled
+                    } else {
+                        State::Open
+                    };
+                    self.side_mut(side)
+                        .entry(price)
+                        .or_default()
+                        .push_back(Order {
+                            id: order_id,
+                            side,
+                            price,
+                            size,
+                            post_only,
+                            remaining_size: remaining,
+                            state,
+                            fills: vec![],
+                        });
+                }
+                _ => {
+                    events.push(self.cancel_event(
+                        owner,
+                        order_id,
+                        side,
+                        CANCEL_REASON_UNFILLED,
+                        time,
+                    ));
+                }
+            }
+        }
+
+        Ok(events)
+    }
+
+    /// Amend a resting order. The order keeps its time priority if the price is unchanged and
+    /// the size is not increased, otherwise it moves to the back of its new level.
+    ///
+    /// # Arguments:
+    ///
+    /// * `owner` - Account that placed the order.
+    /// * `order_id` - Id of the order.
+    /// * `side` - `Side` of the order.
+    /// * `price` - New price in units.
+    /// * `size` - New total size in units, including the size already filled.
+    /// * `time` - Timestamp of the emitted event.
+    pub fn amend_order(
+        &mut self,
+        owner: AccountAddress,
+        order_id: &Id,
+        side: Side,
+        price: u64,
+        size: u64,
+        time: u64,
+    ) -> Result<Vec<LaminarEvent>, SimError> {
+        self.check_price(price)?;
+        let (old_price, idx) = self.find(owner, order_id, side)?;
+        let order = &self.side(side)[&old_price][idx];
+        let filled = order.size - order.remaining_size;
+        if size <= filled || size < self.min_size_amount {
+            return Err(SimError::MinSize);
+        }
+        if size % self.size_tick != 0 {
+            return Err(SimError::SizeTick);
+        }
+        if self
+            .best(opposite(side))
+            .map_or(false, |best| crosses(side, price, best))
+        {
+            return Err(SimError::WouldCross);
+        }
+
+        let keeps_priority = price == old_price && size <= order.size;
+        let levels = self.side_mut(side);
+        let queue = levels.get_mut(&old_price).expect("level exists");
+        let mut order = queue.remove(idx).expect("order exists");
+        if queue.is_empty() {
+            levels.remove(&old_price);
+        }
+        order.price = price;
+        order.size = size;
+        order.remaining_size = size - filled;
+        let queue = levels.entry(price).or_default();
+        if keeps_priority {
+            queue.insert(idx.min(queue.len()), order);
+        } else {
+            queue.push_back(order);
+        }
+
+        let amend_id = self.next_id(owner);
+        Ok(vec![LaminarEvent::AmendOrder(AmendOrderEvent {
+            book_id: self.book_id.clone(),
+            order_id: order_id.clone(),
+            amend_id,
+            side,
+            price,
+            size,
+            time,
+        })])
+    }
+
+    /// Cancel a resting order.
+    ///
+    /// # Arguments:
+    ///
+    /// * `owner` - Account that placed the order.
+    /// * `order_id` - Id of the order.
+    /// * `side` - `Side` of the order.
+    /// * `time` - Timestamp of the emitted event.
+    pub fn cancel_order(
+        &mut self,
+        owner: AccountAddress,
+        order_id: &Id,
+        side: Side,
+        time: u64,
+    ) -> Result<Vec<LaminarEvent>, SimError> {
+        let (price, idx) = self.find(owner, order_id, side)?;
+        let levels = self.side_mut(side);
+        let queue = levels.get_mut(&price).expect("level exists");
+        queue.remove(idx);
+        if queue.is_empty() {
+            levels.remove(&price);
+        }
+
+        Ok(vec![self.cancel_event(
+            owner,
+            order_id.clone(),
+            side,
+            CANCEL_REASON_USER,
+            time,
+        )])
+    }
+
+    /// Execute an `OrderRequest` of `owner`.
+    pub fn execute(
+        &mut self,
+        owner: AccountAddress,
+        request: &OrderRequest,
+        time: u64,
+    ) -> Result<Vec<LaminarEvent>, SimError> {
+        match request {
+            OrderRequest::PlaceLimit {
+                side,
+                price,
+                size,
+                time_in_force,
+                post_only,
+            } => self.place_limit_order(
+                owner,
+                *side,
+                *price,
+                *size,
+                *time_in_force,
+                *post_only,
+                time,
+            ),
+            OrderRequest::PlaceMarket { side, size } => {
+                self.place_market_order(owner, *side, *size, time)
+            }
+            OrderRequest::Amend {
+                order_id,
+                side,
+                price,
+                size,
+            } => self.amend_order(owner, order_id, *side, *price, *size, time),
+            OrderRequest::Cancel { order_id, side } => {
+                self.cancel_order(owner, order_id, *side, time)
+            }
+        }
+    }
+
+    fn side(&self, side: Side) -> &BTreeMap<u64, VecDeque<Order>> {
+        match side {
+            Side::Bid => &self.bids,
+            Side::Ask => &self.asks,
+        }
+    }
+
+    fn side_mut(&mut self, side: Side) -> &mut BTreeMap<u64, VecDeque<Order>> {
+        match side {
+            Side::Bid => &mut self.bids,
+            Side::Ask => &mut self.asks,
+        }
+    }
+
+    fn best(&self, side: Side) -> Option<u64> {
+        match side {
+            Side::Bid => self.best_bid(),
+            Side::Ask => self.best_ask(),
+        }
+    }
+
+    // price level and queue position of an order of `owner`
+    fn find(
+        &self,
+        owner: AccountAddress,
+        order_id: &Id,
+        side: Side,
+    ) -> Result<(u64, usize), SimError> {
+        if order_id.addr.inner() != &owner {
+            return Err(SimError::OrderNotFound);
+        }
+        self.side(side)
+            .iter()
+            .find_map(|(p, orders)| {
+                orders
+                    .iter()
+                    .position(|o| &o.id == order_id)
+                    .map(|i| (*p, i))
+            })
+            .ok_or(SimError::OrderNotFound)
+    }
+
+    fn next_id(&mut self, owner: AccountAddress) -> Id {
+        let next = self.creation_nums.entry(owner).or_default();
+        let creation_num = *next;
+        *next += 1;
+        Id {
+            creation_num: U64(creation_num),
+            addr: Address::from(owner),
+        }
+    }
+
+    fn check_price(&self, price: u64) -> Result<(), SimError> {
+        if price == 0 {
+            return Err(SimError::InvalidPrice);
+        }
+        if price % self.price_tick != 0 {
+            return Err(SimError::PriceTick);
+        }
+        Ok(())
+    }
+
+    fn cancel_event(
+        &mut self,
+        owner: AccountAddress,
+        order_id: Id,
+        side: Side,
+        reason: u8,
+        time: u64,
+    ) -> LaminarEvent {
+        let cancel_id = self.next_id(owner);
+        LaminarEvent::CancelOrder(CancelOrderEvent {
+            book_id: self.book_id.clone(),
+            order_id,
+            cancel_id,
+            side,
+            reason,
+            time,
+        })
+    }
+}
+
+/// Re-executes the events of a live or recorded book on a `SimBook`, so orders only present
+/// in the simulator trade against the real order flow.
+///
+/// Place, amend and cancel events of resting orders are executed, fills and cancels of
+/// unfilled size are produced by the simulator itself. Replayed orders get new ids, which
+/// later events of the order are mapped to. Orders of the snapshot the book was seeded with
+/// keep their ids.
+#[derive(Clone, Debug, Default)]
+pub struct EventReplay {
+    // ids of the source book to the ids of the replayed orders
+    ids: HashMap<Id, Id>,
+}
+
+impl EventReplay {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Execute `event` on `book`, returning the events emitted by the simulator.
+    pub fn apply(
+        &mut self,
+        book: &mut SimBook,
+        event: &LaminarEvent,
+    ) -> Result<Vec<LaminarEvent>, SimError> {
+        match event {
+            LaminarEvent::PlaceOrder(e) => {
+                let owner = *e.order_id.addr.inner();
+                // market orders are emitted without a price
+                let sim_events = if e.price == 0 {
+                    book.place_market_order(owner, e.side, e.size, e.time)?
+                } else {
+                    book.place_limit_order(
+                        owner,
+                        e.side,
+                        e.price,
+                        e.size,
+                        e.time_in_force,
+                        e.post_only,
+                        e.time,
+                    )?
+                };
+                if let Some(LaminarEvent::PlaceOrder(place)) = sim_events.first() {
+                    self.ids.insert(e.order_id.clone(), place.order_id.clone());
+                }
+                Ok(sim_events)
+            }
+            LaminarEvent::AmendOrder(e) => {
+                let id = self.id(&e.order_id);
+                book.amend_order(
+                    *e.order_id.addr.inner(),
+                    &id,
+                    e.side,
+                    e.price,
+                    e.size,
+                    e.time,
+                )
+            }
+            LaminarEvent::CancelOrder(e) => {
+                let id = self
+                    .ids
+                    .remove(&e.order_id)
+                    .unwrap_or_else(|| e.order_id.clone());
+                // the unfilled size of an order that doesn't rest was cancelled on placement
+                if book.order(&id).is_none() {
+                    return Ok(vec![]);
+                }
+                book.cancel_order(*e.order_id.addr.inner(), &id, e.side, e.time)
+            }
+            _ => Ok(vec![]),
+        }
+    }
+
+    fn id(&self, id: &Id) -> Id {
+        self.ids.get(id).unwrap_or(id).clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::order::Instrument;
+
+    fn id(creation_num: u64, addr: AccountAddress) -> Id {
+        Id {
+            creation_num: U64(creation_num),
+            addr: Address::from(addr),
+        }
+    }
+
+    fn order(id: Id, side: Side, price: u64, size: u64) -> Order {
+        Order {
+            id,
+            side,
+            price,
+            size,
+            post_only: false,
+            remaining_size: size,
+            state: State::default(),
+            fills: vec![],
+        }
+    }
+
+    #[test]
+    fn test_replay_amends_and_cancels_snapshot_orders() {
+        let owner = AccountAddress::ONE;
+        let trader = AccountAddress::from_hex_literal("0x2").unwrap();
+        let book_id = id(1, owner);
+        let order_id = id(7, trader);
+        let snapshot = OrderBook {
+            id: book_id.clone(),
+            instrument: Instrument {
+                owner,
+                price_decimals: 2,
+                size_decimals: 2,
+                min_size_amount: 1,
+                base_decimals: 6,
+                quote_decimals: 6,
+                rounding: Default::default(),
+            },
+            bids: BTreeMap::from([(100, vec![order(order_id.clone(), Side::Bid, 100, 10)])]),
+            asks: BTreeMap::new(),
+            type_tags: vec![],
+            ledger_version: None,
+        };
+        let mut book = SimBook::from_order_book(&snapshot);
+        let mut replay = EventReplay::new();
+
+        let amend = LaminarEvent::AmendOrder(AmendOrderEvent {
+            book_id: book_id.clone(),
+            order_id: order_id.clone(),
+            amend_id: id(8, trader),
+            side: Side::Bid,
+            price: 101,
+            size: 5,
+            time: 1,
+        });
+        replay.apply(&mut book, &amend).unwrap();
+        let amended = book.order(&order_id).unwrap();
+        assert_eq!((amended.price, amended.remaining_size), (101, 5));
+
+        let cancel = LaminarEvent::CancelOrder(CancelOrderEvent {
+            book_id,
+            order_id: order_id.clone(),
+            cancel_id: id(9, trader),
+            side: Side::Bid,
+            reason: CANCEL_REASON_USER,
+            time: 2,
+        });
+        replay.apply(&mut book, &cancel).unwrap();
+        assert!(book.order(&order_id).is_none());
+        assert_eq!(book.best_bid(), None);
+    }
+
+    fn book() -> SimBook {
+        SimBook::new(id(1, AccountAddress::ONE), 2)
+    }
+
+    fn account(n: u8) -> AccountAddress {
+        AccountAddress::from_hex_literal(&format!("0x{:x}", n + 1)).unwrap()
+    }
+
+    fn limit(book: &mut SimBook, owner: AccountAddress, side: Side, price: u64, size: u64) -> Id {
+        limit_order(
+            book,
+            owner,
+            side,
+            price,
+            size,
+            TimeInForce::GoodTillCanceled,
+            false,
+        )
+        .unwrap()
+    }
+
+    fn limit_order(
+        book: &mut SimBook,
+        owner: AccountAddress,
+        side: Side,
+        price: u64,
+        size: u64,
+        time_in_force: TimeInForce,
+        post_only: bool,
+    ) -> Result<Id, SimError> {
+        let events =
+            book.place_limit_order(owner, side, price, size, time_in_force, post_only, 0)?;
+        match events.first() {
+            Some(LaminarEvent::PlaceOrder(place)) => Ok(place.order_id.clone()),
+            _ => panic!("no place event"),
+        }
+    }
+
+    // (order id, price, size) of the maker fills
+    fn maker_fills(events: &[LaminarEvent]) -> Vec<(Id, u64, u64)> {
+        events
+            .iter()
+            .filter_map(|e| match e {
+                LaminarEvent::FillEvent(f) if f.is_maker => {
+                    Some((f.order_id.clone(), f.price, f.fill_size))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_price_time_priority() {
+        let mut book = book();
+        let first = limit(&mut book, account(1), Side::Ask, 101, 5);
+        let second = limit(&mut book, account(2), Side::Ask, 101, 5);
+        let better = limit(&mut book, account(3), Side::Ask, 100, 5);
+
+        let events = book
+            .place_market_order(account(4), Side::Bid, 12, 0)
+            .unwrap();
+        assert_eq!(
+            maker_fills(&events),
+            vec![(better, 100, 5), (first, 101, 5), (second.clone(), 101, 2)]
+        );
+        assert_eq!(book.order(&second).unwrap().remaining_size, 3);
+    }
+
+    #[test]
+    fn test_partial_fills_across_levels() {
+        let mut book = book();
+        limit(&mut book, account(1), Side::Ask, 100, 5);
+        limit(&mut book, account(1), Side::Ask, 101, 5);
+        limit(&mut book, account(1), Side::Ask, 102, 5);
+
+        let bid = limit(&mut book, account(2), Side::Bid, 101, 12);
+        assert_eq!(book.depth(Side::Ask), vec![(102, 5)]);
+        // the unfilled size rests at the limit price
+        assert_eq!(book.depth(Side::Bid), vec![(101, 2)]);
+        let bid = book.order(&bid).unwrap();
+        assert_eq!((bid.remaining_size, bid.state), (2, State::PartiallyFilled));
+    }
+
+    #[test]
+    fn test_fill_or_kill_is_all_or_nothing() {
+        let mut book = book();
+        limit(&mut book, account(1), Side::Ask, 100, 5);
+        limit(&mut book, account(1), Side::Ask, 101, 5);
+
+        let err = limit_order(
+            &mut book,
+            account(2),
+            Side::Bid,
+            101,
+            11,
+            TimeInForce::FillOrKill,
+            false,
+        )
+        .unwrap_err();
+        assert_eq!(err, SimError::FillOrKill);
+        assert_eq!(book.depth(Side::Ask), vec![(100, 5), (101, 5)]);
+
+        limit_order(
+            &mut book,
+            account(2),
+            Side::Bid,
+            101,
+            10,
+            TimeInForce::FillOrKill,
+            false,
+        )
+        .unwrap();
+        assert!(book.depth(Side::Ask).is_empty());
+        assert!(book.depth(Side::Bid).is_empty());
+    }
+
+    #[test]
+    fn test_post_only_rejected_on_cross() {
+        let mut book = book();
+        limit(&mut book, account(1), Side::Ask, 100, 5);
+
+        let err = limit_order(
+            &mut book,
+            account(2),
+            Side::Bid,
+            100,
+            5,
+            TimeInForce::GoodTillCanceled,
+            true,
+        )
+        .unwrap_err();
+        assert_eq!(err, SimError::PostOnly);
+        assert_eq!(err.abort_code(), EPOST_ONLY);
+        assert!(book.depth(Side::Bid).is_empty());
+
+        limit_order(
+            &mut book,
+            account(2),
+            Side::Bid,
+            99,
+            5,
+            TimeInForce::GoodTillCanceled,
+            true,
+        )
+        .unwrap();
+        assert_eq!(book.depth(Side::Bid), vec![(99, 5)]);
+    }
+
+    #[test]
+    fn test_min_size() {
+        let mut book = book();
+        let err = limit_order(
+            &mut book,
+            account(1),
+            Side::Bid,
+            100,
+            1,
+            TimeInForce::GoodTillCanceled,
+            false,
+        )
+        .unwrap_err();
+        assert_eq!(err, SimError::MinSize);
+        let order_id = limit(&mut book, account(1), Side::Bid, 100, 2);
+
+        let err = book
+            .amend_order(account(1), &order_id, Side::Bid, 100, 1, 0)
+            .unwrap_err();
+        assert_eq!(err, SimError::MinSize);
+    }
+
+    #[test]
+    fn test_amend_size_increase_loses_priority() {
+        let mut book = book();
+        let first = limit(&mut book, account(1), Side::Bid, 100, 5);
+        let second = limit(&mut book, account(2), Side::Bid, 100, 5);
+
+        // a size decrease keeps the order in front
+        book.amend_order(account(1), &first, Side::Bid, 100, 4, 0)
+            .unwrap();
+        let ids = book
+            .orders(Side::Bid)
+            .map(|o| o.id.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![first.clone(), second.clone()]);
+
+        book.amend_order(account(1), &first, Side::Bid, 100, 6, 0)
+            .unwrap();
+        let ids = book
+            .orders(Side::Bid)
+            .map(|o| o.id.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![second, first]);
+    }
+
+    #[test]
+    fn test_off_tick_price_and_size_rejected() {
+        let mut book = book().with_ticks(5, 2);
+        let err = limit_order(
+            &mut book,
+            account(1),
+            Side::Bid,
+            101,
+            4,
+            TimeInForce::GoodTillCanceled,
+            false,
+        )
+        .unwrap_err();
+        assert_eq!(err, SimError::PriceTick);
+        assert_eq!(err.abort_code(), EINVALID_ARGUMENT);
+        let err = limit_order(
+            &mut book,
+            account(1),
+            Side::Bid,
+            100,
+            5,
+            TimeInForce::GoodTillCanceled,
+            false,
+        )
+        .unwrap_err();
+        assert_eq!(err, SimError::SizeTick);
+
+        let order_id = limit(&mut book, account(1), Side::Bid, 100, 4);
+        let err = book
+            .amend_order(account(1), &order_id, Side::Bid, 102, 4, 0)
+            .unwrap_err();
+        assert_eq!(err, SimError::PriceTick);
+    }
+}
@@ -0,0 +1,434 @@
+use crate::checkpoint::Checkpoint;
+use crate::error::{LaminarError, Result};
+use crate::market_data::{MarketDataFeed, MarketDataUpdate};
+use crate::time::{sleep, unix_now_micros, Instant};
+use crate::trading::Trader;
+use crate::types::events::{FillEvent, LaminarEvent};
+use crate::types::l2::L2Book;
+use crate::types::market::Market;
+use crate::types::order::{Id, Side, TimeInForce};
+use crate::LaminarClient;
+use anyhow::anyhow;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Order action requested by a `Strategy`. Prices and sizes are in units.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OrderRequest {
+    PlaceLimit {
+        side: Side,
+        price: u64,
+        size: u64,
+        time_in_force: TimeInForce,
+        post_only: bool,
+    },
+    PlaceMarket {
+        side: Side,
+        size: u64,
+    },
+    Amend {
+        order_id: Id,
+        side: Side,
+        price: u64,
+        size: u64,
+    },
+    Cancel {
+        order_id: Id,
+        side: Side,
+    },
+}
+
+/// Passed to `Strategy` callbacks to read the current time and request order actions.
+/// Requests are executed after the callback returns, in order.
+#[derive(Debug)]
+pub struct StrategyContext {
+    book_id: Id,
+    time: u64,
+    requests: Vec<OrderRequest>,
+}
+
+impl StrategyContext {
+    pub fn new(book_id: Id, time: u64) -> Self {
+        Self {
+            book_id,
+            time,
+            requests: vec![],
+        }
+    }
+
+    /// Book the strategy trades on.
+    pub fn book_id(&self) -> &Id {
+        &self.book_id
+    }
+
+    /// Time of the event being handled, in the unit of `LaminarEvent::time`.
+    pub fn time(&self) -> u64 {
+        self.time
+    }
+
+    pub fn set_time(&mut self, time: u64) {
+        self.time = time;
+    }
+
+    pub fn place_limit_order(
+        &mut self,
+        side: Side,
+        price: u64,
+        size: u64,
+        time_in_force: TimeInForce,
+        post_only: bool,
+    ) {
+        self.requests.push(OrderRequest::PlaceLimit {
+            side,
+            price,
+            size,
+            time_in_force,
+            post_only,
+        });
+    }
+
+    pub fn place_market_order(&mut self, side: Side, size: u64) {
+        self.requests.push(OrderRequest::PlaceMarket { side, size });
+    }
+
+    pub fn amend_order(&mut self, order_id: &Id, side: Side, price: u64, size: u64) {
+        self.requests.push(OrderRequest::Amend {
+            order_id: order_id.clone(),
+            side,
+            price,
+            size,
+        });
+    }
+
+    pub fn cancel_order(&mut self, order_id: &Id, side: Side) {
+        self.requests.push(OrderRequest::Cancel {
+            order_id: order_id.clone(),
+            side,
+        });
+    }
+
+    /// Take the requests made since the last call.
+    pub fn take_requests(&mut self) -> Vec<OrderRequest> {
+        std::mem::take(&mut self.requests)
+    }
+}
+
+/// Trading logic driven by book and order events of a single book. All callbacks default to
+/// doing nothing.
+pub trait Strategy {
+    /// Latest aggregated depth of the book, read from a snapshot on every poll of the driver.
+    fn on_book_update(&mut self, _ctx: &mut StrategyContext, _book: &L2Book) {}
+
+    /// An order of the strategy was filled.
+    fn on_fill(&mut self, _ctx: &mut StrategyContext, _fill: &FillEvent) {}
+
+    /// An order of the strategy was placed, amended or cancelled.
+    fn on_order_update(&mut self, _ctx: &mut StrategyContext, _event: &LaminarEvent) {}
+
+    /// Called at the interval configured in the driver.
+    fn on_timer(&mut self, _ctx: &mut StrategyContext) {}
+
+    /// A request of the strategy was rejected, by the book or before it was submitted.
+    fn on_reject(&mut self, _ctx: &mut StrategyContext, _request: &OrderRequest, _reason: &str) {}
+
+    /// A request failed without being rejected, e.g. the node was unreachable. If
+    /// `error.is_outcome_unknown()` the request may still take effect, its events are then
+    /// delivered as usual.
+    fn on_error(
+        &mut self,
+        _ctx: &mut StrategyContext,
+        _request: &OrderRequest,
+        _error: &LaminarError,
+    ) {
+    }
+}
+
+/// Stops a `StrategyRunner` after its current poll.
+#[derive(Clone, Debug, Default)]
+pub struct ShutdownHandle(Arc<AtomicBool>);
+
+impl ShutdownHandle {
+    pub fn shutdown(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_shutdown(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// Drives a `Strategy` on a live book: polls a `MarketDataFeed`, passes the events of the
+/// trader's orders and the book depth to the strategy, and submits its requests with the
+/// trader, a `LaminarClient` or a `PaperTrader`.
+///
+/// The feed starts at the latest committed transaction, so events from before the start are
+/// not passed to the strategy. With `with_checkpoint` a restarted runner resumes after the
+/// events already handled instead.
+///
+/// A panic in a callback stops the runner instead of unwinding through the caller, as the
+/// strategy's state can no longer be trusted. Open orders are cancelled when the runner
+/// stops, unless disabled with `with_cancel_on_shutdown`.
+pub struct StrategyRunner<S> {
+    market: Market,
+    strategy: S,
+    feed: MarketDataFeed,
+    poll_interval: Duration,
+    timer_interval: Option<Duration>,
+    depth: usize,
+    cancel_on_shutdown: bool,
+    shutdown: ShutdownHandle,
+}
+
+impl<S: Strategy> StrategyRunner<S> {
+    pub fn new(market: Market, strategy: S) -> Self {
+        let feed =
+            MarketDataFeed::new(market.base.clone(), market.quote.clone(), market.book_owner);
+        Self {
+            market,
+            strategy,
+            feed,
+            poll_interval: Duration::from_secs(1),
+            timer_interval: None,
+            depth: 10,
+            cancel_on_shutdown: true,
+            shutdown: ShutdownHandle::default(),
+        }
+    }
+
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Call `Strategy::on_timer` at `interval`, checked after every poll.
+    pub fn with_timer_interval(mut self, interval: Duration) -> Self {
+        self.timer_interval = Some(interval);
+        self
+    }
+
+    /// Number of levels per side passed to `Strategy::on_book_update`.
+    pub fn with_depth(mut self, depth: usize) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    /// Resume the feed from the cursor saved under `key` in `checkpoint`, saving it there
+    /// after the events of each poll were handled.
+    pub fn with_checkpoint(
+        mut self,
+        checkpoint: Arc<dyn Checkpoint>,
+        key: impl Into<String>,
+    ) -> Result<Self> {
+        self.feed = self.feed.with_checkpoint(checkpoint, key)?;
+        Ok(self)
+    }
+
+    pub fn with_cancel_on_shutdown(mut self, cancel_on_shutdown: bool) -> Self {
+        self.cancel_on_shutdown = cancel_on_shutdown;
+        self
+    }
+
+    pub fn shutdown_handle(&self) -> ShutdownHandle {
+        self.shutdown.clone()
+    }
+
+    /// Run until shut down, trading with `client`, returning the strategy. Feed errors are
+    /// retried at the next poll. Returns an error if a callback panicked, after cancelling
+    /// open orders.
+    pub async fn run(self, client: &LaminarClient) -> Result<S> {
+        self.run_with(client, client).await
+    }
+
+    /// Same as `run`, trading with `trader`, e.g. a `PaperTrader`. `client` reads the live
+    /// book and its events.
+    pub async fn run_with<T: Trader>(mut self, client: &LaminarClient, trader: &T) -> Result<S> {
+        let res = self.run_loop(client, trader).await;
+        let cancelled = if self.cancel_on_shutdown {
+            trader
+                .cancel_all_orders(&[self.market.clone()])
+                .await
+                .map(|_| ())
+        } else {
+            Ok(())
+        };
+        res.and(cancelled).map(|_| self.strategy)
+    }
+
+    async fn run_loop<T: Trader>(&mut self, client: &LaminarClient, trader: &T) -> Result<()> {
+        let book = client
+            .fetch_orderbook(
+                &self.market.base,
+                &self.market.quote,
+                &self.market.book_owner,
+            )
+            .await?;
+        let mut ctx = StrategyContext::new(book.id, unix_now_micros());
+        let mut last_timer = Instant::now();
+
+        while !self.shutdown.is_shutdown() {
+            match self.feed.poll(client).await {
+                Ok(updates) => {
+                    if !updates.is_empty() {
+                        self.dispatch(trader, &mut ctx, &updates).await?;
+                    }
+                    self.feed.commit()?;
+                }
+                Err(_e) => {
+                    trace_event!(warn, error = %_e, "market data poll failed");
+                }
+            }
+            // the depth is read every poll, a quiet book still drives the strategy
+            match trader.fetch_depth(&self.market, self.depth).await {
+                Ok(l2) => {
+                    ctx.set_time(unix_now_micros());
+                    self.callback("on_book_update", |s| s.on_book_update(&mut ctx, &l2))?;
+                }
+                Err(_e) => {
+                    trace_event!(warn, error = %_e, "book fetch failed");
+                }
+            }
+
+            if let Some(interval) = self.timer_interval {
+                if last_timer.elapsed() >= interval {
+                    last_timer = Instant::now();
+                    ctx.set_time(unix_now_micros());
+                    self.callback("on_timer", |s| s.on_timer(&mut ctx))?;
+                }
+            }
+            self.execute(trader, &mut ctx).await?;
+
+            sleep(self.poll_interval).await;
+        }
+
+        Ok(())
+    }
+
+    async fn dispatch<T: Trader>(
+        &mut self,
+        trader: &T,
+        ctx: &mut StrategyContext,
+        updates: &[MarketDataUpdate],
+    ) -> Result<()> {
+        let events = updates
+            .iter()
+            .filter_map(|update| match update {
+                MarketDataUpdate::Event(event) => Some(event.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let own_events = match trader.order_events(&self.market, &events).await {
+            Ok(own_events) => own_events,
+            Err(_e) => {
+                trace_event!(warn, error = %_e, "order events failed");
+                vec![]
+            }
+        };
+        for event in &own_events {
+            ctx.set_time(event.time());
+            match event {
+                LaminarEvent::FillEvent(fill) => {
+                    self.callback("on_fill", |s| s.on_fill(ctx, fill))?;
+                }
+                LaminarEvent::PlaceOrder(_)
+                | LaminarEvent::AmendOrder(_)
+                | LaminarEvent::CancelOrder(_) => {
+                    self.callback("on_order_update", |s| s.on_order_update(ctx, event))?;
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    // submit the requests made in callbacks, rejections and other errors are passed back to
+    // the strategy
+    async fn execute<T: Trader>(&mut self, trader: &T, ctx: &mut StrategyContext) -> Result<()> {
+        let Market {
+            base,
+            quote,
+            book_owner,
+        } = &self.market;
+        for request in ctx.take_requests() {
+            let res = match &request {
+                OrderRequest::PlaceLimit {
+                    side,
+                    price,
+                    size,
+                    time_in_force,
+                    post_only,
+                } => trader
+                    .place_limit_order(
+                        base,
+                        quote,
+                        book_owner,
+                        *side,
+                        *price,
+                        *size,
+                        *time_in_force,
+                        *post_only,
+                    )
+                    .await
+                    .map(|_| ()),
+                OrderRequest::PlaceMarket { side, size } => trader
+                    .place_market_order(base, quote, book_owner, *side, *size)
+                    .await
+                    .map(|_| ()),
+                OrderRequest::Amend {
+                    order_id,
+                    side,
+                    price,
+                    size,
+                } => trader
+                    .amend_order(base, quote, book_owner, order_id, *side, *price, *size)
+                    .await
+                    .map(|_| ()),
+                OrderRequest::Cancel { order_id, side } => trader
+                    .cancel_order(base, quote, book_owner, order_id, *side)
+                    .await
+                    .map(|_| ()),
+            };
+            let Err(e) = res else {
+                continue;
+            };
+            let strategy = &mut self.strategy;
+            let rejected = matches!(
+                e,
+                LaminarError::MoveAbort { .. }
+                    | LaminarError::ExecutionFailure { .. }
+                    | LaminarError::Rejected(_)
+                    | LaminarError::InvalidInput(_)
+                    | LaminarError::RiskLimit(_)
+            );
+            if rejected {
+                let reason = e.to_string();
+                catch_unwind(AssertUnwindSafe(|| {
+                    strategy.on_reject(ctx, &request, &reason)
+                }))
+                .map_err(|p| panicked("on_reject", p))?;
+            } else {
+                trace_event!(warn, error = %e, "strategy request failed");
+                catch_unwind(AssertUnwindSafe(|| strategy.on_error(ctx, &request, &e)))
+                    .map_err(|p| panicked("on_error", p))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn callback(&mut self, name: &str, f: impl FnOnce(&mut S)) -> Result<()> {
+        let strategy = &mut self.strategy;
+        catch_unwind(AssertUnwindSafe(|| f(strategy))).map_err(|p| panicked(name, p))
+    }
+}
+
+fn panicked(callback: &str, payload: Box<dyn std::any::Any + Send>) -> LaminarError {
+    let message = payload
+        .downcast_ref::<&str>()
+        .map(|m| m.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_default();
+    LaminarError::Other(anyhow!("strategy panicked in {}: {}", callback, message))
+}
@@ -0,0 +1,234 @@
+//! Submission of transactions that must not commit twice, e.g. order placements.
+//!
+//! Signing a new transaction on every retry could end with two committed after a timeout while
+//! waiting for one the node had already accepted. Instead a payload is signed once and the same
+//! signed transaction is resubmitted for as long as it may commit, which it can only do once.
+//! A new one is only signed once the first is provably absent: not committed, and either expired
+//! or its sequence number used by another transaction. `build_and_submit_tx` and
+//! `build_and_submit_tx_with_deadline` submit every transaction this way.
+
+use crate::error::{LaminarError, Result};
+use crate::rate_limit::Endpoint;
+use crate::time::sleep;
+use crate::{LaminarClient, LaminarTransaction, TxOptions};
+use aptos_api_types::{AptosErrorCode, HashValue, Transaction};
+use aptos_sdk::rest_client::error::RestError;
+use aptos_sdk::types::transaction::{EntryFunction, SignedTransaction, TransactionPayload};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+const STATUS_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Signed transaction that is submitted until its outcome is known. It can be persisted to be
+/// submitted again after a restart.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Submission {
+    pub hash: HashValue,
+    pub sequence_number: u64,
+    pub expiration_timestamp_secs: u64,
+    signed_tx: SignedTransaction,
+}
+
+impl Submission {
+    fn new(signed_tx: SignedTransaction) -> Self {
+        Self {
+            hash: signed_tx.clone().committed_hash().into(),
+            sequence_number: signed_tx.sequence_number(),
+            expiration_timestamp_secs: signed_tx.expiration_timestamp_secs(),
+            signed_tx,
+        }
+    }
+}
+
+/// Outcome of a `Submission` as far as the chain can tell.
+#[derive(Debug)]
+pub enum SubmissionStatus {
+    /// The transaction was committed and executed successfully.
+    Committed(LaminarTransaction),
+    /// The transaction was committed and failed.
+    Failed(LaminarError),
+    /// The transaction may still be committed.
+    Pending,
+    /// The transaction was not committed and never will be, its payload can be signed again.
+    /// `replaced_by` is the Laminar transaction that used its sequence number instead, if any.
+    Absent {
+        replaced_by: Option<LaminarTransaction>,
+    },
+}
+
+impl LaminarClient {
+    /// Sign a tx without submitting it, for `submit_once`. The tx takes the next sequence
+    /// number, so it must be submitted for later txs to commit.
+    ///
+    /// # Arguments:
+    ///
+    /// * `payload` - Entry function payload to be used in the tx.
+    /// * `options` - Gas and expiration settings for this tx.
+    pub async fn sign_submission(
+        &self,
+        payload: EntryFunction,
+        options: TxOptions,
+    ) -> Result<Submission> {
+        let signed_tx = self
+            .sign_payload(TransactionPayload::EntryFunction(payload), options)
+            .await?;
+        Ok(Submission::new(signed_tx))
+    }
+
+    /// Look up whether a submitted tx was committed, may still be, or never will be.
+    ///
+    /// # Arguments:
+    ///
+    /// * `submission` - Submission to check.
+    pub async fn submission_status(&self, submission: &Submission) -> Result<SubmissionStatus> {
+        self.tx_status(
+            submission.hash,
+            submission.sequence_number,
+            submission.expiration_timestamp_secs,
+        )
+        .await
+    }
+
+    // status of a tx sent from this client's account. The sequence number of a tx that expired
+    // unused is handed out again, unless another tx was signed with it since.
+    pub(crate) async fn tx_status(
+        &self,
+        hash: HashValue,
+        sequence_number: u64,
+        expiration_timestamp_secs: u64,
+    ) -> Result<SubmissionStatus> {
+        if let Some(status) = self.status_by_hash(hash).await? {
+            return Ok(status);
+        }
+
+        // the ledger time is read before the sequence number: once the tx is expired and its
+        // sequence number still unused, it can't commit anymore
+        self.rate_limit(Endpoint::Index).await;
+        let index = self
+            .aptos_client
+            .get_index()
+            .await
+            .map_err(|e| LaminarError::rest(e, "failed getting ledger info"))?
+            .into_inner();
+        let chain_seq_num = self.get_sequence_number().await?;
+        self.sequence_numbers.sync(chain_seq_num);
+        if chain_seq_num > sequence_number {
+            // the sequence number was used, by this tx if it committed since the lookup above
+            if let Some(status) = self.status_by_hash(hash).await? {
+                return Ok(status);
+            }
+            let replaced_by = self.transaction_at_sequence_number(sequence_number).await?;
+            return Ok(SubmissionStatus::Absent { replaced_by });
+        }
+        if index.ledger_timestamp.0 >= expiration_timestamp_secs * 1_000_000 {
+            self.sequence_numbers.release(sequence_number, Some(hash));
+            return Ok(SubmissionStatus::Absent { replaced_by: None });
+        }
+
+        Ok(SubmissionStatus::Pending)
+    }
+
+    async fn status_by_hash(&self, hash: HashValue) -> Result<Option<SubmissionStatus>> {
+        self.rate_limit(Endpoint::Transactions).await;
+        let tx = match self.aptos_client.get_transaction_by_hash(hash.into()).await {
+            Ok(tx) => tx.into_inner(),
+            Err(RestError::Api(a)) if a.error.error_code == AptosErrorCode::TransactionNotFound => {
+                return Ok(None)
+            }
+            Err(e) => {
+                return Err(LaminarError::rest(
+                    e,
+                    format!("failed getting tx: {}", hash),
+                ))
+            }
+        };
+        let status = match tx {
+            Transaction::PendingTransaction(_) => SubmissionStatus::Pending,
+            tx => match self.to_laminar_transaction(tx) {
+                Ok(lt) => SubmissionStatus::Committed(lt),
+                Err(e) => SubmissionStatus::Failed(e),
+            },
+        };
+        Ok(Some(status))
+    }
+
+    async fn transaction_at_sequence_number(
+        &self,
+        sequence_number: u64,
+    ) -> Result<Option<LaminarTransaction>> {
+        self.rate_limit(Endpoint::Transactions).await;
+        let txs = self
+            .aptos_client
+            .get_account_transactions(self.signer.address(), Some(sequence_number), Some(1))
+            .await
+            .map_err(|e| {
+                LaminarError::rest(
+                    e,
+                    format!(
+                        "failed getting tx with sequence number: {}",
+                        sequence_number
+                    ),
+                )
+            })?
+            .into_inner();
+        Ok(txs
+            .into_iter()
+            .next()
+            .and_then(|tx| self.to_laminar_transaction(tx).ok()))
+    }
+
+    /// Submit a signed tx until it is committed or provably absent. While the outcome is
+    /// unknown the same tx is resubmitted, which can't commit it twice.
+    ///
+    /// Returns `SubmissionStatus::Committed` or `SubmissionStatus::Absent`, failed txs are
+    /// returned as errors. If the outcome is still unknown after the client's `max_retries`,
+    /// `LaminarError::Unconfirmed` is returned and the submission can be checked again later.
+    ///
+    /// # Arguments:
+    ///
+    /// * `submission` - Submission returned by `sign_submission`.
+    pub async fn submit_once(&self, submission: &Submission) -> Result<SubmissionStatus> {
+        for i in 0..=self.tx_options.max_retries {
+            let submitted = match i {
+                0 => self.broadcast(&submission.signed_tx).await,
+                _ => self.submit_signed_tx(&submission.signed_tx).await,
+            };
+            let res = match submitted {
+                Ok(pending) => self.wait_for_laminar_tx(&pending).await,
+                Err(e) => Err(e),
+            };
+            match res {
+                Ok(lt) => {
+                    self.record_audit(&lt);
+                    return Ok(SubmissionStatus::Committed(lt));
+                }
+                Err(
+                    e @ (LaminarError::MoveAbort { .. } | LaminarError::ExecutionFailure { .. }),
+                ) => return Err(e),
+                // a copy submitted earlier may still be in the mempool, only trust the first
+                Err(e @ LaminarError::Rejected(_)) if i == 0 => return Err(e),
+                Err(_e) => {
+                    trace_event!(
+                        warn,
+                        attempt = i + 1,
+                        hash = %submission.hash,
+                        error = %_e,
+                        "tx outcome unknown"
+                    );
+                }
+            }
+
+            match self.submission_status(submission).await {
+                Ok(SubmissionStatus::Committed(lt)) => {
+                    self.record_audit(&lt);
+                    return Ok(SubmissionStatus::Committed(lt));
+                }
+                Ok(SubmissionStatus::Failed(e)) => return Err(e),
+                Ok(SubmissionStatus::Pending) | Err(_) => sleep(STATUS_POLL_INTERVAL).await,
+                Ok(absent) => return Ok(absent),
+            }
+        }
+
+        Err(LaminarError::Unconfirmed(Box::new(submission.clone())))
+    }
+}
@@ -0,0 +1,236 @@
+//! Paged reading of book sides whose levels are stored in a Move table.
+//!
+//! An inline side is returned whole with its resource, so very deep books run into the
+//! resource size and response limits of the API. When the splay tree of a side keeps its
+//! nodes in a `Table` or `TableWithLength` keyed by node index, its `nodes` field only holds
+//! the table handle and the levels are read as table items, a page at a time.
+
+use crate::error::{LaminarError, Result};
+use crate::rate_limit::Endpoint;
+use crate::types::order::{removed_nodes, side_tree, OrderBook, Side};
+use crate::LaminarClient;
+use anyhow::anyhow;
+use aptos_api_types::{AptosError, AptosErrorCode};
+use aptos_sdk::move_types::language_storage::TypeTag;
+use aptos_sdk::rest_client::error::RestError;
+use aptos_sdk::rest_client::Resource;
+use aptos_sdk::types::account_address::AccountAddress;
+use futures::future::join_all;
+use serde_json::{json, Value};
+use std::collections::HashSet;
+
+/// Move types of a book side whose splay tree keeps its nodes in a table.
+#[derive(Clone, Debug)]
+pub struct TableBookLayout {
+    node_type: String,
+    page_size: usize,
+}
+
+impl TableBookLayout {
+    /// # Arguments:
+    ///
+    /// * `node_type` - Move type of the table values, the splay tree node of a price level as
+    /// declared by the deployed book module. `{laminar}` is replaced by the Laminar address.
+    pub fn new(node_type: impl Into<String>) -> Self {
+        Self {
+            node_type: node_type.into(),
+            page_size: 25,
+        }
+    }
+
+    /// Set how many table items are requested at once.
+    pub fn with_page_size(mut self, page_size: usize) -> Self {
+        self.page_size = page_size.max(1);
+        self
+    }
+}
+
+// a `Table` is `{ handle }`, a `TableWithLength` is `{ inner: { handle }, length }`
+fn table_handle(nodes: &Value) -> Option<(AccountAddress, Option<u64>)> {
+    let (table, length) = match nodes.get("inner") {
+        Some(inner) => (inner, nodes.get("length")),
+        None => (nodes, None),
+    };
+    let handle = AccountAddress::from_hex_literal(table.get("handle")?.as_str()?).ok()?;
+    let length = length.and_then(|l| l.as_str()).and_then(|l| l.parse().ok());
+    Some((handle, length))
+}
+
+fn parse_error(e: serde_json::Error) -> LaminarError {
+    LaminarError::deserialization(e, "order book")
+}
+
+impl LaminarClient {
+    /// Fetch a single side of an `OrderBook`, reading its levels page by page if the side
+    /// stores them in a table. Sides stored inline are parsed from their resource like
+    /// `fetch_orderbook_side` does, so this works with both layouts.
+    ///
+    /// Table items are read at the version of the resource, so the levels and the freed nodes
+    /// come from the same snapshot. `OrderBook::ledger_version` is that version.
+    ///
+    /// # Arguments:
+    ///
+    /// * `base` - Aptos `TypeTag` of the orderbook base coin.
+    /// * `quote` - Aptos `TypeTag` of the orderbook quote coin.
+    /// * `book_owner` - Address of the account that owns the `OrderBook`.
+    /// * `side` - Side to fetch.
+    /// * `layout` - Move types of the table.
+    /// * `depth` - Number of levels to keep, `None` for all.
+    pub async fn fetch_orderbook_side_paged(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        book_owner: &AccountAddress,
+        side: Side,
+        layout: &TableBookLayout,
+        depth: Option<usize>,
+    ) -> Result<OrderBook> {
+        let book_type = match side {
+            Side::Bid => self.get_book_bids_type(base, quote),
+            Side::Ask => self.get_book_asks_type(base, quote),
+        };
+        let (resource, read_at) = self
+            .fetch_resource_at(*book_owner, &book_type, None)
+            .await?;
+        let Resource {
+            data,
+            resource_type,
+        } = resource.ok_or_else(|| LaminarError::BookNotFound(book_type))?;
+
+        let tree = side_tree(&data, side).map_err(parse_error)?;
+        let mut book = match tree.get("nodes").and_then(table_handle) {
+            Some((handle, length)) => {
+                let removed = removed_nodes(tree).map_err(parse_error)?;
+                let nodes = self
+                    .fetch_table_nodes(handle, length, &removed, layout, read_at)
+                    .await?;
+                let nodes = nodes.iter().map(|(i, node)| (*i, node));
+                OrderBook::from_side_nodes(&data, side, nodes, depth).map_err(parse_error)?
+            }
+            None => match depth {
+                Some(depth) => OrderBook::from_side_resource(&data, side, depth),
+                None => serde_json::from_value::<OrderBook>(data),
+            }
+            .map_err(parse_error)?,
+        };
+        let types = resource_type.type_params;
+        book.type_tags.extend(types);
+        book.ledger_version = Some(read_at);
+        trace_event!(
+            debug,
+            bid_levels = book.bids.len(),
+            ask_levels = book.asks.len(),
+            version = read_at,
+            "fetched paged book side"
+        );
+        Ok(book)
+    }
+
+    /// Read the nodes of a splay tree stored in a table with their index at `version`,
+    /// skipping freed nodes. Without a known length nodes are read until the first missing
+    /// index.
+    async fn fetch_table_nodes(
+        &self,
+        handle: AccountAddress,
+        length: Option<u64>,
+        removed: &HashSet<usize>,
+        layout: &TableBookLayout,
+        version: u64,
+    ) -> Result<Vec<(usize, Value)>> {
+        let node_type = layout
+            .node_type
+            .replace("{laminar}", &self.laminar.to_hex_literal());
+        let mut nodes = vec![];
+        let mut next = 0;
+        loop {
+            let mut page = vec![];
+            while page.len() < layout.page_size && length.map_or(true, |l| (next as u64) < l) {
+                if !removed.contains(&next) {
+                    page.push(next);
+                }
+                next += 1;
+            }
+            if page.is_empty() {
+                return Ok(nodes);
+            }
+
+            let items = join_all(
+                page.iter()
+                    .map(|i| self.fetch_table_item(handle, &node_type, *i, version)),
+            )
+            .await;
+            for (i, item) in page.into_iter().zip(items) {
+                match item? {
+                    Some(node) => nodes.push((i, node)),
+                    None if length.is_none() => return Ok(nodes),
+                    None => {
+                        return Err(LaminarError::NotFound(format!(
+                            "node {} of table {}",
+                            i,
+                            handle.to_hex_literal()
+                        )))
+                    }
+                }
+            }
+        }
+    }
+
+    // the rest client only reads json table items at the latest version, so the versioned
+    // request is made directly
+    async fn fetch_table_item(
+        &self,
+        handle: AccountAddress,
+        node_type: &str,
+        index: usize,
+        version: u64,
+    ) -> Result<Option<Value>> {
+        let context = || {
+            format!(
+                "failed getting item {} of table {}",
+                index,
+                handle.to_hex_literal()
+            )
+        };
+        let url = self
+            .aptos_client
+            .build_path(&format!("tables/{}/item", handle.to_hex_literal()))
+            .map_err(|e| LaminarError::rest(e, context()))?;
+        let body = json!({
+            "key_type": "u64",
+            "value_type": node_type,
+            "key": index.to_string(),
+        });
+
+        self.rate_limit(Endpoint::Resources).await;
+        let response = self
+            .http_client
+            .post(url)
+            .query(&[("ledger_version", version)])
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.to_string())
+            .send()
+            .await
+            .map_err(|e| LaminarError::rest(RestError::from(e), context()))?;
+        let status = response.status();
+        let bytes = response
+            .bytes()
+            .await
+            .map_err(|e| LaminarError::rest(RestError::from(e), context()))?;
+        if status.is_success() {
+            let item = serde_json::from_slice(&bytes)
+                .map_err(|e| LaminarError::deserialization(e, "table item"))?;
+            return Ok(Some(item));
+        }
+        match serde_json::from_slice::<AptosError>(&bytes) {
+            Ok(e) if e.error_code == AptosErrorCode::TableItemNotFound => Ok(None),
+            Ok(e) => Err(LaminarError::rest(
+                RestError::Unknown(anyhow!("{}: {}", status, e.message)),
+                context(),
+            )),
+            Err(_) => Err(LaminarError::rest(
+                RestError::Unknown(anyhow!("{}", status)),
+                context(),
+            )),
+        }
+    }
+}
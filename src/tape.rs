@@ -0,0 +1,245 @@
+use crate::error::Result;
+use crate::types::events::FillEvent;
+use crate::types::order::{Id, Side};
+use crate::LaminarClient;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+
+/// Public trade, reconciled from the maker and taker fills of one match.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Trade {
+    pub book_id: Id,
+    pub price: u64,
+    pub size: u64,
+    /// Side of the taker.
+    pub aggressor: Side,
+    pub time: u64,
+    pub maker_order: Option<Id>,
+    pub taker_order: Option<Id>,
+}
+
+impl Trade {
+    fn from_fill(fill: &FillEvent) -> Self {
+        let (aggressor, maker_order, taker_order) = if fill.is_maker {
+            let aggressor = match fill.side {
+                Side::Bid => Side::Ask,
+                Side::Ask => Side::Bid,
+            };
+            (aggressor, Some(fill.order_id.clone()), None)
+        } else {
+            (fill.side, None, Some(fill.order_id.clone()))
+        };
+
+        Self {
+            book_id: fill.book_id.clone(),
+            price: fill.price,
+            size: fill.fill_size,
+            aggressor,
+            time: fill.time,
+            maker_order,
+            taker_order,
+        }
+    }
+
+    fn merge(&mut self, fill: &FillEvent) {
+        if fill.is_maker {
+            self.maker_order = Some(fill.order_id.clone());
+        } else {
+            self.aggressor = fill.side;
+            self.taker_order = Some(fill.order_id.clone());
+        }
+    }
+}
+
+/// Fields shared by the maker and taker fill of a match.
+type TradeKey = (Id, u64, u64, u64);
+
+fn trade_key(fill: &FillEvent) -> TradeKey {
+    (fill.book_id.clone(), fill.time, fill.price, fill.fill_size)
+}
+
+/// Turns `FillEvent`s into a deduplicated trade tape.
+///
+/// Each match emits a maker and a taker fill. The first fill of a match seen produces a
+/// `Trade`, the counterpart fill is suppressed if it arrives within `horizon`.
+#[derive(Debug)]
+pub struct TradeTape {
+    horizon: u64,
+    /// Unpaired fills by key, with their maker flag.
+    unpaired: HashMap<TradeKey, Vec<bool>>,
+    order: VecDeque<(u64, TradeKey)>,
+}
+
+impl Default for TradeTape {
+    fn default() -> Self {
+        Self::new(60_000_000)
+    }
+}
+
+impl TradeTape {
+    /// # Arguments:
+    ///
+    /// * `horizon` - How long to wait for the counterpart of a fill, in the unit of `FillEvent::time`.
+    pub fn new(horizon: u64) -> Self {
+        Self {
+            horizon,
+            unpaired: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    /// Add a fill, returning a `Trade` unless the fill is the counterpart of one already emitted.
+    pub fn push(&mut self, fill: &FillEvent) -> Option<Trade> {
+        self.expire(fill.time);
+
+        let key = trade_key(fill);
+        let unpaired = self.unpaired.entry(key.clone()).or_default();
+        if let Some(i) = unpaired
+            .iter()
+            .position(|is_maker| *is_maker != fill.is_maker)
+        {
+            unpaired.swap_remove(i);
+            if unpaired.is_empty() {
+                self.unpaired.remove(&key);
+            }
+            return None;
+        }
+
+        unpaired.push(fill.is_maker);
+        self.order.push_back((fill.time, key));
+        Some(Trade::from_fill(fill))
+    }
+
+    fn expire(&mut self, now: u64) {
+        while let Some((time, _)) = self.order.front() {
+            if now.saturating_sub(*time) <= self.horizon {
+                break;
+            }
+            let Some((_, key)) = self.order.pop_front() else {
+                break;
+            };
+            if let Some(unpaired) = self.unpaired.get_mut(&key) {
+                if !unpaired.is_empty() {
+                    unpaired.remove(0);
+                }
+                if unpaired.is_empty() {
+                    self.unpaired.remove(&key);
+                }
+            }
+        }
+    }
+
+    /// Build the trade tape of historical fills, sorted by time.
+    /// Maker and taker fills of the same match are merged into one `Trade` carrying both order ids.
+    pub fn from_fills<'a, I>(fills: I) -> Vec<Trade>
+    where
+        I: IntoIterator<Item = &'a FillEvent>,
+    {
+        let mut fills = fills.into_iter().collect::<Vec<_>>();
+        fills.sort_by_key(|f| f.time);
+
+        let mut trades: Vec<Trade> = vec![];
+        let mut unpaired: HashMap<TradeKey, Vec<(bool, usize)>> = HashMap::new();
+        for fill in fills {
+            let key = trade_key(fill);
+            let entries = unpaired.entry(key).or_default();
+            match entries.iter().position(|(m, _)| *m != fill.is_maker) {
+                Some(i) => {
+                    let (_, idx) = entries.swap_remove(i);
+                    trades[idx].merge(fill);
+                }
+                None => {
+                    entries.push((fill.is_maker, trades.len()));
+                    trades.push(Trade::from_fill(fill));
+                }
+            }
+        }
+
+        trades
+    }
+}
+
+impl LaminarClient {
+    /// Fetch the trade tape of a book from this client's account fill events.
+    ///
+    /// # Arguments:
+    ///
+    /// * `book_id` - `OrderBook` Id.
+    pub async fn fetch_trades(&self, book_id: &Id) -> Result<Vec<Trade>> {
+        let fills = self.fetch_all_fill_events(book_id).await?;
+        Ok(TradeTape::from_fills(&fills))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_api_types::{Address, U64};
+    use aptos_sdk::types::account_address::AccountAddress;
+
+    fn id(creation_num: u64) -> Id {
+        Id {
+            creation_num: U64(creation_num),
+            addr: Address::from(AccountAddress::ONE),
+        }
+    }
+
+    fn fill(order: u64, side: Side, price: u64, size: u64, time: u64, is_maker: bool) -> FillEvent {
+        FillEvent {
+            book_id: id(0),
+            order_id: id(order),
+            side,
+            price,
+            fill_size: size,
+            fee: 0,
+            fee_rate: 0,
+            time,
+            remaining_size: 0,
+            is_maker,
+        }
+    }
+
+    #[test]
+    fn test_counterpart_fill_is_suppressed() {
+        let mut tape = TradeTape::default();
+        let trade = tape.push(&fill(1, Side::Ask, 100, 5, 10, true)).unwrap();
+        assert_eq!(trade.aggressor, Side::Bid);
+        assert_eq!(trade.maker_order, Some(id(1)));
+        assert_eq!(trade.taker_order, None);
+        assert!(tape.push(&fill(2, Side::Bid, 100, 5, 10, false)).is_none());
+
+        // a second match with the same price and size is a new trade
+        assert!(tape.push(&fill(3, Side::Bid, 100, 5, 10, false)).is_some());
+        assert!(tape.push(&fill(1, Side::Ask, 100, 5, 10, true)).is_none());
+    }
+
+    #[test]
+    fn test_unpaired_fill_expires_after_horizon() {
+        let mut tape = TradeTape::new(10);
+        assert!(tape.push(&fill(1, Side::Ask, 100, 5, 0, true)).is_some());
+        assert!(tape.push(&fill(3, Side::Ask, 101, 1, 20, true)).is_some());
+
+        // the counterpart arrived too late to be matched
+        assert!(tape.push(&fill(2, Side::Bid, 100, 5, 0, false)).is_some());
+    }
+
+    #[test]
+    fn test_from_fills_merges_order_ids() {
+        let fills = vec![
+            fill(4, Side::Bid, 101, 2, 20, false),
+            fill(2, Side::Bid, 100, 5, 10, false),
+            fill(1, Side::Ask, 100, 5, 10, true),
+            fill(3, Side::Ask, 101, 2, 20, true),
+        ];
+        let trades = TradeTape::from_fills(&fills);
+
+        assert_eq!(trades.len(), 2);
+        assert_eq!(trades[0].time, 10);
+        assert_eq!(trades[0].aggressor, Side::Bid);
+        assert_eq!(trades[0].maker_order, Some(id(1)));
+        assert_eq!(trades[0].taker_order, Some(id(2)));
+        assert_eq!(trades[1].price, 101);
+        assert_eq!(trades[1].maker_order, Some(id(3)));
+        assert_eq!(trades[1].taker_order, Some(id(4)));
+    }
+}
@@ -0,0 +1,80 @@
+use crate::error::Result;
+use anyhow::Context;
+use opentelemetry::global;
+use opentelemetry::sdk::propagation::TraceContextPropagator;
+use opentelemetry::sdk::{trace, Resource};
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::WithExportConfig;
+use std::collections::HashMap;
+use tracing::Span;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::EnvFilter;
+
+/// Filter used if `RUST_LOG` is unset.
+pub const DEFAULT_FILTER: &str = "info,laminar_sdk=debug";
+
+/// Flushes pending spans when dropped, keep it alive until the process exits.
+#[derive(Debug)]
+#[must_use = "spans are only flushed while the guard is alive"]
+pub struct TelemetryGuard(());
+
+impl Drop for TelemetryGuard {
+    fn drop(&mut self) {
+        global::shutdown_tracer_provider();
+    }
+}
+
+/// Install a global `tracing` subscriber exporting spans over OTLP/gRPC, e.g. to Jaeger or
+/// Tempo. Order spans carry the `laminar.book_id`, `laminar.order_id` and `tx.hash`
+/// attributes, and fills seen by a `MarketDataFeed` are recorded as `fill observed` events.
+/// Must be called from within a tokio runtime.
+///
+/// # Arguments:
+///
+/// * `service_name` - Value of the `service.name` resource attribute.
+/// * `endpoint` - OTLP collector endpoint, e.g. `http://localhost:4317`.
+pub fn init_otlp(service_name: &str, endpoint: &str) -> Result<TelemetryGuard> {
+    global::set_text_map_propagator(TraceContextPropagator::new());
+    let tracer = opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(
+            opentelemetry_otlp::new_exporter()
+                .tonic()
+                .with_endpoint(endpoint),
+        )
+        .with_trace_config(
+            trace::config().with_resource(Resource::new(vec![KeyValue::new(
+                "service.name",
+                service_name.to_string(),
+            )])),
+        )
+        .install_batch(opentelemetry::runtime::Tokio)
+        .context("failed installing otlp pipeline")?;
+    let filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(DEFAULT_FILTER));
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_opentelemetry::layer().with_tracer(tracer))
+        .try_init()
+        .context("failed installing tracing subscriber")?;
+    Ok(TelemetryGuard(()))
+}
+
+/// W3C trace context of the current span, to pass to another service, e.g. as message
+/// headers.
+pub fn current_context() -> HashMap<String, String> {
+    let mut carrier = HashMap::new();
+    let cx = Span::current().context();
+    global::get_text_map_propagator(|p| p.inject_context(&cx, &mut carrier));
+    carrier
+}
+
+/// Continue a trace started in another service, making `span` a child of the span that
+/// produced `carrier` with `current_context`.
+pub fn set_parent(span: &Span, carrier: &HashMap<String, String>) {
+    let cx = global::get_text_map_propagator(|p| p.extract(carrier));
+    span.set_parent(cx);
+}
@@ -0,0 +1,1845 @@
+//! In-process fake of the parts of the Aptos REST API used by `LaminarClient`, with a
+//! simple price-time priority matching engine standing in for the Laminar `book` module.
+//!
+//! Balances are not settled on fills and gas is not charged. Rejected orders abort with the
+//! simulator's codes, see `sim::EINVALID_ARGUMENT`.
+
+use crate::sim::{
+    CANCEL_REASON_UNFILLED, CANCEL_REASON_USER, EFILL_OR_KILL, EMIN_SIZE, EORDER_NOT_FOUND,
+    EPOST_ONLY,
+};
+use crate::types::order::{Side, TimeInForce};
+use anyhow::{anyhow, Context, Result};
+use aptos_sdk::bcs;
+use aptos_sdk::move_types::language_storage::{StructTag, TypeTag};
+use aptos_sdk::types::account_address::AccountAddress;
+use aptos_sdk::types::transaction::authenticator::TransactionAuthenticator;
+use aptos_sdk::types::transaction::{EntryFunction, SignedTransaction, TransactionPayload};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use reqwest::Url;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::oneshot;
+
+const CHAIN_ID: u8 = 4;
+
+const EBOOK_NOT_FOUND: u64 = 0x60005;
+const EBOOK_EXISTS: u64 = 0x80006;
+const EUSER_NOT_REGISTERED: u64 = 0x60007;
+
+fn now_micros() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_micros() as u64)
+}
+
+fn id_json(creation_num: u64, addr: &AccountAddress) -> Value {
+    json!({ "creation_num": creation_num.to_string(), "addr": addr.to_hex_literal() })
+}
+
+fn type_info_json(tag: &TypeTag) -> Value {
+    match tag {
+        TypeTag::Struct(s) => json!({
+            "account_address": s.address.to_hex_literal(),
+            "module_name": format!("0x{}", hex::encode(s.module.as_str())),
+            "struct_name": format!("0x{}", hex::encode(s.name.as_str())),
+        }),
+        t => json!({
+            "account_address": "0x1",
+            "module_name": "0x",
+            "struct_name": format!("0x{}", hex::encode(t.to_string())),
+        }),
+    }
+}
+
+#[derive(Clone, Debug)]
+struct RestingOrder {
+    creation_num: u64,
+    owner: AccountAddress,
+    price: u64,
+    size: u64,
+    remaining_size: u64,
+    post_only: bool,
+}
+
+impl RestingOrder {
+    fn to_json(&self, side: Side) -> Value {
+        json!({
+            "id": id_json(self.creation_num, &self.owner),
+            "side": side as u8,
+            "price": self.price.to_string(),
+            "size": self.size.to_string(),
+            "post_only": self.post_only,
+            "remaining_size": self.remaining_size.to_string(),
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+struct FakeBook {
+    creation_num: u64,
+    owner: AccountAddress,
+    base: TypeTag,
+    quote: TypeTag,
+    price_decimals: u8,
+    size_decimals: u8,
+    min_size_amount: u64,
+    base_decimals: u8,
+    quote_decimals: u8,
+    bids: BTreeMap<u64, Vec<RestingOrder>>,
+    asks: BTreeMap<u64, Vec<RestingOrder>>,
+}
+
+impl FakeBook {
+    fn side(&self, side: Side) -> &BTreeMap<u64, Vec<RestingOrder>> {
+        match side {
+            Side::Bid => &self.bids,
+            Side::Ask => &self.asks,
+        }
+    }
+
+    fn side_mut(&mut self, side: Side) -> &mut BTreeMap<u64, Vec<RestingOrder>> {
+        match side {
+            Side::Bid => &mut self.bids,
+            Side::Ask => &mut self.asks,
+        }
+    }
+
+    fn best_opposite(&self, side: Side) -> Option<u64> {
+        match side {
+            Side::Bid => self.asks.keys().next().copied(),
+            Side::Ask => self.bids.keys().next_back().copied(),
+        }
+    }
+
+    fn crosses(side: Side, price: u64, opposite: u64) -> bool {
+        match side {
+            Side::Bid => price >= opposite,
+            Side::Ask => price <= opposite,
+        }
+    }
+
+    fn fillable(&self, side: Side, price: Option<u64>) -> u64 {
+        let opposite = self.side(match side {
+            Side::Bid => Side::Ask,
+            Side::Ask => Side::Bid,
+        });
+        let levels: Box<dyn Iterator<Item = (&u64, &Vec<RestingOrder>)>> = match side {
+            Side::Bid => Box::new(opposite.iter()),
+            Side::Ask => Box::new(opposite.iter().rev()),
+        };
+        levels
+            .take_while(|(p, _)| price.map_or(true, |price| Self::crosses(side, price, **p)))
+            .flat_map(|(_, orders)| orders.iter().map(|o| o.remaining_size))
+            .sum()
+    }
+
+    fn instrument_json(&self) -> Value {
+        json!({
+            "owner": self.owner.to_hex_literal(),
+            "price_decimals": self.price_decimals,
+            "size_decimals": self.size_decimals,
+            "min_size_amount": self.min_size_amount.to_string(),
+            "base_decimals": self.base_decimals,
+            "quote_decimals": self.quote_decimals,
+        })
+    }
+
+    fn side_json(&self, side: Side) -> Value {
+        let nodes = self
+            .side(side)
+            .iter()
+            .map(|(price, orders)| {
+                let queue = orders
+                    .iter()
+                    .enumerate()
+                    .map(|(i, o)| {
+                        let next = if i + 1 < orders.len() {
+                            (i + 1) as u64
+                        } else {
+                            u64::MAX
+                        };
+                        json!({
+                            "next": { "value": next.to_string() },
+                            "value": { "vec": [o.to_json(side)] },
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                let head = if orders.is_empty() { u64::MAX } else { 0 };
+                json!({
+                    "key": price.to_string(),
+                    "left": { "value": u64::MAX.to_string() },
+                    "right": { "value": u64::MAX.to_string() },
+                    "value": { "head": { "value": head.to_string() }, "nodes": queue },
+                })
+            })
+            .collect::<Vec<_>>();
+
+        json!({
+            "max": { "value": u64::MAX.to_string() },
+            "min": { "value": u64::MAX.to_string() },
+            "nodes": nodes,
+            "removed_nodes": [],
+            "root": { "value": u64::MAX.to_string() },
+            "single_splay": true,
+        })
+    }
+
+    fn resource_json(&self, side: Side) -> Value {
+        let field = match side {
+            Side::Bid => "bids",
+            Side::Ask => "asks",
+        };
+        json!({
+            "id": id_json(self.creation_num, &self.owner),
+            "instrument": self.instrument_json(),
+            field: self.side_json(side),
+            "signer_addr": self.owner.to_hex_literal(),
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+struct FakeAccount {
+    sequence_number: u64,
+    registered: bool,
+    creation_num: u64,
+    resources: BTreeMap<String, Value>,
+    events: HashMap<&'static str, Vec<(u64, Value)>>,
+}
+
+impl FakeAccount {
+    fn next_creation_num(&mut self) -> u64 {
+        self.creation_num += 1;
+        self.creation_num
+    }
+}
+
+struct EmittedEvent {
+    account: AccountAddress,
+    field: &'static str,
+    typ: &'static str,
+    data: Value,
+}
+
+struct Abort {
+    code: u64,
+    reason: &'static str,
+}
+
+#[derive(Clone, Debug)]
+struct NodeState {
+    laminar: AccountAddress,
+    version: u64,
+    accounts: HashMap<AccountAddress, FakeAccount>,
+    books: HashMap<(AccountAddress, String, String), FakeBook>,
+    transactions: HashMap<String, Value>,
+}
+
+impl NodeState {
+    fn headers(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("X-Aptos-Chain-Id", CHAIN_ID.to_string()),
+            ("X-Aptos-Ledger-Version", self.version.to_string()),
+            ("X-Aptos-Ledger-Oldest-Version", "0".to_string()),
+            ("X-Aptos-Ledger-TimestampUsec", now_micros().to_string()),
+            ("X-Aptos-Epoch", "1".to_string()),
+            ("X-Aptos-Block-Height", self.version.to_string()),
+            ("X-Aptos-Oldest-Block-Height", "0".to_string()),
+        ]
+    }
+
+    fn store_type(&self) -> String {
+        format!("{}::book::OrderBookStore", self.laminar.to_hex_literal())
+    }
+
+    fn book_type(&self, side: Side, base: &TypeTag, quote: &TypeTag) -> String {
+        let name = match side {
+            Side::Bid => "OrderBookBids",
+            Side::Ask => "OrderBookAsks",
+        };
+        format!(
+            "{}::book::{}<{}, {}>",
+            self.laminar.to_hex_literal(),
+            name,
+            base,
+            quote
+        )
+    }
+
+    fn resource(&self, addr: &AccountAddress, typ: &str) -> Option<Value> {
+        let typ = typ.replace(' ', "");
+        if let Some(r) = self.accounts.get(addr).and_then(|a| a.resources.get(&typ)) {
+            return Some(r.clone());
+        }
+        if typ == self.store_type().replace(' ', "") {
+            return self
+                .accounts
+                .get(addr)
+                .filter(|a| a.registered)
+                .map(|_| json!({}));
+        }
+
+        self.books.values().find_map(|b| {
+            if &b.owner != addr {
+                return None;
+            }
+            [Side::Bid, Side::Ask].into_iter().find_map(|side| {
+                (self.book_type(side, &b.base, &b.quote).replace(' ', "") == typ)
+                    .then(|| b.resource_json(side))
+            })
+        })
+    }
+
+    fn book_mut(
+        &mut self,
+        owner: &AccountAddress,
+        base: &TypeTag,
+        quote: &TypeTag,
+    ) -> std::result::Result<&mut FakeBook, Abort> {
+        self.books
+            .get_mut(&(*owner, base.to_string(), quote.to_string()))
+            .ok_or(Abort {
+                code: EBOOK_NOT_FOUND,
+                reason: "EBOOK_NOT_FOUND",
+            })
+    }
+
+    fn coin_decimals(&self, coin: &TypeTag) -> u8 {
+        let TypeTag::Struct(s) = coin else {
+            return 0;
+        };
+        self.accounts
+            .get(&s.address)
+            .and_then(|a| a.resources.get(&format!("0x1::coin::CoinInfo<{}>", coin)))
+            .and_then(|r| r.get("decimals"))
+            .and_then(|d| d.as_u64())
+            .unwrap_or(0) as u8
+    }
+
+    fn next_creation_num(&mut self, addr: &AccountAddress) -> u64 {
+        self.accounts.entry(*addr).or_default().next_creation_num()
+    }
+
+    fn execute(
+        &mut self,
+        sender: AccountAddress,
+        entry: &EntryFunction,
+        time: u64,
+    ) -> std::result::Result<Vec<EmittedEvent>, Abort> {
+        let arg = |i: usize| entry.args().get(i).cloned().unwrap_or_default();
+        let decode_err = |_| Abort {
+            code: 0x10000,
+            reason: "EINVALID_ARGUMENT",
+        };
+        let function = entry.function().as_str().to_string();
+        if function != "register_user"
+            && !self.accounts.get(&sender).map_or(false, |a| a.registered)
+        {
+            return Err(Abort {
+                code: EUSER_NOT_REGISTERED,
+                reason: "EUSER_NOT_REGISTERED",
+            });
+        }
+
+        let ty_args = entry.ty_args();
+        match function.as_str() {
+            "register_user" => {
+                self.accounts.entry(sender).or_default().registered = true;
+                Ok(vec![])
+            }
+            "create_orderbook" => {
+                let (base, quote) = (ty_args[0].clone(), ty_args[1].clone());
+                let price_decimals: u8 = bcs::from_bytes(&arg(0)).map_err(decode_err)?;
+                let size_decimals: u8 = bcs::from_bytes(&arg(1)).map_err(decode_err)?;
+                let min_size_amount: u64 = bcs::from_bytes(&arg(2)).map_err(decode_err)?;
+                let key = (sender, base.to_string(), quote.to_string());
+                if self.books.contains_key(&key) {
+                    return Err(Abort {
+                        code: EBOOK_EXISTS,
+                        reason: "EBOOK_EXISTS",
+                    });
+                }
+
+                let book = FakeBook {
+                    creation_num: self.next_creation_num(&sender),
+                    owner: sender,
+                    base_decimals: self.coin_decimals(&base),
+                    quote_decimals: self.coin_decimals(&quote),
+                    base,
+                    quote,
+                    price_decimals,
+                    size_decimals,
+                    min_size_amount,
+                    bids: BTreeMap::new(),
+                    asks: BTreeMap::new(),
+                };
+                let data = json!({
+                    "book_id": id_json(book.creation_num, &sender),
+                    "creator": sender.to_hex_literal(),
+                    "base": type_info_json(&book.base),
+                    "quote": type_info_json(&book.quote),
+                    "price_decimals": price_decimals,
+                    "size_decimals": size_decimals,
+                    "min_size_amount": min_size_amount.to_string(),
+                    "base_decimals": book.base_decimals,
+                    "quote_decimals": book.quote_decimals,
+                    "time": time.to_string(),
+                });
+                self.books.insert(key, book);
+                Ok(vec![EmittedEvent {
+                    account: sender,
+                    field: "create_orderbook_events",
+                    typ: "CreateOrderBookEvent",
+                    data,
+                }])
+            }
+            "place_limit_order" | "place_market_order" => {
+                let owner: AccountAddress = bcs::from_bytes(&arg(0)).map_err(decode_err)?;
+                let side: u8 = bcs::from_bytes(&arg(1)).map_err(decode_err)?;
+                let side = if side == 0 { Side::Bid } else { Side::Ask };
+                let (price, size, time_in_force, post_only) = if function == "place_limit_order" {
+                    let price: u64 = bcs::from_bytes(&arg(2)).map_err(decode_err)?;
+                    let size: u64 = bcs::from_bytes(&arg(3)).map_err(decode_err)?;
+                    let tif: u8 = bcs::from_bytes(&arg(4)).map_err(decode_err)?;
+                    let post_only: bool = bcs::from_bytes(&arg(5)).map_err(decode_err)?;
+                    let tif = match tif {
+                        0 => TimeInForce::GoodTillCanceled,
+                        1 => TimeInForce::ImmediateOrCancel,
+                        _ => TimeInForce::FillOrKill,
+                    };
+                    (Some(price), size, tif, post_only)
+                } else {
+                    let size: u64 = bcs::from_bytes(&arg(2)).map_err(decode_err)?;
+                    (None, size, TimeInForce::ImmediateOrCancel, false)
+                };
+                self.place(
+                    sender,
+                    &owner,
+                    (&ty_args[0], &ty_args[1]),
+                    side,
+                    price,
+                    size,
+                    time_in_force,
+                    post_only,
+                    time,
+                )
+            }
+            "amend_order" => {
+                let owner: AccountAddress = bcs::from_bytes(&arg(0)).map_err(decode_err)?;
+                let creation_num: u64 = bcs::from_bytes(&arg(1)).map_err(decode_err)?;
+                let side: u8 = bcs::from_bytes(&arg(2)).map_err(decode_err)?;
+                let side = if side == 0 { Side::Bid } else { Side::Ask };
+                let price: u64 = bcs::from_bytes(&arg(3)).map_err(decode_err)?;
+                let size: u64 = bcs::from_bytes(&arg(4)).map_err(decode_err)?;
+                let amend_num = self.next_creation_num(&sender);
+                let book = self.book_mut(&owner, &ty_args[0], &ty_args[1])?;
+                let book_id = id_json(book.creation_num, &book.owner);
+                let levels = book.side_mut(side);
+                let (old_price, idx) = levels
+                    .iter()
+                    .find_map(|(p, orders)| {
+                        orders
+                            .iter()
+                            .position(|o| o.creation_num == creation_num && o.owner == sender)
+                            .map(|i| (*p, i))
+                    })
+                    .ok_or(Abort {
+                        code: EORDER_NOT_FOUND,
+                        reason: "EORDER_NOT_FOUND",
+                    })?;
+
+                let queue = levels.get_mut(&old_price).expect("level exists");
+                let mut order = queue.remove(idx);
+                if queue.is_empty() {
+                    levels.remove(&old_price);
+                }
+                let filled = order.size - order.remaining_size;
+                let keeps_priority = price == old_price && size <= order.size;
+                order.price = price;
+                order.remaining_size = size.saturating_sub(filled);
+                order.size = size;
+                let queue = levels.entry(price).or_default();
+                if keeps_priority {
+                    queue.insert(idx.min(queue.len()), order);
+                } else {
+                    queue.push(order);
+                }
+
+                Ok(vec![EmittedEvent {
+                    account: sender,
+                    field: "amend_order_events",
+                    typ: "AmendOrderEvent",
+                    data: json!({
+                        "book_id": book_id,
+                        "order_id": id_json(creation_num, &sender),
+                        "amend_id": id_json(amend_num, &sender),
+                        "side": side as u8,
+                        "price": price.to_string(),
+                        "size": size.to_string(),
+                        "time": time.to_string(),
+                    }),
+                }])
+            }
+            "cancel_order" => {
+                let owner: AccountAddress = bcs::from_bytes(&arg(0)).map_err(decode_err)?;
+                let creation_num: u64 = bcs::from_bytes(&arg(1)).map_err(decode_err)?;
+                let side: u8 = bcs::from_bytes(&arg(2)).map_err(decode_err)?;
+                let side = if side == 0 { Side::Bid } else { Side::Ask };
+                let cancel_num = self.next_creation_num(&sender);
+                let book = self.book_mut(&owner, &ty_args[0], &ty_args[1])?;
+                let book_id = id_json(book.creation_num, &book.owner);
+                let levels = book.side_mut(side);
+                let price = levels
+                    .iter()
+                    .find(|(_, orders)| {
+                        orders
+                            .iter()
+                            .any(|o| o.creation_num == creation_num && o.owner == sender)
+                    })
+                    .map(|(p, _)| *p)
+                    .ok_or(Abort {
+                        code: EORDER_NOT_FOUND,
+                        reason: "EORDER_NOT_FOUND",
+                    })?;
+                let queue = levels.get_mut(&price).expect("level exists");
+                queue.retain(|o| !(o.creation_num == creation_num && o.owner == sender));
+                if queue.is_empty() {
+                    levels.remove(&price);
+                }
+
+                Ok(vec![Self::cancel_event(
+                    book_id,
+                    sender,
+                    creation_num,
+                    cancel_num,
+                    side,
+                    CANCEL_REASON_USER,
+                    time,
+                )])
+            }
+            _ => Err(Abort {
+                code: 0x10000,
+                reason: "EUNKNOWN_FUNCTION",
+            }),
+        }
+    }
+
+    fn cancel_event(
+        book_id: Value,
+        owner: AccountAddress,
+        creation_num: u64,
+        cancel_num: u64,
+        side: Side,
+        reason: u8,
+        time: u64,
+    ) -> EmittedEvent {
+        EmittedEvent {
+            account: owner,
+            field: "cancel_order_events",
+            typ: "CancelOrderEvent",
+            data: json!({
+                "book_id": book_id,
+                "order_id": id_json(creation_num, &owner),
+                "cancel_id": id_json(cancel_num, &owner),
+                "side": side as u8,
+                "reason": reason,
+                "time": time.to_string(),
+            }),
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn fill_event(
+        book_id: &Value,
+        owner: AccountAddress,
+        creation_num: u64,
+        side: Side,
+        price: u64,
+        fill_size: u64,
+        remaining_size: u64,
+        is_maker: bool,
+        time: u64,
+    ) -> EmittedEvent {
+        EmittedEvent {
+            account: owner,
+            field: "fill_events",
+            typ: "FillEvent",
+            data: json!({
+                "book_id": book_id,
+                "order_id": id_json(creation_num, &owner),
+                "side": side as u8,
+                "price": price.to_string(),
+                "fill_size": fill_size.to_string(),
+                "fee": "0",
+                "fee_rate": "0",
+                "time": time.to_string(),
+                "remaining_size": remaining_size.to_string(),
+                "is_maker": is_maker,
+            }),
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn place(
+        &mut self,
+        sender: AccountAddress,
+        owner: &AccountAddress,
+        (base, quote): (&TypeTag, &TypeTag),
+        side: Side,
+        price: Option<u64>,
+        size: u64,
+        time_in_force: TimeInForce,
+        post_only: bool,
+        time: u64,
+    ) -> std::result::Result<Vec<EmittedEvent>, Abort> {
+        let creation_num = self.next_creation_num(&sender);
+        let book = self.book_mut(owner, base, quote)?;
+        if size < book.min_size_amount {
+            return Err(Abort {
+                code: EMIN_SIZE,
+                reason: "EMIN_SIZE",
+            });
+        }
+        let crosses = |book: &FakeBook| match (book.best_opposite(side), price) {
+            (Some(opposite), Some(price)) => FakeBook::crosses(side, price, opposite),
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+        if post_only && crosses(book) {
+            return Err(Abort {
+                code: EPOST_ONLY,
+                reason: "EPOST_ONLY",
+            });
+        }
+        if matches!(time_in_force, TimeInForce::FillOrKill) && book.fillable(side, price) < size {
+            return Err(Abort {
+                code: EFILL_OR_KILL,
+                reason: "EFILL_OR_KILL",
+            });
+        }
+
+        let book_id = id_json(book.creation_num, &book.owner);
+        let mut events = vec![EmittedEvent {
+            account: sender,
+            field: "place_order_events",
+            typ: "PlaceOrderEvent",
+            data: json!({
+                "book_id": book_id,
+                "order_id": id_json(creation_num, &sender),
+                "side": side as u8,
+                "price": price.unwrap_or_default().to_string(),
+                "size": size.to_string(),
+                "time_in_force": time_in_force as u8,
+                "post_only": post_only,
+                "time": time.to_string(),
+            }),
+        }];
+
+        let opposite_side = match side {
+            Side::Bid => Side::Ask,
+            Side::Ask => Side::Bid,
+        };
+        let mut remaining = size;
+        while remaining > 0 && crosses(book) {
+            let level_price = book.best_opposite(side).expect("crossing level exists");
+            let levels = book.side_mut(opposite_side);
+            let queue = levels.get_mut(&level_price).expect("level exists");
+            let maker = &mut queue[0];
+            let fill = remaining.min(maker.remaining_size);
+            maker.remaining_size -= fill;
+            remaining -= fill;
+            events.push(Self::fill_event(
+                &book_id,
+                maker.owner,
+                maker.creation_num,
+                opposite_side,
+                level_price,
+                fill,
+                maker.remaining_size,
+                true,
+                time,
+            ));
+            events.push(Self::fill_event(
+                &book_id,
+                sender,
+                creation_num,
+                side,
+                level_price,
+                fill,
+                remaining,
+                false,
+                time,
+            ));
+            if maker.remaining_size == 0 {
+                queue.remove(0);
+                if queue.is_empty() {
+                    levels.remove(&level_price);
+                }
+            }
+        }
+
+        if remaining > 0 {
+            match (time_in_force, price) {
+                (TimeInForce::GoodTillCanceled, Some(price)) => {
+                    book.side_mut(side)
+                        .entry(price)
+                        .or_default()
+                        .push(RestingOrder {
+                            creation_num,
+                            owner: sender,
+                            price,
+                            size,
+                            remaining_size: remaining,
+                            post_only,
+                        });
+                }
+                _ => {
+                    let cancel_num = self.next_creation_num(&sender);
+                    events.push(Self::cancel_event(
+                        book_id,
+                        sender,
+                        creation_num,
+                        cancel_num,
+                        side,
+                        CANCEL_REASON_UNFILLED,
+                        time,
+                    ));
+                }
+            }
+        }
+
+        Ok(events)
+    }
+
+    fn submit(
+        &mut self,
+        signed: SignedTransaction,
+    ) -> std::result::Result<Value, (StatusCode, Value)> {
+        let sender = signed.sender();
+        let account = self.accounts.entry(sender).or_default();
+        let expected = account.sequence_number;
+        if signed.sequence_number() != expected {
+            let code = if signed.sequence_number() < expected {
+                "sequence_number_too_old"
+            } else {
+                "vm_error"
+            };
+            return Err((
+                StatusCode::BAD_REQUEST,
+                json!({
+                    "message": format!("invalid sequence number, expected {}", expected),
+                    "error_code": code,
+                    "vm_error_code": null,
+                }),
+            ));
+        }
+        account.sequence_number += 1;
+
+        let TransactionPayload::EntryFunction(entry) = signed.payload().clone() else {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                json!({
+                    "message": "only entry function payloads are supported",
+                    "error_code": "invalid_input",
+                    "vm_error_code": null,
+                }),
+            ));
+        };
+
+        let hash = signed.clone().committed_hash().to_hex_literal();
+        let time = now_micros();
+        self.version += 1;
+        let version = self.version;
+        let is_laminar =
+            entry.module().address() == &self.laminar && entry.module().name().as_str() == "book";
+        let result = if is_laminar {
+            self.execute(sender, &entry, time)
+        } else {
+            Ok(vec![])
+        };
+
+        let (success, vm_status, emitted) = match result {
+            Ok(events) => (true, "Executed successfully".to_string(), events),
+            Err(Abort { code, reason }) => (
+                false,
+                format!(
+                    "Move abort in {}::book: {}(0x{:x}): ",
+                    self.laminar.to_hex_literal(),
+                    reason,
+                    code
+                ),
+                vec![],
+            ),
+        };
+
+        let mut events = vec![];
+        for e in emitted {
+            let account = self.accounts.entry(e.account).or_default();
+            let stream = account.events.entry(e.field).or_default();
+            let sequence_number = stream.len() as u64;
+            let event = json!({
+                "guid": {
+                    "creation_number": "0",
+                    "account_address": e.account.to_hex_literal(),
+                },
+                "sequence_number": sequence_number.to_string(),
+                "type": format!("{}::book::{}", self.laminar.to_hex_literal(), e.typ),
+                "data": e.data,
+            });
+            stream.push((version, event.clone()));
+            events.push(event);
+        }
+
+        let signature = match signed.authenticator() {
+            TransactionAuthenticator::Ed25519 {
+                public_key,
+                signature,
+            } => json!({
+                "type": "ed25519_signature",
+                "public_key": format!("0x{}", hex::encode(public_key.to_bytes())),
+                "signature": format!("0x{}", hex::encode(signature.to_bytes())),
+            }),
+            _ => Value::Null,
+        };
+        let request = json!({
+            "hash": hash,
+            "sender": sender.to_hex_literal(),
+            "sequence_number": signed.sequence_number().to_string(),
+            "max_gas_amount": signed.max_gas_amount().to_string(),
+            "gas_unit_price": signed.gas_unit_price().to_string(),
+            "expiration_timestamp_secs": signed.expiration_timestamp_secs().to_string(),
+            "payload": {
+                "type": "entry_function_payload",
+                "function": format!(
+                    "{}::{}::{}",
+                    entry.module().address().to_hex_literal(),
+                    entry.module().name(),
+                    entry.function()
+                ),
+                "type_arguments": entry.ty_args().iter().map(|t| t.to_string()).collect::<Vec<_>>(),
+                "arguments": [],
+            },
+            "signature": signature,
+        });
+
+        let zero_hash = format!("0x{}", "0".repeat(64));
+        let mut committed = request.clone();
+        let fields = json!({
+            "type": "user_transaction",
+            "version": version.to_string(),
+            "state_change_hash": zero_hash,
+            "event_root_hash": zero_hash,
+            "state_checkpoint_hash": null,
+            "gas_used": "0",
+            "success": success,
+            "vm_status": vm_status,
+            "accumulator_root_hash": zero_hash,
+            "changes": [],
+            "events": events,
+            "timestamp": time.to_string(),
+        });
+        if let (Value::Object(c), Value::Object(f)) = (&mut committed, fields) {
+            c.extend(f);
+        }
+        self.transactions
+            .insert(hash.trim_start_matches("0x").to_string(), committed);
+
+        let mut pending = request;
+        if let Value::Object(p) = &mut pending {
+            p.insert("type".to_string(), json!("pending_transaction"));
+        }
+        Ok(pending)
+    }
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            if let Ok(b) = u8::from_str_radix(&s[i + 1..i + 3], 16) {
+                out.push(b);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn parse_address(s: &str) -> Option<AccountAddress> {
+    AccountAddress::from_hex_literal(&format!("0x{}", s.trim_start_matches("0x"))).ok()
+}
+
+fn not_found(message: &str, error_code: &str) -> (StatusCode, Value) {
+    (
+        StatusCode::NOT_FOUND,
+        json!({ "message": message, "error_code": error_code, "vm_error_code": null }),
+    )
+}
+
+fn route(
+    state: &Mutex<NodeState>,
+    method: &Method,
+    path: &str,
+    query: &HashMap<String, String>,
+    body: &[u8],
+) -> (StatusCode, Value) {
+    let mut state = state.lock().expect("fake node state poisoned");
+    let segments = path
+        .trim_start_matches('/')
+        .trim_start_matches("v1")
+        .trim_start_matches('/')
+        .split('/')
+        .map(percent_decode)
+        .collect::<Vec<_>>();
+    let segments = segments.iter().map(|s| s.as_str()).collect::<Vec<_>>();
+
+    match (method, segments.as_slice()) {
+        (&Method::GET, [""]) => (
+            StatusCode::OK,
+            json!({
+                "chain_id": CHAIN_ID,
+                "epoch": "1",
+                "ledger_version": state.version.to_string(),
+                "oldest_ledger_version": "0",
+                "ledger_timestamp": now_micros().to_string(),
+                "node_role": "full_node",
+                "oldest_block_height": "0",
+                "block_height": state.version.to_string(),
+                "git_hash": null,
+            }),
+        ),
+        (&Method::GET, ["accounts", addr]) => match parse_address(addr) {
+            Some(addr) => {
+                let account = state.accounts.entry(addr).or_default();
+                (
+                    StatusCode::OK,
+                    json!({
+                        "sequence_number": account.sequence_number.to_string(),
+                        "authentication_key": addr.to_hex_literal(),
+                    }),
+                )
+            }
+            None => not_found("invalid address", "invalid_input"),
+        },
+        (&Method::GET, ["accounts", addr, "resource", typ]) => {
+            match parse_address(addr).and_then(|a| state.resource(&a, typ)) {
+                Some(data) => (StatusCode::OK, json!({ "type": typ, "data": data })),
+                None => not_found("resource not found", "resource_not_found"),
+            }
+        }
+        (&Method::GET, ["accounts", addr, "events", _handle, field]) => {
+            let Some(account) = parse_address(addr).and_then(|a| state.accounts.get(&a)) else {
+                return not_found("account not found", "account_not_found");
+            };
+            if !account.registered {
+                return not_found("resource not found", "resource_not_found");
+            }
+            let start = query
+                .get("start")
+                .and_then(|s| s.parse::<usize>().ok())
+                .unwrap_or(0);
+            let limit = query
+                .get("limit")
+                .and_then(|s| s.parse::<usize>().ok())
+                .unwrap_or(usize::MAX);
+            let events = account
+                .events
+                .iter()
+                .find(|(k, _)| **k == *field)
+                .map(|(_, v)| v.as_slice())
+                .unwrap_or_default()
+                .iter()
+                .skip(start)
+                .take(limit)
+                .map(|(version, e)| {
+                    let mut e = e.clone();
+                    if let Value::Object(m) = &mut e {
+                        m.insert("version".to_string(), json!(version.to_string()));
+                    }
+                    e
+                })
+                .collect::<Vec<_>>();
+            (StatusCode::OK, Value::Array(events))
+        }
+        (&Method::GET, ["accounts", addr, "transactions"]) => {
+            let Some(addr) = parse_address(addr) else {
+                return not_found("invalid address", "invalid_input");
+            };
+            let start = query
+                .get("start")
+                .and_then(|s| s.parse::<u64>().ok())
+                .unwrap_or(0);
+            let limit = query
+                .get("limit")
+                .and_then(|s| s.parse::<usize>().ok())
+                .unwrap_or(usize::MAX);
+            let sender = addr.to_hex_literal();
+            let mut txs = state
+                .transactions
+                .values()
+                .filter_map(|tx| {
+                    let seq = tx["sequence_number"].as_str()?.parse::<u64>().ok()?;
+                    (tx["sender"] == sender.as_str() && seq >= start).then(|| (seq, tx.clone()))
+                })
+                .collect::<Vec<_>>();
+            txs.sort_unstable_by_key(|(seq, _)| *seq);
+            let txs = txs.into_iter().take(limit).map(|(_, tx)| tx).collect();
+            (StatusCode::OK, Value::Array(txs))
+        }
+        (&Method::GET, ["transactions"]) => {
+            let start = query
+                .get("start")
+                .and_then(|s| s.parse::<u64>().ok())
+                .unwrap_or(0);
+            let limit = query
+                .get("limit")
+                .and_then(|s| s.parse::<usize>().ok())
+                .unwrap_or(100);
+            if start > state.version {
+                return not_found("transaction not found", "version_not_found");
+            }
+            let mut txs = state
+                .transactions
+                .values()
+                .filter_map(|tx| {
+                    let version = tx["version"].as_str()?.parse::<u64>().ok()?;
+                    (version >= start).then(|| (version, tx.clone()))
+                })
+                .collect::<Vec<_>>();
+            txs.sort_unstable_by_key(|(version, _)| *version);
+            let txs = txs.into_iter().take(limit).map(|(_, tx)| tx).collect();
+            (StatusCode::OK, Value::Array(txs))
+        }
+        (&Method::GET, ["transactions", "by_hash", hash]) => {
+            match state.transactions.get(hash.trim_start_matches("0x")) {
+                Some(tx) => (StatusCode::OK, tx.clone()),
+                None => not_found("transaction not found", "transaction_not_found"),
+            }
+        }
+        (&Method::POST, ["transactions", "simulate"]) => {
+            match bcs::from_bytes::<SignedTransaction>(body) {
+                Ok(signed) => {
+                    // execute against a copy so the simulation leaves no trace
+                    let mut scratch = (*state).clone();
+                    match scratch.submit(signed) {
+                        Ok(pending) => {
+                            let hash = pending["hash"].as_str().unwrap_or_default();
+                            let tx = scratch.transactions.remove(hash.trim_start_matches("0x"));
+                            (StatusCode::OK, json!([tx]))
+                        }
+                        Err(e) => e,
+                    }
+                }
+                Err(e) => (
+                    StatusCode::BAD_REQUEST,
+                    json!({
+                        "message": format!("invalid transaction: {}", e),
+                        "error_code": "invalid_input",
+                        "vm_error_code": null,
+                    }),
+                ),
+            }
+        }
+        (&Method::POST, ["transactions"]) => match bcs::from_bytes::<SignedTransaction>(body) {
+            Ok(signed) => match state.submit(signed) {
+                Ok(pending) => (StatusCode::ACCEPTED, pending),
+                Err(e) => e,
+            },
+            Err(e) => (
+                StatusCode::BAD_REQUEST,
+                json!({
+                    "message": format!("invalid transaction: {}", e),
+                    "error_code": "invalid_input",
+                    "vm_error_code": null,
+                }),
+            ),
+        },
+        _ => not_found("route not supported by fake node", "web_framework_error"),
+    }
+}
+
+/// Fake Aptos node serving the REST surface used by `LaminarClient`.
+///
+/// Accounts are created on first use with sequence number 0. Laminar `book` entry functions
+/// are executed immediately on submission, so transactions are committed by the time
+/// `wait_for_transaction` polls for them.
+pub struct FakeLaminarNode {
+    addr: SocketAddr,
+    state: Arc<Mutex<NodeState>>,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl FakeLaminarNode {
+    /// Start the fake node on a random local port.
+    ///
+    /// # Arguments:
+    ///
+    /// * `laminar` - Address the Laminar modules are considered to be published at.
+    pub async fn start(laminar: AccountAddress) -> Result<Self> {
+        let state = Arc::new(Mutex::new(NodeState {
+            laminar,
+            version: 0,
+            accounts: HashMap::new(),
+            books: HashMap::new(),
+            transactions: HashMap::new(),
+        }));
+
+        let service_state = state.clone();
+        let make_svc = make_service_fn(move |_| {
+            let state = service_state.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |req: Request<Body>| {
+                    let state = state.clone();
+                    async move { Ok::<_, Infallible>(handle(&state, req).await) }
+                }))
+            }
+        });
+
+        let server = Server::try_bind(&SocketAddr::from(([127, 0, 0, 1], 0)))
+            .context("failed binding fake node")?
+            .serve(make_svc);
+        let addr = server.local_addr();
+        let (tx, rx) = oneshot::channel::<()>();
+        tokio::spawn(server.with_graceful_shutdown(async {
+            rx.await.ok();
+        }));
+
+        Ok(Self {
+            addr,
+            state,
+            shutdown: Some(tx),
+        })
+    }
+
+    /// REST url to pass to `LaminarClient::connect`.
+    pub fn url(&self) -> Url {
+        Url::parse(&format!("http://{}/v1", self.addr)).expect("valid fake node url")
+    }
+
+    pub fn laminar(&self) -> AccountAddress {
+        self.state.lock().expect("fake node state poisoned").laminar
+    }
+
+    /// Set a raw resource on an account, e.g. `0x1::coin::CoinInfo<...>` or `0x1::coin::CoinStore<...>`.
+    pub fn set_resource(&self, addr: AccountAddress, resource_type: &str, data: Value) {
+        let mut state = self.state.lock().expect("fake node state poisoned");
+        state
+            .accounts
+            .entry(addr)
+            .or_default()
+            .resources
+            .insert(resource_type.replace(' ', ""), data);
+    }
+
+    /// Publish `CoinInfo` for a coin type at its module address.
+    pub fn create_coin(&self, coin: &TypeTag, symbol: &str, decimals: u8) -> Result<()> {
+        let TypeTag::Struct(tag) = coin else {
+            return Err(anyhow!("not a coin type: {}", coin));
+        };
+        let StructTag { address, name, .. } = tag.as_ref();
+        self.set_resource(
+            *address,
+            &format!("0x1::coin::CoinInfo<{}>", coin),
+            json!({
+                "name": name.as_str(),
+                "symbol": symbol,
+                "decimals": decimals,
+                "supply": { "vec": [] },
+            }),
+        );
+        Ok(())
+    }
+
+    /// Register an account for a coin with the given balance.
+    pub fn set_coin_balance(&self, addr: AccountAddress, coin: &TypeTag, value: u64) {
+        self.set_resource(
+            addr,
+            &format!("0x1::coin::CoinStore<{}>", coin),
+            json!({
+                "coin": { "value": value.to_string() },
+                "frozen": false,
+                "deposit_events": { "counter": "0", "guid": { "id": { "addr": addr.to_hex_literal(), "creation_num": "0" } } },
+                "withdraw_events": { "counter": "0", "guid": { "id": { "addr": addr.to_hex_literal(), "creation_num": "1" } } },
+            }),
+        );
+    }
+
+    /// Number of committed transactions.
+    pub fn version(&self) -> u64 {
+        self.state.lock().expect("fake node state poisoned").version
+    }
+}
+
+impl Drop for FakeLaminarNode {
+    fn drop(&mut self) {
+        if let Some(tx) = self.shutdown.take() {
+            let _ = tx.send(());
+        }
+    }
+}
+
+async fn handle(state: &Mutex<NodeState>, req: Request<Body>) -> Response<Body> {
+    let method = req.method().clone();
+    let path = req.uri().path().to_string();
+    let query = req
+        .uri()
+        .query()
+        .unwrap_or_default()
+        .split('&')
+        .filter_map(|kv| kv.split_once('='))
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect::<HashMap<_, _>>();
+    let body = hyper::body::to_bytes(req.into_body())
+        .await
+        .map(|b| b.to_vec())
+        .unwrap_or_default();
+
+    let (status, value) = route(state, &method, &path, &query, &body);
+    let headers = state.lock().expect("fake node state poisoned").headers();
+    let mut builder = Response::builder()
+        .status(status)
+        .header("Content-Type", "application/json");
+    for (k, v) in headers {
+        builder = builder.header(k, v);
+    }
+    builder
+        .body(Body::from(value.to_string()))
+        .unwrap_or_else(|_| Response::new(Body::empty()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::LaminarError;
+    use crate::market_data::MarketDataFeed;
+    use crate::oco::{OcoManager, OcoPair, OcoState};
+    use crate::paper::PaperTrader;
+    use crate::queue::{ActionKind, SubmissionQueue};
+    use crate::rate_limit::{Endpoint, RateLimit, RateLimiter};
+    use crate::risk::{RiskLimits, RiskManager};
+    use crate::submission::SubmissionStatus;
+    use crate::trigger::StopOrder;
+    use crate::types::events::LaminarEvent;
+    use crate::LaminarClient;
+    use aptos_sdk::move_types::identifier::Identifier;
+    use aptos_sdk::types::LocalAccount;
+    use std::time::Duration;
+
+    struct Market {
+        node: FakeLaminarNode,
+        base: TypeTag,
+        quote: TypeTag,
+        owner: AccountAddress,
+    }
+
+    fn coin(name: &str) -> TypeTag {
+        TypeTag::Struct(Box::new(StructTag {
+            address: AccountAddress::from_hex_literal("0xc0").unwrap(),
+            module: Identifier::new("coins").unwrap(),
+            name: Identifier::new(name).unwrap(),
+            type_params: vec![],
+        }))
+    }
+
+    // connect `n` clients of a new account and register it
+    async fn connect(node: &FakeLaminarNode, n: usize) -> Vec<LaminarClient> {
+        let account = LocalAccount::generate(&mut rand::rngs::OsRng);
+        let key = hex::encode(account.private_key().to_bytes());
+        let mut clients = vec![];
+        for _ in 0..n {
+            let client = LaminarClient::connect_with_strings(
+                node.url().as_str(),
+                &node.laminar().to_hex_literal(),
+                &account.address().to_hex_literal(),
+                &key,
+            )
+            .await
+            .unwrap();
+            clients.push(client);
+        }
+        clients[0].register_user().await.unwrap();
+        clients
+    }
+
+    async fn client(node: &FakeLaminarNode) -> LaminarClient {
+        connect(node, 1).await.remove(0)
+    }
+
+    async fn market() -> (Market, LaminarClient) {
+        let laminar = AccountAddress::from_hex_literal("0x1a").unwrap();
+        let node = FakeLaminarNode::start(laminar).await.unwrap();
+        let (base, quote) = (coin("Base"), coin("Quote"));
+        node.create_coin(&base, "BASE", 6).unwrap();
+        node.create_coin(&quote, "QUOTE", 6).unwrap();
+        let owner = client(&node).await;
+        owner
+            .create_orderbook(&base, &quote, 2, 2, 1)
+            .await
+            .unwrap();
+
+        let market = Market {
+            node,
+            base,
+            quote,
+            owner: owner.address(),
+        };
+        (market, owner)
+    }
+
+    #[tokio::test]
+    async fn test_place_matches_resting_order() {
+        let (m, maker) = market().await;
+        let taker = client(&m.node).await;
+
+        let ask = maker
+            .place_limit_order(
+                &m.base,
+                &m.quote,
+                &m.owner,
+                Side::Ask,
+                100,
+                10,
+                TimeInForce::GoodTillCanceled,
+                false,
+            )
+            .await
+            .unwrap();
+        assert!(ask.fills.is_empty());
+
+        let bid = taker
+            .place_limit_order(
+                &m.base,
+                &m.quote,
+                &m.owner,
+                Side::Bid,
+                101,
+                4,
+                TimeInForce::ImmediateOrCancel,
+                false,
+            )
+            .await
+            .unwrap();
+        assert_eq!(bid.fills.len(), 1);
+        assert_eq!(bid.fills[0].price, 100);
+        assert_eq!(bid.fills[0].fill_size, 4);
+        assert!(bid.cancel.is_none());
+
+        let book = taker
+            .fetch_orderbook(&m.base, &m.quote, &m.owner)
+            .await
+            .unwrap();
+        assert!(book.bids.is_empty());
+        let asks = &book.asks[&100];
+        assert_eq!(asks.len(), 1);
+        assert_eq!(asks[0].id, ask.place.order_id);
+        assert_eq!(asks[0].remaining_size, 6);
+    }
+
+    #[tokio::test]
+    async fn test_amend_and_cancel() {
+        let (m, client) = market().await;
+        let placed = client
+            .place_limit_order(
+                &m.base,
+                &m.quote,
+                &m.owner,
+                Side::Bid,
+                100,
+                10,
+                TimeInForce::GoodTillCanceled,
+                false,
+            )
+            .await
+            .unwrap();
+        let order_id = placed.place.order_id;
+
+        let amend = client
+            .amend_order(&m.base, &m.quote, &m.owner, &order_id, Side::Bid, 101, 8)
+            .await
+            .unwrap();
+        assert_eq!(amend.order_id, order_id);
+        let book = client
+            .fetch_orderbook(&m.base, &m.quote, &m.owner)
+            .await
+            .unwrap();
+        assert!(!book.bids.contains_key(&100));
+        assert_eq!(book.bids[&101][0].id, order_id);
+        assert_eq!(book.bids[&101][0].remaining_size, 8);
+
+        let cancel = client
+            .cancel_order(&m.base, &m.quote, &m.owner, &order_id, Side::Bid)
+            .await
+            .unwrap();
+        assert_eq!(cancel.order_id, order_id);
+        let book = client
+            .fetch_orderbook(&m.base, &m.quote, &m.owner)
+            .await
+            .unwrap();
+        assert!(book.bids.is_empty());
+
+        let err = client
+            .cancel_order(&m.base, &m.quote, &m.owner, &order_id, Side::Bid)
+            .await
+            .unwrap_err();
+        assert_eq!(err.abort_code(), Some(EORDER_NOT_FOUND));
+    }
+
+    #[tokio::test]
+    async fn test_stale_sequence_number_is_recovered() {
+        let (m, _owner) = market().await;
+        let clients = connect(&m.node, 2).await;
+        let (first, second) = (&clients[0], &clients[1]);
+        // `second` connected before `first` registered, its sequence number is stale
+        assert_eq!(second.sequence_number(), 0);
+
+        for (client, price) in [(first, 100), (second, 99)] {
+            client
+                .place_limit_order(
+                    &m.base,
+                    &m.quote,
+                    &m.owner,
+                    Side::Bid,
+                    price,
+                    10,
+                    TimeInForce::GoodTillCanceled,
+                    false,
+                )
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(second.get_sequence_number().await.unwrap(), 3);
+        assert_eq!(second.sequence_number(), 3);
+        let book = second
+            .fetch_orderbook(&m.base, &m.quote, &m.owner)
+            .await
+            .unwrap();
+        assert_eq!(book.bids.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_resubmitted_tx_commits_once() {
+        let (m, client) = market().await;
+        let payload = client
+            .place_limit_order_payload(
+                &m.base,
+                &m.quote,
+                &m.owner,
+                Side::Bid,
+                100,
+                10,
+                TimeInForce::GoodTillCanceled,
+                false,
+            )
+            .unwrap();
+        let submission = client
+            .sign_submission(payload, *client.tx_options())
+            .await
+            .unwrap();
+
+        let SubmissionStatus::Committed(first) = client.submit_once(&submission).await.unwrap()
+        else {
+            panic!("tx not committed");
+        };
+        let version = m.node.version();
+        let SubmissionStatus::Committed(again) = client.submit_once(&submission).await.unwrap()
+        else {
+            panic!("tx not found after resubmitting");
+        };
+        assert_eq!(again.hash(), first.hash());
+        assert_eq!(m.node.version(), version);
+
+        let book = client
+            .fetch_orderbook(&m.base, &m.quote, &m.owner)
+            .await
+            .unwrap();
+        assert_eq!(book.bids[&100].len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_feed_delivers_events_of_other_accounts() {
+        let (m, maker) = market().await;
+        let taker = client(&m.node).await;
+        let watcher = client(&m.node).await;
+        let mut feed = MarketDataFeed::new(m.base.clone(), m.quote.clone(), m.owner);
+        // the first poll starts at the latest transaction, the book creation is not replayed
+        assert!(feed.poll_envelopes(&watcher).await.unwrap().is_empty());
+
+        for (client, side) in [(&maker, Side::Ask), (&taker, Side::Bid)] {
+            client
+                .place_limit_order(
+                    &m.base,
+                    &m.quote,
+                    &m.owner,
+                    side,
+                    100,
+                    4,
+                    TimeInForce::GoodTillCanceled,
+                    false,
+                )
+                .await
+                .unwrap();
+        }
+
+        let events = feed.poll_envelopes(&watcher).await.unwrap();
+        let places = events
+            .iter()
+            .filter(|e| matches!(e.event, LaminarEvent::PlaceOrder(_)))
+            .count();
+        assert_eq!(places, 2);
+        assert!(events
+            .iter()
+            .any(|e| matches!(&e.event, LaminarEvent::FillEvent(f) if f.fill_size == 4)));
+        assert_eq!(feed.cursor().map(|c| c.version), Some(m.node.version() + 1));
+        assert!(feed.poll_envelopes(&watcher).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_paper_order_fills_against_live_order() {
+        let (m, maker) = market().await;
+        let trader = client(&m.node).await;
+        let live = crate::types::market::Market {
+            base: m.base.clone(),
+            quote: m.quote.clone(),
+            book_owner: m.owner,
+        };
+        let paper = PaperTrader::connect(&trader, live).await.unwrap();
+        let bid = paper
+            .place_limit_order(
+                &m.base,
+                &m.quote,
+                &m.owner,
+                Side::Bid,
+                100,
+                4,
+                TimeInForce::GoodTillCanceled,
+                false,
+            )
+            .await
+            .unwrap();
+        assert!(bid.fills.is_empty());
+
+        maker
+            .place_limit_order(
+                &m.base,
+                &m.quote,
+                &m.owner,
+                Side::Ask,
+                100,
+                10,
+                TimeInForce::GoodTillCanceled,
+                false,
+            )
+            .await
+            .unwrap();
+
+        let events = paper.sync(&trader).await.unwrap();
+        assert!(events.iter().any(|e| matches!(
+            e,
+            LaminarEvent::FillEvent(f) if f.order_id == bid.place.order_id && f.fill_size == 4
+        )));
+        assert!(paper.open_orders().is_empty());
+        assert!(paper.position().size > 0.0);
+        // the live book is untouched by the paper order
+        let book = trader
+            .fetch_orderbook(&m.base, &m.quote, &m.owner)
+            .await
+            .unwrap();
+        assert_eq!(book.asks[&100][0].remaining_size, 10);
+    }
+
+    #[tokio::test]
+    async fn test_order_refused_after_kill_switch() {
+        let (m, mut client) = market().await;
+        client.set_risk_manager(Some(RiskManager::new(RiskLimits::default())));
+        client.kill_switch().await.unwrap();
+        let version = m.node.version();
+
+        let err = client
+            .place_limit_order(
+                &m.base,
+                &m.quote,
+                &m.owner,
+                Side::Bid,
+                100,
+                10,
+                TimeInForce::GoodTillCanceled,
+                false,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, LaminarError::RiskLimit(_)));
+        assert_eq!(m.node.version(), version);
+    }
+
+    #[tokio::test]
+    async fn test_replace_reports_cancelled_order_when_place_fails() {
+        let (m, maker) = market().await;
+        let mut taker = client(&m.node).await;
+        taker.set_risk_manager(Some(RiskManager::new(RiskLimits {
+            max_net_position: Some(5),
+            ..RiskLimits::default()
+        })));
+        maker
+            .place_limit_order(
+                &m.base,
+                &m.quote,
+                &m.owner,
+                Side::Ask,
+                100,
+                10,
+                TimeInForce::GoodTillCanceled,
+                false,
+            )
+            .await
+            .unwrap();
+        let bid = taker
+            .place_limit_order(
+                &m.base,
+                &m.quote,
+                &m.owner,
+                Side::Bid,
+                90,
+                5,
+                TimeInForce::GoodTillCanceled,
+                false,
+            )
+            .await
+            .unwrap();
+
+        // the amend crosses, the bid is cancelled and the larger order breaks the position limit
+        let err = taker
+            .replace_order(
+                &m.base,
+                &m.quote,
+                &m.owner,
+                bid.order_id(),
+                Side::Bid,
+                100,
+                10,
+                false,
+            )
+            .await
+            .unwrap_err();
+        match err {
+            LaminarError::ReplaceFailed { cancelled, source } => {
+                assert_eq!(&cancelled.order_id, bid.order_id());
+                assert!(matches!(*source, LaminarError::RiskLimit(_)));
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+        let book = taker
+            .fetch_orderbook(&m.base, &m.quote, &m.owner)
+            .await
+            .unwrap();
+        assert!(book.bids.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_queued_cancel_overtakes_place_waiting_for_rate_limiter() {
+        let (m, mut client) = market().await;
+        let resting = client
+            .place_limit_order(
+                &m.base,
+                &m.quote,
+                &m.owner,
+                Side::Bid,
+                90,
+                10,
+                TimeInForce::GoodTillCanceled,
+                false,
+            )
+            .await
+            .unwrap();
+        client.set_rate_limiter(Some(Arc::new(
+            RateLimiter::new().with_limit(Endpoint::Submit, RateLimit::new(5.0, 1)),
+        )));
+        // use up the burst so the drain waits for the next token
+        client
+            .rate_limiter()
+            .unwrap()
+            .acquire(Endpoint::Submit)
+            .await;
+
+        let queue = Mutex::new(SubmissionQueue::new());
+        let place = client
+            .place_limit_order_payload(
+                &m.base,
+                &m.quote,
+                &m.owner,
+                Side::Bid,
+                95,
+                10,
+                TimeInForce::GoodTillCanceled,
+                false,
+            )
+            .unwrap();
+        queue.lock().unwrap().enqueue_place(place);
+        let cancel = client
+            .cancel_order_payload(&m.base, &m.quote, &m.owner, resting.order_id(), Side::Bid)
+            .unwrap();
+
+        let (results, _) = tokio::join!(client.submit_queued(&queue, 2), async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            queue.lock().unwrap().enqueue_cancel(cancel);
+        });
+        let kinds = results.iter().map(|(a, _)| a.kind).collect::<Vec<_>>();
+        assert_eq!(kinds, vec![ActionKind::Cancel, ActionKind::Place]);
+        assert!(results.iter().all(|(_, res)| res.is_ok()));
+    }
+
+    // place a resting GTC order from a new account
+    async fn rest_order(m: &Market, side: Side, price: u64, size: u64) {
+        client(&m.node)
+            .await
+            .place_limit_order(
+                &m.base,
+                &m.quote,
+                &m.owner,
+                side,
+                price,
+                size,
+                TimeInForce::GoodTillCanceled,
+                false,
+            )
+            .await
+            .unwrap();
+    }
+
+    // write an oco state file holding a single pair, as left by a crash
+    fn write_oco_state(client: &LaminarClient, pair: OcoPair) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("laminar-oco-{}.json", client.address()));
+        let state = json!({ "next_id": pair.id + 1, "pairs": [pair] });
+        std::fs::write(&path, serde_json::to_vec(&state).unwrap()).unwrap();
+        path
+    }
+
+    #[tokio::test]
+    async fn test_oco_fill_drops_stop() {
+        let (m, trader) = market().await;
+        let book = crate::types::market::Market::new(m.base.clone(), m.quote.clone(), m.owner);
+        let mut oco = OcoManager::new();
+        let id = oco
+            .place(&trader, book.clone(), Side::Ask, 10, 110, 90)
+            .await
+            .unwrap();
+        assert_eq!(oco.pair(id).unwrap().state, OcoState::Active);
+
+        rest_order(&m, Side::Bid, 110, 10).await;
+        rest_order(&m, Side::Bid, 90, 10).await;
+
+        // the take-profit filled before the fill event was applied, the stop must not fire
+        let placed = oco.on_price(&trader, &book, 90).await.unwrap();
+        assert!(placed.is_empty());
+        assert_eq!(oco.pair(id).unwrap().state, OcoState::TakeProfitFilled);
+        let l2 = trader
+            .fetch_orderbook(&m.base, &m.quote, &m.owner)
+            .await
+            .unwrap();
+        assert_eq!(l2.bids[&90][0].remaining_size, 10);
+    }
+
+    #[tokio::test]
+    async fn test_oco_stop_submitted_before_crash_is_not_placed_twice() {
+        let (m, trader) = market().await;
+        let book = crate::types::market::Market::new(m.base.clone(), m.quote.clone(), m.owner);
+        rest_order(&m, Side::Bid, 90, 20).await;
+
+        // the stop was signed, saved and committed, the process died before saving the result
+        let stop = StopOrder::stop(book.clone(), Side::Ask, 10, 95);
+        let submission = trader
+            .sign_submission(stop.payload(&trader).unwrap(), *trader.tx_options())
+            .await
+            .unwrap();
+        let status = trader.submit_once(&submission).await.unwrap();
+        assert!(matches!(status, SubmissionStatus::Committed(_)));
+        let path = write_oco_state(
+            &trader,
+            OcoPair {
+                id: 0,
+                book: book.clone(),
+                take_profit_price: 110,
+                take_profit_order: None,
+                stop,
+                state: OcoState::StopPending,
+                submission: Some(submission),
+            },
+        );
+
+        let mut oco = OcoManager::open(&path).unwrap();
+        let placed = oco.on_price(&trader, &book, 95).await.unwrap();
+        assert_eq!(placed.len(), 1);
+        assert_eq!(oco.pair(0).unwrap().state, OcoState::StopTriggered);
+        let l2 = trader
+            .fetch_orderbook(&m.base, &m.quote, &m.owner)
+            .await
+            .unwrap();
+        assert_eq!(l2.bids[&90][0].remaining_size, 10);
+
+        let reopened = OcoManager::open(&path).unwrap();
+        assert_eq!(reopened.pair(0).unwrap().state, OcoState::StopTriggered);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_oco_take_profit_submitted_before_crash_is_resumed() {
+        let (m, trader) = market().await;
+        let book = crate::types::market::Market::new(m.base.clone(), m.quote.clone(), m.owner);
+
+        // the take-profit was signed, saved and committed, the process died before saving it
+        let payload = trader
+            .place_limit_order_payload(
+                &m.base,
+                &m.quote,
+                &m.owner,
+                Side::Ask,
+                110,
+                10,
+                TimeInForce::GoodTillCanceled,
+                false,
+            )
+            .unwrap();
+        let submission = trader
+            .sign_submission(payload, *trader.tx_options())
+            .await
+            .unwrap();
+        trader.submit_once(&submission).await.unwrap();
+        let path = write_oco_state(
+            &trader,
+            OcoPair {
+                id: 0,
+                book: book.clone(),
+                take_profit_price: 110,
+                take_profit_order: None,
+                stop: StopOrder::stop(book.clone(), Side::Ask, 10, 90),
+                state: OcoState::Placing,
+                submission: Some(submission),
+            },
+        );
+
+        let mut oco = OcoManager::open(&path).unwrap();
+        let placed = oco.on_price(&trader, &book, 100).await.unwrap();
+        assert!(placed.is_empty());
+        let pair = oco.pair(0).unwrap();
+        assert_eq!(pair.state, OcoState::Active);
+        assert!(pair.submission.is_none());
+        let l2 = trader
+            .fetch_orderbook(&m.base, &m.quote, &m.owner)
+            .await
+            .unwrap();
+        assert_eq!(l2.asks[&110].len(), 1);
+        assert_eq!(pair.take_profit_order.as_ref(), Some(&l2.asks[&110][0].id));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
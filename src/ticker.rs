@@ -0,0 +1,143 @@
+use crate::error::Result;
+use crate::tape::TradeTape;
+use crate::time::unix_now_micros;
+use crate::types::events::FillEvent;
+use crate::types::order::Id;
+use crate::LaminarClient;
+use serde::Serialize;
+
+/// Length of the rolling ticker window in microseconds.
+pub const TICKER_WINDOW: u64 = 24 * 3_600_000_000;
+
+/// Rolling 24h summary of a book's trades, in price and size units of the book.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Ticker {
+    pub book_id: Id,
+    /// Price of the most recent trade, including trades older than the window.
+    pub last_price: Option<u64>,
+    pub high: Option<u64>,
+    pub low: Option<u64>,
+    pub volume: u64,
+    pub trades: u64,
+    /// Change from the first trade in the window to the last trade, in percent.
+    pub price_change_pct: Option<f64>,
+}
+
+impl Ticker {
+    /// Compute the ticker for the window ending at `now`.
+    /// Fills are reconciled into trades with `TradeTape`, so each match is counted once.
+    ///
+    /// # Arguments:
+    ///
+    /// * `book_id` - `OrderBook` Id, fills of other books are ignored.
+    /// * `fills` - Fill events, in any order.
+    /// * `now` - End of the window, in the same unit as `FillEvent::time`.
+    pub fn from_fills<'a, I>(book_id: &Id, fills: I, now: u64) -> Self
+    where
+        I: IntoIterator<Item = &'a FillEvent>,
+    {
+        let trades = TradeTape::from_fills(
+            fills
+                .into_iter()
+                .filter(|f| &f.book_id == book_id && f.time <= now),
+        );
+
+        let start = now.saturating_sub(TICKER_WINDOW);
+        let window = trades.iter().filter(|t| t.time > start).collect::<Vec<_>>();
+        let open = window.first().map(|t| t.price);
+        let last_price = trades.last().map(|t| t.price);
+        let price_change_pct = match (open, window.last()) {
+            (Some(open), Some(last)) if open > 0 => {
+                Some((last.price as f64 - open as f64) / open as f64 * 100.0)
+            }
+            _ => None,
+        };
+
+        Self {
+            book_id: book_id.clone(),
+            last_price,
+            high: window.iter().map(|t| t.price).max(),
+            low: window.iter().map(|t| t.price).min(),
+            volume: window.iter().map(|t| t.size).sum(),
+            trades: window.len() as u64,
+            price_change_pct,
+        }
+    }
+}
+
+impl LaminarClient {
+    /// Compute the 24h `Ticker` of a book.
+    /// Fill events are stored per account, so only trades this client's account took part in
+    /// are included.
+    ///
+    /// # Arguments:
+    ///
+    /// * `book_id` - `OrderBook` Id.
+    pub async fn get_ticker(&self, book_id: &Id) -> Result<Ticker> {
+        let fills = self.fetch_all_fill_events(book_id).await?;
+        Ok(Ticker::from_fills(book_id, &fills, unix_now_micros()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::order::Side;
+    use aptos_api_types::{Address, U64};
+    use aptos_sdk::types::account_address::AccountAddress;
+
+    fn id(creation_num: u64) -> Id {
+        Id {
+            creation_num: U64(creation_num),
+            addr: Address::from(AccountAddress::ONE),
+        }
+    }
+
+    // maker and taker fill of one match
+    fn trade(book: u64, price: u64, size: u64, time: u64) -> [FillEvent; 2] {
+        let fill = |order, side, is_maker| FillEvent {
+            book_id: id(book),
+            order_id: id(order),
+            side,
+            price,
+            fill_size: size,
+            fee: 0,
+            fee_rate: 0,
+            time,
+            remaining_size: 0,
+            is_maker,
+        };
+        [fill(1, Side::Ask, true), fill(2, Side::Bid, false)]
+    }
+
+    #[test]
+    fn test_window() {
+        let now = TICKER_WINDOW + 10;
+        let fills = [
+            trade(0, 50, 1, 5),
+            trade(0, 110, 2, 30),
+            trade(0, 100, 3, 20),
+            // other books and trades after `now` are left out
+            trade(1, 200, 1, 25),
+            trade(0, 300, 1, now + 1),
+        ]
+        .concat();
+        let ticker = Ticker::from_fills(&id(0), &fills, now);
+
+        assert_eq!(ticker.last_price, Some(110));
+        assert_eq!((ticker.high, ticker.low), (Some(110), Some(100)));
+        assert_eq!((ticker.volume, ticker.trades), (5, 2));
+        assert_eq!(ticker.price_change_pct, Some(10.0));
+    }
+
+    #[test]
+    fn test_no_trades_in_window() {
+        let fills = trade(0, 50, 1, 5);
+        let ticker = Ticker::from_fills(&id(0), &fills, TICKER_WINDOW + 10);
+
+        assert_eq!(ticker.last_price, Some(50));
+        assert_eq!((ticker.high, ticker.low), (None, None));
+        assert_eq!((ticker.volume, ticker.trades), (0, 0));
+        assert_eq!(ticker.price_change_pct, None);
+    }
+}
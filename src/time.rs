@@ -0,0 +1,75 @@
+// Clock and timers used by the client. Natively they are backed by std and tokio, on
+// `wasm32` with the `wasm` feature by the browser, where std clocks panic and there is no
+// tokio timer.
+
+use std::future::Future;
+use std::time::Duration;
+
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+pub(crate) use instant::Instant;
+#[cfg(not(all(target_arch = "wasm32", feature = "wasm")))]
+pub(crate) use std::time::Instant;
+
+/// The future passed to `timeout` did not complete in time.
+#[derive(Debug)]
+pub(crate) struct Elapsed;
+
+#[cfg(not(all(target_arch = "wasm32", feature = "wasm")))]
+pub(crate) async fn sleep(duration: Duration) {
+    tokio::time::sleep(duration).await
+}
+
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+pub(crate) async fn sleep(duration: Duration) {
+    gloo_timers::future::sleep(duration).await
+}
+
+#[cfg(not(all(target_arch = "wasm32", feature = "wasm")))]
+pub(crate) async fn timeout<F: Future>(
+    duration: Duration,
+    future: F,
+) -> Result<F::Output, Elapsed> {
+    tokio::time::timeout(duration, future)
+        .await
+        .map_err(|_| Elapsed)
+}
+
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+pub(crate) async fn timeout<F: Future>(
+    duration: Duration,
+    future: F,
+) -> Result<F::Output, Elapsed> {
+    use futures::future::{select, Either};
+
+    futures::pin_mut!(future);
+    let timer = sleep(duration);
+    futures::pin_mut!(timer);
+    match select(future, timer).await {
+        Either::Left((output, _)) => Ok(output),
+        Either::Right(_) => Err(Elapsed),
+    }
+}
+
+/// Time since the unix epoch.
+#[cfg(not(all(target_arch = "wasm32", feature = "wasm")))]
+pub(crate) fn unix_now() -> Duration {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+}
+
+/// Time since the unix epoch, at the millisecond precision of the browser clock.
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+pub(crate) fn unix_now() -> Duration {
+    Duration::from_millis(js_sys::Date::now() as u64)
+}
+
+/// Seconds since the unix epoch.
+pub(crate) fn unix_now_secs() -> u64 {
+    unix_now().as_secs()
+}
+
+/// Microseconds since the unix epoch.
+pub(crate) fn unix_now_micros() -> u64 {
+    unix_now().as_micros() as u64
+}
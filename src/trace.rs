@@ -0,0 +1,17 @@
+// Emit a `tracing` event if the `tracing` feature is enabled. Arguments are not evaluated
+// otherwise, e.g. `trace_event!(debug, hash = %hash, "submitted tx")`.
+macro_rules! trace_event {
+    ($level:ident, $($arg:tt)+) => {
+        #[cfg(feature = "tracing")]
+        tracing::$level!($($arg)+);
+    };
+}
+
+// Record a field declared on the current span, e.g. `trace_record!("tx.hash", hash)`.
+// Fields not declared on the span are ignored.
+macro_rules! trace_record {
+    ($field:literal, $value:expr) => {
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record($field, tracing::field::display($value));
+    };
+}
@@ -0,0 +1,230 @@
+use crate::types::events::{
+    AmendOrderEvent, CancelOrderEvent, FillEvent, LaminarEvent, PlaceOrderEvent,
+};
+use crate::types::order::{Id, Order, Side, State, TimeInForce};
+use std::collections::{HashMap, HashSet};
+
+#[derive(Clone, Debug)]
+pub struct TrackedOrder {
+    pub book_id: Id,
+    pub time_in_force: TimeInForce,
+    pub order: Order,
+}
+
+/// Tracks orders from their Laminar events and keeps secondary indices by book,
+/// side, price level and state.
+#[derive(Debug, Default)]
+pub struct OrderTracker {
+    orders: HashMap<Id, TrackedOrder>,
+    by_book: HashMap<Id, HashSet<Id>>,
+    by_side: HashMap<(Id, Side), HashSet<Id>>,
+    by_level: HashMap<(Id, Side, u64), Vec<Id>>,
+    by_state: HashMap<State, HashSet<Id>>,
+}
+
+impl OrderTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn index(&mut self, id: &Id) {
+        let Some(t) = self.orders.get(id) else {
+            return;
+        };
+        let (book_id, side, price, state) = (
+            t.book_id.clone(),
+            t.order.side,
+            t.order.price,
+            t.order.state,
+        );
+
+        self.by_book
+            .entry(book_id.clone())
+            .or_default()
+            .insert(id.clone());
+        self.by_state.entry(state).or_default().insert(id.clone());
+        if state.is_open() {
+            self.by_side
+                .entry((book_id.clone(), side))
+                .or_default()
+                .insert(id.clone());
+            self.by_level
+                .entry((book_id, side, price))
+                .or_default()
+                .push(id.clone());
+        }
+    }
+
+    fn unindex(&mut self, id: &Id) {
+        let Some(t) = self.orders.get(id) else {
+            return;
+        };
+        let (book_id, side, price, state) = (
+            t.book_id.clone(),
+            t.order.side,
+            t.order.price,
+            t.order.state,
+        );
+
+        if let Some(ids) = self.by_state.get_mut(&state) {
+            ids.remove(id);
+        }
+        if let Some(ids) = self.by_side.get_mut(&(book_id.clone(), side)) {
+            ids.remove(id);
+        }
+        let level = (book_id, side, price);
+        if let Some(ids) = self.by_level.get_mut(&level) {
+            ids.retain(|i| i != id);
+            if ids.is_empty() {
+                self.by_level.remove(&level);
+            }
+        }
+    }
+
+    fn update<F: FnOnce(&mut TrackedOrder)>(&mut self, id: &Id, f: F) {
+        if !self.orders.contains_key(id) {
+            return;
+        }
+        self.unindex(id);
+        if let Some(t) = self.orders.get_mut(id) {
+            f(t);
+        }
+        self.index(id);
+    }
+
+    pub fn on_place(&mut self, e: &PlaceOrderEvent) {
+        // IOC and FOK orders never rest, they end filled or with their unfilled size expired
+        let state = if matches!(e.time_in_force, TimeInForce::GoodTillCanceled) {
+            State::Open
+        } else {
+            State::Expired
+        };
+        let order = Order {
+            id: e.order_id.clone(),
+            side: e.side,
+            price: e.price,
+            size: e.size,
+            post_only: e.post_only,
+            remaining_size: e.size,
+            state,
+            fills: vec![],
+        };
+
+        self.unindex(&e.order_id);
+        self.orders.insert(
+            e.order_id.clone(),
+            TrackedOrder {
+                book_id: e.book_id.clone(),
+                time_in_force: e.time_in_force,
+                order,
+            },
+        );
+        self.index(&e.order_id);
+    }
+
+    pub fn on_amend(&mut self, e: &AmendOrderEvent) {
+        self.update(&e.order_id, |t| {
+            let filled = t.order.size.saturating_sub(t.order.remaining_size);
+            t.order.price = e.price;
+            t.order.size = e.size;
+            t.order.remaining_size = e.size.saturating_sub(filled);
+        });
+    }
+
+    pub fn on_cancel(&mut self, e: &CancelOrderEvent) {
+        self.update(&e.order_id, |t| {
+            t.order.state = e.order_state(t.time_in_force)
+        });
+    }
+
+    pub fn on_fill(&mut self, e: &FillEvent) {
+        self.update(&e.order_id, |t| {
+            t.order.remaining_size = e.remaining_size;
+            if e.remaining_size == 0 {
+                t.order.state = State::Filled;
+            } else if t.order.state == State::Open {
+                t.order.state = State::PartiallyFilled;
+            }
+            t.order.fills.push(e.clone());
+        });
+    }
+
+    /// Apply any Laminar event. Events for orders that are not tracked are ignored,
+    /// except for place events which start tracking a new order.
+    pub fn apply(&mut self, event: &LaminarEvent) {
+        match event {
+            LaminarEvent::PlaceOrder(e) => self.on_place(e),
+            LaminarEvent::AmendOrder(e) => self.on_amend(e),
+            LaminarEvent::CancelOrder(e) => self.on_cancel(e),
+            LaminarEvent::FillEvent(e) => self.on_fill(e),
+            LaminarEvent::CreateOrderBook(_) | LaminarEvent::Unknown { .. } => {}
+        }
+    }
+
+    /// Stop tracking an order.
+    pub fn remove(&mut self, id: &Id) -> Option<TrackedOrder> {
+        self.unindex(id);
+        let t = self.orders.remove(id)?;
+        if let Some(ids) = self.by_book.get_mut(&t.book_id) {
+            ids.remove(id);
+        }
+        Some(t)
+    }
+
+    pub fn get(&self, id: &Id) -> Option<&TrackedOrder> {
+        self.orders.get(id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.orders.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.orders.is_empty()
+    }
+
+    fn collect<'a>(&'a self, ids: impl IntoIterator<Item = &'a Id>) -> Vec<&'a TrackedOrder> {
+        ids.into_iter()
+            .filter_map(|id| self.orders.get(id))
+            .collect()
+    }
+
+    /// Resting orders at a price level, in placement order.
+    pub fn orders_at(&self, book_id: &Id, side: Side, price: u64) -> Vec<&TrackedOrder> {
+        self.by_level
+            .get(&(book_id.clone(), side, price))
+            .map(|ids| self.collect(ids))
+            .unwrap_or_default()
+    }
+
+    /// Resting orders on one side of a book.
+    pub fn orders_on_side(&self, book_id: &Id, side: Side) -> Vec<&TrackedOrder> {
+        self.by_side
+            .get(&(book_id.clone(), side))
+            .map(|ids| self.collect(ids))
+            .unwrap_or_default()
+    }
+
+    /// All tracked orders of a book, including closed ones.
+    pub fn orders_in_book(&self, book_id: &Id) -> Vec<&TrackedOrder> {
+        self.by_book
+            .get(book_id)
+            .map(|ids| self.collect(ids))
+            .unwrap_or_default()
+    }
+
+    pub fn orders_by_state(&self, state: State) -> Vec<&TrackedOrder> {
+        self.by_state
+            .get(&state)
+            .map(|ids| self.collect(ids))
+            .unwrap_or_default()
+    }
+
+    /// Resting size at a price level.
+    pub fn size_at(&self, book_id: &Id, side: Side, price: u64) -> u64 {
+        self.orders_at(book_id, side, price)
+            .iter()
+            .map(|t| t.order.remaining_size)
+            .sum()
+    }
+}
@@ -0,0 +1,167 @@
+//! Order operations shared by `LaminarClient` and `PaperTrader`, so a strategy runs live or
+//! on paper unchanged.
+
+use crate::error::Result;
+use crate::types::events::{AmendOrderEvent, CancelOrderEvent, LaminarEvent};
+use crate::types::l2::L2Book;
+use crate::types::market::Market;
+use crate::types::order::{Id, Side, TimeInForce};
+use crate::{LaminarClient, PlacedOrder};
+use aptos_sdk::move_types::language_storage::TypeTag;
+use aptos_sdk::types::account_address::AccountAddress;
+use async_trait::async_trait;
+
+/// Places and manages the orders of an account. The order methods take the same arguments as
+/// the ones of `LaminarClient`, see there.
+#[async_trait]
+pub trait Trader: Send + Sync {
+    /// Account the orders are placed for.
+    fn address(&self) -> AccountAddress;
+
+    /// Aggregated depth of the book of `market` the orders trade on, `depth` levels per side.
+    async fn fetch_depth(&self, market: &Market, depth: usize) -> Result<L2Book>;
+
+    /// Events of the account's orders, given the live events of `market` since the last call.
+    async fn order_events(
+        &self,
+        market: &Market,
+        events: &[LaminarEvent],
+    ) -> Result<Vec<LaminarEvent>>;
+
+    #[allow(clippy::too_many_arguments)]
+    async fn place_limit_order(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        book_owner: &AccountAddress,
+        side: Side,
+        price: u64,
+        size: u64,
+        time_in_force: TimeInForce,
+        post_only: bool,
+    ) -> Result<PlacedOrder>;
+
+    async fn place_market_order(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        book_owner: &AccountAddress,
+        side: Side,
+        size: u64,
+    ) -> Result<PlacedOrder>;
+
+    #[allow(clippy::too_many_arguments)]
+    async fn amend_order(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        book_owner: &AccountAddress,
+        order_id: &Id,
+        side: Side,
+        price: u64,
+        size: u64,
+    ) -> Result<AmendOrderEvent>;
+
+    async fn cancel_order(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        book_owner: &AccountAddress,
+        order_id: &Id,
+        side: Side,
+    ) -> Result<CancelOrderEvent>;
+
+    /// Cancel the account's resting orders on `books`.
+    async fn cancel_all_orders(&self, books: &[Market]) -> Result<Vec<CancelOrderEvent>>;
+}
+
+#[async_trait]
+impl Trader for LaminarClient {
+    fn address(&self) -> AccountAddress {
+        LaminarClient::address(self)
+    }
+
+    async fn fetch_depth(&self, market: &Market, depth: usize) -> Result<L2Book> {
+        let book = self
+            .fetch_orderbook(&market.base, &market.quote, &market.book_owner)
+            .await?;
+        Ok(book.to_l2(depth))
+    }
+
+    async fn order_events(
+        &self,
+        _market: &Market,
+        events: &[LaminarEvent],
+    ) -> Result<Vec<LaminarEvent>> {
+        let account = LaminarClient::address(self);
+        Ok(events
+            .iter()
+            .filter(|e| e.order_id().map_or(false, |id| id.addr.inner() == &account))
+            .cloned()
+            .collect())
+    }
+
+    async fn place_limit_order(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        book_owner: &AccountAddress,
+        side: Side,
+        price: u64,
+        size: u64,
+        time_in_force: TimeInForce,
+        post_only: bool,
+    ) -> Result<PlacedOrder> {
+        LaminarClient::place_limit_order(
+            self,
+            base,
+            quote,
+            book_owner,
+            side,
+            price,
+            size,
+            time_in_force,
+            post_only,
+        )
+        .await
+    }
+
+    async fn place_market_order(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        book_owner: &AccountAddress,
+        side: Side,
+        size: u64,
+    ) -> Result<PlacedOrder> {
+        LaminarClient::place_market_order(self, base, quote, book_owner, side, size).await
+    }
+
+    async fn amend_order(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        book_owner: &AccountAddress,
+        order_id: &Id,
+        side: Side,
+        price: u64,
+        size: u64,
+    ) -> Result<AmendOrderEvent> {
+        LaminarClient::amend_order(self, base, quote, book_owner, order_id, side, price, size).await
+    }
+
+    async fn cancel_order(
+        &self,
+        base: &TypeTag,
+        quote: &TypeTag,
+        book_owner: &AccountAddress,
+        order_id: &Id,
+        side: Side,
+    ) -> Result<CancelOrderEvent> {
+        LaminarClient::cancel_order(self, base, quote, book_owner, order_id, side).await
+    }
+
+    async fn cancel_all_orders(&self, books: &[Market]) -> Result<Vec<CancelOrderEvent>> {
+        LaminarClient::cancel_all_orders(self, books).await
+    }
+}
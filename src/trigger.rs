@@ -0,0 +1,164 @@
+use crate::error::{LaminarError, Result};
+use crate::types::market::Market;
+use crate::types::order::{Side, TimeInForce};
+use crate::{LaminarClient, PlacedOrder};
+use aptos_sdk::types::transaction::EntryFunction;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Price condition of a `StopOrder`, in price units.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TriggerCondition {
+    AtOrAbove(u64),
+    AtOrBelow(u64),
+}
+
+impl TriggerCondition {
+    pub fn is_met(&self, price: u64) -> bool {
+        match self {
+            TriggerCondition::AtOrAbove(p) => price >= *p,
+            TriggerCondition::AtOrBelow(p) => price <= *p,
+        }
+    }
+}
+
+/// Order kept client-side until its trigger condition is met. The book does not support
+/// conditional orders, so it is submitted as a market order, or as an IOC limit order if
+/// `limit_price` is set, once triggered.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StopOrder {
+    pub book: Market,
+    pub side: Side,
+    pub size: u64,
+    pub condition: TriggerCondition,
+    pub limit_price: Option<u64>,
+}
+
+impl StopOrder {
+    /// Stop order triggering once the price moves through `stop_price` against a position,
+    /// i.e. at or below it for asks and at or above it for bids.
+    pub fn stop(book: Market, side: Side, size: u64, stop_price: u64) -> Self {
+        let condition = match side {
+            Side::Bid => TriggerCondition::AtOrAbove(stop_price),
+            Side::Ask => TriggerCondition::AtOrBelow(stop_price),
+        };
+
+        Self {
+            book,
+            side,
+            size,
+            condition,
+            limit_price: None,
+        }
+    }
+
+    /// Submit as an IOC limit order at `limit_price` instead of a market order.
+    pub fn with_limit_price(mut self, limit_price: u64) -> Self {
+        self.limit_price = Some(limit_price);
+        self
+    }
+
+    /// Entry function payload submitting the order.
+    pub fn payload(&self, client: &LaminarClient) -> Result<EntryFunction> {
+        let book = &self.book;
+        match self.limit_price {
+            Some(price) => client.place_limit_order_payload(
+                &book.base,
+                &book.quote,
+                &book.book_owner,
+                self.side,
+                price,
+                self.size,
+                TimeInForce::ImmediateOrCancel,
+                false,
+            ),
+            None => client.place_market_order_payload(
+                &book.base,
+                &book.quote,
+                &book.book_owner,
+                self.side,
+                self.size,
+            ),
+        }
+    }
+
+    /// Submit the order regardless of its condition.
+    pub async fn execute(&self, client: &LaminarClient) -> Result<PlacedOrder> {
+        let tx = client.build_and_submit_tx(self.payload(client)?).await?;
+        PlacedOrder::from_transaction(&tx)
+            .ok_or_else(|| LaminarError::NotFound("place order event".to_string()))
+    }
+}
+
+/// Handle of a `StopOrder` registered in a `TriggerEngine`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct TriggerId(u64);
+
+/// Holds `StopOrder`s and fires them when a price update meets their condition.
+/// Prices are supplied by the caller, e.g. from a `TradeTape` or `MarketDataFeed`.
+#[derive(Clone, Debug, Default)]
+pub struct TriggerEngine {
+    next_id: u64,
+    stops: BTreeMap<TriggerId, StopOrder>,
+}
+
+impl TriggerEngine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, stop: StopOrder) -> TriggerId {
+        let id = TriggerId(self.next_id);
+        self.next_id += 1;
+        self.stops.insert(id, stop);
+        id
+    }
+
+    pub fn remove(&mut self, id: TriggerId) -> Option<StopOrder> {
+        self.stops.remove(&id)
+    }
+
+    pub fn get(&self, id: TriggerId) -> Option<&StopOrder> {
+        self.stops.get(&id)
+    }
+
+    pub fn get_mut(&mut self, id: TriggerId) -> Option<&mut StopOrder> {
+        self.stops.get_mut(&id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.stops.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stops.is_empty()
+    }
+
+    /// Remove and return the stops on `book` whose condition is met by `price`.
+    pub fn on_price(&mut self, book: &Market, price: u64) -> Vec<(TriggerId, StopOrder)> {
+        let ids = self
+            .stops
+            .iter()
+            .filter(|(_, s)| &s.book == book && s.condition.is_met(price))
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        ids.into_iter()
+            .filter_map(|id| self.stops.remove(&id).map(|s| (id, s)))
+            .collect()
+    }
+
+    /// Fire the stops on `book` triggered by `price`, returning the result of each submission.
+    pub async fn execute(
+        &mut self,
+        client: &LaminarClient,
+        book: &Market,
+        price: u64,
+    ) -> Vec<(TriggerId, Result<PlacedOrder>)> {
+        let mut results = vec![];
+        for (id, stop) in self.on_price(book, price) {
+            results.push((id, stop.execute(client).await));
+        }
+
+        results
+    }
+}
@@ -0,0 +1,292 @@
+//! Terminal order book viewer, to watch and operate a book by hand, e.g. during incidents.
+//!
+//! The viewer re-reads the book on every poll, so the depth and the account's resting orders
+//! always come from the same snapshot, including changes no event reported. Trades and the
+//! feed's status come from a `MarketDataFeed`.
+
+use crate::error::Result;
+use crate::market_data::{MarketDataFeed, MarketDataUpdate};
+use crate::mirror::OrderBookMirror;
+use crate::types::decimal::format_units;
+use crate::types::events::{FillEvent, LaminarEvent};
+use crate::types::market::Market;
+use crate::types::order::{Instrument, OrderBook, Side};
+use crate::LaminarClient;
+use anyhow::Context;
+use aptos_sdk::types::account_address::AccountAddress;
+use crossterm::event::{self, Event, KeyCode};
+use crossterm::execute;
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use ratatui::backend::{Backend, CrosstermBackend};
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::widgets::{Block, Borders, Cell, Paragraph, Row, Table};
+use ratatui::{Frame, Terminal};
+use std::collections::{HashMap, VecDeque};
+use std::io::Stdout;
+use std::time::Duration;
+
+const MAX_TRADES: usize = 50;
+
+/// State of the viewer: depth of the book, the account's resting size per level and the
+/// latest trades.
+pub struct BookPanel {
+    account: AccountAddress,
+    instrument: Instrument,
+    mirror: OrderBookMirror,
+    mine: HashMap<(Side, u64), u64>,
+    trades: VecDeque<FillEvent>,
+    status: String,
+    // error of the last poll, shown until a poll succeeds
+    error: Option<String>,
+}
+
+impl BookPanel {
+    /// # Arguments:
+    ///
+    /// * `book` - Snapshot to start from.
+    /// * `account` - Account whose resting orders are highlighted.
+    /// * `depth` - Number of levels shown per side.
+    pub fn new(book: &OrderBook, account: AccountAddress, depth: usize) -> Self {
+        let mut panel = Self {
+            account,
+            instrument: book.instrument.clone(),
+            mirror: OrderBookMirror::with_depth(depth),
+            mine: HashMap::new(),
+            trades: VecDeque::new(),
+            status: String::new(),
+            error: None,
+        };
+        panel.apply_snapshot(book);
+        panel
+    }
+
+    pub fn apply_snapshot(&mut self, book: &OrderBook) {
+        self.mirror.apply_snapshot(book);
+        self.mine.clear();
+        for open in book.open_orders_of(&self.account) {
+            *self
+                .mine
+                .entry((open.order.side, open.order.price))
+                .or_default() += open.order.remaining_size;
+        }
+    }
+
+    /// Apply an update of the feed. Trades are taken from the taker's fills, so each trade
+    /// is listed once.
+    pub fn apply_update(&mut self, update: &MarketDataUpdate) {
+        match update {
+            MarketDataUpdate::Event(LaminarEvent::FillEvent(fill)) if !fill.is_maker => {
+                self.trades.push_front(fill.clone());
+                self.trades.truncate(MAX_TRADES);
+            }
+            MarketDataUpdate::Degraded { reason } => {
+                self.status = format!("polling snapshots: {}", reason)
+            }
+            MarketDataUpdate::Recovered => self.status.clear(),
+            _ => self.mirror.apply_update(update),
+        }
+    }
+
+    pub fn needs_refresh(&self) -> bool {
+        self.mirror.needs_refresh()
+    }
+
+    fn level_rows(&self, side: Side) -> Vec<Row> {
+        let mut levels = self.mirror.levels(side);
+        // asks are listed from the worst to the best so the spread is in the middle
+        if side == Side::Ask {
+            levels.reverse();
+        }
+        let color = match side {
+            Side::Bid => Color::Green,
+            Side::Ask => Color::Red,
+        };
+
+        levels
+            .into_iter()
+            .map(|(price, size)| {
+                let mine = self.mine.get(&(side, price)).copied().unwrap_or_default();
+                let mut style = Style::default().fg(color);
+                if mine > 0 {
+                    style = style.add_modifier(Modifier::BOLD | Modifier::REVERSED);
+                }
+                Row::new(vec![
+                    Cell::from(format_units(price, self.instrument.price_decimals)),
+                    Cell::from(format_units(size, self.instrument.size_decimals)),
+                    Cell::from(if mine > 0 {
+                        format_units(mine, self.instrument.size_decimals)
+                    } else {
+                        String::new()
+                    }),
+                ])
+                .style(style)
+            })
+            .collect()
+    }
+
+    fn trade_rows(&self) -> Vec<Row> {
+        self.trades
+            .iter()
+            .map(|fill| {
+                let (side, color) = match fill.side {
+                    Side::Bid => ("buy", Color::Green),
+                    Side::Ask => ("sell", Color::Red),
+                };
+                Row::new(vec![
+                    Cell::from(side),
+                    Cell::from(format_units(fill.price, self.instrument.price_decimals)),
+                    Cell::from(format_units(fill.fill_size, self.instrument.size_decimals)),
+                ])
+                .style(Style::default().fg(color))
+            })
+            .collect()
+    }
+
+    /// Draw the depth next to the latest trades.
+    pub fn render<B: Backend>(&self, frame: &mut Frame<B>, area: Rect) {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(1)])
+            .split(area);
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+            .split(rows[0]);
+
+        let widths = [
+            Constraint::Percentage(40),
+            Constraint::Percentage(35),
+            Constraint::Percentage(25),
+        ];
+        let mut levels = self.level_rows(Side::Ask);
+        levels.push(Row::new(vec![self.spread()]));
+        levels.extend(self.level_rows(Side::Bid));
+        let depth = Table::new(levels)
+            .header(Row::new(vec!["price", "size", "mine"]).style(Style::default().fg(Color::Gray)))
+            .block(Block::default().title("Book").borders(Borders::ALL))
+            .widths(&widths);
+        frame.render_widget(depth, columns[0]);
+
+        let trades = Table::new(self.trade_rows())
+            .header(Row::new(vec!["side", "price", "size"]).style(Style::default().fg(Color::Gray)))
+            .block(Block::default().title("Trades").borders(Borders::ALL))
+            .widths(&widths);
+        frame.render_widget(trades, columns[1]);
+
+        let status = match &self.error {
+            Some(e) => Paragraph::new(format!("q to quit error: {}", e))
+                .style(Style::default().fg(Color::Red)),
+            None => Paragraph::new(format!("q to quit {}", self.status)),
+        };
+        frame.render_widget(status, rows[1]);
+    }
+
+    fn spread(&self) -> String {
+        match (self.mirror.best_bid(), self.mirror.best_ask()) {
+            (Some((bid, _)), Some((ask, _))) => format!(
+                "spread {}",
+                format_units(ask.saturating_sub(bid), self.instrument.price_decimals)
+            ),
+            _ => String::new(),
+        }
+    }
+}
+
+fn setup_terminal() -> Result<Terminal<CrosstermBackend<Stdout>>> {
+    enable_raw_mode().context("failed enabling raw mode")?;
+    let mut stdout = std::io::stdout();
+    execute!(stdout, EnterAlternateScreen).context("failed entering alternate screen")?;
+    let terminal =
+        Terminal::new(CrosstermBackend::new(stdout)).context("failed creating terminal")?;
+    Ok(terminal)
+}
+
+fn restore_terminal(terminal: &mut Terminal<CrosstermBackend<Stdout>>) -> Result<()> {
+    disable_raw_mode().context("failed disabling raw mode")?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)
+        .context("failed leaving alternate screen")?;
+    terminal.show_cursor().context("failed showing cursor")?;
+    Ok(())
+}
+
+/// Show a live `BookPanel` of a book in the terminal until `q` or `Esc` is pressed.
+///
+/// # Arguments:
+///
+/// * `client` - `LaminarClient` whose account's orders are highlighted.
+/// * `market` - Book to show.
+/// * `depth` - Number of levels shown per side.
+/// * `poll_interval` - Delay between polls of the book and the feed.
+pub async fn run(
+    client: &LaminarClient,
+    market: &Market,
+    depth: usize,
+    poll_interval: Duration,
+) -> Result<()> {
+    let book = client
+        .fetch_orderbook(&market.base, &market.quote, &market.book_owner)
+        .await?;
+    let mut panel = BookPanel::new(&book, client.address(), depth);
+    // the snapshot covers the history, only events from now on are read
+    let mut feed =
+        MarketDataFeed::new(market.base.clone(), market.quote.clone(), market.book_owner)
+            .with_cursor(client.fetch_event_cursor().await?);
+
+    let mut terminal = setup_terminal()?;
+    let res = run_panel(
+        client,
+        market,
+        &mut terminal,
+        &mut panel,
+        &mut feed,
+        poll_interval,
+    )
+    .await;
+    restore_terminal(&mut terminal)?;
+    res
+}
+
+async fn run_panel(
+    client: &LaminarClient,
+    market: &Market,
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    panel: &mut BookPanel,
+    feed: &mut MarketDataFeed,
+    poll_interval: Duration,
+) -> Result<()> {
+    loop {
+        // node errors are shown and the poll repeated, the viewer is meant for incidents
+        match feed.poll(client).await {
+            Ok(updates) => {
+                panel.error = None;
+                for update in &updates {
+                    panel.apply_update(update);
+                }
+            }
+            Err(e) => panel.error = Some(e.to_string()),
+        }
+        match client
+            .fetch_orderbook(&market.base, &market.quote, &market.book_owner)
+            .await
+        {
+            Ok(book) => panel.apply_snapshot(&book),
+            Err(e) => panel.error = Some(e.to_string()),
+        }
+
+        terminal
+            .draw(|frame| panel.render(frame, frame.size()))
+            .context("failed drawing")?;
+
+        // waiting for input paces the polls
+        if event::poll(poll_interval).context("failed reading input")? {
+            if let Event::Key(key) = event::read().context("failed reading input")? {
+                if matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
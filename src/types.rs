@@ -1,6 +1,11 @@
 #[cfg(feature = "fuzzing")]
 pub mod arbitrary;
+pub mod coin;
+pub mod decimal;
+pub mod delta;
 pub mod events;
+pub mod l2;
+pub mod market;
 pub mod order;
 
 use serde::de::{Error, Visitor};
@@ -58,3 +63,18 @@ where
         Some(v) => serializer.serialize_str(&v.to_string()),
     }
 }
+
+/// Year, month and day of `days` since the unix epoch in the proleptic Gregorian calendar,
+/// see http://howardhinnant.github.io/date_algorithms.html
+pub(crate) fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
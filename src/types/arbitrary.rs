@@ -1,3 +1,4 @@
+use crate::types::decimal::RoundingPolicy;
 use crate::types::events::{AmendOrderEvent, FillEvent, PlaceOrderEvent};
 use crate::types::order::{Id, Instrument, Side, TimeInForce};
 use aptos_api_types::{Address, U64};
@@ -42,6 +43,7 @@ impl<'a> arbitrary::Arbitrary<'a> for Instrument {
             min_size_amount,
             base_decimals,
             quote_decimals,
+            rounding: RoundingPolicy::default(),
         })
     }
 }
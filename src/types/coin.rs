@@ -0,0 +1,74 @@
+use crate::types::decimal::{self, RoundingMode};
+use crate::types::deserialize_from_str;
+use serde::{Deserialize, Deserializer, Serialize};
+
+/// Metadata of a coin, read from its `0x1::coin::CoinInfo` resource.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct CoinInfo {
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+    /// Total supply in the smallest unit of the coin. `None` if the supply is not tracked, or
+    /// is kept in an aggregator, which cannot be read from the resource.
+    pub supply: Option<u128>,
+}
+
+impl CoinInfo {
+    /// Convert an amount in the smallest unit of the coin to a decimal amount.
+    pub fn to_decimal(&self, amount: u64) -> f64 {
+        decimal::from_units(amount, self.decimals)
+    }
+
+    /// Convert a decimal amount to the smallest unit of the coin, rounding down.
+    pub fn to_units(&self, amount: f64) -> crate::error::Result<u64> {
+        decimal::to_units(amount, self.decimals, RoundingMode::Floor)
+    }
+}
+
+// `Option<T>` is represented as a vector of at most one element in Move
+#[derive(Deserialize)]
+struct MoveOption<T> {
+    vec: Vec<T>,
+}
+
+#[derive(Deserialize)]
+struct Integer {
+    #[serde(deserialize_with = "deserialize_from_str")]
+    value: u128,
+}
+
+#[derive(Deserialize)]
+struct OptionalAggregator {
+    integer: MoveOption<Integer>,
+}
+
+#[derive(Deserialize)]
+struct RawCoinInfo {
+    name: String,
+    symbol: String,
+    decimals: u8,
+    supply: MoveOption<OptionalAggregator>,
+}
+
+impl<'de> Deserialize<'de> for CoinInfo {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = RawCoinInfo::deserialize(deserializer)?;
+        let supply = raw
+            .supply
+            .vec
+            .into_iter()
+            .next()
+            .and_then(|s| s.integer.vec.into_iter().next())
+            .map(|i| i.value);
+
+        Ok(Self {
+            name: raw.name,
+            symbol: raw.symbol,
+            decimals: raw.decimals,
+            supply,
+        })
+    }
+}
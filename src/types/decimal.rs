@@ -0,0 +1,268 @@
+use crate::error::{LaminarError, Result};
+use crate::types::order::Side;
+use serde::{Deserialize, Serialize};
+
+/// Rounding strategy used when a decimal value does not land exactly on a unit boundary.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub enum RoundingMode {
+    /// Round towards zero. Never overpays on a bid.
+    #[default]
+    Floor,
+    /// Round away from zero. Never undersells on an ask.
+    Ceil,
+    /// Round half away from zero.
+    HalfUp,
+    /// Round half to the nearest even unit (banker's rounding).
+    HalfEven,
+}
+
+/// Rounding modes applied to an instrument's conversions depending on intent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub struct RoundingPolicy {
+    pub bid: RoundingMode,
+    pub ask: RoundingMode,
+    pub report: RoundingMode,
+}
+
+impl Default for RoundingPolicy {
+    fn default() -> Self {
+        Self {
+            bid: RoundingMode::Floor,
+            ask: RoundingMode::Ceil,
+            report: RoundingMode::HalfEven,
+        }
+    }
+}
+
+impl RoundingPolicy {
+    /// Rounding mode to use for prices on the given side of the book.
+    pub fn for_side(&self, side: Side) -> RoundingMode {
+        match side {
+            Side::Bid => self.bid,
+            Side::Ask => self.ask,
+        }
+    }
+}
+
+fn pow10(decimals: u8) -> Result<u128> {
+    10u128
+        .checked_pow(decimals as u32)
+        .ok_or_else(|| LaminarError::InvalidInput(format!("decimals out of range: {}", decimals)))
+}
+
+fn round_up(units: u128, first: u8, rest_nonzero: bool, mode: RoundingMode) -> bool {
+    let any_remainder = first != 0 || rest_nonzero;
+    match mode {
+        RoundingMode::Floor => false,
+        RoundingMode::Ceil => any_remainder,
+        RoundingMode::HalfUp => first >= 5,
+        RoundingMode::HalfEven => first > 5 || (first == 5 && (rest_nonzero || units % 2 == 1)),
+    }
+}
+
+/// Convert a decimal value into integer units with `decimals` fractional digits.
+///
+/// The value is converted through its shortest round-trip string representation, so
+/// `1.1` scaled by two decimals yields exactly `110` regardless of float error.
+///
+/// # Arguments:
+///
+/// * `value` - Non-negative decimal value, e.g. a human readable price.
+/// * `decimals` - Number of fractional digits represented by one unit.
+/// * `mode` - `RoundingMode` applied to any digits beyond `decimals`.
+pub fn to_units(value: f64, decimals: u8, mode: RoundingMode) -> Result<u64> {
+    if !value.is_finite() || value < 0.0 {
+        return Err(LaminarError::InvalidInput(format!(
+            "value must be finite and non-negative: {}",
+            value
+        )));
+    }
+    decimal_str_to_units(&value.to_string(), decimals, mode)
+}
+
+/// Convert a decimal string such as `"12.345"` into integer units with `decimals`
+/// fractional digits. See [`to_units`].
+pub fn decimal_str_to_units(value: &str, decimals: u8, mode: RoundingMode) -> Result<u64> {
+    let (int_part, frac_part) = value.split_once('.').unwrap_or((value, ""));
+    if int_part.is_empty() && frac_part.is_empty()
+        || !int_part.chars().all(|c| c.is_ascii_digit())
+        || !frac_part.chars().all(|c| c.is_ascii_digit())
+    {
+        return Err(LaminarError::InvalidInput(format!(
+            "invalid decimal string: {}",
+            value
+        )));
+    }
+
+    let split = frac_part.len().min(decimals as usize);
+    let (kept, dropped) = frac_part.split_at(split);
+    let digits = format!("{}{:0<width$}", int_part, kept, width = decimals as usize);
+    let digits = digits.trim_start_matches('0');
+    let mut units = if digits.is_empty() {
+        0u128
+    } else {
+        digits
+            .parse::<u128>()
+            .map_err(|_| LaminarError::InvalidInput(format!("value out of range: {}", value)))?
+    };
+
+    let mut dropped = dropped.bytes().map(|b| b - b'0');
+    let first = dropped.next().unwrap_or(0);
+    let rest_nonzero = dropped.any(|d| d != 0);
+    if round_up(units, first, rest_nonzero, mode) {
+        units += 1;
+    }
+
+    u64::try_from(units)
+        .map_err(|_| LaminarError::InvalidInput(format!("value out of range: {}", value)))
+}
+
+/// Convert integer units back into a decimal value.
+pub fn from_units(units: u64, decimals: u8) -> f64 {
+    format_units(units, decimals)
+        .parse::<f64>()
+        .unwrap_or(f64::NAN)
+}
+
+/// Format integer units as an exact decimal string, e.g. `12345` with two decimals is `"123.45"`.
+pub fn format_units(units: u64, decimals: u8) -> String {
+    let s = format!("{:0>width$}", units, width = decimals as usize + 1);
+    let (int_part, frac_part) = s.split_at(s.len() - decimals as usize);
+    if frac_part.is_empty() {
+        int_part.to_string()
+    } else {
+        format!("{}.{}", int_part, frac_part)
+    }
+}
+
+/// Convert units expressed with `from_decimals` fractional digits into units with
+/// `to_decimals` fractional digits, rounding with `mode` when precision is lost.
+pub fn rescale_units(
+    units: u64,
+    from_decimals: u8,
+    to_decimals: u8,
+    mode: RoundingMode,
+) -> Result<u64> {
+    if to_decimals >= from_decimals {
+        let factor = pow10(to_decimals - from_decimals)?;
+        let res = (units as u128)
+            .checked_mul(factor)
+            .ok_or_else(|| LaminarError::InvalidInput(format!("value out of range: {}", units)))?;
+        return u64::try_from(res)
+            .map_err(|_| LaminarError::InvalidInput(format!("value out of range: {}", units)));
+    }
+
+    // any `u64` is below 10^20, so a larger factor leaves a quotient of 0 and the units as the
+    // remainder, below half the factor, exactly like 10^20 does
+    let factor = pow10((from_decimals - to_decimals).min(20))?;
+    let quotient = units as u128 / factor;
+    let remainder = units as u128 % factor;
+    let half = factor / 2;
+    let up = match mode {
+        RoundingMode::Floor => false,
+        RoundingMode::Ceil => remainder != 0,
+        RoundingMode::HalfUp => remainder >= half,
+        RoundingMode::HalfEven => remainder > half || (remainder == half && quotient % 2 == 1),
+    };
+
+    let res = if up { quotient + 1 } else { quotient };
+    u64::try_from(res)
+        .map_err(|_| LaminarError::InvalidInput(format!("value out of range: {}", units)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODES: [RoundingMode; 4] = [
+        RoundingMode::Floor,
+        RoundingMode::Ceil,
+        RoundingMode::HalfUp,
+        RoundingMode::HalfEven,
+    ];
+
+    #[test]
+    fn test_zero() {
+        for mode in MODES {
+            assert_eq!(to_units(0.0, 6, mode).unwrap(), 0);
+            assert_eq!(decimal_str_to_units("0.000", 2, mode).unwrap(), 0);
+            assert_eq!(rescale_units(0, 6, 2, mode).unwrap(), 0);
+        }
+        assert_eq!(format_units(0, 2), "0.00");
+        assert_eq!(format_units(0, 0), "0");
+    }
+
+    #[test]
+    fn test_exact_multiples_are_not_rounded() {
+        for mode in MODES {
+            assert_eq!(to_units(1.1, 2, mode).unwrap(), 110);
+            assert_eq!(decimal_str_to_units("12.34", 2, mode).unwrap(), 1234);
+            assert_eq!(decimal_str_to_units("12.3400", 2, mode).unwrap(), 1234);
+            assert_eq!(rescale_units(1_200, 4, 2, mode).unwrap(), 12);
+        }
+    }
+
+    #[test]
+    fn test_rounding_modes() {
+        let units = |value, mode| decimal_str_to_units(value, 2, mode).unwrap();
+        assert_eq!(units("1.005", RoundingMode::Floor), 100);
+        assert_eq!(units("1.001", RoundingMode::Ceil), 101);
+        assert_eq!(units("1.004", RoundingMode::HalfUp), 100);
+        assert_eq!(units("1.005", RoundingMode::HalfUp), 101);
+        // ties go to the even unit, anything past the tie rounds up
+        assert_eq!(units("1.005", RoundingMode::HalfEven), 100);
+        assert_eq!(units("1.015", RoundingMode::HalfEven), 102);
+        assert_eq!(units("1.0051", RoundingMode::HalfEven), 101);
+
+        let rescaled = |units, mode| rescale_units(units, 3, 1, mode).unwrap();
+        assert_eq!(rescaled(1_299, RoundingMode::Floor), 12);
+        assert_eq!(rescaled(1_201, RoundingMode::Ceil), 13);
+        assert_eq!(rescaled(1_250, RoundingMode::HalfUp), 13);
+        assert_eq!(rescaled(1_250, RoundingMode::HalfEven), 12);
+        assert_eq!(rescaled(1_350, RoundingMode::HalfEven), 14);
+    }
+
+    #[test]
+    fn test_overflow() {
+        let max = u64::MAX.to_string();
+        assert_eq!(
+            decimal_str_to_units(&max, 0, RoundingMode::Floor).unwrap(),
+            u64::MAX
+        );
+        assert!(decimal_str_to_units("18446744073709551616", 0, RoundingMode::Floor).is_err());
+        // rounding up past the largest unit
+        let above_max = format!("{}.1", max);
+        assert!(decimal_str_to_units(&above_max, 0, RoundingMode::Ceil).is_err());
+        assert!(to_units(1.0, 40, RoundingMode::Floor).is_err());
+
+        assert!(rescale_units(u64::MAX, 0, 1, RoundingMode::Floor).is_err());
+        assert!(rescale_units(1, 0, 39, RoundingMode::Floor).is_err());
+        // scaling down never overflows, it rounds to 0 or 1
+        for mode in MODES {
+            let ceil = (mode == RoundingMode::Ceil) as u64;
+            assert_eq!(rescale_units(u64::MAX, 60, 0, mode).unwrap(), ceil);
+            assert_eq!(rescale_units(1, 255, 0, mode).unwrap(), ceil);
+            assert_eq!(rescale_units(0, 255, 0, mode).unwrap(), 0);
+        }
+        assert_eq!(
+            rescale_units(5 * 10u64.pow(18), 19, 0, RoundingMode::HalfUp).unwrap(),
+            1
+        );
+        assert_eq!(
+            rescale_units(u64::MAX, 1, 0, RoundingMode::Ceil).unwrap(),
+            u64::MAX / 10 + 1
+        );
+    }
+
+    #[test]
+    fn test_invalid_values() {
+        for value in [-1.0, f64::NAN, f64::INFINITY] {
+            assert!(to_units(value, 2, RoundingMode::Floor).is_err());
+        }
+        for value in ["", ".", "1.2.3", "-1", "1e5"] {
+            assert!(decimal_str_to_units(value, 2, RoundingMode::Floor).is_err());
+        }
+    }
+}
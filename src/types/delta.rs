@@ -0,0 +1,159 @@
+use crate::types::order::{Id, Order, OrderBook, Side};
+use std::collections::{BTreeMap, HashMap};
+
+/// Change of the total remaining size at a price level between two snapshots.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LevelChange {
+    pub side: Side,
+    pub price: u64,
+    /// Size before the change, 0 if the level was added.
+    pub old_size: u64,
+    /// Size after the change, 0 if the level was removed.
+    pub new_size: u64,
+}
+
+/// Change of a single resting order between two snapshots.
+#[derive(Clone, Debug)]
+pub enum OrderChange {
+    Added(Order),
+    Removed(Order),
+    /// Order was amended or partially filled.
+    Changed {
+        old: Order,
+        new: Order,
+    },
+}
+
+impl OrderChange {
+    pub fn order(&self) -> &Order {
+        match self {
+            OrderChange::Added(o) | OrderChange::Removed(o) => o,
+            OrderChange::Changed { new, .. } => new,
+        }
+    }
+}
+
+/// Incremental update between two snapshots of the same `OrderBook`, see `OrderBook::diff`.
+#[derive(Clone, Debug)]
+pub struct BookDelta {
+    pub book_id: Id,
+    pub from_version: Option<u64>,
+    pub to_version: Option<u64>,
+    pub added_levels: Vec<LevelChange>,
+    pub removed_levels: Vec<LevelChange>,
+    pub changed_levels: Vec<LevelChange>,
+    pub orders: Vec<OrderChange>,
+}
+
+impl BookDelta {
+    pub fn is_empty(&self) -> bool {
+        self.added_levels.is_empty()
+            && self.removed_levels.is_empty()
+            && self.changed_levels.is_empty()
+            && self.orders.is_empty()
+    }
+
+    /// All level changes, ordered by side and price.
+    pub fn levels(&self) -> Vec<LevelChange> {
+        let mut levels = self
+            .added_levels
+            .iter()
+            .chain(&self.removed_levels)
+            .chain(&self.changed_levels)
+            .copied()
+            .collect::<Vec<_>>();
+        levels.sort_by_key(|l| (l.side as u8, l.price));
+        levels
+    }
+}
+
+fn level_sizes(levels: &BTreeMap<u64, Vec<Order>>) -> BTreeMap<u64, u64> {
+    levels
+        .iter()
+        .map(|(p, orders)| (*p, orders.iter().map(|o| o.remaining_size).sum::<u64>()))
+        .filter(|(_, size)| *size > 0)
+        .collect()
+}
+
+fn orders_by_id(book: &OrderBook) -> HashMap<&Id, &Order> {
+    book.bids
+        .values()
+        .chain(book.asks.values())
+        .flatten()
+        .map(|o| (&o.id, o))
+        .collect()
+}
+
+impl OrderBook {
+    /// Compute the changes that turn `older` into this book.
+    pub fn diff(&self, older: &OrderBook) -> BookDelta {
+        let mut delta = BookDelta {
+            book_id: self.id.clone(),
+            from_version: older.ledger_version,
+            to_version: self.ledger_version,
+            added_levels: vec![],
+            removed_levels: vec![],
+            changed_levels: vec![],
+            orders: vec![],
+        };
+
+        for (side, new, old) in [
+            (Side::Bid, &self.bids, &older.bids),
+            (Side::Ask, &self.asks, &older.asks),
+        ] {
+            let new = level_sizes(new);
+            let old = level_sizes(old);
+            for (&price, &new_size) in &new {
+                let change = |old_size| LevelChange {
+                    side,
+                    price,
+                    old_size,
+                    new_size,
+                };
+                match old.get(&price) {
+                    None => delta.added_levels.push(change(0)),
+                    Some(&old_size) if old_size != new_size => {
+                        delta.changed_levels.push(change(old_size))
+                    }
+                    Some(_) => {}
+                }
+            }
+            for (&price, &old_size) in &old {
+                if !new.contains_key(&price) {
+                    delta.removed_levels.push(LevelChange {
+                        side,
+                        price,
+                        old_size,
+                        new_size: 0,
+                    });
+                }
+            }
+        }
+
+        let new_orders = orders_by_id(self);
+        let old_orders = orders_by_id(older);
+        for (id, new) in &new_orders {
+            match old_orders.get(id) {
+                None => delta.orders.push(OrderChange::Added((*new).clone())),
+                Some(old)
+                    if old.price != new.price
+                        || old.size != new.size
+                        || old.remaining_size != new.remaining_size =>
+                {
+                    delta.orders.push(OrderChange::Changed {
+                        old: (*old).clone(),
+                        new: (*new).clone(),
+                    })
+                }
+                Some(_) => {}
+            }
+        }
+        for (id, old) in &old_orders {
+            if !new_orders.contains_key(id) {
+                delta.orders.push(OrderChange::Removed((*old).clone()));
+            }
+        }
+
+        delta
+    }
+}
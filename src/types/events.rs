@@ -1,4 +1,4 @@
-use crate::types::order::Id;
+use crate::types::order::{Id, State};
 use crate::types::{deserialize_from_str, u64_to_str};
 use crate::{Side, TimeInForce};
 use anyhow::Context;
@@ -7,13 +7,54 @@ use aptos_sdk::move_types::language_storage::{StructTag, TypeTag};
 use aptos_sdk::types::account_address::AccountAddress;
 use serde::de::{Error, MapAccess, Visitor};
 use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
 use std::fmt::Formatter;
 use std::str::FromStr;
 
-pub(crate) trait EventStoreField<'a> {
+/// Field of the `OrderBookStore` resource holding the event handle of an event type.
+pub trait EventStoreField<'a> {
     fn event_store_field() -> &'a str;
 }
 
+/// Identifies an event stream: the account holding the event handle and the handle's
+/// creation number.
+#[derive(Clone, Copy, Debug, Serialize, Eq, PartialEq, Hash)]
+pub struct EventKey {
+    pub account_address: AccountAddress,
+    pub creation_number: u64,
+}
+
+/// Event together with its on-chain metadata.
+///
+/// `(key, sequence_number)` uniquely identifies an event, and `next_cursor` is the sequence
+/// number to resume fetching from once the event has been processed.
+#[derive(Clone, Debug, Serialize)]
+pub struct LaminarEventEnvelope<E> {
+    pub key: EventKey,
+    pub sequence_number: u64,
+    pub version: u64,
+    pub event: E,
+}
+
+impl<E> LaminarEventEnvelope<E> {
+    pub fn next_cursor(&self) -> u64 {
+        self.sequence_number + 1
+    }
+
+    pub fn into_event(self) -> E {
+        self.event
+    }
+
+    pub fn map<F>(self, f: impl FnOnce(E) -> F) -> LaminarEventEnvelope<F> {
+        LaminarEventEnvelope {
+            key: self.key,
+            sequence_number: self.sequence_number,
+            version: self.version,
+            event: f(self.event),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Eq, PartialEq)]
 pub struct TypeInfo {
     pub account_address: AccountAddress,
@@ -251,7 +292,8 @@ pub struct CancelOrderEvent {
     pub order_id: Id,
     pub cancel_id: Id,
     pub side: Side,
-    // TODO change reason to enum
+    /// Reason code of the `book` module. Its values are not documented, use `order_state`
+    /// to tell a cancelled order from an expired one.
     pub reason: u8,
     #[serde(
         deserialize_with = "deserialize_from_str",
@@ -260,6 +302,22 @@ pub struct CancelOrderEvent {
     pub time: u64,
 }
 
+impl CancelOrderEvent {
+    /// Terminal `State` of the cancelled order. Only GTC orders rest on the book, so a
+    /// cancelled GTC order was cancelled by its owner and `Cancelled`, while the book cancels
+    /// the unfilled size of any other order, which is `Expired`.
+    ///
+    /// # Arguments:
+    ///
+    /// * `time_in_force` - Time in force of the order, from its `PlaceOrderEvent`.
+    pub fn order_state(&self, time_in_force: TimeInForce) -> State {
+        match time_in_force {
+            TimeInForce::GoodTillCanceled => State::Cancelled,
+            TimeInForce::ImmediateOrCancel | TimeInForce::FillOrKill => State::Expired,
+        }
+    }
+}
+
 impl<'a> EventStoreField<'a> for CancelOrderEvent {
     fn event_store_field() -> &'a str {
         "cancel_order_events"
@@ -310,12 +368,91 @@ impl<'a> EventStoreField<'a> for FillEvent {
     }
 }
 
-#[derive(Debug, Deserialize)]
-#[serde(untagged)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "type", content = "data")]
 pub enum LaminarEvent {
     CreateOrderBook(CreateOrderBookEvent),
     PlaceOrder(PlaceOrderEvent),
     AmendOrder(AmendOrderEvent),
     CancelOrder(CancelOrderEvent),
     FillEvent(FillEvent),
+    /// Event of a Laminar struct this version of the SDK does not know about.
+    Unknown {
+        type_name: String,
+        data: Value,
+    },
+}
+
+impl LaminarEvent {
+    /// Parse the data of an on-chain event based on its Move type,
+    /// e.g. `0x1::book::PlaceOrderEvent`.
+    pub fn from_move_event(event_type: &str, data: Value) -> serde_json::Result<Self> {
+        let struct_name = event_type.rsplit("::").next().unwrap_or(event_type);
+        let event = match struct_name {
+            "CreateOrderBookEvent" => Self::CreateOrderBook(serde_json::from_value(data)?),
+            "PlaceOrderEvent" => Self::PlaceOrder(serde_json::from_value(data)?),
+            "AmendOrderEvent" => Self::AmendOrder(serde_json::from_value(data)?),
+            "CancelOrderEvent" => Self::CancelOrder(serde_json::from_value(data)?),
+            "FillEvent" => Self::FillEvent(serde_json::from_value(data)?),
+            _ => Self::Unknown {
+                type_name: event_type.to_string(),
+                data,
+            },
+        };
+
+        Ok(event)
+    }
+
+    pub fn time(&self) -> u64 {
+        match self {
+            LaminarEvent::CreateOrderBook(e) => e.time,
+            LaminarEvent::PlaceOrder(e) => e.time,
+            LaminarEvent::AmendOrder(e) => e.time,
+            LaminarEvent::CancelOrder(e) => e.time,
+            LaminarEvent::FillEvent(e) => e.time,
+            LaminarEvent::Unknown { data, .. } => data
+                .get("time")
+                .and_then(|t| t.as_str())
+                .and_then(|t| t.parse().ok())
+                .unwrap_or_default(),
+        }
+    }
+
+    pub(crate) fn set_time(&mut self, time: u64) {
+        match self {
+            LaminarEvent::CreateOrderBook(e) => e.time = time,
+            LaminarEvent::PlaceOrder(e) => e.time = time,
+            LaminarEvent::AmendOrder(e) => e.time = time,
+            LaminarEvent::CancelOrder(e) => e.time = time,
+            LaminarEvent::FillEvent(e) => e.time = time,
+            LaminarEvent::Unknown { data, .. } => {
+                if let Some(t) = data.get_mut("time") {
+                    *t = Value::String(time.to_string());
+                }
+            }
+        }
+    }
+
+    /// Id of the book the event belongs to, `None` for unknown events.
+    pub fn book_id(&self) -> Option<&Id> {
+        match self {
+            LaminarEvent::CreateOrderBook(e) => Some(&e.book_id),
+            LaminarEvent::PlaceOrder(e) => Some(&e.book_id),
+            LaminarEvent::AmendOrder(e) => Some(&e.book_id),
+            LaminarEvent::CancelOrder(e) => Some(&e.book_id),
+            LaminarEvent::FillEvent(e) => Some(&e.book_id),
+            LaminarEvent::Unknown { .. } => None,
+        }
+    }
+
+    /// Id of the order the event belongs to, `None` for book creation and unknown events.
+    pub fn order_id(&self) -> Option<&Id> {
+        match self {
+            LaminarEvent::PlaceOrder(e) => Some(&e.order_id),
+            LaminarEvent::AmendOrder(e) => Some(&e.order_id),
+            LaminarEvent::CancelOrder(e) => Some(&e.order_id),
+            LaminarEvent::FillEvent(e) => Some(&e.order_id),
+            LaminarEvent::CreateOrderBook(_) | LaminarEvent::Unknown { .. } => None,
+        }
+    }
 }
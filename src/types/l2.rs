@@ -0,0 +1,96 @@
+use crate::types::order::{Id, Order, OrderBook, Side};
+use crate::types::{deserialize_from_str, u64_to_str};
+use serde::{Deserialize, Serialize};
+
+/// Aggregated price level of an `L2Book`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct L2Level {
+    #[serde(
+        deserialize_with = "deserialize_from_str",
+        serialize_with = "u64_to_str"
+    )]
+    pub price: u64,
+    /// Sum of the remaining sizes of the orders at this price.
+    #[serde(
+        deserialize_with = "deserialize_from_str",
+        serialize_with = "u64_to_str"
+    )]
+    pub total_size: u64,
+    pub order_count: u32,
+}
+
+impl L2Level {
+    fn from_orders(price: u64, orders: &[Order]) -> Self {
+        Self {
+            price,
+            total_size: orders.iter().map(|o| o.remaining_size).sum(),
+            order_count: orders.len() as u32,
+        }
+    }
+}
+
+/// Depth view of an `OrderBook` aggregated per price level.
+/// Levels are ordered best first: descending prices for bids, ascending for asks.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct L2Book {
+    pub book_id: Id,
+    pub bids: Vec<L2Level>,
+    pub asks: Vec<L2Level>,
+    /// Ledger version of the snapshot the view was built from, if known.
+    pub ledger_version: Option<u64>,
+}
+
+impl L2Book {
+    pub fn levels(&self, side: Side) -> &[L2Level] {
+        match side {
+            Side::Bid => &self.bids,
+            Side::Ask => &self.asks,
+        }
+    }
+
+    pub fn best_bid(&self) -> Option<&L2Level> {
+        self.bids.first()
+    }
+
+    pub fn best_ask(&self) -> Option<&L2Level> {
+        self.asks.first()
+    }
+
+    /// Difference between the best ask and best bid in price units.
+    pub fn spread(&self) -> Option<u64> {
+        Some(
+            self.best_ask()?
+                .price
+                .saturating_sub(self.best_bid()?.price),
+        )
+    }
+}
+
+impl OrderBook {
+    /// Aggregate the book into at most `depth` levels per side.
+    /// Levels without any remaining size are skipped.
+    pub fn to_l2(&self, depth: usize) -> L2Book {
+        let bids = self
+            .bids
+            .iter()
+            .rev()
+            .map(|(p, orders)| L2Level::from_orders(*p, orders))
+            .filter(|l| l.total_size > 0)
+            .take(depth)
+            .collect();
+        let asks = self
+            .asks
+            .iter()
+            .map(|(p, orders)| L2Level::from_orders(*p, orders))
+            .filter(|l| l.total_size > 0)
+            .take(depth)
+            .collect();
+
+        L2Book {
+            book_id: self.id.clone(),
+            bids,
+            asks,
+            ledger_version: self.ledger_version,
+        }
+    }
+}
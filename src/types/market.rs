@@ -0,0 +1,33 @@
+use aptos_sdk::move_types::language_storage::TypeTag;
+use aptos_sdk::types::account_address::AccountAddress;
+use serde::{Deserialize, Serialize};
+
+/// Identifies an `OrderBook` by its coin pair and owner.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Market {
+    pub base: TypeTag,
+    pub quote: TypeTag,
+    pub book_owner: AccountAddress,
+}
+
+impl Market {
+    pub fn new(base: TypeTag, quote: TypeTag, book_owner: AccountAddress) -> Self {
+        Self {
+            base,
+            quote,
+            book_owner,
+        }
+    }
+}
+
+impl std::fmt::Display for Market {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}/{}@{}",
+            self.base,
+            self.quote,
+            self.book_owner.to_hex_literal()
+        )
+    }
+}
@@ -1,24 +1,65 @@
+use crate::types::decimal::{self, RoundingMode, RoundingPolicy};
 use crate::types::deserialize_from_str;
 use crate::types::events::FillEvent;
+use anyhow::anyhow;
 #[cfg(feature = "db")]
-use anyhow::{anyhow, Context};
+use anyhow::Context;
 use aptos_api_types::{Address, U64};
 use aptos_sdk::move_types::language_storage::TypeTag;
 use aptos_sdk::types::account_address::AccountAddress;
 use serde::de::{Error, MapAccess, Visitor};
 use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use std::borrow::Cow;
 use std::collections::{BTreeMap, HashSet};
 use std::fmt::Formatter;
-use std::num::ParseIntError;
 #[cfg(feature = "db")]
 use std::str::FromStr;
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
 pub struct Id {
     pub creation_num: U64,
     pub addr: Address,
 }
 
+impl<'de> Deserialize<'de> for Id {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        // ids are in every order and event, their strings are borrowed from the input when
+        // it allows instead of being copied before parsing
+        #[derive(Deserialize)]
+        struct RawId<'a> {
+            #[serde(borrow)]
+            creation_num: Cow<'a, str>,
+            #[serde(borrow)]
+            addr: Cow<'a, str>,
+        }
+
+        let raw = RawId::deserialize(deserializer)?;
+        let creation_num = raw
+            .creation_num
+            .parse::<u64>()
+            .map_err(|_| Error::custom("failed parsing string as u64"))?;
+        let addr = raw
+            .addr
+            .parse::<Address>()
+            .map_err(|_| Error::custom("failed parsing address"))?;
+        Ok(Self {
+            creation_num: U64::from(creation_num),
+            addr,
+        })
+    }
+}
+
+impl std::hash::Hash for Id {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.addr.inner().hash(state);
+        self.creation_num.0.hash(state);
+    }
+}
+
 impl std::fmt::Display for Id {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let s = format!(
@@ -43,7 +84,7 @@ impl FromStr for Id {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
 #[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 #[repr(u8)]
 pub enum Side {
@@ -93,6 +134,12 @@ impl<'de> Deserialize<'de> for Side {
             where
                 E: Error,
             {
+                // variant names are what the derived `Serialize` writes
+                match v {
+                    "Bid" => return Ok(Side::Bid),
+                    "Ask" => return Ok(Side::Ask),
+                    _ => {}
+                }
                 let number = v
                     .parse::<u64>()
                     .map_err(|e| E::custom(format!("{:?} is an invalid OrderSide string", e)))?;
@@ -168,14 +215,57 @@ impl<'de> Deserialize<'de> for TimeInForce {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Default)]
+/// Lifecycle of an order. `Filled`, `Cancelled`, `Expired` and `Rejected` are terminal.
+///
+/// The discriminants of the states read from chain events, `Open` to `Expired`, grow along
+/// the lifecycle, so stores can keep the greater of two of them. `Rejected` and
+/// `PendingSubmit` are local states outside that order. `Filled` keeps the discriminant of
+/// the former `Closed` state.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Default)]
 #[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 #[repr(u8)]
 pub enum State {
     #[default]
     Open = 0,
     PartiallyFilled = 1,
-    Closed = 2,
+    /// The whole size was filled.
+    Filled = 2,
+    /// Cancelled by its owner, possibly after partial fills.
+    Cancelled = 3,
+    /// The unfilled size was cancelled by the book, e.g. of an IOC or market order.
+    Expired = 4,
+    /// The transaction placing the order failed, the order never reached the book.
+    Rejected = 5,
+    /// Submitted and not yet seen on chain.
+    PendingSubmit = 6,
+}
+
+impl State {
+    fn from_u64(value: u64) -> Option<Self> {
+        match value {
+            0 => Some(Self::Open),
+            1 => Some(Self::PartiallyFilled),
+            2 => Some(Self::Filled),
+            3 => Some(Self::Cancelled),
+            4 => Some(Self::Expired),
+            5 => Some(Self::Rejected),
+            6 => Some(Self::PendingSubmit),
+            _ => None,
+        }
+    }
+
+    /// Whether the order can't change anymore.
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            Self::Filled | Self::Cancelled | Self::Expired | Self::Rejected
+        )
+    }
+
+    /// Whether the order is resting on the book.
+    pub fn is_open(&self) -> bool {
+        matches!(self, Self::Open | Self::PartiallyFilled)
+    }
 }
 
 #[cfg(feature = "db")]
@@ -183,12 +273,10 @@ impl TryFrom<i16> for State {
     type Error = anyhow::Error;
 
     fn try_from(value: i16) -> Result<Self, Self::Error> {
-        match value {
-            0 => Ok(Self::Open),
-            1 => Ok(Self::PartiallyFilled),
-            2 => Ok(Self::Closed),
-            _ => Err(anyhow!("failed parsing state: {:?}", value)),
-        }
+        u64::try_from(value)
+            .ok()
+            .and_then(Self::from_u64)
+            .ok_or_else(|| anyhow!("failed parsing state: {:?}", value))
     }
 }
 
@@ -203,19 +291,14 @@ impl<'de> Deserialize<'de> for State {
             type Value = State;
 
             fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
-                formatter.write_str("Open=0, PartiallyFilled=1 or Closed=2")
+                formatter.write_str("a State discriminant from 0 to 6")
             }
 
             fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
             where
                 E: Error,
             {
-                match v {
-                    0 => Ok(State::Open),
-                    1 => Ok(State::PartiallyFilled),
-                    2 => Ok(State::Closed),
-                    _ => Err(E::custom("GTC=0 or IOC=1 or FOK=2")),
-                }
+                State::from_u64(v).ok_or_else(|| E::custom("state must be from 0 to 6"))
             }
 
             fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
@@ -224,7 +307,7 @@ impl<'de> Deserialize<'de> for State {
             {
                 let number = v
                     .parse::<u64>()
-                    .map_err(|e| E::custom(format!("{:?} is an invalid TimeInForce string", e)))?;
+                    .map_err(|e| E::custom(format!("{:?} is an invalid State string", e)))?;
                 self.visit_u64(number)
             }
         }
@@ -233,6 +316,17 @@ impl<'de> Deserialize<'de> for State {
     }
 }
 
+/// How `Instrument::checked_price_units` and `checked_size_units` handle decimal values that
+/// do not land on a price or size tick.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum TickPolicy {
+    /// Return an error describing the offending value.
+    #[default]
+    Reject,
+    /// Round prices with the instrument's `RoundingPolicy` and sizes down.
+    Round,
+}
+
 #[derive(Clone, Debug, Deserialize)]
 pub struct Instrument {
     pub owner: AccountAddress,
@@ -242,6 +336,97 @@ pub struct Instrument {
     pub min_size_amount: u64,
     pub base_decimals: u8,
     pub quote_decimals: u8,
+    #[serde(skip)]
+    pub rounding: RoundingPolicy,
+}
+
+impl Instrument {
+    /// Convert a decimal price into price units using the given `RoundingMode`.
+    pub fn price_to_units(&self, price: f64, mode: RoundingMode) -> crate::error::Result<u64> {
+        decimal::to_units(price, self.price_decimals, mode)
+    }
+
+    /// Convert a decimal price into price units, rounding according to this instrument's
+    /// `RoundingPolicy` for `side`.
+    pub fn side_price_to_units(&self, price: f64, side: Side) -> crate::error::Result<u64> {
+        self.price_to_units(price, self.rounding.for_side(side))
+    }
+
+    /// Convert a decimal price into price units using this instrument's reporting rounding.
+    pub fn report_price_to_units(&self, price: f64) -> crate::error::Result<u64> {
+        self.price_to_units(price, self.rounding.report)
+    }
+
+    /// Convert a decimal size into size units using the given `RoundingMode`.
+    pub fn size_to_units(&self, size: f64, mode: RoundingMode) -> crate::error::Result<u64> {
+        decimal::to_units(size, self.size_decimals, mode)
+    }
+
+    /// Convert a decimal price into price units, checking it is a whole number of ticks of
+    /// `price_decimals` and not zero.
+    pub fn checked_price_units(
+        &self,
+        price: f64,
+        side: Side,
+        policy: TickPolicy,
+    ) -> anyhow::Result<u64> {
+        let units = match policy {
+            TickPolicy::Reject => {
+                let units = self.price_to_units(price, RoundingMode::Floor)?;
+                if units != self.price_to_units(price, RoundingMode::Ceil)? {
+                    return Err(anyhow!(
+                        "price {} is not a multiple of the price tick {}",
+                        price,
+                        decimal::format_units(1, self.price_decimals)
+                    ));
+                }
+                units
+            }
+            TickPolicy::Round => self.side_price_to_units(price, side)?,
+        };
+        if units == 0 {
+            return Err(anyhow!("price {} is below the price tick", price));
+        }
+
+        Ok(units)
+    }
+
+    /// Convert a decimal size into size units, checking it is a whole number of ticks of
+    /// `size_decimals` and at least `min_size_amount`.
+    pub fn checked_size_units(&self, size: f64, policy: TickPolicy) -> anyhow::Result<u64> {
+        let units = self.size_to_units(size, RoundingMode::Floor)?;
+        if policy == TickPolicy::Reject && units != self.size_to_units(size, RoundingMode::Ceil)? {
+            return Err(anyhow!(
+                "size {} is not a multiple of the size tick {}",
+                size,
+                decimal::format_units(1, self.size_decimals)
+            ));
+        }
+        self.validate_size(units)?;
+
+        Ok(units)
+    }
+
+    /// Check a size in units against `min_size_amount`.
+    pub fn validate_size(&self, size: u64) -> anyhow::Result<()> {
+        if size < self.min_size_amount {
+            return Err(anyhow!(
+                "size {} is below the minimum size {}",
+                decimal::format_units(size, self.size_decimals),
+                decimal::format_units(self.min_size_amount, self.size_decimals)
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub fn price_from_units(&self, price: u64) -> f64 {
+        decimal::from_units(price, self.price_decimals)
+    }
+
+    pub fn size_from_units(&self, size: u64) -> f64 {
+        decimal::from_units(size, self.size_decimals)
+    }
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -262,12 +447,16 @@ pub struct Order {
     pub fills: Vec<FillEvent>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, Copy)]
 struct GuardedIdx {
     #[serde(deserialize_with = "deserialize_from_str")]
     value: u64,
 }
 
+/// A `u64` sent as a string, read without allocating.
+#[derive(Deserialize)]
+struct StrU64(#[serde(deserialize_with = "deserialize_from_str")] u64);
+
 #[derive(Debug, Deserialize, Clone)]
 struct OrderOption {
     vec: Vec<Order>,
@@ -285,6 +474,29 @@ struct OrderQueue {
     nodes: Vec<OrderNode>,
 }
 
+impl OrderQueue {
+    /// Orders of the queue, following the links from its head. Orders are moved out of their
+    /// node, a node linked twice fails instead of being read again.
+    fn into_orders(mut self) -> Result<Vec<Order>, &'static str> {
+        let mut orders = Vec::with_capacity(self.nodes.len());
+        let mut current = self.head.value;
+        while current != u64::MAX {
+            let node = self
+                .nodes
+                .get_mut(current as usize)
+                .ok_or("failed finding order in nodes")?;
+            current = node.next.value;
+            let o = node
+                .value
+                .vec
+                .pop()
+                .ok_or("failed fetching order out of option")?;
+            orders.push(o);
+        }
+        Ok(orders)
+    }
+}
+
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 struct OrderPriceLevel {
@@ -328,30 +540,14 @@ impl<'de> Deserialize<'de> for OrderPriceLevel {
                             if price.is_some() {
                                 return Err(Error::duplicate_field("key"));
                             }
-                            let price_str = map.next_value::<String>()?;
-                            let price_num = price_str
-                                .parse::<u64>()
-                                .map_err(|_| Error::custom("failed parsing string as u64"))?;
-                            price = Some(price_num)
+                            price = Some(map.next_value::<StrU64>()?.0)
                         }
                         Field::Value => {
                             if orders.is_some() {
                                 return Err(Error::duplicate_field("value"));
                             }
                             let res = map.next_value::<OrderQueue>()?;
-                            let mut order_queue = vec![];
-                            let mut current = res.head;
-                            while current.value != u64::MAX {
-                                let o = res.nodes.get(current.value as usize).ok_or_else(|| {
-                                    Error::custom("failed finding order in nodes")
-                                })?;
-                                current = o.next.clone();
-                                let o = o.value.vec.get(0).ok_or_else(|| {
-                                    Error::custom("failed fetching order out of option")
-                                })?;
-                                order_queue.push(o.clone());
-                            }
-                            orders = Some(order_queue);
+                            orders = Some(res.into_orders().map_err(Error::custom)?);
                         }
                         Field::Left | Field::Right => {}
                     }
@@ -375,6 +571,15 @@ struct OrderBookSide {
     levels: Vec<OrderPriceLevel>,
 }
 
+impl OrderBookSide {
+    fn into_levels(self) -> BTreeMap<u64, Vec<Order>> {
+        self.levels
+            .into_iter()
+            .map(|level| (level.price, level.orders))
+            .collect()
+    }
+}
+
 impl<'de> Deserialize<'de> for OrderBookSide {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -419,26 +624,21 @@ impl<'de> Deserialize<'de> for OrderBookSide {
                             if removed_nodes.is_some() {
                                 return Err(Error::duplicate_field("removed_nodes"));
                             }
-                            let res = map
-                                .next_value::<Vec<String>>()?
-                                .into_iter()
-                                .map(|s| s.parse::<usize>())
-                                .collect::<Result<HashSet<usize>, ParseIntError>>()
-                                .map_err(|_| Error::custom("failed parsing string as usize"))?;
-                            removed_nodes = Some(res);
+                            let res = map.next_value::<Vec<StrU64>>()?;
+                            removed_nodes = Some(res.into_iter().map(|i| i.0 as usize).collect());
                         }
                         Field::Max | Field::Min | Field::Root | Field::SingleSplay => {}
                     }
                 }
 
                 let nodes = nodes.ok_or_else(|| Error::missing_field("nodes"))?;
-                let removed_nodes =
+                let removed_nodes: HashSet<usize> =
                     removed_nodes.ok_or_else(|| Error::missing_field("removed_nodes"))?;
                 let nodes = nodes
-                    .iter()
+                    .into_iter()
                     .enumerate()
                     .filter(|(i, _)| !removed_nodes.contains(i))
-                    .map(|(_, n)| n.clone())
+                    .map(|(_, n)| n)
                     .collect();
                 Ok(OrderBookSide { levels: nodes })
             }
@@ -456,6 +656,147 @@ pub struct OrderBook {
     pub bids: BTreeMap<u64, Vec<Order>>,
     pub asks: BTreeMap<u64, Vec<Order>>,
     pub type_tags: Vec<TypeTag>,
+    /// Ledger version the book was read at, if known.
+    pub ledger_version: Option<u64>,
+}
+
+impl OrderBook {
+    /// Worst acceptable price for an order on `side` taking liquidity at most `max_slippage_bps`
+    /// away from the best opposite price. Bids round down and asks round up so the limit never
+    /// exceeds the tolerance. `None` if the opposite side is empty.
+    pub fn slippage_limit_price(&self, side: Side, max_slippage_bps: u64) -> Option<u64> {
+        let bps = max_slippage_bps as u128;
+        match side {
+            Side::Bid => {
+                let best_ask = *self.asks.keys().next()? as u128;
+                let limit = best_ask * (10_000 + bps) / 10_000;
+                Some(limit.min(u64::MAX as u128) as u64)
+            }
+            Side::Ask => {
+                let best_bid = *self.bids.keys().next_back()? as u128;
+                let limit = (best_bid * 10_000u128.saturating_sub(bps) + 9_999) / 10_000;
+                Some(limit as u64)
+            }
+        }
+    }
+
+    /// Resting orders placed by `owner`, with decimal prices and sizes.
+    pub fn open_orders_of(&self, owner: &AccountAddress) -> Vec<OpenOrder> {
+        self.bids
+            .values()
+            .chain(self.asks.values())
+            .flatten()
+            .filter(|o| o.id.addr.inner() == owner)
+            .map(|o| {
+                let mut order = o.clone();
+                if order.remaining_size < order.size {
+                    order.state = State::PartiallyFilled;
+                }
+                OpenOrder {
+                    price: self.instrument.price_from_units(order.price),
+                    size: self.instrument.size_from_units(order.size),
+                    remaining_size: self.instrument.size_from_units(order.remaining_size),
+                    order,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Resting order with its price and sizes converted to decimals using the book's `Instrument`.
+#[derive(Clone, Debug)]
+pub struct OpenOrder {
+    pub order: Order,
+    pub price: f64,
+    pub size: f64,
+    pub remaining_size: f64,
+}
+
+fn field<'a>(value: &'a Value, name: &'static str) -> serde_json::Result<&'a Value> {
+    value.get(name).ok_or_else(|| Error::missing_field(name))
+}
+
+/// Splay tree holding the levels of `side` in the resource of a book side.
+pub(crate) fn side_tree(data: &Value, side: Side) -> serde_json::Result<&Value> {
+    match side {
+        Side::Bid => field(data, "bids"),
+        Side::Ask => field(data, "asks"),
+    }
+}
+
+/// Indices of the freed nodes of a splay tree, which no longer hold a level.
+pub(crate) fn removed_nodes(tree: &Value) -> serde_json::Result<HashSet<usize>> {
+    let removed = Vec::<StrU64>::deserialize(field(tree, "removed_nodes")?)?;
+    Ok(removed.into_iter().map(|i| i.0 as usize).collect())
+}
+
+impl OrderBook {
+    /// Parse the resource of a single side of a book, e.g. `OrderBookAsks`, keeping only its
+    /// best `depth` levels with resting orders. Only the prices of the other levels are read,
+    /// their order queues are skipped.
+    pub(crate) fn from_side_resource(
+        data: &Value,
+        side: Side,
+        depth: usize,
+    ) -> serde_json::Result<Self> {
+        let nodes = field(side_tree(data, side)?, "nodes")?
+            .as_array()
+            .ok_or_else(|| Error::custom("nodes is not an array"))?;
+        Self::from_side_nodes(data, side, nodes.iter().enumerate(), Some(depth))
+    }
+
+    /// Build a single side of a book from the splay tree nodes of its levels, given with their
+    /// index in the tree, and the resource of the side for the book's id and instrument.
+    /// Levels without resting orders are dropped.
+    pub(crate) fn from_side_nodes<'a>(
+        data: &Value,
+        side: Side,
+        nodes: impl IntoIterator<Item = (usize, &'a Value)>,
+        depth: Option<usize>,
+    ) -> serde_json::Result<Self> {
+        let id = Id::deserialize(field(data, "id")?)?;
+        let instrument = Instrument::deserialize(field(data, "instrument")?)?;
+        let removed_nodes = removed_nodes(side_tree(data, side)?)?;
+
+        let mut prices = vec![];
+        for (i, node) in nodes {
+            if removed_nodes.contains(&i) {
+                continue;
+            }
+            let price = StrU64::deserialize(field(node, "key")?)?.0;
+            prices.push((price, node));
+        }
+        match side {
+            Side::Bid => prices.sort_unstable_by(|a, b| b.0.cmp(&a.0)),
+            Side::Ask => prices.sort_unstable_by_key(|(price, _)| *price),
+        }
+
+        let mut levels = BTreeMap::new();
+        for (price, node) in prices {
+            if depth.map_or(false, |depth| levels.len() == depth) {
+                break;
+            }
+            let orders = OrderQueue::deserialize(field(node, "value")?)?
+                .into_orders()
+                .map_err(Error::custom)?;
+            if !orders.is_empty() {
+                levels.insert(price, orders);
+            }
+        }
+
+        let (bids, asks) = match side {
+            Side::Bid => (levels, BTreeMap::new()),
+            Side::Ask => (BTreeMap::new(), levels),
+        };
+        Ok(OrderBook {
+            id,
+            instrument,
+            bids,
+            asks,
+            type_tags: vec![],
+            ledger_version: None,
+        })
+    }
 }
 
 impl<'de> Deserialize<'de> for OrderBook {
@@ -512,11 +853,7 @@ impl<'de> Deserialize<'de> for OrderBook {
                             }
 
                             let book_side = map.next_value::<OrderBookSide>()?;
-                            let mut res = BTreeMap::<u64, Vec<Order>>::new();
-                            for level in &book_side.levels {
-                                res.insert(level.price, level.orders.clone());
-                            }
-                            bids = Some(res);
+                            bids = Some(book_side.into_levels());
                         }
                         Field::Asks => {
                             if asks.is_some() {
@@ -524,11 +861,7 @@ impl<'de> Deserialize<'de> for OrderBook {
                             }
 
                             let book_side = map.next_value::<OrderBookSide>()?;
-                            let mut res = BTreeMap::<u64, Vec<Order>>::new();
-                            for level in &book_side.levels {
-                                res.insert(level.price, level.orders.clone());
-                            }
-                            asks = Some(res);
+                            asks = Some(book_side.into_levels());
                         }
                         Field::Orders | Field::SignerAddr => {}
                     }
@@ -548,6 +881,7 @@ impl<'de> Deserialize<'de> for OrderBook {
                     bids,
                     asks,
                     type_tags: vec![],
+                    ledger_version: None,
                 })
             }
         }
@@ -559,6 +893,58 @@ impl<'de> Deserialize<'de> for OrderBook {
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+
+    fn instrument() -> Instrument {
+        Instrument {
+            owner: AccountAddress::ONE,
+            price_decimals: 2,
+            size_decimals: 3,
+            min_size_amount: 10,
+            base_decimals: 8,
+            quote_decimals: 6,
+            rounding: RoundingPolicy::default(),
+        }
+    }
+
+    #[test]
+    fn test_checked_price_units() {
+        let inst = instrument();
+        for policy in [TickPolicy::Reject, TickPolicy::Round] {
+            assert_eq!(
+                inst.checked_price_units(1.23, Side::Bid, policy).unwrap(),
+                123
+            );
+            assert!(inst.checked_price_units(0.0, Side::Ask, policy).is_err());
+        }
+
+        assert!(inst
+            .checked_price_units(1.234, Side::Bid, TickPolicy::Reject)
+            .is_err());
+        // bids round down and asks up, so neither gets a worse price than asked for
+        let round = |price, side| inst.checked_price_units(price, side, TickPolicy::Round);
+        assert_eq!(round(1.234, Side::Bid).unwrap(), 123);
+        assert_eq!(round(1.234, Side::Ask).unwrap(), 124);
+        assert_eq!(round(0.004, Side::Ask).unwrap(), 1);
+        assert!(round(0.004, Side::Bid).is_err());
+    }
+
+    #[test]
+    fn test_checked_size_units() {
+        let inst = instrument();
+        for policy in [TickPolicy::Reject, TickPolicy::Round] {
+            assert_eq!(inst.checked_size_units(0.01, policy).unwrap(), 10);
+            assert!(inst.checked_size_units(0.0, policy).is_err());
+            assert!(inst.checked_size_units(0.009, policy).is_err());
+        }
+
+        assert!(inst.checked_size_units(0.0105, TickPolicy::Reject).is_err());
+        assert_eq!(
+            inst.checked_size_units(0.0105, TickPolicy::Round).unwrap(),
+            10
+        );
+    }
+
     // use super::*;
     //
     // #[test]
@@ -0,0 +1,382 @@
+//! Responses of the TradingView UDF datafeed protocol, to back a charting library with
+//! Laminar books.
+//!
+//! Each method builds the JSON body of one UDF endpoint: `config` for `/config`, `symbol_info`
+//! for `/symbols`, `search` for `/search`, `history` for `/history` and `marks` for `/marks`.
+//! Serving them over HTTP is left to the application. Symbols are the pair symbols of the
+//! `MarketRegistry` and times are in seconds, as UDF expects.
+
+use crate::candles::{Candle, CandleBuilder, Interval};
+use crate::error::{LaminarError, Result};
+use crate::query::EventQuery;
+use crate::registry::{MarketEntry, MarketRegistry};
+use crate::types::decimal;
+use crate::types::events::FillEvent;
+use crate::types::order::Side;
+use crate::LaminarClient;
+use aptos_sdk::types::account_address::AccountAddress;
+use serde::Serialize;
+
+const MICROS_PER_SECOND: u64 = 1_000_000;
+
+/// Body of `/config`.
+#[derive(Clone, Debug, Serialize)]
+pub struct UdfConfig {
+    pub supported_resolutions: Vec<String>,
+    pub supports_group_request: bool,
+    pub supports_marks: bool,
+    pub supports_search: bool,
+    pub supports_timescale_marks: bool,
+    pub supports_time: bool,
+}
+
+/// Body of `/symbols`.
+#[derive(Clone, Debug, Serialize)]
+pub struct SymbolInfo {
+    pub name: String,
+    pub ticker: String,
+    pub description: String,
+    #[serde(rename = "type")]
+    pub symbol_type: String,
+    pub session: String,
+    pub exchange: String,
+    pub listed_exchange: String,
+    pub timezone: String,
+    pub format: String,
+    pub minmov: u64,
+    /// `10^price_decimals`, so the price tick is `minmov / pricescale`.
+    pub pricescale: u64,
+    pub has_intraday: bool,
+    pub has_seconds: bool,
+    pub has_daily: bool,
+    pub volume_precision: u8,
+    pub supported_resolutions: Vec<String>,
+    pub data_status: String,
+}
+
+/// Entry of the body of `/search`.
+#[derive(Clone, Debug, Serialize)]
+pub struct SearchResult {
+    pub symbol: String,
+    pub full_name: String,
+    pub description: String,
+    pub exchange: String,
+    pub ticker: String,
+    #[serde(rename = "type")]
+    pub symbol_type: String,
+}
+
+/// Body of `/history`, bars as columns with decimal prices and volumes.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "s", rename_all = "snake_case")]
+pub enum History {
+    Ok {
+        t: Vec<u64>,
+        o: Vec<f64>,
+        h: Vec<f64>,
+        l: Vec<f64>,
+        c: Vec<f64>,
+        v: Vec<f64>,
+    },
+    /// No bars in the requested range, `next_time` is the time of the closest earlier bar.
+    NoData {
+        #[serde(rename = "nextTime", skip_serializing_if = "Option::is_none")]
+        next_time: Option<u64>,
+    },
+}
+
+/// Entry of the body of `/marks`, a fill of an account shown on its bar.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Mark {
+    pub id: String,
+    pub time: u64,
+    pub color: String,
+    pub text: String,
+    pub label: String,
+    pub label_font_color: String,
+    pub min_size: u32,
+}
+
+/// Convert a UDF resolution into a candle `Interval`.
+///
+/// Minutes are written as a bare number, e.g. `5`, seconds with an `S` suffix, e.g. `1S`, and
+/// days with a `D` suffix, e.g. `1D` or `D`.
+pub fn resolution_interval(resolution: &str) -> Result<Interval> {
+    let invalid = || LaminarError::InvalidInput(format!("unsupported resolution {}", resolution));
+    let (count, unit) = match resolution.char_indices().last() {
+        Some((i, c)) if c.is_ascii_alphabetic() => (&resolution[..i], Some(c)),
+        _ => (resolution, None),
+    };
+    let count = if count.is_empty() {
+        1
+    } else {
+        count.parse::<u64>().map_err(|_| invalid())?
+    };
+    let seconds = match unit {
+        None => 60,
+        Some('S') => 1,
+        Some('D') => 86_400,
+        _ => return Err(invalid()),
+    };
+    let seconds = count
+        .checked_mul(seconds)
+        .filter(|s| *s > 0 && *s <= u64::MAX / MICROS_PER_SECOND)
+        .ok_or_else(invalid)?;
+
+    Ok(match seconds {
+        1 => Interval::OneSecond,
+        60 => Interval::OneMinute,
+        300 => Interval::FiveMinutes,
+        3_600 => Interval::OneHour,
+        seconds => Interval::Custom(seconds * MICROS_PER_SECOND),
+    })
+}
+
+/// Builds UDF responses for the books of a `MarketRegistry`.
+#[derive(Clone, Debug)]
+pub struct UdfDatafeed {
+    registry: MarketRegistry,
+    exchange: String,
+    resolutions: Vec<String>,
+}
+
+impl UdfDatafeed {
+    pub fn new(registry: MarketRegistry) -> Self {
+        Self {
+            registry,
+            exchange: "Laminar".to_string(),
+            resolutions: ["1S", "1", "5", "15", "60", "240", "1D"]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+        }
+    }
+
+    /// Exchange name shown in the chart, `Laminar` by default.
+    pub fn with_exchange(mut self, exchange: impl Into<String>) -> Self {
+        self.exchange = exchange.into();
+        self
+    }
+
+    /// Resolutions offered to the chart, see `resolution_interval` for the accepted forms.
+    pub fn with_resolutions(mut self, resolutions: Vec<String>) -> Result<Self> {
+        for resolution in &resolutions {
+            resolution_interval(resolution)?;
+        }
+        self.resolutions = resolutions;
+        Ok(self)
+    }
+
+    pub fn registry(&self) -> &MarketRegistry {
+        &self.registry
+    }
+
+    fn market(&self, symbol: &str) -> Result<&MarketEntry> {
+        self.registry
+            .get(symbol)
+            .ok_or_else(|| LaminarError::BookNotFound(symbol.to_string()))
+    }
+
+    pub fn config(&self) -> UdfConfig {
+        UdfConfig {
+            supported_resolutions: self.resolutions.clone(),
+            supports_group_request: false,
+            supports_marks: true,
+            supports_search: true,
+            supports_timescale_marks: false,
+            supports_time: false,
+        }
+    }
+
+    pub fn symbol_info(&self, symbol: &str) -> Result<SymbolInfo> {
+        let entry = self.market(symbol)?;
+        let has_seconds = self.resolutions.iter().any(|r| r.ends_with('S'));
+        let has_daily = self.resolutions.iter().any(|r| r.ends_with('D'));
+
+        Ok(SymbolInfo {
+            name: entry.symbol.clone(),
+            ticker: entry.symbol.clone(),
+            description: description(entry),
+            symbol_type: "crypto".to_string(),
+            session: "24x7".to_string(),
+            exchange: self.exchange.clone(),
+            listed_exchange: self.exchange.clone(),
+            timezone: "Etc/UTC".to_string(),
+            format: "price".to_string(),
+            minmov: 1,
+            pricescale: 10u64.saturating_pow(entry.book.price_decimals as u32),
+            has_intraday: true,
+            has_seconds,
+            has_daily,
+            volume_precision: entry.book.size_decimals,
+            supported_resolutions: self.resolutions.clone(),
+            data_status: "streaming".to_string(),
+        })
+    }
+
+    /// Symbols containing `query`, case insensitive, at most `limit`.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<SearchResult> {
+        let query = query.to_uppercase();
+        let mut res: Vec<SearchResult> = vec![];
+        for entry in self.registry.entries() {
+            if res.len() == limit {
+                break;
+            }
+            // books sharing a pair symbol are only reachable through the first one
+            if !entry.symbol.to_uppercase().contains(&query)
+                || res.iter().any(|r| r.symbol == entry.symbol)
+            {
+                continue;
+            }
+            res.push(SearchResult {
+                symbol: entry.symbol.clone(),
+                full_name: format!("{}:{}", self.exchange, entry.symbol),
+                description: description(entry),
+                exchange: self.exchange.clone(),
+                ticker: entry.symbol.clone(),
+                symbol_type: "crypto".to_string(),
+            });
+        }
+        res
+    }
+
+    /// Bars of `symbol` starting in `[from, to)`.
+    ///
+    /// # Arguments:
+    ///
+    /// * `symbol` - Pair symbol of the book.
+    /// * `candles` - Candles of the book, e.g. from a `CandleBuilder`, sorted by start time.
+    ///   Candles of other books are ignored.
+    /// * `from` - Start of the range in seconds.
+    /// * `to` - End of the range in seconds.
+    pub fn history(&self, symbol: &str, candles: &[Candle], from: u64, to: u64) -> Result<History> {
+        let entry = self.market(symbol)?;
+        let price_decimals = entry.book.price_decimals;
+        let size_decimals = entry.book.size_decimals;
+        let from = from.saturating_mul(MICROS_PER_SECOND);
+        let to = to.saturating_mul(MICROS_PER_SECOND);
+        let candles = candles
+            .iter()
+            .filter(|c| c.book_id == entry.book.book_id)
+            .collect::<Vec<_>>();
+        let bars = candles
+            .iter()
+            .filter(|c| c.start >= from && c.start < to)
+            .collect::<Vec<_>>();
+        if bars.is_empty() {
+            let next_time = candles
+                .iter()
+                .filter(|c| c.start < from)
+                .map(|c| c.start / MICROS_PER_SECOND)
+                .max();
+            return Ok(History::NoData { next_time });
+        }
+
+        let price = |units| decimal::from_units(units, price_decimals);
+        Ok(History::Ok {
+            t: bars.iter().map(|c| c.start / MICROS_PER_SECOND).collect(),
+            o: bars.iter().map(|c| price(c.open)).collect(),
+            h: bars.iter().map(|c| price(c.high)).collect(),
+            l: bars.iter().map(|c| price(c.low)).collect(),
+            c: bars.iter().map(|c| price(c.close)).collect(),
+            v: bars
+                .iter()
+                .map(|c| decimal::from_units(c.volume, size_decimals))
+                .collect(),
+        })
+    }
+
+    /// Build the bars of `symbol` at `resolution` from the book's fills on chain, see
+    /// `history`.
+    pub async fn fetch_history(
+        &self,
+        client: &LaminarClient,
+        symbol: &str,
+        resolution: &str,
+        from: u64,
+        to: u64,
+    ) -> Result<History> {
+        let interval = resolution_interval(resolution)?;
+        let fills = self.fetch_fills(client, symbol).await?;
+        let candles = CandleBuilder::from_fills(interval, &fills);
+        self.history(symbol, &candles, from, to)
+    }
+
+    /// Marks for the fills of `account` in `symbol` between `from` and `to` seconds, on the
+    /// bars of `resolution`.
+    ///
+    /// # Arguments:
+    ///
+    /// * `symbol` - Pair symbol of the book.
+    /// * `fills` - Fills of the book, fills of other books and accounts are ignored.
+    /// * `account` - Account whose fills are marked.
+    /// * `resolution` - Resolution of the chart, marks are placed at the start of their bar.
+    /// * `from` - Start of the range in seconds.
+    /// * `to` - End of the range in seconds.
+    pub fn marks(
+        &self,
+        symbol: &str,
+        fills: &[FillEvent],
+        account: &AccountAddress,
+        resolution: &str,
+        from: u64,
+        to: u64,
+    ) -> Result<Vec<Mark>> {
+        let entry = self.market(symbol)?;
+        let interval = resolution_interval(resolution)?;
+        let from = from.saturating_mul(MICROS_PER_SECOND);
+        let to = to.saturating_mul(MICROS_PER_SECOND);
+
+        Ok(fills
+            .iter()
+            .filter(|f| f.book_id == entry.book.book_id && f.order_id.addr.inner() == account)
+            .filter(|f| f.time >= from && f.time < to)
+            .map(|f| {
+                let (color, label, action) = match f.side {
+                    Side::Bid => ("green", "B", "Bought"),
+                    Side::Ask => ("red", "S", "Sold"),
+                };
+                Mark {
+                    id: format!("{}-{}", f.order_id, f.time),
+                    time: interval.bucket(f.time) / MICROS_PER_SECOND,
+                    color: color.to_string(),
+                    text: format!(
+                        "{} {} {} @ {}",
+                        action,
+                        decimal::format_units(f.fill_size, entry.book.size_decimals),
+                        entry.base_symbol,
+                        decimal::format_units(f.price, entry.book.price_decimals),
+                    ),
+                    label: label.to_string(),
+                    label_font_color: "white".to_string(),
+                    min_size: 14,
+                }
+            })
+            .collect())
+    }
+
+    /// Marks for the fills of `account` in `symbol` on chain, see `marks`.
+    pub async fn fetch_marks(
+        &self,
+        client: &LaminarClient,
+        symbol: &str,
+        account: &AccountAddress,
+        resolution: &str,
+        from: u64,
+        to: u64,
+    ) -> Result<Vec<Mark>> {
+        let fills = self.fetch_fills(client, symbol).await?;
+        self.marks(symbol, &fills, account, resolution, from, to)
+    }
+
+    async fn fetch_fills(&self, client: &LaminarClient, symbol: &str) -> Result<Vec<FillEvent>> {
+        let book_id = &self.market(symbol)?.book.book_id;
+        let query = EventQuery::<FillEvent>::new().for_book(book_id);
+        client.query_events(&query).await
+    }
+}
+
+fn description(entry: &MarketEntry) -> String {
+    format!("{} / {}", entry.base_symbol, entry.quote_symbol)
+}
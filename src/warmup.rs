@@ -0,0 +1,126 @@
+use crate::error::Result;
+use crate::mirror::OrderBookMirror;
+use crate::types::market::Market;
+use crate::types::order::{Id, Instrument};
+use crate::LaminarClient;
+use futures::future::join_all;
+use futures::try_join;
+
+/// Readiness of a single market after `LaminarClient::warmup`.
+#[derive(Debug)]
+pub struct MarketReadiness {
+    pub market: Market,
+    pub book_id: Option<Id>,
+    pub instrument: Option<Instrument>,
+    pub mirror: Option<OrderBookMirror>,
+    pub base_registered: bool,
+    pub quote_registered: bool,
+    pub errors: Vec<String>,
+}
+
+impl MarketReadiness {
+    pub fn is_ready(&self) -> bool {
+        self.errors.is_empty()
+            && self.mirror.is_some()
+            && self.base_registered
+            && self.quote_registered
+    }
+}
+
+/// Result of `LaminarClient::warmup`.
+#[derive(Debug)]
+pub struct ReadinessReport {
+    pub sequence_number: u64,
+    pub user_registered: bool,
+    pub markets: Vec<MarketReadiness>,
+}
+
+impl ReadinessReport {
+    /// Whether the account is registered and every market is ready to trade.
+    pub fn is_ready(&self) -> bool {
+        self.user_registered && self.markets.iter().all(|m| m.is_ready())
+    }
+
+    /// Human readable reasons why the client is not ready.
+    pub fn problems(&self) -> Vec<String> {
+        let mut res = vec![];
+        if !self.user_registered {
+            res.push("user is not registered on laminar".to_string());
+        }
+        for m in &self.markets {
+            res.extend(m.errors.iter().map(|e| format!("{}: {}", m.market, e)));
+            if !m.base_registered {
+                res.push(format!("{}: not registered for base coin", m.market));
+            }
+            if !m.quote_registered {
+                res.push(format!("{}: not registered for quote coin", m.market));
+            }
+        }
+
+        res
+    }
+}
+
+impl LaminarClient {
+    async fn warmup_market(&self, market: &Market) -> MarketReadiness {
+        let mut res = MarketReadiness {
+            market: market.clone(),
+            book_id: None,
+            instrument: None,
+            mirror: None,
+            base_registered: false,
+            quote_registered: false,
+            errors: vec![],
+        };
+
+        let (book, base, quote) = futures::join!(
+            self.fetch_orderbook(&market.base, &market.quote, &market.book_owner),
+            self.is_registered_for_coin(&market.base),
+            self.is_registered_for_coin(&market.quote),
+        );
+
+        match book {
+            Ok(book) => {
+                let mut mirror = OrderBookMirror::new();
+                mirror.apply_snapshot(&book);
+                res.book_id = Some(book.id.clone());
+                res.instrument = Some(book.instrument.clone());
+                res.mirror = Some(mirror);
+            }
+            Err(e) => res.errors.push(format!("failed fetching book: {}", e)),
+        }
+        match base {
+            Ok(r) => res.base_registered = r,
+            Err(e) => res.errors.push(format!("failed checking base coin: {}", e)),
+        }
+        match quote {
+            Ok(r) => res.quote_registered = r,
+            Err(e) => res
+                .errors
+                .push(format!("failed checking quote coin: {}", e)),
+        }
+
+        res
+    }
+
+    /// Prime sequence number, books and registration checks for a set of markets concurrently,
+    /// so a bot reaches a known-good state before its strategy loop starts.
+    ///
+    /// # Arguments:
+    ///
+    /// * `markets` - `Market`s the client is going to trade on.
+    pub async fn warmup(&self, markets: &[Market]) -> Result<ReadinessReport> {
+        let (account, markets) = futures::join!(
+            async { try_join!(self.get_sequence_number(), self.is_user_registered()) },
+            join_all(markets.iter().map(|m| self.warmup_market(m))),
+        );
+        let (sequence_number, user_registered) = account?;
+        self.sequence_numbers.sync(sequence_number);
+
+        Ok(ReadinessReport {
+            sequence_number,
+            user_registered,
+            markets,
+        })
+    }
+}
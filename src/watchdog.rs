@@ -0,0 +1,81 @@
+use crate::time::{sleep, Instant};
+use crate::types::market::Market;
+use crate::LaminarClient;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+#[derive(Debug)]
+struct Heartbeat {
+    last: Mutex<Instant>,
+    armed: AtomicBool,
+    triggered: AtomicU64,
+}
+
+/// Client-side cancel-on-disconnect.
+///
+/// A background task cancels every resting order of the client's account on the configured
+/// books once `heartbeat` has not been called for `timeout`, e.g. because the trading loop hung
+/// or lost its connection. After triggering the switch stays disarmed until the next heartbeat.
+/// The task runs on the same runtime as the caller, so it cannot protect against the whole
+/// process stalling. The task is aborted when the switch is dropped.
+#[derive(Debug)]
+pub struct DeadMansSwitch {
+    heartbeat: Arc<Heartbeat>,
+    handle: JoinHandle<()>,
+}
+
+impl DeadMansSwitch {
+    /// Start the watchdog task. Must be called from within a tokio runtime.
+    ///
+    /// # Arguments:
+    ///
+    /// * `client` - Client whose account's orders are cancelled.
+    /// * `books` - Books to cancel orders on.
+    /// * `timeout` - Maximum time between heartbeats.
+    pub fn spawn(client: Arc<LaminarClient>, books: Vec<Market>, timeout: Duration) -> Self {
+        let heartbeat = Arc::new(Heartbeat {
+            last: Mutex::new(Instant::now()),
+            armed: AtomicBool::new(true),
+            triggered: AtomicU64::new(0),
+        });
+
+        let hb = heartbeat.clone();
+        let handle = tokio::spawn(async move {
+            let check_every = (timeout / 4).max(Duration::from_millis(10));
+            loop {
+                sleep(check_every).await;
+                let last = *hb.last.lock().expect("heartbeat lock poisoned");
+                if last.elapsed() < timeout || !hb.armed.swap(false, Ordering::SeqCst) {
+                    continue;
+                }
+
+                hb.triggered.fetch_add(1, Ordering::SeqCst);
+                // failed cancels are retried on the next check until the account is flat
+                if client.cancel_all_orders(&books).await.is_err() {
+                    hb.armed.store(true, Ordering::SeqCst);
+                }
+            }
+        });
+
+        Self { heartbeat, handle }
+    }
+
+    /// Signal that the client is alive, re-arming the switch if it was triggered.
+    pub fn heartbeat(&self) {
+        *self.heartbeat.last.lock().expect("heartbeat lock poisoned") = Instant::now();
+        self.heartbeat.armed.store(true, Ordering::SeqCst);
+    }
+
+    /// Number of times the switch fired, including retries after failed cancels.
+    pub fn triggered(&self) -> u64 {
+        self.heartbeat.triggered.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for DeadMansSwitch {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
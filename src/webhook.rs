@@ -0,0 +1,187 @@
+//! Webhook notifications on order fills, cancels and rejected transactions.
+
+use crate::alerts::{Alert, Notifier};
+use crate::error::{LaminarError, Result};
+use crate::time::{sleep, unix_now_secs};
+use crate::types::events::{CancelOrderEvent, FillEvent, LaminarEvent};
+use anyhow::anyhow;
+use aptos_sdk::types::account_address::AccountAddress;
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use reqwest::StatusCode;
+use serde::Serialize;
+use sha2::Sha256;
+use std::time::Duration;
+
+/// Payload POSTed to the webhooks.
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OrderNotification {
+    Fill(FillEvent),
+    Cancel(CancelOrderEvent),
+    /// A transaction of the account failed, e.g. aborted with a Laminar error code.
+    Rejected {
+        /// What was submitted, e.g. `place_limit_order`.
+        operation: String,
+        error: String,
+        abort_code: Option<u64>,
+    },
+}
+
+/// POSTs `OrderNotification`s as JSON to webhook urls, e.g. Slack or PagerDuty integrations.
+///
+/// With a secret every request carries `X-Laminar-Timestamp`, the unix time in seconds, and
+/// `X-Laminar-Signature: sha256={hex}`, the HMAC-SHA256 of `{timestamp}.{body}`. Requests
+/// failing with a connection error, 429 or a 5xx status are retried with exponential backoff.
+///
+/// Also a `Notifier`, so anomaly `Alert`s can go to the same webhooks.
+#[derive(Clone, Debug)]
+pub struct WebhookNotifier {
+    http: reqwest::Client,
+    urls: Vec<String>,
+    secret: Option<Vec<u8>>,
+    account: Option<AccountAddress>,
+    max_retries: u32,
+    retry_delay: Duration,
+}
+
+impl WebhookNotifier {
+    /// # Arguments:
+    ///
+    /// * `urls` - Webhook urls every notification is POSTed to.
+    pub fn new(urls: Vec<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            urls,
+            secret: None,
+            account: None,
+            max_retries: 3,
+            retry_delay: Duration::from_millis(500),
+        }
+    }
+
+    /// Sign requests with `secret`.
+    pub fn with_secret(mut self, secret: impl Into<Vec<u8>>) -> Self {
+        self.secret = Some(secret.into());
+        self
+    }
+
+    /// Only notify fills and cancels of orders placed by `account`.
+    pub fn with_account(mut self, account: AccountAddress) -> Self {
+        self.account = Some(account);
+        self
+    }
+
+    /// Retry failed requests up to `max_retries` times, doubling `delay` after each attempt.
+    pub fn with_retries(mut self, max_retries: u32, delay: Duration) -> Self {
+        self.max_retries = max_retries;
+        self.retry_delay = delay;
+        self
+    }
+
+    /// Notify the fills and cancels among `events`, returning how many were sent.
+    pub async fn on_events(&self, events: &[LaminarEvent]) -> Result<usize> {
+        let mut sent = 0;
+        for event in events {
+            let notification = match event {
+                LaminarEvent::FillEvent(e) => OrderNotification::Fill(e.clone()),
+                LaminarEvent::CancelOrder(e) => OrderNotification::Cancel(e.clone()),
+                _ => continue,
+            };
+            let is_own = match (&self.account, event.order_id()) {
+                (Some(account), Some(id)) => id.addr.inner() == account,
+                _ => true,
+            };
+            if is_own {
+                self.send(&notification).await?;
+                sent += 1;
+            }
+        }
+
+        Ok(sent)
+    }
+
+    /// Notify a failed transaction.
+    ///
+    /// # Arguments:
+    ///
+    /// * `operation` - What was submitted, e.g. `place_limit_order`.
+    /// * `error` - Error the submission returned.
+    pub async fn on_rejection(&self, operation: &str, error: &LaminarError) -> Result<()> {
+        self.send(&OrderNotification::Rejected {
+            operation: operation.to_string(),
+            error: error.to_string(),
+            abort_code: error.abort_code(),
+        })
+        .await
+    }
+
+    /// POST `payload` to every url.
+    pub async fn send<T: Serialize + ?Sized>(&self, payload: &T) -> Result<()> {
+        let body = serde_json::to_vec(payload)
+            .map_err(|e| LaminarError::deserialization(e, "webhook payload"))?;
+        for url in &self.urls {
+            self.post(url, &body).await?;
+        }
+        Ok(())
+    }
+
+    async fn post(&self, url: &str, body: &[u8]) -> Result<()> {
+        let mut delay = self.retry_delay;
+        let mut attempt = 0;
+        loop {
+            let res = self.request(url, body).send().await;
+            let error = match res {
+                Ok(res) if res.status().is_success() => return Ok(()),
+                Ok(res) => {
+                    let status = res.status();
+                    let error = anyhow!("webhook {} returned {}", url, status);
+                    if !status.is_server_error() && status != StatusCode::TOO_MANY_REQUESTS {
+                        return Err(error.into());
+                    }
+                    error
+                }
+                Err(e) => anyhow!("failed posting to webhook {}: {}", url, e),
+            };
+
+            if attempt >= self.max_retries {
+                return Err(error.into());
+            }
+            trace_event!(debug, url, attempt, error = %error, "retrying webhook");
+            sleep(delay).await;
+            delay *= 2;
+            attempt += 1;
+        }
+    }
+
+    fn request(&self, url: &str, body: &[u8]) -> reqwest::RequestBuilder {
+        let mut req = self
+            .http
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.to_vec());
+        if let Some(secret) = &self.secret {
+            let timestamp = unix_now_secs().to_string();
+            req = req
+                .header("X-Laminar-Timestamp", &timestamp)
+                .header("X-Laminar-Signature", sign(secret, &timestamp, body));
+        }
+        req
+    }
+}
+
+#[async_trait]
+impl Notifier for WebhookNotifier {
+    async fn notify(&self, alert: &Alert) -> anyhow::Result<()> {
+        self.send(alert).await.map_err(anyhow::Error::from)
+    }
+}
+
+/// `sha256={hex}` HMAC of `{timestamp}.{body}`, what receivers compare the signature header to.
+pub fn sign(secret: &[u8], timestamp: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("hmac accepts any key length");
+    mac.update(timestamp.as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}